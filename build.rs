@@ -0,0 +1,33 @@
+//! Adds the NDI SDK library directory to the link search path when building with `--features ndi`.
+//!
+//! Set `NDI_SDK_DIR` to the SDK install location if the library is not on the default search path.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=NDI_SDK_DIR");
+
+    if env::var_os("CARGO_FEATURE_NDI").is_none() {
+        return;
+    }
+
+    let Some(sdk_dir) = env::var_os("NDI_SDK_DIR").map(PathBuf::from) else {
+        return;
+    };
+
+    // Library locations used by the Linux, Windows and macOS SDK installers
+    let candidates = [
+        "lib/x86_64-linux-gnu",
+        "lib/aarch64-rpi4-linux-gnueabi",
+        "Lib/x64",
+        "lib/macOS",
+        "lib",
+    ];
+    for candidate in candidates {
+        let dir = sdk_dir.join(candidate);
+        if dir.is_dir() {
+            println!("cargo:rustc-link-search=native={}", dir.display());
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Remote configuration editing: section updates from the API are validated,
+//! saved to the configuration file and recorded in the audit trail
+
+use crate::config::{CameraConfig, Config};
+use crate::events::{EventCategory, EventLog};
+use crate::matrix::impact;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Part of the configuration that can be edited remotely
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSection {
+    /// `[matrix] outputs`: list of output names
+    Outputs,
+    /// `[birddog] cameras`: list of cameras
+    Cameras,
+    /// `[ndi.aliases]`: friendly names by NDI source name
+    Aliases,
+    /// `[ndi.tags]`: tags by source name or pattern
+    Tags,
+}
+
+impl fmt::Display for ConfigSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSection::Outputs => write!(f, "outputs"),
+            ConfigSection::Cameras => write!(f, "cameras"),
+            ConfigSection::Aliases => write!(f, "aliases"),
+            ConfigSection::Tags => write!(f, "tags"),
+        }
+    }
+}
+
+impl FromStr for ConfigSection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "outputs" => Ok(ConfigSection::Outputs),
+            "cameras" => Ok(ConfigSection::Cameras),
+            "aliases" => Ok(ConfigSection::Aliases),
+            "tags" => Ok(ConfigSection::Tags),
+            _ => anyhow::bail!("Unknown configuration section '{}'", s),
+        }
+    }
+}
+
+/// Describe how a list of names changed ("added X; removed Y")
+fn describe_changes<'a>(
+    before: impl IntoIterator<Item = &'a String>,
+    after: impl IntoIterator<Item = &'a String>,
+    changed: Vec<&'a String>,
+) -> String {
+    let before: Vec<&String> = before.into_iter().collect();
+    let after: Vec<&String> = after.into_iter().collect();
+    let mut parts = Vec::new();
+    let mut list = |label: &str, names: Vec<&String>| {
+        if !names.is_empty() {
+            let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+            parts.push(format!("{} {}", label, names.join(", ")));
+        }
+    };
+    list(
+        "added",
+        after
+            .iter()
+            .filter(|n| !before.contains(n))
+            .copied()
+            .collect(),
+    );
+    list(
+        "removed",
+        before
+            .iter()
+            .filter(|n| !after.contains(n))
+            .copied()
+            .collect(),
+    );
+    list("changed", changed);
+    if parts.is_empty() {
+        "no changes".to_string()
+    } else {
+        parts.join("; ")
+    }
+}
+
+/// Replace one section of a configuration, describing the change
+pub fn apply(config: &mut Config, section: ConfigSection, value: Value) -> Result<String> {
+    let invalid = || format!("Invalid {} section", section);
+    Ok(match section {
+        ConfigSection::Outputs => {
+            let outputs: Vec<String> = serde_json::from_value(value).with_context(invalid)?;
+            let summary = describe_changes(&config.matrix.outputs, &outputs, vec![]);
+            config.matrix.outputs = outputs;
+            // Saved routes to an output that goes away would come back on restart
+            let outputs = config.router_outputs();
+            config.matrix.routes.retain(|r| outputs.contains(&r.output));
+            summary
+        }
+        ConfigSection::Cameras => {
+            let cameras: Vec<CameraConfig> = serde_json::from_value(value).with_context(invalid)?;
+            let changed = cameras
+                .iter()
+                .filter(|c| {
+                    let old = config.birddog.cameras.iter().find(|o| o.name == c.name);
+                    old.is_some_and(|old| old != *c)
+                })
+                .map(|c| &c.name)
+                .collect();
+            let summary = describe_changes(
+                config.birddog.cameras.iter().map(|c| &c.name),
+                cameras.iter().map(|c| &c.name),
+                changed,
+            );
+            config.birddog.cameras = cameras;
+            summary
+        }
+        ConfigSection::Aliases => {
+            let aliases: BTreeMap<String, String> =
+                serde_json::from_value(value).with_context(invalid)?;
+            let changed = aliases
+                .iter()
+                .filter(|(source, alias)| {
+                    config
+                        .ndi
+                        .aliases
+                        .get(*source)
+                        .is_some_and(|old| old != *alias)
+                })
+                .map(|(source, _)| source)
+                .collect();
+            let mut summary = describe_changes(config.ndi.aliases.keys(), aliases.keys(), changed);
+            // Whatever names a source by an alias that goes away stops finding it
+            let broken: Vec<String> = config
+                .ndi
+                .aliases
+                .values()
+                .filter(|old| !aliases.values().any(|alias| alias == *old))
+                .flat_map(|old| impact::alias_references(config, old))
+                .map(|reference| reference.to_string())
+                .collect();
+            if !broken.is_empty() {
+                summary.push_str(&format!("; now unresolved: {}", broken.join(", ")));
+            }
+            config.ndi.aliases = aliases;
+            summary
+        }
+        ConfigSection::Tags => {
+            let tags: BTreeMap<String, Vec<String>> =
+                serde_json::from_value(value).with_context(invalid)?;
+            let changed = tags
+                .iter()
+                .filter(|(pattern, tags)| {
+                    config
+                        .ndi
+                        .tags
+                        .get(*pattern)
+                        .is_some_and(|old| old != *tags)
+                })
+                .map(|(pattern, _)| pattern)
+                .collect();
+            let summary = describe_changes(config.ndi.tags.keys(), tags.keys(), changed);
+            config.ndi.tags = tags;
+            summary
+        }
+    })
+}
+
+/// Current value of one section of a configuration
+pub fn section(config: &Config, section: ConfigSection) -> Value {
+    match section {
+        ConfigSection::Outputs => serde_json::json!(config.matrix.outputs),
+        ConfigSection::Cameras => serde_json::json!(config.birddog.cameras),
+        ConfigSection::Aliases => serde_json::json!(config.ndi.aliases),
+        ConfigSection::Tags => serde_json::json!(config.ndi.tags),
+    }
+}
+
+/// Edits the configuration file on behalf of API clients
+pub struct ConfigEditor {
+    path: PathBuf,
+    events: EventLog,
+    /// Held while a section is read, changed and written back
+    writing: Mutex<()>,
+}
+
+impl ConfigEditor {
+    pub fn new(path: PathBuf, events: EventLog) -> Self {
+        Self {
+            path,
+            events,
+            writing: Mutex::new(()),
+        }
+    }
+
+    /// Current value of a section, as saved
+    pub fn get(&self, section: ConfigSection) -> Result<Value> {
+        Ok(self::section(&Config::from_file(&self.path)?, section))
+    }
+
+    /// Replace a section, saving the configuration only if it validates.
+    /// Returns the saved configuration, for applying the change live
+    pub fn update(&self, section: ConfigSection, value: Value, actor: &str) -> Result<Config> {
+        let _writing = self.writing.lock().unwrap();
+        // Read afresh: the file may have changed since startup
+        let mut config = Config::from_file(&self.path)?;
+        let summary = apply(&mut config, section, value)?;
+        config.validate()?;
+        config.to_file(&self.path)?;
+
+        self.events.info(
+            EventCategory::System,
+            actor,
+            format!("Configuration {} edited: {}", section, summary),
+        );
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_update() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut config = Config::default();
+        config.matrix.routes.push(crate::matrix::Route::new(
+            "Cam 1".into(),
+            "Monitor 4".into(),
+        ));
+        config.to_file(file.path()).unwrap();
+
+        let events = EventLog::new();
+        let editor = ConfigEditor::new(file.path().to_path_buf(), events.clone());
+        let outputs = json!(["Monitor 1", "Monitor 2", "Monitor 3", "Stage"]);
+        let updated = editor
+            .update(ConfigSection::Outputs, outputs.clone(), "admin")
+            .unwrap();
+        assert!(updated.matrix.routes.is_empty());
+        assert_eq!(editor.get(ConfigSection::Outputs).unwrap(), outputs);
+
+        let recent = events.recent();
+        assert_eq!(recent[0].actor.as_deref(), Some("admin"));
+        assert_eq!(
+            recent[0].message,
+            "Configuration outputs edited: added Stage; removed Monitor 4"
+        );
+
+        // Invalid changes are not saved
+        assert!(editor
+            .update(ConfigSection::Outputs, json!(["Stage", "Stage"]), "admin")
+            .is_err());
+        assert!(editor
+            .update(ConfigSection::Aliases, json!(["not", "a", "map"]), "admin")
+            .is_err());
+        assert_eq!(editor.get(ConfigSection::Outputs).unwrap(), outputs);
+        assert!("routes".parse::<ConfigSection>().is_err());
+
+        editor
+            .update(ConfigSection::Tags, json!({"CAM-*": ["camera"]}), "admin")
+            .unwrap();
+        assert_eq!(
+            events.recent().last().unwrap().message,
+            "Configuration tags edited: added CAM-*"
+        );
+    }
+}
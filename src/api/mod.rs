@@ -0,0 +1,1010 @@
+//! HTTP control API used by Companion and other integrations
+
+pub mod config_edit;
+
+pub use config_edit::{ConfigEditor, ConfigSection};
+
+use crate::birddog::api::CameraStatus;
+use crate::birddog::BirdDogClient;
+use crate::companion::{CompanionAction, CompanionControl, CompanionFeedback, CompanionRoute};
+use crate::config::{Config, PtzProtocol};
+use crate::devices::DeviceState;
+use crate::events::Event;
+use crate::intercom::{IntercomHub, IntercomMessage, Relayed};
+use crate::matrix::{MatrixRouter, Route, Salvo};
+use crate::metrics;
+use crate::ndi::metadata::SourceMetadata;
+use crate::ndi::proxy::ProxyStatus;
+use crate::ndi::{MetadataHub, NdiDiscovery, ProxyManager, ReceiverStats, StatsHub};
+use crate::pairing::{PairingRole, PairingState};
+use crate::supervisor::{Supervisor, TaskStatus};
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::header::{HeaderName, CONTENT_TYPE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Json;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Web admin page, served at `/admin`
+const DASHBOARD: &str = include_str!("dashboard.html");
+
+/// Time the camera health check waits for each BirdDog camera
+const CAMERA_STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Events returned by `/api/events` unless a limit is given
+const DEFAULT_EVENT_LIMIT: usize = 100;
+
+/// Shared state for API handlers
+#[derive(Clone)]
+pub struct ApiState {
+    pub router: Arc<Mutex<MatrixRouter>>,
+    pub discovery: Arc<NdiDiscovery>,
+    pub control: Arc<CompanionControl>,
+    pub metadata: Arc<MetadataHub>,
+    /// Statistics of the receivers that publish them
+    pub stats: Arc<StatsHub>,
+    pub proxies: Arc<ProxyManager>,
+    pub supervisor: Arc<Supervisor>,
+    /// Saves configuration edits (None unless `[api] config_editing` is on)
+    pub config: Option<Arc<ConfigEditor>>,
+    /// Role in a hot-standby pair (None in the GUI, which doesn't pair)
+    pub pairing: Option<Arc<PairingRole>>,
+    /// Intercom partyline (None unless this station hosts it)
+    pub intercom: Option<Arc<IntercomHub>>,
+}
+
+/// Request header naming who made a configuration edit, for the audit trail
+const ACTOR_HEADER: &str = "x-rustv-actor";
+
+/// Build the API routes
+pub fn routes(state: ApiState) -> axum::Router {
+    axum::Router::new()
+        .route("/admin", get(dashboard))
+        .route("/api/companion/action", post(companion_action))
+        .route("/api/companion/feedback", get(companion_feedback))
+        .route("/api/routes", get(routes_with_notes))
+        .route("/api/salvos", get(salvos))
+        .route("/api/salvos/:salvo/preflight", get(salvo_preflight))
+        .route("/api/metadata", get(metadata))
+        .route("/api/metadata/:source", get(source_metadata))
+        .route("/api/receivers", get(receiver_stats))
+        .route("/api/receivers/:source", get(source_receiver_stats))
+        .route("/api/proxies", get(proxies))
+        .route("/api/proxies/:source/start", post(start_proxy))
+        .route("/api/proxies/:source/stop", post(stop_proxy))
+        .route("/api/tasks", get(tasks))
+        .route("/metrics", get(prometheus_metrics))
+        .route("/api/devices", get(devices))
+        .route("/api/cameras", get(cameras))
+        .route("/api/events", get(events))
+        .route("/api/pairing", get(pairing))
+        .route("/api/intercom", get(intercom))
+        .route(
+            "/api/config/:section",
+            get(config_section).put(update_config_section),
+        )
+        .with_state(state)
+}
+
+/// Serve the API on the given port until the process exits
+pub async fn serve(state: ApiState, port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .await
+        .with_context(|| format!("Failed to bind API port {}", port))?;
+    serve_on(listener, state).await
+}
+
+/// Serve the API on an already bound listener
+pub async fn serve_on(listener: TcpListener, state: ApiState) -> Result<()> {
+    info!("Control API listening on {}", listener.local_addr()?);
+    axum::serve(listener, routes(state))
+        .await
+        .context("Control API server failed")
+}
+
+/// Web admin dashboard: routes, salvos, camera health, events and aliases
+async fn dashboard() -> Html<&'static str> {
+    Html(DASHBOARD)
+}
+
+async fn companion_action(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(action): Json<CompanionAction>,
+) -> (StatusCode, Json<Value>) {
+    let actor = headers
+        .get(ACTOR_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("companion");
+    match state.control.handle_from(actor, action).await {
+        Ok(()) => (StatusCode::OK, Json(json!({ "ok": true }))),
+        Err(e) => {
+            warn!("Companion action failed: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "ok": false, "error": e.to_string() })),
+            )
+        }
+    }
+}
+
+async fn companion_feedback(State(state): State<ApiState>) -> Json<CompanionFeedback> {
+    let (routes, maintenance, blanked) = {
+        let router = state.router.lock().unwrap();
+        let routes = router
+            .get_all_routes()
+            .into_iter()
+            .map(|r| CompanionRoute {
+                input: r.input,
+                output: r.output,
+            })
+            .collect();
+        (routes, router.maintenance_inputs(), router.is_blanked())
+    };
+    let sources = state
+        .discovery
+        .get_sources()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+
+    Json(CompanionFeedback {
+        layout: None,
+        routes,
+        sources,
+        maintenance,
+        blanked,
+        recording: state.control.recording(),
+        next_event: state.control.next_event(),
+    })
+}
+
+/// Current routes with their notes and tags, sorted by output
+async fn routes_with_notes(State(state): State<ApiState>) -> Json<Vec<Route>> {
+    let mut routes = state.router.lock().unwrap().get_all_routes();
+    routes.sort_by(|a, b| a.output.cmp(&b.output));
+    Json(routes)
+}
+
+/// Role of this daemon and the state a standby mirrors
+async fn pairing(State(state): State<ApiState>) -> (StatusCode, Json<Value>) {
+    let Some(role) = &state.pairing else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "ok": false, "error": "Pairing runs headless only" })),
+        );
+    };
+    let mut routes = state.router.lock().unwrap().get_all_routes();
+    routes.sort_by(|a, b| a.output.cmp(&b.output));
+    let pairing = PairingState {
+        active: role.is_active(),
+        term: role.term(),
+        routes,
+        salvos: state.control.salvos().to_vec(),
+        aliases: state.discovery.aliases(),
+        tags: state.discovery.tags(),
+    };
+    (StatusCode::OK, Json(json!(pairing)))
+}
+
+/// Join the intercom partyline over a WebSocket
+async fn intercom(State(state): State<ApiState>, upgrade: WebSocketUpgrade) -> Response {
+    let Some(hub) = state.intercom.clone() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "ok": false, "error": "This station doesn't host the intercom" })),
+        )
+            .into_response();
+    };
+    upgrade.on_upgrade(move |socket| relay_intercom(socket, hub))
+}
+
+/// Replay recent messages to a station, then relay messages and talkback
+/// both ways until it disconnects
+async fn relay_intercom(mut socket: WebSocket, hub: Arc<IntercomHub>) {
+    let (recent, mut relayed) = hub.subscribe();
+    for message in recent {
+        if send_intercom(&mut socket, &message).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            relayed = relayed.recv() => match relayed {
+                Ok(Relayed::Message(message)) => {
+                    if send_intercom(&mut socket, &message).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(Relayed::Talkback(audio)) => {
+                    if socket.send(Message::Binary(audio)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Intercom station fell behind, {} messages missed", missed);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+            },
+            received = socket.recv() => match received {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(message) => hub.post(message),
+                    Err(e) => warn!("Ignoring malformed intercom message: {}", e),
+                },
+                Some(Ok(Message::Binary(audio))) => hub.post_talkback(audio),
+                Some(Ok(Message::Close(_))) | None => return,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    debug!("Intercom station disconnected: {}", e);
+                    return;
+                }
+            },
+        }
+    }
+}
+
+async fn send_intercom(socket: &mut WebSocket, message: &IntercomMessage) -> Result<()> {
+    let text = serde_json::to_string(message)?;
+    socket.send(Message::Text(text)).await?;
+    Ok(())
+}
+
+/// Salvos that can be recalled, with their notes and tags
+async fn salvos(State(state): State<ApiState>) -> Json<Vec<Salvo>> {
+    Json(state.control.salvos().to_vec())
+}
+
+/// What recalling a salvo would do: routes ready to apply and problems found
+async fn salvo_preflight(
+    State(state): State<ApiState>,
+    Path(salvo): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    match state.control.preflight(&salvo) {
+        Ok(preflight) => (StatusCode::OK, Json(json!(preflight))),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "ok": false, "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Latest metadata (product, PTZ capability, tally echo) of every received source
+async fn metadata(State(state): State<ApiState>) -> Json<HashMap<String, SourceMetadata>> {
+    Json(state.metadata.snapshot())
+}
+
+/// Latest metadata of one source
+async fn source_metadata(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+) -> Result<Json<SourceMetadata>, StatusCode> {
+    state
+        .metadata
+        .get(&source)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Statistics of every receiver, by source name
+async fn receiver_stats(State(state): State<ApiState>) -> Json<HashMap<String, ReceiverStats>> {
+    Json(state.stats.snapshot())
+}
+
+/// Statistics of the receiver of one source
+async fn source_receiver_stats(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+) -> Result<Json<ReceiverStats>, StatusCode> {
+    state
+        .stats
+        .get(&source)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// State of every configured NDI proxy
+async fn proxies(State(state): State<ApiState>) -> Json<Vec<ProxyStatus>> {
+    Json(state.proxies.status())
+}
+
+/// Start re-publishing a source
+async fn start_proxy(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    match state.proxies.start(&source) {
+        Ok(()) => (StatusCode::OK, Json(json!({ "ok": true }))),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "ok": false, "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Stop re-publishing a source
+async fn stop_proxy(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    match state.proxies.stop(&source) {
+        Ok(()) => (StatusCode::OK, Json(json!({ "ok": true }))),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "ok": false, "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Supervised background tasks with their restart counts
+async fn tasks(State(state): State<ApiState>) -> Json<Vec<TaskStatus>> {
+    Json(state.supervisor.status())
+}
+
+/// Metrics in the Prometheus text format
+async fn prometheus_metrics(
+    State(state): State<ApiState>,
+) -> ([(HeaderName, &'static str); 1], String) {
+    let outputs = {
+        let router = state.router.lock().unwrap();
+        router
+            .get_outputs()
+            .iter()
+            .map(|output| (output.clone(), router.get_route(output).is_some()))
+            .collect()
+    };
+    let snapshot = metrics::Snapshot {
+        outputs,
+        receivers: state.stats.snapshot(),
+        tasks: state.supervisor.status(),
+        sources: state.discovery.get_sources().len(),
+    };
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(&snapshot),
+    )
+}
+
+/// Last polled state of every HTTP device
+async fn devices(State(state): State<ApiState>) -> Json<Vec<DeviceState>> {
+    Json(state.control.devices().states())
+}
+
+/// Health of a configured camera
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CameraHealth {
+    pub name: String,
+    pub ndi_name: String,
+    pub ptz: PtzProtocol,
+    /// Whether discovery currently lists the camera's NDI source
+    pub source_online: bool,
+    /// Status reported by the BirdDog API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<CameraStatus>,
+    /// Why the BirdDog API couldn't be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Every configured camera with its source and, for BirdDog cameras, API status
+async fn cameras(State(state): State<ApiState>) -> Json<Vec<CameraHealth>> {
+    let sources = state.discovery.get_sources();
+    // Cameras are queried in parallel so one offline camera doesn't hold up the rest
+    let checks: Vec<_> = state
+        .control
+        .cameras()
+        .into_iter()
+        .map(|camera| {
+            let status = camera.is_birddog().then(|| {
+                let ip = camera.ip_address.clone();
+                tokio::spawn(async move {
+                    tokio::time::timeout(
+                        CAMERA_STATUS_TIMEOUT,
+                        BirdDogClient::new(&ip).get_status(),
+                    )
+                    .await
+                })
+            });
+            (camera, status)
+        })
+        .collect();
+
+    let mut health = Vec::new();
+    for (camera, status) in checks {
+        let (status, error) = match status {
+            None => (None, None),
+            Some(task) => match task.await {
+                Ok(Ok(Ok(status))) => (Some(status), None),
+                Ok(Ok(Err(e))) => (None, Some(format!("{:#}", e))),
+                Ok(Err(_)) => (None, Some("No response".to_string())),
+                Err(e) => (None, Some(e.to_string())),
+            },
+        };
+        health.push(CameraHealth {
+            source_online: sources.iter().any(|s| s.name == camera.ndi_name),
+            name: camera.name,
+            ndi_name: camera.ndi_name,
+            ptz: camera.ptz,
+            status,
+            error,
+        });
+    }
+    Json(health)
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsParams {
+    limit: Option<usize>,
+}
+
+/// Most recent events, oldest first
+async fn events(
+    State(state): State<ApiState>,
+    Query(params): Query<EventsParams>,
+) -> Json<Vec<Event>> {
+    let mut events = state.control.events().recent();
+    let limit = params.limit.unwrap_or(DEFAULT_EVENT_LIMIT);
+    events.drain(..events.len().saturating_sub(limit));
+    Json(events)
+}
+
+/// The configuration editor and the section a request names
+fn config_editor<'a>(
+    state: &'a ApiState,
+    section: &str,
+) -> Result<(&'a ConfigEditor, ConfigSection), (StatusCode, Json<Value>)> {
+    let not_found = |error: String| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "ok": false, "error": error })),
+        )
+    };
+    let editor = state
+        .config
+        .as_deref()
+        .ok_or_else(|| not_found("Configuration editing is disabled".to_string()))?;
+    let section = section.parse().map_err(|e| not_found(format!("{}", e)))?;
+    Ok((editor, section))
+}
+
+/// Saved value of an editable configuration section
+async fn config_section(
+    State(state): State<ApiState>,
+    Path(section): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    let (editor, section) = match config_editor(&state, &section) {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+    match editor.get(section) {
+        Ok(value) => (StatusCode::OK, Json(value)),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "ok": false, "error": format!("{:#}", e) })),
+        ),
+    }
+}
+
+/// Replace an editable configuration section, applying it live once it is
+/// validated and saved
+async fn update_config_section(
+    State(state): State<ApiState>,
+    Path(section): Path<String>,
+    headers: HeaderMap,
+    Json(value): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    let (editor, section) = match config_editor(&state, &section) {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+    let actor = headers
+        .get(ACTOR_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("api");
+    match editor.update(section, value, actor) {
+        Ok(config) => {
+            apply_config(&state, section, config);
+            (StatusCode::OK, Json(json!({ "ok": true })))
+        }
+        Err(e) => {
+            warn!("Configuration edit rejected: {:#}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "ok": false, "error": format!("{:#}", e) })),
+            )
+        }
+    }
+}
+
+/// Put an edited section into effect without a restart
+fn apply_config(state: &ApiState, section: ConfigSection, config: Config) {
+    match section {
+        ConfigSection::Outputs => state
+            .router
+            .lock()
+            .unwrap()
+            .set_outputs(config.router_outputs()),
+        ConfigSection::Cameras => state.control.set_cameras(config.birddog.cameras),
+        ConfigSection::Aliases => state.discovery.set_aliases(config.ndi.aliases),
+        ConfigSection::Tags => state.discovery.set_tags(config.ndi.tags),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ndi::metadata::MetadataEvent;
+
+    #[tokio::test]
+    async fn test_companion_action_endpoint() {
+        let mut router = MatrixRouter::new();
+        router.add_output("Program".to_string());
+        let mut noted = Route::new("Cam 1".to_string(), "Program".to_string());
+        noted.note = Some("Wide shot".to_string());
+        router.set_notes(&[noted]);
+        let router = Arc::new(Mutex::new(router));
+        let discovery = Arc::new(NdiDiscovery::new());
+
+        let state = ApiState {
+            router: Arc::clone(&router),
+            discovery: Arc::clone(&discovery),
+            control: Arc::new(CompanionControl::new(Arc::clone(&router), vec![])),
+            metadata: Arc::new(MetadataHub::new()),
+            stats: Arc::new(StatsHub::new()),
+            proxies: Arc::new(ProxyManager::new(vec![], discovery)),
+            supervisor: Arc::new(Supervisor::new()),
+            config: None,
+            pairing: None,
+            intercom: None,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_on(listener, state));
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("http://{}/api/companion/action", addr))
+            .json(&json!({ "type": "Route", "input": "Cam 1", "output": "Program" }))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        let response = client
+            .post(format!("http://{}/api/companion/action", addr))
+            .json(&json!({ "type": "Stop" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let feedback: CompanionFeedback = client
+            .get(format!("http://{}/api/companion/feedback", addr))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(feedback.routes.len(), 1);
+        assert_eq!(feedback.routes[0].input, "Cam 1");
+
+        let routes: Vec<Route> = client
+            .get(format!("http://{}/api/routes", addr))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(routes[0].note.as_deref(), Some("Wide shot"));
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_endpoints() {
+        let mut router = MatrixRouter::new();
+        router.add_output("Program".to_string());
+        router.add_input(crate::ndi::NdiSource::new(
+            "Cam 1".to_string(),
+            "ndi://10.0.0.5".to_string(),
+        ));
+        let router = Arc::new(Mutex::new(router));
+        let discovery = Arc::new(NdiDiscovery::new());
+        let control = CompanionControl::new(Arc::clone(&router), vec![])
+            .with_events(crate::events::EventLog::new());
+
+        let state = ApiState {
+            router: Arc::clone(&router),
+            discovery: Arc::clone(&discovery),
+            control: Arc::new(control),
+            metadata: Arc::new(MetadataHub::new()),
+            stats: Arc::new(StatsHub::new()),
+            proxies: Arc::new(ProxyManager::new(vec![], discovery)),
+            supervisor: Arc::new(Supervisor::new()),
+            config: None,
+            pairing: None,
+            intercom: None,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_on(listener, state));
+
+        let page = reqwest::get(format!("http://{}/admin", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(page.contains("RusTV Admin"));
+
+        let client = reqwest::Client::new();
+        client
+            .post(format!("http://{}/api/companion/action", addr))
+            .header(ACTOR_HEADER, "web admin")
+            .json(&json!({ "type": "Route", "input": "Cam 1", "output": "Program" }))
+            .send()
+            .await
+            .unwrap();
+        let events: Vec<Event> = reqwest::get(format!("http://{}/api/events?limit=1", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].actor.as_deref(), Some("web admin"));
+
+        let cameras: Value = reqwest::get(format!("http://{}/api/cameras", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(cameras, json!([]));
+
+        // Aliases can't be edited unless configuration editing is on
+        let response = reqwest::get(format!("http://{}/api/config/aliases", addr))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_config_endpoints() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        Config::default().to_file(file.path()).unwrap();
+        let mut router = MatrixRouter::new();
+        router.add_output("Monitor 1".to_string());
+        let router = Arc::new(Mutex::new(router));
+        let discovery = Arc::new(NdiDiscovery::new());
+        let events = crate::events::EventLog::new();
+
+        let state = ApiState {
+            router: Arc::clone(&router),
+            discovery: Arc::clone(&discovery),
+            control: Arc::new(CompanionControl::new(Arc::clone(&router), vec![])),
+            metadata: Arc::new(MetadataHub::new()),
+            stats: Arc::new(StatsHub::new()),
+            proxies: Arc::new(ProxyManager::new(vec![], discovery)),
+            supervisor: Arc::new(Supervisor::new()),
+            config: Some(Arc::new(ConfigEditor::new(
+                file.path().to_path_buf(),
+                events.clone(),
+            ))),
+            pairing: None,
+            intercom: None,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_on(listener, state));
+
+        let client = reqwest::Client::new();
+        let response = client
+            .put(format!("http://{}/api/config/outputs", addr))
+            .header(ACTOR_HEADER, "Dana")
+            .json(&json!(["Program", "Stage"]))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(router.lock().unwrap().get_outputs(), ["Program", "Stage"]);
+        assert_eq!(events.recent()[0].actor.as_deref(), Some("Dana"));
+
+        let response = client
+            .put(format!("http://{}/api/config/outputs", addr))
+            .json(&json!(["Program", ""]))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let outputs: Value = reqwest::get(format!("http://{}/api/config/outputs", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(outputs, json!(["Program", "Stage"]));
+
+        let response = reqwest::get(format!("http://{}/api/config/routes", addr))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_endpoints() {
+        let router = Arc::new(Mutex::new(MatrixRouter::new()));
+        let metadata = Arc::new(MetadataHub::new());
+        metadata.publish(
+            "CAM1",
+            MetadataEvent::Capabilities {
+                ptz: true,
+                kvm: false,
+                web_control: None,
+            },
+        );
+        let stats = Arc::new(StatsHub::new());
+        stats.publish(
+            "CAM1",
+            ReceiverStats {
+                frames_received: 1500,
+                jitter_ms: 1.5,
+                ..ReceiverStats::default()
+            },
+        );
+
+        let state = ApiState {
+            router: Arc::clone(&router),
+            discovery: Arc::new(NdiDiscovery::new()),
+            control: Arc::new(CompanionControl::new(router, vec![])),
+            metadata,
+            stats,
+            proxies: Arc::new(ProxyManager::new(vec![], Arc::new(NdiDiscovery::new()))),
+            supervisor: Arc::new(Supervisor::new()),
+            config: None,
+            pairing: None,
+            intercom: None,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_on(listener, state));
+
+        let all: Value = reqwest::get(format!("http://{}/api/metadata", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(all["CAM1"]["ptz"], true);
+
+        let response = reqwest::get(format!("http://{}/api/metadata/CAM2", addr))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let cam1: Value = reqwest::get(format!("http://{}/api/receivers/CAM1", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(cam1["frames_received"], 1500);
+        assert_eq!(cam1["jitter_ms"], 1.5);
+
+        let metrics = reqwest::get(format!("http://{}/metrics", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(metrics.contains("rustv_receiver_frames_received_total{source=\"CAM1\"} 1500"));
+
+        let proxies: Value = reqwest::get(format!("http://{}/api/proxies", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(proxies, json!([]));
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/api/proxies/CAM1/start", addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let tasks: Value = reqwest::get(format!("http://{}/api/tasks", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(tasks, json!([]));
+    }
+
+    /// Wait up to two seconds for a condition to hold
+    async fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+        for _ in 0..200 {
+            if condition() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    /// Serve an API hosting the intercom; returns its address
+    async fn serve_intercom() -> String {
+        let router = Arc::new(Mutex::new(MatrixRouter::new()));
+        let discovery = Arc::new(NdiDiscovery::new());
+        let state = ApiState {
+            router: Arc::clone(&router),
+            discovery: Arc::clone(&discovery),
+            control: Arc::new(CompanionControl::new(router, vec![])),
+            metadata: Arc::new(MetadataHub::new()),
+            stats: Arc::new(StatsHub::new()),
+            proxies: Arc::new(ProxyManager::new(vec![], discovery)),
+            supervisor: Arc::new(Supervisor::new()),
+            config: None,
+            pairing: None,
+            intercom: Some(Arc::new(IntercomHub::new())),
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve_on(listener, state));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_intercom_relay() {
+        use crate::intercom::{IntercomChannel, MessageKind};
+
+        let addr = serve_intercom().await;
+        let director = IntercomChannel::new("Director", &addr);
+        assert!(director.send(MessageKind::Cue, "Standby").is_err());
+        let cam = IntercomChannel::new("Cam1", &addr);
+        director.start();
+        cam.start();
+        assert!(wait_for(|| director.is_connected() && cam.is_connected()).await);
+
+        director.send(MessageKind::Chat, "@cam1 go wide").unwrap();
+        assert!(wait_for(|| !cam.history().is_empty() && !director.history().is_empty()).await);
+        assert_eq!(cam.history()[0].from, "Director");
+        assert_eq!(cam.unread_alerts(), 1);
+        assert_eq!(director.unread_alerts(), 0);
+
+        // A station joining later gets the recent messages
+        crate::intercom::send_once(&addr, "Stage", MessageKind::Cue, "Go")
+            .await
+            .unwrap();
+        let late = IntercomChannel::new("Cam2", &addr);
+        late.start();
+        assert!(wait_for(|| late.history().len() == 2).await);
+        assert_eq!(late.unread_alerts(), 1);
+    }
+
+    /// Forward connections to `target` through another port; notifying the
+    /// returned handle cuts the connections open at the time
+    async fn cuttable_forward(target: String) -> (String, Arc<tokio::sync::Notify>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let cut = Arc::new(tokio::sync::Notify::new());
+        let notify = Arc::clone(&cut);
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                let mut server = tokio::net::TcpStream::connect(&target).await.unwrap();
+                let cut = Arc::clone(&notify);
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = tokio::io::copy_bidirectional(&mut client, &mut server) => {}
+                        _ = cut.notified() => {}
+                    }
+                });
+            }
+        });
+        (addr, cut)
+    }
+
+    #[tokio::test]
+    async fn test_intercom_reconnect_skips_replayed_messages() {
+        use crate::intercom::{IntercomChannel, MessageKind};
+
+        let addr = serve_intercom().await;
+        let (forwarded, cut) = cuttable_forward(addr.clone()).await;
+        let director = IntercomChannel::new("Director", &addr);
+        let cam = IntercomChannel::new("Cam1", &forwarded);
+        director.start();
+        cam.start();
+        assert!(wait_for(|| director.is_connected() && cam.is_connected()).await);
+
+        director.send(MessageKind::Cue, "Standby").unwrap();
+        assert!(wait_for(|| cam.history().len() == 1).await);
+        assert_eq!(cam.unread_alerts(), 1);
+        cam.clear_alerts();
+
+        // The host replays the cue when Cam1 reconnects (after a 1 s backoff)
+        cut.notify_waiters();
+        assert!(wait_for(|| !cam.is_connected()).await);
+        let mut reconnected = false;
+        for _ in 0..3 {
+            reconnected |= wait_for(|| cam.is_connected()).await;
+        }
+        assert!(reconnected);
+
+        director.send(MessageKind::Chat, "Thanks").unwrap();
+        assert!(wait_for(|| cam.history().len() >= 2).await);
+        let history = cam.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].text, "Thanks");
+        assert_eq!(cam.unread_alerts(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_talkback_over_intercom() {
+        use crate::audio::{AudioChunk, Talkback};
+        use crate::config::{TalkbackConfig, TalkbackDestinationConfig, TalkbackKind};
+        use crate::intercom::{IntercomChannel, MessageKind};
+
+        let addr = serve_intercom().await;
+        let director = Arc::new(IntercomChannel::new("Director", &addr));
+        let stage = IntercomChannel::new("Stage", &addr);
+        let cam = IntercomChannel::new("Cam1", &addr);
+        director.start();
+        stage.start();
+        cam.start();
+        assert!(
+            wait_for(|| director.is_connected() && stage.is_connected() && cam.is_connected())
+                .await
+        );
+
+        let config = TalkbackConfig {
+            enabled: true,
+            destinations: vec![TalkbackDestinationConfig {
+                name: "Stage manager".to_string(),
+                kind: TalkbackKind::Intercom,
+                target: "stage".to_string(),
+            }],
+            ..Default::default()
+        };
+        let mut talkback =
+            Talkback::from_config(&config).with_intercom(Some(Arc::clone(&director)));
+        let chunk = AudioChunk {
+            sample_rate: 48_000,
+            channels: 1,
+            samples: vec![0.25; 480],
+        };
+        assert_eq!(talkback.send(&chunk), 0);
+        talkback.press();
+        assert_eq!(talkback.send(&chunk), 1);
+
+        let mut received = Vec::new();
+        assert!(
+            wait_for(|| {
+                received.extend(stage.take_talkback());
+                !received.is_empty()
+            })
+            .await
+        );
+        assert_eq!(received[0].sample_rate, 48_000);
+        assert_eq!(received[0].samples, chunk.samples);
+        // Only the addressed station plays it; the message relayed after the
+        // audio shows Cam1 has seen it too
+        director.send(MessageKind::Chat, "Thanks").unwrap();
+        assert!(wait_for(|| !cam.history().is_empty()).await);
+        assert!(cam.take_talkback().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Microphone capture (requires the `audio` feature)
+
+use anyhow::Result;
+use std::sync::mpsc::Receiver;
+
+/// A chunk of captured audio
+#[derive(Debug, Clone)]
+pub struct AudioChunk {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of interleaved channels
+    pub channels: u16,
+    /// Interleaved samples in the range -1.0 to 1.0
+    pub samples: Vec<f32>,
+}
+
+/// Live microphone capture; audio stops when this is dropped
+#[cfg(feature = "audio")]
+pub struct MicCapture {
+    _stream: cpal::Stream,
+}
+
+#[cfg(feature = "audio")]
+impl MicCapture {
+    /// Start capturing from the named input device (or the system default)
+    pub fn start(device_name: Option<&str>) -> Result<(Self, Receiver<AudioChunk>)> {
+        use anyhow::Context;
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+        use log::{error, info};
+        use std::sync::mpsc;
+
+        let host = cpal::default_host();
+        let device = match device_name {
+            Some(name) => host
+                .input_devices()
+                .context("Failed to list input devices")?
+                .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+                .with_context(|| format!("Input device '{}' not found", name))?,
+            None => host
+                .default_input_device()
+                .context("No default input device available")?,
+        };
+
+        let supported = device
+            .default_input_config()
+            .context("Failed to query input device configuration")?;
+        let sample_rate = supported.sample_rate().0;
+        let channels = supported.channels();
+        let sample_format = supported.sample_format();
+        let stream_config: cpal::StreamConfig = supported.into();
+
+        info!(
+            "Starting microphone capture on {} ({} Hz, {} ch)",
+            device.name().unwrap_or_default(),
+            sample_rate,
+            channels
+        );
+
+        let (tx, rx) = mpsc::channel();
+        let on_error = |e| error!("Microphone stream error: {}", e);
+
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    let _ = tx.send(AudioChunk {
+                        sample_rate,
+                        channels,
+                        samples: data.to_vec(),
+                    });
+                },
+                on_error,
+                None,
+            ),
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    let _ = tx.send(AudioChunk {
+                        sample_rate,
+                        channels,
+                        samples: data.iter().map(|&s| s as f32 / i16::MAX as f32).collect(),
+                    });
+                },
+                on_error,
+                None,
+            ),
+            other => anyhow::bail!("Unsupported microphone sample format: {:?}", other),
+        }
+        .context("Failed to open microphone stream")?;
+
+        stream.play().context("Failed to start microphone stream")?;
+
+        Ok((Self { _stream: stream }, rx))
+    }
+}
+
+/// Placeholder used when RusTV is built without audio support
+#[cfg(not(feature = "audio"))]
+pub struct MicCapture;
+
+#[cfg(not(feature = "audio"))]
+impl MicCapture {
+    /// Always fails: microphone capture requires the `audio` feature
+    pub fn start(_device_name: Option<&str>) -> Result<(Self, Receiver<AudioChunk>)> {
+        anyhow::bail!("RusTV was built without audio support (enable the `audio` feature)")
+    }
+}
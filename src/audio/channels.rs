@@ -0,0 +1,147 @@
+//! Channel mapping of received audio: which of a source's channels are heard
+//! (e.g. 3/4 of an 8-channel stage feed) or a 5.1 downmix to stereo
+
+use super::AudioChunk;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Gain of the centre and surround channels in a 5.1 downmix (-3 dB)
+const DOWNMIX_GAIN: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Which of a source's audio channels are used, and how
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ChannelMap {
+    /// Every channel as sent
+    #[default]
+    All,
+    /// These channels (numbered from 1) in this order, e.g. "3/4"
+    Select(Vec<u16>),
+    /// 5.1 (L R C LFE Ls Rs) folded down to stereo, without the LFE
+    Downmix51,
+}
+
+impl ChannelMap {
+    /// A chunk with the mapped channels. Channels the source doesn't send are
+    /// silent, and a downmix of fewer than six channels leaves them as they are
+    pub fn apply(&self, chunk: AudioChunk) -> AudioChunk {
+        let in_channels = chunk.channels.max(1) as usize;
+        let samples = match self {
+            ChannelMap::All => return chunk,
+            ChannelMap::Downmix51 if in_channels < 6 => return chunk,
+            ChannelMap::Select(channels) => chunk
+                .samples
+                .chunks_exact(in_channels)
+                .flat_map(|frame| {
+                    channels
+                        .iter()
+                        .map(|c| frame.get(*c as usize - 1).copied().unwrap_or(0.0))
+                })
+                .collect(),
+            ChannelMap::Downmix51 => chunk
+                .samples
+                .chunks_exact(in_channels)
+                .flat_map(|frame| {
+                    let centre = frame[2] * DOWNMIX_GAIN;
+                    [
+                        (frame[0] + centre + frame[4] * DOWNMIX_GAIN).clamp(-1.0, 1.0),
+                        (frame[1] + centre + frame[5] * DOWNMIX_GAIN).clamp(-1.0, 1.0),
+                    ]
+                })
+                .collect(),
+        };
+        AudioChunk {
+            channels: match self {
+                ChannelMap::Select(channels) => channels.len() as u16,
+                _ => 2,
+            },
+            samples,
+            ..chunk
+        }
+    }
+}
+
+impl fmt::Display for ChannelMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelMap::All => write!(f, "all"),
+            ChannelMap::Select(channels) => {
+                let channels: Vec<String> = channels.iter().map(|c| c.to_string()).collect();
+                write!(f, "{}", channels.join("/"))
+            }
+            ChannelMap::Downmix51 => write!(f, "5.1"),
+        }
+    }
+}
+
+impl FromStr for ChannelMap {
+    type Err = String;
+
+    /// Parse "all", "5.1" or channels numbered from 1 such as "3/4" or "7"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "all" | "" => Ok(ChannelMap::All),
+            "5.1" => Ok(ChannelMap::Downmix51),
+            list => list
+                .split('/')
+                .map(|c| c.trim().parse().ok().filter(|&c: &u16| c > 0))
+                .collect::<Option<Vec<u16>>>()
+                .map(ChannelMap::Select)
+                .ok_or_else(|| format!("Invalid channel map '{}' (e.g. \"3/4\" or \"5.1\")", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for ChannelMap {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ChannelMap> for String {
+    fn from(map: ChannelMap) -> Self {
+        map.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(channels: u16, samples: Vec<f32>) -> AudioChunk {
+        AudioChunk {
+            sample_rate: 48000,
+            channels,
+            samples,
+        }
+    }
+
+    #[test]
+    fn test_channel_map() {
+        let map: ChannelMap = "3/4".parse().unwrap();
+        assert_eq!(map, ChannelMap::Select(vec![3, 4]));
+        assert_eq!(map.to_string(), "3/4");
+        let quad = chunk(4, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]);
+        let pair = map.apply(quad.clone());
+        assert_eq!(pair.channels, 2);
+        assert_eq!(pair.samples, [0.3, 0.4, 0.7, 0.8]);
+        // Channels the source doesn't send are silent
+        let missing = ChannelMap::Select(vec![4, 9]).apply(quad.clone());
+        assert_eq!(missing.samples, [0.4, 0.0, 0.8, 0.0]);
+        assert_eq!(ChannelMap::All.apply(quad.clone()).samples, quad.samples);
+
+        let surround = chunk(6, vec![0.2, 0.1, 0.5, 0.9, 0.4, 0.0]);
+        let stereo = ChannelMap::Downmix51.apply(surround);
+        assert_eq!(stereo.channels, 2);
+        assert!((stereo.samples[0] - (0.2 + 0.9 * DOWNMIX_GAIN)).abs() < 1e-6);
+        assert!((stereo.samples[1] - (0.1 + 0.5 * DOWNMIX_GAIN)).abs() < 1e-6);
+        assert_eq!(ChannelMap::Downmix51.apply(quad.clone()).channels, 4);
+
+        assert_eq!("5.1".parse(), Ok(ChannelMap::Downmix51));
+        assert!("0/1".parse::<ChannelMap>().is_err());
+        assert!("left".parse::<ChannelMap>().is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Discovery of AES67/Dante audio streams from SAP announcements
+
+use super::sap::{AudioStream, SapPacket, SAP_MULTICAST_ADDR, SAP_PORT};
+use crate::network::multicast::join_multicast;
+use anyhow::Result;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Read-only listing of audio streams announced on the network
+pub struct AudioStreamDiscovery {
+    /// How long a stream stays listed without being re-announced
+    timeout: Duration,
+    /// Announced streams by session id, with the time they were last announced
+    streams: Arc<Mutex<HashMap<String, (AudioStream, Instant)>>>,
+}
+
+impl AudioStreamDiscovery {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            streams: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Start listening for SAP announcements
+    pub fn start(&self) -> Result<()> {
+        let socket = join_multicast(SAP_MULTICAST_ADDR, SAP_PORT)?;
+        info!(
+            "Listening for AES67 announcements on {}:{}",
+            SAP_MULTICAST_ADDR, SAP_PORT
+        );
+
+        let streams = Arc::clone(&self.streams);
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 4096];
+            loop {
+                let len = match socket.recv(&mut buf).await {
+                    Ok(len) => len,
+                    Err(e) => {
+                        warn!("SAP receive error: {}", e);
+                        continue;
+                    }
+                };
+
+                if let Err(e) = Self::handle_packet(&streams, &buf[..len], Instant::now()) {
+                    debug!("Ignoring SAP packet: {}", e);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Apply one SAP packet to the stream list
+    fn handle_packet(
+        streams: &Mutex<HashMap<String, (AudioStream, Instant)>>,
+        data: &[u8],
+        now: Instant,
+    ) -> Result<()> {
+        let packet = SapPacket::parse(data)?;
+        let stream = AudioStream::from_sdp(&packet.sdp)?;
+        let mut streams = streams.lock().unwrap();
+
+        if packet.deletion {
+            if streams.remove(&stream.id).is_some() {
+                info!("Audio stream withdrawn: {}", stream.name);
+            }
+        } else if streams
+            .insert(stream.id.clone(), (stream.clone(), now))
+            .is_none()
+        {
+            info!(
+                "Audio stream discovered: {} ({})",
+                stream.name,
+                stream.format()
+            );
+        }
+        Ok(())
+    }
+
+    /// Currently announced streams, sorted by name
+    pub fn streams(&self) -> Vec<AudioStream> {
+        self.streams_at(Instant::now())
+    }
+
+    fn streams_at(&self, now: Instant) -> Vec<AudioStream> {
+        let mut streams = self.streams.lock().unwrap();
+        streams.retain(|_, (_, last_seen)| now.duration_since(*last_seen) < self.timeout);
+
+        let mut list: Vec<AudioStream> = streams.values().map(|(s, _)| s.clone()).collect();
+        list.sort_by_key(|s| s.name.to_lowercase());
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(name: &str, session: u32, deletion: bool) -> Vec<u8> {
+        let mut packet = vec![0x20 | if deletion { 0x04 } else { 0 }, 0, 0, 1];
+        packet.extend_from_slice(&[10, 0, 0, 5]);
+        packet.extend_from_slice(
+            format!(
+                "v=0\r\no=- {session} 1 IN IP4 10.0.0.5\r\ns={name}\r\n\
+                 c=IN IP4 239.69.0.{session}/32\r\nm=audio 5004 RTP/AVP 96\r\n\
+                 a=rtpmap:96 L24/48000/8\r\n"
+            )
+            .as_bytes(),
+        );
+        packet
+    }
+
+    #[test]
+    fn test_announce_withdraw_and_expire() {
+        let discovery = AudioStreamDiscovery::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        for (name, session) in [("Stage Box", 1), ("FOH Mix", 2)] {
+            AudioStreamDiscovery::handle_packet(
+                &discovery.streams,
+                &announcement(name, session, false),
+                start,
+            )
+            .unwrap();
+        }
+        let names: Vec<String> = discovery
+            .streams_at(start)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["FOH Mix", "Stage Box"]);
+
+        AudioStreamDiscovery::handle_packet(
+            &discovery.streams,
+            &announcement("FOH Mix", 2, true),
+            start,
+        )
+        .unwrap();
+        assert_eq!(discovery.streams_at(start).len(), 1);
+
+        assert!(discovery
+            .streams_at(start + Duration::from_secs(61))
+            .is_empty());
+    }
+}
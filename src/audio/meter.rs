@@ -0,0 +1,313 @@
+//! Audio level metering, of received NDI audio and AES67 RTP streams
+
+use super::sap::AudioStream;
+use crate::network::multicast::join_multicast;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::Serialize;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Lowest level shown on meters
+pub const METER_FLOOR_DB: f32 = -60.0;
+
+/// Levels are measured over windows of this length (20 updates a second)
+pub const LEVEL_WINDOW: Duration = Duration::from_millis(50);
+
+/// Levels older than this are dropped (the source stopped sending audio)
+const LEVEL_HOLD: Duration = Duration::from_millis(500);
+
+/// Convert a linear peak (0.0 to 1.0) to dBFS, clamped to the meter floor
+pub fn to_dbfs(peak: f32) -> f32 {
+    if peak <= 0.0 {
+        METER_FLOOR_DB
+    } else {
+        (20.0 * peak.log10()).max(METER_FLOOR_DB)
+    }
+}
+
+/// Get the payload of an RTP packet, skipping CSRCs, extensions and padding
+pub fn rtp_payload(packet: &[u8]) -> Option<&[u8]> {
+    if packet.len() < 12 || packet[0] >> 6 != 2 {
+        return None;
+    }
+
+    let csrc_count = (packet[0] & 0x0f) as usize;
+    let mut offset = 12 + csrc_count * 4;
+
+    if packet[0] & 0x10 != 0 {
+        let ext = packet.get(offset..offset + 4)?;
+        let ext_words = u16::from_be_bytes([ext[2], ext[3]]) as usize;
+        offset += 4 + ext_words * 4;
+    }
+
+    let mut end = packet.len();
+    if packet[0] & 0x20 != 0 {
+        end = end.checked_sub(*packet.last()? as usize)?;
+    }
+
+    packet.get(offset..end)
+}
+
+/// Peak level per channel of interleaved big-endian linear PCM
+pub fn peak_levels(payload: &[u8], bytes_per_sample: usize, channels: usize) -> Vec<f32> {
+    let mut peaks = vec![0.0f32; channels];
+    if channels == 0 {
+        return peaks;
+    }
+
+    let full_scale = (1i32 << (bytes_per_sample * 8 - 1)) as f32;
+    for (i, sample) in payload.chunks_exact(bytes_per_sample).enumerate() {
+        // Sign-extend the big-endian sample into an i32
+        let value = sample.iter().fold(0i32, |acc, &b| (acc << 8) | b as i32)
+            << (32 - bytes_per_sample * 8)
+            >> (32 - bytes_per_sample * 8);
+
+        let level = (value as f32 / full_scale).abs();
+        let peak = &mut peaks[i % channels];
+        *peak = peak.max(level);
+    }
+    peaks
+}
+
+/// Level of one audio channel over a metering window (linear, 0.0 to 1.0)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ChannelLevel {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+impl ChannelLevel {
+    #[cfg(test)]
+    pub fn peak_dbfs(&self) -> f32 {
+        to_dbfs(self.peak)
+    }
+
+    #[cfg(test)]
+    pub fn rms_dbfs(&self) -> f32 {
+        to_dbfs(self.rms)
+    }
+}
+
+/// Measures per-channel peak and RMS levels of interleaved audio over fixed windows
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    /// Peak and sum of squares per channel in the current window
+    peaks: Vec<f32>,
+    squares: Vec<f64>,
+    /// Samples per channel in the current window
+    samples: usize,
+    window_start: Option<Instant>,
+    /// Levels of the last complete window, and when it completed
+    levels: Vec<ChannelLevel>,
+    updated: Option<Instant>,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add interleaved float samples received at `now`
+    pub fn push(&mut self, samples: &[f32], channels: usize, now: Instant) {
+        if channels == 0 {
+            return;
+        }
+        // A change of channel count starts afresh
+        if self.peaks.len() != channels {
+            self.reset();
+            self.peaks = vec![0.0; channels];
+            self.squares = vec![0.0; channels];
+        }
+        let window_start = *self.window_start.get_or_insert(now);
+
+        for frame in samples.chunks_exact(channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                self.peaks[channel] = self.peaks[channel].max(sample.abs());
+                self.squares[channel] += f64::from(*sample) * f64::from(*sample);
+            }
+            self.samples += 1;
+        }
+
+        if now.saturating_duration_since(window_start) >= LEVEL_WINDOW {
+            let samples = self.samples.max(1) as f64;
+            self.levels = self
+                .peaks
+                .iter()
+                .zip(&self.squares)
+                .map(|(peak, squares)| ChannelLevel {
+                    peak: *peak,
+                    rms: (squares / samples).sqrt() as f32,
+                })
+                .collect();
+            self.updated = Some(now);
+            self.peaks.iter_mut().for_each(|p| *p = 0.0);
+            self.squares.iter_mut().for_each(|s| *s = 0.0);
+            self.samples = 0;
+            self.window_start = Some(now);
+        }
+    }
+
+    /// Levels of the last complete window (none if no audio arrived lately)
+    pub fn levels(&self, now: Instant) -> Vec<ChannelLevel> {
+        match self.updated {
+            Some(updated) if now.saturating_duration_since(updated) <= LEVEL_HOLD => {
+                self.levels.clone()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Receives an AES67 stream and tracks per-channel peak levels
+pub struct StreamMeter {
+    peaks: Arc<Mutex<Vec<f32>>>,
+    task: JoinHandle<()>,
+}
+
+impl StreamMeter {
+    /// Join the stream's multicast group and start metering
+    pub fn start(stream: &AudioStream) -> Result<Self> {
+        let bytes_per_sample = stream
+            .bytes_per_sample()
+            .with_context(|| format!("Cannot meter {} audio", stream.encoding))?;
+        let group = match stream.address {
+            Some(IpAddr::V4(addr)) if addr.is_multicast() => addr,
+            _ => anyhow::bail!("Stream '{}' is not an IPv4 multicast stream", stream.name),
+        };
+        let channels = stream.channels as usize;
+
+        let socket = join_multicast(group, stream.port)?;
+        info!("Metering {} ({}:{})", stream.name, group, stream.port);
+
+        let peaks = Arc::new(Mutex::new(vec![0.0f32; channels]));
+        let task_peaks = Arc::clone(&peaks);
+        let name = stream.name.clone();
+
+        let task = tokio::spawn(async move {
+            let mut buf = vec![0u8; 2048];
+            loop {
+                let len = match socket.recv(&mut buf).await {
+                    Ok(len) => len,
+                    Err(e) => {
+                        warn!("Meter receive error on {}: {}", name, e);
+                        continue;
+                    }
+                };
+                let Some(payload) = rtp_payload(&buf[..len]) else {
+                    continue;
+                };
+
+                let levels = peak_levels(payload, bytes_per_sample, channels);
+                let mut peaks = task_peaks.lock().unwrap();
+                for (peak, level) in peaks.iter_mut().zip(levels) {
+                    *peak = peak.max(level);
+                }
+            }
+        });
+
+        Ok(Self { peaks, task })
+    }
+
+    /// Take the peak of each channel since the last call
+    pub fn take_peaks(&self) -> Vec<f32> {
+        let mut peaks = self.peaks.lock().unwrap();
+        let taken = peaks.clone();
+        peaks.iter_mut().for_each(|p| *p = 0.0);
+        taken
+    }
+}
+
+impl Drop for StreamMeter {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rtp_payload() {
+        let mut packet = vec![0x80, 97, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        packet.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(rtp_payload(&packet), Some(&[1, 2, 3][..]));
+
+        // One CSRC and two bytes of padding
+        let mut packet = vec![0xa1, 97, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 9, 9, 9, 9];
+        packet.extend_from_slice(&[1, 2, 3, 0, 2]);
+        assert_eq!(rtp_payload(&packet), Some(&[1, 2, 3][..]));
+
+        assert_eq!(rtp_payload(&[0x40; 12]), None);
+    }
+
+    #[test]
+    fn test_peak_levels_l24() {
+        // Two channels: full-scale negative and half-scale positive
+        let payload = [
+            0x80, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        ];
+        let peaks = peak_levels(&payload, 3, 2);
+        assert_eq!(peaks[0], 1.0);
+        assert_eq!(peaks[1], 0.5);
+    }
+
+    #[test]
+    fn test_level_meter() {
+        let mut meter = LevelMeter::new();
+        let start = Instant::now();
+        // Left: full-scale square wave, right: silence
+        let samples: Vec<f32> = (0..480)
+            .flat_map(|i| [if i % 2 == 0 { 1.0 } else { -1.0 }, 0.0])
+            .collect();
+
+        meter.push(&samples, 2, start);
+        assert!(meter.levels(start).is_empty());
+
+        meter.push(&samples, 2, start + LEVEL_WINDOW);
+        let levels = meter.levels(start + LEVEL_WINDOW);
+        assert_eq!(
+            levels,
+            vec![
+                ChannelLevel {
+                    peak: 1.0,
+                    rms: 1.0
+                },
+                ChannelLevel {
+                    peak: 0.0,
+                    rms: 0.0
+                }
+            ]
+        );
+        assert_eq!(levels[0].rms_dbfs(), 0.0);
+        assert_eq!(levels[1].peak_dbfs(), METER_FLOOR_DB);
+
+        // Half-amplitude sine has an RMS of 0.5 / sqrt(2)
+        let sine: Vec<f32> = (0..4800)
+            .map(|i| 0.5 * (i as f32 * std::f32::consts::TAU / 48.0).sin())
+            .collect();
+        let next = start + LEVEL_WINDOW * 2;
+        meter.push(&sine, 1, next);
+        meter.push(&sine, 1, next + LEVEL_WINDOW);
+        let levels = meter.levels(next + LEVEL_WINDOW);
+        assert_eq!(levels.len(), 1);
+        assert!((levels[0].rms - 0.5 / 2f32.sqrt()).abs() < 0.001);
+
+        // Stale once audio stops
+        assert!(meter.levels(next + Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn test_to_dbfs() {
+        assert_eq!(to_dbfs(1.0), 0.0);
+        assert!((to_dbfs(0.5) + 6.02).abs() < 0.01);
+        assert_eq!(to_dbfs(0.0), METER_FLOOR_DB);
+    }
+}
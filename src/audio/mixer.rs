@@ -0,0 +1,144 @@
+//! Monitoring mixer: per-source gain and mute applied to what the audio monitor
+//! plays, and a mute-all for when something unexpectedly loud comes through
+
+use super::AudioChunk;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Lowest gain offered (dB)
+pub const MIN_GAIN_DB: f32 = -60.0;
+/// Highest gain offered (dB)
+pub const MAX_GAIN_DB: f32 = 12.0;
+
+/// Monitoring level of one source
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorLevel {
+    pub gain_db: f32,
+    pub muted: bool,
+}
+
+impl MonitorLevel {
+    pub fn new(gain_db: f32) -> Self {
+        Self {
+            gain_db: gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB),
+            muted: false,
+        }
+    }
+}
+
+impl Default for MonitorLevel {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+/// Gain and mute of every monitored source, by NDI name
+pub struct MonitorMixer {
+    levels: BTreeMap<String, MonitorLevel>,
+    /// Level of sources that haven't been adjusted
+    default_level: MonitorLevel,
+    muted_all: bool,
+}
+
+impl MonitorMixer {
+    /// A mixer starting sources that haven't been adjusted at `default_gain_db`
+    pub fn new(default_gain_db: f32) -> Self {
+        Self {
+            levels: BTreeMap::new(),
+            default_level: MonitorLevel::new(default_gain_db),
+            muted_all: false,
+        }
+    }
+
+    /// Take over saved levels
+    pub fn with_levels(mut self, levels: BTreeMap<String, MonitorLevel>) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Every adjusted source's level
+    pub fn levels(&self) -> &BTreeMap<String, MonitorLevel> {
+        &self.levels
+    }
+
+    /// Level of a source
+    pub fn level(&self, source: &str) -> MonitorLevel {
+        self.levels
+            .get(source)
+            .copied()
+            .unwrap_or(self.default_level)
+    }
+
+    pub fn set_level(&mut self, source: &str, level: MonitorLevel) {
+        self.levels.insert(source.to_string(), level);
+    }
+
+    /// Silence every source until unmuted, keeping their own settings
+    pub fn set_muted_all(&mut self, muted: bool) {
+        self.muted_all = muted;
+    }
+
+    pub fn is_muted_all(&self) -> bool {
+        self.muted_all
+    }
+
+    /// Linear gain a source is played at (0 when muted)
+    pub fn gain(&self, source: &str) -> f32 {
+        let level = self.level(source);
+        if self.muted_all || level.muted {
+            0.0
+        } else {
+            10f32.powf(level.gain_db / 20.0)
+        }
+    }
+
+    /// A chunk of a source's audio at its monitoring level, clipped to full scale
+    pub fn apply(&self, source: &str, chunk: &AudioChunk) -> AudioChunk {
+        let gain = self.gain(source);
+        AudioChunk {
+            samples: chunk
+                .samples
+                .iter()
+                .map(|s| (s * gain).clamp(-1.0, 1.0))
+                .collect(),
+            ..chunk.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_mixer() {
+        let chunk = AudioChunk {
+            sample_rate: 48000,
+            channels: 2,
+            samples: vec![0.5, -0.5, 0.9, -0.9],
+        };
+        let mut mixer = MonitorMixer::new(-6.0);
+        let quiet = mixer.apply("CAM-1 (Wide)", &chunk);
+        assert!((quiet.samples[0] - 0.25).abs() < 0.01);
+
+        mixer.set_level("CAM-1 (Wide)", MonitorLevel::new(12.0));
+        let loud = mixer.apply("CAM-1 (Wide)", &chunk);
+        assert_eq!(loud.samples[2..], [1.0, -1.0]);
+
+        mixer.set_level(
+            "CAM-1 (Wide)",
+            MonitorLevel {
+                gain_db: 0.0,
+                muted: true,
+            },
+        );
+        assert_eq!(mixer.gain("CAM-1 (Wide)"), 0.0);
+
+        mixer.set_muted_all(true);
+        assert_eq!(mixer.gain("CAM-2 (Tight)"), 0.0);
+        mixer.set_muted_all(false);
+        assert!((mixer.gain("CAM-2 (Tight)") - 0.5).abs() < 0.01);
+        assert_eq!(MonitorLevel::new(40.0).gain_db, MAX_GAIN_DB);
+    }
+}
@@ -0,0 +1,21 @@
+//! Audio support: microphone capture, operator talkback, source monitoring and AES67/Dante
+//! stream awareness
+
+pub mod capture;
+pub mod channels;
+pub mod discovery;
+pub mod meter;
+pub mod mixer;
+pub mod playback;
+pub mod sap;
+pub mod talkback;
+pub mod trim;
+
+pub use capture::{AudioChunk, MicCapture};
+pub use channels::ChannelMap;
+pub use discovery::AudioStreamDiscovery;
+pub use meter::StreamMeter;
+pub use mixer::{MonitorLevel, MonitorMixer};
+pub use playback::AudioPlayback;
+pub use talkback::Talkback;
+pub use trim::InputTrim;
@@ -0,0 +1,295 @@
+//! Playback of received audio on a local output device (requires the `audio` feature)
+
+use super::AudioChunk;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Maximum buffered playback audio in milliseconds; older audio is dropped to keep latency low
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+const MAX_LATENCY_MS: usize = 200;
+
+/// Convert interleaved audio to another channel count and sample rate.
+///
+/// Mono is copied to every output channel, downmixing to mono averages all input
+/// channels, and otherwise channels map one to one (extra output channels are silent).
+/// Sample rates are converted by linear interpolation.
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub fn convert(chunk: &AudioChunk, channels: u16, sample_rate: u32) -> Vec<f32> {
+    let in_channels = chunk.channels.max(1) as usize;
+    let out_channels = channels.max(1) as usize;
+    let frames = chunk.samples.len() / in_channels;
+    if frames == 0 {
+        return Vec::new();
+    }
+
+    let sample = |frame: usize, channel: usize| -> f32 {
+        let frame = &chunk.samples[frame * in_channels..(frame + 1) * in_channels];
+        if in_channels == 1 {
+            frame[0]
+        } else if out_channels == 1 {
+            frame.iter().sum::<f32>() / in_channels as f32
+        } else {
+            frame.get(channel).copied().unwrap_or(0.0)
+        }
+    };
+
+    let out_frames = if chunk.sample_rate == 0 || chunk.sample_rate == sample_rate {
+        frames
+    } else {
+        (frames as u64 * sample_rate as u64 / chunk.sample_rate as u64) as usize
+    };
+    let step = frames as f64 / out_frames.max(1) as f64;
+
+    let mut out = Vec::with_capacity(out_frames * out_channels);
+    for i in 0..out_frames {
+        let position = i as f64 * step;
+        let index = position as usize;
+        let next = (index + 1).min(frames - 1);
+        let fraction = (position - index as f64) as f32;
+        for channel in 0..out_channels {
+            let a = sample(index, channel);
+            let b = sample(next, channel);
+            out.push(a + (b - a) * fraction);
+        }
+    }
+    out
+}
+
+/// Fades the previous source's queued audio out while the next source fades in
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+#[derive(Debug, Default)]
+struct Crossfade {
+    /// Audio of the previous source not yet played
+    outgoing: VecDeque<f32>,
+    /// Samples over which the fade runs
+    length: usize,
+    /// Samples mixed so far
+    position: usize,
+    channels: usize,
+}
+
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+impl Crossfade {
+    fn new(outgoing: VecDeque<f32>, length: usize, channels: u16) -> Self {
+        Self {
+            outgoing,
+            length,
+            position: 0,
+            channels: channels.max(1) as usize,
+        }
+    }
+
+    /// Mix incoming samples (interleaved) with the fading outgoing audio in place
+    fn mix(&mut self, incoming: &mut [f32]) {
+        for sample in incoming {
+            if self.position >= self.length {
+                return;
+            }
+            // Every channel of a frame gets the same gain
+            let frame_start = self.position - self.position % self.channels;
+            let gain = frame_start as f32 / self.length as f32;
+            let old = self.outgoing.pop_front().unwrap_or(0.0);
+            *sample = old * (1.0 - gain) + *sample * gain;
+            self.position += 1;
+        }
+    }
+}
+
+/// Live playback to an output device; audio stops when this is dropped
+#[cfg(feature = "audio")]
+pub struct AudioPlayback {
+    _stream: cpal::Stream,
+    buffer: std::sync::Arc<std::sync::Mutex<VecDeque<f32>>>,
+    /// Crossfade from the previous source still in progress
+    fade: std::sync::Mutex<Crossfade>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+#[cfg(feature = "audio")]
+impl AudioPlayback {
+    /// Start playback on the named output device (or the system default)
+    pub fn start(device_name: Option<&str>) -> Result<Self> {
+        use anyhow::Context;
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+        use log::{error, info};
+        use std::sync::{Arc, Mutex};
+
+        let host = cpal::default_host();
+        let device = match device_name {
+            Some(name) => host
+                .output_devices()
+                .context("Failed to list output devices")?
+                .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+                .with_context(|| format!("Output device '{}' not found", name))?,
+            None => host
+                .default_output_device()
+                .context("No default output device available")?,
+        };
+
+        let supported = device
+            .default_output_config()
+            .context("Failed to query output device configuration")?;
+        let sample_rate = supported.sample_rate().0;
+        let channels = supported.channels();
+        let sample_format = supported.sample_format();
+        let stream_config: cpal::StreamConfig = supported.into();
+
+        info!(
+            "Starting audio playback on {} ({} Hz, {} ch)",
+            device.name().unwrap_or_default(),
+            sample_rate,
+            channels
+        );
+
+        let buffer = Arc::new(Mutex::new(VecDeque::new()));
+        let on_error = |e| error!("Playback stream error: {}", e);
+
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => {
+                let buffer = Arc::clone(&buffer);
+                device.build_output_stream(
+                    &stream_config,
+                    move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                        let mut buffer = buffer.lock().unwrap();
+                        for sample in data.iter_mut() {
+                            *sample = buffer.pop_front().unwrap_or(0.0);
+                        }
+                    },
+                    on_error,
+                    None,
+                )
+            }
+            cpal::SampleFormat::I16 => {
+                let buffer = Arc::clone(&buffer);
+                device.build_output_stream(
+                    &stream_config,
+                    move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                        let mut buffer = buffer.lock().unwrap();
+                        for sample in data.iter_mut() {
+                            let value = buffer.pop_front().unwrap_or(0.0).clamp(-1.0, 1.0);
+                            *sample = (value * i16::MAX as f32) as i16;
+                        }
+                    },
+                    on_error,
+                    None,
+                )
+            }
+            other => anyhow::bail!("Unsupported output sample format: {:?}", other),
+        }
+        .context("Failed to open output stream")?;
+
+        stream.play().context("Failed to start output stream")?;
+
+        Ok(Self {
+            _stream: stream,
+            buffer,
+            fade: Mutex::new(Crossfade::default()),
+            sample_rate,
+            channels,
+        })
+    }
+
+    /// Queue a chunk of audio, converted to the device format
+    pub fn play(&self, chunk: &AudioChunk) {
+        let mut samples = convert(chunk, self.channels, self.sample_rate);
+        self.fade.lock().unwrap().mix(&mut samples);
+        let max_samples =
+            self.sample_rate as usize * self.channels as usize * MAX_LATENCY_MS / 1000;
+
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.extend(samples);
+        let excess = buffer.len().saturating_sub(max_samples);
+        buffer.drain(..excess);
+    }
+
+    /// Drop queued audio (e.g. when switching sources)
+    pub fn clear(&self) {
+        self.buffer.lock().unwrap().clear();
+        *self.fade.lock().unwrap() = Crossfade::default();
+    }
+
+    /// Switch sources smoothly: queued audio fades out under the audio played next
+    pub fn crossfade(&self, duration: Duration) {
+        let length = (self.sample_rate as u128 * duration.as_millis() / 1000) as usize
+            * self.channels as usize;
+        let outgoing = std::mem::take(&mut *self.buffer.lock().unwrap());
+        *self.fade.lock().unwrap() = Crossfade::new(outgoing, length, self.channels);
+    }
+}
+
+/// Placeholder used when RusTV is built without audio support
+#[cfg(not(feature = "audio"))]
+pub struct AudioPlayback;
+
+#[cfg(not(feature = "audio"))]
+impl AudioPlayback {
+    /// Always fails: playback requires the `audio` feature
+    pub fn start(_device_name: Option<&str>) -> Result<Self> {
+        anyhow::bail!("RusTV was built without audio support (enable the `audio` feature)")
+    }
+
+    pub fn play(&self, _chunk: &AudioChunk) {}
+
+    pub fn clear(&self) {}
+
+    pub fn crossfade(&self, _duration: Duration) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(sample_rate: u32, channels: u16, samples: Vec<f32>) -> AudioChunk {
+        AudioChunk {
+            sample_rate,
+            channels,
+            samples,
+        }
+    }
+
+    #[test]
+    fn test_channel_mapping() {
+        let mono = chunk(48000, 1, vec![0.5, -0.5]);
+        assert_eq!(convert(&mono, 2, 48000), vec![0.5, 0.5, -0.5, -0.5]);
+
+        let stereo = chunk(48000, 2, vec![1.0, 0.0, 0.5, 0.5]);
+        assert_eq!(convert(&stereo, 1, 48000), vec![0.5, 0.5]);
+
+        let quad = chunk(48000, 4, vec![0.1, 0.2, 0.3, 0.4]);
+        assert_eq!(convert(&quad, 2, 48000), vec![0.1, 0.2]);
+        assert_eq!(convert(&stereo, 3, 48000)[..3], [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let outgoing: VecDeque<f32> = vec![1.0; 8].into();
+        let mut fade = Crossfade::new(outgoing, 8, 2);
+        let mut incoming = vec![0.0; 6];
+        fade.mix(&mut incoming);
+        // Both channels of a frame share a gain, stepping a quarter per frame
+        assert_eq!(incoming, vec![1.0, 1.0, 0.75, 0.75, 0.5, 0.5]);
+
+        let mut incoming = vec![0.0; 4];
+        fade.mix(&mut incoming);
+        assert_eq!(incoming, vec![0.25, 0.25, 0.0, 0.0]);
+
+        // Without queued audio the next source simply fades in
+        let mut fade = Crossfade::new(VecDeque::new(), 4, 1);
+        let mut incoming = vec![1.0; 4];
+        fade.mix(&mut incoming);
+        assert_eq!(incoming, vec![0.0, 0.25, 0.5, 0.75]);
+    }
+
+    #[test]
+    fn test_resample() {
+        let input = chunk(24000, 1, vec![0.0, 1.0, 0.0, -1.0]);
+        let output = convert(&input, 1, 48000);
+        assert_eq!(output.len(), 8);
+        assert_eq!(output[..4], [0.0, 0.5, 1.0, 0.5]);
+
+        let input = chunk(96000, 1, vec![0.0; 960]);
+        assert_eq!(convert(&input, 1, 48000).len(), 480);
+    }
+}
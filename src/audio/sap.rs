@@ -0,0 +1,266 @@
+//! SAP (RFC 2974) announcement and SDP parsing for AES67/Dante streams
+
+use anyhow::{bail, Context, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Multicast group SAP announcements are sent to (administratively scoped)
+pub const SAP_MULTICAST_ADDR: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 255);
+
+/// UDP port used for SAP
+pub const SAP_PORT: u16 = 9875;
+
+/// A parsed SAP packet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SapPacket {
+    /// Whether this announces (false) or withdraws (true) the session
+    pub deletion: bool,
+    /// Message identifier hash
+    pub msg_id_hash: u16,
+    /// Address of the announcing device
+    pub origin: IpAddr,
+    /// Session description
+    pub sdp: String,
+}
+
+impl SapPacket {
+    /// Parse a SAP packet carrying an SDP payload
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 {
+            bail!("SAP packet too short");
+        }
+
+        let flags = data[0];
+        if flags >> 5 != 1 {
+            bail!("Unsupported SAP version {}", flags >> 5);
+        }
+        if flags & 0x02 != 0 || flags & 0x01 != 0 {
+            bail!("Encrypted or compressed SAP payloads are not supported");
+        }
+
+        let ipv6 = flags & 0x10 != 0;
+        let deletion = flags & 0x04 != 0;
+        let auth_len = data[1] as usize * 4;
+        let msg_id_hash = u16::from_be_bytes([data[2], data[3]]);
+
+        let (origin, mut offset) = if ipv6 {
+            let bytes: [u8; 16] = data
+                .get(4..20)
+                .context("SAP packet too short")?
+                .try_into()?;
+            (IpAddr::V6(Ipv6Addr::from(bytes)), 20)
+        } else {
+            (
+                IpAddr::V4(Ipv4Addr::new(data[4], data[5], data[6], data[7])),
+                8,
+            )
+        };
+        offset += auth_len;
+
+        let payload = data.get(offset..).context("SAP packet too short")?;
+        let payload = if payload.starts_with(b"v=0") {
+            payload
+        } else {
+            // Skip the optional null-terminated payload type (e.g. "application/sdp")
+            let end = payload
+                .iter()
+                .position(|&b| b == 0)
+                .context("Missing SAP payload type terminator")?;
+            let payload_type = String::from_utf8_lossy(&payload[..end]);
+            if payload_type != "application/sdp" {
+                bail!("Unsupported SAP payload type {}", payload_type);
+            }
+            &payload[end + 1..]
+        };
+
+        Ok(Self {
+            deletion,
+            msg_id_hash,
+            origin,
+            sdp: String::from_utf8_lossy(payload).into_owned(),
+        })
+    }
+}
+
+/// An audio stream described by SDP
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioStream {
+    /// Session identifier (origin address and session id)
+    pub id: String,
+    /// Session name
+    pub name: String,
+    /// Address of the device sending the stream
+    pub origin: String,
+    /// Destination address of the RTP stream
+    pub address: Option<IpAddr>,
+    /// Destination port of the RTP stream
+    pub port: u16,
+    /// RTP encoding (e.g. "L24")
+    pub encoding: String,
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: u16,
+}
+
+impl AudioStream {
+    /// Parse the first audio media section of an SDP session description
+    pub fn from_sdp(sdp: &str) -> Result<Self> {
+        let mut origin = None;
+        let mut name = String::new();
+        let mut address = None;
+        let mut media: Option<(u16, String)> = None;
+        let mut rtpmap: Option<(String, u32, u16)> = None;
+
+        for line in sdp.lines() {
+            let Some((key, value)) = line.trim_end().split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "o" => {
+                    // o=<username> <sess-id> <sess-version> <nettype> <addrtype> <address>
+                    let fields: Vec<&str> = value.split_whitespace().collect();
+                    if fields.len() == 6 {
+                        origin = Some((fields[1].to_string(), fields[5].to_string()));
+                    }
+                }
+                "s" => name = value.trim().to_string(),
+                "c" => {
+                    // c=IN IP4 239.69.1.1/32
+                    if let Some(addr) = value.split_whitespace().nth(2) {
+                        let addr = addr.split('/').next().unwrap_or(addr);
+                        address = addr.parse().ok();
+                    }
+                }
+                "m" if media.is_none() => {
+                    // m=audio 5004 RTP/AVP 97
+                    let fields: Vec<&str> = value.split_whitespace().collect();
+                    if fields.len() >= 4 && fields[0] == "audio" {
+                        let port = fields[1].parse().context("Invalid media port")?;
+                        media = Some((port, fields[3].to_string()));
+                    }
+                }
+                "a" => {
+                    // a=rtpmap:97 L24/48000/2
+                    if let (Some((_, payload_type)), Some(map)) =
+                        (&media, value.strip_prefix("rtpmap:"))
+                    {
+                        if let Some((pt, format)) = map.split_once(' ') {
+                            if pt == payload_type && rtpmap.is_none() {
+                                let mut parts = format.split('/');
+                                let encoding = parts.next().unwrap_or_default().to_string();
+                                let sample_rate = parts
+                                    .next()
+                                    .and_then(|r| r.parse().ok())
+                                    .unwrap_or_default();
+                                let channels =
+                                    parts.next().and_then(|c| c.parse().ok()).unwrap_or(1);
+                                rtpmap = Some((encoding, sample_rate, channels));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let (session_id, origin) = origin.context("SDP has no origin line")?;
+        let (port, _) = media.context("SDP has no audio media section")?;
+        let (encoding, sample_rate, channels) = rtpmap.unwrap_or_default();
+
+        Ok(Self {
+            id: format!("{}/{}", origin, session_id),
+            name,
+            origin,
+            address,
+            port,
+            encoding,
+            sample_rate,
+            channels,
+        })
+    }
+
+    /// Bytes per sample for linear PCM encodings we can meter
+    pub fn bytes_per_sample(&self) -> Option<usize> {
+        match self.encoding.as_str() {
+            "L16" => Some(2),
+            "L24" => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Whether RusTV can join and meter this stream
+    pub fn is_receivable(&self) -> bool {
+        self.bytes_per_sample().is_some()
+            && self.channels > 0
+            && matches!(self.address, Some(IpAddr::V4(addr)) if addr.is_multicast())
+    }
+
+    /// Short format description, e.g. "L24 48 kHz 2 ch"
+    pub fn format(&self) -> String {
+        format!(
+            "{} {} kHz {} ch",
+            self.encoding,
+            self.sample_rate as f32 / 1000.0,
+            self.channels
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SDP: &str = "v=0\r\n\
+        o=- 1311738121 1311738121 IN IP4 192.168.1.20\r\n\
+        s=Stage Box : 32\r\n\
+        c=IN IP4 239.69.83.171/32\r\n\
+        t=0 0\r\n\
+        a=clock-domain:PTPv2 0\r\n\
+        m=audio 5004 RTP/AVP 97\r\n\
+        c=IN IP4 239.69.83.171/32\r\n\
+        a=rtpmap:97 L24/48000/2\r\n\
+        a=recvonly\r\n";
+
+    fn sap_packet(deletion: bool) -> Vec<u8> {
+        let mut packet = vec![0x20 | if deletion { 0x04 } else { 0 }, 0, 0x12, 0x34];
+        packet.extend_from_slice(&[192, 168, 1, 20]);
+        packet.extend_from_slice(b"application/sdp\0");
+        packet.extend_from_slice(SDP.as_bytes());
+        packet
+    }
+
+    #[test]
+    fn test_parse_sap_packet() {
+        let packet = SapPacket::parse(&sap_packet(false)).unwrap();
+        assert!(!packet.deletion);
+        assert_eq!(packet.msg_id_hash, 0x1234);
+        assert_eq!(packet.origin, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20)));
+        assert!(packet.sdp.starts_with("v=0"));
+
+        assert!(SapPacket::parse(&sap_packet(true)).unwrap().deletion);
+        assert!(SapPacket::parse(&[0x20, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sdp() {
+        let stream = AudioStream::from_sdp(SDP).unwrap();
+        assert_eq!(stream.id, "192.168.1.20/1311738121");
+        assert_eq!(stream.name, "Stage Box : 32");
+        assert_eq!(
+            stream.address,
+            Some(IpAddr::V4(Ipv4Addr::new(239, 69, 83, 171)))
+        );
+        assert_eq!(stream.port, 5004);
+        assert_eq!(stream.format(), "L24 48 kHz 2 ch");
+        assert!(stream.is_receivable());
+    }
+
+    #[test]
+    fn test_unsupported_encoding_not_receivable() {
+        let sdp = SDP.replace("L24/48000/2", "AM824/48000/8");
+        let stream = AudioStream::from_sdp(&sdp).unwrap();
+        assert_eq!(stream.channels, 8);
+        assert!(!stream.is_receivable());
+    }
+}
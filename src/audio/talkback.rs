@@ -0,0 +1,290 @@
+//! Push-to-talk audio return to camera operators and monitor positions
+
+use super::AudioChunk;
+use crate::birddog::BirdDogClient;
+use crate::config::{TalkbackConfig, TalkbackKind};
+use crate::intercom::IntercomChannel;
+use crate::ndi::SendInstance;
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Wait before trying again to publish a sender that couldn't be created
+const SENDER_RETRY: Duration = Duration::from_secs(5);
+
+/// A talkback destination with its runtime enable state
+#[derive(Debug, Clone)]
+pub struct TalkbackDestination {
+    pub name: String,
+    pub kind: TalkbackKind,
+    pub target: String,
+    pub enabled: bool,
+}
+
+/// Talkback controller tracking push-to-talk state and enabled destinations
+pub struct Talkback {
+    destinations: Vec<TalkbackDestination>,
+    talking: bool,
+    /// Partyline carrying audio to intercom destinations
+    intercom: Option<Arc<IntercomChannel>>,
+    /// NDI sender per destination, created on first use
+    senders: HashMap<String, SendInstance>,
+    /// When publishing a destination's sender last failed
+    sender_failures: HashMap<String, Instant>,
+}
+
+impl Talkback {
+    /// Create a talkback controller from configuration (all destinations enabled)
+    pub fn from_config(config: &TalkbackConfig) -> Self {
+        let destinations = config
+            .destinations
+            .iter()
+            .map(|d| TalkbackDestination {
+                name: d.name.clone(),
+                kind: d.kind,
+                target: d.target.clone(),
+                enabled: true,
+            })
+            .collect();
+
+        Self {
+            destinations,
+            talking: false,
+            intercom: None,
+            senders: HashMap::new(),
+            sender_failures: HashMap::new(),
+        }
+    }
+
+    /// Deliver intercom destinations over this station's partyline connection
+    pub fn with_intercom(mut self, intercom: Option<Arc<IntercomChannel>>) -> Self {
+        self.intercom = intercom;
+        self
+    }
+
+    /// Get all configured destinations
+    pub fn destinations(&self) -> &[TalkbackDestination] {
+        &self.destinations
+    }
+
+    /// Enable or disable a destination by name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.destinations.iter_mut().find(|d| d.name == name) {
+            Some(destination) => {
+                destination.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Push-to-talk pressed
+    pub fn press(&mut self) {
+        if !self.talking {
+            info!("Talkback open");
+            self.talking = true;
+        }
+    }
+
+    /// Push-to-talk released
+    pub fn release(&mut self) {
+        if self.talking {
+            info!("Talkback closed");
+            self.talking = false;
+        }
+    }
+
+    /// Check if talkback is currently open
+    pub fn is_talking(&self) -> bool {
+        self.talking
+    }
+
+    /// Destinations that currently receive audio (none unless talking)
+    pub fn active_destinations(&self) -> Vec<&TalkbackDestination> {
+        if !self.talking {
+            return Vec::new();
+        }
+        self.destinations.iter().filter(|d| d.enabled).collect()
+    }
+
+    /// Deliver a chunk of microphone audio to every active destination.
+    /// Returns the number of destinations the audio was sent to.
+    pub fn send(&mut self, chunk: &AudioChunk) -> usize {
+        let active: Vec<_> = self.active_destinations().into_iter().cloned().collect();
+        let mut sent = 0;
+        for destination in &active {
+            let delivered = match destination.kind {
+                // NDI monitors and BirdDog audio return both decode an NDI sender
+                // published for the destination
+                TalkbackKind::Ndi | TalkbackKind::BirdDog => match self.sender(destination) {
+                    Some(sender) => {
+                        sender.send_audio(chunk);
+                        true
+                    }
+                    None => false,
+                },
+                TalkbackKind::Intercom => match &self.intercom {
+                    Some(intercom) => intercom
+                        .send_talkback(&destination.target, chunk)
+                        .map_err(|e| debug!("Talkback to {}: {:#}", destination.name, e))
+                        .is_ok(),
+                    None => false,
+                },
+            };
+            sent += delivered as usize;
+        }
+        sent
+    }
+
+    /// The destination's NDI sender, published on first use. A BirdDog camera's
+    /// audio return is pointed at it once it is published
+    fn sender(&mut self, destination: &TalkbackDestination) -> Option<&mut SendInstance> {
+        if !self.senders.contains_key(&destination.name) {
+            if self
+                .sender_failures
+                .get(&destination.name)
+                .is_some_and(|failed| failed.elapsed() < SENDER_RETRY)
+            {
+                return None;
+            }
+            let name = sender_name(destination);
+            match SendInstance::create(&name) {
+                Ok(sender) => {
+                    self.sender_failures.remove(&destination.name);
+                    if destination.kind == TalkbackKind::BirdDog {
+                        point_audio_return(destination, &name);
+                    }
+                    self.senders.insert(destination.name.clone(), sender);
+                }
+                Err(e) => {
+                    warn!("Talkback to {}: {:#}", destination.name, e);
+                    self.sender_failures
+                        .insert(destination.name.clone(), Instant::now());
+                    return None;
+                }
+            }
+        }
+        self.senders.get_mut(&destination.name)
+    }
+}
+
+/// Name of the NDI sender published for a destination
+fn sender_name(destination: &TalkbackDestination) -> String {
+    match destination.kind {
+        TalkbackKind::Ndi => destination.target.clone(),
+        _ => format!("Talkback {}", destination.name),
+    }
+}
+
+/// Set the camera's audio return to the sender published for it, as NDI lists
+/// it on the network ("HOST (name)")
+fn point_audio_return(destination: &TalkbackDestination, sender: &str) {
+    let Some(host) = crate::network::local_hostname() else {
+        error!(
+            "Talkback to {}: hostname unknown, set the camera's audio return to {} by hand",
+            destination.name, sender
+        );
+        return;
+    };
+    let source = format!("{} ({})", host.to_uppercase(), sender);
+    let camera = destination.target.clone();
+    let name = destination.name.clone();
+    tokio::spawn(async move {
+        if let Err(e) = BirdDogClient::new(&camera).set_audio_return(&source).await {
+            error!("Failed to set the audio return of {}: {:#}", name, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TalkbackDestinationConfig;
+
+    fn config() -> TalkbackConfig {
+        TalkbackConfig {
+            enabled: true,
+            destinations: vec![
+                TalkbackDestinationConfig {
+                    name: "Cam 1".to_string(),
+                    kind: TalkbackKind::BirdDog,
+                    target: "192.168.1.101".to_string(),
+                },
+                TalkbackDestinationConfig {
+                    name: "Stage".to_string(),
+                    kind: TalkbackKind::Ndi,
+                    target: "STAGE-PC (Monitor)".to_string(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_push_to_talk() {
+        let mut talkback = Talkback::from_config(&config());
+        assert!(talkback.active_destinations().is_empty());
+
+        talkback.press();
+        assert!(talkback.is_talking());
+        assert_eq!(talkback.active_destinations().len(), 2);
+
+        talkback.release();
+        assert!(talkback.active_destinations().is_empty());
+    }
+
+    #[test]
+    fn test_per_destination_enable() {
+        let mut talkback = Talkback::from_config(&config());
+        assert!(talkback.set_enabled("Cam 1", false));
+        assert!(!talkback.set_enabled("Unknown", false));
+
+        talkback.press();
+        assert_eq!(talkback.active_destinations().len(), 1);
+        assert_eq!(talkback.active_destinations()[0].name, "Stage");
+    }
+
+    #[test]
+    fn test_send_only_while_talking() {
+        let mut talkback = Talkback::from_config(&config());
+        let chunk = AudioChunk {
+            sample_rate: 48000,
+            channels: 1,
+            samples: vec![0.0; 480],
+        };
+        assert_eq!(talkback.send(&chunk), 0);
+        assert!(talkback.senders.is_empty());
+    }
+
+    #[test]
+    fn test_sender_names() {
+        let talkback = Talkback::from_config(&config());
+        let names: Vec<_> = talkback.destinations().iter().map(sender_name).collect();
+        assert_eq!(names, ["Talkback Cam 1", "STAGE-PC (Monitor)"]);
+    }
+
+    #[cfg(not(feature = "ndi"))]
+    #[test]
+    fn test_failed_sender_retried() {
+        let mut talkback = Talkback::from_config(&config());
+        talkback.set_enabled("Cam 1", false);
+        talkback.press();
+        let chunk = AudioChunk {
+            sample_rate: 48000,
+            channels: 1,
+            samples: vec![0.0; 480],
+        };
+        // Senders can't be created without the NDI SDK
+        assert_eq!(talkback.send(&chunk), 0);
+        assert!(talkback.senders.is_empty());
+        assert!(talkback.sender_failures.contains_key("Stage"));
+
+        // Tried again once the retry wait is over
+        talkback
+            .sender_failures
+            .insert("Stage".to_string(), Instant::now() - SENDER_RETRY);
+        talkback.send(&chunk);
+        assert!(talkback.sender_failures["Stage"].elapsed() < SENDER_RETRY);
+    }
+}
@@ -0,0 +1,89 @@
+//! Input trim of received audio: a per-source gain and the level the source
+//! lines up at, applied before metering and monitoring so a feed that runs
+//! hotter than the others reads and sounds like them
+
+use super::AudioChunk;
+use serde::{Deserialize, Serialize};
+
+/// Line-up level every source is brought to (EBU R68)
+pub const HOUSE_REFERENCE_DBFS: f32 = -18.0;
+/// Lowest trim gain offered (dB)
+pub const MIN_TRIM_DB: f32 = -40.0;
+/// Highest trim gain offered (dB)
+pub const MAX_TRIM_DB: f32 = 20.0;
+
+/// Trim of one source
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputTrim {
+    /// Gain on top of the reference level correction (dB)
+    pub gain_db: f32,
+    /// Level the source's line-up tone arrives at (dBFS)
+    pub reference_dbfs: f32,
+}
+
+impl InputTrim {
+    /// Total gain: the reference level brought to the house reference, plus the gain
+    pub fn total_db(&self) -> f32 {
+        self.gain_db + HOUSE_REFERENCE_DBFS - self.reference_dbfs
+    }
+
+    /// Trim a chunk in place, clipped to full scale
+    pub fn apply(&self, chunk: &mut AudioChunk) {
+        let total_db = self.total_db();
+        if total_db == 0.0 {
+            return;
+        }
+        let gain = 10f32.powf(total_db / 20.0);
+        for sample in &mut chunk.samples {
+            *sample = (*sample * gain).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+impl Default for InputTrim {
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            reference_dbfs: HOUSE_REFERENCE_DBFS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_trim() {
+        let mut chunk = AudioChunk {
+            sample_rate: 48000,
+            channels: 2,
+            samples: vec![0.5, -0.5, 0.05, -1.0],
+        };
+        let untouched = chunk.samples.clone();
+        InputTrim::default().apply(&mut chunk);
+        assert_eq!(chunk.samples, untouched);
+
+        // A playback server lining up 20 dB hot
+        let hot = InputTrim {
+            gain_db: 0.0,
+            reference_dbfs: 2.0,
+        };
+        assert_eq!(hot.total_db(), -20.0);
+        hot.apply(&mut chunk);
+        assert!((chunk.samples[0] - 0.05).abs() < 1e-6);
+        assert!((chunk.samples[3] + 0.1).abs() < 1e-6);
+
+        let boost = InputTrim {
+            gain_db: 20.0,
+            ..InputTrim::default()
+        };
+        let mut loud = AudioChunk {
+            samples: untouched,
+            ..chunk
+        };
+        boost.apply(&mut loud);
+        assert_eq!(loud.samples, [1.0, -1.0, 0.5, -1.0]);
+    }
+}
@@ -1,4 +1,6 @@
 use super::ptz::{PtzCommand, PtzPosition};
+use super::stats::EncoderStats;
+use super::transport::StreamTransport;
 use anyhow::{Context, Result};
 use log::{debug, info};
 use reqwest::Client;
@@ -83,6 +85,96 @@ impl BirdDogClient {
         Ok(status)
     }
 
+    /// Get NDI stream transport settings (unicast/multicast, TTL, address range)
+    pub async fn get_stream_transport(&self) -> Result<StreamTransport> {
+        debug!("Fetching stream transport from {}", self.base_url);
+
+        let url = format!("{}/encodetransport", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to send request")?;
+
+        let transport: StreamTransport = response
+            .json()
+            .await
+            .context("Failed to parse stream transport")?;
+
+        Ok(transport)
+    }
+
+    /// Get encoder and network statistics (bitrate, format, dropped packets)
+    pub async fn get_encoder_stats(&self) -> Result<EncoderStats> {
+        debug!("Fetching encoder stats from {}", self.base_url);
+
+        let url = format!("{}/encodestats", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to send request")?;
+
+        let stats: EncoderStats = response
+            .json()
+            .await
+            .context("Failed to parse encoder stats")?;
+
+        Ok(stats)
+    }
+
+    /// Update NDI stream transport settings
+    pub async fn set_stream_transport(&self, transport: &StreamTransport) -> Result<()> {
+        info!("Setting stream transport: {:?}", transport);
+
+        let url = format!("{}/encodetransport", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(transport)
+            .send()
+            .await
+            .context("Failed to send stream transport")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Stream transport update failed with status: {}",
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Point the camera's NDI decoder, which feeds its audio return, at a source
+    pub async fn set_audio_return(&self, source_name: &str) -> Result<()> {
+        info!("Setting audio return source: {}", source_name);
+
+        let url = format!("{}/connectTo", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "sourceName": source_name }))
+            .send()
+            .await
+            .context("Failed to send audio return source")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Audio return update failed with status: {}",
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+
     /// Send PTZ command to camera
     pub async fn send_ptz_command(&self, command: &PtzCommand) -> Result<()> {
         info!("Sending PTZ command: {:?}", command);
@@ -1,5 +1,42 @@
 pub mod api;
 pub mod ptz;
+pub mod stats;
+pub mod transport;
 
 pub use api::BirdDogClient;
 pub use ptz::PtzPosition;
+pub use stats::EncoderStats;
+pub use transport::{StreamTransport, TransmitMode};
+
+/// The BirdDog API sends numbers as strings; accept either form
+mod string_number {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrNumber<T> {
+            String(String),
+            Number(T),
+        }
+
+        match StringOrNumber::<T>::deserialize(deserializer)? {
+            StringOrNumber::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+            StringOrNumber::Number(n) => Ok(n),
+        }
+    }
+}
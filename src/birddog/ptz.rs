@@ -17,7 +17,6 @@ impl PtzPosition {
         }
     }
 
-    #[allow(dead_code)]
     pub fn home() -> Self {
         Self {
             pan: 0.0,
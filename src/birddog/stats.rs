@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Received bitrate below this share of what the camera sends points at the network
+const RECEIVED_SHARE_MIN: f64 = 0.8;
+
+/// Camera-side encoder and network statistics (BirdDog `/encodestats` endpoint)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncoderStats {
+    /// Bitrate the encoder is sending, in kbit/s
+    #[serde(rename = "bitrate", with = "super::string_number")]
+    pub bitrate_kbps: f64,
+    /// Video format being encoded (e.g. "1920x1080p59.94")
+    #[serde(rename = "resolution")]
+    pub resolution: String,
+    /// Packets the camera has dropped since it started
+    #[serde(rename = "droppedpkts", with = "super::string_number")]
+    pub dropped_packets: u64,
+}
+
+/// Which end of a camera link is losing the picture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkFault {
+    /// The camera is dropping packets before they leave it
+    Camera,
+    /// The camera sends more than arrives here
+    Network,
+}
+
+impl fmt::Display for LinkFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkFault::Camera => write!(f, "Camera is dropping packets"),
+            LinkFault::Network => write!(f, "Less arrives than the camera sends (network)"),
+        }
+    }
+}
+
+impl EncoderStats {
+    /// Bitrate the encoder is sending, in bytes per second
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bitrate_kbps * 1000.0 / 8.0
+    }
+
+    /// Compare with the previous sample and what RusTV receives at full bandwidth
+    /// (`None` when not received at full quality) to tell which end is at fault
+    pub fn locate_fault(
+        &self,
+        previous: Option<&EncoderStats>,
+        received_bytes_per_second: Option<f64>,
+    ) -> Option<LinkFault> {
+        // A camera restart resets its counter
+        if previous.is_some_and(|p| self.dropped_packets > p.dropped_packets) {
+            return Some(LinkFault::Camera);
+        }
+        let received = received_bytes_per_second?;
+        (self.bitrate_kbps > 0.0 && received < self.bytes_per_second() * RECEIVED_SHARE_MIN)
+            .then_some(LinkFault::Network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(bitrate_kbps: f64, dropped_packets: u64) -> EncoderStats {
+        EncoderStats {
+            bitrate_kbps,
+            resolution: "1920x1080p59.94".to_string(),
+            dropped_packets,
+        }
+    }
+
+    #[test]
+    fn test_stats_deserialization() {
+        let json = r#"{"bitrate":"125000","resolution":"1920x1080p59.94","droppedpkts":"12"}"#;
+        let stats: EncoderStats = serde_json::from_str(json).unwrap();
+        assert_eq!(stats.bitrate_kbps, 125000.0);
+        assert_eq!(stats.dropped_packets, 12);
+        assert_eq!(stats.bytes_per_second(), 15_625_000.0);
+
+        let json = r#"{"bitrate":8000.5,"resolution":"1280x720p50","droppedpkts":0}"#;
+        let stats: EncoderStats = serde_json::from_str(json).unwrap();
+        assert_eq!(stats.bitrate_kbps, 8000.5);
+    }
+
+    #[test]
+    fn test_locate_fault() {
+        let before = stats(100_000.0, 5);
+        let full_rate = Some(12_500_000.0);
+
+        assert_eq!(
+            stats(100_000.0, 5).locate_fault(Some(&before), full_rate),
+            None
+        );
+        assert_eq!(
+            stats(100_000.0, 9).locate_fault(Some(&before), full_rate),
+            Some(LinkFault::Camera)
+        );
+        // Counter reset after a reboot isn't a drop
+        assert_eq!(
+            stats(100_000.0, 0).locate_fault(Some(&before), full_rate),
+            None
+        );
+        assert_eq!(
+            stats(100_000.0, 5).locate_fault(Some(&before), Some(6_000_000.0)),
+            Some(LinkFault::Network)
+        );
+        // A proxy or audio-only receiver can't be compared
+        assert_eq!(stats(100_000.0, 5).locate_fault(None, None), None);
+    }
+}
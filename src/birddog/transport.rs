@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// NDI transmit mode of a BirdDog encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum TransmitMode {
+    /// Unicast TCP
+    #[serde(rename = "TCP")]
+    Tcp,
+    /// Unicast UDP
+    #[serde(rename = "UDP")]
+    Udp,
+    /// Multicast
+    #[serde(rename = "Multicast")]
+    Multicast,
+    /// Multi-stream TCP
+    #[serde(rename = "Multi-TCP")]
+    MultiTcp,
+}
+
+impl TransmitMode {
+    pub fn all() -> [TransmitMode; 4] {
+        [
+            TransmitMode::Tcp,
+            TransmitMode::Udp,
+            TransmitMode::Multicast,
+            TransmitMode::MultiTcp,
+        ]
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        *self == TransmitMode::Multicast
+    }
+}
+
+impl fmt::Display for TransmitMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TransmitMode::Tcp => "TCP",
+            TransmitMode::Udp => "UDP",
+            TransmitMode::Multicast => "Multicast",
+            TransmitMode::MultiTcp => "Multi-TCP",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// NDI stream transport settings (BirdDog `/encodetransport` endpoint)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamTransport {
+    /// Transmit mode
+    #[serde(rename = "txpm")]
+    pub mode: TransmitMode,
+    /// Multicast address prefix (e.g. 239.255.0.0)
+    #[serde(rename = "txnetprefix")]
+    pub multicast_prefix: String,
+    /// Multicast netmask (e.g. 255.255.0.0)
+    #[serde(rename = "txnetmask")]
+    pub multicast_mask: String,
+    /// Multicast TTL (hops)
+    #[serde(rename = "txmcttl", with = "super::string_number")]
+    pub multicast_ttl: u8,
+}
+
+impl StreamTransport {
+    /// Switch between multicast and unicast (TCP), keeping the multicast settings
+    pub fn set_multicast(&mut self, enabled: bool) {
+        self.mode = if enabled {
+            TransmitMode::Multicast
+        } else {
+            TransmitMode::Tcp
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_deserialization() {
+        let json = r#"{"txpm":"Multicast","txnetprefix":"239.255.0.0","txnetmask":"255.255.0.0","txmcttl":"4"}"#;
+        let transport: StreamTransport = serde_json::from_str(json).unwrap();
+        assert!(transport.mode.is_multicast());
+        assert_eq!(transport.multicast_ttl, 4);
+
+        let json =
+            r#"{"txpm":"TCP","txnetprefix":"239.255.0.0","txnetmask":"255.255.0.0","txmcttl":1}"#;
+        let transport: StreamTransport = serde_json::from_str(json).unwrap();
+        assert_eq!(transport.mode, TransmitMode::Tcp);
+    }
+
+    #[test]
+    fn test_transport_round_trip() {
+        let mut transport = StreamTransport {
+            mode: TransmitMode::Tcp,
+            multicast_prefix: "239.255.0.0".to_string(),
+            multicast_mask: "255.255.0.0".to_string(),
+            multicast_ttl: 2,
+        };
+        transport.set_multicast(true);
+
+        let json = serde_json::to_string(&transport).unwrap();
+        assert!(json.contains(r#""txpm":"Multicast""#));
+        assert!(json.contains(r#""txmcttl":"2""#));
+    }
+}
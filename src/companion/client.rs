@@ -4,7 +4,18 @@ use super::{CompanionAction, CompanionFeedback};
 use anyhow::{Context, Result};
 use log::{debug, error, info};
 use reqwest::Client;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Result of probing the Companion server
+#[derive(Debug, Clone)]
+pub struct CompanionProbe {
+    /// Status returned for the feedback endpoint
+    pub status: reqwest::StatusCode,
+    /// Round trip of the feedback request
+    pub latency: Duration,
+    /// Version reported by the server, if any
+    pub version: Option<String>,
+}
 
 /// Client for communicating with Companion server
 pub struct CompanionClient {
@@ -74,6 +85,10 @@ impl CompanionClient {
                 layout: None,
                 routes: vec![],
                 sources: vec![],
+                maintenance: vec![],
+                blanked: false,
+                recording: None,
+                next_event: None,
             });
         }
 
@@ -151,6 +166,35 @@ impl CompanionClient {
         self.send_action(CompanionAction::RefreshSources).await
     }
 
+    /// Probe the server for diagnostics: whether it answers, how fast, and its version
+    pub async fn probe(&self) -> Result<CompanionProbe> {
+        let url = format!("{}/api/feedback", self.base_url);
+        let start = Instant::now();
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("No response from {}", self.base_url))?;
+        let latency = start.elapsed();
+        let status = response.status();
+
+        // Older servers have no version endpoint; that is not an error
+        let url = format!("{}/api/version", self.base_url);
+        let version = match self.client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => {
+                parse_version(&response.text().await.unwrap_or_default())
+            }
+            _ => None,
+        };
+
+        Ok(CompanionProbe {
+            status,
+            latency,
+            version,
+        })
+    }
+
     /// Test connection to Companion server
     pub async fn test_connection(&self) -> bool {
         if !self.enabled {
@@ -165,10 +209,34 @@ impl CompanionClient {
     }
 }
 
+/// Version from a version endpoint body: `{"version": "..."}` or plain text
+fn parse_version(body: &str) -> Option<String> {
+    let body = body.trim();
+    if let Ok(serde_json::Value::Object(json)) = serde_json::from_str(body) {
+        return json
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+    }
+    (!body.is_empty() && !body.contains('<')).then(|| body.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version(r#"{"version": "3.2.0"}"#),
+            Some("3.2.0".to_string())
+        );
+        assert_eq!(parse_version("3.2.0\n"), Some("3.2.0".to_string()));
+        assert_eq!(parse_version(r#"{"status": "ok"}"#), None);
+        assert_eq!(parse_version("<html>Not found</html>"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
     #[test]
     fn test_client_creation() {
         let client = CompanionClient::new("localhost", 8888, true);
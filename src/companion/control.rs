@@ -0,0 +1,777 @@
+//! Inbound control path: actions sent to RusTV by Companion buttons
+
+use super::{CompanionAction, NextEventFeedback, RecordingFeedback};
+use crate::birddog::ptz::PtzCommand;
+use crate::birddog::{BirdDogClient, PtzPosition};
+use crate::config::{CameraConfig, CameraScene, Config, PtzProtocol, SceneShot};
+use crate::devices::{DeviceCommand, Devices};
+use crate::events::{Event, EventCategory, EventLog, Severity};
+use crate::inputs::clip::CLIP_URL_PREFIX;
+use crate::inputs::test_pattern::TEST_PATTERN_URL_PREFIX;
+use crate::inputs::watch_folder::FILE_URL_PREFIX;
+use crate::matrix::{BackupRoute, MatrixRouter, RecallPolicy, Salvo, SalvoPreflight};
+use crate::ndi::{NdiDiscovery, NdiPtz};
+use crate::propresenter::ProPresenterClient;
+use crate::recording::MarkerRequests;
+use crate::timecode::TimecodeListener;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long a scene waits for NDI PTZ commands to reach each camera
+const SCENE_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of one camera's shot when recalling a scene
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShotResult {
+    pub shot: SceneShot,
+    pub result: Result<(), String>,
+}
+
+/// Handles actions received from Companion
+pub struct CompanionControl {
+    /// Matrix router shared with the rest of the application
+    router: Arc<Mutex<MatrixRouter>>,
+    /// Configured BirdDog cameras
+    cameras: Mutex<Vec<CameraConfig>>,
+    /// Camera targeted by PTZ actions that don't name one
+    selected_camera: Mutex<Option<String>>,
+    /// Control connections to NDI PTZ cameras, keyed by camera name
+    ndi_ptz: Mutex<HashMap<String, Arc<NdiPtz>>>,
+    /// Saved salvos that can be recalled
+    salvos: Vec<Salvo>,
+    /// What a salvo recall does with routes its preflight finds problems with
+    recall_policy: RecallPolicy,
+    /// Backup inputs that can stand in for missing salvo inputs
+    backups: Vec<BackupRoute>,
+    /// Discovered sources, checked by salvo preflights (the router's inputs if unset)
+    discovery: Option<Arc<NdiDiscovery>>,
+    /// Camera scenes that can be recalled
+    scenes: Vec<CameraScene>,
+    /// ProPresenter machine for slide actions (if configured)
+    propresenter: Option<ProPresenterClient>,
+    /// HTTP devices whose actions can be run
+    devices: Arc<Devices>,
+    /// Input outputs show while blanked (nothing if unset)
+    standby_slate: Option<String>,
+    /// Markers for the GUI's active recording (recordings need the GUI)
+    markers: Option<MarkerRequests>,
+    /// Show timecode, whose next trigger is reported as feedback
+    timecode: Option<Arc<TimecodeListener>>,
+    /// Event log recording handled actions
+    events: EventLog,
+}
+
+impl CompanionControl {
+    pub fn new(router: Arc<Mutex<MatrixRouter>>, cameras: Vec<CameraConfig>) -> Self {
+        Self {
+            router,
+            cameras: Mutex::new(cameras),
+            selected_camera: Mutex::new(None),
+            ndi_ptz: Mutex::new(HashMap::new()),
+            salvos: Vec::new(),
+            recall_policy: RecallPolicy::default(),
+            backups: Vec::new(),
+            discovery: None,
+            scenes: Vec::new(),
+            propresenter: None,
+            devices: Arc::new(Devices::new(Vec::new())),
+            standby_slate: None,
+            markers: None,
+            timecode: None,
+            events: EventLog::new(),
+        }
+    }
+
+    /// Record handled actions in the given event log
+    pub fn with_events(mut self, events: EventLog) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Allow the given salvos to be recalled
+    pub fn with_salvos(mut self, salvos: Vec<Salvo>) -> Self {
+        self.salvos = salvos;
+        self
+    }
+
+    /// Handle problems found by salvo preflights with a policy, substituting the given backups
+    pub fn with_recall_policy(mut self, policy: RecallPolicy, backups: Vec<BackupRoute>) -> Self {
+        self.recall_policy = policy;
+        self.backups = backups;
+        self
+    }
+
+    /// Check salvo inputs against the sources found by discovery
+    pub fn with_discovery(mut self, discovery: Arc<NdiDiscovery>) -> Self {
+        self.discovery = Some(discovery);
+        self
+    }
+
+    /// Allow the given camera scenes to be recalled
+    pub fn with_scenes(mut self, scenes: Vec<CameraScene>) -> Self {
+        self.scenes = scenes;
+        self
+    }
+
+    /// Allow the actions of the given HTTP devices to be run
+    pub fn with_devices(mut self, devices: Arc<Devices>) -> Self {
+        self.devices = devices;
+        self
+    }
+
+    /// Show this input on every output while blanked
+    pub fn with_standby_slate(mut self, slate: Option<String>) -> Self {
+        self.standby_slate = slate;
+        self
+    }
+
+    /// Pass marker actions to the active recording
+    pub fn with_markers(mut self, markers: MarkerRequests) -> Self {
+        self.markers = Some(markers);
+        self
+    }
+
+    /// Report the next trigger of this timecode listener
+    pub fn with_timecode(mut self, timecode: Arc<TimecodeListener>) -> Self {
+        self.timecode = Some(timecode);
+        self
+    }
+
+    /// The active recording, for feedback
+    pub fn recording(&self) -> Option<RecordingFeedback> {
+        let elapsed = self.markers.as_ref()?.recording_time()?;
+        Some(RecordingFeedback::new(elapsed))
+    }
+
+    /// The next timecode trigger, for feedback
+    pub fn next_event(&self) -> Option<NextEventFeedback> {
+        let (trigger, countdown) = self.timecode.as_ref()?.next_trigger()?;
+        Some(NextEventFeedback::new(
+            trigger.at,
+            &trigger.action,
+            countdown,
+        ))
+    }
+
+    /// HTTP devices whose actions can be run
+    pub fn devices(&self) -> &Arc<Devices> {
+        &self.devices
+    }
+
+    /// Event log handled actions are recorded in
+    pub fn events(&self) -> &EventLog {
+        &self.events
+    }
+
+    /// Configured cameras
+    pub fn cameras(&self) -> Vec<CameraConfig> {
+        self.cameras.lock().unwrap().clone()
+    }
+
+    /// Create a handler for the cameras and integrations in the configuration
+    pub fn from_config(router: Arc<Mutex<MatrixRouter>>, config: &Config) -> Self {
+        let control = Self::new(router, config.birddog.cameras.clone())
+            .with_salvos(config.matrix.salvos.clone())
+            .with_recall_policy(config.matrix.salvo_recall, config.matrix.backups.clone())
+            .with_scenes(config.birddog.scenes.clone())
+            .with_standby_slate(config.matrix.standby_slate.clone())
+            .with_devices(Arc::new(Devices::new(config.devices.clone())));
+        if config.propresenter.enabled {
+            control.with_propresenter(ProPresenterClient::new(
+                &config.propresenter.host,
+                config.propresenter.port,
+            ))
+        } else {
+            control
+        }
+    }
+
+    /// Handle slide actions with the given ProPresenter client
+    pub fn with_propresenter(mut self, client: ProPresenterClient) -> Self {
+        self.propresenter = Some(client);
+        self
+    }
+
+    fn propresenter(&self) -> Result<&ProPresenterClient> {
+        self.propresenter
+            .as_ref()
+            .context("ProPresenter integration is not enabled")
+    }
+
+    /// Get the currently selected camera name
+    pub fn selected_camera(&self) -> Option<String> {
+        self.selected_camera.lock().unwrap().clone()
+    }
+
+    /// Find a camera by name or NDI name, falling back to the selected camera
+    fn resolve_camera(&self, camera: Option<&str>) -> Result<CameraConfig> {
+        let name = match camera {
+            Some(name) => name.to_string(),
+            None => self
+                .selected_camera()
+                .context("No camera specified and no camera selected")?,
+        };
+
+        self.cameras
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|c| c.name == name || c.ndi_name == name)
+            .cloned()
+            .with_context(|| format!("Camera '{}' not found", name))
+    }
+
+    /// A camera's NDI control connection, opened on first use
+    fn ndi_ptz(&self, camera: &CameraConfig) -> Result<Arc<NdiPtz>> {
+        let mut connections = self.ndi_ptz.lock().unwrap();
+        let ptz = match connections.entry(camera.name.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(NdiPtz::connect_by_name(&camera.ndi_name)?))
+            }
+        };
+        Ok(Arc::clone(ptz))
+    }
+
+    /// Run a PTZ operation on a camera's NDI control connection
+    fn with_ndi_ptz(
+        &self,
+        camera: &CameraConfig,
+        operation: impl FnOnce(&NdiPtz) -> Result<()>,
+    ) -> Result<()> {
+        let ptz = self.ndi_ptz(camera)?;
+        operation(&ptz)
+    }
+
+    /// Send a PTZ command to a camera over its configured protocol
+    async fn send_ptz(&self, camera: &CameraConfig, command: PtzCommand) -> Result<()> {
+        match camera.ptz {
+            PtzProtocol::BirdDog => {
+                BirdDogClient::new(&camera.ip_address)
+                    .send_ptz_command(&command)
+                    .await
+            }
+            PtzProtocol::Ndi => self.with_ndi_ptz(camera, |ptz| ptz.send_ptz_command(&command)),
+        }
+    }
+
+    /// Replace the configured cameras, closing the control connections of
+    /// cameras that went away or now point at another source
+    pub fn set_cameras(&self, cameras: Vec<CameraConfig>) {
+        let mut current = self.cameras.lock().unwrap();
+        let unchanged = |name: &str| {
+            let source = |list: &[CameraConfig]| {
+                list.iter()
+                    .find(|c| c.name == name && c.ptz == PtzProtocol::Ndi)
+                    .map(|c| c.ndi_name.clone())
+            };
+            source(&current).is_some() && source(&current) == source(&cameras)
+        };
+        self.ndi_ptz
+            .lock()
+            .unwrap()
+            .retain(|name, _| unchanged(name));
+        *current = cameras;
+    }
+
+    /// Salvos that can be recalled, with their notes
+    pub fn salvos(&self) -> &[Salvo] {
+        &self.salvos
+    }
+
+    /// Check a salvo's routes against the sources and outputs that exist now
+    pub fn preflight(&self, name: &str) -> Result<SalvoPreflight> {
+        let salvo = self
+            .salvos
+            .iter()
+            .find(|s| s.name == name)
+            .with_context(|| format!("Salvo '{}' not found", name))?;
+        let router = self.router.lock().unwrap();
+        // The router remembers every input it has seen; discovery knows what is there now
+        let sources = match &self.discovery {
+            Some(discovery) => {
+                let mut sources = discovery.get_sources();
+                sources.extend(
+                    router
+                        .get_inputs()
+                        .iter()
+                        .filter(|s| {
+                            s.url.starts_with(FILE_URL_PREFIX)
+                                || s.url.starts_with(TEST_PATTERN_URL_PREFIX)
+                                || s.url.starts_with(CLIP_URL_PREFIX)
+                        })
+                        .cloned(),
+                );
+                sources
+            }
+            None => router.get_inputs().to_vec(),
+        };
+        Ok(SalvoPreflight::check(
+            salvo,
+            &sources,
+            router.get_outputs(),
+            &self.backups,
+        ))
+    }
+
+    /// Apply a salvo after its preflight, handling problems with `policy` (the
+    /// configured policy if None). Returns the preflight report
+    pub fn recall_salvo(&self, name: &str, policy: Option<RecallPolicy>) -> Result<SalvoPreflight> {
+        let preflight = self.preflight(name)?;
+        let policy = policy.unwrap_or(self.recall_policy);
+        let Some(routes) = preflight.plan(policy) else {
+            anyhow::bail!("Recall aborted. {}", preflight);
+        };
+        if !preflight.is_clean() {
+            warn!("{} ({})", preflight, policy.name());
+        }
+
+        let mut router = self.router.lock().unwrap();
+        for route in &routes {
+            Self::apply_route(&mut router, &route.input, &route.output)?;
+        }
+        drop(router);
+        self.run_device_actions(name);
+        Ok(preflight)
+    }
+
+    /// Run a salvo's device actions in the background, in order
+    fn run_device_actions(&self, salvo: &str) {
+        let Some(salvo) = self.salvos.iter().find(|s| s.name == salvo) else {
+            return;
+        };
+        if salvo.device_actions.is_empty() {
+            return;
+        }
+        let devices = Arc::clone(&self.devices);
+        let commands = salvo.device_actions.clone();
+        let events = self.events.clone();
+        let name = salvo.name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = devices.run_all(&commands).await {
+                warn!("Salvo '{}': {:#}", name, e);
+                events.record(
+                    Event::new(
+                        Severity::Warning,
+                        EventCategory::Control,
+                        format!("Salvo '{}': {:#}", name, e),
+                    )
+                    .with_actor("devices"),
+                );
+            }
+        });
+    }
+
+    /// Names of the scenes that can be recalled
+    pub fn scene_names(&self) -> Vec<String> {
+        self.scenes.iter().map(|s| s.name.clone()).collect()
+    }
+
+    /// Move every camera of a scene, reporting each camera's outcome.
+    ///
+    /// All cameras are sent their command before waiting for any NDI camera to
+    /// receive it, so they move together.
+    pub async fn recall_scene(&self, name: &str) -> Result<Vec<ShotResult>> {
+        let scene = self
+            .scenes
+            .iter()
+            .find(|s| s.name == name)
+            .with_context(|| format!("Scene '{}' not found", name))?;
+        info!("Recalling scene: {}", scene.name);
+
+        let mut results = Vec::new();
+        let mut sending = Vec::new();
+        for shot in &scene.shots {
+            let command = match shot.preset {
+                Some(preset) => PtzCommand::RecallPreset(preset),
+                None => PtzCommand::Home,
+            };
+            let result = match self.resolve_camera(Some(&shot.camera)) {
+                Ok(camera) if camera.ptz == PtzProtocol::Ndi => {
+                    self.ndi_ptz(&camera).and_then(|ptz| {
+                        ptz.send_ptz_command(&command)?;
+                        sending.push((results.len(), ptz));
+                        Ok(())
+                    })
+                }
+                Ok(camera) => self.send_ptz(&camera, command).await,
+                Err(e) => Err(e),
+            };
+            results.push(ShotResult {
+                shot: shot.clone(),
+                result: result.map_err(|e| format!("{:#}", e)),
+            });
+        }
+
+        for (idx, ptz) in sending {
+            if let Err(e) = ptz.flush(SCENE_SEND_TIMEOUT).await {
+                results[idx].result = Err(format!("{:#}", e));
+            }
+        }
+        for result in &results {
+            if let Err(e) = &result.result {
+                warn!("Scene '{}': {} failed: {}", scene.name, result.shot, e);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Route an input, keeping the route as a placeholder until the input appears
+    fn apply_route(router: &mut MatrixRouter, input: &str, output: &str) -> Result<()> {
+        if router.input_exists(input) {
+            router.route(input, output)
+        } else {
+            router.route_placeholder(input, output)
+        }
+    }
+
+    /// Blank every output for standby, showing the standby slate if one is configured
+    pub fn blank_all(&self) -> Result<()> {
+        let slate = self.standby_slate.as_deref();
+        if !self.router.lock().unwrap().blank_all(slate) {
+            anyhow::bail!("Outputs are already blanked");
+        }
+        Ok(())
+    }
+
+    /// Restore the routes from before `blank_all`
+    pub fn resume(&self) -> Result<()> {
+        if !self.router.lock().unwrap().resume() {
+            anyhow::bail!("Outputs are not blanked");
+        }
+        Ok(())
+    }
+
+    /// Handle an action received from Companion
+    #[cfg(test)]
+    pub async fn handle(&self, action: CompanionAction) -> Result<()> {
+        self.handle_from("companion", action).await
+    }
+
+    /// Handle an action, recording `actor` as its origin in the event log
+    pub async fn handle_from(&self, actor: &str, action: CompanionAction) -> Result<()> {
+        info!("Action received from {}: {:?}", actor, action);
+        let category = match action {
+            CompanionAction::Route { .. }
+            | CompanionAction::Unroute { .. }
+            | CompanionAction::RecallSalvo { .. }
+            | CompanionAction::SetMaintenance { .. }
+            | CompanionAction::BlankAll
+            | CompanionAction::Resume => EventCategory::Routing,
+            CompanionAction::SelectCamera { .. }
+            | CompanionAction::RecallPreset { .. }
+            | CompanionAction::RecallScene { .. }
+            | CompanionAction::Move { .. }
+            | CompanionAction::MoveSpeed { .. }
+            | CompanionAction::Stop { .. } => EventCategory::Camera,
+            _ => EventCategory::Control,
+        };
+        let description = format!("{:?}", action);
+
+        match action {
+            CompanionAction::SelectCamera { camera } => {
+                let name = self.resolve_camera(Some(&camera))?.name.clone();
+                info!("Selected camera: {}", name);
+                *self.selected_camera.lock().unwrap() = Some(name);
+            }
+            CompanionAction::RecallPreset { camera, preset } => {
+                let camera = self.resolve_camera(camera.as_deref())?;
+                self.send_ptz(&camera, PtzCommand::RecallPreset(preset))
+                    .await?;
+            }
+            CompanionAction::RecallScene { scene } => {
+                let failed: Vec<String> = self
+                    .recall_scene(&scene)
+                    .await?
+                    .into_iter()
+                    .filter_map(|r| Some(format!("{}: {}", r.shot.camera, r.result.err()?)))
+                    .collect();
+                if !failed.is_empty() {
+                    anyhow::bail!("Scene '{}' incomplete ({})", scene, failed.join("; "));
+                }
+            }
+            CompanionAction::Move {
+                camera,
+                pan,
+                tilt,
+                zoom,
+            } => {
+                let camera = self.resolve_camera(camera.as_deref())?;
+                let position = PtzPosition::new(pan, tilt, zoom);
+                self.send_ptz(&camera, PtzCommand::MoveAbsolute(position))
+                    .await?;
+            }
+            CompanionAction::MoveSpeed {
+                camera,
+                pan,
+                tilt,
+                zoom,
+            } => {
+                let camera = self.resolve_camera(camera.as_deref())?;
+                if camera.ptz != PtzProtocol::Ndi {
+                    anyhow::bail!(
+                        "Speed moves need an NDI PTZ camera ({} is BirdDog)",
+                        camera.name
+                    );
+                }
+                self.with_ndi_ptz(&camera, |ptz| ptz.move_speed(pan, tilt, zoom))?;
+            }
+            CompanionAction::Stop { camera } => {
+                let camera = self.resolve_camera(camera.as_deref())?;
+                self.send_ptz(&camera, PtzCommand::Stop).await?;
+            }
+            CompanionAction::Route { input, output } => {
+                Self::apply_route(&mut self.router.lock().unwrap(), &input, &output)?;
+            }
+            CompanionAction::RecallSalvo { salvo, policy } => {
+                self.recall_salvo(&salvo, policy)?;
+            }
+            CompanionAction::Unroute { output } => {
+                self.router.lock().unwrap().unroute(&output);
+            }
+            CompanionAction::SetMaintenance { input, enabled } => {
+                self.router.lock().unwrap().set_maintenance(&input, enabled);
+            }
+            CompanionAction::BlankAll => self.blank_all()?,
+            CompanionAction::Resume => self.resume()?,
+            CompanionAction::DeviceAction {
+                device,
+                action,
+                params,
+            } => {
+                self.devices
+                    .run(&DeviceCommand {
+                        device,
+                        action,
+                        params,
+                    })
+                    .await?;
+            }
+            CompanionAction::AddMarker { label } => {
+                self.markers
+                    .as_ref()
+                    .context("Recording is only available in the GUI")?
+                    .request(label)?;
+            }
+            CompanionAction::NextSlide => self.propresenter()?.next_slide().await?,
+            CompanionAction::PreviousSlide => self.propresenter()?.previous_slide().await?,
+            other => anyhow::bail!("Action not handled by RusTV: {:?}", other),
+        }
+
+        self.events.info(category, actor, description);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn control() -> CompanionControl {
+        let mut router = MatrixRouter::new();
+        router.add_output("Program".to_string());
+
+        let cameras = vec![CameraConfig {
+            name: "Camera 1".to_string(),
+            ip_address: "192.168.1.101".to_string(),
+            ndi_name: "BirdDog-CAM1".to_string(),
+            ptz: PtzProtocol::BirdDog,
+        }];
+
+        CompanionControl::new(Arc::new(Mutex::new(router)), cameras)
+    }
+
+    #[tokio::test]
+    async fn test_select_camera() {
+        let control = control();
+        assert!(control.resolve_camera(None).is_err());
+
+        control
+            .handle(CompanionAction::SelectCamera {
+                camera: "BirdDog-CAM1".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(control.selected_camera(), Some("Camera 1".to_string()));
+        assert_eq!(control.resolve_camera(None).unwrap().name, "Camera 1");
+
+        assert!(control
+            .handle(CompanionAction::SelectCamera {
+                camera: "Unknown".to_string(),
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_route_actions() {
+        let control = control();
+        control
+            .handle(CompanionAction::Route {
+                input: "Camera 1".to_string(),
+                output: "Program".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            control.router.lock().unwrap().get_route("Program"),
+            Some(&"Camera 1".to_string())
+        );
+
+        control
+            .handle(CompanionAction::Unroute {
+                output: "Program".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(control.router.lock().unwrap().get_route("Program"), None);
+
+        let events = control.events.recent();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].category, EventCategory::Routing);
+        assert_eq!(events[0].actor.as_deref(), Some("companion"));
+    }
+
+    #[tokio::test]
+    async fn test_recall_salvo() {
+        let control = control().with_salvos(vec![Salvo::new(
+            "Service".to_string(),
+            vec![crate::matrix::Route::new(
+                "Camera 1".to_string(),
+                "Program".to_string(),
+            )],
+        )]);
+        control
+            .router
+            .lock()
+            .unwrap()
+            .add_input(crate::ndi::NdiSource::new(
+                "Camera 1".to_string(),
+                "ndi://camera1".to_string(),
+            ));
+
+        control
+            .handle(CompanionAction::RecallSalvo {
+                salvo: "Service".to_string(),
+                policy: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            control.router.lock().unwrap().get_route("Program"),
+            Some(&"Camera 1".to_string())
+        );
+
+        assert!(control
+            .handle(CompanionAction::RecallSalvo {
+                salvo: "Unknown".to_string(),
+                policy: None,
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recall_salvo_with_missing_input() {
+        let control = control().with_salvos(vec![Salvo::new(
+            "Service".to_string(),
+            vec![crate::matrix::Route::new(
+                "Camera 9".to_string(),
+                "Program".to_string(),
+            )],
+        )]);
+        let recall = |policy| {
+            control
+                .recall_salvo("Service", Some(policy))
+                .map(|_| control.router.lock().unwrap().get_route("Program").cloned())
+        };
+
+        assert!(recall(RecallPolicy::Abort).is_err());
+        // The output keeps what it had rather than going blank
+        assert_eq!(recall(RecallPolicy::Partial).unwrap(), None);
+        assert_eq!(
+            recall(RecallPolicy::Force).unwrap(),
+            Some("Camera 9".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recall_scene() {
+        let control = control().with_scenes(vec![CameraScene {
+            name: "Worship look".to_string(),
+            shots: vec![SceneShot {
+                camera: "Camera 9".to_string(),
+                preset: Some(2),
+            }],
+        }]);
+        assert_eq!(control.scene_names(), vec!["Worship look"]);
+
+        // Each camera reports on its own
+        let results = control.recall_scene("Worship look").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0]
+            .result
+            .as_ref()
+            .unwrap_err()
+            .contains("not found"));
+
+        assert!(control
+            .handle(CompanionAction::RecallScene {
+                scene: "Worship look".to_string(),
+            })
+            .await
+            .is_err());
+        assert!(control.recall_scene("Unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unhandled_action() {
+        let control = control();
+        assert!(control
+            .handle(CompanionAction::RefreshSources)
+            .await
+            .is_err());
+        assert!(control.handle(CompanionAction::NextSlide).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blank_all() {
+        let control = control().with_standby_slate(Some("Goodnight".to_string()));
+        control
+            .handle(CompanionAction::Route {
+                input: "Camera 1".to_string(),
+                output: "Program".to_string(),
+            })
+            .await
+            .unwrap();
+
+        control.handle(CompanionAction::BlankAll).await.unwrap();
+        assert!(control.handle(CompanionAction::BlankAll).await.is_err());
+        let route = || control.router.lock().unwrap().get_route("Program").cloned();
+        assert_eq!(route().as_deref(), Some("Goodnight"));
+
+        control.handle(CompanionAction::Resume).await.unwrap();
+        assert_eq!(route().as_deref(), Some("Camera 1"));
+        assert!(control.handle(CompanionAction::Resume).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_marker() {
+        let marker = || CompanionAction::AddMarker {
+            label: Some("Sermon".to_string()),
+        };
+        // Headless: nothing records
+        assert!(control().handle(marker()).await.is_err());
+
+        let markers = MarkerRequests::new();
+        let control = control().with_markers(markers.clone());
+        assert!(control.handle(marker()).await.is_err());
+        markers.set_recording(true);
+        control.handle(marker()).await.unwrap();
+        assert_eq!(markers.take(), vec![Some("Sermon".to_string())]);
+    }
+}
@@ -4,10 +4,16 @@
 //! which enhances the usability of streamdecks and other control surfaces.
 
 mod client;
+mod control;
 
 pub use client::CompanionClient;
+pub use control::{CompanionControl, ShotResult};
 
+use crate::matrix::RecallPolicy;
+use crate::timecode::Timecode;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 /// Action types that can be sent to Companion
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +33,86 @@ pub enum CompanionAction {
     SetButtonText { page: u8, bank: u8, text: String },
     /// Set button color
     SetButtonColor { page: u8, bank: u8, color: String },
+    /// Select the camera targeted by PTZ actions without an explicit camera
+    SelectCamera { camera: String },
+    /// Recall a PTZ preset (on the selected camera if none is given)
+    RecallPreset { camera: Option<String>, preset: u8 },
+    /// Move a camera to an absolute PTZ position
+    Move {
+        camera: Option<String>,
+        pan: f64,
+        tilt: f64,
+        zoom: f64,
+    },
+    /// Pan, tilt and zoom a camera at speeds (-1.0 to 1.0; zeros stop). NDI PTZ cameras only
+    MoveSpeed {
+        camera: Option<String>,
+        pan: f64,
+        tilt: f64,
+        zoom: f64,
+    },
+    /// Stop camera movement
+    Stop { camera: Option<String> },
+    /// Advance ProPresenter to the next slide
+    NextSlide,
+    /// Go back to the previous ProPresenter slide
+    PreviousSlide,
+    /// Apply the routes of a saved salvo. Routes its preflight finds problems with
+    /// are handled by `policy` (`[matrix] salvo_recall` if not given)
+    RecallSalvo {
+        salvo: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        policy: Option<RecallPolicy>,
+    },
+    /// Move every camera of a scene to its preset
+    RecallScene { scene: String },
+    /// Put an input into or out of maintenance (its outputs show the substitute)
+    SetMaintenance { input: String, enabled: bool },
+    /// Run a named action of an HTTP device (`[[devices]]`)
+    DeviceAction {
+        device: String,
+        action: String,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        params: BTreeMap<String, String>,
+    },
+    /// Blank every output (standby), remembering the routing table
+    BlankAll,
+    /// Restore the routing table from before `BlankAll`
+    Resume,
+    /// Drop a marker into the active recording (numbered if no label is given)
+    AddMarker {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+}
+
+impl CompanionAction {
+    /// Short description for button text (e.g. "Salvo PM")
+    pub fn summary(&self) -> String {
+        match self {
+            CompanionAction::SetLayout { layout } => format!("Layout {}", layout),
+            CompanionAction::Route { input, output } => format!("{} → {}", input, output),
+            CompanionAction::Unroute { output } => format!("Clear {}", output),
+            CompanionAction::RecallPreset { preset, .. } => format!("Preset {}", preset),
+            CompanionAction::RecallSalvo { salvo, .. } => format!("Salvo {}", salvo),
+            CompanionAction::RecallScene { scene } => format!("Scene {}", scene),
+            CompanionAction::DeviceAction { device, action, .. } => {
+                format!("{} {}", device, action)
+            }
+            CompanionAction::BlankAll => "Blank All".to_string(),
+            CompanionAction::NextSlide => "Next slide".to_string(),
+            CompanionAction::PreviousSlide => "Previous slide".to_string(),
+            CompanionAction::AddMarker { label } => match label {
+                Some(label) => format!("Marker {}", label),
+                None => "Marker".to_string(),
+            },
+            // The action's type name
+            other => serde_json::to_value(other)
+                .ok()
+                .and_then(|v| v["type"].as_str().map(String::from))
+                .unwrap_or_default(),
+        }
+    }
 }
 
 /// Feedback from Companion
@@ -38,6 +124,64 @@ pub struct CompanionFeedback {
     pub routes: Vec<CompanionRoute>,
     /// Available sources
     pub sources: Vec<String>,
+    /// Inputs under maintenance
+    #[serde(default)]
+    pub maintenance: Vec<String>,
+    /// Whether every output is blanked for standby
+    #[serde(default)]
+    pub blanked: bool,
+    /// The GUI's active recording
+    #[serde(default)]
+    pub recording: Option<RecordingFeedback>,
+    /// The next timecode trigger
+    #[serde(default)]
+    pub next_event: Option<NextEventFeedback>,
+}
+
+/// A running recording, for a button showing "REC 00:41:12"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingFeedback {
+    pub elapsed_secs: u64,
+    pub text: String,
+}
+
+impl RecordingFeedback {
+    pub fn new(elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs();
+        Self {
+            elapsed_secs: secs,
+            text: format!(
+                "REC {:02}:{:02}:{:02}",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60
+            ),
+        }
+    }
+}
+
+/// The next timecode trigger, for a button showing "Next: Salvo PM 17:55"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NextEventFeedback {
+    /// Timecode the trigger fires at
+    pub at: Timecode,
+    /// What it does
+    pub action: String,
+    /// Seconds until timecode reaches it
+    pub countdown_secs: u64,
+    pub text: String,
+}
+
+impl NextEventFeedback {
+    pub fn new(at: Timecode, action: &CompanionAction, countdown: Duration) -> Self {
+        let action = action.summary();
+        Self {
+            at,
+            text: format!("Next: {} {:02}:{:02}", action, at.hours, at.minutes),
+            action,
+            countdown_secs: countdown.as_secs(),
+        }
+    }
 }
 
 /// Route information for Companion
@@ -60,6 +204,19 @@ mod tests {
         assert!(json.contains("SetLayout"));
     }
 
+    #[test]
+    fn test_ptz_action_deserialization() {
+        let action: CompanionAction =
+            serde_json::from_str(r#"{"type":"RecallPreset","preset":3}"#).unwrap();
+        match action {
+            CompanionAction::RecallPreset { camera, preset } => {
+                assert_eq!(camera, None);
+                assert_eq!(preset, 3);
+            }
+            other => panic!("Unexpected action: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_companion_route() {
         let route = CompanionRoute {
@@ -69,4 +226,23 @@ mod tests {
         assert_eq!(route.input, "Camera 1");
         assert_eq!(route.output, "Monitor 1");
     }
+
+    #[test]
+    fn test_schedule_feedback() {
+        let recording = RecordingFeedback::new(Duration::from_secs(41 * 60 + 12));
+        assert_eq!(recording.text, "REC 00:41:12");
+
+        let action = CompanionAction::RecallSalvo {
+            salvo: "PM".to_string(),
+            policy: None,
+        };
+        let next = NextEventFeedback::new(
+            "17:55:00:00".parse().unwrap(),
+            &action,
+            Duration::from_secs(90),
+        );
+        assert_eq!(next.text, "Next: Salvo PM 17:55");
+        assert_eq!(next.countdown_secs, 90);
+        assert_eq!(CompanionAction::Resume.summary(), "Resume");
+    }
 }
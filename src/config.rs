@@ -1,9 +1,21 @@
-use crate::gui::layouts::Layout;
-use crate::matrix::Route;
+use crate::audio::{ChannelMap, InputTrim};
+use crate::companion::CompanionAction;
+use crate::devices::DeviceConfig;
+use crate::gui::layouts::{Layout, SlotRole};
+use crate::gui::power::DisplayPower;
+use crate::inputs::TestPattern;
+use crate::matrix::{BackupRoute, OutputMirror, RebindPolicy, RecallPolicy, Route, Salvo};
+use crate::ndi::{FrameSyncConfig, NdiTransport, ReceiveBandwidth, ReceiverTuning, SourceFilter};
+use crate::timecode::Timecode;
+use crate::video::framerate::{FrameRate, RateConversion};
+use crate::video::hx::HwDecoder;
+use crate::video::transition::RouteTransition;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +32,60 @@ pub struct Config {
     /// Companion integration settings
     #[serde(default)]
     pub companion: CompanionConfig,
+    /// Operator talkback settings
+    #[serde(default)]
+    pub talkback: TalkbackConfig,
+    /// Intercom chat settings
+    #[serde(default)]
+    pub intercom: IntercomConfig,
+    /// Recording settings
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    /// Graphics watch folder settings
+    #[serde(default)]
+    pub watch_folder: WatchFolderConfig,
+    /// Built-in test pattern input
+    #[serde(default)]
+    pub test_pattern: TestPatternConfig,
+    /// Video files played as inputs
+    #[serde(default)]
+    pub clips: Vec<ClipConfig>,
+    /// Control API settings
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// ProPresenter integration settings
+    #[serde(default)]
+    pub propresenter: ProPresenterConfig,
+    /// AES67/Dante audio stream discovery settings
+    #[serde(default)]
+    pub audio_sources: AudioSourcesConfig,
+    /// Event and audit output settings
+    #[serde(default)]
+    pub events: EventsConfig,
+    /// MQTT / Home Assistant integration settings
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    /// Source audio monitoring settings
+    #[serde(default)]
+    pub audio_monitor: AudioMonitorConfig,
+    /// Show timecode and timecode-triggered actions
+    #[serde(default)]
+    pub timecode: TimecodeConfig,
+    /// Pre-show self-test
+    #[serde(default)]
+    pub selftest: SelfTestConfig,
+    /// Persistence of routes, salvos and events
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// Hot-standby pairing with another daemon
+    #[serde(default)]
+    pub pairing: PairingConfig,
+    /// Stream health alert thresholds
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    /// Simple devices controlled over HTTP
+    #[serde(default)]
+    pub devices: Vec<DeviceConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,12 +96,178 @@ pub struct NdiConfig {
     pub discovery_interval: u64,
     /// Static sources (if any)
     pub static_sources: Vec<StaticSource>,
+    /// NDI groups to search (the "Public" group if empty)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Extra sender IPs to query, for networks where mDNS does not reach
+    #[serde(default)]
+    pub extra_ips: Vec<String>,
+    /// NDI Discovery Server ("host" or "host:port"), used instead of mDNS
+    #[serde(default)]
+    pub discovery_server: Option<String>,
+    /// Latency settings of every receiver (slot settings take precedence)
+    #[serde(default)]
+    pub receive: ReceiverTuning,
+    /// Align frames from every receiver to one output cadence
+    #[serde(default)]
+    pub frame_sync: FrameSyncConfig,
+    /// How discovery speeds up and slows down with operator activity
+    #[serde(default)]
+    pub scan: ScanPolicy,
+    /// Friendly display names by NDI source name (e.g. "STUDIO-PC (OBS Output 3)" = "Slides")
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// Sources re-published as new NDI senders
+    #[serde(default)]
+    pub proxies: Vec<ProxyConfig>,
+    /// Remote sites whose sources arrive through NDI Bridge
+    #[serde(default)]
+    pub bridges: Vec<BridgeConfig>,
+    /// Quarantine of newly discovered sources until an admin approves them
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+    /// Decoder for NDI|HX video (`hx` feature): auto, software, nvdec, vaapi or videotoolbox
+    #[serde(default)]
+    pub hx_decoder: HwDecoder,
+    /// How receivers ask senders to deliver streams: auto, multicast, tcp or rudp
+    #[serde(default)]
+    pub transport: NdiTransport,
+    /// Transport overrides by NDI source name ("auto" uses `transport`)
+    #[serde(default)]
+    pub source_transports: BTreeMap<String, NdiTransport>,
+    /// Networks whose copy of a source is kept when a dual-homed sender announces
+    /// it on several interfaces, most preferred first (e.g. "10.10.0.0/16")
+    #[serde(default)]
+    pub interface_preference: Vec<String>,
+    /// Sources (names, aliases or patterns like "CAM-*") whose reported tally is
+    /// watched even while nothing here shows them, e.g. cameras on another switcher
+    #[serde(default)]
+    pub remote_tally: Vec<String>,
+    /// Tags by source name or pattern (e.g. "CAM-*" = ["camera"]), for filtering
+    /// the routing panel
+    #[serde(default)]
+    pub tags: BTreeMap<String, Vec<String>>,
+    /// Audio channels used by NDI source name: "3/4" takes channels 3 and 4,
+    /// "5.1" downmixes 5.1 to stereo (every channel as sent if not listed)
+    #[serde(default)]
+    pub channel_maps: BTreeMap<String, ChannelMap>,
+    /// Audio gain and line-up level by NDI source name, applied before metering
+    /// and monitoring (e.g. reference_dbfs = 2.0 for a source 20 dB hot)
+    #[serde(default)]
+    pub audio_trims: BTreeMap<String, InputTrim>,
+}
+
+/// Sources that may be routed when approval is required. Static sources are
+/// always approved, since they are configured by an admin already
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    /// Quarantine discovered sources (listed but not routable) until approved
+    #[serde(default)]
+    pub required: bool,
+    /// NDI names of approved sources
+    #[serde(default)]
+    pub approved: BTreeSet<String>,
+    /// PIN asked for before a source is approved in the GUI (no prompt if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_pin: Option<String>,
+}
+
+impl ApprovalConfig {
+    /// Whether a source (by NDI name) may be routed
+    pub fn is_approved(&self, name: &str) -> bool {
+        !self.required || self.approved.contains(name)
+    }
+}
+
+/// A source received and re-published under a new NDI name, e.g. for a network
+/// segment that can't carry the original stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Source to re-publish (NDI name, URL or alias)
+    pub source: String,
+    /// NDI name of the new sender ("<source> Proxy" if unset)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Scale video down to at most this many lines (original size if unset)
+    #[serde(default)]
+    pub max_height: Option<u32>,
+    /// How much of the source to pull
+    #[serde(default)]
+    pub bandwidth: ReceiveBandwidth,
+    /// Send at this fixed frame rate ("25", "29.97", "30000/1001"), whatever the
+    /// source's rate (the source's rate if unset)
+    #[serde(default)]
+    pub frame_rate: Option<FrameRate>,
+    /// How frames are made for the fixed rate: drop (drop or repeat frames) or blend
+    #[serde(default)]
+    pub rate_conversion: RateConversion,
+    /// `source` names a matrix output: re-publish whatever is routed to it,
+    /// switching inputs on a frame boundary
+    #[serde(default)]
+    pub output: bool,
+    /// Frame sent when the routed input changes: cut, black or crossfade
+    #[serde(default)]
+    pub transition: RouteTransition,
+    /// Start with RusTV (otherwise only when started through the API)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// A remote site connected through NDI Bridge. The Bridge Join publishes the
+/// site's sources on the local network, where discovery lists them with the site
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    /// Site name shown with its sources
+    pub name: String,
+    /// NDI Bridge Host at the remote site
+    pub host: String,
+    #[serde(default = "default_bridge_port")]
+    pub port: u16,
+    /// Encryption key shared with the Bridge Host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_key: Option<String>,
+    /// IP address of the machine running the Bridge Join, where the site's sources
+    /// are published
+    pub join_address: String,
+    /// Command starting the Bridge Join on this machine, kept running while RusTV
+    /// runs; `{name}`, `{host}`, `{port}` and `{key}` are filled in
+    #[serde(default)]
+    pub join_command: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// NDI name the proxy publishes under
+    pub fn sender_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{} Proxy", self.source))
+    }
+}
+
+/// Discovery pacing: `discovery_interval` applies between the fast and idle rates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanPolicy {
+    /// Adapt the scan rate to activity (always `discovery_interval` if false)
+    #[serde(default = "default_true")]
+    pub adaptive: bool,
+    /// Seconds between scans while the routing panel is open or sources are changing
+    #[serde(default = "default_scan_fast_interval")]
+    pub fast_interval: u64,
+    /// Seconds between scans once idle
+    #[serde(default = "default_scan_idle_interval")]
+    pub idle_interval: u64,
+    /// Seconds without operator input or source changes before discovery idles
+    #[serde(default = "default_scan_idle_after")]
+    pub idle_after: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StaticSource {
     pub name: String,
     pub url: String,
+    /// Receive bandwidth for this source (slot settings take precedence)
+    #[serde(default)]
+    pub bandwidth: ReceiveBandwidth,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,19 +276,106 @@ pub struct MatrixConfig {
     pub outputs: Vec<String>,
     /// Saved routes
     pub routes: Vec<Route>,
+    /// Saved salvos
+    #[serde(default)]
+    pub salvos: Vec<Salvo>,
+    /// Outputs whose input is on air (program tally)
+    #[serde(default)]
+    pub program_outputs: Vec<String>,
+    /// Outputs whose input is up next (preview tally)
+    #[serde(default)]
+    pub preview_outputs: Vec<String>,
+    /// Input shown instead of inputs under maintenance (built-in bars if unset)
+    #[serde(default)]
+    pub maintenance_substitute: Option<String>,
+    /// Input every output shows while blanked for standby (nothing if unset)
+    #[serde(default)]
+    pub standby_slate: Option<String>,
+    /// Backup inputs that take over outputs whose source stops delivering
+    #[serde(default)]
+    pub backups: Vec<BackupRoute>,
+    /// Outputs that always show whatever is routed to another output
+    #[serde(default)]
+    pub mirrors: Vec<OutputMirror>,
+    /// What a salvo recall does when some of its inputs or outputs are missing
+    /// (abort, partial, substitute_backups or force)
+    #[serde(default)]
+    pub salvo_recall: RecallPolicy,
+    /// What a wildcard route ("*Slides*") does when the source it bound to goes
+    /// away: stay on it (sticky) or move to another match (follow)
+    #[serde(default)]
+    pub wildcard_rebind: RebindPolicy,
+    /// Advertise every output as an NDI routing destination ("RusTV <output>")
+    #[serde(default)]
+    pub ndi_routing: bool,
+    /// Keep routes made in the GUI, CLI or API across restarts
+    #[serde(default = "default_true")]
+    pub persist_routes: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BirdDogConfig {
     /// BirdDog camera configurations
     pub cameras: Vec<CameraConfig>,
+    /// Looks that recall a preset on several cameras at once
+    #[serde(default)]
+    pub scenes: Vec<CameraScene>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A look made of one position per camera, e.g. "Worship look"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraScene {
+    pub name: String,
+    pub shots: Vec<SceneShot>,
+}
+
+/// One camera's part in a scene
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneShot {
+    /// Camera name or NDI name
+    pub camera: String,
+    /// Preset to recall (the home position if unset)
+    #[serde(default)]
+    pub preset: Option<u8>,
+}
+
+impl fmt::Display for SceneShot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.preset {
+            Some(preset) => write!(f, "{} preset {}", self.camera, preset),
+            None => write!(f, "{} home", self.camera),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CameraConfig {
     pub name: String,
+    /// BirdDog API address (unused by NDI PTZ cameras)
+    #[serde(default)]
     pub ip_address: String,
     pub ndi_name: String,
+    /// How PTZ commands reach the camera
+    #[serde(default)]
+    pub ptz: PtzProtocol,
+}
+
+impl CameraConfig {
+    /// Whether the camera has the BirdDog REST API (status, transport, etc.)
+    pub fn is_birddog(&self) -> bool {
+        self.ptz == PtzProtocol::BirdDog
+    }
+}
+
+/// PTZ control protocol of a camera
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PtzProtocol {
+    /// BirdDog REST API at `ip_address`
+    #[default]
+    BirdDog,
+    /// Standard NDI PTZ metadata sent to the `ndi_name` source (any NDI PTZ camera)
+    Ndi,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,12 +383,89 @@ pub struct GuiConfig {
     /// Default layout to use on startup
     #[serde(default)]
     pub default_layout: Layout,
+    /// Switch to the smallest layout showing every routed output when routes change
+    #[serde(default)]
+    pub auto_layout: bool,
     /// Window width
     #[serde(default = "default_window_width")]
     pub window_width: f32,
     /// Window height
     #[serde(default = "default_window_height")]
     pub window_height: f32,
+    /// Start in monitoring wall mode (show every discovered source)
+    #[serde(default)]
+    pub wall_mode: bool,
+    /// Show each slot's measured source latency in its corner
+    #[serde(default)]
+    pub show_latency: bool,
+    /// Background of empty slots and around video that doesn't fill its slot
+    #[serde(default)]
+    pub slot_background: SlotBackground,
+    /// Slot background color ("#RRGGBB"), also the base of the checkerboard and logo
+    #[serde(default = "default_slot_color")]
+    pub slot_color: String,
+    /// Logo image shown by the "image" slot background
+    #[serde(default)]
+    pub slot_image: Option<PathBuf>,
+    /// Receive bandwidth of each slot, keyed by output (full if not listed)
+    #[serde(default)]
+    pub slot_bandwidth: HashMap<String, ReceiveBandwidth>,
+    /// Latency settings of each slot, keyed by output (the NDI defaults if not listed)
+    #[serde(default)]
+    pub slot_latency: HashMap<String, ReceiverTuning>,
+    /// Video delay of each slot in frames, keyed by output, to line up sources
+    /// arriving with different latencies
+    #[serde(default)]
+    pub slot_delay: HashMap<String, u32>,
+    /// Outputs whose slots show the captions or subtitles their source sends as metadata
+    #[serde(default)]
+    pub slot_captions: Vec<String>,
+    /// Role of each slot, keyed by output: program, preview or auxN. Outputs keep their
+    /// role's window when the layout changes
+    #[serde(default)]
+    pub slot_roles: HashMap<String, SlotRole>,
+    /// Highest frame rate shown in thumbnail-sized slots (0 shows every frame)
+    #[serde(default)]
+    pub thumbnail_fps: u32,
+    /// Slots shorter than this (in points) count as thumbnails
+    #[serde(default = "default_thumbnail_height")]
+    pub thumbnail_height: f32,
+    /// Saved routing panel filters (e.g. "Cameras only" = sources tagged "camera")
+    #[serde(default)]
+    pub source_filters: Vec<SourceFilter>,
+    /// Fullscreen windows showing single outputs on other displays
+    #[serde(default)]
+    pub output_windows: Vec<OutputWindowConfig>,
+    /// Start in training mode (routing only changes the local preview)
+    #[serde(default)]
+    pub training_mode: bool,
+    /// Operator profiles file, kept apart from this venue config
+    #[serde(default = "default_profiles_file")]
+    pub profiles_file: PathBuf,
+    /// Help file with venue tooltips and tour (built-in help if unset)
+    #[serde(default)]
+    pub help_file: Option<PathBuf>,
+}
+
+/// An output shown fullscreen on a display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputWindowConfig {
+    pub output: String,
+    /// Display to place it on: EDID id ("DEL-A0B1-5ZKQ2V3"), EDID model name or connector ("HDMI-1")
+    pub display: String,
+    /// Put the display to sleep while every output is blanked: none, ddc or dpms
+    #[serde(default)]
+    pub power: DisplayPower,
+}
+
+/// Slot background style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlotBackground {
+    #[default]
+    Color,
+    Checkerboard,
+    Image,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +481,407 @@ pub struct CompanionConfig {
     pub port: u16,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TalkbackConfig {
+    /// Enable operator talkback
+    #[serde(default)]
+    pub enabled: bool,
+    /// Key held to talk (egui key name, e.g. "T" or "Space")
+    #[serde(default = "default_push_to_talk_key")]
+    pub push_to_talk_key: String,
+    /// Microphone device name (system default if unset)
+    #[serde(default)]
+    pub input_device: Option<String>,
+    /// Talkback destinations
+    #[serde(default)]
+    pub destinations: Vec<TalkbackDestinationConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TalkbackDestinationConfig {
+    /// Display name of the destination
+    pub name: String,
+    /// How audio is delivered to the destination
+    #[serde(default)]
+    pub kind: TalkbackKind,
+    /// Name of the NDI sender published for an `ndi` destination, BirdDog
+    /// camera IP address, or the operator of an intercom station
+    pub target: String,
+}
+
+/// Delivery path for talkback audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TalkbackKind {
+    /// Publish an NDI sender named by the target for the destination to receive
+    #[default]
+    Ndi,
+    /// Publish an NDI sender and point the BirdDog camera's audio return at it
+    BirdDog,
+    /// Play on the intercom station of the operator named by the target
+    Intercom,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntercomConfig {
+    /// Enable intercom chat between RusTV stations
+    #[serde(default)]
+    pub enabled: bool,
+    /// Operator/station name shown to peers
+    #[serde(default = "default_intercom_operator")]
+    pub operator: String,
+    /// Control API of the station hosting the partyline ("host:port"); this
+    /// station hosts it on its own control API if unset
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Chime when a cue or mention arrives (with the `audio` feature)
+    #[serde(default = "default_true")]
+    pub alert_sound: bool,
+}
+
+impl IntercomConfig {
+    /// Whether this station hosts the partyline
+    pub fn hosts(&self) -> bool {
+        self.enabled && self.server.is_none()
+    }
+
+    /// Control API the station connects to: the host's, or its own
+    pub fn server_address(&self, api: &ApiConfig) -> String {
+        self.server
+            .clone()
+            .unwrap_or_else(|| format!("127.0.0.1:{}", api.port))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    /// Directory recordings are written to
+    #[serde(default = "default_recording_directory")]
+    pub directory: PathBuf,
+    /// Capture rate for composite/output recordings (frames per second)
+    #[serde(default = "default_recording_fps")]
+    pub fps: u32,
+    /// How composite/output recordings are written
+    #[serde(default)]
+    pub format: RecordingFormat,
+}
+
+/// Output format of composite/output recordings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    /// H.264 in a Matroska file, encoded by `ffmpeg` from the PATH
+    #[default]
+    Mkv,
+    /// One PNG file per frame
+    Png,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchFolderConfig {
+    /// Turn image files in the directory into routable inputs
+    #[serde(default)]
+    pub enabled: bool,
+    /// Directory to watch
+    #[serde(default = "default_watch_folder_directory")]
+    pub directory: PathBuf,
+    /// Scan interval in seconds
+    #[serde(default = "default_watch_folder_interval")]
+    pub scan_interval: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestPatternConfig {
+    /// List "RusTV Test Pattern" among the sources
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// What it shows (other patterns can be routed as `pattern://<name>`)
+    #[serde(default)]
+    pub pattern: TestPattern,
+}
+
+/// A video file played as an input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipConfig {
+    /// Name listed with the sources (routed as `clip://<name>`)
+    pub name: String,
+    /// Video file to play
+    pub path: PathBuf,
+    /// Play the file over and over (otherwise once, holding the last frame)
+    #[serde(default, rename = "loop")]
+    pub looping: bool,
+    /// Decode at most this many lines
+    #[serde(default = "default_clip_max_height")]
+    pub max_height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Enable the HTTP control API (used for inbound Companion actions)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Port the API listens on
+    #[serde(default = "default_api_port")]
+    pub port: u16,
+    /// Allow outputs, cameras and aliases to be edited through the API
+    #[serde(default)]
+    pub config_editing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProPresenterConfig {
+    /// Enable ProPresenter integration
+    #[serde(default)]
+    pub enabled: bool,
+    /// ProPresenter machine host
+    #[serde(default = "default_propresenter_host")]
+    pub host: String,
+    /// ProPresenter network API port
+    #[serde(default = "default_propresenter_port")]
+    pub port: u16,
+    /// Source carrying the ProPresenter output, labelled with the current slide
+    #[serde(default = "default_propresenter_source")]
+    pub slides_source: String,
+    /// Poll interval in milliseconds
+    #[serde(default = "default_propresenter_poll_interval")]
+    pub poll_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioSourcesConfig {
+    /// Listen for AES67/Dante stream announcements (SAP)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds a stream stays listed without being re-announced
+    #[serde(default = "default_audio_stream_timeout")]
+    pub stream_timeout: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EventsConfig {
+    /// Forward events to a syslog server
+    #[serde(default)]
+    pub syslog: SyslogConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyslogConfig {
+    /// Enable syslog output
+    #[serde(default)]
+    pub enabled: bool,
+    /// Syslog server (host:port)
+    #[serde(default = "default_syslog_server")]
+    pub server: String,
+    /// Transport protocol
+    #[serde(default)]
+    pub protocol: SyslogProtocol,
+    /// Syslog facility (user, daemon, local0-local7)
+    #[serde(default = "default_syslog_facility")]
+    pub facility: String,
+    /// Application name in syslog messages
+    #[serde(default = "default_syslog_app_name")]
+    pub app_name: String,
+    /// Hostname in syslog messages (system hostname if unset)
+    #[serde(default)]
+    pub hostname: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioMonitorConfig {
+    /// Output device for monitoring source audio (system default if unset)
+    #[serde(default)]
+    pub output_device: Option<String>,
+    /// Output whose audio is heard on startup (the route output if unset)
+    #[serde(default)]
+    pub default_output: Option<String>,
+    /// Audio-only output that exists just to select what the monitor plays
+    /// (e.g. "Booth"). It is routed like any other output but has no slot
+    #[serde(default)]
+    pub route_output: Option<String>,
+    /// Monitoring gain (dB) of sources not yet adjusted in the mixer, e.g. -12
+    /// so an unfamiliar source can't come through at full blast
+    #[serde(default)]
+    pub default_gain_db: f32,
+    /// Crossfade in milliseconds when the monitored source changes (0 cuts)
+    #[serde(default = "default_audio_crossfade_ms")]
+    pub crossfade_ms: u64,
+    /// Outputs whose audio always cuts hard on a route change
+    #[serde(default)]
+    pub hard_cut_outputs: Vec<String>,
+}
+
+fn default_audio_crossfade_ms() -> u64 {
+    50
+}
+
+impl Default for AudioMonitorConfig {
+    fn default() -> Self {
+        Self {
+            output_device: None,
+            default_output: None,
+            route_output: None,
+            default_gain_db: 0.0,
+            crossfade_ms: default_audio_crossfade_ms(),
+            hard_cut_outputs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeConfig {
+    /// Listen for show timecode
+    #[serde(default)]
+    pub enabled: bool,
+    /// Timecode source
+    #[serde(default)]
+    pub source: TimecodeSource,
+    /// Audio input device (LTC) or MIDI port (MTC); the default device or first port if unset
+    #[serde(default)]
+    pub input: Option<String>,
+    /// Frame rate of the show timecode
+    #[serde(default = "default_timecode_fps")]
+    pub fps: u32,
+    /// Actions fired at specific timecode values
+    #[serde(default)]
+    pub triggers: Vec<TimecodeTrigger>,
+}
+
+/// Where show timecode is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimecodeSource {
+    /// Linear timecode on an audio input
+    #[default]
+    Ltc,
+    /// MIDI timecode
+    Mtc,
+}
+
+/// An action fired when show timecode reaches a value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeTrigger {
+    /// Timecode (HH:MM:SS:FF) at which the action fires
+    pub at: Timecode,
+    /// Action to perform (same format as inbound Companion actions)
+    pub action: CompanionAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestConfig {
+    /// Run the self-test on startup (`serve` exits with an error if it fails)
+    #[serde(default)]
+    pub enabled: bool,
+    /// NDI sources (name or URL) that must be on the network
+    #[serde(default)]
+    pub required_sources: Vec<String>,
+    /// Seconds to wait for required sources to be discovered
+    #[serde(default = "default_selftest_source_timeout")]
+    pub source_timeout: u64,
+    /// Free space needed on the recording disk, in GB
+    #[serde(default = "default_selftest_min_free_space")]
+    pub min_free_space_gb: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingConfig {
+    /// Run as the standby of another daemon: mirror its routes and only start
+    /// publishing outputs and control servers once it stops answering
+    #[serde(default)]
+    pub standby: bool,
+    /// Control API of the primary ("host:port")
+    #[serde(default)]
+    pub primary: String,
+    /// Control API of the standby ("host:port"), set on the primary so it
+    /// stands by itself if the standby took over while it was away
+    #[serde(default)]
+    pub peer: String,
+    /// Seconds between heartbeats to the primary
+    #[serde(default = "default_heartbeat_interval")]
+    pub heartbeat_interval: u64,
+    /// Seconds without an answer from the primary before taking over
+    #[serde(default = "default_takeover_after")]
+    pub takeover_after: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    /// Frames a receiver may drop within a minute before alerting (0 = never)
+    #[serde(default = "default_alert_dropped_frames")]
+    pub dropped_frames: u64,
+    /// Stalls within `stall_window_secs` before alerting (0 = never)
+    #[serde(default = "default_alert_stalls")]
+    pub stalls: usize,
+    #[serde(default = "default_alert_stall_window")]
+    pub stall_window_secs: u64,
+    /// Bit rate a full-quality video receiver must stay above (0 = never alert)
+    #[serde(default)]
+    pub min_bitrate_mbps: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Keep routes, salvos and the event history across restarts
+    #[serde(default)]
+    pub enabled: bool,
+    /// Storage backend
+    #[serde(default)]
+    pub backend: StorageBackend,
+    /// Directory holding the files or database
+    #[serde(default = "default_storage_directory")]
+    pub directory: PathBuf,
+}
+
+/// Where persisted state is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// JSON files, with events appended to a JSONL file
+    #[default]
+    File,
+    /// An embedded SQLite database (requires building with `--features sqlite`)
+    Sqlite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// Connect to an MQTT broker
+    #[serde(default)]
+    pub enabled: bool,
+    /// Broker host
+    #[serde(default = "default_mqtt_host")]
+    pub host: String,
+    /// Broker port
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    /// MQTT client ID (also identifies this instance in Home Assistant)
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    /// Broker username
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Broker password
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Prefix of RusTV state and command topics
+    #[serde(default = "default_mqtt_base_topic")]
+    pub base_topic: String,
+    /// Publish Home Assistant MQTT discovery configs
+    #[serde(default = "default_true")]
+    pub home_assistant_discovery: bool,
+    /// Home Assistant discovery prefix
+    #[serde(default = "default_mqtt_discovery_prefix")]
+    pub discovery_prefix: String,
+}
+
+/// Transport used to reach the syslog server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogProtocol {
+    #[default]
+    Udp,
+    Tcp,
+}
+
 fn default_window_width() -> f32 {
     1280.0
 }
@@ -93,6 +890,18 @@ fn default_window_height() -> f32 {
     720.0
 }
 
+fn default_slot_color() -> String {
+    "#282832".to_string()
+}
+
+fn default_thumbnail_height() -> f32 {
+    300.0
+}
+
+fn default_profiles_file() -> PathBuf {
+    PathBuf::from("profiles.toml")
+}
+
 fn default_companion_host() -> String {
     "localhost".to_string()
 }
@@ -101,12 +910,170 @@ fn default_companion_port() -> u16 {
     8888
 }
 
+fn default_push_to_talk_key() -> String {
+    "T".to_string()
+}
+
+fn default_intercom_operator() -> String {
+    "RusTV".to_string()
+}
+
+fn default_recording_directory() -> PathBuf {
+    PathBuf::from("recordings")
+}
+
+fn default_recording_fps() -> u32 {
+    10
+}
+
+fn default_clip_max_height() -> u32 {
+    720
+}
+
+fn default_watch_folder_directory() -> PathBuf {
+    PathBuf::from("graphics")
+}
+
+fn default_watch_folder_interval() -> u64 {
+    2
+}
+
+fn default_api_port() -> u16 {
+    8889
+}
+
+fn default_propresenter_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_propresenter_port() -> u16 {
+    1025
+}
+
+fn default_propresenter_source() -> String {
+    "ProPresenter".to_string()
+}
+
+fn default_propresenter_poll_interval() -> u64 {
+    500
+}
+
+fn default_audio_stream_timeout() -> u64 {
+    300
+}
+
+fn default_syslog_server() -> String {
+    "127.0.0.1:514".to_string()
+}
+
+fn default_syslog_facility() -> String {
+    "local0".to_string()
+}
+
+fn default_syslog_app_name() -> String {
+    "rustv".to_string()
+}
+
+fn default_mqtt_host() -> String {
+    "localhost".to_string()
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "rustv".to_string()
+}
+
+fn default_mqtt_base_topic() -> String {
+    "rustv".to_string()
+}
+
+fn default_mqtt_discovery_prefix() -> String {
+    "homeassistant".to_string()
+}
+
+fn default_timecode_fps() -> u32 {
+    25
+}
+
+fn default_selftest_source_timeout() -> u64 {
+    10
+}
+
+fn default_selftest_min_free_space() -> f64 {
+    10.0
+}
+
+fn default_storage_directory() -> PathBuf {
+    PathBuf::from("rustv-data")
+}
+
+fn default_heartbeat_interval() -> u64 {
+    1
+}
+
+fn default_takeover_after() -> u64 {
+    5
+}
+
+fn default_alert_dropped_frames() -> u64 {
+    30
+}
+
+fn default_alert_stalls() -> usize {
+    3
+}
+
+fn default_alert_stall_window() -> u64 {
+    600
+}
+
+fn default_scan_fast_interval() -> u64 {
+    1
+}
+
+fn default_scan_idle_interval() -> u64 {
+    30
+}
+
+fn default_scan_idle_after() -> u64 {
+    120
+}
+
+fn default_bridge_port() -> u16 {
+    5990
+}
+
+fn default_true() -> bool {
+    true
+}
+
 impl Default for GuiConfig {
     fn default() -> Self {
         Self {
             default_layout: Layout::default(),
+            auto_layout: false,
             window_width: default_window_width(),
             window_height: default_window_height(),
+            wall_mode: false,
+            show_latency: false,
+            slot_background: SlotBackground::default(),
+            slot_color: default_slot_color(),
+            slot_image: None,
+            slot_bandwidth: HashMap::new(),
+            slot_latency: HashMap::new(),
+            slot_delay: HashMap::new(),
+            slot_captions: Vec::new(),
+            slot_roles: HashMap::new(),
+            thumbnail_fps: 0,
+            thumbnail_height: default_thumbnail_height(),
+            source_filters: Vec::new(),
+            output_windows: Vec::new(),
+            training_mode: false,
+            profiles_file: default_profiles_file(),
+            help_file: None,
         }
     }
 }
@@ -121,6 +1088,196 @@ impl Default for CompanionConfig {
     }
 }
 
+impl Default for TalkbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            push_to_talk_key: default_push_to_talk_key(),
+            input_device: None,
+            destinations: vec![],
+        }
+    }
+}
+
+impl Default for IntercomConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            operator: default_intercom_operator(),
+            server: None,
+            alert_sound: true,
+        }
+    }
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            directory: default_recording_directory(),
+            fps: default_recording_fps(),
+            format: RecordingFormat::default(),
+        }
+    }
+}
+
+impl Default for TestPatternConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pattern: TestPattern::default(),
+        }
+    }
+}
+
+impl Default for WatchFolderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: default_watch_folder_directory(),
+            scan_interval: default_watch_folder_interval(),
+        }
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_api_port(),
+            config_editing: false,
+        }
+    }
+}
+
+impl Default for ProPresenterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_propresenter_host(),
+            port: default_propresenter_port(),
+            slides_source: default_propresenter_source(),
+            poll_interval_ms: default_propresenter_poll_interval(),
+        }
+    }
+}
+
+impl Default for AudioSourcesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stream_timeout: default_audio_stream_timeout(),
+        }
+    }
+}
+
+impl Default for SyslogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: default_syslog_server(),
+            protocol: SyslogProtocol::default(),
+            facility: default_syslog_facility(),
+            app_name: default_syslog_app_name(),
+            hostname: None,
+        }
+    }
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_mqtt_host(),
+            port: default_mqtt_port(),
+            client_id: default_mqtt_client_id(),
+            username: None,
+            password: None,
+            base_topic: default_mqtt_base_topic(),
+            home_assistant_discovery: true,
+            discovery_prefix: default_mqtt_discovery_prefix(),
+        }
+    }
+}
+
+impl Default for TimecodeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: TimecodeSource::default(),
+            input: None,
+            fps: default_timecode_fps(),
+            triggers: Vec::new(),
+        }
+    }
+}
+
+impl Default for ScanPolicy {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            fast_interval: default_scan_fast_interval(),
+            idle_interval: default_scan_idle_interval(),
+            idle_after: default_scan_idle_after(),
+        }
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: StorageBackend::default(),
+            directory: default_storage_directory(),
+        }
+    }
+}
+
+impl PairingConfig {
+    /// Control API of the other daemon of the pair, if this one is paired
+    pub fn peer_address(&self) -> Option<&str> {
+        let peer = if self.standby {
+            &self.primary
+        } else {
+            &self.peer
+        };
+        Some(peer.trim()).filter(|peer| !peer.is_empty())
+    }
+}
+
+impl Default for PairingConfig {
+    fn default() -> Self {
+        Self {
+            standby: false,
+            primary: String::new(),
+            peer: String::new(),
+            heartbeat_interval: default_heartbeat_interval(),
+            takeover_after: default_takeover_after(),
+        }
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            dropped_frames: default_alert_dropped_frames(),
+            stalls: default_alert_stalls(),
+            stall_window_secs: default_alert_stall_window(),
+            min_bitrate_mbps: 0.0,
+        }
+    }
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            required_sources: Vec::new(),
+            source_timeout: default_selftest_source_timeout(),
+            min_free_space_gb: default_selftest_min_free_space(),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -128,6 +1285,24 @@ impl Default for Config {
                 auto_discovery: true,
                 discovery_interval: 5,
                 static_sources: vec![],
+                groups: vec![],
+                extra_ips: vec![],
+                discovery_server: None,
+                receive: ReceiverTuning::default(),
+                frame_sync: FrameSyncConfig::default(),
+                scan: ScanPolicy::default(),
+                aliases: BTreeMap::new(),
+                proxies: vec![],
+                bridges: vec![],
+                approval: ApprovalConfig::default(),
+                hx_decoder: HwDecoder::default(),
+                transport: NdiTransport::default(),
+                source_transports: BTreeMap::new(),
+                interface_preference: vec![],
+                remote_tally: vec![],
+                tags: BTreeMap::new(),
+                channel_maps: BTreeMap::new(),
+                audio_trims: BTreeMap::new(),
             },
             matrix: MatrixConfig {
                 outputs: vec![
@@ -137,10 +1312,42 @@ impl Default for Config {
                     "Monitor 4".to_string(),
                 ],
                 routes: vec![],
+                salvos: vec![],
+                program_outputs: vec![],
+                preview_outputs: vec![],
+                maintenance_substitute: None,
+                standby_slate: None,
+                backups: vec![],
+                mirrors: vec![],
+                salvo_recall: RecallPolicy::default(),
+                wildcard_rebind: RebindPolicy::default(),
+                ndi_routing: false,
+                persist_routes: true,
+            },
+            birddog: BirdDogConfig {
+                cameras: vec![],
+                scenes: vec![],
             },
-            birddog: BirdDogConfig { cameras: vec![] },
             gui: GuiConfig::default(),
             companion: CompanionConfig::default(),
+            talkback: TalkbackConfig::default(),
+            intercom: IntercomConfig::default(),
+            recording: RecordingConfig::default(),
+            watch_folder: WatchFolderConfig::default(),
+            test_pattern: TestPatternConfig::default(),
+            clips: Vec::new(),
+            api: ApiConfig::default(),
+            propresenter: ProPresenterConfig::default(),
+            audio_sources: AudioSourcesConfig::default(),
+            events: EventsConfig::default(),
+            mqtt: MqttConfig::default(),
+            audio_monitor: AudioMonitorConfig::default(),
+            timecode: TimecodeConfig::default(),
+            selftest: SelfTestConfig::default(),
+            storage: StorageConfig::default(),
+            pairing: PairingConfig::default(),
+            alerts: AlertsConfig::default(),
+            devices: Vec::new(),
         }
     }
 }
@@ -160,6 +1367,72 @@ impl Config {
         Ok(())
     }
 
+    /// Every output of the router: the matrix outputs and the audio monitor output
+    pub fn router_outputs(&self) -> Vec<String> {
+        let mut outputs = self.matrix.outputs.clone();
+        outputs.extend(self.audio_monitor.route_output.clone());
+        outputs
+    }
+
+    /// Check the outputs, cameras and aliases for mistakes the TOML format
+    /// can't catch (duplicates, blanks, dangling references)
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let mut outputs = BTreeSet::new();
+        for output in self.router_outputs() {
+            if output.trim().is_empty() {
+                problems.push("Output names can't be blank".to_string());
+            } else if !outputs.insert(output.clone()) {
+                problems.push(format!("Output '{}' is listed twice", output));
+            }
+        }
+
+        let mut cameras = BTreeSet::new();
+        for camera in &self.birddog.cameras {
+            if camera.name.trim().is_empty() || camera.ndi_name.trim().is_empty() {
+                problems.push("Cameras need a name and an NDI name".to_string());
+            } else if !cameras.insert(&camera.name) {
+                problems.push(format!("Camera '{}' is listed twice", camera.name));
+            }
+            if camera.is_birddog() && camera.ip_address.trim().is_empty() {
+                problems.push(format!(
+                    "BirdDog camera '{}' needs an ip_address",
+                    camera.name
+                ));
+            }
+        }
+        for scene in &self.birddog.scenes {
+            for shot in &scene.shots {
+                let known = self
+                    .birddog
+                    .cameras
+                    .iter()
+                    .any(|c| c.name == shot.camera || c.ndi_name == shot.camera);
+                if !known {
+                    problems.push(format!(
+                        "Scene '{}' uses unknown camera '{}'",
+                        scene.name, shot.camera
+                    ));
+                }
+            }
+        }
+
+        let mut aliases = BTreeSet::new();
+        for (source, alias) in &self.ndi.aliases {
+            if alias.trim().is_empty() {
+                problems.push(format!("Alias of '{}' is blank", source));
+            } else if !aliases.insert(alias) {
+                problems.push(format!("Alias '{}' is given to several sources", alias));
+            }
+        }
+
+        if !problems.is_empty() {
+            anyhow::bail!("{}", problems.join("; "));
+        }
+        Ok(())
+    }
+
     /// Create a default config file if it doesn't exist
     pub fn ensure_default_config<P: AsRef<Path>>(path: P) -> Result<Self> {
         if path.as_ref().exists() {
@@ -190,4 +1463,77 @@ mod tests {
         assert!(toml_str.contains("[ndi]"));
         assert!(toml_str.contains("[matrix]"));
     }
+
+    #[test]
+    fn test_validate() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.matrix.outputs.push("Monitor 1".to_string());
+        config.ndi.aliases = [
+            ("CAM-1 (Wide)".to_string(), "Wide".to_string()),
+            ("CAM-2 (Wide)".to_string(), "Wide".to_string()),
+        ]
+        .into();
+        config.birddog.scenes.push(CameraScene {
+            name: "Worship look".to_string(),
+            shots: vec![SceneShot {
+                camera: "Camera 9".to_string(),
+                preset: None,
+            }],
+        });
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("Output 'Monitor 1' is listed twice"));
+        assert!(error.contains("Alias 'Wide' is given to several sources"));
+        assert!(error.contains("unknown camera 'Camera 9'"));
+
+        let mut config = Config::default();
+        config.audio_monitor.route_output = Some("Booth".to_string());
+        assert_eq!(config.router_outputs().last().unwrap(), "Booth");
+        assert!(config.validate().is_ok());
+        config.audio_monitor.route_output = Some("Monitor 2".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_camera_scenes() {
+        let birddog: BirdDogConfig = toml::from_str(
+            r#"
+            cameras = []
+
+            [[scenes]]
+            name = "Worship look"
+            shots = [
+                { camera = "Camera 1", preset = 2 },
+                { camera = "Camera 3" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let shots = &birddog.scenes[0].shots;
+        assert_eq!(shots[0].to_string(), "Camera 1 preset 2");
+        assert_eq!(shots[1].preset, None);
+        assert_eq!(shots[1].to_string(), "Camera 3 home");
+    }
+
+    #[test]
+    fn test_timecode_triggers() {
+        let timecode: TimecodeConfig = toml::from_str(
+            r#"
+            enabled = true
+            source = "mtc"
+
+            [[triggers]]
+            at = "01:00:00:00"
+            action = { type = "RecallSalvo", salvo = "Walk-in" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(timecode.source, TimecodeSource::Mtc);
+        assert_eq!(timecode.fps, 25);
+        assert_eq!(timecode.triggers[0].at, Timecode::new(1, 0, 0, 0));
+        assert!(toml::to_string(&timecode).unwrap().contains("01:00:00:00"));
+    }
 }
@@ -0,0 +1,272 @@
+use super::{fill_template, DeviceCommand, DeviceConfig, DevicePoll, DeviceRequest};
+use crate::events::{Event, EventCategory, EventLog, Severity};
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time;
+
+/// Longest polled value kept (device pages can be large)
+const MAX_VALUE_LEN: usize = 200;
+
+/// Last polled value of a device state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceState {
+    pub device: String,
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Why the value could not be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Runs actions on and polls the configured HTTP devices
+pub struct Devices {
+    devices: Vec<DeviceConfig>,
+    client: Client,
+    /// Last polled values, keyed by device and state name
+    states: Mutex<HashMap<(String, String), Result<String, String>>>,
+}
+
+impl Devices {
+    pub fn new(devices: Vec<DeviceConfig>) -> Self {
+        Self {
+            devices,
+            client: Client::new(),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn devices(&self) -> &[DeviceConfig] {
+        &self.devices
+    }
+
+    fn device(&self, name: &str) -> Result<&DeviceConfig> {
+        self.devices
+            .iter()
+            .find(|d| d.name == name)
+            .with_context(|| format!("Device '{}' not found", name))
+    }
+
+    /// Send a request to a device, returning the response body
+    async fn send(
+        &self,
+        device: &DeviceConfig,
+        request: &DeviceRequest,
+        params: &BTreeMap<String, String>,
+    ) -> Result<String> {
+        let url = format!(
+            "{}{}",
+            device.base_url.trim_end_matches('/'),
+            fill_template(&request.path, params)?
+        );
+        debug!("{} {} {}", device.name, request.method, url);
+
+        let mut builder = self
+            .client
+            .request(request.method.into(), &url)
+            .timeout(Duration::from_millis(device.timeout_ms));
+        for (name, value) in &device.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            let content_type = request
+                .content_type
+                .as_deref()
+                .unwrap_or("application/json");
+            builder = builder
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(fill_template(body, params)?);
+        }
+
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", device.name))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("{} returned {}", device.name, status);
+        }
+        Ok(body)
+    }
+
+    /// Run a device action, returning the device's response
+    pub async fn run(&self, command: &DeviceCommand) -> Result<String> {
+        let device = self.device(&command.device)?;
+        let request = device.actions.get(&command.action).with_context(|| {
+            format!(
+                "Device '{}' has no action '{}'",
+                device.name, command.action
+            )
+        })?;
+        info!("Device action: {}", command);
+        self.send(device, request, &command.params).await
+    }
+
+    /// Run several actions in order, stopping at the first failure
+    pub async fn run_all(&self, commands: &[DeviceCommand]) -> Result<()> {
+        for command in commands {
+            self.run(command)
+                .await
+                .with_context(|| format!("Device action '{}' failed", command))?;
+        }
+        Ok(())
+    }
+
+    /// Read a device state now, remembering the value
+    pub async fn poll(&self, device: &str, state: &str) -> Result<String> {
+        let device = self.device(device)?;
+        let poll = device
+            .polls
+            .get(state)
+            .with_context(|| format!("Device '{}' has no state '{}'", device.name, state))?;
+
+        let value = self
+            .send(device, &poll.request, &BTreeMap::new())
+            .await
+            .and_then(|body| extract_value(&body, poll));
+        self.states.lock().unwrap().insert(
+            (device.name.clone(), state.to_string()),
+            value.as_ref().map_err(|e| format!("{:#}", e)).cloned(),
+        );
+        value
+    }
+
+    /// Last polled value of every state, by device
+    pub fn states(&self) -> Vec<DeviceState> {
+        let states = self.states.lock().unwrap();
+        let mut list: Vec<DeviceState> = states
+            .iter()
+            .map(|((device, state), value)| DeviceState {
+                device: device.clone(),
+                state: state.clone(),
+                value: value.clone().ok(),
+                error: value.clone().err(),
+            })
+            .collect();
+        list.sort_by(|a, b| (&a.device, &a.state).cmp(&(&b.device, &b.state)));
+        list
+    }
+
+    /// Poll every device state at its interval, recording changes in the event log
+    pub fn start_polling(self: Arc<Self>, events: EventLog) {
+        for device in &self.devices {
+            for (state, poll) in &device.polls {
+                let devices = Arc::clone(&self);
+                let events = events.clone();
+                let device = device.name.clone();
+                let state = state.clone();
+                let interval = Duration::from_secs(poll.interval_secs.max(1));
+
+                tokio::spawn(async move {
+                    let mut last: Option<Result<String, String>> = None;
+                    loop {
+                        let value = devices
+                            .poll(&device, &state)
+                            .await
+                            .map_err(|e| format!("{:#}", e));
+                        if last.as_ref() != Some(&value) {
+                            let (severity, message) = match &value {
+                                Ok(value) => {
+                                    (Severity::Info, format!("{} {}: {}", device, state, value))
+                                }
+                                Err(e) => {
+                                    warn!("Failed to poll {} {}: {}", device, state, e);
+                                    (
+                                        Severity::Warning,
+                                        format!("{} {} unavailable: {}", device, state, e),
+                                    )
+                                }
+                            };
+                            events.record(
+                                Event::new(severity, EventCategory::Control, message)
+                                    .with_actor("devices"),
+                            );
+                            last = Some(value);
+                        }
+                        time::sleep(interval).await;
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// The polled value in a response: the JSON pointer's value, or the whole body
+fn extract_value(body: &str, poll: &DevicePoll) -> Result<String> {
+    let value = match &poll.pointer {
+        Some(pointer) => {
+            let json: serde_json::Value =
+                serde_json::from_str(body).context("Response is not JSON")?;
+            match json
+                .pointer(pointer)
+                .with_context(|| format!("No value at {}", pointer))?
+            {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }
+        }
+        None => body.trim().to_string(),
+    };
+    Ok(value.chars().take(MAX_VALUE_LEN).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::HttpMethod;
+
+    fn poll(pointer: Option<&str>) -> DevicePoll {
+        DevicePoll {
+            request: DeviceRequest {
+                method: HttpMethod::Get,
+                path: "/status".to_string(),
+                body: None,
+                content_type: None,
+            },
+            pointer: pointer.map(str::to_string),
+            interval_secs: 10,
+        }
+    }
+
+    #[test]
+    fn test_extract_value() {
+        let body = r#"{"power":{"state":"on","lamp_hours":1204}}"#;
+        assert_eq!(
+            extract_value(body, &poll(Some("/power/state"))).unwrap(),
+            "on"
+        );
+        assert_eq!(
+            extract_value(body, &poll(Some("/power/lamp_hours"))).unwrap(),
+            "1204"
+        );
+        assert!(extract_value(body, &poll(Some("/input"))).is_err());
+        assert!(extract_value("ON", &poll(Some("/power"))).is_err());
+        assert_eq!(extract_value(" ON\n", &poll(None)).unwrap(), "ON");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_action() {
+        let devices = Devices::new(vec![DeviceConfig {
+            name: "Projector".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            headers: BTreeMap::new(),
+            timeout_ms: 100,
+            actions: BTreeMap::new(),
+            polls: BTreeMap::new(),
+        }]);
+        let command = |device: &str| DeviceCommand {
+            device: device.to_string(),
+            action: "power_on".to_string(),
+            params: BTreeMap::new(),
+        };
+        let err = devices.run(&command("Projector")).await.unwrap_err();
+        assert!(err.to_string().contains("no action 'power_on'"));
+        assert!(devices.run(&command("Encoder")).await.is_err());
+    }
+}
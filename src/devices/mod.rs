@@ -0,0 +1,217 @@
+//! Generic HTTP device drivers
+//!
+//! Simple devices (projector power, HDMI matrix, streaming encoder) are described in
+//! the configuration as HTTP request templates: named actions that salvos, Companion
+//! and timecode triggers can run, and named states that are polled in the background.
+
+mod driver;
+
+pub use driver::{DeviceState, Devices};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A device controlled over HTTP (`[[devices]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    pub name: String,
+    /// URL that request paths are relative to (e.g. "http://192.168.1.50")
+    pub base_url: String,
+    /// Headers sent with every request (e.g. Authorization)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Request timeout in milliseconds
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Named actions, e.g. `power_on`
+    #[serde(default)]
+    pub actions: BTreeMap<String, DeviceRequest>,
+    /// Named states polled in the background, e.g. `power`
+    #[serde(default)]
+    pub polls: BTreeMap<String, DevicePoll>,
+}
+
+/// An HTTP request template. `{name}` in the path or body is replaced by the
+/// parameter of that name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceRequest {
+    #[serde(default)]
+    pub method: HttpMethod,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Content type of the body (JSON if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+/// A state read from the device at an interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevicePoll {
+    #[serde(flatten)]
+    pub request: DeviceRequest,
+    /// JSON pointer to the value in the response (e.g. "/power/state");
+    /// the whole response if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    /// Seconds between polls
+    #[serde(default = "default_poll_interval")]
+    pub interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Delete => "DELETE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl From<HttpMethod> for reqwest::Method {
+    fn from(method: HttpMethod) -> Self {
+        match method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Patch => reqwest::Method::PATCH,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        }
+    }
+}
+
+/// A device action to run, e.g. as part of a salvo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCommand {
+    pub device: String,
+    pub action: String,
+    /// Values for the action's `{name}` placeholders
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl fmt::Display for DeviceCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.device, self.action)?;
+        for (name, value) in &self.params {
+            write!(f, " {}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_poll_interval() -> u64 {
+    10
+}
+
+/// Fill the `{name}` placeholders of a template. Braces around anything other than
+/// a plain name (e.g. JSON objects) are left alone
+pub fn fill_template(template: &str, params: &BTreeMap<String, String>) -> Result<String> {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let name = after.find('}').map(|close| &after[..close]).filter(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+        match name {
+            Some(name) => {
+                let value = params
+                    .get(name)
+                    .ok_or_else(|| anyhow::anyhow!("Missing parameter '{}'", name))?;
+                filled.push_str(value);
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_template() {
+        let params = BTreeMap::from([
+            ("input".to_string(), "3".to_string()),
+            ("state".to_string(), "on".to_string()),
+        ]);
+        assert_eq!(
+            fill_template("/cgi-bin/route?in={input}&out=1", &params).unwrap(),
+            "/cgi-bin/route?in=3&out=1"
+        );
+        // JSON braces are kept
+        assert_eq!(
+            fill_template(r#"{"power":"{state}"}"#, &params).unwrap(),
+            r#"{"power":"on"}"#
+        );
+        assert_eq!(fill_template("{}", &params).unwrap(), "{}");
+        assert!(fill_template("/input/{port}", &params).is_err());
+    }
+
+    #[test]
+    fn test_device_config() {
+        let device: DeviceConfig = toml::from_str(
+            r#"
+            name = "Projector"
+            base_url = "http://192.168.1.50"
+            headers = { Authorization = "Basic YWRtaW46YWRtaW4=" }
+
+            [actions.power_on]
+            method = "POST"
+            path = "/api/power"
+            body = '{"state":"on"}'
+
+            [actions.input]
+            path = "/api/input?source={source}"
+
+            [polls.power]
+            path = "/api/power"
+            pointer = "/state"
+            interval_secs = 30
+            "#,
+        )
+        .unwrap();
+        assert_eq!(device.timeout_ms, 5000);
+        assert_eq!(device.actions["power_on"].method, HttpMethod::Post);
+        assert_eq!(device.actions["input"].method, HttpMethod::Get);
+        assert_eq!(device.polls["power"].request.path, "/api/power");
+        assert_eq!(device.polls["power"].pointer.as_deref(), Some("/state"));
+
+        let command: DeviceCommand =
+            toml::from_str("device = \"Matrix\"\naction = \"route\"\nparams = { input = \"2\" }")
+                .unwrap();
+        assert_eq!(command.to_string(), "Matrix route input=2");
+    }
+}
@@ -0,0 +1,217 @@
+//! Events and audit trail: a record of routing changes, control actions and
+//! system activity, fanned out to configurable sinks (e.g. syslog).
+
+mod syslog;
+
+pub use syslog::SyslogSink;
+
+use crate::config::EventsConfig;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of recent events kept in memory
+const MAX_RECENT: usize = 1000;
+
+/// Area of the system an event relates to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EventCategory {
+    /// Route and salvo changes
+    Routing,
+    /// Camera control
+    Camera,
+    /// Actions from control surfaces and integrations
+    Control,
+    /// Application lifecycle and health
+    System,
+}
+
+impl fmt::Display for EventCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Routing => write!(f, "routing"),
+            Self::Camera => write!(f, "camera"),
+            Self::Control => write!(f, "control"),
+            Self::System => write!(f, "system"),
+        }
+    }
+}
+
+/// Event severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Something that happened in RusTV
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Time in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub severity: Severity,
+    pub category: EventCategory,
+    /// Who or what caused the event (operator, "companion", "cli", ...)
+    pub actor: Option<String>,
+    pub message: String,
+}
+
+impl Event {
+    pub fn new(severity: Severity, category: EventCategory, message: String) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            timestamp_ms,
+            severity,
+            category,
+            actor: None,
+            message,
+        }
+    }
+
+    pub fn with_actor(mut self, actor: &str) -> Self {
+        self.actor = Some(actor.to_string());
+        self
+    }
+
+    /// Timestamp in RFC 3339 format (UTC, millisecond precision)
+    pub fn rfc3339(&self) -> String {
+        format_rfc3339(self.timestamp_ms)
+    }
+}
+
+/// Destination for events
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &Event);
+}
+
+/// Shared event log: keeps recent events and forwards every event to the sinks
+#[derive(Clone, Default)]
+pub struct EventLog {
+    sinks: Arc<Mutex<Vec<Box<dyn EventSink>>>>,
+    recent: Arc<Mutex<VecDeque<Event>>>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an event log with the sinks enabled in configuration
+    pub fn from_config(config: &EventsConfig) -> Self {
+        let log = Self::new();
+        if config.syslog.enabled {
+            match SyslogSink::new(&config.syslog) {
+                Ok(sink) => log.add_sink(Box::new(sink)),
+                Err(e) => error!("Failed to set up syslog output: {}", e),
+            }
+        }
+        log
+    }
+
+    pub fn add_sink(&self, sink: Box<dyn EventSink>) {
+        self.sinks.lock().unwrap().push(sink);
+    }
+
+    /// Record an event
+    pub fn record(&self, event: Event) {
+        for sink in self.sinks.lock().unwrap().iter() {
+            sink.emit(&event);
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        recent.push_back(event);
+        if recent.len() > MAX_RECENT {
+            recent.pop_front();
+        }
+    }
+
+    /// Record an informational event caused by `actor`
+    pub fn info(&self, category: EventCategory, actor: &str, message: String) {
+        self.record(Event::new(Severity::Info, category, message).with_actor(actor));
+    }
+
+    /// Recent events, oldest first
+    #[allow(dead_code)]
+    pub fn recent(&self) -> Vec<Event> {
+        self.recent.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Format milliseconds since the Unix epoch as an RFC 3339 UTC timestamp
+pub fn format_rfc3339(timestamp_ms: u64) -> String {
+    let secs = timestamp_ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60,
+        timestamp_ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CollectSink(Arc<Mutex<Vec<String>>>);
+
+    impl EventSink for CollectSink {
+        fn emit(&self, event: &Event) {
+            self.0.lock().unwrap().push(event.message.clone());
+        }
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_rfc3339(951_782_400_123), "2000-02-29T00:00:00.123Z");
+        assert_eq!(
+            format_rfc3339(1_792_152_245_000),
+            "2026-10-16T12:04:05.000Z"
+        );
+    }
+
+    #[test]
+    fn test_event_log_fans_out_and_caps_history() {
+        let collected = Arc::new(Mutex::new(Vec::new()));
+        let log = EventLog::new();
+        log.add_sink(Box::new(CollectSink(Arc::clone(&collected))));
+
+        for i in 0..MAX_RECENT + 5 {
+            log.info(EventCategory::Routing, "test", format!("event {}", i));
+        }
+
+        assert_eq!(collected.lock().unwrap().len(), MAX_RECENT + 5);
+        let recent = log.recent();
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent[0].message, "event 5");
+        assert_eq!(recent[0].actor.as_deref(), Some("test"));
+    }
+}
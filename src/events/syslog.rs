@@ -0,0 +1,348 @@
+//! RFC 5424 syslog output over UDP or TCP
+
+use super::{Event, EventSink, Severity};
+use crate::config::{SyslogConfig, SyslogProtocol};
+use anyhow::{bail, Context, Result};
+use log::warn;
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Structured data ID for RusTV event fields (example enterprise number from RFC 5612)
+const SD_ID: &str = "rustv@32473";
+
+/// Messages waiting for the TCP connection before new ones are dropped
+const TCP_QUEUE_SIZE: usize = 1024;
+
+/// Connection and write timeout for a TCP syslog server
+const TCP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Wait before the first reconnection to a TCP syslog server
+const INITIAL_RECONNECT: Duration = Duration::from_secs(1);
+
+/// Longest wait between reconnections to a TCP syslog server that stays down
+const MAX_RECONNECT: Duration = Duration::from_secs(60);
+
+/// Sends events to a syslog server
+pub struct SyslogSink {
+    transport: Transport,
+    facility: u8,
+    hostname: String,
+    app_name: String,
+    proc_id: String,
+}
+
+enum Transport {
+    Udp(UdpSocket),
+    /// Queue for a background thread that holds the connection, so a server
+    /// that is down never holds up whoever records the event
+    Tcp(SyncSender<String>),
+}
+
+impl SyslogSink {
+    pub fn new(config: &SyslogConfig) -> Result<Self> {
+        let facility = facility_code(&config.facility)?;
+
+        let transport = match config.protocol {
+            SyslogProtocol::Udp => {
+                let socket =
+                    UdpSocket::bind("0.0.0.0:0").context("Failed to open syslog socket")?;
+                socket
+                    .connect(&config.server)
+                    .with_context(|| format!("Invalid syslog server {}", config.server))?;
+                Transport::Udp(socket)
+            }
+            SyslogProtocol::Tcp => {
+                let (queue, messages) = mpsc::sync_channel(TCP_QUEUE_SIZE);
+                let connection = TcpConnection::new(config.server.clone());
+                thread::spawn(move || connection.run(messages));
+                Transport::Tcp(queue)
+            }
+        };
+
+        Ok(Self {
+            transport,
+            facility,
+            hostname: config.hostname.clone().unwrap_or_else(local_hostname),
+            app_name: config.app_name.clone(),
+            proc_id: std::process::id().to_string(),
+        })
+    }
+
+    /// Format an event as an RFC 5424 message
+    fn format(&self, event: &Event) -> String {
+        let severity = match event.severity {
+            Severity::Info => 6,
+            Severity::Warning => 4,
+            Severity::Error => 3,
+        };
+        let structured_data = match &event.actor {
+            Some(actor) => format!("[{} actor=\"{}\"]", SD_ID, escape_param(actor)),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "<{}>1 {} {} {} {} {} {} {}",
+            self.facility as u16 * 8 + severity,
+            event.rfc3339(),
+            header_field(&self.hostname, 255),
+            header_field(&self.app_name, 48),
+            header_field(&self.proc_id, 128),
+            header_field(&event.category.to_string(), 32),
+            structured_data,
+            event.message
+        )
+    }
+
+    fn send(&self, message: &str) -> Result<()> {
+        match &self.transport {
+            Transport::Udp(socket) => {
+                socket.send(message.as_bytes())?;
+            }
+            Transport::Tcp(queue) => match queue.try_send(message.to_string()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => bail!("Syslog queue full, event dropped"),
+                Err(TrySendError::Disconnected(_)) => bail!("Syslog connection closed"),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Persistent connection to a TCP syslog server, re-established with backoff
+struct TcpConnection {
+    server: String,
+    stream: Option<TcpStream>,
+    /// No reconnection is tried before this time
+    retry_at: Option<Instant>,
+    backoff: Duration,
+}
+
+impl TcpConnection {
+    fn new(server: String) -> Self {
+        Self {
+            server,
+            stream: None,
+            retry_at: None,
+            backoff: INITIAL_RECONNECT,
+        }
+    }
+
+    /// Send queued messages until the sink is dropped. Messages arriving while
+    /// the server is unreachable are dropped
+    fn run(mut self, messages: Receiver<String>) {
+        for message in messages {
+            let Some(stream) = self.connect() else {
+                continue;
+            };
+            // Octet-counting framing (RFC 6587)
+            let frame = format!("{} {}", message.len(), message);
+            if let Err(e) = stream.write_all(frame.as_bytes()) {
+                warn!("Failed to send event to syslog: {}", e);
+                self.stream = None;
+            }
+        }
+    }
+
+    /// The open connection, connecting first unless a recent attempt failed
+    fn connect(&mut self) -> Option<&mut TcpStream> {
+        if self.stream.is_none() {
+            if self.retry_at.is_some_and(|at| Instant::now() < at) {
+                return None;
+            }
+            match self.open() {
+                Ok(stream) => {
+                    self.retry_at = None;
+                    self.backoff = INITIAL_RECONNECT;
+                    self.stream = Some(stream);
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to connect to syslog server {}: {} (retrying in {:?})",
+                        self.server, e, self.backoff
+                    );
+                    self.retry_at = Some(Instant::now() + self.backoff);
+                    self.backoff = (self.backoff * 2).min(MAX_RECONNECT);
+                    return None;
+                }
+            }
+        }
+        self.stream.as_mut()
+    }
+
+    fn open(&self) -> Result<TcpStream> {
+        let addr = self
+            .server
+            .to_socket_addrs()?
+            .next()
+            .with_context(|| format!("Cannot resolve {}", self.server))?;
+        let stream = TcpStream::connect_timeout(&addr, TCP_TIMEOUT)?;
+        stream.set_write_timeout(Some(TCP_TIMEOUT))?;
+        Ok(stream)
+    }
+}
+
+impl EventSink for SyslogSink {
+    fn emit(&self, event: &Event) {
+        if let Err(e) = self.send(&self.format(event)) {
+            warn!("Failed to send event to syslog: {}", e);
+        }
+    }
+}
+
+/// Numeric syslog facility for a name such as "local0" or "daemon"
+fn facility_code(name: &str) -> Result<u8> {
+    let code = match name.to_lowercase().as_str() {
+        "user" => 1,
+        "daemon" => 3,
+        "local0" => 16,
+        "local1" => 17,
+        "local2" => 18,
+        "local3" => 19,
+        "local4" => 20,
+        "local5" => 21,
+        "local6" => 22,
+        "local7" => 23,
+        other => bail!("Unknown syslog facility '{}'", other),
+    };
+    Ok(code)
+}
+
+/// Header fields are printable ASCII without spaces, "-" when empty
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// Escape a structured data parameter value
+fn escape_param(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+fn local_hostname() -> String {
+    crate::network::local_hostname().unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventCategory;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn config(server: String, protocol: SyslogProtocol) -> SyslogConfig {
+        SyslogConfig {
+            enabled: true,
+            server,
+            protocol,
+            facility: "local0".to_string(),
+            app_name: "rustv".to_string(),
+            hostname: Some("control room".to_string()),
+        }
+    }
+
+    fn event() -> Event {
+        let mut event = Event::new(
+            Severity::Info,
+            EventCategory::Routing,
+            "Route created: Cam 1 -> Program".to_string(),
+        )
+        .with_actor("op \"A\"");
+        event.timestamp_ms = 1_792_152_245_000;
+        event
+    }
+
+    #[test]
+    fn test_rfc5424_format() {
+        let sink =
+            SyslogSink::new(&config("127.0.0.1:514".to_string(), SyslogProtocol::Udp)).unwrap();
+        let message = sink.format(&event());
+        let expected_prefix = format!(
+            "<134>1 2026-10-16T12:04:05.000Z controlroom rustv {} routing ",
+            std::process::id()
+        );
+        assert!(message.starts_with(&expected_prefix), "{}", message);
+        assert!(message
+            .ends_with("[rustv@32473 actor=\"op \\\"A\\\"\"] Route created: Cam 1 -> Program"));
+    }
+
+    #[test]
+    fn test_udp_delivery() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let sink = SyslogSink::new(&config(
+            server.local_addr().unwrap().to_string(),
+            SyslogProtocol::Udp,
+        ))
+        .unwrap();
+
+        sink.emit(&event());
+        let mut buf = [0u8; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        assert!(String::from_utf8_lossy(&buf[..len]).starts_with("<134>1 "));
+    }
+
+    #[test]
+    fn test_tcp_octet_counting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let sink = SyslogSink::new(&config(
+            listener.local_addr().unwrap().to_string(),
+            SyslogProtocol::Tcp,
+        ))
+        .unwrap();
+
+        sink.emit(&event());
+        drop(sink);
+
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        let (len, message) = received.split_once(' ').unwrap();
+        assert_eq!(len.parse::<usize>().unwrap(), message.len());
+    }
+
+    #[test]
+    fn test_tcp_server_down() {
+        // Nothing listens on the port once the listener is gone
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = listener.local_addr().unwrap();
+        drop(listener);
+
+        let sink = SyslogSink::new(&config(server.to_string(), SyslogProtocol::Tcp)).unwrap();
+        let started = Instant::now();
+        for _ in 0..100 {
+            sink.emit(&event());
+        }
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        // Connection attempts back off while the server stays down
+        let mut connection = TcpConnection::new(server.to_string());
+        assert!(connection.connect().is_none());
+        assert!(connection.retry_at.is_some());
+        assert_eq!(connection.backoff, INITIAL_RECONNECT * 2);
+        assert!(connection.connect().is_none());
+        assert_eq!(connection.backoff, INITIAL_RECONNECT * 2);
+    }
+
+    #[test]
+    fn test_unknown_facility() {
+        let mut config = config("127.0.0.1:514".to_string(), SyslogProtocol::Udp);
+        config.facility = "kern2".to_string();
+        assert!(SyslogSink::new(&config).is_err());
+    }
+}
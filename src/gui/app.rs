@@ -1,11 +1,102 @@
-use crate::config::Config;
-use crate::gui::layouts::Layout;
-use crate::matrix::{MatrixRouter, Route};
-use crate::ndi::{NdiDiscovery, NdiSource};
+use crate::api::{self, ApiState, ConfigEditor};
+use crate::audio::meter::to_dbfs;
+use crate::audio::mixer::{MAX_GAIN_DB, MIN_GAIN_DB};
+use crate::audio::trim::{MAX_TRIM_DB, MIN_TRIM_DB};
+use crate::audio::{
+    AudioChunk, AudioPlayback, AudioStreamDiscovery, InputTrim, MicCapture, MonitorMixer,
+    StreamMeter, Talkback,
+};
+use crate::companion::CompanionControl;
+use crate::config::{ClipConfig, Config, OutputWindowConfig, RecordingConfig, RecordingFormat};
+use crate::events::{Event, EventCategory, EventLog, Severity};
+use crate::gui::background::BackgroundPainter;
+use crate::gui::camera_panel::CameraPanel;
+use crate::gui::compare::CompareView;
+use crate::gui::displays::{self, Display, DisplayChange, DisplayWatcher};
+use crate::gui::help::Help;
+use crate::gui::kvm::KvmFocus;
+use crate::gui::layouts::{self, Layout, SlotRole};
+use crate::gui::power::{self, DisplayPower};
+use crate::gui::profiles::{Panel, ProfileStore, Shortcut};
+use crate::gui::selftest_panel::SelfTestPanel;
+use crate::gui::telestrator::{Telestrator, Tool};
+use crate::gui::wall::MonitoringWall;
+use crate::inputs::clip::{self, ClipPlayer};
+use crate::inputs::test_pattern::{TestPattern, ToneGenerator, MOTION_FPS, TEST_PATTERN_NAME};
+use crate::inputs::watch_folder::FILE_URL_PREFIX;
+use crate::inputs::WatchFolder;
+use crate::intercom::{self, IntercomChannel, IntercomHub, MessageKind};
+use crate::matrix::impact::{self, Reference, ReferenceKind};
+use crate::matrix::rename::{self, RenameChange, RenameDetector, RenamePlan};
+use crate::matrix::{
+    Failover, FailoverAction, MatrixRouter, RebindPolicy, RecallPolicy, Route, SalvoPreflight,
+    TallyOutputs, TallyState,
+};
+use crate::mqtt::MqttBridge;
+use crate::ndi::alerts::{StreamAlerts, StreamSample};
+use crate::ndi::bridge;
+use crate::ndi::kvm::KvmEvent;
+use crate::ndi::metadata::{MetadataEvent, SourceEvent, SourceMetadata};
+use crate::ndi::tags;
+use crate::ndi::{
+    ConnectionEvent, ConnectionInfo, ConnectionState, DiscoveryEvent, FrameSync, LatencyMode,
+    MetadataHub, NdiDiscovery, NdiReceiver, NdiSource, ProxyManager, ReceiveBandwidth,
+    ReceivedFrame, ReceiverPool, ReceiverTuning, RemoteTallyWatcher, RoutingDestinations,
+    SourceFilter, StatsHub, TransportPolicy,
+};
+use crate::network::usage::local_interface_for;
+use crate::network::{attribute_usage, format_bit_rate, ReceiverUsage};
+use crate::propresenter::{ProPresenterClient, ProPresenterMonitor};
+use crate::recording::markers::CHAPTERS_FILE;
+use crate::recording::{
+    FfmpegEncoder, FrameEncoder, ImageSequenceEncoder, MarkerRequests, Recorder, RecordingTarget,
+};
+use crate::storage::{self, Storage, StorageSink};
+use crate::supervisor::{Supervisor, TaskState};
+use crate::timecode::TimecodeListener;
+use crate::video::delay::{FrameDelay, MAX_DELAY_FRAMES};
+use crate::video::VideoFrame;
 use anyhow::Result;
 use eframe::egui;
-use log::{error, info};
+use log::{error, info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// How long output identification stays on screen
+const IDENTIFY_DURATION: Duration = Duration::from_secs(5);
+
+/// How long a display change notice stays on screen
+const DISPLAY_NOTICE_DURATION: Duration = Duration::from_secs(15);
+
+/// Metadata events kept for the source metadata panel
+const MAX_METADATA_EVENTS: usize = 100;
+
+/// Highlight around the control a tour step explains
+const TOUR_COLOR: egui::Color32 = egui::Color32::from_rgb(80, 200, 255);
+
+/// Color of the training mode watermark and status
+const TRAINING_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 150, 30);
+
+/// Latency shown amber above this, and red above LATENCY_BAD_MS
+const LATENCY_WARN_MS: f64 = 100.0;
+const LATENCY_BAD_MS: f64 = 250.0;
+
+/// Connection state changes buffered between frames
+const HEALTH_EVENT_CAPACITY: usize = 64;
+
+/// How long a source is hovered in the routing panel before its preview opens
+const PREVIEW_DELAY: Duration = Duration::from_secs(1);
+
+/// Size of the hover preview of a source
+const PREVIEW_SIZE: egui::Vec2 = egui::vec2(320.0, 180.0);
+
+/// Captions the sender never clears are taken off after this long
+const CAPTION_HOLD: Duration = Duration::from_secs(10);
 
 /// View state for each matrix view slot
 #[derive(Clone, Debug)]
@@ -14,6 +105,10 @@ struct ViewSlot {
     output_name: String,
     /// Currently assigned input (if any)
     assigned_input: Option<String>,
+    /// The assigned input is under maintenance and a substitute is shown
+    maintenance: bool,
+    /// Pattern of a wildcard route, when the assigned input was resolved from one
+    wildcard: Option<String>,
     /// Whether this view is selected
     selected: bool,
 }
@@ -22,10 +117,20 @@ struct ViewSlot {
 pub struct MatrixViewerApp {
     /// Current layout configuration
     layout: Layout,
+    /// Switch to the smallest layout showing every routed output when routes change
+    auto_layout: bool,
+    /// Outputs routed when the layout was last fitted to them
+    fitted_outputs: Vec<String>,
     /// Matrix router
     router: Arc<Mutex<MatrixRouter>>,
+    /// Training mode: a copy of the router that GUI routing changes go to instead
+    training: Option<Arc<Mutex<MatrixRouter>>>,
     /// NDI discovery service
     discovery: Arc<NdiDiscovery>,
+    /// Source changes from discovery
+    discovery_events: broadcast::Receiver<DiscoveryEvent>,
+    /// Discovered NDI sources, kept up to date from discovery events
+    discovered_sources: Vec<NdiSource>,
     /// Available NDI sources
     available_sources: Vec<NdiSource>,
     /// View slots for the matrix
@@ -40,11 +145,262 @@ pub struct MatrixViewerApp {
     selected_view_idx: Option<usize>,
     /// Manual input name for creating placeholder routes
     manual_input_name: String,
+    /// Monitoring wall mode: show every discovered source instead of routes
+    wall_mode: bool,
+    /// Show measured source latency in slot corners
+    show_latency: bool,
+    /// Highest frame rate of thumbnail-sized slots (0 for every frame)
+    thumbnail_fps: u32,
+    /// Height below which a slot is a thumbnail
+    thumbnail_height: f32,
+    /// Monitoring wall paging state
+    wall: MonitoringWall,
+    /// Source picked as the left side of a comparison
+    compare_left: Option<String>,
+    /// Source picked as the right side of a comparison
+    compare_right: Option<String>,
+    /// Active split-screen comparison shown in the main slot
+    compare: Option<CompareView>,
+    /// Whether talkback is enabled in configuration
+    talkback_enabled: bool,
+    /// Talkback controller
+    talkback: Talkback,
+    /// Key held for push-to-talk
+    talkback_key: Option<egui::Key>,
+    /// Microphone device for talkback
+    talkback_device: Option<String>,
+    /// Live microphone capture (opened on first use)
+    mic: Option<(MicCapture, Receiver<AudioChunk>)>,
+    /// Show talkback panel
+    show_talkback_panel: bool,
+    /// Intercom channel (if enabled)
+    intercom: Option<Arc<IntercomChannel>>,
+    /// Show intercom panel
+    show_intercom_panel: bool,
+    /// Intercom message being composed
+    intercom_draft: String,
+    /// Alerts already signalled to the window manager
+    intercom_alerts_seen: usize,
+    /// Chime on cues and mentions
+    intercom_alert_sound: bool,
+    /// Audio output for chimes and talkback from other stations (opened on first use)
+    intercom_output: Option<AudioPlayback>,
+    /// Opening the intercom audio output failed; not retried
+    intercom_output_failed: bool,
+    /// Recording settings
+    recording_config: RecordingConfig,
+    /// Target selected in the recordings panel
+    recording_target: RecordingTarget,
+    /// Active recording
+    recorder: Option<Recorder>,
+    /// Markers requested for the active recording by the API or Companion
+    marker_requests: MarkerRequests,
+    /// Label typed for the next marker
+    marker_label: String,
+    /// Show recordings panel
+    show_recordings_panel: bool,
+    /// Screen rectangle of each output's slot from the last frame
+    slot_rects: HashMap<String, egui::Rect>,
+    /// Graphics watch folder (if enabled)
+    watch_folder: Option<Arc<WatchFolder>>,
+    /// Loaded file input textures keyed by URL, with the file version they show
+    file_textures: HashMap<String, (u64, egui::TextureHandle)>,
+    /// Pattern listed as "RusTV Test Pattern" (None if not listed)
+    test_pattern: Option<TestPattern>,
+    /// When test patterns started moving (motion and tone run from here)
+    pattern_started: Instant,
+    /// Drawn test pattern textures, with the motion frame they show
+    pattern_textures: HashMap<TestPattern, (u64, egui::TextureHandle)>,
+    /// Video files played as inputs (`[[clips]]`)
+    clips: Vec<ClipConfig>,
+    /// Players of the clips shown on screen, by clip name (None if it failed
+    /// to start). A clip plays from the start each time it comes on screen
+    clip_players: HashMap<String, Option<ClipPlayer>>,
+    /// Clips shown this frame
+    clips_shown: HashSet<String>,
+    /// Clip textures by clip name, with the frame sequence they show
+    clip_textures: HashMap<String, (u64, egui::TextureHandle)>,
+    /// Line-up tone while a tone pattern is monitored
+    tone: Option<ToneGenerator>,
+    /// Live video textures keyed by input, with the frame sequence they show
+    video_textures: HashMap<String, (u64, egui::TextureHandle)>,
+    /// Switches every slot's frames together on one cadence (`[ndi.frame_sync]`)
+    frame_sync: Option<FrameSync>,
+    /// Source hovered in the routing panel and since when; previewed after PREVIEW_DELAY
+    hover_preview: Option<(NdiSource, Instant)>,
+    /// Camera maintenance panel
+    camera_panel: CameraPanel,
+    /// Show camera panel
+    show_camera_panel: bool,
+    /// Pre-show self-test checklist
+    selftest_panel: SelfTestPanel,
+    /// Show self-test panel
+    show_selftest_panel: bool,
+    /// Receivers for routed inputs, shared by every slot showing the same source
+    receivers: ReceiverPool,
+    /// Subscription to connection state changes
+    health_rx: broadcast::Receiver<ConnectionEvent>,
+    /// Local interface used to reach each sender address
+    interface_cache: HashMap<IpAddr, Option<IpAddr>>,
+    /// Transport receivers ask senders for, shown in the connection details
+    transport: TransportPolicy,
+    /// Show network usage panel
+    show_network_panel: bool,
+    /// ProPresenter slide state (if enabled)
+    propresenter: Option<Arc<ProPresenterMonitor>>,
+    /// Source labelled with the current ProPresenter slide
+    slides_source: String,
+    /// AES67/Dante stream discovery (if enabled)
+    audio_discovery: Option<Arc<AudioStreamDiscovery>>,
+    /// Active stream meters, keyed by stream id
+    stream_meters: HashMap<String, StreamMeter>,
+    /// Displayed meter levels (linear peak per channel), keyed by stream id
+    meter_levels: HashMap<String, Vec<f32>>,
+    /// Sources found to send audio but no video, by name
+    audio_only_sources: HashSet<String>,
+    /// Displayed meter levels of audio-only inputs (linear peak per channel)
+    audio_levels: HashMap<String, Vec<f32>>,
+    /// Show audio sources panel
+    show_audio_panel: bool,
+    /// Event log for operator actions
+    events: EventLog,
+    /// Restarts discovery, receivers, proxies and the API if they fail
+    supervisor: Arc<Supervisor>,
+    /// Output whose routed source is heard locally
+    audio_output: Option<String>,
+    /// Audio-only output selecting what the monitor plays (has no slot)
+    audio_route_output: Option<String>,
+    /// Input currently feeding the audio monitor
+    audio_input: Option<String>,
+    /// Local playback of the monitored source (opened on first use)
+    playback: Option<AudioPlayback>,
+    /// Output device used for monitoring
+    playback_device: Option<String>,
+    /// Crossfade when the monitored source changes (zero cuts)
+    audio_crossfade: Duration,
+    /// Outputs whose audio always cuts hard
+    hard_cut_outputs: Vec<String>,
+    /// Monitoring gain and mute of each source
+    mixer: MonitorMixer,
+    /// Show the monitor mixer panel
+    show_mixer_panel: bool,
+    /// Keeps monitoring levels and salvos across restarts (if storage is enabled)
+    storage: Option<Arc<dyn Storage>>,
+    /// Keeps the live routes across restarts (if enabled)
+    saved_routes: Option<Arc<dyn Storage>>,
+    /// Show timecode listener (if enabled)
+    timecode: Option<Arc<TimecodeListener>>,
+    /// Slot annotations
+    telestrator: Telestrator,
+    /// Show telestrator panel
+    show_telestrator_panel: bool,
+    /// A recording screenshot was requested and has not arrived yet
+    screenshot_pending: bool,
+    /// Program and preview outputs
+    tally_outputs: TallyOutputs,
+    /// Tally of each input from the current routes
+    tally: HashMap<String, TallyState>,
+    /// Switches outputs to their backup input when their source fails
+    failover: Failover,
+    /// Alerts on receivers dropping frames, stalling or losing bit rate
+    stream_alerts: StreamAlerts,
+    /// When receivers were last checked for alerts
+    last_alert_check: Instant,
+    /// Slot background
+    background: BackgroundPainter,
+    /// Show each output's number and name over its slot until this time
+    identify_until: Option<Instant>,
+    /// NDI proxies, which identify the outputs they publish too
+    proxies: Option<Arc<ProxyManager>>,
+    /// Receive bandwidth chosen for each slot, keyed by output
+    slot_bandwidth: HashMap<String, ReceiveBandwidth>,
+    /// Receive bandwidth configured for static sources, keyed by name and URL
+    source_bandwidth: HashMap<String, ReceiveBandwidth>,
+    /// Latency settings chosen for each slot, keyed by output
+    slot_latency: HashMap<String, ReceiverTuning>,
+    /// Video delay of each slot in frames, keyed by output
+    slot_delay: HashMap<String, u32>,
+    /// Frames held back for delayed slots, with the input they show, keyed by output
+    delay_lines: HashMap<String, (String, FrameDelay)>,
+    /// Textures of delayed slots, with the frame sequence they show, keyed by output
+    delayed_textures: HashMap<String, (u64, egui::TextureHandle)>,
+    /// Outputs whose slots show their source's captions
+    slot_captions: HashSet<String>,
+    /// Role of each slot by output, keeping it in the same window across layouts
+    slot_roles: HashMap<String, SlotRole>,
+    /// Caption each source is showing, by NDI name, with when it arrived
+    captions: HashMap<String, (String, Instant)>,
+    /// Latency settings of slots without their own
+    default_latency: ReceiverTuning,
+    /// Input shown instead of inputs under maintenance (built-in bars if unset)
+    maintenance_substitute: Option<String>,
+    /// Whether wildcard routes move to another match when their source goes away
+    wildcard_rebind: RebindPolicy,
+    /// Metadata received from connected sources
+    metadata: Arc<MetadataHub>,
+    /// Statistics of every pooled receiver, for the control API
+    stats: Arc<StatsHub>,
+    /// Subscription to metadata events
+    metadata_events: broadcast::Receiver<SourceEvent>,
+    /// Most recent metadata events, newest last
+    metadata_log: VecDeque<SourceEvent>,
+    /// Slot whose mouse and keyboard are forwarded to its source (NDI KVM)
+    kvm: Option<KvmFocus>,
+    /// Show source metadata panel
+    show_metadata_panel: bool,
+    /// Outputs shown fullscreen on other displays
+    output_windows: Vec<OutputWindowConfig>,
+    /// Watches for displays being plugged in or unplugged
+    display_watcher: Option<DisplayWatcher>,
+    /// Whether output window displays were put to sleep for Blank All
+    displays_asleep: bool,
+    /// Displays connected at the last check
+    displays: Vec<Display>,
+    /// Latest display change affecting output windows, shown until this time
+    display_notice: Option<(Instant, String)>,
+    /// Show displays panel
+    show_displays_panel: bool,
+    /// Operator profiles
+    profiles: ProfileStore,
+    /// Name of the chosen operator profile
+    profile: Option<String>,
+    /// Shortcut keys of the chosen profile
+    shortcuts: HashMap<Shortcut, egui::Key>,
+    /// Show the operator picker (at launch and for quick-switching)
+    show_profile_picker: bool,
+    /// Tooltips and the guided tour
+    help: Help,
+    /// Running configuration, kept in step with what the GUI saves to the file
+    config: Config,
+    /// Configuration file approved sources are saved to
+    config_path: PathBuf,
+    /// PIN required to approve quarantined sources (if any)
+    admin_pin: Option<String>,
+    /// Source being approved and the PIN typed so far
+    approval_prompt: Option<(String, String)>,
+    /// Tags by source name or pattern, as saved
+    source_tags: BTreeMap<String, Vec<String>>,
+    /// Source whose tags are being edited and the tags typed so far
+    tag_prompt: Option<(String, String)>,
+    /// Source whose dependents are shown, with what refers to it
+    impact: Option<(String, Vec<Reference>)>,
+    /// Sources gone lately, for noticing one come back under a new name
+    renames: RenameDetector,
+    /// Source that looks renamed, its new name and what renaming it changes
+    rename_offer: Option<(String, String, Vec<RenameChange>)>,
+    /// Saved routing panel filters
+    source_filters: Vec<SourceFilter>,
+    /// Filter the routing panel's source list is showing (all sources if none)
+    source_filter: Option<SourceFilter>,
+    /// Handles salvo recalls (set once the app is running)
+    control: Option<Arc<CompanionControl>>,
+    /// Salvo preflight waiting for the operator to confirm the recall
+    salvo_preflight: Option<SalvoPreflight>,
 }
 
 impl MatrixViewerApp {
     /// Create a new matrix viewer application
-    pub fn new(cc: &eframe::CreationContext<'_>, config: Config) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, config: Config, config_path: PathBuf) -> Self {
         // Configure egui style
         let mut style = (*cc.egui_ctx.style()).clone();
         style.spacing.item_spacing = egui::vec2(8.0, 8.0);
@@ -52,9 +408,11 @@ impl MatrixViewerApp {
 
         // Initialize matrix router
         let mut router = MatrixRouter::new();
-        for output in &config.matrix.outputs {
-            router.add_output(output.clone());
+        for output in config.router_outputs() {
+            router.add_output(output);
         }
+        router.set_notes(&config.matrix.routes);
+        router.set_mirrors(config.matrix.mirrors.clone());
 
         // Create view slots
         let view_slots: Vec<ViewSlot> = config
@@ -64,17 +422,49 @@ impl MatrixViewerApp {
             .map(|output| ViewSlot {
                 output_name: output.clone(),
                 assigned_input: None,
+                maintenance: false,
+                wildcard: None,
                 selected: false,
             })
             .collect();
 
+        // Background tasks are restarted if they fail
+        let events = EventLog::from_config(&config.events);
+        let supervisor = Arc::new(Supervisor::new().with_events(events.clone()));
+
         // Initialize NDI discovery
-        let discovery = Arc::new(NdiDiscovery::new());
+        let discovery = Arc::new(
+            NdiDiscovery::from_config(&config.ndi).with_supervisor(Arc::clone(&supervisor)),
+        );
+
+        // Displays are only watched when outputs have windows to place
+        let display_watcher = (!config.gui.output_windows.is_empty()).then(DisplayWatcher::start);
+        let metadata = Arc::new(MetadataHub::new());
+        let stats = Arc::new(StatsHub::new());
+        let (health_events, health_rx) = broadcast::channel(HEALTH_EVENT_CAPACITY);
+
+        let profiles = ProfileStore::load(&config.gui.profiles_file).unwrap_or_else(|e| {
+            error!("Failed to load operator profiles: {:#}", e);
+            ProfileStore::default()
+        });
+
+        let intercom = config.intercom.enabled.then(|| {
+            Arc::new(IntercomChannel::new(
+                &config.intercom.operator,
+                &config.intercom.server_address(&config.api),
+            ))
+        });
 
         Self {
             layout: config.gui.default_layout,
+            auto_layout: config.gui.auto_layout,
+            fitted_outputs: Vec::new(),
             router: Arc::new(Mutex::new(router)),
-            discovery,
+            training: None,
+            discovery_events: discovery.subscribe(),
+            // Static sources are listed before the first scan
+            discovered_sources: discovery.get_sources(),
+            discovery: Arc::clone(&discovery),
             available_sources: Vec::new(),
             view_slots,
             show_layout_panel: true,
@@ -82,301 +472,4180 @@ impl MatrixViewerApp {
             selected_source_idx: None,
             selected_view_idx: None,
             manual_input_name: String::new(),
+            wall_mode: config.gui.wall_mode,
+            show_latency: config.gui.show_latency,
+            thumbnail_fps: config.gui.thumbnail_fps,
+            thumbnail_height: config.gui.thumbnail_height,
+            wall: MonitoringWall::new(),
+            compare_left: None,
+            compare_right: None,
+            compare: None,
+            talkback_enabled: config.talkback.enabled,
+            talkback: Talkback::from_config(&config.talkback).with_intercom(intercom.clone()),
+            talkback_key: egui::Key::from_name(&config.talkback.push_to_talk_key),
+            talkback_device: config.talkback.input_device.clone(),
+            mic: None,
+            show_talkback_panel: false,
+            intercom,
+            show_intercom_panel: config.intercom.enabled,
+            intercom_draft: String::new(),
+            intercom_alerts_seen: 0,
+            intercom_alert_sound: config.intercom.alert_sound,
+            intercom_output: None,
+            intercom_output_failed: false,
+            recording_config: config.recording.clone(),
+            recording_target: RecordingTarget::Multiview,
+            recorder: None,
+            marker_requests: MarkerRequests::new(),
+            marker_label: String::new(),
+            show_recordings_panel: false,
+            slot_rects: HashMap::new(),
+            watch_folder: config.watch_folder.enabled.then(|| {
+                Arc::new(WatchFolder::new(
+                    &config.watch_folder.directory,
+                    Duration::from_secs(config.watch_folder.scan_interval.max(1)),
+                ))
+            }),
+            file_textures: HashMap::new(),
+            test_pattern: config
+                .test_pattern
+                .enabled
+                .then_some(config.test_pattern.pattern),
+            pattern_started: Instant::now(),
+            pattern_textures: HashMap::new(),
+            clips: config.clips.clone(),
+            clip_players: HashMap::new(),
+            clips_shown: HashSet::new(),
+            clip_textures: HashMap::new(),
+            tone: None,
+            video_textures: HashMap::new(),
+            frame_sync: config
+                .ndi
+                .frame_sync
+                .enabled
+                .then(|| FrameSync::new(&config.ndi.frame_sync)),
+            hover_preview: None,
+            camera_panel: CameraPanel::new(
+                config
+                    .birddog
+                    .cameras
+                    .iter()
+                    .filter(|camera| camera.is_birddog())
+                    .cloned()
+                    .collect(),
+            ),
+            show_camera_panel: false,
+            selftest_panel: SelfTestPanel::new(config.clone(), discovery),
+            show_selftest_panel: false,
+            receivers: ReceiverPool::new()
+                .with_channel_maps(config.ndi.channel_maps.clone())
+                .with_trims(config.ndi.audio_trims.clone())
+                .with_metadata_hub(Arc::clone(&metadata))
+                .with_stats_hub(Arc::clone(&stats))
+                .with_health_events(health_events)
+                .with_supervisor(Arc::clone(&supervisor)),
+            health_rx,
+            interface_cache: HashMap::new(),
+            transport: TransportPolicy::from_config(&config.ndi),
+            show_network_panel: false,
+            propresenter: config.propresenter.enabled.then(|| {
+                Arc::new(ProPresenterMonitor::new(
+                    ProPresenterClient::new(&config.propresenter.host, config.propresenter.port),
+                    Duration::from_millis(config.propresenter.poll_interval_ms.max(100)),
+                ))
+            }),
+            slides_source: config.propresenter.slides_source.clone(),
+            audio_discovery: config.audio_sources.enabled.then(|| {
+                Arc::new(AudioStreamDiscovery::new(Duration::from_secs(
+                    config.audio_sources.stream_timeout,
+                )))
+            }),
+            stream_meters: HashMap::new(),
+            meter_levels: HashMap::new(),
+            audio_only_sources: HashSet::new(),
+            audio_levels: HashMap::new(),
+            show_audio_panel: false,
+            events,
+            supervisor,
+            audio_output: config
+                .audio_monitor
+                .default_output
+                .clone()
+                .or_else(|| config.audio_monitor.route_output.clone()),
+            audio_route_output: config.audio_monitor.route_output.clone(),
+            audio_input: None,
+            playback: None,
+            playback_device: config.audio_monitor.output_device.clone(),
+            audio_crossfade: Duration::from_millis(config.audio_monitor.crossfade_ms),
+            hard_cut_outputs: config.audio_monitor.hard_cut_outputs.clone(),
+            mixer: MonitorMixer::new(config.audio_monitor.default_gain_db),
+            show_mixer_panel: false,
+            storage: None,
+            saved_routes: None,
+            timecode: config
+                .timecode
+                .enabled
+                .then(|| Arc::new(TimecodeListener::new(&config.timecode))),
+            telestrator: Telestrator::new(),
+            show_telestrator_panel: false,
+            screenshot_pending: false,
+            tally_outputs: TallyOutputs::from_config(&config.matrix),
+            tally: HashMap::new(),
+            failover: Failover::from_config(&config.matrix),
+            stream_alerts: StreamAlerts::new(config.alerts.clone()),
+            last_alert_check: Instant::now(),
+            background: BackgroundPainter::from_config(&config.gui),
+            identify_until: None,
+            proxies: None,
+            slot_bandwidth: config.gui.slot_bandwidth.clone(),
+            source_bandwidth: config
+                .ndi
+                .static_sources
+                .iter()
+                .flat_map(|s| [(s.name.clone(), s.bandwidth), (s.url.clone(), s.bandwidth)])
+                .collect(),
+            slot_latency: config.gui.slot_latency.clone(),
+            slot_delay: config.gui.slot_delay.clone(),
+            delay_lines: HashMap::new(),
+            delayed_textures: HashMap::new(),
+            slot_captions: config.gui.slot_captions.iter().cloned().collect(),
+            slot_roles: config.gui.slot_roles.clone(),
+            captions: HashMap::new(),
+            default_latency: config.ndi.receive,
+            maintenance_substitute: config.matrix.maintenance_substitute.clone(),
+            wildcard_rebind: config.matrix.wildcard_rebind,
+            metadata_events: metadata.subscribe(),
+            metadata,
+            stats,
+            metadata_log: VecDeque::new(),
+            kvm: None,
+            show_metadata_panel: false,
+            output_windows: config.gui.output_windows.clone(),
+            displays: display_watcher
+                .as_ref()
+                .and_then(DisplayWatcher::displays)
+                .unwrap_or_default(),
+            display_watcher,
+            displays_asleep: false,
+            display_notice: None,
+            show_displays_panel: false,
+            show_profile_picker: !profiles.is_empty(),
+            profiles,
+            profile: None,
+            shortcuts: HashMap::new(),
+            help: match &config.gui.help_file {
+                Some(path) => Help::load(path).unwrap_or_else(|e| {
+                    error!("Failed to load help file: {:#}", e);
+                    Help::builtin()
+                }),
+                None => Help::builtin(),
+            },
+            config: config.clone(),
+            config_path,
+            admin_pin: config.ndi.approval.admin_pin.clone(),
+            approval_prompt: None,
+            source_tags: config.ndi.tags.clone(),
+            tag_prompt: None,
+            impact: None,
+            renames: RenameDetector::new(),
+            rename_offer: None,
+            source_filters: config.gui.source_filters.clone(),
+            source_filter: None,
+            control: None,
+            salvo_preflight: None,
         }
     }
 
-    /// Update available sources from discovery
-    fn update_sources(&mut self) {
-        self.available_sources = self.discovery.get_sources();
+    /// Switch to an operator profile: theme, layout, keys, panels and favorites
+    fn select_profile(&mut self, ctx: &egui::Context, name: &str) {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            error!("Unknown operator profile: {}", name);
+            return;
+        };
+        info!("Operator profile: {}", profile.name);
 
-        // Auto-resolve placeholder routes when matching sources appear
-        if let Ok(mut router) = self.router.lock() {
-            for source in &self.available_sources {
-                // Add newly discovered sources to router
-                router.add_input(source.clone());
+        ctx.set_visuals(profile.theme.visuals());
+        if let Some(layout) = profile.layout {
+            self.layout = layout;
+        }
+        self.shortcuts = profile.shortcut_keys();
+        if let Some(panels) = &profile.panels {
+            for panel in [
+                Panel::Layout,
+                Panel::Routing,
+                Panel::Talkback,
+                Panel::Cameras,
+                Panel::SelfTest,
+                Panel::Network,
+                Panel::Metadata,
+                Panel::Displays,
+                Panel::Audio,
+                Panel::Mixer,
+                Panel::Recordings,
+                Panel::Telestrator,
+                Panel::Intercom,
+            ] {
+                *self.panel_visibility(panel) = panels.contains(&panel);
             }
         }
+        self.profile = Some(profile.name);
+        self.show_profile_picker = false;
     }
 
-    /// Create or update a route (including placeholder routes)
-    fn create_route(&mut self, input: String, output: String) {
-        if let Ok(mut router) = self.router.lock() {
-            // Try to add input to router if it's a discovered source
-            if let Some(source) = self
-                .available_sources
-                .iter()
-                .find(|s| s.name == input || s.url == input)
-            {
-                router.add_input(source.clone());
-            }
-
-            // Create the route (placeholder if source doesn't exist yet)
-            let result = if router.input_exists(&input) {
-                router.route(&input, &output)
-            } else {
-                router.route_placeholder(&input, &output)
-            };
-
-            if let Err(e) = result {
-                error!("Failed to create route: {}", e);
-            } else {
-                // Update view slot
-                if let Some(slot) = self.view_slots.iter_mut().find(|s| s.output_name == output) {
-                    slot.assigned_input = Some(input.clone());
-                }
-                info!("Route created: {} -> {}", input, output);
-            }
+    /// Visibility flag of a panel
+    fn panel_visibility(&mut self, panel: Panel) -> &mut bool {
+        match panel {
+            Panel::Layout => &mut self.show_layout_panel,
+            Panel::Routing => &mut self.show_routing_panel,
+            Panel::Talkback => &mut self.show_talkback_panel,
+            Panel::Cameras => &mut self.show_camera_panel,
+            Panel::SelfTest => &mut self.show_selftest_panel,
+            Panel::Network => &mut self.show_network_panel,
+            Panel::Metadata => &mut self.show_metadata_panel,
+            Panel::Displays => &mut self.show_displays_panel,
+            Panel::Audio => &mut self.show_audio_panel,
+            Panel::Mixer => &mut self.show_mixer_panel,
+            Panel::Recordings => &mut self.show_recordings_panel,
+            Panel::Telestrator => &mut self.show_telestrator_panel,
+            Panel::Intercom => &mut self.show_intercom_panel,
         }
     }
 
-    /// Remove a route
-    fn remove_route(&mut self, output: &str) {
-        if let Ok(mut router) = self.router.lock() {
-            router.unroute(output);
-            if let Some(slot) = self.view_slots.iter_mut().find(|s| s.output_name == output) {
-                slot.assigned_input = None;
-            }
-            info!("Route removed for output: {}", output);
+    /// Star or unstar a source in the chosen profile and save the profiles file
+    fn set_favorite(&mut self, source: &NdiSource, favorite: bool) {
+        let Some(profile) = self
+            .profile
+            .as_deref()
+            .and_then(|name| self.profiles.get_mut(name))
+        else {
+            return;
+        };
+        profile.set_favorite(&source.name, &source.url, favorite);
+        if let Err(e) = self.profiles.save() {
+            error!("Failed to save operator profiles: {:#}", e);
         }
     }
 
-    /// Draw the matrix view area
-    fn draw_matrix_view(&mut self, ui: &mut egui::Ui) {
-        let available_rect = ui.available_rect_before_wrap();
-        let rects = self.layout.calculate_view_rects();
-
-        // Limit view slots to the number supported by the layout
-        let num_views = self.layout.view_count().min(self.view_slots.len());
-
-        for (i, (x, y, w, h)) in rects.iter().enumerate().take(num_views) {
-            let rect = egui::Rect::from_min_size(
-                available_rect.min
-                    + egui::vec2(available_rect.width() * x, available_rect.height() * y),
-                egui::vec2(
-                    available_rect.width() * w - 4.0,
-                    available_rect.height() * h - 4.0,
-                ),
-            );
-
-            let view_slot = &self.view_slots[i];
-
-            // Draw view rectangle
-            let response = ui.allocate_rect(rect, egui::Sense::click());
+    /// Show what refers to a source, as saved in the config file and routed now
+    fn show_impact(&mut self, source: &NdiSource) {
+        let config = match Config::from_file(&self.config_path) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to read config for impact of {}: {}", source.name, e);
+                return;
+            }
+        };
+        let live = self.router.lock().unwrap().get_all_routes();
+        let references = impact::references(&config, &live, source);
+        self.impact = Some((source.display_name().to_string(), references));
+    }
 
-            let fill_color = if view_slot.selected {
-                egui::Color32::from_rgb(60, 80, 100)
-            } else {
-                egui::Color32::from_rgb(40, 40, 50)
-            };
+    /// Offer to move everything naming `from` to `to`, if anything does
+    fn offer_rename(&mut self, from: &str, to: &str) {
+        let mut changes: Vec<RenameChange> = self
+            .router
+            .lock()
+            .unwrap()
+            .get_all_routes()
+            .into_iter()
+            .filter(|route| route.input == from)
+            .map(|route| RenameChange {
+                kind: ReferenceKind::Output,
+                name: route.output,
+                before: from.to_string(),
+                after: to.to_string(),
+            })
+            .collect();
+        changes.extend(rename::rename(&mut self.config.clone(), from, to));
+        if !changes.is_empty() {
+            self.rename_offer = Some((from.to_string(), to.to_string(), changes));
+        }
+    }
 
-            ui.painter().rect_filled(rect, 4.0, fill_color);
-            ui.painter().rect_stroke(
-                rect,
-                4.0,
-                egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 100, 120)),
-            );
+    /// Move routes and settings from a source's old name to its new one
+    fn apply_rename(&mut self, from: &str, to: &str) {
+        let saved_routes = self.saved_routes.as_deref();
+        let storage = self.storage.as_deref();
+        let result = Config::from_file(&self.config_path).and_then(|config| {
+            let plan = RenamePlan::new(config, saved_routes, storage, from, to)?;
+            plan.save(&self.config_path, saved_routes, storage)?;
+            Ok(plan.changes.len())
+        });
+        let changes = match result {
+            Ok(changes) => changes,
+            Err(e) => {
+                error!("Failed to rename {} to {}: {:#}", from, to, e);
+                return;
+            }
+        };
+        rename::rename(&mut self.config, from, to);
 
-            // Draw label
-            let label_text = if let Some(input) = &view_slot.assigned_input {
-                // Check if this is a placeholder route (input doesn't exist)
-                let is_placeholder = if let Ok(router) = self.router.lock() {
-                    !router.input_exists(input)
+        let outputs: Vec<String> = {
+            let mut router = self.router.lock().unwrap();
+            let outputs: Vec<String> = router
+                .get_all_routes()
+                .into_iter()
+                .filter(|route| route.input == from)
+                .map(|route| route.output)
+                .collect();
+            for output in &outputs {
+                let result = if router.input_exists(to) {
+                    router.route(to, output)
                 } else {
-                    false
+                    router.route_placeholder(to, output)
                 };
-
-                if is_placeholder {
-                    format!("{}\n← {} (no feed)", view_slot.output_name, input)
-                } else {
-                    format!("{}\n← {}", view_slot.output_name, input)
+                if let Err(e) = result {
+                    error!("Failed to route {} to {}: {}", to, output, e);
                 }
-            } else {
-                format!("{}\n(No input)", view_slot.output_name)
-            };
-
-            ui.painter().text(
-                rect.center(),
-                egui::Align2::CENTER_CENTER,
-                label_text,
-                egui::FontId::proportional(14.0),
-                egui::Color32::WHITE,
-            );
-
-            // Handle click
-            if response.clicked() {
-                self.selected_view_idx = Some(i);
-                // Toggle selection
-                self.view_slots[i].selected = !self.view_slots[i].selected;
             }
+            outputs
+        };
+        for slot in &mut self.view_slots {
+            if slot.assigned_input.as_deref() == Some(from) {
+                slot.assigned_input = Some(to.to_string());
+            }
+        }
+
+        self.source_tags = self.config.ndi.tags.clone();
+        self.discovery.set_aliases(self.config.ndi.aliases.clone());
+        self.discovery.set_tags(self.config.ndi.tags.clone());
+        if let Some(trim) = self.config.ndi.audio_trims.get(to) {
+            self.receivers.set_trim(to, *trim);
         }
+        self.discovered_sources = self.discovery.get_sources();
+        self.update_sources();
+
+        self.events.info(
+            EventCategory::Routing,
+            "gui",
+            format!(
+                "Renamed {} to {} ({} entries)",
+                from,
+                to,
+                outputs.len() + changes
+            ),
+        );
     }
 
-    /// Draw the layout selection panel
-    fn draw_layout_panel(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Layout");
-        ui.separator();
+    /// Whether a source is a favorite of the chosen profile
+    fn is_favorite(&self, source: &NdiSource) -> bool {
+        self.profile
+            .as_deref()
+            .and_then(|name| self.profiles.get(name))
+            .is_some_and(|profile| profile.is_favorite(&source.name, &source.url))
+    }
 
-        for layout in Layout::all() {
-            let is_selected = self.layout == layout;
-            if ui.selectable_label(is_selected, layout.name()).clicked() {
-                self.layout = layout;
-                info!("Layout changed to: {}", layout.name());
+    /// Fire the chosen profile's shortcut keys
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        // Keys belong to the text field or the KVM source while either has focus
+        if ctx.wants_keyboard_input() || self.kvm.is_some() {
+            return;
+        }
+        let pressed: Vec<Shortcut> = self
+            .shortcuts
+            .iter()
+            .filter(|(_, key)| ctx.input(|i| i.key_pressed(**key)))
+            .map(|(shortcut, _)| *shortcut)
+            .collect();
+        for shortcut in pressed {
+            match shortcut {
+                // Held rather than pressed; see update_talkback
+                Shortcut::Talkback => {}
+                Shortcut::IdentifyOutputs => self.identify_outputs(),
+                Shortcut::MonitoringWall => {
+                    self.wall_mode = !self.wall_mode;
+                    info!("Monitoring wall mode: {}", self.wall_mode);
+                }
+                Shortcut::NextLayout => {
+                    let layouts = Layout::all();
+                    let current = layouts.iter().position(|l| *l == self.layout);
+                    self.layout = layouts[current.map_or(0, |i| (i + 1) % layouts.len())];
+                    info!("Layout changed to: {}", self.layout.name());
+                }
+                Shortcut::SwitchOperator => {
+                    self.show_profile_picker = !self.profiles.is_empty();
+                }
+                Shortcut::RecordingMarker => self.add_marker(None),
             }
         }
     }
 
-    /// Draw the routing panel
-    fn draw_routing_panel(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Routing Control");
-        ui.separator();
-
-        // Refresh sources button
-        if ui.button("🔄 Refresh Sources").clicked() {
-            self.update_sources();
+    /// Draw the current guided tour step, highlighting the control it explains
+    fn draw_tour(&mut self, ctx: &egui::Context) {
+        let Some((index, step)) = self.help.current_step() else {
+            return;
+        };
+        let step = step.clone();
+        let count = self.help.step_count();
+        if let Some(panel) = step.panel {
+            *self.panel_visibility(panel) = true;
         }
 
-        ui.add_space(10.0);
+        let target = self.help.target(&step.target);
+        if let Some(rect) = target {
+            ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("tour_highlight"),
+            ))
+            .rect_stroke(rect.expand(4.0), 6.0, egui::Stroke::new(3.0, TOUR_COLOR));
+        }
 
-        // Available sources
-        ui.label(format!(
-            "Available Sources ({})",
-            self.available_sources.len()
-        ));
-        ui.separator();
+        let mut window = egui::Window::new("Guided Tour")
+            .collapsible(false)
+            .resizable(false);
+        window = match target {
+            // Next to the control, on whichever side has room
+            Some(rect) if rect.center().x < ctx.screen_rect().center().x => window
+                .pivot(egui::Align2::LEFT_TOP)
+                .fixed_pos(rect.right_top() + egui::vec2(12.0, 0.0)),
+            Some(rect) => window
+                .pivot(egui::Align2::RIGHT_TOP)
+                .fixed_pos(rect.left_top() - egui::vec2(12.0, 0.0)),
+            None => window.anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0)),
+        };
+        window.show(ctx, |ui| {
+            ui.set_max_width(320.0);
+            ui.label(egui::RichText::new(&step.title).strong().size(16.0));
+            ui.label(&step.text);
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(format!("{} of {}", index + 1, count));
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("◀ Back"))
+                    .clicked()
+                {
+                    self.help.previous_step();
+                }
+                let next = if index + 1 == count {
+                    "Done"
+                } else {
+                    "Next ▶"
+                };
+                if ui.button(next).clicked() {
+                    self.help.next_step();
+                }
+                if ui.button("Skip").clicked() {
+                    self.help.end_tour();
+                }
+            });
+        });
+    }
 
-        egui::ScrollArea::vertical()
-            .max_height(200.0)
-            .show(ui, |ui| {
-                for (idx, source) in self.available_sources.iter().enumerate() {
-                    let is_selected = self.selected_source_idx == Some(idx);
-                    if ui.selectable_label(is_selected, &source.name).clicked() {
-                        self.selected_source_idx = Some(idx);
+    /// Draw the operator picker
+    fn draw_profile_picker(&mut self, ctx: &egui::Context) {
+        let mut chosen = None;
+        let mut open = true;
+        egui::Window::new("Operator")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("Who is operating?");
+                ui.separator();
+                for name in self.profiles.names() {
+                    let current = self.profile.as_ref() == Some(&name);
+                    if ui.selectable_label(current, name.as_str()).clicked() {
+                        chosen = Some(name);
                     }
                 }
             });
+        if let Some(name) = chosen {
+            self.select_profile(ctx, &name);
+        } else if !open {
+            self.show_profile_picker = false;
+        }
+    }
 
-        ui.add_space(10.0);
-
-        // Route button for selected source
-        ui.horizontal(|ui| {
-            let can_route = self.selected_source_idx.is_some() && self.selected_view_idx.is_some();
+    /// Router the GUI routes with: the training sandbox while practicing, else the live router
+    fn routing(&self) -> Arc<Mutex<MatrixRouter>> {
+        Arc::clone(self.training.as_ref().unwrap_or(&self.router))
+    }
 
-            if ui
-                .add_enabled(can_route, egui::Button::new("➡ Route Selected"))
-                .clicked()
-            {
-                if let (Some(source_idx), Some(view_idx)) =
-                    (self.selected_source_idx, self.selected_view_idx)
-                {
-                    if let (Some(source), Some(view)) = (
-                        self.available_sources.get(source_idx),
-                        self.view_slots.get(view_idx),
-                    ) {
-                        self.create_route(source.url.clone(), view.output_name.clone());
-                        self.selected_source_idx = None;
-                        self.view_slots[view_idx].selected = false;
-                    }
+    /// Start or end training mode. Routing then only changes the local preview, and
+    /// nothing is sent to sources, cameras or other stations
+    fn set_training(&mut self, enabled: bool) {
+        if enabled == self.training.is_some() {
+            return;
+        }
+        if enabled {
+            let sandbox = match self.router.lock() {
+                Ok(router) => router.clone(),
+                Err(e) => {
+                    error!("Failed to start training mode: {}", e);
+                    return;
                 }
+            };
+            self.training = Some(Arc::new(Mutex::new(sandbox)));
+            self.end_kvm();
+        } else {
+            // Practice routes are thrown away
+            self.training = None;
+        }
+        let state = if enabled { "started" } else { "ended" };
+        info!("Training mode {}", state);
+        self.events.info(
+            EventCategory::System,
+            "gui",
+            format!("Training mode {}", state),
+        );
+    }
+
+    /// Input and maintenance state an output window shows: always the live routing
+    fn window_input(&self, output: &str) -> (Option<String>, bool) {
+        if self.training.is_none() {
+            return self
+                .view_slots
+                .iter()
+                .find(|slot| slot.output_name == output)
+                .map_or((None, false), |slot| {
+                    (self.shown_input(slot), slot.maintenance)
+                });
+        }
+        let Ok(router) = self.router.lock() else {
+            return (None, false);
+        };
+        match router.get_route(output) {
+            Some(input) if router.in_maintenance(input) => {
+                (self.maintenance_substitute.clone(), true)
             }
-        });
+            Some(input) => (Some(input.clone()), false),
+            None => (None, false),
+        }
+    }
 
-        ui.add_space(10.0);
-        ui.separator();
+    /// Receive bandwidth of a slot: its own setting, else its static source's, else full
+    fn slot_bandwidth(&self, output: &str, input: &str) -> ReceiveBandwidth {
+        self.slot_bandwidth
+            .get(output)
+            .or_else(|| self.source_bandwidth.get(input))
+            .copied()
+            .unwrap_or_default()
+    }
 
-        // Manual input name entry for placeholder routes
-        ui.label("Or enter input name manually:");
-        ui.horizontal(|ui| {
-            ui.label("Input name:");
-            ui.text_edit_singleline(&mut self.manual_input_name);
-        });
+    /// Latency settings of a slot: its own, else the configured default
+    fn slot_latency(&self, output: &str) -> ReceiverTuning {
+        self.slot_latency
+            .get(output)
+            .copied()
+            .unwrap_or(self.default_latency)
+    }
 
-        ui.horizontal(|ui| {
-            let can_create_placeholder =
-                !self.manual_input_name.is_empty() && self.selected_view_idx.is_some();
+    /// Input a slot displays: its route, or the substitute while that input is under maintenance
+    fn shown_input(&self, slot: &ViewSlot) -> Option<String> {
+        if slot.maintenance {
+            self.maintenance_substitute.clone()
+        } else {
+            slot.assigned_input.clone()
+        }
+    }
 
-            if ui
-                .add_enabled(
-                    can_create_placeholder,
-                    egui::Button::new("➡ Create Placeholder Route"),
-                )
-                .clicked()
-            {
-                if let Some(view_idx) = self.selected_view_idx {
-                    if let Some(view) = self.view_slots.get(view_idx) {
-                        self.create_route(self.manual_input_name.clone(), view.output_name.clone());
-                        self.manual_input_name.clear();
-                        self.view_slots[view_idx].selected = false;
-                    }
+    /// Subscribe every slot, window and watcher to the NDI input it shows
+    fn sync_receivers(&mut self) {
+        // Subscribers showing the same source share one receiver at the highest
+        // bandwidth and the lowest latency any of them wants
+        let mut wanted: Vec<(String, String, ReceiveBandwidth, ReceiverTuning)> = Vec::new();
+        for slot in &self.view_slots {
+            if let Some(input) = self.shown_input(slot) {
+                let bandwidth = self.slot_bandwidth(&slot.output_name, &input);
+                let tuning = self.slot_latency(&slot.output_name);
+                wanted.push((
+                    format!("slot:{}", slot.output_name),
+                    input,
+                    bandwidth,
+                    tuning,
+                ));
+            }
+        }
+        // Sources replaced by a backup are watched for their return
+        for input in self.failover.watched_inputs() {
+            let subscriber = format!("failover:{}", input);
+            wanted.push((
+                subscriber,
+                input,
+                ReceiveBandwidth::Low,
+                self.default_latency,
+            ));
+        }
+        // The audio monitor output pulls audio only
+        let slotless = self
+            .audio_output
+            .as_ref()
+            .filter(|output| !self.view_slots.iter().any(|s| &s.output_name == *output));
+        if let (Some(output), Some(input)) = (slotless, self.monitored_input()) {
+            wanted.push((
+                format!("audio:{}", output),
+                input,
+                ReceiveBandwidth::AudioOnly,
+                self.default_latency,
+            ));
+        }
+        // Low-bandwidth preview of the source hovered in the routing panel
+        if let Some(source) = self.previewed_source() {
+            wanted.push((
+                "preview".to_string(),
+                source.url.clone(),
+                ReceiveBandwidth::Low,
+                self.default_latency,
+            ));
+        }
+        // Output windows keep showing the live routing during training
+        if self.training.is_some() {
+            for window in &self.output_windows {
+                if let (Some(input), _) = self.window_input(&window.output) {
+                    let bandwidth = self.slot_bandwidth(&window.output, &input);
+                    let tuning = self.slot_latency(&window.output);
+                    wanted.push((
+                        format!("window:{}", window.output),
+                        input,
+                        bandwidth,
+                        tuning,
+                    ));
                 }
             }
+        }
+
+        self.receivers.retain(|subscriber, source| {
+            wanted
+                .iter()
+                .any(|(s, input, ..)| s == subscriber && source.matches(input))
         });
 
-        ui.add_space(10.0);
+        for (subscriber, input, bandwidth, tuning) in wanted {
+            if input.starts_with(FILE_URL_PREFIX)
+                || self.pattern_of(&input).is_some()
+                || clip::find(&self.clips, &input).is_some()
+            {
+                continue;
+            }
+            let Some(source) = self
+                .available_sources
+                .iter()
+                .find(|s| s.matches(&input) && !s.quarantined)
+            else {
+                continue;
+            };
+            if let Err(e) = self
+                .receivers
+                .subscribe(&subscriber, source, bandwidth, tuning)
+            {
+                error!("Failed to connect to {}: {}", source.name, e);
+                continue;
+            }
+            let max_fps = self.display_rate(&subscriber);
+            self.receivers.set_display_rate(&subscriber, max_fps);
+        }
 
-        // Current routes
-        ui.label("Current Routes");
-        ui.separator();
+        self.video_textures
+            .retain(|input, _| self.receivers.get(input).is_some());
+        self.delay_lines
+            .retain(|_, (input, _)| self.receivers.get(input).is_some());
+    }
 
-        let routes: Vec<Route> = if let Ok(router) = self.router.lock() {
-            router.get_all_routes()
-        } else {
-            Vec::new()
-        };
+    /// Frame rate a subscriber is shown at: slots drawn smaller than a thumbnail
+    /// are limited, everything else gets every frame
+    fn display_rate(&self, subscriber: &str) -> Option<u32> {
+        let output = subscriber.strip_prefix("slot:")?;
+        let rect = self.slot_rects.get(output)?;
+        (self.thumbnail_fps > 0 && rect.height() < self.thumbnail_height)
+            .then_some(self.thumbnail_fps)
+    }
 
-        egui::ScrollArea::vertical()
-            .max_height(150.0)
-            .show(ui, |ui| {
-                for route in &routes {
-                    ui.horizontal(|ui| {
-                        ui.label(format!("{} ← {}", route.output, route.input));
-                        if ui.button("❌").clicked() {
-                            self.remove_route(&route.output);
-                        }
-                    });
-                }
+    /// The source whose hover preview is open, once it has been hovered long enough
+    fn previewed_source(&self) -> Option<&NdiSource> {
+        self.hover_preview
+            .as_ref()
+            .filter(|(_, since)| self.show_routing_panel && since.elapsed() >= PREVIEW_DELAY)
+            .map(|(source, _)| source)
+    }
 
-                if routes.is_empty() {
-                    ui.label("No routes configured");
-                }
-            });
+    /// Record receivers losing and regaining their sources in the event log
+    fn update_connection_health(&mut self) {
+        loop {
+            let event = match self.health_rx.try_recv() {
+                Ok(event) => event,
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            };
+            let severity = match event.state {
+                ConnectionState::Connecting => continue,
+                ConnectionState::Connected => Severity::Info,
+                ConnectionState::Stale => Severity::Warning,
+                ConnectionState::Disconnected => Severity::Error,
+            };
+            self.events.record(
+                Event::new(
+                    severity,
+                    EventCategory::System,
+                    format!("Source {} is {}", event.source, event.state),
+                )
+                .with_actor("gui"),
+            );
+        }
     }
-}
 
-impl eframe::App for MatrixViewerApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Update sources periodically
-        self.update_sources();
+    /// Check every receiver against the alert thresholds once a second, recording
+    /// raised and cleared alerts so they reach the event log and its sinks
+    fn update_stream_alerts(&mut self) {
+        let now = Instant::now();
+        if now.duration_since(self.last_alert_check) < Duration::from_secs(1) {
+            return;
+        }
+        self.last_alert_check = now;
 
-        // Top panel - menu bar
+        let mut changes = Vec::new();
+        let mut present = HashSet::new();
+        for (source, receiver) in self.receivers.iter() {
+            let sample = StreamSample {
+                dropped_frames: receiver.dropped_frames(),
+                state: receiver.connection_state(),
+                bytes_per_second: receiver.bytes_per_second(),
+                full_video: receiver.bandwidth() == ReceiveBandwidth::Full,
+            };
+            present.insert(source.display_name().to_string());
+            changes.extend(self.stream_alerts.check(source.display_name(), sample, now));
+        }
+        self.stream_alerts.retain(|source| present.contains(source));
+
+        for change in changes {
+            let event = if change.raised {
+                Event::new(
+                    Severity::Warning,
+                    EventCategory::System,
+                    format!(
+                        "Source {} is {}: {}",
+                        change.source, change.kind, change.detail
+                    ),
+                )
+            } else {
+                Event::new(
+                    Severity::Info,
+                    EventCategory::System,
+                    format!("Source {} is no longer {}", change.source, change.kind),
+                )
+            };
+            self.events.record(event.with_actor("gui"));
+        }
+    }
+
+    /// Connection state of the receiver for an input (None for inputs not received over NDI)
+    fn connection_state(&self, input: &str) -> Option<ConnectionState> {
+        self.receivers.get(input).map(NdiReceiver::connection_state)
+    }
+
+    /// Switch live routes to backups when their source fails, and back when it returns
+    fn update_failover(&mut self) {
+        if self.failover.is_empty() {
+            return;
+        }
+        let receivers = &self.receivers;
+        let delivering = |input: &str| match receivers.get(input)?.connection_state() {
+            ConnectionState::Connected => Some(true),
+            ConnectionState::Stale | ConnectionState::Disconnected => Some(false),
+            ConnectionState::Connecting => None,
+        };
+        let actions = match self.router.lock() {
+            Ok(mut router) => self
+                .failover
+                .update(Instant::now(), &mut router, delivering),
+            Err(_) => return,
+        };
+
+        for action in actions {
+            let (severity, message) = match action {
+                FailoverAction::SwitchedToBackup {
+                    output,
+                    primary,
+                    backup,
+                } => (
+                    Severity::Warning,
+                    format!(
+                        "Failover: {} switched from {} to backup {}",
+                        output, primary, backup
+                    ),
+                ),
+                FailoverAction::Restored { output, primary } => (
+                    Severity::Info,
+                    format!("Failover: {} switched back to {}", output, primary),
+                ),
+            };
+            info!("{}", message);
+            self.events.record(
+                Event::new(severity, EventCategory::Routing, message).with_actor("failover"),
+            );
+        }
+    }
+
+    /// Send program/preview tally to the sources routed to tally outputs
+    fn update_tally(&mut self) {
+        if self.tally_outputs.is_empty() {
+            return;
+        }
+        if let Ok(router) = self.router.lock() {
+            self.tally = self.tally_outputs.tally(&router.get_all_routes());
+        }
+        for (source, receiver) in self.receivers.iter() {
+            // Routes may name a source by name or URL
+            let tally = [&source.name, &source.url]
+                .into_iter()
+                .filter_map(|input| self.tally.get(input))
+                .fold(TallyState::default(), |a, b| TallyState {
+                    program: a.program || b.program,
+                    preview: a.preview || b.preview,
+                });
+            if receiver.tally() != tally {
+                receiver.set_tally(tally);
+            }
+        }
+    }
+
+    /// Input routed to the monitored output
+    fn monitored_input(&self) -> Option<String> {
+        let output = self.audio_output.as_ref()?;
+        match self
+            .view_slots
+            .iter()
+            .find(|slot| &slot.output_name == output)
+        {
+            Some(slot) => slot.assigned_input.clone(),
+            // The audio monitor output has no slot to follow
+            None => {
+                let routing = self.routing();
+                let router = routing.lock().ok()?;
+                router.get_route(output).cloned()
+            }
+        }
+    }
+
+    /// Play the audio of the input routed to the monitored output
+    fn update_audio_monitor(&mut self) {
+        let input = self.monitored_input();
+
+        // Start from live audio whenever the monitored source changes, crossfading
+        // from the previous source unless the output cuts hard
+        if input != self.audio_input {
+            if let Some(playback) = &self.playback {
+                let hard_cut = self
+                    .audio_output
+                    .as_ref()
+                    .is_some_and(|output| self.hard_cut_outputs.contains(output));
+                if hard_cut || self.audio_crossfade.is_zero() {
+                    playback.clear();
+                } else {
+                    playback.crossfade(self.audio_crossfade);
+                }
+            }
+            if let Some(receiver) = input.as_ref().and_then(|i| self.receivers.get(i)) {
+                receiver.clear_audio();
+            }
+            self.audio_input = input.clone();
+            self.tone = None;
+        }
+
+        // Tone test patterns are generated here rather than received
+        let tone = input
+            .as_deref()
+            .and_then(|i| self.pattern_of(i))
+            .is_some_and(|pattern| pattern.has_tone());
+        let receiver = input.as_ref().and_then(|i| self.receivers.get(i));
+        if receiver.is_none() && !tone {
+            return;
+        }
+
+        if self.playback.is_none() {
+            match AudioPlayback::start(self.playback_device.as_deref()) {
+                Ok(playback) => self.playback = Some(playback),
+                Err(e) => {
+                    error!("Failed to start audio monitoring: {}", e);
+                    self.audio_output = None;
+                    return;
+                }
+            }
+        }
+
+        let key = input
+            .as_deref()
+            .map(|i| self.monitor_key(i))
+            .unwrap_or_default();
+        if let Some(playback) = &self.playback {
+            if tone {
+                let chunk = self
+                    .tone
+                    .get_or_insert_with(ToneGenerator::default)
+                    .generate(self.pattern_started.elapsed());
+                playback.play(&self.mixer.apply(&key, &chunk));
+            } else if let Some(receiver) = receiver {
+                while let Ok(Some(chunk)) = receiver.receive_audio_frame() {
+                    playback.play(&self.mixer.apply(&key, &chunk));
+                }
+            }
+        }
+    }
+
+    /// Name an input's monitoring level is kept under: its NDI name if known
+    fn monitor_key(&self, input: &str) -> String {
+        self.available_sources
+            .iter()
+            .find(|s| s.matches(input))
+            .map_or(input, |s| s.name.as_str())
+            .to_string()
+    }
+
+    /// Silence the audio monitor at once, or bring it back
+    fn set_muted_all(&mut self, muted: bool) {
+        self.mixer.set_muted_all(muted);
+        if muted {
+            if let Some(playback) = &self.playback {
+                playback.clear();
+            }
+        }
+        let message = if muted {
+            "Audio monitor muted"
+        } else {
+            "Audio monitor unmuted"
+        };
+        self.events
+            .info(EventCategory::System, "gui", message.to_string());
+    }
+
+    /// Save the mixer's levels, if storage is enabled
+    fn save_monitor_levels(&self) {
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.save_monitor_levels(self.mixer.levels()) {
+                error!("Failed to save monitoring levels: {}", e);
+            }
+        }
+    }
+
+    /// Draw the monitor mixer: gain and mute of each source that can be heard
+    fn draw_mixer_panel(&mut self, ui: &mut egui::Ui) {
+        let muted_all = self.mixer.is_muted_all();
+        let (label, fill) = if muted_all {
+            ("🔊 Unmute All", egui::Color32::from_rgb(180, 40, 40))
+        } else {
+            ("🔇 Mute All", ui.visuals().widgets.inactive.weak_bg_fill)
+        };
+        let response = ui.add(
+            egui::Button::new(egui::RichText::new(label).strong())
+                .fill(fill)
+                .min_size(egui::vec2(ui.available_width(), 28.0)),
+        );
+        if self.help.attach(response, "mixer.mute_all").clicked() {
+            self.set_muted_all(!muted_all);
+        }
+        ui.separator();
+
+        // Sources routed to a slot or the audio monitor output
+        let heard = self.monitored_input().map(|i| self.monitor_key(&i));
+        let mut sources: Vec<(String, String)> = Vec::new();
+        let inputs = self
+            .view_slots
+            .iter()
+            .filter_map(|slot| slot.assigned_input.clone())
+            .chain(self.monitored_input());
+        for input in inputs {
+            let key = self.monitor_key(&input);
+            if !sources.iter().any(|(k, _)| k == &key) {
+                let name = self
+                    .available_sources
+                    .iter()
+                    .find(|s| s.matches(&input))
+                    .map_or(input.as_str(), |s| s.display_name())
+                    .to_string();
+                sources.push((key, name));
+            }
+        }
+        if sources.is_empty() {
+            ui.label("Nothing is routed");
+            return;
+        }
+
+        let mut save = false;
+        let mut save_trim = None;
+        egui::Grid::new("monitor_mixer")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for (key, name) in &sources {
+                    let mut level = self.mixer.level(key);
+                    if heard.as_ref() == Some(key) {
+                        ui.label(egui::RichText::new(format!("🔊 {}", name)).strong());
+                    } else {
+                        ui.label(name);
+                    }
+                    let mute = ui.checkbox(&mut level.muted, "Mute");
+                    let gain = ui.add(
+                        egui::Slider::new(&mut level.gain_db, MIN_GAIN_DB..=MAX_GAIN_DB)
+                            .suffix(" dB")
+                            .fixed_decimals(1),
+                    );
+                    let gain = self.help.attach(gain, "mixer.gain");
+                    if mute.changed() || gain.changed() {
+                        self.mixer.set_level(key, level);
+                    }
+                    // Save once a drag ends rather than on every step
+                    save |= mute.changed()
+                        || gain.drag_stopped()
+                        || (gain.changed() && !gain.dragged());
+
+                    let mut trim = self.receivers.trim(key);
+                    let trim_gain = ui.add(
+                        egui::Slider::new(&mut trim.gain_db, MIN_TRIM_DB..=MAX_TRIM_DB)
+                            .text("Trim")
+                            .suffix(" dB")
+                            .fixed_decimals(1),
+                    );
+                    let trim_gain = self.help.attach(trim_gain, "mixer.trim");
+                    let reference = ui.add(
+                        egui::DragValue::new(&mut trim.reference_dbfs)
+                            .range(-40.0..=0.0)
+                            .speed(0.5)
+                            .prefix("Ref ")
+                            .suffix(" dBFS"),
+                    );
+                    let reference = self.help.attach(reference, "mixer.reference");
+                    if trim_gain.changed() || reference.changed() {
+                        self.receivers.set_trim(key, trim);
+                    }
+                    if trim_gain.drag_stopped()
+                        || reference.drag_stopped()
+                        || ((trim_gain.changed() || reference.changed())
+                            && !trim_gain.dragged()
+                            && !reference.dragged())
+                    {
+                        save_trim = Some((key.clone(), trim));
+                    }
+                    ui.end_row();
+                }
+            });
+        if save {
+            self.save_monitor_levels();
+        }
+        if let Some((source, trim)) = save_trim {
+            self.save_trim(&source, trim);
+        }
+    }
+
+    /// Save a source's audio trim to the config file
+    fn save_trim(&mut self, source: &str, trim: InputTrim) {
+        let result = Config::from_file(&self.config_path).and_then(|mut config| {
+            if trim == InputTrim::default() {
+                config.ndi.audio_trims.remove(source);
+            } else {
+                config.ndi.audio_trims.insert(source.to_string(), trim);
+            }
+            config.to_file(&self.config_path)?;
+            Ok(config.ndi.audio_trims)
+        });
+        match result {
+            Ok(trims) => {
+                self.config.ndi.audio_trims = trims;
+                self.events.info(
+                    EventCategory::System,
+                    "gui",
+                    format!("Audio trim of {} set to {:+.1} dB", source, trim.total_db()),
+                )
+            }
+            Err(e) => error!("Failed to save audio trim: {:#}", e),
+        }
+    }
+
+    /// Track audio-only sources and the levels shown in their slots
+    fn update_audio_only(&mut self) {
+        let mut levels = HashMap::new();
+        for (source, receiver) in self.receivers.iter() {
+            let info = receiver.stream_info();
+            // The source list shows the format each source is sending
+            self.discovery.record_stream(&source.name, &info);
+            if info.audio_only && self.audio_only_sources.insert(source.name.clone()) {
+                info!("{} sends audio only", source.name);
+            } else if info.has_video() {
+                self.audio_only_sources.remove(&source.name);
+            }
+            if !info.audio_only {
+                continue;
+            }
+
+            // Hold peaks briefly so the meters fall back smoothly
+            let peaks: Vec<f32> = receiver.audio_levels().iter().map(|l| l.peak).collect();
+            let mut shown = self.audio_levels.remove(&source.url).unwrap_or_default();
+            // Meters fall away rather than vanish when the audio stops
+            if !peaks.is_empty() {
+                shown.resize(peaks.len(), 0.0);
+            }
+            for (i, level) in shown.iter_mut().enumerate() {
+                *level = peaks.get(i).copied().unwrap_or(0.0).max(*level * 0.85);
+            }
+            levels.insert(source.url.clone(), shown);
+        }
+        self.audio_levels = levels;
+    }
+
+    /// Reflect the router's routes in the view slots (routes may change via the API)
+    fn sync_view_slots(&mut self) {
+        let routing = self.routing();
+        let Ok(router) = routing.lock() else {
+            return;
+        };
+        for slot in &mut self.view_slots {
+            slot.assigned_input = router.get_route(&slot.output_name).cloned();
+            slot.maintenance = slot
+                .assigned_input
+                .as_ref()
+                .is_some_and(|input| router.in_maintenance(input));
+            slot.wildcard = router
+                .wildcard(&slot.output_name)
+                .map(|w| w.pattern.clone());
+        }
+    }
+
+    /// Update available sources from discovery
+    fn update_sources(&mut self) {
+        let mut renamed = Vec::new();
+        loop {
+            match self.discovery_events.try_recv() {
+                Ok(event) => {
+                    let now = Instant::now();
+                    match &event {
+                        DiscoveryEvent::SourceRemoved(source) => {
+                            self.renames.removed(source.clone(), now)
+                        }
+                        DiscoveryEvent::SourceAdded(source) => {
+                            if let Some(from) = self.renames.added(source, now) {
+                                renamed.push((from, source.name.clone()));
+                            }
+                        }
+                        DiscoveryEvent::SourceChanged(_) => {}
+                    }
+                    event.apply(&mut self.discovered_sources);
+                }
+                // Missed changes; start over from the full list
+                Err(broadcast::error::TryRecvError::Lagged(_)) => {
+                    self.discovered_sources = self.discovery.get_sources();
+                }
+                Err(_) => break,
+            }
+        }
+        self.available_sources = self.discovered_sources.clone();
+        if let Some(watch_folder) = &self.watch_folder {
+            self.available_sources.extend(watch_folder.sources());
+        }
+        if let Some(pattern) = self.test_pattern {
+            self.available_sources.push(pattern.to_source());
+        }
+        self.available_sources
+            .extend(self.clips.iter().map(clip::to_source));
+        for source in &mut self.available_sources {
+            source.audio_only = self.audio_only_sources.contains(&source.name);
+            source.tally = self
+                .metadata
+                .get(&source.name)
+                .and_then(|metadata| metadata.tally());
+        }
+
+        // Auto-resolve placeholder routes when matching sources appear
+        for routing in [Some(&self.router), self.training.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            if let Ok(mut router) = routing.lock() {
+                for source in &self.available_sources {
+                    // Add newly discovered sources to router
+                    router.add_input(source.clone());
+                }
+                for (output, source) in
+                    router.resolve_wildcards(&self.available_sources, self.wildcard_rebind)
+                {
+                    self.events.info(
+                        EventCategory::Routing,
+                        "gui",
+                        format!("Wildcard route on {} resolved to {}", output, source),
+                    );
+                }
+            }
+        }
+
+        // A source back under a new name on the same machine
+        if self.rename_offer.is_none() {
+            if let Some((from, to)) = renamed.pop() {
+                self.offer_rename(&from, &to);
+            }
+        }
+    }
+
+    /// Create or update a route (including placeholder routes)
+    fn create_route(&mut self, input: String, output: String) {
+        let routing = self.routing();
+        let Ok(mut router) = routing.lock() else {
+            return;
+        };
+        // Try to add input to router if it's a discovered source
+        if let Some(source) = self.available_sources.iter().find(|s| s.matches(&input)) {
+            router.add_input(source.clone());
+        }
+
+        // Create the route (placeholder if source doesn't exist yet)
+        let result = if router.input_exists(&input) {
+            router.route(&input, &output)
+        } else {
+            router.route_placeholder(&input, &output)
+        };
+
+        if let Err(e) = result {
+            error!("Failed to create route: {}", e);
+        } else {
+            // Update view slot
+            if let Some(slot) = self.view_slots.iter_mut().find(|s| s.output_name == output) {
+                slot.assigned_input = Some(input.clone());
+            }
+            info!("Route created: {} -> {}", input, output);
+            self.events.info(
+                EventCategory::Routing,
+                "gui",
+                format!("Route created: {} -> {}", input, output),
+            );
+        }
+    }
+
+    /// Put a source into or out of maintenance
+    fn set_maintenance(&mut self, input: &str, enabled: bool) {
+        let routing = self.routing();
+        if let Ok(mut router) = routing.lock() {
+            if !enabled {
+                // The source may have been put into maintenance by URL (e.g. via the API)
+                for source in self.available_sources.iter().filter(|s| s.name == input) {
+                    router.set_maintenance(&source.url, false);
+                }
+            }
+            router.set_maintenance(input, enabled);
+        }
+        let state = if enabled { "started" } else { "ended" };
+        self.events.info(
+            EventCategory::Routing,
+            "gui",
+            format!("Maintenance {}: {}", state, input),
+        );
+    }
+
+    /// Remove a route
+    fn remove_route(&mut self, output: &str) {
+        let routing = self.routing();
+        let Ok(mut router) = routing.lock() else {
+            return;
+        };
+        router.unroute(output);
+        if let Some(slot) = self.view_slots.iter_mut().find(|s| s.output_name == output) {
+            slot.assigned_input = None;
+        }
+        info!("Route removed for output: {}", output);
+        self.events.info(
+            EventCategory::Routing,
+            "gui",
+            format!("Route removed for output: {}", output),
+        );
+    }
+
+    /// Let a wildcard route bind afresh to the first matching source
+    fn rebind_wildcard(&mut self, output: &str) {
+        let routing = self.routing();
+        let Ok(mut router) = routing.lock() else {
+            return;
+        };
+        router.rebind_wildcard(output);
+        router.resolve_wildcards(&self.available_sources, self.wildcard_rebind);
+        let bound = router.get_route(output).cloned().unwrap_or_default();
+        self.events.info(
+            EventCategory::Routing,
+            "gui",
+            format!("Wildcard route on {} rebound to {}", output, bound),
+        );
+    }
+
+    /// Build view slots for the monitoring wall from the current page of sources
+    fn wall_slots(&self) -> Vec<ViewSlot> {
+        self.wall
+            .visible_sources(&self.available_sources, self.layout.view_count())
+            .into_iter()
+            .map(|source| ViewSlot {
+                output_name: source.display_name().to_string(),
+                assigned_input: Some(source.url),
+                maintenance: false,
+                wildcard: None,
+                selected: false,
+            })
+            .collect()
+    }
+
+    /// Role of each view slot, in slot order
+    fn view_roles(&self) -> Vec<Option<SlotRole>> {
+        self.view_slots
+            .iter()
+            .map(|slot| self.slot_roles.get(&slot.output_name).copied())
+            .collect()
+    }
+
+    /// Smallest layout showing every routed output
+    fn suggested_layout(&self) -> Layout {
+        let routed: Vec<bool> = self
+            .view_slots
+            .iter()
+            .map(|slot| slot.assigned_input.is_some())
+            .collect();
+        layouts::smallest_fitting(&self.view_roles(), &routed)
+    }
+
+    /// Routed outputs the current layout has no window for
+    fn hidden_outputs(&self) -> Vec<String> {
+        layouts::hidden(self.layout, &self.view_roles())
+            .into_iter()
+            .map(|i| &self.view_slots[i])
+            .filter(|slot| slot.assigned_input.is_some())
+            .map(|slot| slot.output_name.clone())
+            .collect()
+    }
+
+    /// With auto layout on, switch to the smallest layout fitting the routed
+    /// outputs whenever they change (and leave the layout alone otherwise)
+    fn fit_layout(&mut self) {
+        if !self.auto_layout {
+            return;
+        }
+        let routed: Vec<String> = self
+            .view_slots
+            .iter()
+            .filter(|slot| slot.assigned_input.is_some())
+            .map(|slot| slot.output_name.clone())
+            .collect();
+        if routed == self.fitted_outputs {
+            return;
+        }
+        self.fitted_outputs = routed;
+        if self.fitted_outputs.is_empty() {
+            return;
+        }
+        let layout = self.suggested_layout();
+        if layout != self.layout {
+            self.layout = layout;
+            info!(
+                "Layout changed to {} to fit the routed outputs",
+                layout.name()
+            );
+        }
+    }
+
+    /// Draw the matrix view area
+    fn draw_matrix_view(&mut self, ui: &mut egui::Ui) {
+        if self.wall_mode {
+            self.draw_wall_view(ui);
+            return;
+        }
+
+        let available_rect = ui.available_rect_before_wrap();
+        let rects = self.layout.calculate_view_rects();
+        self.slot_rects.clear();
+
+        // Outputs with a role keep its window in every layout
+        let roles = self.view_roles();
+        let order = layouts::arrange(self.layout, &roles);
+
+        for (view, (&i, (x, y, w, h))) in order.iter().zip(rects.iter()).enumerate() {
+            let rect = egui::Rect::from_min_size(
+                available_rect.min
+                    + egui::vec2(available_rect.width() * x, available_rect.height() * y),
+                egui::vec2(
+                    available_rect.width() * w - 4.0,
+                    available_rect.height() * h - 4.0,
+                ),
+            );
+
+            self.slot_rects
+                .insert(self.view_slots[i].output_name.clone(), rect);
+
+            // The main slot shows the comparison while compare mode is active
+            if view == 0 && self.compare.is_some() {
+                self.draw_compare_view(ui, rect);
+                continue;
+            }
+
+            // File inputs show their image, NDI inputs their latest video frame
+            let texture = self.shown_input(&self.view_slots[i]).and_then(|input| {
+                self.file_texture(ui.ctx(), &input)
+                    .or_else(|| self.pattern_texture(ui.ctx(), &input))
+                    .or_else(|| self.clip_texture(ui.ctx(), &input))
+                    .or_else(|| {
+                        let output = self.view_slots[i].output_name.clone();
+                        self.slot_video_texture(ui.ctx(), &output, &input)
+                    })
+            });
+
+            // Audio-only inputs show level meters instead
+            let audio_levels = self
+                .shown_input(&self.view_slots[i])
+                .and_then(|input| self.receivers.source(&input))
+                .and_then(|source| self.audio_levels.get(&source.url).cloned());
+
+            let view_slot = &self.view_slots[i];
+
+            // Draw view rectangle (dragging draws annotations in telestrator mode)
+            let sense = if self.telestrator.active {
+                egui::Sense::click_and_drag()
+            } else {
+                egui::Sense::click()
+            };
+            let response = ui.allocate_rect(rect, sense);
+
+            self.background.paint(ui.ctx(), ui.painter(), rect);
+            if view_slot.selected {
+                ui.painter().rect_filled(
+                    rect,
+                    4.0,
+                    egui::Color32::from_rgba_unmultiplied(60, 80, 100, 200),
+                );
+            }
+
+            if let Some(texture) = &texture {
+                ui.painter().image(
+                    texture.id(),
+                    fit_rect(rect, texture.aspect_ratio()),
+                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                    egui::Color32::WHITE,
+                );
+            } else if view_slot.maintenance && self.maintenance_substitute.is_none() {
+                draw_bars(ui.painter(), rect);
+            } else if let Some(levels) = &audio_levels {
+                draw_audio_meters(ui.painter(), rect, levels);
+            }
+
+            // Grey out slots whose source stopped delivering
+            let connection = self
+                .shown_input(view_slot)
+                .and_then(|input| self.connection_state(&input));
+            if matches!(
+                connection,
+                Some(ConnectionState::Stale | ConnectionState::Disconnected)
+            ) {
+                ui.painter()
+                    .rect_filled(rect, 4.0, egui::Color32::from_black_alpha(170));
+            }
+
+            // Red border for inputs on program, green for preview
+            let tally = view_slot
+                .assigned_input
+                .as_ref()
+                .and_then(|input| self.tally.get(input))
+                .copied()
+                .unwrap_or_default();
+            let border = if tally.program {
+                egui::Stroke::new(4.0, egui::Color32::from_rgb(220, 40, 40))
+            } else if tally.preview {
+                egui::Stroke::new(4.0, egui::Color32::from_rgb(40, 200, 60))
+            } else {
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 100, 120))
+            };
+            ui.painter().rect_stroke(rect, 4.0, border);
+
+            // Draw label (marking the slot being listened to, and its role)
+            let mut output_name = if self.audio_output.as_ref() == Some(&view_slot.output_name) {
+                format!("🔊 {}", view_slot.output_name)
+            } else {
+                view_slot.output_name.clone()
+            };
+            if let Some(role) = self.slot_roles.get(&view_slot.output_name) {
+                output_name.push_str(&format!(" [{}]", role));
+            }
+            let label_text = if let Some(input) = &view_slot.assigned_input {
+                // Check if this is a placeholder route (input doesn't exist)
+                let is_placeholder = if let Ok(router) = self.routing().lock() {
+                    !router.input_exists(input)
+                } else {
+                    false
+                };
+                let quarantined = self
+                    .available_sources
+                    .iter()
+                    .any(|s| s.quarantined && s.matches(input));
+                let unresolved = view_slot.wildcard.as_ref() == Some(input);
+                let input = match &view_slot.wildcard {
+                    Some(pattern) if !unresolved => {
+                        format!("{} [{}]", self.input_label(input), pattern)
+                    }
+                    _ => self.input_label(input).to_string(),
+                };
+
+                if unresolved {
+                    format!("{}\n← {} (waiting for a match)", output_name, input)
+                } else if quarantined {
+                    format!("{}\n🔒 {} (awaiting approval)", output_name, input)
+                } else if view_slot.maintenance {
+                    format!("{}\n🔧 {} (maintenance)", output_name, input)
+                } else if connection == Some(ConnectionState::Stale) {
+                    format!("{}\n← {} (no frames)", output_name, input)
+                } else if connection == Some(ConnectionState::Disconnected) {
+                    format!("{}\n← {} (reconnecting…)", output_name, input)
+                } else if is_placeholder {
+                    format!("{}\n← {} (no feed)", output_name, input)
+                } else if audio_levels.is_some() {
+                    format!("{}\n← {} (audio only)", output_name, input)
+                } else {
+                    format!("{}\n← {}", output_name, input)
+                }
+            } else {
+                format!("{}\n(No input)", output_name)
+            };
+
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                label_text,
+                egui::FontId::proportional(14.0),
+                egui::Color32::WHITE,
+            );
+
+            // Tally the source reports from elsewhere (e.g. the main switcher), bottom left
+            let remote_tally = self
+                .shown_input(view_slot)
+                .and_then(|input| self.available_sources.iter().find(|s| s.matches(&input)))
+                .and_then(|source| source.tally);
+            if let Some(tally) = remote_tally.filter(|t| !t.is_off()) {
+                let (text, color) = if tally.program {
+                    ("● LIVE", egui::Color32::from_rgb(230, 50, 50))
+                } else {
+                    ("● PVW", egui::Color32::from_rgb(60, 200, 80))
+                };
+                let galley = ui.painter().layout_no_wrap(
+                    text.to_string(),
+                    egui::FontId::proportional(12.0),
+                    color,
+                );
+                let overlay = egui::Rect::from_min_size(
+                    egui::pos2(rect.min.x + 6.0, rect.max.y - galley.size().y - 10.0),
+                    galley.size() + egui::vec2(8.0, 4.0),
+                );
+                ui.painter()
+                    .rect_filled(overlay, 3.0, egui::Color32::from_black_alpha(180));
+                ui.painter()
+                    .galley(overlay.min + egui::vec2(4.0, 2.0), galley, color);
+            }
+
+            // Measured latency in the top right corner
+            let latency = self
+                .shown_input(view_slot)
+                .filter(|_| self.show_latency)
+                .and_then(|input| self.receivers.get(&input))
+                .and_then(|receiver| receiver.stream_info().latency_ms);
+            if let Some(latency) = latency {
+                let color = if latency > LATENCY_BAD_MS {
+                    egui::Color32::from_rgb(230, 60, 60)
+                } else if latency > LATENCY_WARN_MS {
+                    egui::Color32::from_rgb(240, 170, 40)
+                } else {
+                    egui::Color32::from_rgb(80, 210, 90)
+                };
+                let galley = ui.painter().layout_no_wrap(
+                    format!("{:.0} ms", latency),
+                    egui::FontId::monospace(12.0),
+                    color,
+                );
+                let overlay = egui::Rect::from_min_size(
+                    egui::pos2(rect.max.x - galley.size().x - 14.0, rect.min.y + 6.0),
+                    galley.size() + egui::vec2(8.0, 4.0),
+                );
+                ui.painter()
+                    .rect_filled(overlay, 3.0, egui::Color32::from_black_alpha(180));
+                ui.painter()
+                    .galley(overlay.min + egui::vec2(4.0, 2.0), galley, color);
+            }
+
+            // Captions the source sends, in the lower third
+            if let Some(caption) = self.slot_caption(view_slot) {
+                let galley = ui.painter().layout(
+                    caption.to_string(),
+                    egui::FontId::proportional(16.0),
+                    egui::Color32::WHITE,
+                    rect.width() - 32.0,
+                );
+                let overlay = egui::Rect::from_center_size(
+                    egui::pos2(
+                        rect.center().x,
+                        rect.max.y - rect.height() / 6.0 - galley.size().y / 2.0,
+                    ),
+                    galley.size() + egui::vec2(12.0, 6.0),
+                );
+                ui.painter()
+                    .rect_filled(overlay, 2.0, egui::Color32::from_black_alpha(200));
+                ui.painter().galley(
+                    overlay.min + egui::vec2(6.0, 3.0),
+                    galley,
+                    egui::Color32::WHITE,
+                );
+            }
+
+            // Label the slides source with the current ProPresenter slide
+            let slide_status = view_slot
+                .assigned_input
+                .as_deref()
+                .filter(|input| self.is_slides_source(input))
+                .and_then(|_| self.propresenter.as_ref()?.status());
+            let response = match slide_status {
+                Some(status) => {
+                    let font = egui::FontId::proportional(13.0);
+                    let galley =
+                        ui.painter()
+                            .layout_no_wrap(status.label(), font, egui::Color32::WHITE);
+                    let overlay = egui::Rect::from_min_max(
+                        egui::pos2(rect.min.x, rect.max.y - galley.size().y - 12.0),
+                        rect.max,
+                    );
+                    ui.painter()
+                        .rect_filled(overlay, 4.0, egui::Color32::from_black_alpha(180));
+                    ui.painter().galley(
+                        overlay.min + egui::vec2(8.0, 6.0),
+                        galley,
+                        egui::Color32::WHITE,
+                    );
+                    if status.next_text.is_empty() {
+                        response
+                    } else {
+                        response.on_hover_text(format!("Next: {}", status.next_text))
+                    }
+                }
+                None => response,
+            };
+
+            // Annotations are left out of recorded frames unless burned in
+            let output = view_slot.output_name.clone();
+            if self.telestrator.burn_in || !self.screenshot_pending {
+                self.telestrator.paint(ui.painter(), rect, &output);
+            }
+
+            if self.identify_until.is_some() {
+                draw_identification(ui.painter(), rect, i + 1, &output);
+            }
+
+            if self.telestrator.active {
+                if let Some(pos) = response.interact_pointer_pos() {
+                    if response.drag_started() {
+                        self.telestrator.begin(&output, rect, pos);
+                    } else if response.dragged() {
+                        self.telestrator.drag_to(rect, pos);
+                    }
+                }
+                if response.drag_stopped() {
+                    self.telestrator.finish();
+                }
+                continue;
+            }
+
+            // A KVM slot forwards every click instead of opening menus or selecting
+            if self
+                .kvm
+                .as_ref()
+                .is_some_and(|focus| focus.output == output)
+            {
+                let video = texture
+                    .as_ref()
+                    .map_or(rect, |texture| fit_rect(rect, texture.aspect_ratio()));
+                self.forward_kvm(ui.ctx(), video);
+                ui.painter().rect_stroke(
+                    rect,
+                    4.0,
+                    egui::Stroke::new(3.0, egui::Color32::from_rgb(60, 160, 255)),
+                );
+                ui.painter().text(
+                    rect.left_top() + egui::vec2(8.0, 8.0),
+                    egui::Align2::LEFT_TOP,
+                    "⌨ KVM (Ctrl+Shift+K to release)",
+                    egui::FontId::proportional(13.0),
+                    egui::Color32::from_rgb(60, 160, 255),
+                );
+                continue;
+            }
+
+            let has_kvm = self.slot_has_kvm(&output);
+            response.context_menu(|ui| {
+                let listening = self.audio_output.as_ref() == Some(&output);
+                if ui.selectable_label(listening, "🔊 Listen").clicked() {
+                    self.audio_output = (!listening).then(|| output.clone());
+                    ui.close_menu();
+                }
+                let mut captions = self.slot_captions.contains(&output);
+                let response = ui.checkbox(&mut captions, "💬 Captions");
+                if self.help.attach(response, "slot.captions").changed() {
+                    if captions {
+                        self.slot_captions.insert(output.clone());
+                    } else {
+                        self.slot_captions.remove(&output);
+                    }
+                }
+                let role_menu = ui.menu_button("🎬 Role", |ui| {
+                    let current = self.slot_roles.get(&output).copied();
+                    let mut role = current;
+                    ui.radio_value(&mut role, None, "None");
+                    let aux_count = self.layout.view_count().saturating_sub(2).max(1) as u8;
+                    let choices = [SlotRole::Program, SlotRole::Preview]
+                        .into_iter()
+                        .chain((1..=aux_count).map(SlotRole::Aux));
+                    for choice in choices {
+                        ui.radio_value(&mut role, Some(choice), choice.to_string());
+                    }
+                    if role != current {
+                        // A role belongs to one output; taking it moves it here
+                        self.slot_roles.retain(|_, r| Some(*r) != role);
+                        match role {
+                            Some(role) => self.slot_roles.insert(output.clone(), role),
+                            None => self.slot_roles.remove(&output),
+                        };
+                        ui.close_menu();
+                    }
+                });
+                self.help.attach(role_menu.response, "slot.role");
+                ui.separator();
+
+                if has_kvm {
+                    if ui.button("⌨ Control (KVM)").clicked() {
+                        self.start_kvm(&output);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                }
+
+                ui.label("Bandwidth");
+                let mut bandwidth = self.slot_bandwidth.get(&output).copied();
+                ui.radio_value(&mut bandwidth, None, "Default");
+                for option in ReceiveBandwidth::all() {
+                    ui.radio_value(&mut bandwidth, Some(option), option.name());
+                }
+                if bandwidth != self.slot_bandwidth.get(&output).copied() {
+                    match bandwidth {
+                        Some(bandwidth) => self.slot_bandwidth.insert(output.clone(), bandwidth),
+                        None => self.slot_bandwidth.remove(&output),
+                    };
+                    ui.close_menu();
+                }
+
+                ui.separator();
+                ui.label("Latency");
+                let mut latency = self.slot_latency.get(&output).map(|t| t.latency);
+                ui.radio_value(&mut latency, None, "Default");
+                for option in LatencyMode::all() {
+                    ui.radio_value(&mut latency, Some(option), option.name());
+                }
+                if latency != self.slot_latency.get(&output).map(|t| t.latency) {
+                    match latency {
+                        Some(latency) => self.slot_latency.insert(
+                            output.clone(),
+                            ReceiverTuning {
+                                latency,
+                                ..self.default_latency
+                            },
+                        ),
+                        None => self.slot_latency.remove(&output),
+                    };
+                    ui.close_menu();
+                }
+                let mut delay = self.slot_delay.get(&output).copied().unwrap_or(0);
+                let response = ui.add(
+                    egui::Slider::new(&mut delay, 0..=MAX_DELAY_FRAMES)
+                        .text("Delay")
+                        .suffix(" frames"),
+                );
+                if self.help.attach(response, "slot.delay").changed() {
+                    if delay == 0 {
+                        self.slot_delay.remove(&output);
+                    } else {
+                        self.slot_delay.insert(output.clone(), delay);
+                    }
+                }
+                let shown = self.shown_input(&self.view_slots[i]).and_then(|input| {
+                    Some((self.receivers.source(&input)?, self.receivers.get(&input)?))
+                });
+                if let Some((source, receiver)) = shown {
+                    ui.separator();
+                    if ui.button("📷 Snapshot").clicked() {
+                        self.save_snapshot(&output, receiver);
+                        ui.close_menu();
+                    }
+                    let info = receiver.stream_info();
+                    if info.has_video() || info.has_audio() {
+                        ui.separator();
+                        ui.weak(info.to_string());
+                    }
+                    let dropped = receiver.dropped_frames();
+                    if dropped > 0 {
+                        ui.weak(format!("{} frames dropped", dropped));
+                    }
+                    let local_interface = source.address().and_then(|peer| {
+                        *self
+                            .interface_cache
+                            .entry(peer)
+                            .or_insert_with(|| local_interface_for(peer))
+                    });
+                    let connection = ConnectionInfo::new(
+                        source,
+                        receiver,
+                        self.transport.for_source(&source.name),
+                        local_interface,
+                    );
+                    let drawer = ui.collapsing("ℹ Connection", |ui| {
+                        egui::Grid::new(format!("connection_{}", output))
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                for (label, value) in connection.rows() {
+                                    ui.weak(label);
+                                    ui.monospace(value);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                    self.help.attach(drawer.header_response, "slot.connection");
+                }
+            });
+
+            // Handle click
+            if response.clicked() {
+                self.selected_view_idx = Some(i);
+                // Toggle selection
+                self.view_slots[i].selected = !self.view_slots[i].selected;
+            }
+        }
+
+        // Badge listing routed outputs the layout has no window for
+        let hidden = self.hidden_outputs();
+        if !hidden.is_empty() {
+            let text = format!("⚠ Hidden by layout: {}", hidden.join(", "));
+            let galley = ui.painter().layout(
+                text,
+                egui::FontId::proportional(13.0),
+                egui::Color32::from_rgb(230, 160, 40),
+                available_rect.width() / 2.0,
+            );
+            let badge = egui::Rect::from_min_size(
+                egui::pos2(
+                    available_rect.max.x - galley.size().x - 16.0,
+                    available_rect.min.y + 4.0,
+                ),
+                galley.size() + egui::vec2(12.0, 8.0),
+            );
+            ui.painter()
+                .rect_filled(badge, 4.0, egui::Color32::from_black_alpha(200));
+            ui.painter().galley(
+                badge.min + egui::vec2(6.0, 4.0),
+                galley,
+                egui::Color32::from_rgb(230, 160, 40),
+            );
+        }
+    }
+
+    /// Whether an input (name or URL) is the configured ProPresenter source
+    fn is_slides_source(&self, input: &str) -> bool {
+        input == self.slides_source
+            || self
+                .available_sources
+                .iter()
+                .any(|s| s.url == input && s.name == self.slides_source)
+    }
+
+    /// Get the texture for a file input, reloading it when the file changed
+    fn file_texture(&mut self, ctx: &egui::Context, input: &str) -> Option<egui::TextureHandle> {
+        if !input.starts_with(FILE_URL_PREFIX) {
+            return None;
+        }
+        let file = self.watch_folder.as_ref()?.get_by_url(input)?;
+
+        if let Some((version, texture)) = self.file_textures.get(input) {
+            if *version == file.version {
+                return Some(texture.clone());
+            }
+        }
+
+        let image = match image::open(&file.path) {
+            Ok(image) => image.to_rgba8(),
+            Err(e) => {
+                error!("Failed to load {:?}: {}", file.path, e);
+                return None;
+            }
+        };
+        let size = [image.width() as usize, image.height() as usize];
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+        let texture = ctx.load_texture(input, color_image, egui::TextureOptions::LINEAR);
+
+        self.file_textures
+            .insert(input.to_string(), (file.version, texture.clone()));
+        Some(texture)
+    }
+
+    /// The test pattern an input (name or URL) shows, if it is one
+    fn pattern_of(&self, input: &str) -> Option<TestPattern> {
+        if input == TEST_PATTERN_NAME {
+            self.test_pattern
+        } else {
+            TestPattern::from_url(input)
+        }
+    }
+
+    /// Get the texture for a test pattern, redrawing moving patterns every frame
+    fn pattern_texture(&mut self, ctx: &egui::Context, input: &str) -> Option<egui::TextureHandle> {
+        const SIZE: (u32, u32) = (1280, 720);
+
+        let pattern = self.pattern_of(input)?;
+        let elapsed = self.pattern_started.elapsed();
+        let frame = if pattern.is_moving() {
+            ctx.request_repaint();
+            (elapsed.as_secs_f64() * MOTION_FPS as f64) as u64
+        } else {
+            0
+        };
+        let image = || frame_image(&pattern.render(SIZE.0, SIZE.1, elapsed));
+
+        if let Some((shown, texture)) = self.pattern_textures.get_mut(&pattern) {
+            if *shown != frame {
+                texture.set(image(), egui::TextureOptions::LINEAR);
+                *shown = frame;
+            }
+            return Some(texture.clone());
+        }
+
+        let texture = ctx.load_texture(
+            format!("pattern:{}", pattern),
+            image(),
+            egui::TextureOptions::LINEAR,
+        );
+        self.pattern_textures
+            .insert(pattern, (frame, texture.clone()));
+        Some(texture)
+    }
+
+    /// Get the texture for a clip, starting its player when it comes on screen
+    fn clip_texture(&mut self, ctx: &egui::Context, input: &str) -> Option<egui::TextureHandle> {
+        let clip = clip::find(&self.clips, input)?.clone();
+        self.clips_shown.insert(clip.name.clone());
+        let player = self
+            .clip_players
+            .entry(clip.name.clone())
+            .or_insert_with(|| match ClipPlayer::start(&clip) {
+                Ok(player) => Some(player),
+                Err(e) => {
+                    error!("Failed to play clip {}: {}", clip.name, e);
+                    None
+                }
+            })
+            .as_ref()?;
+        if !player.is_finished() {
+            ctx.request_repaint();
+        }
+
+        let shown = self.clip_textures.get(&clip.name).map_or(0, |(s, _)| *s);
+        if let Some((sequence, frame)) = player.frame_after(shown) {
+            match self.clip_textures.get_mut(&clip.name) {
+                Some((shown, texture)) => {
+                    texture.set(frame_image(&frame), egui::TextureOptions::LINEAR);
+                    *shown = sequence;
+                }
+                None => {
+                    let texture = ctx.load_texture(
+                        format!("clip:{}", clip.name),
+                        frame_image(&frame),
+                        egui::TextureOptions::LINEAR,
+                    );
+                    self.clip_textures
+                        .insert(clip.name.clone(), (sequence, texture));
+                }
+            }
+        }
+        self.clip_textures.get(&clip.name).map(|(_, t)| t.clone())
+    }
+
+    /// Stop the clips no longer on screen, so they start over when shown again
+    fn release_clips(&mut self) {
+        let shown = std::mem::take(&mut self.clips_shown);
+        self.clip_players.retain(|name, _| shown.contains(name));
+        self.clip_textures.retain(|name, _| shown.contains(name));
+    }
+
+    /// Feed frame sync with the frames every receiver has received, and move it
+    /// to the next output frame when due
+    fn update_frame_sync(&mut self) {
+        let Some(frame_sync) = &mut self.frame_sync else {
+            return;
+        };
+        let now = Instant::now();
+        for (source, receiver) in self.receivers.iter() {
+            if let Ok(Some(frame)) = receiver.receive_video_frame() {
+                frame_sync.push(&source.url, frame, now);
+            }
+        }
+        frame_sync.retain(|url| self.receivers.get(url).is_some());
+        frame_sync.advance(now);
+    }
+
+    /// Get the texture showing an input's latest video frame (or its frame-synced
+    /// frame), updating it when a new frame arrived
+    fn video_texture(&mut self, ctx: &egui::Context, input: &str) -> Option<egui::TextureHandle> {
+        let received = self.received_frame(input)?;
+
+        if let Some((sequence, texture)) = self.video_textures.get_mut(input) {
+            if *sequence != received.sequence {
+                texture.set(frame_image(&received.frame), egui::TextureOptions::LINEAR);
+                *sequence = received.sequence;
+            }
+            return Some(texture.clone());
+        }
+
+        let texture = ctx.load_texture(
+            format!("video:{}", input),
+            frame_image(&received.frame),
+            egui::TextureOptions::LINEAR,
+        );
+        self.video_textures
+            .insert(input.to_string(), (received.sequence, texture.clone()));
+        Some(texture)
+    }
+
+    /// Latest frame of an NDI input, through frame sync if enabled
+    fn received_frame(&self, input: &str) -> Option<ReceivedFrame> {
+        match &self.frame_sync {
+            Some(frame_sync) => frame_sync.frame(&self.receivers.source(input)?.url),
+            None => self.receivers.get(input)?.receive_video_frame().ok()?,
+        }
+    }
+
+    /// Get the texture of an NDI input for a slot, held back by the slot's delay
+    fn slot_video_texture(
+        &mut self,
+        ctx: &egui::Context,
+        output: &str,
+        input: &str,
+    ) -> Option<egui::TextureHandle> {
+        let frames = self.slot_delay.get(output).copied().unwrap_or(0);
+        if frames == 0 {
+            self.delay_lines.remove(output);
+            self.delayed_textures.remove(output);
+            return self.video_texture(ctx, input);
+        }
+        let received = self.received_frame(input)?;
+        // Assume 30 fps until the source reports its rate
+        let fps = self
+            .receivers
+            .get(input)
+            .and_then(|receiver| receiver.stream_info().frame_rate())
+            .unwrap_or(30.0);
+        let delay = FrameDelay::frames(frames, fps);
+
+        // A new input starts with an empty delay
+        if self
+            .delay_lines
+            .get(output)
+            .is_none_or(|(shown, _)| shown != input)
+        {
+            self.delayed_textures.remove(output);
+            self.delay_lines.insert(
+                output.to_string(),
+                (input.to_string(), FrameDelay::new(delay)),
+            );
+        }
+        let (_, line) = self.delay_lines.get_mut(output)?;
+        line.set_delay(delay);
+        let (sequence, frame) = line.push(received.sequence, &received.frame, Instant::now());
+
+        if let Some((shown, texture)) = self.delayed_textures.get_mut(output) {
+            if *shown != sequence {
+                texture.set(frame_image(&frame), egui::TextureOptions::LINEAR);
+                *shown = sequence;
+            }
+            return Some(texture.clone());
+        }
+        let texture = ctx.load_texture(
+            format!("delayed:{}", output),
+            frame_image(&frame),
+            egui::TextureOptions::LINEAR,
+        );
+        self.delayed_textures
+            .insert(output.to_string(), (sequence, texture.clone()));
+        Some(texture)
+    }
+
+    /// Draw the split-screen comparison with a draggable vertical wipe
+    fn draw_compare_view(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
+        let Some(compare) = self.compare.as_mut() else {
+            return;
+        };
+
+        let ((left_x, left_w), (right_x, right_w)) = compare.split(rect.min.x, rect.width());
+        let left_rect = egui::Rect::from_min_size(
+            egui::pos2(left_x, rect.min.y),
+            egui::vec2(left_w, rect.height()),
+        );
+        let right_rect = egui::Rect::from_min_size(
+            egui::pos2(right_x, rect.min.y),
+            egui::vec2(right_w, rect.height()),
+        );
+
+        let painter = ui.painter();
+        painter.rect_filled(left_rect, 0.0, egui::Color32::from_rgb(45, 50, 70));
+        painter.rect_filled(right_rect, 0.0, egui::Color32::from_rgb(70, 50, 45));
+        painter.text(
+            left_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            format!("A: {}", compare.left),
+            egui::FontId::proportional(14.0),
+            egui::Color32::WHITE,
+        );
+        painter.text(
+            right_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            format!("B: {}", compare.right),
+            egui::FontId::proportional(14.0),
+            egui::Color32::WHITE,
+        );
+        painter.rect_stroke(
+            rect,
+            4.0,
+            egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 100, 120)),
+        );
+
+        // Divider handle
+        let handle = egui::Rect::from_center_size(
+            egui::pos2(right_x, rect.center().y),
+            egui::vec2(12.0, rect.height()),
+        );
+        let response = ui
+            .allocate_rect(handle, egui::Sense::drag())
+            .on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+        if response.dragged() {
+            compare.drag_by(response.drag_delta().x, rect.width());
+        }
+
+        ui.painter().line_segment(
+            [
+                egui::pos2(right_x, rect.min.y),
+                egui::pos2(right_x, rect.max.y),
+            ],
+            egui::Stroke::new(3.0, egui::Color32::YELLOW),
+        );
+    }
+
+    /// Draw the compare controls in the routing panel
+    fn draw_compare_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Compare Sources");
+        ui.separator();
+
+        let selected_source = self
+            .selected_source_idx
+            .and_then(|idx| self.available_sources.get(idx))
+            .map(|s| s.name.clone());
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(selected_source.is_some(), egui::Button::new("Set A"))
+                .clicked()
+            {
+                self.compare_left = selected_source.clone();
+            }
+            ui.label(self.compare_left.as_deref().unwrap_or("-"));
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(selected_source.is_some(), egui::Button::new("Set B"))
+                .clicked()
+            {
+                self.compare_right = selected_source.clone();
+            }
+            ui.label(self.compare_right.as_deref().unwrap_or("-"));
+        });
+
+        ui.horizontal(|ui| {
+            if let Some(compare) = self.compare.as_mut() {
+                if ui.button("⇄ Swap").clicked() {
+                    compare.swap();
+                }
+                if ui.button("Stop Compare").clicked() {
+                    self.compare = None;
+                }
+            } else {
+                let can_compare = self.compare_left.is_some() && self.compare_right.is_some();
+                if ui
+                    .add_enabled(can_compare, egui::Button::new("Start Compare"))
+                    .clicked()
+                {
+                    if let (Some(left), Some(right)) =
+                        (self.compare_left.clone(), self.compare_right.clone())
+                    {
+                        info!("Comparing {} | {}", left, right);
+                        self.compare = Some(CompareView::new(left, right));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Draw the monitoring wall: every discovered source in its own slot, read-only
+    fn draw_wall_view(&mut self, ui: &mut egui::Ui) {
+        let available_rect = ui.available_rect_before_wrap();
+        let rects = self.layout.calculate_view_rects();
+        let slots = self.wall_slots();
+
+        if slots.is_empty() {
+            ui.painter().text(
+                available_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Waiting for NDI sources...",
+                egui::FontId::proportional(18.0),
+                egui::Color32::GRAY,
+            );
+            return;
+        }
+
+        for (slot, (x, y, w, h)) in slots.iter().zip(rects.iter()) {
+            let rect = egui::Rect::from_min_size(
+                available_rect.min
+                    + egui::vec2(available_rect.width() * x, available_rect.height() * y),
+                egui::vec2(
+                    available_rect.width() * w - 4.0,
+                    available_rect.height() * h - 4.0,
+                ),
+            );
+
+            self.background.paint(ui.ctx(), ui.painter(), rect);
+            ui.painter().rect_stroke(
+                rect,
+                4.0,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 100, 120)),
+            );
+
+            let url = slot.assigned_input.as_deref().unwrap_or_default();
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                format!("{}\n{}", slot.output_name, url),
+                egui::FontId::proportional(14.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+
+    /// Draw the wall page selector in the menu bar
+    fn draw_wall_pager(&mut self, ui: &mut egui::Ui) {
+        let source_count = self.available_sources.len();
+        let per_page = self.layout.view_count();
+        let pages = MonitoringWall::page_count(source_count, per_page);
+
+        if ui.button("◀").clicked() {
+            self.wall.previous_page(source_count, per_page);
+        }
+        ui.label(format!(
+            "Wall page {}/{} ({} sources)",
+            self.wall.page(source_count, per_page) + 1,
+            pages,
+            source_count
+        ));
+        if ui.button("▶").clicked() {
+            self.wall.next_page(source_count, per_page);
+        }
+    }
+
+    /// Update push-to-talk state and forward captured microphone audio
+    fn update_talkback(&mut self, ctx: &egui::Context, button_held: bool) {
+        if !self.talkback_enabled {
+            return;
+        }
+        if self.training.is_some() {
+            self.talkback.release();
+            return;
+        }
+
+        let key = self
+            .shortcuts
+            .get(&Shortcut::Talkback)
+            .copied()
+            .or(self.talkback_key);
+        let key_held = match key {
+            Some(key) => !ctx.wants_keyboard_input() && ctx.input(|i| i.key_down(key)),
+            None => false,
+        };
+
+        if key_held || button_held {
+            if self.mic.is_none() {
+                match MicCapture::start(self.talkback_device.as_deref()) {
+                    Ok(mic) => self.mic = Some(mic),
+                    Err(e) => {
+                        error!("Failed to start talkback microphone: {}", e);
+                        self.talkback_enabled = false;
+                        return;
+                    }
+                }
+            }
+            self.talkback.press();
+        } else {
+            self.talkback.release();
+        }
+
+        // Drain captured audio; it is only delivered while talking
+        if let Some((_, rx)) = &self.mic {
+            while let Ok(chunk) = rx.try_recv() {
+                self.talkback.send(&chunk);
+            }
+        }
+    }
+
+    /// Draw the talkback panel. Returns true while the talk button is held.
+    fn draw_talkback_panel(&mut self, ui: &mut egui::Ui) -> bool {
+        if !self.talkback_enabled {
+            ui.label("Talkback is disabled in configuration");
+            return false;
+        }
+
+        let talk_label = if self.talkback.is_talking() {
+            "🎙 TALKING"
+        } else {
+            "🎙 Hold to Talk"
+        };
+        let held = ui
+            .add(egui::Button::new(talk_label).min_size(egui::vec2(160.0, 40.0)))
+            .is_pointer_button_down_on();
+
+        if let Some(key) = self.talkback_key {
+            ui.label(format!("Push-to-talk key: {}", key.name()));
+        }
+
+        ui.separator();
+        ui.label("Destinations");
+
+        let destinations: Vec<(String, bool)> = self
+            .talkback
+            .destinations()
+            .iter()
+            .map(|d| (d.name.clone(), d.enabled))
+            .collect();
+
+        if destinations.is_empty() {
+            ui.label("No talkback destinations configured");
+        }
+
+        for (name, mut enabled) in destinations {
+            if ui.checkbox(&mut enabled, &name).changed() {
+                self.talkback.set_enabled(&name, enabled);
+            }
+        }
+
+        held
+    }
+
+    /// Send the drafted intercom message in the background
+    fn send_intercom(&mut self, kind: MessageKind) {
+        let Some(intercom) = &self.intercom else {
+            return;
+        };
+        if self.training.is_some() {
+            return;
+        }
+        let text = std::mem::take(&mut self.intercom_draft);
+        if text.trim().is_empty() {
+            return;
+        }
+
+        if let Err(e) = intercom.send(kind, &text) {
+            error!("Failed to send intercom message: {:#}", e);
+            // Keep the text to send again once reconnected
+            self.intercom_draft = text;
+        }
+    }
+
+    /// Play intercom audio, opening the output on first use
+    fn play_intercom_audio(&mut self, chunk: &AudioChunk) {
+        if self.intercom_output.is_none() && !self.intercom_output_failed {
+            match AudioPlayback::start(self.playback_device.as_deref()) {
+                Ok(playback) => self.intercom_output = Some(playback),
+                Err(e) => {
+                    error!("Failed to open audio output for the intercom: {}", e);
+                    self.intercom_output_failed = true;
+                }
+            }
+        }
+        if let Some(output) = &self.intercom_output {
+            output.play(chunk);
+        }
+    }
+
+    /// Draw the intercom chat panel
+    fn draw_intercom_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(intercom) = self.intercom.clone() else {
+            return;
+        };
+
+        let alerts = intercom.unread_alerts();
+        let mut header = if alerts > 0 {
+            format!("💬 Intercom ({} new)", alerts)
+        } else {
+            "💬 Intercom".to_string()
+        };
+        if !intercom.is_connected() {
+            header.push_str(" - reconnecting");
+        }
+
+        egui::CollapsingHeader::new(header)
+            .id_source("intercom_panel")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(150.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for message in intercom.history() {
+                            let text = format!(
+                                "[{}] {}: {}",
+                                message.time_of_day(),
+                                message.from,
+                                message.text
+                            );
+                            let rich = match message.kind {
+                                MessageKind::Cue => egui::RichText::new(format!("CUE {}", text))
+                                    .strong()
+                                    .color(egui::Color32::from_rgb(255, 200, 0)),
+                                MessageKind::Chat if message.mentions(intercom.operator()) => {
+                                    egui::RichText::new(text).color(egui::Color32::LIGHT_BLUE)
+                                }
+                                MessageKind::Chat => egui::RichText::new(text),
+                            };
+                            ui.label(rich);
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    let response = ui.text_edit_singleline(&mut self.intercom_draft);
+                    let enter =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Send").clicked() || enter {
+                        self.send_intercom(MessageKind::Chat);
+                    }
+                    if ui.button("📣 Cue").clicked() {
+                        self.send_intercom(MessageKind::Cue);
+                    }
+                });
+
+                if alerts > 0 && ui.button("Mark read").clicked() {
+                    intercom.clear_alerts();
+                    self.intercom_alerts_seen = 0;
+                }
+            });
+    }
+
+    /// Save a still of a slot's latest frame to the recording directory
+    fn save_snapshot(&self, output: &str, receiver: &NdiReceiver) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let path = self.recording_config.directory.join(format!(
+            "snapshot_{}_{}.png",
+            RecordingTarget::Output(output.to_string()).file_stem(),
+            timestamp
+        ));
+
+        let saved = receiver.snapshot().and_then(|image| {
+            std::fs::create_dir_all(&self.recording_config.directory)?;
+            image.save(&path)?;
+            Ok(())
+        });
+        match saved {
+            Ok(()) => info!("Saved snapshot of {} to {:?}", output, path),
+            Err(e) => error!("Failed to save snapshot of {}: {}", output, e),
+        }
+    }
+
+    /// Recall a salvo, asking the operator first if its preflight finds problems
+    fn start_salvo_recall(&mut self, name: &str) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        match control.preflight(name) {
+            Ok(preflight) if preflight.is_clean() => {
+                self.recall_salvo(name, RecallPolicy::Partial);
+            }
+            Ok(preflight) => self.salvo_preflight = Some(preflight),
+            Err(e) => error!("Failed to check salvo {}: {}", name, e),
+        }
+    }
+
+    /// Blank every output for standby, or restore the routes from before
+    fn set_standby(&mut self, blank: bool) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        let (result, message) = if blank {
+            (control.blank_all(), "All outputs blanked")
+        } else {
+            (control.resume(), "Outputs resumed")
+        };
+        match result {
+            Ok(()) => self
+                .events
+                .info(EventCategory::Routing, "gui", message.to_string()),
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    fn recall_salvo(&mut self, name: &str, policy: RecallPolicy) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        match control.recall_salvo(name, Some(policy)) {
+            Ok(preflight) => {
+                let mut message = format!("Salvo recalled: {}", name);
+                if !preflight.is_clean() {
+                    message.push_str(&format!(
+                        " ({}, {} routes with problems)",
+                        policy.name(),
+                        preflight.issues.len()
+                    ));
+                }
+                self.events.info(EventCategory::Routing, "gui", message);
+            }
+            Err(e) => error!("Failed to recall salvo {}: {}", name, e),
+        }
+    }
+
+    /// Let a quarantined source be routed, remembering it in the configuration file
+    fn approve_source(&mut self, name: &str) {
+        self.discovery.approve(name);
+        self.events.info(
+            EventCategory::System,
+            "gui",
+            format!("Source approved: {}", name),
+        );
+        // Read afresh: the running configuration may include stored routes
+        let saved = Config::from_file(&self.config_path).and_then(|mut config| {
+            config.ndi.approval.approved.insert(name.to_string());
+            config.to_file(&self.config_path)
+        });
+        match saved {
+            Ok(()) => {
+                self.config.ndi.approval.approved.insert(name.to_string());
+            }
+            Err(e) => error!("Failed to save approval of {}: {:#}", name, e),
+        }
+    }
+
+    /// Replace the tags saved for one source (by NDI name), retagging it live
+    fn set_source_tags(&mut self, name: &str, tags: Vec<String>) {
+        // Read afresh: the running configuration may include stored routes
+        let saved = Config::from_file(&self.config_path).and_then(|mut config| {
+            if tags.is_empty() {
+                config.ndi.tags.remove(name);
+            } else {
+                config.ndi.tags.insert(name.to_string(), tags.clone());
+            }
+            config.to_file(&self.config_path)?;
+            Ok(config.ndi.tags)
+        });
+        match saved {
+            Ok(saved) => {
+                self.config.ndi.tags = saved.clone();
+                self.source_tags = saved;
+                self.discovery.set_tags(self.source_tags.clone());
+                self.discovered_sources = self.discovery.get_sources();
+                self.update_sources();
+                self.events.info(
+                    EventCategory::System,
+                    "gui",
+                    format!("Source tags of {} set to: {}", name, tags.join(", ")),
+                );
+            }
+            Err(e) => error!("Failed to save tags of {}: {:#}", name, e),
+        }
+    }
+
+    /// Start recording the selected target
+    fn start_recording(&mut self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let directory = self.recording_config.directory.join(format!(
+            "{}_{}",
+            self.recording_target.file_stem(),
+            timestamp
+        ));
+
+        let encoder: Result<Box<dyn FrameEncoder>> = match self.recording_config.format {
+            RecordingFormat::Mkv => {
+                let path = directory.join(format!("{}.mkv", self.recording_target.file_stem()));
+                FfmpegEncoder::create(path, self.recording_config.fps)
+                    .map(|encoder| Box::new(encoder) as _)
+            }
+            RecordingFormat::Png => {
+                ImageSequenceEncoder::create(&directory).map(|encoder| Box::new(encoder) as _)
+            }
+        };
+        match encoder {
+            Ok(encoder) => {
+                self.recorder = Some(
+                    Recorder::new(
+                        self.recording_target.clone(),
+                        encoder,
+                        self.recording_config.fps,
+                    )
+                    .with_chapters(directory.join(CHAPTERS_FILE)),
+                );
+                self.marker_requests.set_recording(true);
+            }
+            Err(e) => error!("Failed to start recording: {}", e),
+        }
+    }
+
+    /// Drop a marker into the active recording
+    fn add_marker(&mut self, label: Option<String>) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        match recorder.add_marker(label) {
+            Ok(marker) => {
+                self.events.info(
+                    EventCategory::Control,
+                    "recording",
+                    format!(
+                        "Marker '{}' at {}",
+                        marker.label,
+                        format_hms(marker.position)
+                    ),
+                );
+            }
+            Err(e) => error!("Failed to add marker: {}", e),
+        }
+    }
+
+    /// Stop the active recording
+    fn stop_recording(&mut self) {
+        self.marker_requests.set_recording(false);
+        if let Some(recorder) = self.recorder.take() {
+            if let Err(e) = recorder.stop() {
+                error!("Failed to finalize recording: {}", e);
+            }
+        }
+    }
+
+    /// Feed screenshots into the active recording and request the next one
+    fn update_recording(&mut self, ctx: &egui::Context) {
+        for label in self.marker_requests.take() {
+            self.add_marker(label);
+        }
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+
+        let screenshots: Vec<Arc<egui::ColorImage>> = ctx.input(|i| {
+            i.raw
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Screenshot { image, .. } => Some(Arc::clone(image)),
+                    _ => None,
+                })
+                .collect()
+        });
+
+        if !screenshots.is_empty() {
+            self.screenshot_pending = false;
+        }
+        let pixels_per_point = ctx.pixels_per_point();
+        for image in screenshots {
+            let data = image.pixels.iter().flat_map(|p| p.to_array()).collect();
+            let frame = VideoFrame::new(image.size[0] as u32, image.size[1] as u32, data);
+
+            let frame = match recorder.target() {
+                RecordingTarget::Multiview => frame,
+                RecordingTarget::Output(name) => match self.slot_rects.get(name) {
+                    Some(rect) => frame.crop(
+                        (rect.min.x * pixels_per_point) as u32,
+                        (rect.min.y * pixels_per_point) as u32,
+                        (rect.width() * pixels_per_point) as u32,
+                        (rect.height() * pixels_per_point) as u32,
+                    ),
+                    // Output not visible in the current layout
+                    None => continue,
+                },
+            };
+
+            if let Err(e) = recorder.push_frame(frame) {
+                error!("Recording failed: {:#}", e);
+                self.stop_recording();
+                return;
+            }
+        }
+
+        let now = Instant::now();
+        if recorder.is_due(now) {
+            recorder.mark_captured(now);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            self.screenshot_pending = true;
+        }
+        ctx.request_repaint();
+    }
+
+    /// Draw the telestrator panel
+    fn draw_telestrator_panel(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.telestrator.active, "Draw on slots");
+        ui.horizontal(|ui| {
+            for tool in Tool::all() {
+                ui.selectable_value(&mut self.telestrator.tool, tool, tool.name());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Color:");
+            ui.color_edit_button_srgba(&mut self.telestrator.color);
+            ui.add(egui::Slider::new(&mut self.telestrator.stroke_width, 1.0..=12.0).text("Width"));
+        });
+        ui.checkbox(&mut self.telestrator.burn_in, "Show in recordings")
+            .on_hover_text("Otherwise annotations are only visible on this screen");
+
+        ui.separator();
+        let outputs = self.telestrator.annotated_outputs();
+        if outputs.is_empty() {
+            ui.label("No annotations");
+        }
+        for output in outputs {
+            ui.horizontal(|ui| {
+                ui.label(&output);
+                if ui.small_button("↩ Undo").clicked() {
+                    self.telestrator.undo(&output);
+                }
+                if ui.small_button("🗑 Clear").clicked() {
+                    self.telestrator.clear(&output);
+                }
+            });
+        }
+        if ui.button("Clear All").clicked() {
+            self.telestrator.clear_all();
+        }
+    }
+
+    /// Draw the recordings panel
+    fn draw_recordings_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(recorder) = &self.recorder {
+            ui.label(
+                egui::RichText::new(format!("⏺ REC {}", format_hms(recorder.elapsed())))
+                    .color(egui::Color32::RED)
+                    .strong(),
+            );
+            ui.label(format!(
+                "{} ({} frames)",
+                recorder.target(),
+                recorder.frames()
+            ));
+            if recorder.dropped() > 0 {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("{} frames dropped (encoder behind)", recorder.dropped()),
+                );
+            }
+
+            let mut add_marker = false;
+            ui.horizontal(|ui| {
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut self.marker_label)
+                        .hint_text("Marker label")
+                        .desired_width(140.0),
+                );
+                add_marker = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let response = ui.button("📍 Marker");
+                add_marker |= response.clicked();
+                self.help.attach(response, "recording.marker");
+            });
+            if add_marker {
+                let label = std::mem::take(&mut self.marker_label);
+                self.add_marker(Some(label));
+            }
+            if let Some(recorder) = &self.recorder {
+                for marker in recorder.markers().iter().rev().take(5) {
+                    ui.weak(format!("{}  {}", format_hms(marker.position), marker.label));
+                }
+            }
+
+            if ui.button("⏹ Stop Recording").clicked() {
+                self.stop_recording();
+            }
+            return;
+        }
+
+        ui.label("Record:");
+        egui::ComboBox::from_id_source("recording_target")
+            .selected_text(self.recording_target.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    &mut self.recording_target,
+                    RecordingTarget::Multiview,
+                    RecordingTarget::Multiview.to_string(),
+                );
+                for slot in &self.view_slots {
+                    let target = RecordingTarget::Output(slot.output_name.clone());
+                    let label = target.to_string();
+                    ui.selectable_value(&mut self.recording_target, target, label);
+                }
+            });
+
+        ui.label(format!(
+            "Directory: {}",
+            self.recording_config.directory.display()
+        ));
+        ui.label(format!("Rate: {} fps", self.recording_config.fps));
+
+        if ui.button("⏺ Start Recording").clicked() {
+            self.start_recording();
+        }
+    }
+
+    /// Name to show for an input: the source's alias if it has one
+    fn input_label<'a>(&'a self, input: &'a str) -> &'a str {
+        self.available_sources
+            .iter()
+            .find(|s| s.matches(input))
+            .map_or(input, |s| s.display_name())
+    }
+
+    /// Latest metadata of an input (name or URL)
+    fn input_metadata(&self, input: &str) -> Option<SourceMetadata> {
+        let source = self.available_sources.iter().find(|s| s.matches(input))?;
+        self.metadata.get(&source.name)
+    }
+
+    /// Caption to show over a slot, if the slot shows captions and its source sent one lately
+    fn slot_caption(&self, slot: &ViewSlot) -> Option<&str> {
+        if !self.slot_captions.contains(&slot.output_name) {
+            return None;
+        }
+        let input = self.shown_input(slot)?;
+        let source = self.available_sources.iter().find(|s| s.matches(&input))?;
+        self.captions
+            .get(&source.name)
+            .filter(|(_, since)| since.elapsed() < CAPTION_HOLD)
+            .map(|(caption, _)| caption.as_str())
+    }
+
+    /// Whether the input shown in a slot accepts mouse and keyboard (NDI KVM)
+    fn slot_has_kvm(&self, output: &str) -> bool {
+        if self.training.is_some() {
+            return false;
+        }
+        self.view_slots
+            .iter()
+            .find(|slot| slot.output_name == output)
+            .and_then(|slot| self.shown_input(slot))
+            .filter(|input| self.receivers.get(input).is_some())
+            .and_then(|input| self.input_metadata(&input))
+            .is_some_and(|metadata| metadata.kvm)
+    }
+
+    /// Send KVM events to the source shown in a slot
+    fn send_kvm(&self, output: &str, events: &[KvmEvent]) {
+        let Some(receiver) = self
+            .view_slots
+            .iter()
+            .find(|slot| slot.output_name == output)
+            .and_then(|slot| self.shown_input(slot))
+            .and_then(|input| self.receivers.get(&input))
+        else {
+            return;
+        };
+        for event in events {
+            if let Err(e) = receiver.send_metadata(event.to_xml()) {
+                error!("Failed to send KVM input: {}", e);
+                return;
+            }
+        }
+    }
+
+    /// Forward this frame's mouse and keyboard input from the focused slot
+    fn forward_kvm(&mut self, ctx: &egui::Context, video: egui::Rect) {
+        // Ctrl+Shift+K hands mouse and keyboard back to RusTV
+        if ctx.input_mut(|i| {
+            i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::K)
+        }) {
+            self.end_kvm();
+            return;
+        }
+        let input = ctx.input(|i| i.events.clone());
+        let Some(focus) = self.kvm.as_mut() else {
+            return;
+        };
+        let events = focus.translate(&input, video);
+        let output = focus.output.clone();
+        self.send_kvm(&output, &events);
+    }
+
+    /// Start forwarding a slot's mouse and keyboard to its source
+    fn start_kvm(&mut self, output: &str) {
+        self.end_kvm();
+        self.kvm = Some(KvmFocus::new(output.to_string()));
+        self.events.info(
+            EventCategory::Control,
+            "gui",
+            format!("KVM control started on {}", output),
+        );
+    }
+
+    /// Stop forwarding, releasing any keys and buttons still held remotely
+    fn end_kvm(&mut self) {
+        let Some(mut focus) = self.kvm.take() else {
+            return;
+        };
+        let events = focus.release();
+        self.send_kvm(&focus.output, &events);
+        self.events.info(
+            EventCategory::Control,
+            "gui",
+            format!("KVM control ended on {}", focus.output),
+        );
+    }
+
+    /// Re-place output windows when displays are plugged in or unplugged
+    fn update_displays(&mut self, ctx: &egui::Context) {
+        let Some(watcher) = &self.display_watcher else {
+            return;
+        };
+        let found = watcher
+            .displays()
+            .unwrap_or_else(|| Display::from_viewport(ctx).into_iter().collect());
+        if found == self.displays {
+            return;
+        }
+
+        for change in displays::changes(&self.displays, &found) {
+            let message = match change {
+                DisplayChange::Connected(display) => {
+                    format!("Display connected: {}", display.label())
+                }
+                DisplayChange::Disconnected(display) => {
+                    format!("Display disconnected: {}", display.label())
+                }
+            };
+            info!("{}", message);
+            self.events.info(EventCategory::System, "gui", message);
+        }
+
+        // Tell the operator about every output window that moved, closed or came back
+        let before = displays::place(&self.output_windows, &self.displays);
+        let after = displays::place(&self.output_windows, &found);
+        let mut notices = Vec::new();
+        for ((window, old), (_, new)) in before.iter().zip(&after) {
+            let message = match (old, new) {
+                (Some(old), None) => format!(
+                    "{} window closed: {} was disconnected",
+                    window.output,
+                    old.label()
+                ),
+                (None, Some(new)) => format!("{} window placed on {}", window.output, new.label()),
+                (Some(old), Some(new)) if old.connector != new.connector => format!(
+                    "{} window moved from {} to {}",
+                    window.output,
+                    old.label(),
+                    new.label()
+                ),
+                _ => continue,
+            };
+            let severity = if new.is_some() {
+                Severity::Info
+            } else {
+                Severity::Warning
+            };
+            self.events.record(
+                Event::new(severity, EventCategory::System, message.clone()).with_actor("gui"),
+            );
+            notices.push(message);
+        }
+        if !notices.is_empty() {
+            self.display_notice =
+                Some((Instant::now() + DISPLAY_NOTICE_DURATION, notices.join("\n")));
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+        self.displays = found;
+    }
+
+    /// Put output window displays to sleep while every output is blanked, and wake them on resume
+    fn update_display_power(&mut self) {
+        let blanked = self.router.lock().unwrap().is_blanked();
+        if blanked == self.displays_asleep {
+            return;
+        }
+        self.displays_asleep = blanked;
+
+        let state = if blanked { "asleep" } else { "awake" };
+        let mut dpms_sent = false;
+        let mut controlled = Vec::new();
+        for (window, display) in displays::place(&self.output_windows, &self.displays) {
+            // DPMS switches every display at once
+            if window.power == DisplayPower::Dpms && std::mem::replace(&mut dpms_sent, true) {
+                controlled.push(window.output.clone());
+                continue;
+            }
+            match window.power.command(display, !blanked) {
+                Ok(Some(args)) => {
+                    power::run(args, format!("{} {}", window.output, state));
+                    controlled.push(window.output.clone());
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Can't switch {} {}: {:#}", window.output, state, e),
+            }
+        }
+        if !controlled.is_empty() {
+            self.events.info(
+                EventCategory::System,
+                "gui",
+                format!("Displays {}: {}", state, controlled.join(", ")),
+            );
+        }
+    }
+
+    /// Show each output window fullscreen on its display (windows whose display is missing stay closed)
+    fn show_output_windows(&mut self, ctx: &egui::Context) {
+        let placed: Vec<(String, Display)> = displays::place(&self.output_windows, &self.displays)
+            .into_iter()
+            .filter_map(|(window, display)| Some((window.output.clone(), display?.clone())))
+            .collect();
+        // Displays report physical pixels; viewports are placed in points
+        let scale = ctx.native_pixels_per_point().unwrap_or(1.0);
+
+        for (output, display) in placed {
+            let builder = egui::ViewportBuilder::default()
+                .with_title(format!("RusTV - {}", output))
+                .with_position([display.x as f32 / scale, display.y as f32 / scale])
+                .with_inner_size([display.width as f32 / scale, display.height as f32 / scale])
+                .with_decorations(false)
+                .with_fullscreen(true);
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of(("output_window", &output)),
+                builder,
+                |ctx, _| {
+                    egui::CentralPanel::default()
+                        .frame(egui::Frame::none().fill(egui::Color32::BLACK))
+                        .show(ctx, |ui| self.draw_output_window(ui, &output));
+                },
+            );
+        }
+    }
+
+    /// Show every output's number and name on its slot, output window and proxy
+    fn identify_outputs(&mut self) {
+        self.identify_until = Some(Instant::now() + IDENTIFY_DURATION);
+        if let Some(proxies) = &self.proxies {
+            proxies.identify(IDENTIFY_DURATION);
+        }
+    }
+
+    /// Draw one output filling a window, without labels or borders
+    fn draw_output_window(&mut self, ui: &mut egui::Ui, output: &str) {
+        let rect = ui.max_rect();
+        let (input, maintenance) = self.window_input(output);
+        let texture = input.and_then(|input| {
+            self.file_texture(ui.ctx(), &input)
+                .or_else(|| self.pattern_texture(ui.ctx(), &input))
+                .or_else(|| self.clip_texture(ui.ctx(), &input))
+                .or_else(|| self.video_texture(ui.ctx(), &input))
+        });
+
+        self.background.paint(ui.ctx(), ui.painter(), rect);
+        if let Some(texture) = &texture {
+            ui.painter().image(
+                texture.id(),
+                fit_rect(rect, texture.aspect_ratio()),
+                egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
+        } else if maintenance && self.maintenance_substitute.is_none() {
+            draw_bars(ui.painter(), rect);
+        }
+
+        if self.identify_until.is_some() {
+            if let Some(index) = self.view_slots.iter().position(|s| s.output_name == output) {
+                draw_identification(ui.painter(), rect, index + 1, output);
+            }
+        }
+    }
+
+    /// Draw connected displays and where output windows are placed
+    fn draw_displays_panel(&mut self, ui: &mut egui::Ui) {
+        if self.display_watcher.is_none() {
+            self.display_watcher = Some(DisplayWatcher::start());
+        }
+
+        ui.label(egui::RichText::new("Connected Displays").strong());
+        if self.displays.is_empty() {
+            ui.label("No displays detected");
+        }
+        egui::Grid::new("displays_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for display in &self.displays {
+                    ui.label(&display.connector);
+                    ui.label(display.model.as_deref().unwrap_or("-"));
+                    ui.monospace(display.edid_id.as_deref().unwrap_or("-"));
+                    ui.label(format!(
+                        "{}x{} at {},{}",
+                        display.width, display.height, display.x, display.y
+                    ));
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+        ui.label(egui::RichText::new("Output Windows").strong());
+        if self.output_windows.is_empty() {
+            ui.label("None configured (output_windows under [gui])");
+        }
+        for (window, display) in displays::place(&self.output_windows, &self.displays) {
+            match display {
+                Some(display) if self.displays_asleep && window.power != DisplayPower::None => {
+                    ui.label(format!("{} → {} (asleep)", window.output, display.label()))
+                }
+                Some(display) => ui.label(format!("{} → {}", window.output, display.label())),
+                None => ui.colored_label(
+                    egui::Color32::from_rgb(230, 160, 40),
+                    format!("{} → {} (not connected)", window.output, window.display),
+                ),
+            };
+        }
+    }
+
+    /// Collect metadata events published since the last frame
+    fn update_metadata(&mut self) {
+        loop {
+            match self.metadata_events.try_recv() {
+                Ok(event) => {
+                    if let MetadataEvent::Caption { text, .. } = &event.event {
+                        if text.is_empty() {
+                            self.captions.remove(&event.source);
+                        } else {
+                            let caption = (text.clone(), Instant::now());
+                            self.captions.insert(event.source.clone(), caption);
+                        }
+                    }
+                    if self.metadata_log.len() == MAX_METADATA_EVENTS {
+                        self.metadata_log.pop_front();
+                    }
+                    self.metadata_log.push_back(event);
+                }
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Draw the source metadata panel: what each connected source reports, and recent events
+    fn draw_metadata_panel(&mut self, ui: &mut egui::Ui) {
+        let mut sources: Vec<_> = self.metadata.snapshot().into_iter().collect();
+        sources.sort_by(|a, b| a.0.cmp(&b.0));
+        if sources.is_empty() {
+            ui.label("No metadata received yet");
+        }
+
+        for (source, metadata) in sources {
+            egui::CollapsingHeader::new(&source)
+                .default_open(true)
+                .show(ui, |ui| {
+                    if let Some(product) = &metadata.product {
+                        ui.label(format!(
+                            "{} {} (v{}, serial {})",
+                            product.manufacturer,
+                            product.long_name,
+                            product.version,
+                            product.serial
+                        ));
+                    }
+                    ui.label(format!("PTZ: {}", if metadata.ptz { "yes" } else { "no" }));
+                    ui.label(format!("KVM: {}", if metadata.kvm { "yes" } else { "no" }));
+                    if let Some(url) = &metadata.web_control {
+                        ui.label(format!("Web control: {}", url));
+                    }
+                    let tally = match (metadata.tally_program, metadata.tally_preview) {
+                        (true, _) => "🔴 Program",
+                        (false, true) => "🟢 Preview",
+                        (false, false) => "Off",
+                    };
+                    ui.label(format!("Tally echo: {}", tally));
+                    if let Some(caption) = &metadata.caption {
+                        ui.label(format!("Caption: {}", caption.replace('\n', " / ")));
+                    }
+                });
+        }
+
+        ui.separator();
+        ui.label(egui::RichText::new("Recent Events").strong());
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for event in &self.metadata_log {
+                    let summary = match &event.event {
+                        MetadataEvent::Tally { program, preview } => {
+                            format!("tally program={} preview={}", program, preview)
+                        }
+                        MetadataEvent::Capabilities { ptz, kvm, .. } => {
+                            format!("capabilities ptz={} kvm={}", ptz, kvm)
+                        }
+                        MetadataEvent::Product(product) => {
+                            format!("product {}", product.long_name)
+                        }
+                        MetadataEvent::Caption { text, .. } if text.is_empty() => {
+                            "caption cleared".to_string()
+                        }
+                        MetadataEvent::Caption { text, .. } => {
+                            format!("caption \"{}\"", text.replace('\n', " / "))
+                        }
+                        MetadataEvent::Other { name, .. } => format!("<{}>", name),
+                    };
+                    ui.monospace(format!("{}: {}", event.source, summary));
+                }
+            });
+    }
+
+    /// Draw the network usage panel: receive bandwidth per source, machine and NIC
+    fn draw_network_panel(&mut self, ui: &mut egui::Ui) {
+        let usages: Vec<ReceiverUsage> = self
+            .receivers
+            .iter()
+            .map(|(source, receiver)| ReceiverUsage {
+                source: source.clone(),
+                bytes_per_second: receiver.bytes_per_second(),
+            })
+            .collect();
+
+        let interface_cache = &mut self.interface_cache;
+        let report = attribute_usage(&usages, |source| {
+            let peer = source.address()?;
+            *interface_cache
+                .entry(peer)
+                .or_insert_with(|| local_interface_for(peer))
+        });
+
+        ui.label(egui::RichText::new(format!("Total: {}", format_bit_rate(report.total))).strong());
+        ui.label(format!(
+            "{} network stream(s), shared by slots showing the same source",
+            self.receivers.connection_count()
+        ));
+
+        let max = report
+            .per_source
+            .first()
+            .map(|(_, rate)| *rate)
+            .unwrap_or_default()
+            .max(1.0);
+        let draw_rows = |ui: &mut egui::Ui, rows: &[(String, f64)]| {
+            if rows.is_empty() {
+                ui.label("No active receivers");
+            }
+            for (name, rate) in rows {
+                ui.horizontal(|ui| {
+                    ui.label(name);
+                    ui.add(
+                        egui::ProgressBar::new((*rate / max) as f32)
+                            .desired_width(140.0)
+                            .text(format_bit_rate(*rate)),
+                    );
+                });
+            }
+        };
+
+        egui::CollapsingHeader::new("Per Source")
+            .default_open(true)
+            .show(ui, |ui| draw_rows(ui, &report.per_source));
+        egui::CollapsingHeader::new("Per Machine")
+            .default_open(true)
+            .show(ui, |ui| draw_rows(ui, &report.per_machine));
+        egui::CollapsingHeader::new("Per Interface")
+            .default_open(true)
+            .show(ui, |ui| draw_rows(ui, &report.per_interface));
+    }
+
+    /// Draw the AES67/Dante audio sources panel
+    fn draw_audio_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(discovery) = &self.audio_discovery else {
+            ui.label("Audio stream discovery is disabled ([audio_sources] enabled = false)");
+            return;
+        };
+        let streams = discovery.streams();
+
+        // Drop meters for streams that are no longer announced
+        self.stream_meters
+            .retain(|id, _| streams.iter().any(|s| &s.id == id));
+        self.meter_levels
+            .retain(|id, _| self.stream_meters.contains_key(id));
+
+        if streams.is_empty() {
+            ui.label("No AES67 streams announced");
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for stream in &streams {
+                ui.horizontal(|ui| {
+                    let mut metering = self.stream_meters.contains_key(&stream.id);
+                    let response = ui.add_enabled(
+                        stream.is_receivable(),
+                        egui::Checkbox::new(&mut metering, ""),
+                    );
+                    if response.changed() {
+                        if metering {
+                            match StreamMeter::start(stream) {
+                                Ok(meter) => {
+                                    self.stream_meters.insert(stream.id.clone(), meter);
+                                }
+                                Err(e) => error!("Failed to meter {}: {}", stream.name, e),
+                            }
+                        } else {
+                            self.stream_meters.remove(&stream.id);
+                            self.meter_levels.remove(&stream.id);
+                        }
+                    }
+
+                    ui.label(egui::RichText::new(&stream.name).strong());
+                    ui.label(stream.format());
+                    let address = stream
+                        .address
+                        .map(|a| format!("{}:{}", a, stream.port))
+                        .unwrap_or_else(|| "unknown".to_string());
+                    ui.label(
+                        egui::RichText::new(format!("{} from {}", address, stream.origin)).weak(),
+                    );
+                });
+
+                let Some(meter) = self.stream_meters.get(&stream.id) else {
+                    continue;
+                };
+                // Hold peaks briefly so the meters fall back smoothly
+                let peaks = meter.take_peaks();
+                let levels = self
+                    .meter_levels
+                    .entry(stream.id.clone())
+                    .or_insert_with(|| vec![0.0; peaks.len()]);
+                for (level, peak) in levels.iter_mut().zip(peaks) {
+                    *level = peak.max(*level * 0.85);
+                }
+
+                for (channel, level) in levels.iter().enumerate() {
+                    let db = to_dbfs(*level);
+                    ui.add(
+                        egui::ProgressBar::new(1.0 - db / crate::audio::meter::METER_FLOOR_DB)
+                            .desired_width(220.0)
+                            .text(format!("Ch {}  {:.1} dBFS", channel + 1, db)),
+                    );
+                }
+            }
+        });
+    }
+
+    /// Draw the layout selection panel
+    fn draw_layout_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Layout");
+        ui.separator();
+
+        for layout in Layout::all() {
+            let is_selected = self.layout == layout;
+            let response = ui.selectable_label(is_selected, layout.name());
+            if self.help.attach(response, "layout.select").clicked() {
+                self.layout = layout;
+                info!("Layout changed to: {}", layout.name());
+            }
+        }
+
+        ui.separator();
+        let response = ui.checkbox(&mut self.auto_layout, "Fit routed outputs");
+        if self.help.attach(response, "layout.auto").changed() {
+            // Fit now rather than at the next route change
+            self.fitted_outputs.clear();
+        }
+        let suggested = self.suggested_layout();
+        if !self.auto_layout && suggested != self.layout {
+            let response = ui.button(format!("Suggested: {}", suggested.name()));
+            if self.help.attach(response, "layout.suggest").clicked() {
+                self.layout = suggested;
+                info!("Layout changed to: {}", suggested.name());
+            }
+        }
+    }
+
+    /// Draw the routing panel
+    fn draw_routing_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Routing Control");
+        ui.separator();
+
+        // Refresh sources button
+        let response = ui.button("🔄 Refresh Sources");
+        if self.help.attach(response, "routing.refresh").clicked() {
+            self.discovered_sources = self.discovery.get_sources();
+            self.update_sources();
+        }
+
+        ui.add_space(10.0);
+
+        // Available sources
+        let shown = self
+            .available_sources
+            .iter()
+            .filter(|s| self.source_filter.as_ref().is_none_or(|f| f.matches(s)))
+            .count();
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Available Sources ({}/{})",
+                shown,
+                self.available_sources.len()
+            ));
+            let mut known_tags: Vec<&String> = self
+                .available_sources
+                .iter()
+                .flat_map(|s| &s.tags)
+                .collect();
+            known_tags.sort();
+            known_tags.dedup();
+            let filters: Vec<SourceFilter> = self
+                .source_filters
+                .iter()
+                .cloned()
+                .chain(known_tags.into_iter().map(|tag| SourceFilter::tag(tag)))
+                .collect();
+            let selected = self
+                .source_filter
+                .as_ref()
+                .map_or("All sources", |f| f.name.as_str())
+                .to_string();
+            let response = egui::ComboBox::from_id_source("source_filter")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.source_filter, None, "All sources");
+                    for filter in filters {
+                        let name = filter.name.clone();
+                        ui.selectable_value(&mut self.source_filter, Some(filter), name);
+                    }
+                })
+                .response;
+            self.help.attach(response, "routing.filter");
+        });
+        ui.separator();
+
+        let preview = self
+            .previewed_source()
+            .map(|source| source.url.clone())
+            .map(|url| {
+                let texture = self.video_texture(ui.ctx(), &url);
+                (url, texture)
+            });
+        if let Some((_, since)) = &self.hover_preview {
+            // Open the preview on time even if the pointer is still
+            ui.ctx()
+                .request_repaint_after(PREVIEW_DELAY.saturating_sub(since.elapsed()));
+        }
+
+        let sources = egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                let mut toggle_maintenance = None;
+                let mut toggle_favorite = None;
+                let mut approve = None;
+                let mut edit_tags = None;
+                let mut show_impact = None;
+                let mut hovered = None;
+                // The operator's favorites come first
+                let mut order: Vec<(usize, bool)> = self
+                    .available_sources
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| self.source_filter.as_ref().is_none_or(|f| f.matches(s)))
+                    .map(|(idx, source)| (idx, self.is_favorite(source)))
+                    .collect();
+                order.sort_by_key(|(_, favorite)| !favorite);
+                for (idx, favorite) in order {
+                    let source = &self.available_sources[idx];
+                    let is_selected = self.selected_source_idx == Some(idx);
+                    let in_maintenance = self
+                        .routing()
+                        .lock()
+                        .map(|router| router.in_maintenance(&source.url))
+                        .unwrap_or(false);
+                    ui.horizontal(|ui| {
+                        let response = ui.selectable_label(in_maintenance, "🔧");
+                        if self.help.attach(response, "routing.maintenance").clicked() {
+                            toggle_maintenance = Some((source.name.clone(), !in_maintenance));
+                        }
+                        if self.profile.is_some()
+                            && self
+                                .help
+                                .attach(
+                                    ui.selectable_label(favorite, if favorite { "★" } else { "☆" }),
+                                    "routing.favorite",
+                                )
+                                .clicked()
+                        {
+                            toggle_favorite = Some((source.clone(), !favorite));
+                        }
+                        if source.quarantined {
+                            ui.weak(format!("🔒 {}", source.display_name()));
+                            if self
+                                .help
+                                .attach(ui.small_button("Approve"), "routing.approve")
+                                .clicked()
+                            {
+                                approve = Some(source.name.clone());
+                            }
+                            return;
+                        }
+                        let mut label = if source.audio_only {
+                            format!("🔊 {}", source.display_name())
+                        } else {
+                            source.display_name().to_string()
+                        };
+                        if let Some(site) = &source.site {
+                            label.push_str(&format!("  🌐 {}", site));
+                        }
+                        if source.static_source {
+                            label.push_str("  📌");
+                        }
+                        if source.tally.is_some_and(|t| t.program) {
+                            label.push_str("  🔴");
+                        }
+                        let response = ui.selectable_label(is_selected, label).on_hover_ui(|ui| {
+                            // Discovery has the latest last-seen time
+                            let live = self.discovery.get_source(&source.name);
+                            let now_ms = SystemTime::now()
+                                .duration_since(UNIX_EPOCH)
+                                .map(|d| d.as_millis() as u64)
+                                .unwrap_or_default();
+                            ui.label(&source.name);
+                            let details = live.as_ref().unwrap_or(source).details(now_ms);
+                            if !details.is_empty() {
+                                ui.weak(details);
+                            }
+                            if !source.tags.is_empty() {
+                                ui.weak(format!("🏷 {}", source.tags.join(", ")));
+                            }
+                            if let Some((_, texture)) =
+                                preview.as_ref().filter(|(url, _)| *url == source.url)
+                            {
+                                let (rect, _) =
+                                    ui.allocate_exact_size(PREVIEW_SIZE, egui::Sense::hover());
+                                ui.painter().rect_filled(rect, 2.0, egui::Color32::BLACK);
+                                match texture {
+                                    Some(texture) => {
+                                        ui.painter().image(
+                                            texture.id(),
+                                            fit_rect(rect, texture.aspect_ratio()),
+                                            egui::Rect::from_min_max(
+                                                egui::pos2(0.0, 0.0),
+                                                egui::pos2(1.0, 1.0),
+                                            ),
+                                            egui::Color32::WHITE,
+                                        );
+                                    }
+                                    None => {
+                                        ui.painter().text(
+                                            rect.center(),
+                                            egui::Align2::CENTER_CENTER,
+                                            "Connecting…",
+                                            egui::FontId::proportional(14.0),
+                                            egui::Color32::GRAY,
+                                        );
+                                    }
+                                }
+                            }
+                        });
+                        response.context_menu(|ui| {
+                            if ui.button("🏷 Tags…").clicked() {
+                                edit_tags = Some(source.name.clone());
+                                ui.close_menu();
+                            }
+                            if self
+                                .help
+                                .attach(ui.button("🔗 Impact…"), "routing.impact")
+                                .clicked()
+                            {
+                                show_impact = Some(source.clone());
+                                ui.close_menu();
+                            }
+                        });
+                        if response.hovered() {
+                            hovered = Some(source.clone());
+                        }
+                        if response.clicked() {
+                            self.selected_source_idx = Some(idx);
+                        }
+                    });
+                }
+                // Keep the hover start while the same source stays hovered
+                let same = match (&hovered, &self.hover_preview) {
+                    (Some(source), Some((previewed, _))) => source.url == previewed.url,
+                    _ => false,
+                };
+                if !same {
+                    self.hover_preview = hovered.map(|source| (source, Instant::now()));
+                }
+                if let Some(name) = approve {
+                    match self.admin_pin {
+                        Some(_) => self.approval_prompt = Some((name, String::new())),
+                        None => self.approve_source(&name),
+                    }
+                }
+                if let Some((input, enabled)) = toggle_maintenance {
+                    self.set_maintenance(&input, enabled);
+                }
+                if let Some((source, favorite)) = toggle_favorite {
+                    self.set_favorite(&source, favorite);
+                }
+                if let Some(name) = edit_tags {
+                    let tags = self.source_tags.get(&name).cloned().unwrap_or_default();
+                    self.tag_prompt = Some((name, tags.join(", ")));
+                }
+                if let Some(source) = show_impact {
+                    self.show_impact(&source);
+                }
+            });
+        self.help.mark("routing.sources", sources.inner_rect);
+
+        if let Some((name, text)) = &mut self.tag_prompt {
+            let mut done = None;
+            ui.label(format!("Tags of {} (comma-separated):", name));
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(text)
+                        .hint_text("camera, backup")
+                        .desired_width(140.0),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    done = Some(true);
+                }
+                if ui.button("Save").clicked() {
+                    done = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    done = Some(false);
+                }
+            });
+            // Tags from patterns matching the source, which stay whatever is typed here
+            let inherited: Vec<&String> = self
+                .available_sources
+                .iter()
+                .find(|s| &s.name == name)
+                .map(|s| {
+                    s.tags
+                        .iter()
+                        .filter(|tag| {
+                            self.source_tags
+                                .get(name.as_str())
+                                .is_none_or(|own| !own.contains(tag))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if !inherited.is_empty() {
+                let inherited: Vec<&str> = inherited.iter().map(|t| t.as_str()).collect();
+                ui.weak(format!("Also tagged by pattern: {}", inherited.join(", ")));
+            }
+            if let Some(save) = done {
+                let (name, text) = self.tag_prompt.take().unwrap();
+                if save {
+                    self.set_source_tags(&name, tags::parse_list(&text));
+                }
+            }
+        }
+
+        if let Some((name, pin)) = &mut self.approval_prompt {
+            let mut done = None;
+            ui.label(format!("Admin PIN to approve {}:", name));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(pin)
+                        .password(true)
+                        .desired_width(80.0),
+                );
+                if ui.button("Approve").clicked() {
+                    done = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    done = Some(false);
+                }
+            });
+            if let Some(confirmed) = done {
+                let (name, pin) = self.approval_prompt.take().unwrap();
+                if confirmed && self.admin_pin.as_deref() == Some(pin.as_str()) {
+                    self.approve_source(&name);
+                } else if confirmed {
+                    self.events.record(
+                        Event::new(
+                            Severity::Warning,
+                            EventCategory::System,
+                            format!("Wrong admin PIN approving source {}", name),
+                        )
+                        .with_actor("gui"),
+                    );
+                }
+            }
+        }
+
+        ui.add_space(10.0);
+
+        // Route button for selected source
+        ui.horizontal(|ui| {
+            let can_route = self.selected_source_idx.is_some() && self.selected_view_idx.is_some();
+
+            let response = ui.add_enabled(can_route, egui::Button::new("➡ Route Selected"));
+            if self
+                .help
+                .attach(response, "routing.route_selected")
+                .clicked()
+            {
+                if let (Some(source_idx), Some(view_idx)) =
+                    (self.selected_source_idx, self.selected_view_idx)
+                {
+                    if let (Some(source), Some(view)) = (
+                        self.available_sources.get(source_idx),
+                        self.view_slots.get(view_idx),
+                    ) {
+                        self.create_route(source.url.clone(), view.output_name.clone());
+                        self.selected_source_idx = None;
+                        self.view_slots[view_idx].selected = false;
+                    }
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Manual input name entry for placeholder routes
+        ui.label("Or enter input name manually:");
+        ui.horizontal(|ui| {
+            ui.label("Input name:");
+            let response = ui.text_edit_singleline(&mut self.manual_input_name);
+            self.help.attach(response, "routing.manual_input");
+        });
+
+        ui.horizontal(|ui| {
+            let can_create_placeholder =
+                !self.manual_input_name.is_empty() && self.selected_view_idx.is_some();
+
+            let response = ui.add_enabled(
+                can_create_placeholder,
+                egui::Button::new("➡ Create Placeholder Route"),
+            );
+            if self.help.attach(response, "routing.placeholder").clicked() {
+                if let Some(view_idx) = self.selected_view_idx {
+                    if let Some(view) = self.view_slots.get(view_idx) {
+                        self.create_route(self.manual_input_name.clone(), view.output_name.clone());
+                        self.manual_input_name.clear();
+                        self.view_slots[view_idx].selected = false;
+                    }
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+
+        self.draw_compare_controls(ui);
+
+        ui.add_space(10.0);
+
+        // Current routes
+        ui.label("Current Routes");
+        ui.separator();
+
+        // With the pattern of wildcard routes and the output a mirror follows
+        let routes: Vec<(Route, Option<String>, Option<String>)> =
+            if let Ok(router) = self.routing().lock() {
+                router
+                    .get_all_routes()
+                    .into_iter()
+                    .map(|route| {
+                        let pattern = router.wildcard(&route.output).map(|w| w.pattern.clone());
+                        let primary = router.mirror_of(&route.output).map(str::to_string);
+                        (route, pattern, primary)
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+        egui::ScrollArea::vertical()
+            .max_height(150.0)
+            .show(ui, |ui| {
+                for (route, pattern, primary) in &routes {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} ← {}", route.output, route.input));
+                        if let Some(primary) = primary {
+                            ui.weak(format!("⧉ mirrors {}", primary));
+                        }
+                        if route.has_notes() {
+                            ui.label(egui::RichText::new(route.notes_summary()).small().weak());
+                        }
+                        if let Some(pattern) = pattern.as_ref().filter(|p| **p != route.input) {
+                            ui.label(egui::RichText::new(format!("[{}]", pattern)).small().weak());
+                            if self.help.attach(ui.button("⟳"), "routing.rebind").clicked() {
+                                self.rebind_wildcard(&route.output);
+                            }
+                        }
+                        if self
+                            .help
+                            .attach(ui.button("❌"), "routing.unroute")
+                            .clicked()
+                        {
+                            self.remove_route(&route.output);
+                        }
+                    });
+                }
+
+                if routes.is_empty() {
+                    ui.label("No routes configured");
+                }
+            });
+    }
+}
+
+impl eframe::App for MatrixViewerApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self
+            .identify_until
+            .is_some_and(|until| Instant::now() >= until)
+        {
+            self.identify_until = None;
+        }
+
+        // Discovery scans fast while sources are being picked and slows down when idle
+        self.discovery
+            .set_browsing(self.show_routing_panel || self.wall_mode);
+        if ctx.input(|i| !i.events.is_empty()) {
+            self.discovery.operator_input();
+        }
+
+        // Update sources periodically
+        self.update_audio_only();
+        self.update_sources();
+        self.sync_view_slots();
+        self.fit_layout();
+        self.sync_receivers();
+        self.update_frame_sync();
+        self.update_connection_health();
+        self.update_stream_alerts();
+        self.update_failover();
+        self.update_tally();
+        self.update_metadata();
+        self.update_audio_monitor();
+        self.update_displays(ctx);
+        self.handle_shortcuts(ctx);
+        // Stop KVM once the focused slot no longer shows a KVM source
+        if let Some(focus) = &self.kvm {
+            if !self.slot_has_kvm(&focus.output) {
+                self.end_kvm();
+            }
+        }
+
+        // Top panel - menu bar
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("View", |ui| {
+                let view_menu = ui.menu_button("View", |ui| {
+                    if ui
+                        .checkbox(&mut self.show_layout_panel, "Layout Panel")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_routing_panel, "Routing Panel")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
                     if ui
-                        .checkbox(&mut self.show_layout_panel, "Layout Panel")
+                        .checkbox(&mut self.show_talkback_panel, "Talkback Panel")
                         .clicked()
                     {
                         ui.close_menu();
                     }
                     if ui
-                        .checkbox(&mut self.show_routing_panel, "Routing Panel")
+                        .checkbox(&mut self.show_camera_panel, "Camera Panel")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_selftest_panel, "Self-Test")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_network_panel, "Network Usage")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_metadata_panel, "Source Metadata")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_displays_panel, "Displays")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_audio_panel, "Audio Sources")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_recordings_panel, "Recordings Panel")
                         .clicked()
                     {
                         ui.close_menu();
                     }
+                    if ui
+                        .checkbox(&mut self.show_telestrator_panel, "Telestrator")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    if self.intercom.is_some()
+                        && ui
+                            .checkbox(&mut self.show_intercom_panel, "Intercom Panel")
+                            .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let response = ui.button("🔢 Identify Outputs");
+                    if self.help.attach(response, "view.identify").clicked() {
+                        info!("Identifying outputs");
+                        self.identify_outputs();
+                        ui.close_menu();
+                    }
+                    let response = ui.checkbox(&mut self.show_latency, "⏱ Latency");
+                    if self.help.attach(response, "view.latency").clicked() {
+                        ui.close_menu();
+                    }
+                    let response = ui.checkbox(&mut self.wall_mode, "Monitoring Wall");
+                    if self.help.attach(response, "view.wall").clicked() {
+                        info!("Monitoring wall mode: {}", self.wall_mode);
+                        ui.close_menu();
+                    }
+                    let mut training = self.training.is_some();
+                    let response = ui.checkbox(&mut training, "🎓 Training Mode");
+                    if self.help.attach(response, "view.training").clicked() {
+                        self.set_training(training);
+                        ui.close_menu();
+                    }
+                });
+
+                self.help.attach(view_menu.response, "menu.view");
+
+                let audio_menu = ui.menu_button("Audio", |ui| {
+                    let outputs: Vec<String> = self
+                        .view_slots
+                        .iter()
+                        .map(|slot| slot.output_name.clone())
+                        .collect();
+                    if ui
+                        .radio_value(&mut self.audio_output, None, "Off")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                    for output in outputs.into_iter().chain(self.audio_route_output.clone()) {
+                        let label = format!("Listen to {}", output);
+                        if ui
+                            .radio_value(&mut self.audio_output, Some(output), label)
+                            .clicked()
+                        {
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    if ui.checkbox(&mut self.show_mixer_panel, "🎚 Mixer").clicked() {
+                        ui.close_menu();
+                    }
+                    let muted_all = self.mixer.is_muted_all();
+                    let label = if muted_all {
+                        "🔊 Unmute All"
+                    } else {
+                        "🔇 Mute All"
+                    };
+                    if ui.button(label).clicked() {
+                        self.set_muted_all(!muted_all);
+                        ui.close_menu();
+                    }
+                    if let Some(output) = self.audio_route_output.clone() {
+                        ui.separator();
+                        ui.menu_button(format!("Route to {}", output), |ui| {
+                            let current = {
+                                let routing = self.routing();
+                                let router = routing.lock().unwrap();
+                                router.get_route(&output).cloned()
+                            };
+                            let sources: Vec<(String, String, bool)> = self
+                                .available_sources
+                                .iter()
+                                .map(|s| {
+                                    let routed = current.as_ref().is_some_and(|i| s.matches(i));
+                                    (s.url.clone(), s.display_name().to_string(), routed)
+                                })
+                                .collect();
+                            for (url, name, routed) in sources {
+                                if ui.radio(routed, name).clicked() {
+                                    self.create_route(url, output.clone());
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
+                });
+
+                self.help.attach(audio_menu.response, "menu.audio");
+
+                let salvos: Vec<String> = self
+                    .control
+                    .as_ref()
+                    .map(|control| control.salvos().iter().map(|s| s.name.clone()).collect())
+                    .unwrap_or_default();
+                if !salvos.is_empty() {
+                    let salvo_menu = ui.menu_button("Salvos", |ui| {
+                        if self.training.is_some() {
+                            ui.label("Salvos can't be recalled in training mode");
+                            return;
+                        }
+                        for salvo in salvos {
+                            if ui.button(&salvo).clicked() {
+                                self.start_salvo_recall(&salvo);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    self.help.attach(salvo_menu.response, "menu.salvos");
+                }
+
+                if self.control.is_some() && self.training.is_none() {
+                    let blanked = self.router.lock().unwrap().is_blanked();
+                    let response = if blanked {
+                        ui.button(
+                            egui::RichText::new("▶ Resume")
+                                .color(egui::Color32::from_rgb(230, 60, 60))
+                                .strong(),
+                        )
+                    } else {
+                        ui.button("⏻ Blank All")
+                    };
+                    if self.help.attach(response, "menu.standby").clicked() {
+                        self.set_standby(!blanked);
+                    }
+                }
+
+                let help_menu = ui.menu_button("Help", |ui| {
+                    if ui.button("🧭 Guided Tour").clicked() {
+                        self.help.start_tour();
+                        ui.close_menu();
+                    }
                 });
+                self.help.attach(help_menu.response, "menu.help");
+
+                if !self.profiles.is_empty() {
+                    let title = match &self.profile {
+                        Some(name) => format!("👤 {}", name),
+                        None => "👤 Operator".to_string(),
+                    };
+                    ui.menu_button(title, |ui| {
+                        let mut chosen = None;
+                        for name in self.profiles.names() {
+                            let current = self.profile.as_ref() == Some(&name);
+                            if ui.radio(current, name.as_str()).clicked() {
+                                chosen = Some(name);
+                            }
+                        }
+                        if let Some(name) = chosen {
+                            self.select_profile(ui.ctx(), &name);
+                            ui.close_menu();
+                        }
+                    });
+                }
+
+                if self.training.is_some() {
+                    ui.separator();
+                    ui.colored_label(TRAINING_COLOR, "🎓 TRAINING - routes are not live");
+                }
 
                 ui.separator();
                 ui.label(format!("Current Layout: {}", self.layout.name()));
+                if let Some(output) = &self.audio_output {
+                    ui.separator();
+                    if self.mixer.is_muted_all() {
+                        ui.colored_label(egui::Color32::from_rgb(230, 60, 60), "🔇 MUTED");
+                    } else {
+                        ui.label(format!("🔊 {}", output));
+                    }
+                }
+                if let Some(timecode) = &self.timecode {
+                    ui.separator();
+                    match timecode.current() {
+                        Some(tc) => ui.monospace(format!("⏱ {}", tc)),
+                        None => ui.label("⏱ No timecode"),
+                    };
+                }
+
+                // Only shown once something had to be restarted
+                let restarts = self.supervisor.total_restarts();
+                if restarts > 0 {
+                    let tasks = self.supervisor.status();
+                    let color = if tasks.iter().any(|t| t.state == TaskState::Restarting) {
+                        egui::Color32::from_rgb(230, 60, 60)
+                    } else {
+                        egui::Color32::from_rgb(240, 170, 40)
+                    };
+                    ui.separator();
+                    let response = ui.colored_label(color, format!("⟳ {} restarts", restarts));
+                    let details: Vec<String> = tasks
+                        .iter()
+                        .filter(|t| t.restarts > 0 || t.state == TaskState::Restarting)
+                        .map(|t| match &t.last_error {
+                            Some(error) => format!("{}: {} ({})", t.name, t.restarts, error),
+                            None => format!("{}: {}", t.name, t.restarts),
+                        })
+                        .collect();
+                    response.on_hover_text(format!(
+                        "Restarted automatically after failing:\n{}",
+                        details.join("\n")
+                    ));
+                }
+
+                let alerts = self.stream_alerts.active();
+                if !alerts.is_empty() {
+                    ui.separator();
+                    let response = ui.colored_label(
+                        egui::Color32::from_rgb(240, 170, 40),
+                        format!("⚠ {} stream alerts", alerts.len()),
+                    );
+                    let details: Vec<String> = alerts
+                        .iter()
+                        .map(|(source, kind)| format!("{}: {}", source, kind))
+                        .collect();
+                    response.on_hover_text(details.join("\n"));
+                }
+
+                if self.wall_mode {
+                    ui.separator();
+                    self.draw_wall_pager(ui);
+                }
             });
         });
 
+        if self.show_profile_picker {
+            self.draw_profile_picker(ctx);
+        }
+
         // Left panel - layout selection
         if self.show_layout_panel {
-            egui::SidePanel::left("layout_panel")
+            let panel = egui::SidePanel::left("layout_panel")
                 .default_width(200.0)
                 .show(ctx, |ui| {
                     self.draw_layout_panel(ui);
                 });
+            self.help.mark("layout.panel", panel.response.rect);
         }
 
         // Right panel - routing control
@@ -388,18 +4657,461 @@ impl eframe::App for MatrixViewerApp {
                 });
         }
 
+        // Bottom panel - intercom chat
+        if self.show_intercom_panel && self.intercom.is_some() {
+            egui::TopBottomPanel::bottom("intercom_panel")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    self.draw_intercom_panel(ui);
+                });
+        }
+
+        // Alert the operator when cues or mentions arrive, and play talkback
+        // addressed to this station
+        if let Some(intercom) = self.intercom.clone() {
+            let alerts = intercom.unread_alerts();
+            let new_alert = alerts > self.intercom_alerts_seen;
+            self.intercom_alerts_seen = alerts;
+            if new_alert {
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                    egui::UserAttentionType::Critical,
+                ));
+                if self.intercom_alert_sound {
+                    self.play_intercom_audio(&intercom::alert_chime());
+                }
+            }
+            for chunk in intercom.take_talkback() {
+                self.play_intercom_audio(&chunk);
+            }
+        }
+
+        // Camera maintenance window
+        if self.show_camera_panel {
+            let mut open = true;
+            let window = egui::Window::new("Cameras")
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    if self.training.is_some() {
+                        ui.label("Camera control is disabled in training mode");
+                    } else {
+                        self.camera_panel.show(ui, &self.help, &self.receivers);
+                    }
+                });
+            if let Some(window) = window {
+                self.help.mark("camera.panel", window.response.rect);
+            }
+            self.show_camera_panel = open;
+        }
+
+        // Self-test checklist window (shown by itself when a run fails)
+        if self.selftest_panel.poll() {
+            self.show_selftest_panel = true;
+        }
+        if self.show_selftest_panel {
+            let mut open = true;
+            egui::Window::new("Self-Test")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.selftest_panel.show(ui);
+                });
+            self.show_selftest_panel = open;
+        }
+
+        // Network usage window
+        if self.show_network_panel {
+            let mut open = true;
+            egui::Window::new("Network Usage")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.draw_network_panel(ui);
+                });
+            self.show_network_panel = open;
+        }
+
+        // Source metadata window
+        if self.show_metadata_panel {
+            let mut open = true;
+            egui::Window::new("Source Metadata")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.draw_metadata_panel(ui);
+                });
+            self.show_metadata_panel = open;
+        }
+
+        // Displays window
+        if self.show_displays_panel {
+            let mut open = true;
+            egui::Window::new("Displays")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.draw_displays_panel(ui);
+                });
+            self.show_displays_panel = open;
+        }
+
+        // What refers to a source, from the routing panel
+        if let Some((name, references)) = &self.impact {
+            let mut open = true;
+            egui::Window::new(format!("Impact: {}", name))
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    if references.is_empty() {
+                        ui.label("Nothing routes to or refers to this source.");
+                        return;
+                    }
+                    ui.label("If this source dies or is renamed, these are affected:");
+                    egui::Grid::new("impact").striped(true).show(ui, |ui| {
+                        for reference in references {
+                            ui.label(reference.kind.to_string());
+                            ui.label(&reference.name);
+                            ui.weak(&reference.via);
+                            ui.end_row();
+                        }
+                    });
+                });
+            if !open {
+                self.impact = None;
+            }
+        }
+
+        // Source that looks renamed, with what moving to its new name changes
+        if let Some((from, to, changes)) = &self.rename_offer {
+            let mut decision = None;
+            egui::Window::new("Rename Source")
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "{} disappeared and {} appeared on the same machine.",
+                        from, to
+                    ));
+                    ui.label("Move everything naming the old source to the new one?");
+                    egui::Grid::new("rename").striped(true).show(ui, |ui| {
+                        for change in changes {
+                            ui.label(change.kind.to_string());
+                            ui.label(&change.name);
+                            ui.weak(format!("{} → {}", change.before, change.after));
+                            ui.end_row();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if self
+                            .help
+                            .attach(ui.button("Rename"), "rename.apply")
+                            .clicked()
+                        {
+                            decision = Some(true);
+                        }
+                        if self
+                            .help
+                            .attach(ui.button("Dismiss"), "rename.dismiss")
+                            .clicked()
+                        {
+                            decision = Some(false);
+                        }
+                    });
+                });
+            match decision {
+                Some(true) => {
+                    if let Some((from, to, _)) = self.rename_offer.take() {
+                        self.apply_rename(&from, &to);
+                    }
+                }
+                Some(false) => self.rename_offer = None,
+                None => {}
+            }
+        }
+
+        // Salvo with preflight problems, waiting for the operator's decision
+        if let Some(preflight) = &self.salvo_preflight {
+            let mut decision = None;
+            egui::Window::new("Recall Salvo")
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "{} of {} routes of '{}' can be applied:",
+                        preflight.ready.len(),
+                        preflight.ready.len() + preflight.issues.len(),
+                        preflight.salvo
+                    ));
+                    for issue in &preflight.issues {
+                        let mut line = format!(
+                            "⚠ {} ← {}: {}",
+                            issue.route.output, issue.route.input, issue.problem
+                        );
+                        if let Some(backup) = &issue.backup {
+                            line.push_str(&format!(" (backup {} available)", backup));
+                        }
+                        ui.colored_label(egui::Color32::from_rgb(230, 160, 40), line);
+                    }
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        for policy in RecallPolicy::all() {
+                            if policy == RecallPolicy::SubstituteBackups && !preflight.has_backups()
+                            {
+                                continue;
+                            }
+                            if ui.button(policy.name()).clicked() {
+                                decision = Some(policy);
+                            }
+                        }
+                    });
+                });
+            match decision {
+                Some(RecallPolicy::Abort) => self.salvo_preflight = None,
+                Some(policy) => {
+                    if let Some(preflight) = self.salvo_preflight.take() {
+                        self.recall_salvo(&preflight.salvo, policy);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        // Display change notice, until it times out or is dismissed
+        if self
+            .display_notice
+            .as_ref()
+            .is_some_and(|(until, _)| Instant::now() >= *until)
+        {
+            self.display_notice = None;
+        }
+        if let Some((_, notice)) = &self.display_notice {
+            let mut dismissed = false;
+            egui::TopBottomPanel::top("display_notice").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(230, 160, 40),
+                        format!("🖵 {}", notice),
+                    );
+                    dismissed = ui.small_button("Dismiss").clicked();
+                });
+            });
+            if dismissed {
+                self.display_notice = None;
+            }
+        }
+
+        // Audio sources window
+        if self.show_audio_panel {
+            let mut open = true;
+            egui::Window::new("Audio Sources")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.draw_audio_panel(ui);
+                });
+            self.show_audio_panel = open;
+        }
+
+        // Monitor mixer window
+        if self.show_mixer_panel {
+            let mut open = true;
+            egui::Window::new("Monitor Mixer")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.draw_mixer_panel(ui);
+                });
+            self.show_mixer_panel = open;
+        }
+
+        // Telestrator window (closing it leaves drawing mode)
+        if self.show_telestrator_panel {
+            let mut open = true;
+            egui::Window::new("Telestrator")
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.draw_telestrator_panel(ui);
+                });
+            self.show_telestrator_panel = open;
+            if !open {
+                self.telestrator.active = false;
+            }
+        }
+
+        // Recordings window
+        if self.show_recordings_panel {
+            let mut open = true;
+            egui::Window::new("Recordings")
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.draw_recordings_panel(ui);
+                });
+            self.show_recordings_panel = open;
+        }
+
+        // Talkback window
+        let mut talk_button_held = false;
+        if self.show_talkback_panel {
+            let mut open = true;
+            egui::Window::new("Talkback")
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    talk_button_held = self.draw_talkback_panel(ui);
+                });
+            self.show_talkback_panel = open;
+        }
+        self.update_talkback(ctx, talk_button_held);
+
         // Central panel - matrix view
         egui::CentralPanel::default().show(ctx, |ui| {
+            self.help.mark("matrix.view", ui.max_rect());
             self.draw_matrix_view(ui);
+            if self.training.is_some() {
+                draw_training_watermark(ui.painter(), ui.max_rect());
+            }
         });
 
-        // Request repaint for smooth updates
-        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        self.draw_tour(ctx);
+        self.update_recording(ctx);
+        self.update_display_power();
+        self.show_output_windows(ctx);
+        self.release_clips();
+
+        // Request repaint for smooth updates (at display rate while live video or audio plays)
+        if self.video_textures.is_empty() && self.audio_input.is_none() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        } else {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Color bars shown in place of a source under maintenance
+fn draw_bars(painter: &egui::Painter, rect: egui::Rect) {
+    const BARS: [egui::Color32; 7] = [
+        egui::Color32::from_rgb(192, 192, 192),
+        egui::Color32::from_rgb(192, 192, 0),
+        egui::Color32::from_rgb(0, 192, 192),
+        egui::Color32::from_rgb(0, 192, 0),
+        egui::Color32::from_rgb(192, 0, 192),
+        egui::Color32::from_rgb(192, 0, 0),
+        egui::Color32::from_rgb(0, 0, 192),
+    ];
+    let width = rect.width() / BARS.len() as f32;
+    for (i, color) in BARS.iter().enumerate() {
+        let bar = egui::Rect::from_min_size(
+            rect.min + egui::vec2(width * i as f32, 0.0),
+            egui::vec2(width, rect.height()),
+        );
+        painter.rect_filled(bar, 0.0, *color);
+    }
+    // Dark band behind the slot label
+    let band = egui::Rect::from_center_size(
+        rect.center(),
+        egui::vec2(rect.width(), 48.0_f32.min(rect.height())),
+    );
+    painter.rect_filled(band, 0.0, egui::Color32::from_black_alpha(200));
+}
+
+/// Draw level meters in the slot of a source that sends no video
+fn draw_audio_meters(painter: &egui::Painter, rect: egui::Rect, levels: &[f32]) {
+    use crate::audio::meter::METER_FLOOR_DB;
+
+    painter.rect_filled(rect, 4.0, egui::Color32::from_rgb(20, 24, 32));
+    if levels.is_empty() {
+        return;
+    }
+    let area = rect.shrink2(egui::vec2(rect.width() * 0.2, rect.height() * 0.15));
+    let width = area.width() / levels.len() as f32;
+    for (i, level) in levels.iter().enumerate() {
+        let db = to_dbfs(*level);
+        let height = area.height() * (1.0 - db / METER_FLOOR_DB);
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(area.min.x + width * (i as f32 + 0.15), area.max.y - height),
+            egui::pos2(area.min.x + width * (i as f32 + 0.85), area.max.y),
+        );
+        let color = if db > -6.0 {
+            egui::Color32::from_rgb(220, 40, 40)
+        } else if db > -18.0 {
+            egui::Color32::from_rgb(230, 200, 40)
+        } else {
+            egui::Color32::from_rgb(40, 200, 60)
+        };
+        painter.rect_filled(bar, 2.0, color);
     }
+    // Dark band behind the slot label
+    let band = egui::Rect::from_center_size(
+        rect.center(),
+        egui::vec2(rect.width(), 48.0_f32.min(rect.height())),
+    );
+    painter.rect_filled(band, 0.0, egui::Color32::from_black_alpha(200));
+}
+
+/// Mark the matrix view as a training sandbox so it is never mistaken for the live routing
+fn draw_training_watermark(painter: &egui::Painter, rect: egui::Rect) {
+    painter.rect_stroke(rect, 0.0, egui::Stroke::new(6.0, TRAINING_COLOR));
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        "TRAINING",
+        egui::FontId::proportional((rect.height() * 0.2).max(24.0)),
+        TRAINING_COLOR.gamma_multiply(0.35),
+    );
+}
+
+/// Cover a slot with its output number and name in text big enough to read across a room
+fn draw_identification(painter: &egui::Painter, rect: egui::Rect, number: usize, output: &str) {
+    painter.rect_filled(rect, 4.0, egui::Color32::from_black_alpha(200));
+    let painter = painter.with_clip_rect(rect);
+    painter.text(
+        rect.center() - egui::vec2(0.0, rect.height() * 0.12),
+        egui::Align2::CENTER_CENTER,
+        number.to_string(),
+        egui::FontId::proportional(rect.height() * 0.5),
+        egui::Color32::YELLOW,
+    );
+    painter.text(
+        rect.center() + egui::vec2(0.0, rect.height() * 0.28),
+        egui::Align2::CENTER_CENTER,
+        output,
+        egui::FontId::proportional((rect.height() * 0.12).max(14.0)),
+        egui::Color32::WHITE,
+    );
+}
+
+/// Convert a received RGBA frame into an egui image
+/// A duration as HH:MM:SS
+fn format_hms(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+fn frame_image(frame: &VideoFrame) -> egui::ColorImage {
+    egui::ColorImage::from_rgba_unmultiplied(
+        [frame.width as usize, frame.height as usize],
+        &frame.data,
+    )
+}
+
+/// Largest rectangle with the given aspect ratio centered inside `rect`
+pub fn fit_rect(rect: egui::Rect, aspect_ratio: f32) -> egui::Rect {
+    let rect_aspect = rect.width() / rect.height();
+    let size = if aspect_ratio > rect_aspect {
+        egui::vec2(rect.width(), rect.width() / aspect_ratio)
+    } else {
+        egui::vec2(rect.height() * aspect_ratio, rect.height())
+    };
+    egui::Rect::from_center_size(rect.center(), size)
 }
 
 /// Run the GUI application
-pub fn run_gui(config: Config) -> Result<()> {
+pub fn run_gui(
+    config: Config,
+    config_path: PathBuf,
+    storage: Option<Arc<dyn Storage>>,
+    saved_routes: Option<Arc<dyn Storage>>,
+    profile: Option<String>,
+    training: bool,
+) -> Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([config.gui.window_width, config.gui.window_height])
@@ -411,8 +5123,149 @@ pub fn run_gui(config: Config) -> Result<()> {
     eframe::run_native(
         "RusTV",
         options,
-        Box::new(|cc| {
-            let app = MatrixViewerApp::new(cc, config);
+        Box::new(move |cc| {
+            let mut app = MatrixViewerApp::new(cc, config.clone(), config_path.clone());
+            if let Some(profile) = &profile {
+                app.select_profile(&cc.egui_ctx, profile);
+            }
+
+            if let Some(saved_routes) = &saved_routes {
+                // Carry on with the routing that was live when RusTV last stopped
+                let mut router = app.router.lock().unwrap();
+                for route in &config.matrix.routes {
+                    if let Err(e) = router.route_placeholder(&route.input, &route.output) {
+                        error!(
+                            "Ignoring saved route {} -> {}: {}",
+                            route.input, route.output, e
+                        );
+                    }
+                }
+                drop(router);
+                storage::persist_routes(Arc::clone(&app.router), Arc::clone(saved_routes));
+                app.saved_routes = Some(Arc::clone(saved_routes));
+            }
+
+            if let Some(storage) = &storage {
+                app.events
+                    .add_sink(Box::new(StorageSink::new(Arc::clone(storage))));
+                match storage.load_monitor_levels() {
+                    Ok(levels) => {
+                        app.mixer = MonitorMixer::new(config.audio_monitor.default_gain_db)
+                            .with_levels(levels)
+                    }
+                    Err(e) => error!("Failed to load monitoring levels: {}", e),
+                }
+                app.storage = Some(Arc::clone(storage));
+            }
+
+            if training || config.gui.training_mode {
+                app.set_training(true);
+            }
+
+            if config.selftest.enabled {
+                app.selftest_panel.start();
+                app.show_selftest_panel = true;
+            }
+
+            let mut control = CompanionControl::from_config(Arc::clone(&app.router), &config)
+                .with_events(app.events.clone())
+                .with_discovery(Arc::clone(&app.discovery))
+                .with_markers(app.marker_requests.clone());
+            if let Some(timecode) = &app.timecode {
+                control = control.with_timecode(Arc::clone(timecode));
+            }
+            let control = Arc::new(control);
+            app.camera_panel.set_control(Arc::clone(&control));
+            app.control = Some(Arc::clone(&control));
+
+            if let Some(timecode) = &app.timecode {
+                timecode.start(Some(Arc::clone(&control)));
+            }
+
+            if config.mqtt.enabled {
+                MqttBridge::new(
+                    &config,
+                    Arc::clone(&app.router),
+                    Arc::clone(&app.discovery),
+                    Arc::clone(&control),
+                )
+                .start();
+            }
+
+            if !config.ndi.remote_tally.is_empty() {
+                RemoteTallyWatcher::new(
+                    Arc::clone(&app.discovery),
+                    &config.ndi.remote_tally,
+                    Arc::clone(&app.metadata),
+                )
+                .start();
+            }
+
+            if config.matrix.ndi_routing {
+                RoutingDestinations::new(
+                    Arc::clone(&app.router),
+                    Arc::clone(&app.discovery),
+                    app.events.clone(),
+                )
+                .start();
+            }
+
+            let proxies = Arc::new(
+                ProxyManager::new(config.ndi.proxies.clone(), Arc::clone(&app.discovery))
+                    .with_supervisor(Arc::clone(&app.supervisor))
+                    .with_router(Arc::clone(&app.router)),
+            );
+            proxies.start_enabled();
+            app.proxies = Some(Arc::clone(&proxies));
+            bridge::start_joins(&config.ndi.bridges, &app.supervisor);
+
+            if config.api.enabled {
+                let state = ApiState {
+                    router: Arc::clone(&app.router),
+                    discovery: Arc::clone(&app.discovery),
+                    control,
+                    metadata: Arc::clone(&app.metadata),
+                    stats: Arc::clone(&app.stats),
+                    proxies,
+                    supervisor: Arc::clone(&app.supervisor),
+                    config: config.api.config_editing.then(|| {
+                        Arc::new(ConfigEditor::new(config_path.clone(), app.events.clone()))
+                    }),
+                    pairing: None,
+                    intercom: config
+                        .intercom
+                        .hosts()
+                        .then(|| Arc::new(IntercomHub::new())),
+                };
+                let port = config.api.port;
+                app.supervisor
+                    .spawn("Control API", move || api::serve(state.clone(), port));
+            }
+
+            if let Some(watch_folder) = app.watch_folder.as_ref().map(Arc::clone) {
+                tokio::spawn(async move {
+                    if let Err(e) = watch_folder.start().await {
+                        error!("Failed to start watch folder: {}", e);
+                    }
+                });
+            }
+
+            if let Some(audio_discovery) = &app.audio_discovery {
+                if let Err(e) = audio_discovery.start() {
+                    error!("Failed to start audio stream discovery: {}", e);
+                }
+            }
+
+            if let Some(propresenter) = &app.propresenter {
+                propresenter.start();
+            }
+
+            if let Some(intercom) = &app.intercom {
+                if config.intercom.hosts() && !config.api.enabled {
+                    error!("Hosting the intercom needs [api] enabled = true");
+                }
+                intercom.start();
+            }
 
             // Start async initialization in background
             let discovery = Arc::clone(&app.discovery);
@@ -0,0 +1,139 @@
+//! Background drawn behind slot video: empty slots and letterbox/pillarbox bars
+
+use crate::config::{GuiConfig, SlotBackground};
+use anyhow::{Context, Result};
+use eframe::egui::{self, Color32, Painter, Rect};
+use log::error;
+use std::path::PathBuf;
+
+/// Size of one checkerboard square in points
+const CHECKER_SIZE: f32 = 16.0;
+
+/// Logo size as a fraction of the slot
+const LOGO_SCALE: f32 = 0.5;
+
+/// Fallback when the configured color is invalid (the original slot gray)
+const DEFAULT_COLOR: Color32 = Color32::from_rgb(40, 40, 50);
+
+/// Paints the configured slot background
+pub struct BackgroundPainter {
+    style: SlotBackground,
+    color: Color32,
+    image: Option<PathBuf>,
+    /// Loaded logo texture (None inside when loading failed, so it is tried once)
+    texture: Option<Option<egui::TextureHandle>>,
+}
+
+impl BackgroundPainter {
+    pub fn from_config(config: &GuiConfig) -> Self {
+        let color = parse_color(&config.slot_color).unwrap_or_else(|e| {
+            error!("{}", e);
+            DEFAULT_COLOR
+        });
+        Self {
+            style: config.slot_background,
+            color,
+            image: config.slot_image.clone(),
+            texture: None,
+        }
+    }
+
+    /// Fill a slot rectangle with the background
+    pub fn paint(&mut self, ctx: &egui::Context, painter: &Painter, rect: Rect) {
+        painter.rect_filled(rect, 4.0, self.color);
+
+        match self.style {
+            SlotBackground::Color => {}
+            SlotBackground::Checkerboard => paint_checkerboard(painter, rect, self.color),
+            SlotBackground::Image => {
+                if let Some(texture) = self.logo(ctx) {
+                    let max = Rect::from_center_size(rect.center(), rect.size() * LOGO_SCALE);
+                    painter.image(
+                        texture.id(),
+                        super::app::fit_rect(max, texture.aspect_ratio()),
+                        Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        Color32::WHITE,
+                    );
+                }
+            }
+        }
+    }
+
+    fn logo(&mut self, ctx: &egui::Context) -> Option<egui::TextureHandle> {
+        let image = self.image.as_ref()?;
+        self.texture
+            .get_or_insert_with(|| match image::open(image) {
+                Ok(logo) => {
+                    let logo = logo.to_rgba8();
+                    let size = [logo.width() as usize, logo.height() as usize];
+                    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, logo.as_raw());
+                    Some(ctx.load_texture(
+                        "slot-background",
+                        color_image,
+                        egui::TextureOptions::LINEAR,
+                    ))
+                }
+                Err(e) => {
+                    error!("Failed to load slot background {:?}: {}", image, e);
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+/// Alternate the base color with a lighter shade
+fn paint_checkerboard(painter: &Painter, rect: Rect, color: Color32) {
+    let light = lighten(color, 20);
+    let painter = painter.with_clip_rect(rect);
+    let columns = (rect.width() / CHECKER_SIZE).ceil() as usize;
+    let rows = (rect.height() / CHECKER_SIZE).ceil() as usize;
+
+    for row in 0..rows {
+        for column in (row % 2..columns).step_by(2) {
+            let square = Rect::from_min_size(
+                rect.min + egui::vec2(column as f32, row as f32) * CHECKER_SIZE,
+                egui::vec2(CHECKER_SIZE, CHECKER_SIZE),
+            );
+            painter.rect_filled(square, 0.0, light);
+        }
+    }
+}
+
+fn lighten(color: Color32, amount: u8) -> Color32 {
+    Color32::from_rgb(
+        color.r().saturating_add(amount),
+        color.g().saturating_add(amount),
+        color.b().saturating_add(amount),
+    )
+}
+
+/// Parse a "#RRGGBB" color
+pub fn parse_color(s: &str) -> Result<Color32> {
+    let hex = s.trim().trim_start_matches('#');
+    // Checked before slicing, which would panic inside a multi-byte character
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("Invalid color '{}' (expected #RRGGBB)", s);
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).with_context(|| format!("Invalid color '{}'", s))
+    };
+    Ok(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(
+            parse_color("#282832").unwrap(),
+            Color32::from_rgb(40, 40, 50)
+        );
+        assert_eq!(parse_color("ff0000").unwrap(), Color32::RED);
+        assert!(parse_color("#fff").is_err());
+        assert!(parse_color("#gg0000").is_err());
+        assert!(parse_color("aébcd").is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use crate::birddog::{BirdDogClient, EncoderStats, StreamTransport, TransmitMode};
+use crate::companion::{CompanionControl, ShotResult};
+use crate::config::CameraConfig;
+use crate::gui::help::Help;
+use crate::ndi::{ReceiveBandwidth, ReceiverPool};
+use crate::network::format_bit_rate;
+use eframe::egui;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the selected camera's statistics are refreshed while the panel is open
+const STATS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Outcome of a scene recall per camera
+type SceneOutcome = Result<Vec<ShotResult>, String>;
+
+/// Result of a background camera request
+enum CameraTaskResult {
+    Transport(Result<StreamTransport, String>),
+    TransportApplied(Result<(), String>),
+    SceneRecalled(SceneOutcome),
+    /// Encoder statistics of the camera at this address
+    Stats(String, Result<EncoderStats, String>),
+}
+
+/// Panel for BirdDog camera maintenance settings
+pub struct CameraPanel {
+    /// Configured cameras
+    cameras: Vec<CameraConfig>,
+    /// Selected camera index
+    selected: Option<usize>,
+    /// Stream transport settings being edited
+    transport: Option<StreamTransport>,
+    /// Last status message
+    status: String,
+    /// Latest and previous encoder statistics of the selected camera
+    stats: Option<Result<EncoderStats, String>>,
+    previous_stats: Option<EncoderStats>,
+    /// When statistics were last requested
+    stats_requested: Option<Instant>,
+    /// Recalls camera scenes (scenes are hidden until set)
+    control: Option<Arc<CompanionControl>>,
+    /// Last scene recalled and its outcome per camera (None while recalling)
+    scene: Option<(String, Option<SceneOutcome>)>,
+    /// Channel for results from background requests
+    results_tx: Sender<CameraTaskResult>,
+    results_rx: Receiver<CameraTaskResult>,
+}
+
+impl CameraPanel {
+    pub fn new(cameras: Vec<CameraConfig>) -> Self {
+        let (results_tx, results_rx) = channel();
+        Self {
+            selected: if cameras.is_empty() { None } else { Some(0) },
+            cameras,
+            transport: None,
+            status: String::new(),
+            stats: None,
+            previous_stats: None,
+            stats_requested: None,
+            control: None,
+            scene: None,
+            results_tx,
+            results_rx,
+        }
+    }
+
+    /// Offer the scenes of this control handler
+    pub fn set_control(&mut self, control: Arc<CompanionControl>) {
+        self.control = Some(control);
+    }
+
+    /// Currently selected camera
+    fn selected_camera(&self) -> Option<&CameraConfig> {
+        self.selected.and_then(|idx| self.cameras.get(idx))
+    }
+
+    /// Apply results from finished background requests
+    fn poll_results(&mut self) {
+        while let Ok(result) = self.results_rx.try_recv() {
+            match result {
+                CameraTaskResult::Transport(Ok(transport)) => {
+                    self.status = format!("Loaded transport: {}", transport.mode);
+                    self.transport = Some(transport);
+                }
+                CameraTaskResult::Transport(Err(e)) => {
+                    self.status = format!("Failed to load transport: {}", e);
+                }
+                CameraTaskResult::TransportApplied(Ok(())) => {
+                    self.status = "Transport settings applied".to_string();
+                }
+                CameraTaskResult::TransportApplied(Err(e)) => {
+                    self.status = format!("Failed to apply transport: {}", e);
+                }
+                CameraTaskResult::SceneRecalled(result) => {
+                    if let Some((_, outcome)) = &mut self.scene {
+                        *outcome = Some(result);
+                    }
+                }
+                CameraTaskResult::Stats(ip, result) => {
+                    // Ignore answers from a camera no longer selected
+                    if self.selected_camera().map(|c| &c.ip_address) != Some(&ip) {
+                        continue;
+                    }
+                    if let Some(Ok(stats)) = self.stats.take() {
+                        self.previous_stats = Some(stats);
+                    }
+                    self.stats = Some(result);
+                }
+            }
+        }
+    }
+
+    fn fetch_transport(&mut self) {
+        let Some(camera) = self.selected_camera() else {
+            return;
+        };
+        let ip = camera.ip_address.clone();
+        let tx = self.results_tx.clone();
+        self.status = "Loading...".to_string();
+
+        tokio::spawn(async move {
+            let result = BirdDogClient::new(&ip)
+                .get_stream_transport()
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(CameraTaskResult::Transport(result));
+        });
+    }
+
+    /// Request the selected camera's statistics if they are due
+    fn poll_stats(&mut self) {
+        if self
+            .stats_requested
+            .is_some_and(|at| at.elapsed() < STATS_INTERVAL)
+        {
+            return;
+        }
+        let Some(camera) = self.selected_camera() else {
+            return;
+        };
+        let ip = camera.ip_address.clone();
+        let tx = self.results_tx.clone();
+        self.stats_requested = Some(Instant::now());
+
+        tokio::spawn(async move {
+            let result = BirdDogClient::new(&ip)
+                .get_encoder_stats()
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(CameraTaskResult::Stats(ip, result));
+        });
+    }
+
+    /// Draw the camera's send-side statistics next to what RusTV receives from it
+    fn show_stats(&self, ui: &mut egui::Ui, help: &Help, receivers: &ReceiverPool) {
+        let Some(camera) = self.selected_camera() else {
+            return;
+        };
+        ui.separator();
+        help.attach(ui.label("Link Statistics"), "camera.stats");
+
+        let camera_stats = match &self.stats {
+            Some(Ok(stats)) => Some(stats),
+            Some(Err(e)) => {
+                ui.weak(format!("Camera statistics unavailable: {}", e));
+                None
+            }
+            None => None,
+        };
+        let receiver = receivers.get(&camera.ndi_name);
+        let info = receiver.map(|r| r.stream_info());
+
+        egui::Grid::new("camera_stats_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("Camera sends");
+                ui.strong("RusTV receives");
+                ui.end_row();
+
+                ui.label("Bitrate");
+                ui.label(
+                    camera_stats.map_or("-".to_string(), |s| format_bit_rate(s.bytes_per_second())),
+                );
+                ui.label(
+                    receiver.map_or("-".to_string(), |r| format_bit_rate(r.bytes_per_second())),
+                );
+                ui.end_row();
+
+                ui.label("Format");
+                ui.label(camera_stats.map_or("-", |s| s.resolution.as_str()));
+                ui.label(info.as_ref().map_or("-".to_string(), |i| i.to_string()));
+                ui.end_row();
+
+                ui.label("Dropped");
+                ui.label(camera_stats.map_or("-".to_string(), |s| {
+                    format!("{} packets", s.dropped_packets)
+                }));
+                ui.label(receiver.map_or("-".to_string(), |r| {
+                    format!("{} frames", r.dropped_frames())
+                }));
+                ui.end_row();
+            });
+
+        if receiver.is_none() {
+            ui.weak(format!("{} is not being received", camera.ndi_name));
+        }
+        // Bitrates only compare when the full-quality stream is received
+        let received = receiver
+            .filter(|r| r.bandwidth() == ReceiveBandwidth::Full)
+            .map(|r| r.bytes_per_second());
+        if let Some(fault) =
+            camera_stats.and_then(|s| s.locate_fault(self.previous_stats.as_ref(), received))
+        {
+            ui.colored_label(
+                egui::Color32::from_rgb(220, 160, 60),
+                format!("⚠ {}", fault),
+            );
+        }
+    }
+
+    fn apply_transport(&mut self) {
+        let (Some(camera), Some(transport)) = (self.selected_camera(), self.transport.clone())
+        else {
+            return;
+        };
+        let ip = camera.ip_address.clone();
+        let tx = self.results_tx.clone();
+        self.status = "Applying...".to_string();
+
+        tokio::spawn(async move {
+            let result = BirdDogClient::new(&ip)
+                .set_stream_transport(&transport)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(CameraTaskResult::TransportApplied(result));
+        });
+    }
+
+    fn recall_scene(&mut self, name: String) {
+        let Some(control) = self.control.clone() else {
+            return;
+        };
+        let tx = self.results_tx.clone();
+        self.scene = Some((name.clone(), None));
+
+        tokio::spawn(async move {
+            let result = control.recall_scene(&name).await.map_err(|e| e.to_string());
+            let _ = tx.send(CameraTaskResult::SceneRecalled(result));
+        });
+    }
+
+    /// Draw a button per camera scene and how each camera took the last one
+    fn show_scenes(&mut self, ui: &mut egui::Ui) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        let names = control.scene_names();
+        if names.is_empty() {
+            return;
+        }
+
+        ui.label("Scenes");
+        let mut recall = None;
+        ui.horizontal_wrapped(|ui| {
+            for name in names {
+                if ui.button(&name).clicked() {
+                    recall = Some(name);
+                }
+            }
+        });
+        if let Some(name) = recall {
+            self.recall_scene(name);
+        }
+
+        match &self.scene {
+            Some((name, None)) => {
+                ui.label(format!("Recalling {}...", name));
+            }
+            Some((_, Some(Ok(results)))) => {
+                for result in results {
+                    match &result.result {
+                        Ok(()) => ui.label(format!("✓ {}", result.shot)),
+                        Err(e) => ui.colored_label(
+                            egui::Color32::from_rgb(220, 80, 80),
+                            format!("✗ {}: {}", result.shot, e),
+                        ),
+                    };
+                }
+            }
+            Some((name, Some(Err(e)))) => {
+                ui.label(format!("Failed to recall {}: {}", name, e));
+            }
+            None => {}
+        }
+        ui.separator();
+    }
+
+    /// Draw the panel
+    pub fn show(&mut self, ui: &mut egui::Ui, help: &Help, receivers: &ReceiverPool) {
+        self.poll_results();
+        self.show_scenes(ui);
+
+        if self.cameras.is_empty() {
+            ui.label("No BirdDog cameras configured");
+            return;
+        }
+
+        let selected_name = self
+            .selected_camera()
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
+        let mut selected = self.selected;
+        let combo = egui::ComboBox::from_label("Camera")
+            .selected_text(selected_name)
+            .show_ui(ui, |ui| {
+                for (idx, camera) in self.cameras.iter().enumerate() {
+                    ui.selectable_value(&mut selected, Some(idx), &camera.name);
+                }
+            });
+        help.attach(combo.response, "camera.select");
+        if selected != self.selected {
+            self.selected = selected;
+            self.transport = None;
+            self.status.clear();
+            self.stats = None;
+            self.previous_stats = None;
+            self.stats_requested = None;
+        }
+        self.poll_stats();
+        self.show_stats(ui, help, receivers);
+
+        ui.separator();
+        ui.label("NDI Stream Transport");
+
+        if help
+            .attach(ui.button("🔄 Fetch Settings"), "camera.fetch")
+            .clicked()
+        {
+            self.fetch_transport();
+        }
+
+        let mut apply = false;
+        if let Some(transport) = self.transport.as_mut() {
+            let mut multicast = transport.mode.is_multicast();
+            let response = ui.checkbox(&mut multicast, "Multicast");
+            if help.attach(response, "camera.multicast").changed() {
+                transport.set_multicast(multicast);
+            }
+
+            let combo = egui::ComboBox::from_label("Mode")
+                .selected_text(transport.mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in TransmitMode::all() {
+                        ui.selectable_value(&mut transport.mode, mode, mode.to_string());
+                    }
+                });
+            help.attach(combo.response, "camera.mode");
+
+            ui.add_enabled_ui(transport.mode.is_multicast(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Multicast prefix:");
+                    ui.text_edit_singleline(&mut transport.multicast_prefix);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Netmask:");
+                    ui.text_edit_singleline(&mut transport.multicast_mask);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("TTL:");
+                    ui.add(egui::DragValue::new(&mut transport.multicast_ttl).range(1..=255));
+                });
+            });
+
+            apply = help.attach(ui.button("✔ Apply"), "camera.apply").clicked();
+        }
+        if apply {
+            self.apply_transport();
+        }
+
+        if !self.status.is_empty() {
+            ui.label(&self.status);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+/// Minimum distance of the wipe divider from either edge (fraction of slot width)
+const DIVIDER_MARGIN: f32 = 0.05;
+
+/// Split-screen comparison of two sources with a draggable vertical wipe
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareView {
+    /// Source shown on the left of the divider
+    pub left: String,
+    /// Source shown on the right of the divider
+    pub right: String,
+    /// Divider position as a fraction of the slot width (0.0 to 1.0)
+    divider: f32,
+}
+
+impl CompareView {
+    pub fn new(left: String, right: String) -> Self {
+        Self {
+            left,
+            right,
+            divider: 0.5,
+        }
+    }
+
+    /// Get the divider position as a fraction of the slot width
+    #[allow(dead_code)]
+    pub fn divider(&self) -> f32 {
+        self.divider
+    }
+
+    /// Set the divider position, keeping a small margin from both edges
+    pub fn set_divider(&mut self, divider: f32) {
+        self.divider = divider.clamp(DIVIDER_MARGIN, 1.0 - DIVIDER_MARGIN);
+    }
+
+    /// Move the divider by a pixel delta within a slot of the given width
+    pub fn drag_by(&mut self, delta_px: f32, slot_width: f32) {
+        if slot_width > 0.0 {
+            self.set_divider(self.divider + delta_px / slot_width);
+        }
+    }
+
+    /// Swap the left and right sources
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+    }
+
+    /// Split a horizontal span (x, width) at the divider into left and right spans
+    pub fn split(&self, x: f32, width: f32) -> ((f32, f32), (f32, f32)) {
+        let left_width = width * self.divider;
+        ((x, left_width), (x + left_width, width - left_width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_divider_clamping() {
+        let mut compare = CompareView::new("Cam 1".to_string(), "Cam 2".to_string());
+        assert_eq!(compare.divider(), 0.5);
+
+        compare.set_divider(2.0);
+        assert_eq!(compare.divider(), 1.0 - DIVIDER_MARGIN);
+
+        compare.set_divider(-1.0);
+        assert_eq!(compare.divider(), DIVIDER_MARGIN);
+    }
+
+    #[test]
+    fn test_drag_and_split() {
+        let mut compare = CompareView::new("Cam 1".to_string(), "Cam 2".to_string());
+        compare.drag_by(100.0, 400.0);
+        assert!((compare.divider() - 0.75).abs() < f32::EPSILON);
+
+        let ((lx, lw), (rx, rw)) = compare.split(10.0, 400.0);
+        assert_eq!(lx, 10.0);
+        assert_eq!(lw, 300.0);
+        assert_eq!(rx, 310.0);
+        assert_eq!(rw, 100.0);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut compare = CompareView::new("Cam 1".to_string(), "Cam 2".to_string());
+        compare.swap();
+        assert_eq!(compare.left, "Cam 2");
+        assert_eq!(compare.right, "Cam 1");
+    }
+}
@@ -0,0 +1,396 @@
+//! Connected displays, identified by connector and EDID, for placing per-output
+//! fullscreen windows and noticing when displays are plugged or unplugged
+//!
+//! Displays are read with X11 RandR. Where that is unavailable (e.g. on Wayland)
+//! the monitor the main window is on is reported instead, as [`PRIMARY_CONNECTOR`].
+
+use crate::config::OutputWindowConfig;
+use anyhow::Result;
+use eframe::egui;
+use log::{debug, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often displays are checked for hot-plug changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Connector name of the monitor reported when RandR is unavailable
+pub const PRIMARY_CONNECTOR: &str = "primary";
+
+/// A connected, active display
+#[derive(Debug, Clone, PartialEq)]
+pub struct Display {
+    /// Connector name (e.g. "HDMI-1")
+    pub connector: String,
+    /// Monitor name from the EDID (e.g. "DELL U2720Q")
+    pub model: Option<String>,
+    /// Manufacturer, product code and serial from the EDID (e.g. "DEL-A0B1-5ZKQ2V3")
+    pub edid_id: Option<String>,
+    /// Position on the desktop, in pixels
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Display {
+    /// Whether a saved display name refers to this display (EDID id, model or connector)
+    pub fn matches(&self, name: &str) -> bool {
+        self.edid_id.as_deref() == Some(name)
+            || self.model.as_deref() == Some(name)
+            || self.connector == name
+    }
+
+    /// Short description for notices and panels
+    pub fn label(&self) -> String {
+        match &self.model {
+            Some(model) => format!("{} ({})", model, self.connector),
+            None => self.connector.clone(),
+        }
+    }
+
+    /// The monitor the main window is on, as the windowing system reports it
+    /// (no EDID or desktop position)
+    pub fn from_viewport(ctx: &egui::Context) -> Option<Self> {
+        let size = ctx.input(|i| i.viewport().monitor_size)?;
+        let scale = ctx.native_pixels_per_point().unwrap_or(1.0);
+        Some(Self {
+            connector: PRIMARY_CONNECTOR.to_string(),
+            model: None,
+            edid_id: None,
+            x: 0,
+            y: 0,
+            width: (size.x * scale).round() as u32,
+            height: (size.y * scale).round() as u32,
+        })
+    }
+}
+
+/// Display of each output window, in configuration order (None while it is missing)
+pub fn place<'a>(
+    windows: &'a [OutputWindowConfig],
+    displays: &'a [Display],
+) -> Vec<(&'a OutputWindowConfig, Option<&'a Display>)> {
+    windows
+        .iter()
+        .map(|window| {
+            // A specific EDID id wins over a model name shared by identical monitors
+            let display = displays
+                .iter()
+                .find(|d| d.edid_id.as_deref() == Some(window.display.as_str()))
+                .or_else(|| displays.iter().find(|d| d.matches(&window.display)));
+            (window, display)
+        })
+        .collect()
+}
+
+/// A display plugged in or unplugged since the last check
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayChange {
+    Connected(Display),
+    Disconnected(Display),
+}
+
+/// Displays that appeared or disappeared between two checks
+pub fn changes(old: &[Display], new: &[Display]) -> Vec<DisplayChange> {
+    let same = |a: &Display, b: &Display| a.connector == b.connector && a.edid_id == b.edid_id;
+    let mut changes: Vec<DisplayChange> = old
+        .iter()
+        .filter(|d| !new.iter().any(|n| same(d, n)))
+        .cloned()
+        .map(DisplayChange::Disconnected)
+        .collect();
+    changes.extend(
+        new.iter()
+            .filter(|d| !old.iter().any(|o| same(d, o)))
+            .cloned()
+            .map(DisplayChange::Connected),
+    );
+    changes
+}
+
+/// Identity read from an EDID block
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdidInfo {
+    pub model: Option<String>,
+    pub id: String,
+}
+
+/// Parse the manufacturer, product, serial and monitor name from an EDID
+pub fn parse_edid(edid: &[u8]) -> Option<EdidInfo> {
+    const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+    if edid.len() < 128 || edid[..8] != HEADER {
+        return None;
+    }
+
+    // Three 5-bit letters, 'A' = 1
+    let packed = u16::from_be_bytes([edid[8], edid[9]]);
+    let manufacturer: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (b'A' - 1 + ((packed >> shift) & 0x1f) as u8) as char)
+        .collect();
+    let product = u16::from_le_bytes([edid[10], edid[11]]);
+    let mut serial = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]).to_string();
+
+    let mut model = None;
+    for descriptor in edid[54..126].chunks(18) {
+        if descriptor[..3] != [0, 0, 0] {
+            continue;
+        }
+        let text = || {
+            let bytes = &descriptor[5..];
+            let end = bytes.iter().position(|&b| b == 0x0a).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+        };
+        match descriptor[3] {
+            0xfc => model = Some(text()),
+            0xff => serial = text(),
+            _ => {}
+        }
+    }
+
+    Some(EdidInfo {
+        model,
+        id: format!("{}-{:04X}-{}", manufacturer, product, serial),
+    })
+}
+
+/// List the connected displays
+pub fn probe() -> Result<Vec<Display>> {
+    RandrProbe::connect()?.displays()
+}
+
+/// Connection to the X server for reading displays with RandR
+#[cfg(target_os = "linux")]
+struct RandrProbe {
+    conn: x11rb::rust_connection::RustConnection,
+    root: x11rb::protocol::xproto::Window,
+    edid_atom: x11rb::protocol::xproto::Atom,
+}
+
+#[cfg(target_os = "linux")]
+impl RandrProbe {
+    fn connect() -> Result<Self> {
+        use anyhow::Context;
+        use x11rb::connection::Connection;
+        use x11rb::protocol::randr::ConnectionExt as _;
+        use x11rb::protocol::xproto::ConnectionExt as _;
+
+        let (conn, screen) = x11rb::connect(None).context("Failed to connect to the X server")?;
+        let root = conn.setup().roots[screen].root;
+        conn.randr_query_version(1, 3)?
+            .reply()
+            .context("RandR is not available")?;
+        let edid_atom = conn.intern_atom(false, b"EDID")?.reply()?.atom;
+        Ok(Self {
+            conn,
+            root,
+            edid_atom,
+        })
+    }
+
+    fn displays(&self) -> Result<Vec<Display>> {
+        use anyhow::Context;
+        use x11rb::protocol::randr::{self, ConnectionExt as _};
+        use x11rb::protocol::xproto::AtomEnum;
+
+        let conn = &self.conn;
+        let resources = conn
+            .randr_get_screen_resources_current(self.root)?
+            .reply()
+            .context("RandR is not available")?;
+
+        let mut displays = Vec::new();
+        for output in resources.outputs {
+            let info = conn
+                .randr_get_output_info(output, resources.config_timestamp)?
+                .reply()?;
+            // Connected but switched off displays have no CRTC and can't show windows
+            if info.connection != randr::Connection::CONNECTED || info.crtc == 0 {
+                continue;
+            }
+            let crtc = conn
+                .randr_get_crtc_info(info.crtc, resources.config_timestamp)?
+                .reply()?;
+            let edid = conn
+                .randr_get_output_property(
+                    output,
+                    self.edid_atom,
+                    AtomEnum::ANY,
+                    0,
+                    64,
+                    false,
+                    false,
+                )?
+                .reply()?;
+            let edid = parse_edid(&edid.data);
+            displays.push(Display {
+                connector: String::from_utf8_lossy(&info.name).into_owned(),
+                model: edid.as_ref().and_then(|e| e.model.clone()),
+                edid_id: edid.map(|e| e.id),
+                x: crtc.x.into(),
+                y: crtc.y.into(),
+                width: crtc.width.into(),
+                height: crtc.height.into(),
+            });
+        }
+        displays.sort_by_key(|d| (d.x, d.y));
+        Ok(displays)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+struct RandrProbe;
+
+#[cfg(not(target_os = "linux"))]
+impl RandrProbe {
+    fn connect() -> Result<Self> {
+        anyhow::bail!("Display detection is only supported on Linux (X11)")
+    }
+
+    fn displays(&self) -> Result<Vec<Display>> {
+        unreachable!()
+    }
+}
+
+/// Keeps the list of connected displays up to date on a background thread,
+/// until dropped
+pub struct DisplayWatcher {
+    /// None when RandR can't be used, so displays come from the windowing system
+    displays: Arc<Mutex<Option<Vec<Display>>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl DisplayWatcher {
+    pub fn start() -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let probe = match RandrProbe::connect() {
+            Ok(probe) => probe,
+            Err(e) => {
+                warn!(
+                    "Cannot detect displays, using the window's monitor: {:#}",
+                    e
+                );
+                return Self {
+                    displays: Arc::new(Mutex::new(None)),
+                    stop,
+                };
+            }
+        };
+        let displays = Arc::new(Mutex::new(Some(probe.displays().unwrap_or_else(|e| {
+            warn!("Cannot detect displays: {:#}", e);
+            Vec::new()
+        }))));
+
+        let shared = Arc::clone(&displays);
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            // The connection is kept, and only opened again after it fails
+            let mut probe = Some(probe);
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let result = match &probe {
+                    Some(probe) => probe.displays(),
+                    None => RandrProbe::connect().and_then(|new| {
+                        let found = new.displays();
+                        probe = Some(new);
+                        found
+                    }),
+                };
+                match result {
+                    Ok(found) => *shared.lock().unwrap() = Some(found),
+                    // Keep the last known displays rather than closing every window
+                    Err(e) => {
+                        debug!("Display check failed: {:#}", e);
+                        probe = None;
+                    }
+                }
+            }
+        });
+        Self { displays, stop }
+    }
+
+    /// Displays found by the latest check, or None without RandR
+    pub fn displays(&self) -> Option<Vec<Display>> {
+        self.displays.lock().unwrap().clone()
+    }
+}
+
+impl Drop for DisplayWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(connector: &str, edid_id: Option<&str>) -> Display {
+        Display {
+            connector: connector.to_string(),
+            model: Some("DELL U2720Q".to_string()),
+            edid_id: edid_id.map(str::to_string),
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    #[test]
+    fn test_parse_edid() {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        // "DEL" packed as 5-bit letters
+        edid[8..10].copy_from_slice(&0x10acu16.to_be_bytes());
+        edid[10..12].copy_from_slice(&0xa0b1u16.to_le_bytes());
+        edid[12..16].copy_from_slice(&42u32.to_le_bytes());
+        edid[54..57].copy_from_slice(&[0, 0, 0]);
+        edid[57] = 0xfc;
+        edid[59..72].copy_from_slice(b"DELL U2720Q\n ");
+
+        let info = parse_edid(&edid).unwrap();
+        assert_eq!(info.model.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(info.id, "DEL-A0B1-42");
+        assert!(parse_edid(&edid[..100]).is_none());
+    }
+
+    #[test]
+    fn test_place_and_changes() {
+        let windows = vec![
+            OutputWindowConfig {
+                output: "Monitor 1".to_string(),
+                display: "DEL-A0B1-2".to_string(),
+                power: Default::default(),
+            },
+            OutputWindowConfig {
+                output: "Monitor 2".to_string(),
+                display: "DP-1".to_string(),
+                power: Default::default(),
+            },
+        ];
+        // Two identical models: the EDID id picks the right one
+        let displays = vec![
+            display("HDMI-1", Some("DEL-A0B1-1")),
+            display("HDMI-2", Some("DEL-A0B1-2")),
+        ];
+        let placed = place(&windows, &displays);
+        assert_eq!(placed[0].1.unwrap().connector, "HDMI-2");
+        assert!(placed[1].1.is_none());
+
+        let after = vec![displays[1].clone(), display("DP-1", None)];
+        assert_eq!(
+            changes(&displays, &after),
+            vec![
+                DisplayChange::Disconnected(displays[0].clone()),
+                DisplayChange::Connected(display("DP-1", None)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Inline help: tooltips for GUI controls and a guided tour, read from a help
+//! resource file
+
+use crate::gui::profiles::Panel;
+use anyhow::{Context, Result};
+use eframe::egui;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Help shipped with RusTV
+const BUILTIN_HELP: &str = include_str!("help.toml");
+
+/// One step of the guided tour
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TourStep {
+    pub title: String,
+    pub text: String,
+    /// Control highlighted during the step
+    pub target: String,
+    /// Panel opened for the step
+    #[serde(default)]
+    pub panel: Option<Panel>,
+}
+
+/// Contents of a help file
+#[derive(Debug, Clone, Default, Deserialize)]
+struct HelpFile {
+    #[serde(default)]
+    tooltips: HashMap<String, String>,
+    #[serde(default)]
+    tour: Vec<TourStep>,
+}
+
+/// Tooltips and the guided tour
+#[derive(Debug, Default)]
+pub struct Help {
+    tooltips: HashMap<String, String>,
+    tour: Vec<TourStep>,
+    /// Current tour step, while the tour runs
+    step: Option<usize>,
+    /// Screen rectangle of each control with help, from the last frame
+    targets: RefCell<HashMap<String, egui::Rect>>,
+}
+
+impl Help {
+    /// The built-in help
+    pub fn builtin() -> Self {
+        let file: HelpFile = toml::from_str(BUILTIN_HELP).expect("built-in help is valid");
+        Self {
+            tooltips: file.tooltips,
+            tour: file.tour,
+            ..Self::default()
+        }
+    }
+
+    /// The built-in help with entries from a venue's help file on top (its tour, if
+    /// it has one, replaces the built-in tour)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref()).context("Failed to read help file")?;
+        let file: HelpFile = toml::from_str(&content).context("Failed to parse help file")?;
+
+        let mut help = Self::builtin();
+        help.tooltips.extend(file.tooltips);
+        if !file.tour.is_empty() {
+            help.tour = file.tour;
+        }
+        Ok(help)
+    }
+
+    pub fn tooltip(&self, id: &str) -> Option<&str> {
+        self.tooltips.get(id).map(String::as_str)
+    }
+
+    /// Add a control's tooltip and remember where it is for the tour
+    pub fn attach(&self, response: egui::Response, id: &str) -> egui::Response {
+        self.mark(id, response.rect);
+        match self.tooltip(id) {
+            Some(text) => response.on_hover_text(text).on_disabled_hover_text(text),
+            None => response,
+        }
+    }
+
+    /// Remember where a control or area is for the tour
+    pub fn mark(&self, id: &str, rect: egui::Rect) {
+        self.targets.borrow_mut().insert(id.to_string(), rect);
+    }
+
+    /// Where a control was last drawn
+    pub fn target(&self, id: &str) -> Option<egui::Rect> {
+        self.targets.borrow().get(id).copied()
+    }
+
+    pub fn start_tour(&mut self) {
+        self.step = (!self.tour.is_empty()).then_some(0);
+    }
+
+    pub fn end_tour(&mut self) {
+        self.step = None;
+    }
+
+    /// Current step and its position (zero-based) in the tour
+    pub fn current_step(&self) -> Option<(usize, &TourStep)> {
+        let step = self.step?;
+        Some((step, self.tour.get(step)?))
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.tour.len()
+    }
+
+    /// Advance the tour, ending it after the last step
+    pub fn next_step(&mut self) {
+        self.step = self.step.map(|s| s + 1).filter(|s| *s < self.tour.len());
+    }
+
+    pub fn previous_step(&mut self) {
+        self.step = self.step.map(|s| s.saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_help() {
+        let help = Help::builtin();
+        assert!(help.tooltip("routing.route_selected").is_some());
+        assert!(help.step_count() > 0);
+    }
+
+    #[test]
+    fn test_tour_steps() {
+        let mut help = Help::builtin();
+        assert!(help.current_step().is_none());
+
+        help.start_tour();
+        assert_eq!(help.current_step().unwrap().0, 0);
+        help.previous_step();
+        assert_eq!(help.current_step().unwrap().0, 0);
+        for _ in 1..help.step_count() {
+            help.next_step();
+        }
+        assert_eq!(help.current_step().unwrap().0, help.step_count() - 1);
+        help.next_step();
+        assert!(help.current_step().is_none());
+    }
+
+    #[test]
+    fn test_venue_help_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("help.toml");
+        fs::write(
+            &path,
+            r#"
+            [tooltips]
+            "routing.refresh" = "Ask the tech desk before refreshing"
+            "#,
+        )
+        .unwrap();
+
+        let help = Help::load(&path).unwrap();
+        assert_eq!(
+            help.tooltip("routing.refresh"),
+            Some("Ask the tech desk before refreshing")
+        );
+        // Entries the venue leaves out keep the built-in text, and so does the tour
+        assert!(help.tooltip("routing.route_selected").is_some());
+        assert_eq!(help.step_count(), Help::builtin().step_count());
+    }
+}
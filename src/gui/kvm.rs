@@ -0,0 +1,252 @@
+//! Mouse and keyboard of a focused slot, translated into NDI KVM events
+
+use crate::ndi::kvm::{KvmEvent, MouseButton};
+use eframe::egui;
+use std::collections::HashSet;
+
+const XK_SHIFT_L: u32 = 0xffe1;
+const XK_CONTROL_L: u32 = 0xffe3;
+const XK_ALT_L: u32 = 0xffe9;
+const XK_SUPER_L: u32 = 0xffeb;
+
+/// Forwards input from one slot to the source shown in it
+#[derive(Debug)]
+pub struct KvmFocus {
+    /// Output of the focused slot
+    pub output: String,
+    /// Modifier keys the remote machine has been told are held
+    modifiers: egui::Modifiers,
+    /// Mouse buttons the remote machine has been told are held
+    buttons: HashSet<MouseButton>,
+    /// Keys the remote machine has been told are held
+    keys: HashSet<u32>,
+}
+
+impl KvmFocus {
+    pub fn new(output: String) -> Self {
+        Self {
+            output,
+            modifiers: egui::Modifiers::NONE,
+            buttons: HashSet::new(),
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Translate this frame's input, with the pointer mapped onto the video area
+    pub fn translate(&mut self, events: &[egui::Event], video: egui::Rect) -> Vec<KvmEvent> {
+        let mut out = Vec::new();
+        for event in events {
+            match event {
+                egui::Event::PointerMoved(pos) if video.contains(*pos) => {
+                    out.push(position(video, *pos));
+                }
+                egui::Event::PointerButton {
+                    pos,
+                    button,
+                    pressed,
+                    modifiers,
+                } => {
+                    let Some(button) = mouse_button(*button) else {
+                        continue;
+                    };
+                    self.sync_modifiers(*modifiers, &mut out);
+                    if *pressed && video.contains(*pos) {
+                        out.push(position(video, *pos));
+                        out.push(KvmEvent::MouseDown(button));
+                        self.buttons.insert(button);
+                    } else if !*pressed && self.buttons.remove(&button) {
+                        // Always release what was pressed, even outside the video
+                        out.push(KvmEvent::MouseUp(button));
+                    }
+                }
+                egui::Event::MouseWheel {
+                    unit,
+                    delta,
+                    modifiers,
+                } => {
+                    self.sync_modifiers(*modifiers, &mut out);
+                    let notches = match unit {
+                        egui::MouseWheelUnit::Point => *delta / 50.0,
+                        egui::MouseWheelUnit::Line | egui::MouseWheelUnit::Page => *delta,
+                    };
+                    let (vertical, horizontal) =
+                        (notches.y.round() as i32, notches.x.round() as i32);
+                    if vertical != 0 || horizontal != 0 {
+                        out.push(KvmEvent::Wheel {
+                            vertical,
+                            horizontal: -horizontal,
+                        });
+                    }
+                }
+                egui::Event::Key {
+                    key,
+                    pressed,
+                    modifiers,
+                    ..
+                } => {
+                    self.sync_modifiers(*modifiers, &mut out);
+                    let Some(keysym) = keysym(*key) else {
+                        continue;
+                    };
+                    if *pressed {
+                        // Key repeat is left to the remote machine
+                        if self.keys.insert(keysym) {
+                            out.push(KvmEvent::KeyDown(keysym));
+                        }
+                    } else if self.keys.remove(&keysym) {
+                        out.push(KvmEvent::KeyUp(keysym));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// Release everything still held, before focus leaves the slot
+    pub fn release(&mut self) -> Vec<KvmEvent> {
+        let mut out: Vec<KvmEvent> = self.keys.drain().map(KvmEvent::KeyUp).collect();
+        out.extend(self.buttons.drain().map(KvmEvent::MouseUp));
+        self.sync_modifiers(egui::Modifiers::NONE, &mut out);
+        out
+    }
+
+    /// Press or release modifier keys to match the local keyboard
+    fn sync_modifiers(&mut self, modifiers: egui::Modifiers, out: &mut Vec<KvmEvent>) {
+        let pairs = [
+            (self.modifiers.shift, modifiers.shift, XK_SHIFT_L),
+            (self.modifiers.ctrl, modifiers.ctrl, XK_CONTROL_L),
+            (self.modifiers.alt, modifiers.alt, XK_ALT_L),
+            (self.modifiers.mac_cmd, modifiers.mac_cmd, XK_SUPER_L),
+        ];
+        for (was, now, keysym) in pairs {
+            match (was, now) {
+                (false, true) => out.push(KvmEvent::KeyDown(keysym)),
+                (true, false) => out.push(KvmEvent::KeyUp(keysym)),
+                _ => {}
+            }
+        }
+        self.modifiers = modifiers;
+    }
+}
+
+fn position(video: egui::Rect, pos: egui::Pos2) -> KvmEvent {
+    KvmEvent::MouseMove {
+        x: (pos.x - video.min.x) / video.width(),
+        y: (pos.y - video.min.y) / video.height(),
+    }
+}
+
+fn mouse_button(button: egui::PointerButton) -> Option<MouseButton> {
+    match button {
+        egui::PointerButton::Primary => Some(MouseButton::Left),
+        egui::PointerButton::Middle => Some(MouseButton::Middle),
+        egui::PointerButton::Secondary => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
+/// X11 keysym of a key (unshifted; the remote machine applies Shift itself)
+pub fn keysym(key: egui::Key) -> Option<u32> {
+    use egui::Key;
+
+    Some(match key {
+        Key::ArrowLeft => 0xff51,
+        Key::ArrowUp => 0xff52,
+        Key::ArrowRight => 0xff53,
+        Key::ArrowDown => 0xff54,
+        Key::Escape => 0xff1b,
+        Key::Tab => 0xff09,
+        Key::Backspace => 0xff08,
+        Key::Enter => 0xff0d,
+        Key::Insert => 0xff63,
+        Key::Delete => 0xffff,
+        Key::Home => 0xff50,
+        Key::End => 0xff57,
+        Key::PageUp => 0xff55,
+        Key::PageDown => 0xff56,
+        Key::Space => 0x20,
+        Key::Minus => 0x2d,
+        Key::Equals | Key::Plus => 0x3d,
+        Key::Comma => 0x2c,
+        Key::Period => 0x2e,
+        Key::Semicolon | Key::Colon => 0x3b,
+        Key::Quote => 0x27,
+        Key::Slash | Key::Questionmark => 0x2f,
+        Key::Backslash | Key::Pipe => 0x5c,
+        Key::OpenBracket => 0x5b,
+        Key::CloseBracket => 0x5d,
+        Key::Backtick => 0x60,
+        Key::F1 => 0xffbe,
+        Key::F2 => 0xffbf,
+        Key::F3 => 0xffc0,
+        Key::F4 => 0xffc1,
+        Key::F5 => 0xffc2,
+        Key::F6 => 0xffc3,
+        Key::F7 => 0xffc4,
+        Key::F8 => 0xffc5,
+        Key::F9 => 0xffc6,
+        Key::F10 => 0xffc7,
+        Key::F11 => 0xffc8,
+        Key::F12 => 0xffc9,
+        // Letters and digits are named by their character
+        _ => {
+            let mut chars = key.name().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => c.to_ascii_lowercase() as u32,
+                _ => return None,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keysym() {
+        assert_eq!(keysym(egui::Key::A), Some('a' as u32));
+        assert_eq!(keysym(egui::Key::Num7), Some('7' as u32));
+        assert_eq!(keysym(egui::Key::Enter), Some(0xff0d));
+        assert_eq!(keysym(egui::Key::Copy), None);
+    }
+
+    #[test]
+    fn test_translate_and_release() {
+        let video = egui::Rect::from_min_size(egui::pos2(100.0, 100.0), egui::vec2(200.0, 100.0));
+        let mut focus = KvmFocus::new("Monitor 1".to_string());
+
+        let events = focus.translate(
+            &[
+                egui::Event::PointerMoved(egui::pos2(10.0, 10.0)),
+                egui::Event::PointerButton {
+                    pos: egui::pos2(150.0, 175.0),
+                    button: egui::PointerButton::Primary,
+                    pressed: true,
+                    modifiers: egui::Modifiers::SHIFT,
+                },
+            ],
+            video,
+        );
+        assert_eq!(
+            events,
+            vec![
+                KvmEvent::KeyDown(XK_SHIFT_L),
+                KvmEvent::MouseMove { x: 0.25, y: 0.75 },
+                KvmEvent::MouseDown(MouseButton::Left),
+            ]
+        );
+
+        let mut released = focus.release();
+        released.sort_by_key(|e| format!("{:?}", e));
+        assert_eq!(
+            released,
+            vec![
+                KvmEvent::KeyUp(XK_SHIFT_L),
+                KvmEvent::MouseUp(MouseButton::Left)
+            ]
+        );
+        assert!(focus.release().is_empty());
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Represents different matrix view layouts
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -55,6 +57,18 @@ impl Layout {
         ]
     }
 
+    /// Role of each view in the layout, in the order of `calculate_view_rects`:
+    /// the main view is Program, the next Preview and the rest Aux 1, 2, ...
+    pub fn slot_roles(&self) -> Vec<SlotRole> {
+        (0..self.view_count())
+            .map(|i| match i {
+                0 => SlotRole::Program,
+                1 => SlotRole::Preview,
+                n => SlotRole::Aux((n - 1) as u8),
+            })
+            .collect()
+    }
+
     /// Calculate the position and size for each view in the layout
     /// Returns (x, y, width, height) as fractions of the total area (0.0 to 1.0)
     pub fn calculate_view_rects(&self) -> Vec<(f32, f32, f32, f32)> {
@@ -155,6 +169,100 @@ impl Layout {
     }
 }
 
+/// Role of an output in every layout, so it keeps its window when the layout
+/// changes (Program stays in the big window going from 1+7 to PiP)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SlotRole {
+    Program,
+    Preview,
+    /// Numbered from 1
+    Aux(u8),
+}
+
+impl fmt::Display for SlotRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotRole::Program => write!(f, "Program"),
+            SlotRole::Preview => write!(f, "Preview"),
+            SlotRole::Aux(n) => write!(f, "Aux {}", n),
+        }
+    }
+}
+
+impl FromStr for SlotRole {
+    type Err = String;
+
+    /// Parse "program", "preview" or "aux1" (any case, spaces allowed)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_str() {
+            "program" => Ok(SlotRole::Program),
+            "preview" => Ok(SlotRole::Preview),
+            _ => name
+                .strip_prefix("aux")
+                .and_then(|n| n.parse().ok())
+                .filter(|&n| n > 0)
+                .map(SlotRole::Aux)
+                .ok_or_else(|| format!("Unknown slot role '{}'", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for SlotRole {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<SlotRole> for String {
+    fn from(role: SlotRole) -> Self {
+        role.to_string().replace(' ', "").to_lowercase()
+    }
+}
+
+/// Which slot each view of a layout shows, given each slot's role (in slot
+/// order). A view shows the slot with its role; views whose role no slot has
+/// take the remaining slots in order
+pub fn arrange(layout: Layout, roles: &[Option<SlotRole>]) -> Vec<usize> {
+    let views: Vec<SlotRole> = layout.slot_roles().into_iter().take(roles.len()).collect();
+    let mut shown: Vec<Option<usize>> = views
+        .iter()
+        .map(|view| roles.iter().position(|role| *role == Some(*view)))
+        .collect();
+
+    let taken: Vec<usize> = shown.iter().flatten().copied().collect();
+    let mut rest = (0..roles.len()).filter(|i| !taken.contains(i));
+    for view in &mut shown {
+        if view.is_none() {
+            *view = rest.next();
+        }
+    }
+    shown.into_iter().flatten().collect()
+}
+
+/// Slots a layout leaves out (see `arrange`), in slot order
+pub fn hidden(layout: Layout, roles: &[Option<SlotRole>]) -> Vec<usize> {
+    let shown = arrange(layout, roles);
+    (0..roles.len()).filter(|i| !shown.contains(i)).collect()
+}
+
+/// The layout with the fewest views that shows every routed slot, or the
+/// largest if none does. `routed` tells which slots have a route, in slot order
+pub fn smallest_fitting(roles: &[Option<SlotRole>], routed: &[bool]) -> Layout {
+    let mut layouts = Layout::all();
+    layouts.sort_by_key(Layout::view_count);
+    let fits = |layout: &Layout| hidden(*layout, roles).iter().all(|&i| !routed[i]);
+    let largest = layouts[layouts.len() - 1];
+    layouts.into_iter().find(fits).unwrap_or(largest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,4 +311,44 @@ mod tests {
         assert!(rects[1].0 >= 0.75); // Right edge views have x >= 0.75
         assert!(rects[7].1 >= 0.75); // Bottom edge views have y >= 0.75
     }
+
+    #[test]
+    fn test_slot_roles() {
+        assert_eq!("aux 3".parse::<SlotRole>(), Ok(SlotRole::Aux(3)));
+        assert_eq!("Program".parse::<SlotRole>(), Ok(SlotRole::Program));
+        assert!("aux0".parse::<SlotRole>().is_err());
+        assert_eq!(String::from(SlotRole::Aux(2)), "aux2");
+
+        // Program keeps the main window and Preview the PiP in any layout
+        let roles = [None, Some(SlotRole::Preview), Some(SlotRole::Program), None];
+        assert_eq!(arrange(Layout::PiP, &roles), vec![2, 1]);
+        assert_eq!(arrange(Layout::Grid2x2, &roles), vec![2, 1, 0, 3]);
+
+        // Unassigned roles take the remaining slots in order
+        let roles = [None, None, Some(SlotRole::Program)];
+        assert_eq!(arrange(Layout::OneAndSeven, &roles), vec![2, 0, 1]);
+        assert_eq!(arrange(Layout::PiP, &[None, None, None]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_smallest_fitting() {
+        let roles = vec![None; 6];
+        assert_eq!(hidden(Layout::Grid2x2, &roles), vec![4, 5]);
+
+        let mut routed = vec![true, true, false, false, false, false];
+        assert_eq!(smallest_fitting(&roles, &routed), Layout::PiP);
+        routed[5] = true;
+        assert_eq!(smallest_fitting(&roles, &routed), Layout::OneAndSeven);
+
+        // A role brings its slot into a smaller layout
+        let mut roles = roles;
+        roles[5] = Some(SlotRole::Preview);
+        assert_eq!(
+            smallest_fitting(&roles, &[false, false, false, false, false, true]),
+            Layout::PiP
+        );
+
+        let roles = vec![None; 20];
+        assert_eq!(smallest_fitting(&roles, &[true; 20]), Layout::Grid4x4);
+    }
 }
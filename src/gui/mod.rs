@@ -1,2 +1,13 @@
 pub mod app;
+pub mod background;
+pub mod camera_panel;
+pub mod compare;
+pub mod displays;
+pub mod help;
+pub mod kvm;
 pub mod layouts;
+pub mod power;
+pub mod profiles;
+pub mod selftest_panel;
+pub mod telestrator;
+pub mod wall;
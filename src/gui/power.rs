@@ -0,0 +1,131 @@
+//! Display power control, so screens fed by output windows really switch off while
+//! every output is blanked and wake again on resume
+
+use super::displays::Display;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::thread;
+
+/// How an output window's display is put to sleep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayPower {
+    /// Leave the display on (it shows black while blanked)
+    #[default]
+    None,
+    /// Switch this monitor's power mode over DDC/CI (`ddcutil`, Linux)
+    Ddc,
+    /// Signal standby to every display of the machine (DPMS on X11; the OS
+    /// equivalent on Windows and macOS). Most TVs go to standby without a signal
+    Dpms,
+}
+
+impl DisplayPower {
+    /// Command switching a display on or off (`None` if this method does nothing)
+    pub fn command(self, display: Option<&Display>, on: bool) -> Result<Option<Vec<String>>> {
+        let args: Vec<String> = match self {
+            DisplayPower::None => return Ok(None),
+            DisplayPower::Ddc => {
+                let id = display
+                    .and_then(|d| d.edid_id.as_deref())
+                    .context("DDC power control needs a connected display with an EDID")?;
+                // EDID ids are "manufacturer-product-serial"
+                let mut parts = id.splitn(3, '-');
+                let (Some(mfg), Some(_), Some(serial)) = (parts.next(), parts.next(), parts.next())
+                else {
+                    anyhow::bail!("Unexpected EDID id {}", id);
+                };
+                // VCP 0xD6 power mode: 1 = on, 4 = off
+                let mode = if on { "01" } else { "04" };
+                [
+                    "ddcutil", "--mfg", mfg, "--sn", serial, "setvcp", "D6", mode,
+                ]
+                .map(String::from)
+                .to_vec()
+            }
+            DisplayPower::Dpms => dpms_command(on),
+        };
+        Ok(Some(args))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn dpms_command(on: bool) -> Vec<String> {
+    // SC_MONITORPOWER: -1 = on, 2 = off
+    let state = if on { -1 } else { 2 };
+    let script = format!(
+        "(Add-Type '[DllImport(\"user32.dll\")] public static extern int SendMessage(int h, int m, int w, int l);' -Name Power -PassThru)::SendMessage(0xffff, 0x0112, 0xF170, {})",
+        state
+    );
+    ["powershell", "-NoProfile", "-Command", &script]
+        .map(String::from)
+        .to_vec()
+}
+
+#[cfg(target_os = "macos")]
+fn dpms_command(on: bool) -> Vec<String> {
+    let args: &[&str] = if on {
+        // Declaring user activity wakes the displays
+        &["caffeinate", "-u", "-t", "1"]
+    } else {
+        &["pmset", "displaysleepnow"]
+    };
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn dpms_command(on: bool) -> Vec<String> {
+    let state = if on { "on" } else { "off" };
+    ["xset", "dpms", "force", state].map(String::from).to_vec()
+}
+
+/// Run a power command in the background, logging failures
+pub fn run(args: Vec<String>, label: String) {
+    thread::spawn(move || {
+        let Some((program, rest)) = args.split_first() else {
+            return;
+        };
+        match Command::new(program).args(rest).output() {
+            Ok(output) if output.status.success() => info!("Display power: {}", label),
+            Ok(output) => warn!(
+                "Display power ({}) failed: {}",
+                label,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => warn!("Display power ({}): can't run {}: {}", label, program, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let display = Display {
+            connector: "HDMI-1".to_string(),
+            model: Some("LG TV".to_string()),
+            edid_id: Some("GSM-C0A1-12345".to_string()),
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        assert!(DisplayPower::None
+            .command(Some(&display), false)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            DisplayPower::Ddc
+                .command(Some(&display), false)
+                .unwrap()
+                .unwrap(),
+            vec!["ddcutil", "--mfg", "GSM", "--sn", "12345", "setvcp", "D6", "04"]
+        );
+        assert!(DisplayPower::Ddc.command(None, true).is_err());
+        assert!(DisplayPower::Dpms.command(None, true).unwrap().is_some());
+    }
+}
@@ -0,0 +1,239 @@
+//! Operator profiles: per-user GUI preferences kept in their own file, apart
+//! from the venue configuration
+
+use crate::gui::layouts::Layout;
+use anyhow::{Context, Result};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One operator's preferred setup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperatorProfile {
+    pub name: String,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Layout selected when the profile is chosen (left unchanged if unset)
+    #[serde(default)]
+    pub layout: Option<Layout>,
+    /// Keys for GUI shortcuts (egui key names, e.g. "F1" or "Space")
+    #[serde(default)]
+    pub keys: HashMap<Shortcut, String>,
+    /// Panels opened when the profile is chosen (the usual panels if unset)
+    #[serde(default)]
+    pub panels: Option<Vec<Panel>>,
+    /// Sources (name or URL) listed first and starred in the routing panel
+    #[serde(default)]
+    pub favorite_sources: Vec<String>,
+}
+
+impl OperatorProfile {
+    #[cfg(test)]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            theme: Theme::default(),
+            layout: None,
+            keys: HashMap::new(),
+            panels: None,
+            favorite_sources: Vec::new(),
+        }
+    }
+
+    /// Whether a source (by name or URL) is a favorite
+    pub fn is_favorite(&self, name: &str, url: &str) -> bool {
+        self.favorite_sources
+            .iter()
+            .any(|favorite| favorite == name || favorite == url)
+    }
+
+    /// Star or unstar a source
+    pub fn set_favorite(&mut self, name: &str, url: &str, favorite: bool) {
+        self.favorite_sources.retain(|f| f != name && f != url);
+        if favorite {
+            self.favorite_sources.push(name.to_string());
+        }
+    }
+
+    /// Shortcut keys that parse as egui keys
+    pub fn shortcut_keys(&self) -> HashMap<Shortcut, egui::Key> {
+        self.keys
+            .iter()
+            .filter_map(|(shortcut, key)| Some((*shortcut, egui::Key::from_name(key)?)))
+            .collect()
+    }
+}
+
+/// GUI color theme
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn visuals(&self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        }
+    }
+}
+
+/// GUI actions that can be bound to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shortcut {
+    /// Push-to-talk (held), replacing `push_to_talk_key` from `[talkback]`
+    Talkback,
+    IdentifyOutputs,
+    MonitoringWall,
+    NextLayout,
+    /// Open the operator quick-switch menu
+    SwitchOperator,
+    /// Drop a numbered marker into the active recording
+    RecordingMarker,
+}
+
+/// Panels and windows a profile can open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Panel {
+    Layout,
+    Routing,
+    Talkback,
+    Cameras,
+    SelfTest,
+    Network,
+    Metadata,
+    Displays,
+    Audio,
+    Mixer,
+    Recordings,
+    Telestrator,
+    Intercom,
+}
+
+/// The profiles file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileStore {
+    #[serde(default)]
+    pub profiles: Vec<OperatorProfile>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl ProfileStore {
+    /// Load profiles from a TOML file (no profiles if the file does not exist)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut store: ProfileStore = if path.exists() {
+            let content = fs::read_to_string(path).context("Failed to read profiles file")?;
+            toml::from_str(&content).context("Failed to parse profiles file")?
+        } else {
+            ProfileStore::default()
+        };
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    /// Write the profiles back to the file they were loaded from
+    pub fn save(&self) -> Result<()> {
+        let content = toml::to_string_pretty(self).context("Failed to serialize profiles")?;
+        fs::write(&self.path, content).context("Failed to write profiles file")?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OperatorProfile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut OperatorProfile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_profiles() {
+        let store: ProfileStore = toml::from_str(
+            r#"
+            [[profiles]]
+            name = "Sam"
+            theme = "light"
+            layout = "Grid3x3"
+            panels = ["routing", "talkback"]
+            favorite_sources = ["CAM 1"]
+            keys = { talkback = "Space", next-layout = "F2", identify-outputs = "Nope" }
+
+            [[profiles]]
+            name = "Jo"
+            "#,
+        )
+        .unwrap();
+
+        let sam = store.get("Sam").unwrap();
+        assert_eq!(sam.theme, Theme::Light);
+        assert_eq!(sam.layout, Some(Layout::Grid3x3));
+        assert_eq!(sam.panels, Some(vec![Panel::Routing, Panel::Talkback]));
+        let keys = sam.shortcut_keys();
+        assert_eq!(keys.get(&Shortcut::Talkback), Some(&egui::Key::Space));
+        assert_eq!(keys.get(&Shortcut::NextLayout), Some(&egui::Key::F2));
+        // Unknown key names are ignored
+        assert!(!keys.contains_key(&Shortcut::IdentifyOutputs));
+
+        let jo = store.get("Jo").unwrap();
+        assert_eq!(jo.theme, Theme::Dark);
+        assert_eq!(jo.panels, None);
+        assert_eq!(store.names(), vec!["Sam", "Jo"]);
+    }
+
+    #[test]
+    fn test_favorites() {
+        let mut profile = OperatorProfile::new("Sam");
+        profile.set_favorite("CAM 1", "ndi://10.0.0.5", true);
+        assert!(profile.is_favorite("CAM 1", "ndi://other"));
+        profile.set_favorite("CAM 1", "ndi://10.0.0.5", true);
+        assert_eq!(profile.favorite_sources.len(), 1);
+
+        // Favorites saved by URL are recognised and removed too
+        profile.favorite_sources.push("ndi://10.0.0.6".to_string());
+        assert!(profile.is_favorite("CAM 2", "ndi://10.0.0.6"));
+        profile.set_favorite("CAM 2", "ndi://10.0.0.6", false);
+        assert!(!profile.is_favorite("CAM 2", "ndi://10.0.0.6"));
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.toml");
+
+        let mut store = ProfileStore::load(&path).unwrap();
+        assert!(store.is_empty());
+
+        let mut profile = OperatorProfile::new("Sam");
+        profile
+            .keys
+            .insert(Shortcut::SwitchOperator, "F12".to_string());
+        store.profiles.push(profile.clone());
+        store.save().unwrap();
+
+        let loaded = ProfileStore::load(&path).unwrap();
+        assert_eq!(loaded.get("Sam"), Some(&profile));
+    }
+}
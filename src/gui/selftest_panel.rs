@@ -0,0 +1,101 @@
+use crate::config::Config;
+use crate::ndi::NdiDiscovery;
+use crate::selftest::{self, CheckStatus, SelfTestReport};
+use eframe::egui;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+/// Panel showing the pre-show self-test checklist
+pub struct SelfTestPanel {
+    config: Config,
+    discovery: Arc<NdiDiscovery>,
+    /// Checklist of the last finished run
+    report: Option<SelfTestReport>,
+    running: bool,
+    /// Channel for reports from background runs
+    results_tx: Sender<SelfTestReport>,
+    results_rx: Receiver<SelfTestReport>,
+}
+
+impl SelfTestPanel {
+    pub fn new(config: Config, discovery: Arc<NdiDiscovery>) -> Self {
+        let (results_tx, results_rx) = channel();
+        Self {
+            config,
+            discovery,
+            report: None,
+            running: false,
+            results_tx,
+            results_rx,
+        }
+    }
+
+    /// Run the self-test in the background
+    pub fn start(&mut self) {
+        if self.running {
+            return;
+        }
+        self.running = true;
+
+        let config = self.config.clone();
+        let discovery = Arc::clone(&self.discovery);
+        let tx = self.results_tx.clone();
+        tokio::spawn(async move {
+            let report = selftest::run(&config, &discovery).await;
+            let _ = tx.send(report);
+        });
+    }
+
+    /// Collect a finished run; returns true if one just failed
+    pub fn poll(&mut self) -> bool {
+        let mut failed = false;
+        while let Ok(report) = self.results_rx.try_recv() {
+            self.running = false;
+            failed = !report.passed();
+            self.report = Some(report);
+        }
+        failed
+    }
+
+    /// Draw the panel
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        match &self.report {
+            Some(report) => {
+                for check in &report.checks {
+                    let color = match check.status {
+                        CheckStatus::Pass => egui::Color32::from_rgb(80, 200, 80),
+                        CheckStatus::Fail => egui::Color32::from_rgb(230, 70, 70),
+                        CheckStatus::Skipped => egui::Color32::GRAY,
+                    };
+                    ui.horizontal(|ui| {
+                        ui.colored_label(color, check.symbol());
+                        ui.strong(&check.name);
+                        ui.label(&check.detail);
+                    });
+                }
+                ui.separator();
+                if report.passed() {
+                    ui.colored_label(egui::Color32::from_rgb(80, 200, 80), "All checks passed");
+                } else {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(230, 70, 70),
+                        format!("{} checks failed", report.failures()),
+                    );
+                }
+            }
+            None if !self.running => {
+                ui.label("Self-test has not run yet");
+            }
+            None => {}
+        }
+
+        ui.horizontal(|ui| {
+            if self.running {
+                ui.spinner();
+                ui.label("Running...");
+            } else if ui.button("▶ Run Self-Test").clicked() {
+                self.start();
+            }
+        });
+    }
+}
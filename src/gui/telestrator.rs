@@ -0,0 +1,250 @@
+//! Telestrator: temporary annotations drawn over slots (e.g. to coach camera operators)
+
+use egui::{Color32, Painter, Pos2, Rect, Stroke};
+use std::collections::HashMap;
+
+/// Annotations smaller than this (fraction of the slot) are treated as stray clicks
+const MIN_SIZE: f32 = 0.01;
+
+/// Drawing tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Freehand,
+    Arrow,
+    Circle,
+}
+
+impl Tool {
+    pub fn all() -> [Tool; 3] {
+        [Tool::Freehand, Tool::Arrow, Tool::Circle]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Freehand => "✏ Pen",
+            Tool::Arrow => "➡ Arrow",
+            Tool::Circle => "⭕ Circle",
+        }
+    }
+}
+
+/// One annotation, with points relative to its slot (0.0 to 1.0)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub tool: Tool,
+    pub color: Color32,
+    /// Freehand: every point; arrow: tail and head; circle: center and a point on the edge
+    pub points: Vec<Pos2>,
+}
+
+impl Annotation {
+    /// Whether the annotation is big enough to keep
+    fn is_visible(&self) -> bool {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return false;
+        };
+        match self.tool {
+            Tool::Freehand => self.points.len() > 1,
+            Tool::Arrow | Tool::Circle => first.distance(*last) >= MIN_SIZE,
+        }
+    }
+}
+
+/// Annotation state for all slots
+pub struct Telestrator {
+    /// Drawing mode: dragging over a slot draws instead of selecting it
+    pub active: bool,
+    pub tool: Tool,
+    pub color: Color32,
+    pub stroke_width: f32,
+    /// Include annotations in multiview recordings (otherwise they are local only)
+    pub burn_in: bool,
+    /// Finished annotations keyed by output
+    annotations: HashMap<String, Vec<Annotation>>,
+    /// Annotation being drawn, with its output
+    drawing: Option<(String, Annotation)>,
+}
+
+impl Telestrator {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            tool: Tool::Arrow,
+            color: Color32::YELLOW,
+            stroke_width: 4.0,
+            burn_in: true,
+            annotations: HashMap::new(),
+            drawing: None,
+        }
+    }
+
+    /// Start an annotation at a screen position inside an output's slot
+    pub fn begin(&mut self, output: &str, rect: Rect, pos: Pos2) {
+        let point = to_slot(rect, pos);
+        let points = match self.tool {
+            Tool::Freehand => vec![point],
+            Tool::Arrow | Tool::Circle => vec![point, point],
+        };
+        self.drawing = Some((
+            output.to_string(),
+            Annotation {
+                tool: self.tool,
+                color: self.color,
+                points,
+            },
+        ));
+    }
+
+    /// Extend the annotation being drawn to a screen position
+    pub fn drag_to(&mut self, rect: Rect, pos: Pos2) {
+        let Some((_, annotation)) = &mut self.drawing else {
+            return;
+        };
+        let point = to_slot(rect, pos);
+        match annotation.tool {
+            Tool::Freehand => annotation.points.push(point),
+            Tool::Arrow | Tool::Circle => annotation.points[1] = point,
+        }
+    }
+
+    /// Finish the annotation being drawn (discarding it if it is too small)
+    pub fn finish(&mut self) {
+        if let Some((output, annotation)) = self.drawing.take() {
+            if annotation.is_visible() {
+                self.annotations.entry(output).or_default().push(annotation);
+            }
+        }
+    }
+
+    /// Remove the most recent annotation on an output
+    pub fn undo(&mut self, output: &str) {
+        if let Some(annotations) = self.annotations.get_mut(output) {
+            annotations.pop();
+        }
+    }
+
+    /// Remove all annotations on an output
+    pub fn clear(&mut self, output: &str) {
+        self.annotations.remove(output);
+    }
+
+    /// Remove all annotations
+    pub fn clear_all(&mut self) {
+        self.annotations.clear();
+        self.drawing = None;
+    }
+
+    /// Outputs that currently have annotations
+    pub fn annotated_outputs(&self) -> Vec<String> {
+        let mut outputs: Vec<String> = self
+            .annotations
+            .iter()
+            .filter(|(_, annotations)| !annotations.is_empty())
+            .map(|(output, _)| output.clone())
+            .collect();
+        outputs.sort();
+        outputs
+    }
+
+    /// Finished annotations on an output
+    pub fn annotations(&self, output: &str) -> &[Annotation] {
+        self.annotations.get(output).map_or(&[], Vec::as_slice)
+    }
+
+    /// Paint an output's annotations (including one being drawn) over its slot
+    pub fn paint(&self, painter: &Painter, rect: Rect, output: &str) {
+        let drawing = self
+            .drawing
+            .iter()
+            .filter(|(o, _)| o == output)
+            .map(|(_, annotation)| annotation);
+        let painter = painter.with_clip_rect(rect);
+
+        for annotation in self.annotations(output).iter().chain(drawing) {
+            let stroke = Stroke::new(self.stroke_width, annotation.color);
+            let points: Vec<Pos2> = annotation
+                .points
+                .iter()
+                .map(|p| from_slot(rect, *p))
+                .collect();
+            match (annotation.tool, points.as_slice()) {
+                (Tool::Freehand, _) => {
+                    painter.add(egui::Shape::line(points, stroke));
+                }
+                (Tool::Arrow, [tail, head]) => painter.arrow(*tail, *head - *tail, stroke),
+                (Tool::Circle, [center, edge]) => {
+                    painter.circle_stroke(*center, center.distance(*edge), stroke);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Default for Telestrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Screen position to a position relative to the slot
+fn to_slot(rect: Rect, pos: Pos2) -> Pos2 {
+    let pos = rect.clamp(pos);
+    Pos2::new(
+        (pos.x - rect.min.x) / rect.width().max(1.0),
+        (pos.y - rect.min.y) / rect.height().max(1.0),
+    )
+}
+
+/// Position relative to the slot to a screen position
+fn from_slot(rect: Rect, point: Pos2) -> Pos2 {
+    rect.min + egui::vec2(point.x * rect.width(), point.y * rect.height())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> Rect {
+        Rect::from_min_size(Pos2::new(100.0, 100.0), egui::vec2(200.0, 100.0))
+    }
+
+    #[test]
+    fn test_slot_coordinates() {
+        let point = to_slot(rect(), Pos2::new(150.0, 175.0));
+        assert_eq!(point, Pos2::new(0.25, 0.75));
+        assert_eq!(from_slot(rect(), point), Pos2::new(150.0, 175.0));
+
+        // Positions outside the slot are clamped to its edge
+        assert_eq!(to_slot(rect(), Pos2::new(0.0, 500.0)), Pos2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn test_draw_undo_clear() {
+        let mut telestrator = Telestrator::new();
+        telestrator.begin("Cam 1", rect(), Pos2::new(120.0, 120.0));
+        telestrator.drag_to(rect(), Pos2::new(250.0, 180.0));
+        telestrator.finish();
+
+        let annotations = telestrator.annotations("Cam 1");
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].tool, Tool::Arrow);
+        assert_eq!(annotations[0].points[1], Pos2::new(0.75, 0.8));
+
+        // A click without dragging leaves nothing behind
+        telestrator.begin("Cam 1", rect(), Pos2::new(120.0, 120.0));
+        telestrator.finish();
+        assert_eq!(telestrator.annotations("Cam 1").len(), 1);
+        assert_eq!(telestrator.annotated_outputs(), vec!["Cam 1".to_string()]);
+
+        telestrator.tool = Tool::Freehand;
+        telestrator.begin("Cam 2", rect(), Pos2::new(120.0, 120.0));
+        telestrator.drag_to(rect(), Pos2::new(130.0, 125.0));
+        telestrator.finish();
+        telestrator.undo("Cam 2");
+        assert!(telestrator.annotations("Cam 2").is_empty());
+
+        telestrator.clear("Cam 1");
+        assert!(telestrator.annotated_outputs().is_empty());
+    }
+}
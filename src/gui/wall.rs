@@ -0,0 +1,108 @@
+use crate::ndi::NdiSource;
+
+/// Monitoring wall state: shows every discovered source, sorted and paged
+#[derive(Debug, Clone, Default)]
+pub struct MonitoringWall {
+    /// Current page (zero-based)
+    page: usize,
+}
+
+impl MonitoringWall {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of pages needed to show all sources with `per_page` slots per page
+    pub fn page_count(source_count: usize, per_page: usize) -> usize {
+        if per_page == 0 {
+            return 1;
+        }
+        source_count.div_ceil(per_page).max(1)
+    }
+
+    /// Current page (zero-based), clamped to the available pages
+    pub fn page(&self, source_count: usize, per_page: usize) -> usize {
+        self.page
+            .min(Self::page_count(source_count, per_page).saturating_sub(1))
+    }
+
+    /// Advance to the next page, wrapping around to the first
+    pub fn next_page(&mut self, source_count: usize, per_page: usize) {
+        let pages = Self::page_count(source_count, per_page);
+        self.page = (self.page(source_count, per_page) + 1) % pages;
+    }
+
+    /// Go back to the previous page, wrapping around to the last
+    pub fn previous_page(&mut self, source_count: usize, per_page: usize) {
+        let pages = Self::page_count(source_count, per_page);
+        let page = self.page(source_count, per_page);
+        self.page = if page == 0 { pages - 1 } else { page - 1 };
+    }
+
+    /// Get the sources shown on the current page, sorted by name
+    pub fn visible_sources(&self, sources: &[NdiSource], per_page: usize) -> Vec<NdiSource> {
+        let mut sorted = sources.to_vec();
+        sorted.sort_by_key(|s| s.name.to_lowercase());
+
+        let page = self.page(sorted.len(), per_page);
+        sorted
+            .into_iter()
+            .skip(page * per_page)
+            .take(per_page)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(names: &[&str]) -> Vec<NdiSource> {
+        names
+            .iter()
+            .map(|n| NdiSource::new(n.to_string(), format!("ndi://{}", n)))
+            .collect()
+    }
+
+    #[test]
+    fn test_page_count() {
+        assert_eq!(MonitoringWall::page_count(0, 4), 1);
+        assert_eq!(MonitoringWall::page_count(4, 4), 1);
+        assert_eq!(MonitoringWall::page_count(5, 4), 2);
+        assert_eq!(MonitoringWall::page_count(17, 16), 2);
+    }
+
+    #[test]
+    fn test_visible_sources_sorted_and_paged() {
+        let mut wall = MonitoringWall::new();
+        let all = sources(&["Cam C", "cam a", "Cam B", "Slides", "Audience"]);
+
+        let first: Vec<String> = wall
+            .visible_sources(&all, 4)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(first, vec!["Audience", "cam a", "Cam B", "Cam C"]);
+
+        wall.next_page(all.len(), 4);
+        let second = wall.visible_sources(&all, 4);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].name, "Slides");
+
+        wall.next_page(all.len(), 4);
+        assert_eq!(wall.page(all.len(), 4), 0);
+    }
+
+    #[test]
+    fn test_page_clamped_when_sources_disappear() {
+        let mut wall = MonitoringWall::new();
+        let all = sources(&["A", "B", "C", "D", "E"]);
+        wall.previous_page(all.len(), 4);
+        assert_eq!(wall.page(all.len(), 4), 1);
+
+        // Sources vanish: the wall falls back to the last available page
+        let fewer = sources(&["A", "B"]);
+        assert_eq!(wall.page(fewer.len(), 4), 0);
+        assert_eq!(wall.visible_sources(&fewer, 4).len(), 2);
+    }
+}
@@ -0,0 +1,204 @@
+//! Media player input: plays a local video file through an `ffmpeg` process,
+//! looping or once, so pre-recorded content and holding slides can be routed
+//! like live sources
+
+use crate::config::ClipConfig;
+use crate::ndi::NdiSource;
+use crate::video::VideoFrame;
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// URL scheme used for clips (e.g. `clip://Walk-in Loop`)
+pub const CLIP_URL_PREFIX: &str = "clip://";
+
+/// URL used to route a clip
+pub fn url(clip: &ClipConfig) -> String {
+    format!("{}{}", CLIP_URL_PREFIX, clip.name)
+}
+
+/// Represent a clip as a routable source
+pub fn to_source(clip: &ClipConfig) -> NdiSource {
+    NdiSource::new(clip.name.clone(), url(clip))
+}
+
+/// The clip an input (name or URL) plays, if it is one
+pub fn find<'a>(clips: &'a [ClipConfig], input: &str) -> Option<&'a ClipConfig> {
+    let name = input.strip_prefix(CLIP_URL_PREFIX).unwrap_or(input);
+    clips.iter().find(|c| c.name == name)
+}
+
+/// Parse `ffprobe`'s `WIDTHxHEIGHT` output
+fn parse_probe(output: &str) -> Option<(u32, u32)> {
+    let (width, height) = output.trim().lines().next()?.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Size a clip is decoded at: its own, scaled down to at most `max_height` lines
+/// with the aspect ratio kept (even, as most pixel formats need)
+pub fn decoded_size(width: u32, height: u32, max_height: u32) -> (u32, u32) {
+    if height <= max_height || height == 0 {
+        return (width & !1, height & !1);
+    }
+    let scaled = (width as u64 * max_height as u64 / height as u64) as u32;
+    (scaled & !1, max_height & !1)
+}
+
+/// ffmpeg arguments decoding a clip to raw RGBA at its own frame rate
+pub fn ffmpeg_args(clip: &ClipConfig, (width, height): (u32, u32)) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-loglevel", "error", "-re"]
+        .map(String::from)
+        .to_vec();
+    if clip.looping {
+        args.extend(["-stream_loop", "-1"].map(String::from));
+    }
+    args.push("-i".to_string());
+    args.push(clip.path.display().to_string());
+    args.extend(["-an", "-f", "rawvideo", "-pix_fmt", "rgba", "-s"].map(String::from));
+    args.push(format!("{}x{}", width, height));
+    args.push("pipe:1".to_string());
+    args
+}
+
+/// Size of a video file's first video stream
+fn probe(clip: &ClipConfig) -> Result<(u32, u32)> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args(["-show_entries", "stream=width,height", "-of", "csv=p=0:s=x"])
+        .arg(&clip.path)
+        .output()
+        .context("Failed to run ffprobe (is ffmpeg on the PATH?)")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Can't read {:?}: {}",
+            clip.path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    parse_probe(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("{:?} has no video", clip.path))
+}
+
+/// Newest decoded frame, numbered so callers can tell when it changes
+#[derive(Default)]
+struct Latest {
+    sequence: u64,
+    frame: Option<VideoFrame>,
+}
+
+/// Plays one clip from the start. A one-shot clip holds its last frame once it
+/// has played; dropping the player stops it
+pub struct ClipPlayer {
+    process: Child,
+    latest: Arc<Mutex<Latest>>,
+    finished: Arc<AtomicBool>,
+}
+
+impl ClipPlayer {
+    /// Start playing a clip
+    pub fn start(clip: &ClipConfig) -> Result<Self> {
+        let (width, height) = probe(clip)?;
+        let (width, height) = decoded_size(width, height, clip.max_height);
+        debug!("Playing {:?} at {}x{}", clip.path, width, height);
+
+        let mut process = Command::new("ffmpeg")
+            .args(ffmpeg_args(clip, (width, height)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .context("Failed to start ffmpeg for clip playback (is it on the PATH?)")?;
+        let mut stdout = process.stdout.take().context("ffmpeg stdout unavailable")?;
+
+        let latest = Arc::new(Mutex::new(Latest::default()));
+        let finished = Arc::new(AtomicBool::new(false));
+        {
+            let latest = Arc::clone(&latest);
+            let finished = Arc::clone(&finished);
+            let name = clip.name.clone();
+            thread::spawn(move || {
+                let mut buffer = vec![0u8; (width * height * 4) as usize];
+                while stdout.read_exact(&mut buffer).is_ok() {
+                    let mut latest = latest.lock().unwrap();
+                    latest.sequence += 1;
+                    latest.frame = Some(VideoFrame::new(width, height, buffer.clone()));
+                }
+                info!("Clip {} finished", name);
+                finished.store(true, Ordering::Relaxed);
+            });
+        }
+        info!("Playing clip {} ({:?})", clip.name, clip.path);
+
+        Ok(Self {
+            process,
+            latest,
+            finished,
+        })
+    }
+
+    /// The newest frame with its sequence number, if newer than `shown`
+    pub fn frame_after(&self, shown: u64) -> Option<(u64, VideoFrame)> {
+        let latest = self.latest.lock().unwrap();
+        if latest.sequence == shown {
+            return None;
+        }
+        Some((latest.sequence, latest.frame.clone()?))
+    }
+
+    /// Whether playback has ended (one-shot clips, or a file that can't be decoded)
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ClipPlayer {
+    fn drop(&mut self) {
+        if let Err(e) = self.process.kill() {
+            warn!("Failed to stop ffmpeg: {}", e);
+        }
+        let _ = self.process.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn clip(looping: bool) -> ClipConfig {
+        ClipConfig {
+            name: "Walk-in Loop".to_string(),
+            path: PathBuf::from("/media/walk-in.mp4"),
+            looping,
+            max_height: 720,
+        }
+    }
+
+    #[test]
+    fn test_decoded_size() {
+        assert_eq!(parse_probe("1920x1080\n"), Some((1920, 1080)));
+        assert_eq!(parse_probe("\n"), None);
+        assert_eq!(decoded_size(1920, 1080, 720), (1280, 720));
+        assert_eq!(decoded_size(1280, 720, 720), (1280, 720));
+        assert_eq!(decoded_size(721, 405, 1080), (720, 404));
+    }
+
+    #[test]
+    fn test_ffmpeg_args() {
+        let args = ffmpeg_args(&clip(true), (1280, 720));
+        assert_eq!(args[3..7], ["-re", "-stream_loop", "-1", "-i"]);
+        assert!(args.contains(&"/media/walk-in.mp4".to_string()));
+        assert_eq!(args[args.len() - 2..], ["1280x720", "pipe:1"]);
+        assert!(!ffmpeg_args(&clip(false), (1280, 720)).contains(&"-stream_loop".to_string()));
+
+        let clips = [clip(true)];
+        assert!(find(&clips, "clip://Walk-in Loop").is_some());
+        assert!(find(&clips, "Walk-in Loop").is_some());
+        assert!(find(&clips, "clip://Other").is_none());
+        assert_eq!(to_source(&clips[0]).url, "clip://Walk-in Loop");
+    }
+}
@@ -0,0 +1,8 @@
+//! Non-NDI inputs that can be routed like network sources
+
+pub mod clip;
+pub mod test_pattern;
+pub mod watch_folder;
+
+pub use test_pattern::TestPattern;
+pub use watch_folder::WatchFolder;
@@ -0,0 +1,252 @@
+//! Built-in test pattern input, for setting up layouts and checking monitors
+//! when no cameras are online
+
+use crate::audio::AudioChunk;
+use crate::ndi::NdiSource;
+use crate::video::VideoFrame;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::fmt;
+use std::time::Duration;
+
+/// URL scheme used for test patterns (e.g. `pattern://grid`)
+pub const TEST_PATTERN_URL_PREFIX: &str = "pattern://";
+
+/// Name of the test pattern in the source list
+pub const TEST_PATTERN_NAME: &str = "RusTV Test Pattern";
+
+/// Frame rate of the motion pattern
+pub const MOTION_FPS: u32 = 30;
+
+/// Seconds the motion pattern takes to sweep across the picture
+const SWEEP_SECONDS: f64 = 2.0;
+
+/// 75% color bars, left to right
+const BARS: [[u8; 3]; 7] = [
+    [191, 191, 191],
+    [191, 191, 0],
+    [0, 191, 191],
+    [0, 191, 0],
+    [191, 0, 191],
+    [191, 0, 0],
+    [0, 0, 191],
+];
+
+/// Line-up tone: 1 kHz at -20 dBFS
+const TONE_FREQUENCY: f32 = 1000.0;
+const TONE_LEVEL: f32 = 0.1;
+const TONE_SAMPLE_RATE: u32 = 48000;
+
+/// Longest stretch of tone generated at once, so a stalled caller doesn't get a burst
+const MAX_TONE_CHUNK: Duration = Duration::from_millis(200);
+
+/// What the test pattern input shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestPattern {
+    /// Color bars with a grey ramp
+    #[default]
+    Bars,
+    /// White grid with a centre cross and circle, for geometry and scaling
+    Grid,
+    /// A bar sweeping across the picture, for judder and dropped frames
+    Motion,
+    /// Color bars with a 1 kHz line-up tone
+    Tone,
+}
+
+impl TestPattern {
+    pub fn all() -> [TestPattern; 4] {
+        [
+            TestPattern::Bars,
+            TestPattern::Grid,
+            TestPattern::Motion,
+            TestPattern::Tone,
+        ]
+    }
+
+    /// URL used to route this pattern
+    pub fn url(&self) -> String {
+        format!("{}{}", TEST_PATTERN_URL_PREFIX, self)
+    }
+
+    /// The pattern a test pattern URL names
+    pub fn from_url(url: &str) -> Option<TestPattern> {
+        let name = url.strip_prefix(TEST_PATTERN_URL_PREFIX)?;
+        Self::all().into_iter().find(|p| p.to_string() == name)
+    }
+
+    /// Represent the pattern as a routable source
+    pub fn to_source(self) -> NdiSource {
+        NdiSource::new(TEST_PATTERN_NAME.to_string(), self.url())
+    }
+
+    /// Whether the picture changes over time
+    pub fn is_moving(&self) -> bool {
+        *self == TestPattern::Motion
+    }
+
+    pub fn has_tone(&self) -> bool {
+        *self == TestPattern::Tone
+    }
+
+    /// Draw the pattern `elapsed` after it started
+    pub fn render(&self, width: u32, height: u32, elapsed: Duration) -> VideoFrame {
+        let (w, h) = (width.max(1), height.max(1));
+        let mut data = Vec::with_capacity((w * h * 4) as usize);
+        for y in 0..h {
+            for x in 0..w {
+                let [r, g, b] = self.pixel(x, y, w, h, elapsed);
+                data.extend_from_slice(&[r, g, b, 255]);
+            }
+        }
+        VideoFrame::new(w, h, data)
+    }
+
+    fn pixel(&self, x: u32, y: u32, w: u32, h: u32, elapsed: Duration) -> [u8; 3] {
+        match self {
+            TestPattern::Bars | TestPattern::Tone => {
+                if y < h * 3 / 4 {
+                    BARS[(x * BARS.len() as u32 / w) as usize]
+                } else {
+                    let level = (x * 255 / (w - 1).max(1)).min(255) as u8;
+                    [level; 3]
+                }
+            }
+            TestPattern::Grid => {
+                let cell = (h / 12).max(2);
+                let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+                let distance = ((x as f32 - cx).powi(2) + (y as f32 - cy).powi(2)).sqrt();
+                let on_circle = (distance - h as f32 * 0.4).abs() < 1.0;
+                let on_cross = x == w / 2 || y == h / 2;
+                let on_border = x == 0 || y == 0 || x == w - 1 || y == h - 1;
+                if x.is_multiple_of(cell)
+                    || y.is_multiple_of(cell)
+                    || on_circle
+                    || on_cross
+                    || on_border
+                {
+                    [255; 3]
+                } else {
+                    [0; 3]
+                }
+            }
+            TestPattern::Motion => {
+                let phase = (elapsed.as_secs_f64() / SWEEP_SECONDS).fract();
+                let bar_x = (phase * w as f64) as u32;
+                let bar_width = (w / 32).max(1);
+                if x >= bar_x && x < bar_x + bar_width {
+                    [255; 3]
+                } else {
+                    [48; 3]
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for TestPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TestPattern::Bars => "bars",
+            TestPattern::Grid => "grid",
+            TestPattern::Motion => "motion",
+            TestPattern::Tone => "tone",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Stereo line-up tone, generated as time passes
+#[derive(Debug, Default)]
+pub struct ToneGenerator {
+    /// Position of the next sample (per channel) since the tone started
+    generated: u64,
+}
+
+impl ToneGenerator {
+    /// Tone for the time between the last call and `elapsed` since the tone started
+    pub fn generate(&mut self, elapsed: Duration) -> AudioChunk {
+        let samples_in = |time: Duration| (time.as_secs_f64() * TONE_SAMPLE_RATE as f64) as u64;
+        let end = samples_in(elapsed);
+        let start = self
+            .generated
+            .max(end.saturating_sub(samples_in(MAX_TONE_CHUNK)));
+
+        let mut samples = Vec::with_capacity(end.saturating_sub(start) as usize * 2);
+        for i in start..end {
+            // Wrap each cycle so the phase stays precise
+            let cycle = (i as f32 * TONE_FREQUENCY / TONE_SAMPLE_RATE as f32).fract();
+            let sample = (cycle * TAU).sin() * TONE_LEVEL;
+            samples.extend_from_slice(&[sample, sample]);
+        }
+        self.generated = self.generated.max(end);
+
+        AudioChunk {
+            sample_rate: TONE_SAMPLE_RATE,
+            channels: 2,
+            samples,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_urls() {
+        for pattern in TestPattern::all() {
+            assert_eq!(TestPattern::from_url(&pattern.url()), Some(pattern));
+        }
+        assert_eq!(TestPattern::Grid.url(), "pattern://grid");
+        assert_eq!(TestPattern::from_url("pattern://zebra"), None);
+        assert_eq!(TestPattern::from_url("file://grid"), None);
+
+        let source = TestPattern::Bars.to_source();
+        assert_eq!(source.name, "RusTV Test Pattern");
+        assert_eq!(source.url, "pattern://bars");
+    }
+
+    #[test]
+    fn test_render() {
+        let pixel = |frame: &VideoFrame, x: u32, y: u32| {
+            let i = ((y * frame.width + x) * 4) as usize;
+            [frame.data[i], frame.data[i + 1], frame.data[i + 2]]
+        };
+
+        let bars = TestPattern::Bars.render(70, 40, Duration::ZERO);
+        assert_eq!(pixel(&bars, 0, 0), [191, 191, 191]);
+        assert_eq!(pixel(&bars, 69, 0), [0, 0, 191]);
+        assert_eq!(pixel(&bars, 0, 39), [0, 0, 0]);
+        assert_eq!(pixel(&bars, 69, 39), [255, 255, 255]);
+
+        // The motion bar moves; other patterns don't
+        let start = TestPattern::Motion.render(64, 8, Duration::ZERO);
+        let later = TestPattern::Motion.render(64, 8, Duration::from_millis(500));
+        assert_eq!(pixel(&start, 0, 0), [255, 255, 255]);
+        assert_eq!(pixel(&later, 0, 0), [48, 48, 48]);
+        assert_eq!(pixel(&later, 16, 0), [255, 255, 255]);
+        assert!(!TestPattern::Grid.is_moving());
+    }
+
+    #[test]
+    fn test_tone() {
+        let mut tone = ToneGenerator::default();
+        let chunk = tone.generate(Duration::from_millis(10));
+        assert_eq!(chunk.samples.len(), 480 * 2);
+        let peak = chunk
+            .samples
+            .iter()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - TONE_LEVEL).abs() < 0.001);
+
+        // Continues where it left off, and skips ahead after a stall
+        assert_eq!(
+            tone.generate(Duration::from_millis(20)).samples.len(),
+            480 * 2
+        );
+        let chunk = tone.generate(Duration::from_secs(5));
+        assert_eq!(chunk.samples.len(), 9600 * 2);
+    }
+}
@@ -0,0 +1,232 @@
+//! Watch folder that turns dropped image files into routable inputs
+
+use crate::ndi::NdiSource;
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::time;
+
+/// URL scheme used for file inputs
+pub const FILE_URL_PREFIX: &str = "file://";
+
+/// Image extensions picked up by the watch folder
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// An image file exposed as an input
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileInput {
+    /// Input name (file name without extension)
+    pub name: String,
+    /// Path to the image file
+    pub path: PathBuf,
+    /// Last modification time seen
+    pub modified: SystemTime,
+    /// Incremented every time the file changes on disk
+    pub version: u64,
+}
+
+impl FileInput {
+    /// URL used to route this input
+    pub fn url(&self) -> String {
+        format!("{}{}", FILE_URL_PREFIX, self.path.display())
+    }
+
+    /// Represent the file as a routable source
+    pub fn to_source(&self) -> NdiSource {
+        NdiSource::new(self.name.clone(), self.url())
+    }
+}
+
+/// Watches a directory for image files and keeps a live list of file inputs
+pub struct WatchFolder {
+    directory: PathBuf,
+    scan_interval: Duration,
+    files: Arc<Mutex<HashMap<PathBuf, FileInput>>>,
+    is_running: Arc<Mutex<bool>>,
+}
+
+impl WatchFolder {
+    pub fn new<P: AsRef<Path>>(directory: P, scan_interval: Duration) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            scan_interval,
+            files: Arc::new(Mutex::new(HashMap::new())),
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Start watching the directory (creating it if needed)
+    pub async fn start(&self) -> Result<()> {
+        let mut is_running = self.is_running.lock().unwrap();
+        if *is_running {
+            warn!("Watch folder already running");
+            return Ok(());
+        }
+        *is_running = true;
+        drop(is_running);
+
+        fs::create_dir_all(&self.directory)
+            .with_context(|| format!("Failed to create watch folder {:?}", self.directory))?;
+        info!("Watching {:?} for graphics", self.directory);
+
+        let directory = self.directory.clone();
+        let files = Arc::clone(&self.files);
+        let is_running = Arc::clone(&self.is_running);
+        let scan_interval = self.scan_interval;
+
+        tokio::spawn(async move {
+            while {
+                let running = *is_running.lock().unwrap();
+                running
+            } {
+                if let Err(e) = Self::rescan(&directory, &files) {
+                    warn!("Watch folder scan failed: {}", e);
+                }
+                time::sleep(scan_interval).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Stop watching
+    #[allow(dead_code)]
+    pub fn stop(&self) {
+        *self.is_running.lock().unwrap() = false;
+        info!("Stopped watch folder");
+    }
+
+    /// Scan the directory once without starting the watch task
+    pub fn scan_now(&self) -> Result<()> {
+        Self::rescan(&self.directory, &self.files)
+    }
+
+    /// Current file inputs, sorted by name
+    pub fn inputs(&self) -> Vec<FileInput> {
+        let mut inputs: Vec<FileInput> = self.files.lock().unwrap().values().cloned().collect();
+        inputs.sort_by(|a, b| a.name.cmp(&b.name));
+        inputs
+    }
+
+    /// Current file inputs as routable sources
+    pub fn sources(&self) -> Vec<NdiSource> {
+        self.inputs().iter().map(FileInput::to_source).collect()
+    }
+
+    /// Look up a file input by its URL
+    pub fn get_by_url(&self, url: &str) -> Option<FileInput> {
+        self.files
+            .lock()
+            .unwrap()
+            .values()
+            .find(|f| f.url() == url)
+            .cloned()
+    }
+
+    /// Scan the directory once and update the file list
+    fn rescan(directory: &Path, files: &Mutex<HashMap<PathBuf, FileInput>>) -> Result<()> {
+        let found = scan_directory(directory)?;
+        let mut files = files.lock().unwrap();
+
+        files.retain(|path, input| {
+            let keep = found.contains_key(path);
+            if !keep {
+                info!("File input removed: {}", input.name);
+            }
+            keep
+        });
+
+        for (path, modified) in found {
+            match files.get_mut(&path) {
+                Some(existing) if existing.modified != modified => {
+                    existing.modified = modified;
+                    existing.version += 1;
+                    info!("File input updated: {}", existing.name);
+                }
+                Some(_) => {}
+                None => {
+                    let name = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    info!("File input added: {}", name);
+                    files.insert(
+                        path.clone(),
+                        FileInput {
+                            name,
+                            path,
+                            modified,
+                            version: 0,
+                        },
+                    );
+                }
+            }
+        }
+
+        debug!("Watch folder has {} inputs", files.len());
+        Ok(())
+    }
+}
+
+/// List image files in a directory with their modification times
+fn scan_directory(directory: &Path) -> Result<HashMap<PathBuf, SystemTime>> {
+    let mut found = HashMap::new();
+
+    for entry in fs::read_dir(directory)
+        .with_context(|| format!("Failed to read watch folder {:?}", directory))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        let is_image = path
+            .extension()
+            .map(|ext| IMAGE_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+            .unwrap_or(false);
+
+        if !is_image || !path.is_file() {
+            continue;
+        }
+
+        let modified = entry.metadata()?.modified()?;
+        found.insert(path, modified);
+    }
+
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescan_tracks_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = Mutex::new(HashMap::new());
+
+        fs::write(dir.path().join("Welcome Slide.png"), b"png").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+        WatchFolder::rescan(dir.path(), &files).unwrap();
+
+        {
+            let files = files.lock().unwrap();
+            assert_eq!(files.len(), 1);
+            let input = files.values().next().unwrap();
+            assert_eq!(input.name, "Welcome Slide");
+            assert!(input.url().starts_with(FILE_URL_PREFIX));
+        }
+
+        // Simulate a modification by rewinding the recorded timestamp
+        for input in files.lock().unwrap().values_mut() {
+            input.modified = SystemTime::UNIX_EPOCH;
+        }
+        WatchFolder::rescan(dir.path(), &files).unwrap();
+        assert_eq!(files.lock().unwrap().values().next().unwrap().version, 1);
+
+        fs::remove_file(dir.path().join("Welcome Slide.png")).unwrap();
+        WatchFolder::rescan(dir.path(), &files).unwrap();
+        assert!(files.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! WebSocket connection of a station to the partyline
+
+use super::{IntercomMessage, MessageKind, TalkbackAudio};
+use crate::audio::AudioChunk;
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Maximum number of messages kept in the history
+const MAX_HISTORY: usize = 500;
+
+/// Talkback chunks held for playback; older ones are dropped if nobody plays them
+const MAX_TALKBACK: usize = 50;
+
+/// Wait before reconnecting to the host, doubling up to `MAX_RECONNECT`
+const INITIAL_RECONNECT: Duration = Duration::from_secs(1);
+const MAX_RECONNECT: Duration = Duration::from_secs(30);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Intercom channel shared between the GUI and the connection task
+pub struct IntercomChannel {
+    /// Operator/station name used as the sender
+    operator: String,
+    /// Control API of the station hosting the partyline (host:port)
+    server: String,
+    /// Message history (sent and received)
+    history: Arc<Mutex<Vec<IntercomMessage>>>,
+    /// Number of unread alerts (cues and mentions)
+    unread_alerts: Arc<Mutex<usize>>,
+    /// Talkback audio received for this station, not yet played
+    talkback: Arc<Mutex<VecDeque<AudioChunk>>>,
+    /// Frames for the open connection (None while disconnected)
+    outgoing: Arc<Mutex<Option<mpsc::UnboundedSender<Message>>>>,
+}
+
+impl IntercomChannel {
+    pub fn new(operator: &str, server: &str) -> Self {
+        Self {
+            operator: operator.to_string(),
+            server: server.to_string(),
+            history: Arc::new(Mutex::new(Vec::new())),
+            unread_alerts: Arc::new(Mutex::new(0)),
+            talkback: Arc::new(Mutex::new(VecDeque::new())),
+            outgoing: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Get the operator name
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    /// Whether the connection to the host is open
+    pub fn is_connected(&self) -> bool {
+        self.outgoing.lock().unwrap().is_some()
+    }
+
+    /// Stay connected to the host, reconnecting with backoff whenever the
+    /// connection can't be opened or drops
+    pub fn start(&self) {
+        let server = self.server.clone();
+        let operator = self.operator.clone();
+        let history = Arc::clone(&self.history);
+        let unread_alerts = Arc::clone(&self.unread_alerts);
+        let talkback = Arc::clone(&self.talkback);
+        let outgoing = Arc::clone(&self.outgoing);
+
+        tokio::spawn(async move {
+            let mut backoff = INITIAL_RECONNECT;
+            loop {
+                match tokio_tungstenite::connect_async(url(&server)).await {
+                    Ok((socket, _)) => {
+                        info!("Intercom connected to {}", server);
+                        backoff = INITIAL_RECONNECT;
+                        let (sender, frames) = mpsc::unbounded_channel();
+                        *outgoing.lock().unwrap() = Some(sender);
+                        let received = Received {
+                            history: &history,
+                            unread_alerts: &unread_alerts,
+                            talkback: &talkback,
+                        };
+                        let result = run(socket, frames, &operator, received).await;
+                        *outgoing.lock().unwrap() = None;
+                        match result {
+                            Ok(()) => warn!("Intercom host {} closed the connection", server),
+                            Err(e) => warn!("Intercom connection to {} failed: {:#}", server, e),
+                        }
+                    }
+                    // Only the first failure in a row is worth a warning
+                    Err(e) if backoff == INITIAL_RECONNECT => {
+                        warn!("Failed to connect to intercom host {}: {}", server, e)
+                    }
+                    Err(e) => debug!("Failed to connect to intercom host {}: {}", server, e),
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RECONNECT);
+            }
+        });
+    }
+
+    /// Send a message to every station; it joins the history once the host
+    /// relays it back
+    pub fn send(&self, kind: MessageKind, text: &str) -> Result<()> {
+        let message = IntercomMessage::new(&self.operator, kind, text);
+        let payload = serde_json::to_string(&message).context("Failed to serialize message")?;
+        self.queue(Message::Text(payload))
+    }
+
+    /// Send talkback audio to the station of operator `to`
+    pub fn send_talkback(&self, to: &str, chunk: &AudioChunk) -> Result<()> {
+        let audio = TalkbackAudio {
+            to: to.to_string(),
+            chunk: chunk.clone(),
+        };
+        self.queue(Message::Binary(audio.encode()))
+    }
+
+    /// Talkback audio received for this station since the last call
+    pub fn take_talkback(&self) -> Vec<AudioChunk> {
+        self.talkback.lock().unwrap().drain(..).collect()
+    }
+
+    fn queue(&self, frame: Message) -> Result<()> {
+        let outgoing = self.outgoing.lock().unwrap();
+        let sender = outgoing
+            .as_ref()
+            .with_context(|| format!("Not connected to the intercom host {}", self.server))?;
+        sender
+            .send(frame)
+            .ok()
+            .context("Intercom connection closed")
+    }
+
+    /// Get the message history
+    pub fn history(&self) -> Vec<IntercomMessage> {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// Number of unread alerts (cues and mentions)
+    pub fn unread_alerts(&self) -> usize {
+        *self.unread_alerts.lock().unwrap()
+    }
+
+    /// Mark all alerts as read
+    pub fn clear_alerts(&self) {
+        *self.unread_alerts.lock().unwrap() = 0;
+    }
+}
+
+/// Send one message to the partyline without staying connected
+pub async fn send_once(server: &str, operator: &str, kind: MessageKind, text: &str) -> Result<()> {
+    let message = IntercomMessage::new(operator, kind, text);
+    let (mut socket, _) = tokio_tungstenite::connect_async(url(server))
+        .await
+        .with_context(|| format!("Failed to connect to intercom host {}", server))?;
+    socket
+        .send(Message::Text(serde_json::to_string(&message)?))
+        .await?;
+    socket.close(None).await?;
+    Ok(())
+}
+
+fn url(server: &str) -> String {
+    format!("ws://{}/api/intercom", server)
+}
+
+/// Where the connection task leaves what the host relays
+struct Received<'a> {
+    history: &'a Mutex<Vec<IntercomMessage>>,
+    unread_alerts: &'a Mutex<usize>,
+    talkback: &'a Mutex<VecDeque<AudioChunk>>,
+}
+
+/// Exchange frames with the host until either side closes the connection
+async fn run(
+    socket: Socket,
+    mut outgoing: mpsc::UnboundedReceiver<Message>,
+    operator: &str,
+    received: Received<'_>,
+) -> Result<()> {
+    let (mut sink, mut stream) = socket.split();
+    loop {
+        tokio::select! {
+            frame = outgoing.recv() => match frame {
+                Some(frame) => sink.send(frame).await?,
+                None => return Ok(()),
+            },
+            frame = stream.next() => match frame {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<IntercomMessage>(&text) {
+                        Ok(message) => {
+                            debug!("Intercom message: {:?}", message);
+                            let alert = message.is_alert_for(operator);
+                            // The host replays its recent messages on every connect
+                            if push_history(received.history, message) && alert {
+                                *received.unread_alerts.lock().unwrap() += 1;
+                            }
+                        }
+                        Err(e) => warn!("Ignoring malformed intercom message: {}", e),
+                    }
+                }
+                Some(Ok(Message::Binary(bytes))) => match TalkbackAudio::decode(&bytes) {
+                    // Every station sees all talkback; only the addressee plays it
+                    Some(audio) if audio.to.eq_ignore_ascii_case(operator) => {
+                        let mut talkback = received.talkback.lock().unwrap();
+                        talkback.push_back(audio.chunk);
+                        if talkback.len() > MAX_TALKBACK {
+                            talkback.pop_front();
+                        }
+                    }
+                    Some(_) => {}
+                    None => warn!("Ignoring malformed talkback audio"),
+                },
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+}
+
+/// Add a message to the history. Returns false if it was already there
+fn push_history(history: &Mutex<Vec<IntercomMessage>>, message: IntercomMessage) -> bool {
+    let mut history = history.lock().unwrap();
+    if history.contains(&message) {
+        return false;
+    }
+    history.push(message);
+    if history.len() > MAX_HISTORY {
+        let excess = history.len() - MAX_HISTORY;
+        history.drain(..excess);
+    }
+    true
+}
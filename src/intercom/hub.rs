@@ -0,0 +1,65 @@
+//! The partyline hosted on a station's control API: every message and talkback
+//! frame posted is relayed to each connected station
+
+use super::IntercomMessage;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Messages replayed to a station when it connects
+const RECENT_MESSAGES: usize = 100;
+
+/// Messages a slow station may fall behind by before it misses some
+const RELAY_CAPACITY: usize = 256;
+
+/// What the hub relays to the stations
+#[derive(Debug, Clone)]
+pub enum Relayed {
+    Message(IntercomMessage),
+    /// Encoded `TalkbackAudio`, played by the station it is for
+    Talkback(Vec<u8>),
+}
+
+/// Relays messages between the stations connected to this one
+pub struct IntercomHub {
+    recent: Mutex<VecDeque<IntercomMessage>>,
+    relay: broadcast::Sender<Relayed>,
+}
+
+impl IntercomHub {
+    pub fn new() -> Self {
+        Self {
+            recent: Mutex::new(VecDeque::new()),
+            relay: broadcast::channel(RELAY_CAPACITY).0,
+        }
+    }
+
+    /// Recent messages, and every message posted from now on
+    pub fn subscribe(&self) -> (Vec<IntercomMessage>, broadcast::Receiver<Relayed>) {
+        // Subscribed under the lock, so no message is both replayed and relayed
+        let recent = self.recent.lock().unwrap();
+        (recent.iter().cloned().collect(), self.relay.subscribe())
+    }
+
+    /// Relay a message to every connected station
+    pub fn post(&self, message: IntercomMessage) {
+        let mut recent = self.recent.lock().unwrap();
+        recent.push_back(message.clone());
+        if recent.len() > RECENT_MESSAGES {
+            recent.pop_front();
+        }
+        // No station may be connected
+        let _ = self.relay.send(Relayed::Message(message));
+    }
+
+    /// Relay talkback audio; it isn't replayed to stations joining later
+    pub fn post_talkback(&self, audio: Vec<u8>) {
+        let _ = self.relay.send(Relayed::Talkback(audio));
+    }
+}
+
+impl Default for IntercomHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,210 @@
+//! Intercom module for operator chat and cue messaging between RusTV instances
+//!
+//! One station hosts the partyline on its control API (`/api/intercom`); every
+//! station, the host included, connects to it over a WebSocket and exchanges
+//! messages as JSON text frames. Talkback audio for a station travels alongside
+//! as binary frames.
+
+mod channel;
+mod hub;
+
+pub use channel::{send_once, IntercomChannel};
+pub use hub::{IntercomHub, Relayed};
+
+use crate::audio::AudioChunk;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Sample rate of the alert chime
+const CHIME_SAMPLE_RATE: u32 = 48_000;
+
+/// Kind of intercom message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageKind {
+    /// Freeform operator chat
+    Chat,
+    /// Cue call ("standby", "go") highlighted on every station
+    Cue,
+}
+
+/// A message sent between RusTV stations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntercomMessage {
+    /// Operator/station name of the sender
+    pub from: String,
+    /// Message kind
+    pub kind: MessageKind,
+    /// Message text
+    pub text: String,
+    /// Send time in seconds since the Unix epoch
+    pub sent_at: u64,
+    /// Tells the message apart from others by the same sender (send time in
+    /// nanoseconds), so one replayed by the host isn't shown twice
+    #[serde(default)]
+    pub id: u64,
+}
+
+impl IntercomMessage {
+    pub fn new(from: &str, kind: MessageKind, text: &str) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self {
+            from: from.to_string(),
+            kind,
+            text: text.to_string(),
+            sent_at: now.as_secs(),
+            id: now.as_nanos() as u64,
+        }
+    }
+
+    /// Check if the message mentions an operator (`@name`, case-insensitive)
+    pub fn mentions(&self, name: &str) -> bool {
+        let needle = format!("@{}", name.to_lowercase());
+        self.text.to_lowercase().contains(&needle)
+    }
+
+    /// Check if this message should alert the given operator
+    pub fn is_alert_for(&self, name: &str) -> bool {
+        self.from != name && (self.kind == MessageKind::Cue || self.mentions(name))
+    }
+
+    /// Send time formatted as HH:MM:SS (UTC)
+    pub fn time_of_day(&self) -> String {
+        let secs = self.sent_at % 86_400;
+        format!(
+            "{:02}:{:02}:{:02}",
+            secs / 3600,
+            (secs % 3600) / 60,
+            secs % 60
+        )
+    }
+}
+
+/// Talkback audio for one station (binary frame)
+#[derive(Debug, Clone)]
+pub struct TalkbackAudio {
+    /// Operator of the station that plays it
+    pub to: String,
+    pub chunk: AudioChunk,
+}
+
+impl TalkbackAudio {
+    /// Recipient length (u16) and name, sample rate (u32), channel count (u16),
+    /// then the f32 samples, all little-endian
+    pub fn encode(&self) -> Vec<u8> {
+        let to = self.to.as_bytes();
+        let mut bytes = Vec::with_capacity(8 + to.len() + self.chunk.samples.len() * 4);
+        bytes.extend_from_slice(&(to.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(to);
+        bytes.extend_from_slice(&self.chunk.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&self.chunk.channels.to_le_bytes());
+        for sample in &self.chunk.samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let to_len = u16::from_le_bytes(bytes.get(..2)?.try_into().ok()?) as usize;
+        let to = std::str::from_utf8(bytes.get(2..2 + to_len)?).ok()?;
+        let rest = &bytes[2 + to_len..];
+        let sample_rate = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?);
+        let channels = u16::from_le_bytes(rest.get(4..6)?.try_into().ok()?);
+        let samples = rest.get(6..)?;
+        if samples.len() % 4 != 0 {
+            return None;
+        }
+        let samples = samples
+            .chunks_exact(4)
+            .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+            .collect();
+        Some(Self {
+            to: to.to_string(),
+            chunk: AudioChunk {
+                sample_rate,
+                channels,
+                samples,
+            },
+        })
+    }
+}
+
+/// Two short rising tones played when a cue or mention arrives
+pub fn alert_chime() -> AudioChunk {
+    let tone = CHIME_SAMPLE_RATE as usize / 8;
+    let samples = [880.0, 1320.0]
+        .iter()
+        .flat_map(|frequency| {
+            (0..tone).map(move |i| {
+                // Fade each tone out so it doesn't click
+                let fade = 1.0 - i as f32 / tone as f32;
+                (TAU * frequency * i as f32 / CHIME_SAMPLE_RATE as f32).sin() * 0.3 * fade
+            })
+        })
+        .collect();
+    AudioChunk {
+        sample_rate: CHIME_SAMPLE_RATE,
+        channels: 1,
+        samples,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mentions() {
+        let msg = IntercomMessage::new("Director", MessageKind::Chat, "@Cam2 tighten up");
+        assert!(msg.mentions("cam2"));
+        assert!(!msg.mentions("Cam1"));
+        assert!(msg.is_alert_for("Cam2"));
+        assert!(!msg.is_alert_for("Director"));
+    }
+
+    #[test]
+    fn test_cue_alerts_everyone_else() {
+        let msg = IntercomMessage::new("Director", MessageKind::Cue, "Standby song 2");
+        assert!(msg.is_alert_for("Cam1"));
+        assert!(!msg.is_alert_for("Director"));
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        let mut msg = IntercomMessage::new("A", MessageKind::Chat, "hi");
+        msg.sent_at = 86_400 * 3 + 3661;
+        assert_eq!(msg.time_of_day(), "01:01:01");
+    }
+
+    #[test]
+    fn test_talkback_audio_encoding() {
+        let audio = TalkbackAudio {
+            to: "Cam1".to_string(),
+            chunk: AudioChunk {
+                sample_rate: 48_000,
+                channels: 2,
+                samples: vec![0.5, -0.25, 1.0, 0.0],
+            },
+        };
+        let decoded = TalkbackAudio::decode(&audio.encode()).unwrap();
+        assert_eq!(decoded.to, "Cam1");
+        assert_eq!(decoded.chunk.sample_rate, 48_000);
+        assert_eq!(decoded.chunk.channels, 2);
+        assert_eq!(decoded.chunk.samples, audio.chunk.samples);
+
+        let encoded = audio.encode();
+        assert!(TalkbackAudio::decode(&encoded[..encoded.len() - 1]).is_none());
+        assert!(TalkbackAudio::decode(&[9, 0, b'C']).is_none());
+    }
+
+    #[test]
+    fn test_message_serialization() {
+        let msg = IntercomMessage::new("Director", MessageKind::Cue, "Go");
+        let json = serde_json::to_string(&msg).unwrap();
+        let parsed: IntercomMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, msg);
+    }
+}
@@ -1,19 +1,56 @@
+mod api;
+mod audio;
 mod birddog;
 mod companion;
 mod config;
+mod devices;
+mod events;
 mod gui;
+mod inputs;
+mod intercom;
 mod matrix;
+mod metrics;
+mod mqtt;
 mod ndi;
+mod network;
+mod pairing;
+mod propresenter;
+mod recording;
+mod selftest;
+mod storage;
+mod supervisor;
+mod timecode;
+mod video;
 
 use anyhow::Result;
-use birddog::{BirdDogClient, PtzPosition};
+use audio::AudioPlayback;
+use birddog::{BirdDogClient, PtzPosition, TransmitMode};
 use clap::{Parser, Subcommand};
-use companion::CompanionClient;
+use companion::{CompanionClient, CompanionControl};
 use config::Config;
-use log::{error, info};
-use matrix::MatrixRouter;
-use ndi::{NdiDiscovery, NdiReceiver, NdiSource};
-use std::path::PathBuf;
+use events::{EventCategory, EventLog};
+use inputs::WatchFolder;
+use intercom::{IntercomHub, MessageKind};
+use log::{error, info, warn};
+use matrix::export::{diff_against_salvo, ExportFormat, RouterSnapshot};
+use matrix::import::{ImportContext, ImportPlan};
+use matrix::{MatrixRouter, TallyOutputs};
+use ndi::screen::ScreenSenderConfig;
+use ndi::{
+    DiscoveryEvent, LatencyMode, MetadataHub, NdiDiscovery, NdiPtz, NdiReceiver, NdiSource,
+    ProxyManager, ReceiveBandwidth, RemoteTallyWatcher, RoutingDestinations, StatsHub, StreamInfo,
+    TallyEmitter, TransportPolicy,
+};
+use propresenter::ProPresenterClient;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use storage::{EventQuery, Storage, StorageSink};
+use supervisor::Supervisor;
+use timecode::TimecodeListener;
+use tokio::sync::broadcast;
+use video::screen::ScreenTarget;
 
 #[derive(Parser)]
 #[command(name = "rustv")]
@@ -23,6 +60,14 @@ struct Cli {
     #[arg(short, long, default_value = "rustv.toml")]
     config: PathBuf,
 
+    /// Operator profile to start the GUI with (from the profiles file)
+    #[arg(short, long, global = true)]
+    profile: Option<String>,
+
+    /// Start the GUI in training mode: routing only changes the local preview
+    #[arg(long, global = true)]
+    training: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -36,11 +81,34 @@ enum Commands {
         /// Run in continuous mode
         #[arg(short, long)]
         continuous: bool,
+        /// Print sources with their details as JSON (one change per line when continuous)
+        #[arg(long)]
+        json: bool,
     },
     /// View an NDI source
     View {
         /// Source name or URL
         source: String,
+        /// Play the source's audio on the local output device
+        #[arg(long)]
+        audio: bool,
+        /// Receive bandwidth
+        #[arg(long, value_enum, default_value = "full")]
+        bandwidth: ReceiveBandwidth,
+        /// Latency mode (the configured default if not given)
+        #[arg(long, value_enum)]
+        latency: Option<LatencyMode>,
+    },
+    /// Save a still of an NDI source's latest frame (PNG or JPEG by extension)
+    Snapshot {
+        /// Source name or URL
+        source: String,
+        /// Image file to write
+        #[arg(short, long, default_value = "snapshot.png")]
+        out: PathBuf,
+        /// Seconds to wait for a frame
+        #[arg(long, default_value = "5")]
+        timeout: u64,
     },
     /// Matrix routing commands
     Matrix {
@@ -54,11 +122,98 @@ enum Commands {
         #[command(subcommand)]
         action: BirdDogAction,
     },
+    /// PTZ control of any NDI PTZ camera (standard NDI PTZ metadata)
+    Ptz {
+        /// Camera's NDI source name
+        source: String,
+        #[command(subcommand)]
+        action: PtzAction,
+    },
+    /// Camera scenes: presets recalled on several cameras at once
+    Scene {
+        #[command(subcommand)]
+        action: SceneAction,
+    },
+    /// Simple HTTP devices ([[devices]]): run actions and read states
+    Device {
+        #[command(subcommand)]
+        action: DeviceAction,
+    },
+    /// Friendly display names for NDI sources ([ndi.aliases])
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+    /// Approval of newly discovered sources ([ndi.approval])
+    Approval {
+        #[command(subcommand)]
+        action: ApprovalAction,
+    },
     /// Companion integration commands
     Companion {
         #[command(subcommand)]
         action: CompanionAction,
     },
+    /// ProPresenter slide control
+    #[command(name = "propresenter")]
+    ProPresenter {
+        #[command(subcommand)]
+        action: ProPresenterAction,
+    },
+    /// Prometheus metrics and Grafana dashboards
+    Metrics {
+        #[command(subcommand)]
+        action: MetricsAction,
+    },
+    /// Run headless: NDI discovery and the control API
+    Serve,
+    /// Show incoming show timecode (LTC or MTC) without firing triggers
+    Timecode,
+    /// Run the pre-show self-test (exits with an error if any check fails)
+    Selftest,
+    /// Search the stored event history (requires [storage] enabled = true)
+    Events {
+        /// Only events in this category
+        #[arg(long, value_enum)]
+        category: Option<EventCategory>,
+        /// Only events caused by this actor (e.g. "companion")
+        #[arg(long)]
+        actor: Option<String>,
+        /// Only events from the last N hours
+        #[arg(long)]
+        hours: Option<u64>,
+        /// Show at most this many of the most recent events
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// List connected displays and where output windows are placed
+    Displays,
+    /// Publish this machine's screen (or one display or window) as an NDI source
+    SendScreen {
+        /// Display to capture: connector, model or EDID id (see `rustv displays`)
+        #[arg(long, conflicts_with = "window")]
+        display: Option<String>,
+        /// Capture the first window whose title contains this text
+        #[arg(long)]
+        window: Option<String>,
+        /// NDI name of the sender
+        #[arg(long, default_value = "Screen")]
+        name: String,
+        /// Frames captured per second
+        #[arg(long, default_value_t = 30)]
+        fps: u32,
+        /// Scale down to at most this many lines
+        #[arg(long)]
+        max_height: Option<u32>,
+    },
+    /// Send a message to other RusTV stations over the intercom
+    Intercom {
+        /// Message text
+        message: String,
+        /// Send as a cue instead of a chat message
+        #[arg(long)]
+        cue: bool,
+    },
     /// Generate default configuration file
     InitConfig,
 }
@@ -83,6 +238,67 @@ enum MatrixAction {
     Inputs,
     /// List all outputs
     Outputs,
+    /// Export routes and salvos
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "json")]
+        format: ExportFormat,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Save the current routes as a salvo (requires [storage] enabled = true)
+    SaveSalvo {
+        /// Salvo name
+        name: String,
+    },
+    /// Show every output, salvo, backup, trigger and setting that uses a source
+    Impact {
+        /// Source NDI name or alias
+        source: String,
+    },
+    /// Move every route, salvo, alias, trigger and setting naming a source
+    /// to its new NDI name (e.g. after an OBS output was renamed)
+    Rename {
+        /// Old NDI name
+        from: String,
+        /// New NDI name
+        to: String,
+        /// Only list what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show what changed compared to a saved salvo
+    Diff {
+        /// Salvo name
+        salvo: String,
+    },
+    /// Attach a note and tags to the route of an output, or to a salvo
+    /// (clears them if neither is given)
+    Note {
+        /// Output whose current route is noted, or salvo name with --salvo
+        target: String,
+        /// Note text
+        note: Option<String>,
+        /// Tag (repeat for several)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Note a salvo instead of a route
+        #[arg(long)]
+        salvo: bool,
+    },
+    /// Import routes from a CSV file with input,output(,kind) rows
+    Import {
+        /// CSV file to import
+        file: PathBuf,
+        /// Validate and show the changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Blank every output of the running RusTV for standby (needs [api] enabled)
+    Blank,
+    /// Restore the routes from before `matrix blank` (needs [api] enabled)
+    Resume,
 }
 
 #[derive(Subcommand)]
@@ -109,11 +325,151 @@ enum BirdDogAction {
         /// Preset number (1-255)
         id: u8,
     },
+    /// Show or change NDI stream transport (unicast/multicast)
+    Transport {
+        /// Transmit mode
+        #[arg(long, value_enum)]
+        mode: Option<TransmitMode>,
+        /// Multicast address prefix
+        #[arg(long)]
+        address: Option<String>,
+        /// Multicast netmask
+        #[arg(long)]
+        netmask: Option<String>,
+        /// Multicast TTL
+        #[arg(long)]
+        ttl: Option<u8>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PtzAction {
+    /// Move to home position
+    Home,
+    /// Move camera (pan, tilt, zoom)
+    Move {
+        #[arg(long)]
+        pan: f64,
+        #[arg(long)]
+        tilt: f64,
+        #[arg(long)]
+        zoom: f64,
+    },
+    /// Pan, tilt and zoom at speeds from -1.0 to 1.0 until stopped
+    Speed {
+        #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        pan: f64,
+        #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        tilt: f64,
+        #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        zoom: f64,
+    },
+    /// Stop all movement
+    Stop,
+    /// Recall a preset
+    Preset {
+        /// Preset number (0-99)
+        id: u8,
+    },
+    /// Store the current position as a preset
+    Store {
+        /// Preset number (0-99)
+        id: u8,
+    },
+}
+
+#[derive(Subcommand)]
+enum SceneAction {
+    /// List configured scenes
+    List,
+    /// Move every camera of a scene to its preset
+    Recall {
+        /// Scene name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DeviceAction {
+    /// List configured devices with their actions and states
+    List,
+    /// Run a device action
+    Run {
+        /// Device name
+        device: String,
+        /// Action name
+        action: String,
+        /// Value for a placeholder in the action's request (repeatable)
+        #[arg(short, long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+    },
+    /// Read every state of a device (or of all devices) now
+    Status {
+        /// Device name
+        device: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    /// List configured aliases
+    List,
+    /// Give a source a friendly name
+    Set {
+        /// Source's NDI name (e.g. "STUDIO-PC (OBS Output 3)")
+        source: String,
+        /// Name shown in the GUI and CLI, and accepted for routing
+        alias: String,
+    },
+    /// Show a source by its NDI name again
+    Remove {
+        /// Source's NDI name
+        source: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ApprovalAction {
+    /// List approved sources
+    List,
+    /// Let a source be routed
+    Approve {
+        /// Source's NDI name
+        source: String,
+    },
+    /// Quarantine a source again
+    Revoke {
+        /// Source's NDI name
+        source: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProPresenterAction {
+    /// Show the current presentation and slide
+    Status,
+    /// Advance to the next slide
+    Next,
+    /// Go back to the previous slide
+    Previous,
+}
+
+#[derive(Subcommand)]
+enum MetricsAction {
+    /// Write a Grafana dashboard and Prometheus alert rules for the configured outputs and cameras
+    ExportDashboard {
+        /// Directory to write rustv-dashboard.json and rustv-alerts.yml to
+        #[arg(short, long, default_value = ".")]
+        output_dir: PathBuf,
+        /// Prometheus job name that scrapes this instance's /metrics
+        #[arg(long, default_value = "rustv")]
+        job: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum CompanionAction {
-    /// Test connection to Companion server
+    /// Check that the Companion server answers, and report latency and version
     Test,
     /// Set layout via Companion
     SetLayout {
@@ -139,40 +495,145 @@ enum CompanionAction {
         /// Bank number
         bank: u8,
     },
+    /// Send a raw action, e.g. '{"type": "RecallPreset", "preset": 2}' ("-" reads stdin)
+    SendAction {
+        /// Action as JSON
+        json: String,
+    },
     /// Get feedback from Companion
-    Feedback,
+    Feedback {
+        /// Print the raw feedback as pretty JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     env_logger::init();
 
+    // Before the runtime starts threads: it sets NDI_CONFIG_DIR
+    #[cfg(feature = "ndi")]
+    ndi::transport::prepare_config_dir()?;
+
+    tokio::runtime::Runtime::new()?.block_on(run())
+}
+
+async fn run() -> Result<()> {
     let cli = Cli::parse();
 
     // Load or create configuration
     let config = Config::ensure_default_config(&cli.config)?;
     info!("Configuration loaded from: {:?}", cli.config);
+    config.ndi.hx_decoder.configure();
+    TransportPolicy::from_config(&config.ndi).configure();
 
     match cli.command {
         Some(Commands::Gui) => {
             info!("Starting GUI application...");
-            gui::app::run_gui(config)?;
+            let (mut config, storage) = open_storage(config)?;
+            let saved_routes = storage::open_routes(&mut config, storage.as_ref())?;
+            gui::app::run_gui(
+                config,
+                cli.config,
+                storage,
+                saved_routes,
+                cli.profile,
+                cli.training,
+            )?;
+        }
+        Some(Commands::Discover { continuous, json }) => {
+            cmd_discover(continuous, json, &config).await?;
         }
-        Some(Commands::Discover { continuous }) => {
-            cmd_discover(continuous).await?;
+        Some(Commands::View {
+            source,
+            audio,
+            bandwidth,
+            latency,
+        }) => {
+            cmd_view(&source, audio, bandwidth, latency, &config).await?;
         }
-        Some(Commands::View { source }) => {
-            cmd_view(&source).await?;
+        Some(Commands::Snapshot {
+            source,
+            out,
+            timeout,
+        }) => {
+            cmd_snapshot(&source, &out, timeout).await?;
         }
         Some(Commands::Matrix { action }) => {
-            cmd_matrix(action, &config).await?;
+            cmd_matrix(action, &config, &cli.config).await?;
         }
         Some(Commands::BirdDog { camera_ip, action }) => {
             cmd_birddog(&camera_ip, action).await?;
         }
+        Some(Commands::Ptz { source, action }) => {
+            cmd_ptz(&source, action).await?;
+        }
+        Some(Commands::Scene { action }) => {
+            cmd_scene(action, &config).await?;
+        }
+        Some(Commands::Device { action }) => {
+            cmd_device(action, &config).await?;
+        }
+        Some(Commands::Alias { action }) => {
+            cmd_alias(action, &config, &cli.config)?;
+        }
+        Some(Commands::Approval { action }) => {
+            cmd_approval(action, &config, &cli.config)?;
+        }
         Some(Commands::Companion { action }) => {
             cmd_companion(action, &config).await?;
         }
+        Some(Commands::ProPresenter { action }) => {
+            cmd_propresenter(action, &config).await?;
+        }
+        Some(Commands::Metrics { action }) => {
+            cmd_metrics(action, &config)?;
+        }
+        Some(Commands::Serve) => {
+            let (mut config, storage) = open_storage(config)?;
+            let saved_routes = storage::open_routes(&mut config, storage.as_ref())?;
+            cmd_serve(&config, &cli.config, storage, saved_routes).await?;
+        }
+        Some(Commands::Selftest) => {
+            cmd_selftest(&config).await?;
+        }
+        Some(Commands::Events {
+            category,
+            actor,
+            hours,
+            limit,
+        }) => {
+            cmd_events(category, actor, hours, limit, &config)?;
+        }
+        Some(Commands::Timecode) => {
+            cmd_timecode(&config).await?;
+        }
+        Some(Commands::Displays) => {
+            cmd_displays(&config)?;
+        }
+        Some(Commands::SendScreen {
+            display,
+            window,
+            name,
+            fps,
+            max_height,
+        }) => {
+            let target = match (display, window) {
+                (Some(display), _) => ScreenTarget::Display(display),
+                (None, Some(window)) => ScreenTarget::Window(window),
+                (None, None) => ScreenTarget::Desktop,
+            };
+            cmd_send_screen(ScreenSenderConfig {
+                target,
+                name,
+                fps,
+                max_height,
+            })
+            .await?;
+        }
+        Some(Commands::Intercom { message, cue }) => {
+            cmd_intercom(&message, cue, &config).await?;
+        }
         Some(Commands::InitConfig) => {
             config.to_file(&cli.config)?;
             info!("Configuration file created at: {:?}", cli.config);
@@ -180,34 +641,63 @@ async fn main() -> Result<()> {
         None => {
             // Default: start GUI application
             info!("Starting GUI application...");
-            gui::app::run_gui(config)?;
+            let (mut config, storage) = open_storage(config)?;
+            let saved_routes = storage::open_routes(&mut config, storage.as_ref())?;
+            gui::app::run_gui(
+                config,
+                cli.config,
+                storage,
+                saved_routes,
+                cli.profile,
+                cli.training,
+            )?;
         }
     }
 
     Ok(())
 }
 
-async fn cmd_discover(continuous: bool) -> Result<()> {
+async fn cmd_discover(continuous: bool, json: bool, config: &Config) -> Result<()> {
     info!("Starting NDI source discovery...");
-    let discovery = NdiDiscovery::new();
+    let discovery = NdiDiscovery::from_config(&config.ndi);
     discovery.start().await?;
 
     if continuous {
         info!("Running in continuous mode. Press Ctrl+C to stop.");
+        let mut events = discovery.subscribe();
+        for source in discovery.get_sources() {
+            print_change("added", &source, json)?;
+        }
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-            let sources = discovery.get_sources();
-            info!("Found {} NDI sources:", sources.len());
-            for source in sources {
-                println!("  - {}", source);
+            match events.recv().await {
+                Ok(DiscoveryEvent::SourceAdded(source)) => {
+                    info!("Source added:");
+                    print_change("added", &source, json)?;
+                }
+                Ok(DiscoveryEvent::SourceChanged(source)) => {
+                    info!("Source changed:");
+                    print_change("changed", &source, json)?;
+                }
+                Ok(DiscoveryEvent::SourceRemoved(source)) => {
+                    info!("Source removed: {}", source);
+                    if json {
+                        print_change("removed", &source, json)?;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
             }
         }
     } else {
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
         let sources = discovery.get_sources();
         info!("Found {} NDI sources:", sources.len());
-        for source in sources {
-            println!("  - {}", source);
+        if json {
+            println!("{}", serde_json::to_string_pretty(&sources)?);
+        } else {
+            for source in sources {
+                print_source(&source);
+            }
         }
         discovery.stop();
     }
@@ -215,21 +705,98 @@ async fn cmd_discover(continuous: bool) -> Result<()> {
     Ok(())
 }
 
-async fn cmd_view(source_name: &str) -> Result<()> {
+fn print_source(source: &NdiSource) {
+    if source.groups.is_empty() {
+        println!("  - {}", source);
+    } else {
+        println!("  - {} [{}]", source, source.groups.join(", "));
+    }
+}
+
+/// Print a source change of `rustv discover --continuous`
+fn print_change(change: &str, source: &NdiSource, json: bool) -> Result<()> {
+    if json {
+        let line = serde_json::json!({ "change": change, "source": source });
+        println!("{}", serde_json::to_string(&line)?);
+    } else {
+        print_source(source);
+    }
+    Ok(())
+}
+
+async fn cmd_view(
+    source_name: &str,
+    audio: bool,
+    bandwidth: ReceiveBandwidth,
+    latency: Option<LatencyMode>,
+    config: &Config,
+) -> Result<()> {
     info!("Viewing NDI source: {}", source_name);
 
-    let mut receiver = NdiReceiver::new();
+    let playback = if audio {
+        Some(AudioPlayback::start(
+            config.audio_monitor.output_device.as_deref(),
+        )?)
+    } else {
+        None
+    };
+
+    let mut tuning = config.ndi.receive;
+    if let Some(latency) = latency {
+        tuning.latency = latency;
+    }
+    let mut receiver = NdiReceiver::new().with_tuning(tuning);
+    if let Some(map) = config.ndi.channel_maps.get(source_name) {
+        receiver = receiver.with_channel_map(map.clone());
+    }
+    if let Some(trim) = config.ndi.audio_trims.get(source_name) {
+        receiver = receiver.with_trim(*trim);
+    }
     let source = NdiSource::new(source_name.to_string(), format!("ndi://{}", source_name));
 
-    receiver.connect(source)?;
+    receiver.connect(source, bandwidth)?;
     info!("Connected to source. Press Ctrl+C to stop.");
 
-    // Simulate receiving frames
+    let mut last_sequence = 0;
+    let mut last_info = StreamInfo::default();
+    let mut last_metadata = None;
     loop {
         tokio::time::sleep(tokio::time::Duration::from_millis(33)).await; // ~30fps
-        if let Err(e) = receiver.receive_video_frame() {
-            error!("Error receiving frame: {}", e);
-            break;
+        while let Ok(Some(chunk)) = receiver.receive_audio_frame() {
+            if let Some(playback) = &playback {
+                playback.play(&chunk);
+            }
+        }
+        if let Ok(metadata) = receiver.receive_metadata() {
+            if last_metadata.as_ref() != Some(&metadata) {
+                if let Some(product) = &metadata.product {
+                    info!("Source is a {} {}", product.manufacturer, product.long_name);
+                }
+                info!(
+                    "Metadata: PTZ {}, tally echo program={} preview={}",
+                    metadata.ptz, metadata.tally_program, metadata.tally_preview
+                );
+                last_metadata = Some(metadata);
+            }
+        }
+        // Latency changes with every frame, so it doesn't count as a format change
+        let stream_info = StreamInfo {
+            latency_ms: None,
+            ..receiver.stream_info()
+        };
+        if stream_info != last_info {
+            info!("Receiving {}", stream_info);
+            last_info = stream_info;
+        }
+        match receiver.receive_video_frame() {
+            Ok(Some(received)) if received.sequence != last_sequence => {
+                last_sequence = received.sequence;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Error receiving frame: {}", e);
+                break;
+            }
         }
     }
 
@@ -237,12 +804,70 @@ async fn cmd_view(source_name: &str) -> Result<()> {
     Ok(())
 }
 
-async fn cmd_matrix(action: MatrixAction, config: &Config) -> Result<()> {
+async fn cmd_snapshot(source_name: &str, out: &Path, timeout: u64) -> Result<()> {
+    let mut receiver = NdiReceiver::new();
+    let source = NdiSource::new(source_name.to_string(), format!("ndi://{}", source_name));
+    receiver.connect(source, ReceiveBandwidth::Full)?;
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(timeout);
+    let image = loop {
+        match receiver.snapshot() {
+            Ok(image) => break image,
+            Err(e) if std::time::Instant::now() >= deadline => {
+                receiver.disconnect();
+                return Err(e);
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    };
+    receiver.disconnect();
+
+    image
+        .save(out)
+        .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", out, e))?;
+    info!(
+        "Saved {}x{} snapshot of {} to {:?}",
+        image.width(),
+        image.height(),
+        source_name,
+        out
+    );
+    Ok(())
+}
+
+/// Open the configured storage (if enabled), merging its saved routes and salvos
+/// into the configuration
+fn open_storage(mut config: Config) -> Result<(Config, Option<Arc<dyn Storage>>)> {
+    if !config.storage.enabled {
+        return Ok((config, None));
+    }
+    let storage = storage::open(&config.storage)?;
+    storage::restore(storage.as_ref(), &mut config)?;
+    Ok((config, Some(storage)))
+}
+
+async fn cmd_matrix(action: MatrixAction, config: &Config, config_path: &Path) -> Result<()> {
+    // Stored routes and salvos are used but never written back to the config file
+    let (mut state, storage) = open_storage(config.clone())?;
+    let saved_routes = storage::open_routes(&mut state, storage.as_ref())?;
     let mut router = MatrixRouter::new();
 
-    // Initialize with config
-    for output in &config.matrix.outputs {
-        router.add_output(output.clone());
+    // Initialize with config (static sources are the only inputs known without discovery)
+    for output in state.router_outputs() {
+        router.add_output(output);
+    }
+    for source in &state.ndi.static_sources {
+        router.add_input(ndi::discovery::static_source(source));
+    }
+    router.set_notes(&state.matrix.routes);
+    router.set_mirrors(state.matrix.mirrors.clone());
+    for route in &state.matrix.routes {
+        if let Err(e) = router.route_placeholder(&route.input, &route.output) {
+            error!(
+                "Ignoring saved route {} -> {}: {}",
+                route.input, route.output, e
+            );
+        }
     }
 
     match action {
@@ -250,20 +875,43 @@ async fn cmd_matrix(action: MatrixAction, config: &Config) -> Result<()> {
             let routes = router.get_all_routes();
             info!("Current routes:");
             for route in routes {
-                println!("  {} -> {}", route.input, route.output);
+                if route.has_notes() {
+                    println!(
+                        "  {} -> {}  ({})",
+                        route.input,
+                        route.output,
+                        route.notes_summary()
+                    );
+                } else {
+                    println!("  {} -> {}", route.input, route.output);
+                }
             }
         }
         MatrixAction::Route { input, output } => {
             router.route(&input, &output)?;
             info!("Route created: {} -> {}", input, output);
+            if let Some(saved_routes) = &saved_routes {
+                saved_routes.save_routes(&router.route_intents())?;
+            }
         }
         MatrixAction::Unroute { output } => {
             if let Some(input) = router.unroute(&output) {
                 info!("Route removed: {} -> {}", input, output);
+                if let Some(saved_routes) = &saved_routes {
+                    saved_routes.save_routes(&router.route_intents())?;
+                }
             } else {
                 info!("No route found for output: {}", output);
             }
         }
+        MatrixAction::SaveSalvo { name } => {
+            let storage = storage
+                .ok_or_else(|| anyhow::anyhow!("Saving salvos needs [storage] enabled = true"))?;
+            let mut routes = router.route_intents();
+            routes.sort_by(|a, b| a.output.cmp(&b.output));
+            storage.save_salvo(&matrix::Salvo::new(name.clone(), routes))?;
+            info!("Saved salvo '{}'", name);
+        }
         MatrixAction::Inputs => {
             let inputs = router.get_inputs();
             info!("Available inputs:");
@@ -278,11 +926,446 @@ async fn cmd_matrix(action: MatrixAction, config: &Config) -> Result<()> {
                 println!("  - {}", output);
             }
         }
+        MatrixAction::Export { format, output } => {
+            let snapshot = RouterSnapshot::new(router.route_intents(), state.matrix.salvos.clone());
+            let content = snapshot.export(format)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, content)?;
+                    info!("Routing state exported to {:?}", path);
+                }
+                None => print!("{}", content),
+            }
+        }
+        MatrixAction::Impact { source } => {
+            // Without discovery the source is known by its NDI name and configured alias
+            let name = state
+                .ndi
+                .aliases
+                .iter()
+                .find(|(_, alias)| **alias == source)
+                .map_or(source.clone(), |(name, _)| name.clone());
+            let mut ndi_source = NdiSource::new(name.clone(), String::new());
+            ndi_source.alias = state.ndi.aliases.get(&name).cloned();
+            // The router only holds the saved routes, which are listed as such
+            let references = matrix::impact::references(&state, &[], &ndi_source);
+            if references.is_empty() {
+                println!("Nothing refers to {}", name);
+            } else {
+                println!("{} is used by:", name);
+                for reference in references {
+                    println!("  {}", reference);
+                }
+            }
+        }
+        MatrixAction::Rename { from, to, dry_run } => {
+            let plan = matrix::rename::RenamePlan::new(
+                config.clone(),
+                saved_routes.as_deref(),
+                storage.as_deref(),
+                &from,
+                &to,
+            )?;
+            if plan.changes.is_empty() {
+                println!("Nothing refers to {}", from);
+                return Ok(());
+            }
+            println!(
+                "{} {} to {}:",
+                if dry_run { "Would rename" } else { "Renaming" },
+                from,
+                to
+            );
+            for change in &plan.changes {
+                println!("  {}", change);
+            }
+            if dry_run {
+                return Ok(());
+            }
+            plan.save(config_path, saved_routes.as_deref(), storage.as_deref())?;
+            info!("Renamed {} to {} in {:?}", from, to, config_path);
+        }
+        MatrixAction::Diff { salvo } => {
+            let salvo = state
+                .matrix
+                .salvos
+                .iter()
+                .find(|s| s.name == salvo)
+                .ok_or_else(|| anyhow::anyhow!("Salvo '{}' not found", salvo))?;
+
+            let changes = diff_against_salvo(&router.get_all_routes(), salvo);
+            if changes.is_empty() {
+                println!("No changes since salvo '{}'", salvo.name);
+            } else {
+                println!("Changes since salvo '{}':", salvo.name);
+                for change in changes {
+                    println!(
+                        "  {}: {} -> {}",
+                        change.output,
+                        change.salvo_input.as_deref().unwrap_or("(none)"),
+                        change.current_input.as_deref().unwrap_or("(none)")
+                    );
+                }
+            }
+        }
+        MatrixAction::Note {
+            target,
+            note,
+            tags,
+            salvo,
+        } => {
+            let mut updated = config.clone();
+            if salvo {
+                let index = match updated.matrix.salvos.iter().position(|s| s.name == target) {
+                    Some(index) => index,
+                    None => {
+                        // Salvos saved in storage are copied into the config file to carry the note
+                        let stored = state
+                            .matrix
+                            .salvos
+                            .iter()
+                            .find(|s| s.name == target)
+                            .ok_or_else(|| anyhow::anyhow!("Salvo '{}' not found", target))?;
+                        updated.matrix.salvos.push(stored.clone());
+                        updated.matrix.salvos.len() - 1
+                    }
+                };
+                let salvo = &mut updated.matrix.salvos[index];
+                salvo.note = note;
+                salvo.tags = tags;
+            } else {
+                let input = router
+                    .get_route(&target)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("No route found for output: {}", target))?;
+                updated
+                    .matrix
+                    .routes
+                    .retain(|r| r.output != target || r.input != input);
+                let mut route = matrix::Route::new(input, target.clone());
+                route.note = note;
+                route.tags = tags;
+                updated.matrix.routes.push(route);
+            }
+            updated.to_file(config_path)?;
+            info!("Notes saved for {} in {:?}", target, config_path);
+        }
+        MatrixAction::Import { file, dry_run } => {
+            cmd_matrix_import(&file, dry_run, config, config_path)?;
+        }
+        MatrixAction::Blank => {
+            send_to_running(companion::CompanionAction::BlankAll, config).await?;
+            info!("All outputs blanked");
+        }
+        MatrixAction::Resume => {
+            send_to_running(companion::CompanionAction::Resume, config).await?;
+            info!("Outputs resumed");
+        }
+    }
+
+    Ok(())
+}
+
+/// Run an action in the RusTV instance on this machine, through its control API
+async fn send_to_running(action: companion::CompanionAction, config: &Config) -> Result<()> {
+    if !config.api.enabled {
+        anyhow::bail!("Needs the control API of the running RusTV ([api] enabled = true)");
+    }
+    let url = format!("http://127.0.0.1:{}/api/companion/action", config.api.port);
+    let response = reqwest::Client::new()
+        .post(&url)
+        .json(&action)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("RusTV is not running at {}: {}", url, e))?;
+    if !response.status().is_success() {
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        anyhow::bail!(
+            "{}",
+            body["error"].as_str().unwrap_or("Action failed in RusTV")
+        );
+    }
+    Ok(())
+}
+
+fn cmd_matrix_import(
+    file: &Path,
+    dry_run: bool,
+    config: &Config,
+    config_path: &Path,
+) -> Result<()> {
+    let content = std::fs::read_to_string(file)?;
+
+    let watch_folder = WatchFolder::new(
+        &config.watch_folder.directory,
+        std::time::Duration::from_secs(config.watch_folder.scan_interval),
+    );
+    if config.watch_folder.enabled {
+        if let Err(e) = watch_folder.scan_now() {
+            error!("Failed to scan watch folder: {}", e);
+        }
+    }
+
+    let context = ImportContext {
+        outputs: config.router_outputs(),
+        static_sources: config
+            .ndi
+            .static_sources
+            .iter()
+            .map(|s| s.name.clone())
+            .collect(),
+        file_inputs: watch_folder.inputs().into_iter().map(|f| f.name).collect(),
+    };
+
+    let plan = ImportPlan::parse(&content, &context);
+    if !plan.is_valid() {
+        for err in &plan.errors {
+            println!("  {}", err);
+        }
+        anyhow::bail!(
+            "{} invalid rows in {:?}, nothing imported",
+            plan.errors.len(),
+            file
+        );
+    }
+
+    for imported in &plan.routes {
+        let current = config
+            .matrix
+            .routes
+            .iter()
+            .find(|r| r.output == imported.route.output);
+        let status = match current {
+            None => "new".to_string(),
+            Some(r) if r.input == imported.route.input => "unchanged".to_string(),
+            Some(r) => format!("replaces {}", r.input),
+        };
+        println!(
+            "  {} -> {} [{}] ({})",
+            imported.route.input, imported.route.output, imported.kind, status
+        );
+    }
+
+    if dry_run {
+        info!(
+            "Dry run: {} routes validated, nothing saved",
+            plan.routes.len()
+        );
+        return Ok(());
+    }
+
+    let mut updated = config.clone();
+    plan.apply_to(&mut updated.matrix.routes);
+    updated.to_file(config_path)?;
+    info!(
+        "Imported {} routes into {:?}",
+        plan.routes.len(),
+        config_path
+    );
+
+    EventLog::from_config(&config.events).info(
+        EventCategory::Routing,
+        "cli",
+        format!("Imported {} routes from {:?}", plan.routes.len(), file),
+    );
+
+    Ok(())
+}
+
+async fn cmd_ptz(source: &str, action: PtzAction) -> Result<()> {
+    let ptz = NdiPtz::connect_by_name(source)?;
+
+    match action {
+        PtzAction::Home => ptz.home()?,
+        PtzAction::Move { pan, tilt, zoom } => {
+            ptz.move_absolute(PtzPosition::new(pan, tilt, zoom))?
+        }
+        PtzAction::Speed { pan, tilt, zoom } => ptz.move_speed(pan, tilt, zoom)?,
+        PtzAction::Stop => ptz.stop()?,
+        PtzAction::Preset { id } => ptz.recall_preset(id)?,
+        PtzAction::Store { id } => ptz.save_preset(id)?,
+    }
+
+    ptz.flush(Duration::from_secs(10)).await
+}
+
+async fn cmd_scene(action: SceneAction, config: &Config) -> Result<()> {
+    match action {
+        SceneAction::List => {
+            if config.birddog.scenes.is_empty() {
+                println!("No scenes configured");
+            }
+            for scene in &config.birddog.scenes {
+                println!("{}", scene.name);
+                for shot in &scene.shots {
+                    println!("  {}", shot);
+                }
+            }
+        }
+        SceneAction::Recall { name } => {
+            let router = Arc::new(Mutex::new(MatrixRouter::new()));
+            let control = CompanionControl::from_config(router, config);
+            let results = control.recall_scene(&name).await?;
+            let failed = results.iter().filter(|r| r.result.is_err()).count();
+            for result in &results {
+                match &result.result {
+                    Ok(()) => println!("✓ {}", result.shot),
+                    Err(e) => println!("✗ {}: {}", result.shot, e),
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{} of {} cameras failed", failed, results.len());
+            }
+        }
     }
 
     Ok(())
 }
 
+async fn cmd_device(action: DeviceAction, config: &Config) -> Result<()> {
+    let devices = devices::Devices::new(config.devices.clone());
+    match action {
+        DeviceAction::List => {
+            if config.devices.is_empty() {
+                println!("No devices configured");
+            }
+            for device in &config.devices {
+                println!("{} ({})", device.name, device.base_url);
+                for (name, request) in &device.actions {
+                    println!("  action {}: {} {}", name, request.method, request.path);
+                }
+                for (name, poll) in &device.polls {
+                    println!("  state {}: every {}s", name, poll.interval_secs);
+                }
+            }
+        }
+        DeviceAction::Run {
+            device,
+            action,
+            params,
+        } => {
+            let params = params
+                .iter()
+                .map(|param| {
+                    let (name, value) = param
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected NAME=VALUE, got '{}'", param))?;
+                    Ok((name.to_string(), value.to_string()))
+                })
+                .collect::<Result<_>>()?;
+            let response = devices
+                .run(&devices::DeviceCommand {
+                    device,
+                    action,
+                    params,
+                })
+                .await?;
+            if !response.trim().is_empty() {
+                println!("{}", response.trim());
+            }
+        }
+        DeviceAction::Status { device } => {
+            if let Some(name) = &device {
+                if !devices.devices().iter().any(|d| &d.name == name) {
+                    anyhow::bail!("Device '{}' not found", name);
+                }
+            }
+            let mut failed = 0;
+            for config in devices
+                .devices()
+                .iter()
+                .filter(|d| device.as_ref().is_none_or(|name| &d.name == name))
+            {
+                for state in config.polls.keys() {
+                    match devices.poll(&config.name, state).await {
+                        Ok(value) => println!("{} {}: {}", config.name, state, value),
+                        Err(e) => {
+                            failed += 1;
+                            println!("{} {}: {:#}", config.name, state, e);
+                        }
+                    }
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{} states could not be read", failed);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_alias(action: AliasAction, config: &Config, config_path: &Path) -> Result<()> {
+    let mut updated = config.clone();
+    match action {
+        AliasAction::List => {
+            if config.ndi.aliases.is_empty() {
+                println!("No aliases configured");
+            }
+            for (source, alias) in &config.ndi.aliases {
+                println!("  {} = {}", alias, source);
+            }
+            return Ok(());
+        }
+        AliasAction::Set { source, alias } => {
+            if let Some((other, _)) = config
+                .ndi
+                .aliases
+                .iter()
+                .find(|(other, a)| **a == alias && **other != source)
+            {
+                anyhow::bail!("'{}' is already the alias of {}", alias, other);
+            }
+            info!("Alias set: {} = {}", alias, source);
+            if let Some(old) = updated.ndi.aliases.insert(source, alias) {
+                warn_alias_references(config, &old);
+            }
+        }
+        AliasAction::Remove { source } => {
+            let Some(old) = updated.ndi.aliases.remove(&source) else {
+                anyhow::bail!("{} has no alias", source);
+            };
+            info!("Alias removed: {}", source);
+            warn_alias_references(config, &old);
+        }
+    }
+    updated.to_file(config_path)
+}
+
+/// Warn about what still names a source by an alias that is going away
+fn warn_alias_references(config: &Config, alias: &str) {
+    for reference in matrix::impact::alias_references(config, alias) {
+        warn!("{} no longer finds its source", reference);
+    }
+}
+
+fn cmd_approval(action: ApprovalAction, config: &Config, config_path: &Path) -> Result<()> {
+    let mut updated = config.clone();
+    let approval = &mut updated.ndi.approval;
+    match action {
+        ApprovalAction::List => {
+            if !approval.required {
+                println!("Approval is not required ([ndi.approval] required = false)");
+            }
+            for source in &approval.approved {
+                println!("  {}", source);
+            }
+            return Ok(());
+        }
+        ApprovalAction::Approve { source } => {
+            info!("Source approved: {}", source);
+            approval.approved.insert(source);
+        }
+        ApprovalAction::Revoke { source } => {
+            if !approval.approved.remove(&source) {
+                anyhow::bail!("{} is not approved", source);
+            }
+            info!("Approval revoked: {}", source);
+        }
+    }
+    updated.to_file(config_path)
+}
+
 async fn cmd_birddog(camera_ip: &str, action: BirdDogAction) -> Result<()> {
     let client = BirdDogClient::new(camera_ip);
 
@@ -325,6 +1408,37 @@ async fn cmd_birddog(camera_ip: &str, action: BirdDogAction) -> Result<()> {
             client.recall_preset(id).await?;
             info!("Recalled preset {}", id);
         }
+        BirdDogAction::Transport {
+            mode,
+            address,
+            netmask,
+            ttl,
+        } => {
+            let mut transport = client.get_stream_transport().await?;
+
+            if mode.is_some() || address.is_some() || netmask.is_some() || ttl.is_some() {
+                if let Some(mode) = mode {
+                    transport.mode = mode;
+                }
+                if let Some(address) = address {
+                    transport.multicast_prefix = address;
+                }
+                if let Some(netmask) = netmask {
+                    transport.multicast_mask = netmask;
+                }
+                if let Some(ttl) = ttl {
+                    transport.multicast_ttl = ttl;
+                }
+                client.set_stream_transport(&transport).await?;
+                info!("Stream transport updated");
+            }
+
+            println!("Stream Transport:");
+            println!("  Mode: {}", transport.mode);
+            println!("  Multicast Prefix: {}", transport.multicast_prefix);
+            println!("  Multicast Netmask: {}", transport.multicast_mask);
+            println!("  Multicast TTL: {}", transport.multicast_ttl);
+        }
     }
 
     Ok(())
@@ -344,12 +1458,34 @@ async fn cmd_companion(action: CompanionAction, config: &Config) -> Result<()> {
 
     match action {
         CompanionAction::Test => {
-            info!("Testing connection to Companion server...");
-            if client.test_connection().await {
-                info!("✓ Successfully connected to Companion server");
-            } else {
-                error!("✗ Failed to connect to Companion server");
+            let address = format!("{}:{}", config.companion.host, config.companion.port);
+            println!("Companion server: {}", address);
+            let probe = client.probe().await?;
+            println!(
+                "  Response: {} in {} ms",
+                probe.status,
+                probe.latency.as_millis()
+            );
+            println!(
+                "  Version: {}",
+                probe.version.as_deref().unwrap_or("not reported")
+            );
+            if !probe.status.is_success() {
+                anyhow::bail!("Companion server at {} returned {}", address, probe.status);
             }
+            println!("✓ Companion server is reachable");
+        }
+        CompanionAction::SendAction { json } => {
+            let json = if json == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                json
+            };
+            let action: companion::CompanionAction = serde_json::from_str(&json)
+                .map_err(|e| anyhow::anyhow!("Invalid Companion action: {}", e))?;
+            println!("Sending: {}", serde_json::to_string(&action)?);
+            client.send_action(action).await?;
+            println!("✓ Action accepted");
         }
         CompanionAction::SetLayout { layout } => {
             client.set_layout(&layout).await?;
@@ -367,22 +1503,437 @@ async fn cmd_companion(action: CompanionAction, config: &Config) -> Result<()> {
             client.press_button(page, bank).await?;
             info!("Button pressed: page={}, bank={}", page, bank);
         }
-        CompanionAction::Feedback => {
+        CompanionAction::Feedback { json } => {
             let feedback = client.get_feedback().await?;
-            println!("Companion Feedback:");
-            if let Some(layout) = feedback.layout {
-                println!("  Current Layout: {}", layout);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&feedback)?);
+                return Ok(());
             }
+            println!("Companion Feedback:");
+            println!(
+                "  Current Layout: {}",
+                feedback.layout.as_deref().unwrap_or("(none)")
+            );
             println!("  Routes: {}", feedback.routes.len());
-            for route in feedback.routes {
-                println!("    {} -> {}", route.input, route.output);
+            let width = feedback
+                .routes
+                .iter()
+                .map(|r| r.output.chars().count())
+                .max()
+                .unwrap_or(0);
+            for route in &feedback.routes {
+                println!("    {:<width$} ← {}", route.output, route.input);
             }
             println!("  Sources: {}", feedback.sources.len());
-            for source in feedback.sources {
-                println!("    - {}", source);
+            for source in &feedback.sources {
+                let maintenance = if feedback.maintenance.contains(source) {
+                    " (maintenance)"
+                } else {
+                    ""
+                };
+                println!("    - {}{}", source, maintenance);
+            }
+            if let Some(recording) = &feedback.recording {
+                println!("  {}", recording.text);
+            }
+            if let Some(next) = &feedback.next_event {
+                println!("  {} (in {} s)", next.text, next.countdown_secs);
             }
         }
     }
 
     Ok(())
 }
+
+async fn cmd_propresenter(action: ProPresenterAction, config: &Config) -> Result<()> {
+    let client = ProPresenterClient::new(&config.propresenter.host, config.propresenter.port);
+
+    match action {
+        ProPresenterAction::Status => {
+            let status = client.slide_status().await?;
+            println!("{}", status.label());
+            if !status.next_text.is_empty() {
+                println!("Next: {}", status.next_text);
+            }
+        }
+        ProPresenterAction::Next => client.next_slide().await?,
+        ProPresenterAction::Previous => client.previous_slide().await?,
+    }
+
+    Ok(())
+}
+
+async fn cmd_serve(
+    config: &Config,
+    config_path: &Path,
+    storage: Option<Arc<dyn Storage>>,
+    saved_routes: Option<Arc<dyn Storage>>,
+) -> Result<()> {
+    if config.pairing.standby && config.pairing.primary.trim().is_empty() {
+        anyhow::bail!("A standby needs the primary's address (primary under [pairing])");
+    }
+    let events = EventLog::from_config(&config.events);
+    let supervisor = Arc::new(Supervisor::new().with_events(events.clone()));
+    let discovery =
+        Arc::new(NdiDiscovery::from_config(&config.ndi).with_supervisor(Arc::clone(&supervisor)));
+    if config.ndi.auto_discovery {
+        discovery.start().await?;
+    }
+
+    if config.selftest.enabled {
+        run_selftest(config, &discovery).await?;
+    }
+
+    let mut router = MatrixRouter::new();
+    for output in config.router_outputs() {
+        router.add_output(output);
+    }
+    // Static sources are routable before discovery has scanned
+    for source in discovery.get_sources() {
+        router.add_input(source);
+    }
+    router.set_notes(&config.matrix.routes);
+    router.set_mirrors(config.matrix.mirrors.clone());
+    if saved_routes.is_some() {
+        // Carry on with the routing that was live when RusTV last stopped
+        for route in &config.matrix.routes {
+            if let Err(e) = router.route_placeholder(&route.input, &route.output) {
+                error!(
+                    "Ignoring saved route {} -> {}: {}",
+                    route.input, route.output, e
+                );
+            }
+        }
+    }
+    if let Some(storage) = &storage {
+        events.add_sink(Box::new(StorageSink::new(Arc::clone(storage))));
+    }
+    let router = Arc::new(Mutex::new(router));
+    if let Some(saved_routes) = saved_routes {
+        storage::persist_routes(Arc::clone(&router), saved_routes);
+    }
+    matrix::wildcard::follow_discovery(
+        Arc::clone(&router),
+        Arc::clone(&discovery),
+        config.matrix.wildcard_rebind,
+    );
+
+    let peer = config.pairing.peer_address().map(|peer| {
+        pairing::StandbyMirror::new(
+            &config.pairing,
+            peer,
+            Arc::clone(&router),
+            Arc::clone(&discovery),
+            events.clone(),
+        )
+    });
+    let mut takeover = pairing::Takeover::default();
+    if let Some(peer) = &peer {
+        // A primary back from an outage stands by if the standby took over meanwhile
+        let peer_active =
+            !config.pairing.standby && peer.peer_state().await.is_ok_and(|state| state.active);
+        if config.pairing.standby || peer_active {
+            // Outputs and control servers stay off until the active side is
+            // lost, so the pair never publishes the same NDI names twice
+            tokio::select! {
+                taken = peer.run_until_takeover() => {
+                    info!("Taking over on term {}", taken.term);
+                    takeover = taken;
+                }
+                _ = tokio::signal::ctrl_c() => {
+                    info!("Shutting down");
+                    discovery.stop();
+                    return Ok(());
+                }
+            }
+        }
+    }
+    let role = Arc::new(pairing::PairingRole::default());
+    role.activate(takeover.term);
+    let mut config = config.clone();
+    if let Some(mirrored) = takeover.mirrored {
+        config.matrix.salvos = mirrored.salvos;
+        config.ndi.aliases = mirrored.aliases;
+        config.ndi.tags = mirrored.tags;
+    }
+    let config = &config;
+
+    let timecode = config
+        .timecode
+        .enabled
+        .then(|| Arc::new(TimecodeListener::new(&config.timecode)));
+    let mut control = CompanionControl::from_config(Arc::clone(&router), config)
+        .with_events(events.clone())
+        .with_discovery(Arc::clone(&discovery));
+    if let Some(timecode) = &timecode {
+        control = control.with_timecode(Arc::clone(timecode));
+    }
+    let control = Arc::new(control);
+    Arc::clone(control.devices()).start_polling(events.clone());
+
+    if config.mqtt.enabled {
+        mqtt::MqttBridge::new(
+            config,
+            Arc::clone(&router),
+            Arc::clone(&discovery),
+            Arc::clone(&control),
+        )
+        .start();
+    }
+
+    if let Some(timecode) = &timecode {
+        timecode.start(Some(Arc::clone(&control)));
+    }
+
+    // Filled from the tally and remote tally receivers (the only receivers running headless)
+    let metadata = Arc::new(MetadataHub::new());
+    let tally_outputs = TallyOutputs::from_config(&config.matrix);
+    if !tally_outputs.is_empty() {
+        TallyEmitter::new(
+            Arc::clone(&router),
+            Arc::clone(&discovery),
+            tally_outputs,
+            Arc::clone(&metadata),
+        )
+        .start();
+    }
+
+    if !config.ndi.remote_tally.is_empty() {
+        RemoteTallyWatcher::new(
+            Arc::clone(&discovery),
+            &config.ndi.remote_tally,
+            Arc::clone(&metadata),
+        )
+        .start();
+    }
+
+    if config.matrix.ndi_routing {
+        RoutingDestinations::new(Arc::clone(&router), Arc::clone(&discovery), events.clone())
+            .start();
+    }
+
+    let proxies = Arc::new(
+        ProxyManager::new(config.ndi.proxies.clone(), Arc::clone(&discovery))
+            .with_supervisor(Arc::clone(&supervisor))
+            .with_router(Arc::clone(&router)),
+    );
+    proxies.start_enabled();
+    ndi::bridge::start_joins(&config.ndi.bridges, &supervisor);
+
+    let state = api::ApiState {
+        router: Arc::clone(&router),
+        discovery: Arc::clone(&discovery),
+        control,
+        metadata,
+        // Headless receivers only carry tally and metadata
+        stats: Arc::new(StatsHub::new()),
+        proxies,
+        supervisor: Arc::clone(&supervisor),
+        config: config.api.config_editing.then(|| {
+            Arc::new(api::ConfigEditor::new(
+                config_path.to_path_buf(),
+                events.clone(),
+            ))
+        }),
+        pairing: Some(Arc::clone(&role)),
+        intercom: config
+            .intercom
+            .hosts()
+            .then(|| Arc::new(IntercomHub::new())),
+    };
+    let port = config.api.port;
+    supervisor.spawn("Control API", move || api::serve(state.clone(), port));
+
+    info!("RusTV running headless. Press Ctrl+C to stop.");
+    match &peer {
+        // Exit rather than publish alongside the other side; restarted, this
+        // daemon stands by for it
+        Some(peer) => tokio::select! {
+            term = peer.run_until_fenced(&role) => {
+                discovery.stop();
+                anyhow::bail!("Stood down for the other daemon of the pair, active on term {}", term);
+            }
+            result = tokio::signal::ctrl_c() => result?,
+        },
+        None => tokio::signal::ctrl_c().await?,
+    }
+    info!("Shutting down");
+
+    discovery.stop();
+    Ok(())
+}
+
+fn cmd_events(
+    category: Option<EventCategory>,
+    actor: Option<String>,
+    hours: Option<u64>,
+    limit: usize,
+    config: &Config,
+) -> Result<()> {
+    let (_, storage) = open_storage(config.clone())?;
+    let storage =
+        storage.ok_or_else(|| anyhow::anyhow!("Event history needs [storage] enabled = true"))?;
+
+    let since_ms = hours.map(|hours| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        now.saturating_sub(hours * 3_600_000)
+    });
+    let events = storage.query_events(&EventQuery {
+        since_ms,
+        category,
+        actor,
+        limit: Some(limit),
+    })?;
+
+    if events.is_empty() {
+        println!("No matching events");
+    }
+    for event in events {
+        println!(
+            "{} [{}] {}: {}",
+            event.rfc3339(),
+            event.category,
+            event.actor.as_deref().unwrap_or("-"),
+            event.message
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_send_screen(config: ScreenSenderConfig) -> Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut sender = {
+        let stop = Arc::clone(&stop);
+        tokio::task::spawn_blocking(move || ndi::screen::run(&config, &stop))
+    };
+
+    info!("Press Ctrl+C to stop sending");
+    tokio::select! {
+        result = &mut sender => return result?,
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    // Let the sender finish its frame and leave the network cleanly
+    stop.store(true, Ordering::Relaxed);
+    sender.await??;
+    info!("Screen sender stopped");
+    Ok(())
+}
+
+fn cmd_displays(config: &Config) -> Result<()> {
+    let displays = gui::displays::probe()?;
+    if displays.is_empty() {
+        println!("No active displays");
+    }
+    for display in &displays {
+        println!(
+            "{}: {}x{} at {},{}  model: {}  EDID id: {}",
+            display.connector,
+            display.width,
+            display.height,
+            display.x,
+            display.y,
+            display.model.as_deref().unwrap_or("-"),
+            display.edid_id.as_deref().unwrap_or("-")
+        );
+    }
+
+    for (window, display) in gui::displays::place(&config.gui.output_windows, &displays) {
+        match display {
+            Some(display) => println!("{} -> {}", window.output, display.label()),
+            None => println!("{} -> {} (not connected)", window.output, window.display),
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_selftest(config: &Config) -> Result<()> {
+    let discovery = NdiDiscovery::from_config(&config.ndi);
+    discovery.start().await?;
+    let result = run_selftest(config, &discovery).await;
+    discovery.stop();
+    result
+}
+
+/// Print the self-test checklist, failing if any check failed
+async fn run_selftest(config: &Config, discovery: &NdiDiscovery) -> Result<()> {
+    info!("Running self-test...");
+    let report = selftest::run(config, discovery).await;
+    for check in &report.checks {
+        println!("{}", check);
+    }
+
+    if !report.passed() {
+        anyhow::bail!("Self-test failed ({} checks)", report.failures());
+    }
+    println!("Self-test passed");
+    Ok(())
+}
+
+async fn cmd_timecode(config: &Config) -> Result<()> {
+    let listener = TimecodeListener::new(&config.timecode);
+    listener.start(None);
+
+    for trigger in &config.timecode.triggers {
+        info!("Trigger at {}: {:?}", trigger.at, trigger.action);
+    }
+    info!("Waiting for timecode. Press Ctrl+C to stop.");
+
+    let mut last = None;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(tokio::time::Duration::from_millis(100)) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+        let current = listener.current();
+        if current != last {
+            if let Some(tc) = current {
+                println!("{}", tc);
+            }
+            last = current;
+        }
+    }
+}
+
+async fn cmd_intercom(message: &str, cue: bool, config: &Config) -> Result<()> {
+    if !config.intercom.enabled {
+        error!("Intercom is disabled in configuration");
+        return Ok(());
+    }
+
+    let kind = if cue {
+        MessageKind::Cue
+    } else {
+        MessageKind::Chat
+    };
+    let server = config.intercom.server_address(&config.api);
+    intercom::send_once(&server, &config.intercom.operator, kind, message).await?;
+    info!(
+        "Sent {:?} over the intercom on {}: {}",
+        kind, server, message
+    );
+    Ok(())
+}
+
+fn cmd_metrics(action: MetricsAction, config: &Config) -> Result<()> {
+    match action {
+        MetricsAction::ExportDashboard { output_dir, job } => {
+            std::fs::create_dir_all(&output_dir)?;
+            let dashboard = output_dir.join(metrics::DASHBOARD_FILE);
+            std::fs::write(
+                &dashboard,
+                serde_json::to_string_pretty(&metrics::dashboard(config))?,
+            )?;
+            let alerts = output_dir.join(metrics::ALERTS_FILE);
+            std::fs::write(&alerts, metrics::alert_rules(config, &job))?;
+            println!("Dashboard:   {}", dashboard.display());
+            println!("Alert rules: {}", alerts.display());
+            println!(
+                "Import the dashboard into Grafana and add the rules to Prometheus's rule_files"
+            );
+        }
+    }
+    Ok(())
+}
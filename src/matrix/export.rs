@@ -0,0 +1,239 @@
+//! Export of the routing table and salvos, and diffs against saved salvos
+
+use super::{Route, Salvo};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Output format for `rustv matrix export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Html,
+}
+
+/// Routing state to export
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterSnapshot {
+    pub routes: Vec<Route>,
+    pub salvos: Vec<Salvo>,
+}
+
+impl RouterSnapshot {
+    pub fn new(mut routes: Vec<Route>, salvos: Vec<Salvo>) -> Self {
+        routes.sort_by(|a, b| a.output.cmp(&b.output));
+        Self { routes, salvos }
+    }
+
+    /// Render the snapshot in the given format
+    pub fn export(&self, format: ExportFormat) -> Result<String> {
+        match format {
+            ExportFormat::Csv => Ok(self.to_csv()),
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(self).context("Failed to serialize routing state")
+            }
+            ExportFormat::Html => Ok(self.to_html()),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("kind,name,input,output,note,tags\n");
+        for route in &self.routes {
+            csv.push_str(&format!("route,,{}\n", csv_route(route)));
+        }
+        for salvo in &self.salvos {
+            for route in &salvo.routes {
+                csv.push_str(&format!(
+                    "salvo,{},{}\n",
+                    csv_field(&salvo.name),
+                    csv_route(route)
+                ));
+            }
+        }
+        csv
+    }
+
+    fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>RusTV Routing Report</title>\n\
+             <style>body{font-family:sans-serif}table{border-collapse:collapse;margin-bottom:1em}\
+             td,th{border:1px solid #999;padding:4px 8px;text-align:left}</style>\n\
+             </head>\n<body>\n<h1>RusTV Routing Report</h1>\n",
+        );
+
+        html.push_str("<h2>Current Routes</h2>\n");
+        html.push_str(&routes_table(&self.routes));
+
+        for salvo in &self.salvos {
+            html.push_str(&format!("<h2>Salvo: {}</h2>\n", html_escape(&salvo.name)));
+            let notes = salvo.notes_summary();
+            if !notes.is_empty() {
+                html.push_str(&format!("<p>{}</p>\n", html_escape(&notes)));
+            }
+            html.push_str(&routes_table(&salvo.routes));
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// A difference between a salvo and the current routes
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteChange {
+    pub output: String,
+    /// Input assigned by the salvo
+    pub salvo_input: Option<String>,
+    /// Input currently routed
+    pub current_input: Option<String>,
+}
+
+/// Compare the current routes against a saved salvo
+pub fn diff_against_salvo(current: &[Route], salvo: &Salvo) -> Vec<RouteChange> {
+    let outputs: BTreeSet<&str> = current
+        .iter()
+        .map(|r| r.output.as_str())
+        .chain(salvo.routes.iter().map(|r| r.output.as_str()))
+        .collect();
+
+    outputs
+        .into_iter()
+        .filter_map(|output| {
+            let salvo_input = salvo.input_for(output).map(str::to_string);
+            let current_input = current
+                .iter()
+                .find(|r| r.output == output)
+                .map(|r| r.input.clone());
+
+            (salvo_input != current_input).then(|| RouteChange {
+                output: output.to_string(),
+                salvo_input,
+                current_input,
+            })
+        })
+        .collect()
+}
+
+fn routes_table(routes: &[Route]) -> String {
+    let mut table = String::from("<table>\n<tr><th>Output</th><th>Input</th><th>Notes</th></tr>\n");
+    for route in routes {
+        table.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            html_escape(&route.output),
+            html_escape(&route.input),
+            html_escape(&route.notes_summary())
+        ));
+    }
+    if routes.is_empty() {
+        table.push_str("<tr><td colspan=\"3\">No routes</td></tr>\n");
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+/// Input, output, note and tags (separated by `;`) of a route
+fn csv_route(route: &Route) -> String {
+    format!(
+        "{},{},{},{}",
+        csv_field(&route.input),
+        csv_field(&route.output),
+        csv_field(route.note.as_deref().unwrap_or_default()),
+        csv_field(&route.tags.join(";"))
+    )
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(input: &str, output: &str) -> Route {
+        Route::new(input.to_string(), output.to_string())
+    }
+
+    fn snapshot() -> RouterSnapshot {
+        RouterSnapshot::new(
+            vec![
+                route("Cam, Left", "Monitor 2"),
+                Route {
+                    note: Some("Backup, only if 2 dies".to_string()),
+                    tags: vec!["backup".to_string(), "wide".to_string()],
+                    ..route("Cam 1", "Monitor 1")
+                },
+            ],
+            vec![Salvo {
+                note: Some("Morning service".to_string()),
+                ..Salvo::new("Sunday <AM>".to_string(), vec![route("Cam 1", "Monitor 1")])
+            }],
+        )
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let csv = snapshot().export(ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "kind,name,input,output,note,tags");
+        assert_eq!(
+            lines[1],
+            "route,,Cam 1,Monitor 1,\"Backup, only if 2 dies\",backup;wide"
+        );
+        assert_eq!(lines[2], "route,,\"Cam, Left\",Monitor 2,,");
+        assert_eq!(lines[3], "salvo,Sunday <AM>,Cam 1,Monitor 1,,");
+    }
+
+    #[test]
+    fn test_json_and_html_export() {
+        let json = snapshot().export(ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["routes"].as_array().unwrap().len(), 2);
+
+        let html = snapshot().export(ExportFormat::Html).unwrap();
+        assert!(html.contains("Salvo: Sunday &lt;AM&gt;"));
+        assert!(html.contains("<p>Morning service</p>"));
+        assert!(html.contains("<td>Monitor 1</td>"));
+        assert!(html.contains("<td>#backup #wide Backup, only if 2 dies</td>"));
+    }
+
+    #[test]
+    fn test_diff_against_salvo() {
+        let salvo = Salvo::new(
+            "Setup".to_string(),
+            vec![route("Cam 1", "Monitor 1"), route("Cam 2", "Monitor 2")],
+        );
+        let current = vec![route("Cam 1", "Monitor 1"), route("Slides", "Monitor 3")];
+
+        let changes = diff_against_salvo(&current, &salvo);
+        assert_eq!(
+            changes,
+            vec![
+                RouteChange {
+                    output: "Monitor 2".to_string(),
+                    salvo_input: Some("Cam 2".to_string()),
+                    current_input: None,
+                },
+                RouteChange {
+                    output: "Monitor 3".to_string(),
+                    salvo_input: None,
+                    current_input: Some("Slides".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Backup inputs that take over an output when its source stops delivering
+
+use super::MatrixRouter;
+use crate::config::MatrixConfig;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Backup input of an output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupRoute {
+    pub output: String,
+    /// Input routed to the output when its source fails
+    pub backup: String,
+    /// Seconds a source must be failing before the backup takes over
+    #[serde(default = "default_failover_after")]
+    pub after_secs: u64,
+    /// Route the original source back once it delivers again
+    #[serde(default)]
+    pub switch_back: bool,
+}
+
+fn default_failover_after() -> u64 {
+    3
+}
+
+/// A route change made by failover
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailoverAction {
+    /// The output's source failed and the backup was routed in its place
+    SwitchedToBackup {
+        output: String,
+        primary: String,
+        backup: String,
+    },
+    /// The original source delivers again and was routed back
+    Restored { output: String, primary: String },
+}
+
+/// Watches outputs with backups and switches them when their source fails
+#[derive(Debug, Clone, Default)]
+pub struct Failover {
+    backups: Vec<BackupRoute>,
+    /// Input each output was routed to when it started failing, and since when
+    failing: HashMap<String, (String, Instant)>,
+    /// Outputs switched to their backup, with the input they replaced
+    failed_over: HashMap<String, String>,
+}
+
+impl Failover {
+    pub fn new(backups: Vec<BackupRoute>) -> Self {
+        Self {
+            backups,
+            ..Self::default()
+        }
+    }
+
+    pub fn from_config(config: &MatrixConfig) -> Self {
+        Self::new(config.backups.clone())
+    }
+
+    /// Whether any output has a backup
+    pub fn is_empty(&self) -> bool {
+        self.backups.is_empty()
+    }
+
+    /// Replaced inputs that are watched so they can be switched back
+    pub fn watched_inputs(&self) -> Vec<String> {
+        self.backups
+            .iter()
+            .filter(|b| b.switch_back)
+            .filter_map(|b| self.failed_over.get(&b.output).cloned())
+            .collect()
+    }
+
+    /// Check every output with a backup and switch routes as needed.
+    ///
+    /// `delivering` tells whether an input is delivering frames, or None when that is
+    /// not known (e.g. while connecting), which neither starts nor clears a failure.
+    pub fn update(
+        &mut self,
+        now: Instant,
+        router: &mut MatrixRouter,
+        delivering: impl Fn(&str) -> Option<bool>,
+    ) -> Vec<FailoverAction> {
+        let mut actions = Vec::new();
+        for backup in &self.backups {
+            let output = &backup.output;
+            let Some(current) = router.get_route(output).cloned() else {
+                self.failing.remove(output);
+                self.failed_over.remove(output);
+                continue;
+            };
+
+            if let Some(primary) = self.failed_over.get(output).cloned() {
+                if current != backup.backup {
+                    // Routed by hand since failing over; that route is the new primary
+                    self.failed_over.remove(output);
+                } else {
+                    if backup.switch_back && delivering(&primary) == Some(true) {
+                        match router.route_placeholder(&primary, output) {
+                            Ok(()) => {
+                                self.failed_over.remove(output);
+                                actions.push(FailoverAction::Restored {
+                                    output: output.clone(),
+                                    primary,
+                                });
+                            }
+                            Err(e) => warn!("Failed to restore {} -> {}: {}", primary, output, e),
+                        }
+                    }
+                    continue;
+                }
+            }
+
+            if current == backup.backup {
+                self.failing.remove(output);
+                continue;
+            }
+
+            match delivering(&current) {
+                Some(true) => {
+                    self.failing.remove(output);
+                }
+                Some(false) => {
+                    let since = match self.failing.get(output) {
+                        Some((input, since)) if *input == current => *since,
+                        _ => {
+                            self.failing.insert(output.clone(), (current.clone(), now));
+                            now
+                        }
+                    };
+                    if now.saturating_duration_since(since) < Duration::from_secs(backup.after_secs)
+                    {
+                        continue;
+                    }
+                    match router.route_placeholder(&backup.backup, output) {
+                        Ok(()) => {
+                            self.failing.remove(output);
+                            self.failed_over.insert(output.clone(), current.clone());
+                            actions.push(FailoverAction::SwitchedToBackup {
+                                output: output.clone(),
+                                primary: current,
+                                backup: backup.backup.clone(),
+                            });
+                        }
+                        Err(e) => warn!("Failed to fail over {}: {}", output, e),
+                    }
+                }
+                None => {}
+            }
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(switch_back: bool) -> (Failover, MatrixRouter) {
+        let mut router = MatrixRouter::new();
+        router.add_output("Program".to_string());
+        router.route_placeholder("CAM 1", "Program").unwrap();
+        let failover = Failover::new(vec![BackupRoute {
+            output: "Program".to_string(),
+            backup: "WIDE".to_string(),
+            after_secs: 3,
+            switch_back,
+        }]);
+        (failover, router)
+    }
+
+    #[test]
+    fn test_failover_and_switch_back() {
+        let (mut failover, mut router) = setup(true);
+        let start = Instant::now();
+        let cam1_up = |up: bool| move |input: &str| Some(input != "CAM 1" || up);
+
+        assert!(failover
+            .update(start, &mut router, cam1_up(false))
+            .is_empty());
+        // Unknown state neither clears nor restarts the failure
+        assert!(failover
+            .update(start + Duration::from_secs(2), &mut router, |_| None)
+            .is_empty());
+        let actions = failover.update(start + Duration::from_secs(3), &mut router, cam1_up(false));
+        assert_eq!(
+            actions,
+            vec![FailoverAction::SwitchedToBackup {
+                output: "Program".to_string(),
+                primary: "CAM 1".to_string(),
+                backup: "WIDE".to_string(),
+            }]
+        );
+        assert_eq!(router.get_route("Program"), Some(&"WIDE".to_string()));
+        assert_eq!(failover.watched_inputs(), vec!["CAM 1".to_string()]);
+
+        let actions = failover.update(start + Duration::from_secs(4), &mut router, cam1_up(true));
+        assert_eq!(
+            actions,
+            vec![FailoverAction::Restored {
+                output: "Program".to_string(),
+                primary: "CAM 1".to_string(),
+            }]
+        );
+        assert_eq!(router.get_route("Program"), Some(&"CAM 1".to_string()));
+        assert!(failover.watched_inputs().is_empty());
+    }
+
+    #[test]
+    fn test_recovery_before_timeout() {
+        let (mut failover, mut router) = setup(false);
+        let start = Instant::now();
+        failover.update(start, &mut router, |_| Some(false));
+        failover.update(start + Duration::from_secs(2), &mut router, |_| Some(true));
+        assert!(failover
+            .update(start + Duration::from_secs(4), &mut router, |_| Some(false))
+            .is_empty());
+        assert_eq!(router.get_route("Program"), Some(&"CAM 1".to_string()));
+    }
+
+    #[test]
+    fn test_stays_on_backup_without_switch_back() {
+        let (mut failover, mut router) = setup(false);
+        let start = Instant::now();
+        failover.update(start, &mut router, |_| Some(false));
+        failover.update(start + Duration::from_secs(3), &mut router, |_| Some(false));
+        assert!(failover
+            .update(start + Duration::from_secs(4), &mut router, |_| Some(true))
+            .is_empty());
+        assert_eq!(router.get_route("Program"), Some(&"WIDE".to_string()));
+
+        // A manual route becomes the new primary
+        router.route_placeholder("CAM 3", "Program").unwrap();
+        failover.update(start + Duration::from_secs(5), &mut router, |_| Some(false));
+        assert!(!failover
+            .update(start + Duration::from_secs(8), &mut router, |_| Some(false))
+            .is_empty());
+        assert_eq!(router.get_route("Program"), Some(&"WIDE".to_string()));
+    }
+}
@@ -0,0 +1,195 @@
+//! What depends on a source: outputs showing it, saved routes, salvos, backups,
+//! timecode triggers and settings that name it, so the effect of it dying or
+//! being renamed can be checked beforehand
+
+use super::wildcard::{self, WildcardRoute};
+use super::Route;
+use crate::companion::CompanionAction;
+use crate::config::Config;
+use crate::ndi::NdiSource;
+use std::fmt;
+
+/// What kind of thing refers to a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// Output the source is routed to now
+    Output,
+    /// Route in the configuration
+    SavedRoute,
+    Salvo,
+    /// Backup taking over an output
+    Backup,
+    /// Timecode trigger routing the source or putting it in maintenance
+    Trigger,
+    /// Other setting naming the source (maintenance substitute, standby slate...)
+    Setting,
+    Proxy,
+    Camera,
+    /// Source the self-test requires
+    SelfTest,
+    /// Source watched for remote tally
+    RemoteTally,
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Output => write!(f, "Output"),
+            Self::SavedRoute => write!(f, "Saved route"),
+            Self::Salvo => write!(f, "Salvo"),
+            Self::Backup => write!(f, "Backup"),
+            Self::Trigger => write!(f, "Timecode trigger"),
+            Self::Setting => write!(f, "Setting"),
+            Self::Proxy => write!(f, "Proxy"),
+            Self::Camera => write!(f, "Camera"),
+            Self::SelfTest => write!(f, "Self-test"),
+            Self::RemoteTally => write!(f, "Remote tally"),
+        }
+    }
+}
+
+/// One thing that refers to a source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    /// Which one ("Program", a salvo's name, a trigger's timecode...)
+    pub name: String,
+    /// How it names the source: NDI name, URL, alias or pattern
+    pub via: String,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} (as \"{}\")", self.kind, self.name, self.via)
+    }
+}
+
+/// Everything that refers to a source, by any of its names or a pattern
+/// matching it. `live` are the router's current routes
+pub fn references(config: &Config, live: &[Route], source: &NdiSource) -> Vec<Reference> {
+    collect(config, live, |text| {
+        source.matches(text)
+            || (wildcard::is_pattern(text) && WildcardRoute::new(text.to_string()).matches(source))
+    })
+}
+
+/// What names a source by an alias, and stops finding it if the alias is
+/// renamed or removed (routes made from the GUI or CLI keep the NDI name)
+pub fn alias_references(config: &Config, alias: &str) -> Vec<Reference> {
+    collect(config, &[], |text| text == alias)
+}
+
+fn collect(config: &Config, live: &[Route], names: impl Fn(&str) -> bool) -> Vec<Reference> {
+    let mut found = Vec::new();
+    let mut check = |kind: ReferenceKind, name: &str, via: &str| {
+        if names(via) {
+            found.push(Reference {
+                kind,
+                name: name.to_string(),
+                via: via.to_string(),
+            });
+        }
+    };
+
+    for route in live {
+        check(ReferenceKind::Output, &route.output, &route.input);
+    }
+    let matrix = &config.matrix;
+    for route in &matrix.routes {
+        check(ReferenceKind::SavedRoute, &route.output, &route.input);
+    }
+    for salvo in &matrix.salvos {
+        for route in &salvo.routes {
+            let name = format!("{} ({})", salvo.name, route.output);
+            check(ReferenceKind::Salvo, &name, &route.input);
+        }
+    }
+    for backup in &matrix.backups {
+        check(ReferenceKind::Backup, &backup.output, &backup.backup);
+    }
+    for trigger in &config.timecode.triggers {
+        match &trigger.action {
+            CompanionAction::Route { input, .. }
+            | CompanionAction::SetMaintenance { input, .. } => {
+                check(ReferenceKind::Trigger, &trigger.at.to_string(), input)
+            }
+            _ => {}
+        }
+    }
+    if let Some(substitute) = &matrix.maintenance_substitute {
+        check(ReferenceKind::Setting, "maintenance_substitute", substitute);
+    }
+    if let Some(slate) = &matrix.standby_slate {
+        check(ReferenceKind::Setting, "standby_slate", slate);
+    }
+    // Proxies of a matrix output follow its route
+    for proxy in config.ndi.proxies.iter().filter(|p| !p.output) {
+        let name = proxy
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{} Proxy", proxy.source));
+        check(ReferenceKind::Proxy, &name, &proxy.source);
+    }
+    for camera in &config.birddog.cameras {
+        check(ReferenceKind::Camera, &camera.name, &camera.ndi_name);
+    }
+    for required in &config.selftest.required_sources {
+        check(ReferenceKind::SelfTest, "required_sources", required);
+    }
+    for watched in &config.ndi.remote_tally {
+        check(ReferenceKind::RemoteTally, "remote_tally", watched);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::{BackupRoute, Salvo};
+
+    #[test]
+    fn test_references() {
+        let mut config = Config::default();
+        config.matrix.salvos = vec![Salvo::new(
+            "Sunday".into(),
+            vec![
+                Route::new("OBS".into(), "Program".into()),
+                Route::new("CAM1".into(), "Preview".into()),
+            ],
+        )];
+        config.matrix.backups = vec![BackupRoute {
+            output: "Program".into(),
+            backup: "*Slides*".into(),
+            after_secs: 5,
+            switch_back: false,
+        }];
+        config.selftest.required_sources = vec!["STUDIO (OBS)".into()];
+
+        let mut source = NdiSource::new("STUDIO (OBS)".into(), "ndi://10.0.0.5:5961".into());
+        source.alias = Some("OBS".into());
+        let live = [Route::new("STUDIO (OBS)".into(), "Mon 1".into())];
+        let found = references(&config, &live, &source);
+        let kinds: Vec<ReferenceKind> = found.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ReferenceKind::Output,
+                ReferenceKind::Salvo,
+                ReferenceKind::SelfTest
+            ]
+        );
+        assert_eq!(found[1].to_string(), "Salvo Sunday (Program) (as \"OBS\")");
+
+        // A pattern matching the source counts too
+        let slides = NdiSource::new("MAC (Slides)".into(), String::new());
+        assert_eq!(
+            references(&config, &[], &slides)[0].kind,
+            ReferenceKind::Backup
+        );
+
+        // Renaming the alias breaks the salvo, not the self-test using the NDI name
+        let broken = alias_references(&config, "OBS");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].kind, ReferenceKind::Salvo);
+    }
+}
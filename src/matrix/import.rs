@@ -0,0 +1,288 @@
+//! Import of planned routes from spreadsheet (CSV) files
+
+use super::Route;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+/// How an imported input is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    /// NDI source discovered on the network (default)
+    Ndi,
+    /// Static source from the `[ndi]` config section
+    Static,
+    /// Image from the graphics watch folder
+    File,
+}
+
+impl InputKind {
+    fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "ndi" => Ok(Self::Ndi),
+            "static" => Ok(Self::Static),
+            "file" => Ok(Self::File),
+            other => bail!("Unknown kind '{}' (expected ndi, static or file)", other),
+        }
+    }
+}
+
+impl fmt::Display for InputKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ndi => write!(f, "ndi"),
+            Self::Static => write!(f, "static"),
+            Self::File => write!(f, "file"),
+        }
+    }
+}
+
+/// Names the import is validated against
+#[derive(Debug, Clone, Default)]
+pub struct ImportContext {
+    pub outputs: Vec<String>,
+    pub static_sources: Vec<String>,
+    pub file_inputs: Vec<String>,
+}
+
+/// A validated row from the import file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRoute {
+    pub line: usize,
+    pub route: Route,
+    pub kind: InputKind,
+}
+
+/// A problem found on a row of the import file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Result of parsing and validating an import file
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub routes: Vec<ImportedRoute>,
+    pub errors: Vec<ImportError>,
+}
+
+impl ImportPlan {
+    /// Parse `input,output(,kind)` rows, validating each against the context
+    pub fn parse(content: &str, context: &ImportContext) -> Self {
+        let mut plan = Self::default();
+        let mut seen_outputs: HashMap<String, usize> = HashMap::new();
+        // Spreadsheet exports often start with a byte order mark
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let fields = match parse_csv_line(line) {
+                Ok(fields) => fields,
+                Err(e) => {
+                    plan.error(line_number, e.to_string());
+                    continue;
+                }
+            };
+
+            if index == 0 && is_header(&fields) {
+                continue;
+            }
+
+            if !(2..=3).contains(&fields.len()) {
+                plan.error(
+                    line_number,
+                    format!(
+                        "Expected input,output(,kind) but found {} columns",
+                        fields.len()
+                    ),
+                );
+                continue;
+            }
+
+            let input = fields[0].trim();
+            let output = fields[1].trim();
+            if input.is_empty() || output.is_empty() {
+                plan.error(
+                    line_number,
+                    "Input and output must not be empty".to_string(),
+                );
+                continue;
+            }
+
+            let kind = match InputKind::parse(fields.get(2).map(String::as_str).unwrap_or("")) {
+                Ok(kind) => kind,
+                Err(e) => {
+                    plan.error(line_number, e.to_string());
+                    continue;
+                }
+            };
+
+            if !context.outputs.iter().any(|o| o == output) {
+                plan.error(line_number, format!("Unknown output '{}'", output));
+                continue;
+            }
+
+            let known_input = match kind {
+                InputKind::Ndi => true,
+                InputKind::Static => context.static_sources.iter().any(|s| s == input),
+                InputKind::File => context.file_inputs.iter().any(|s| s == input),
+            };
+            if !known_input {
+                plan.error(line_number, format!("Unknown {} input '{}'", kind, input));
+                continue;
+            }
+
+            if let Some(first) = seen_outputs.insert(output.to_string(), line_number) {
+                plan.error(
+                    line_number,
+                    format!("Output '{}' already routed on line {}", output, first),
+                );
+                continue;
+            }
+
+            plan.routes.push(ImportedRoute {
+                line: line_number,
+                route: Route::new(input.to_string(), output.to_string()),
+                kind,
+            });
+        }
+
+        plan
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Merge the imported routes into existing routes, replacing routes on the same outputs
+    pub fn apply_to(&self, routes: &mut Vec<Route>) {
+        for imported in &self.routes {
+            routes.retain(|r| r.output != imported.route.output);
+            routes.push(imported.route.clone());
+        }
+    }
+
+    fn error(&mut self, line: usize, message: String) {
+        self.errors.push(ImportError { line, message });
+    }
+}
+
+fn is_header(fields: &[String]) -> bool {
+    fields.len() >= 2
+        && fields[0].trim().eq_ignore_ascii_case("input")
+        && fields[1].trim().eq_ignore_ascii_case("output")
+}
+
+/// Split a CSV line into fields, honouring double-quoted fields
+fn parse_csv_line(line: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        bail!("Unterminated quoted field");
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> ImportContext {
+        ImportContext {
+            outputs: vec![
+                "Monitor 1".to_string(),
+                "Monitor 2".to_string(),
+                "Monitor 3".to_string(),
+            ],
+            static_sources: vec!["Studio Feed".to_string()],
+            file_inputs: vec!["lower-third".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_parse_valid_rows() {
+        let csv = "input,output,kind\n\
+                   \"Cam, Left\",Monitor 1\n\
+                   Studio Feed,Monitor 2,static\n\
+                   lower-third,Monitor 3,file\n";
+        let plan = ImportPlan::parse(csv, &context());
+
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert_eq!(plan.routes.len(), 3);
+        assert_eq!(plan.routes[0].route.input, "Cam, Left");
+        assert_eq!(plan.routes[0].kind, InputKind::Ndi);
+        assert_eq!(plan.routes[2].kind, InputKind::File);
+        assert_eq!(plan.routes[2].line, 4);
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        let plan = ImportPlan::parse("\u{feff}Input,Output\nCam 1,Monitor 1\n", &context());
+
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert_eq!(plan.routes.len(), 1);
+        assert_eq!(plan.routes[0].route.input, "Cam 1");
+
+        let plan = ImportPlan::parse("\u{feff}Cam 1,Monitor 1\n", &context());
+        assert_eq!(plan.routes[0].route.input, "Cam 1");
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let csv = "Cam 1,Monitor 9\n\
+                   Cam 1,Monitor 1,rtmp\n\
+                   Missing Feed,Monitor 1,static\n\
+                   Cam 1,Monitor 1\n\
+                   Cam 2,Monitor 1\n\
+                   ,Monitor 2\n\
+                   \"Cam 3,Monitor 3\n";
+        let plan = ImportPlan::parse(csv, &context());
+
+        let lines: Vec<usize> = plan.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 2, 3, 5, 6, 7]);
+        assert_eq!(plan.routes.len(), 1);
+        assert!(plan.errors[3].message.contains("line 4"));
+    }
+
+    #[test]
+    fn test_apply_replaces_existing_outputs() {
+        let plan = ImportPlan::parse("Cam 2,Monitor 1\n", &context());
+        let mut routes = vec![
+            Route::new("Cam 1".to_string(), "Monitor 1".to_string()),
+            Route::new("Cam 3".to_string(), "Monitor 2".to_string()),
+        ];
+        plan.apply_to(&mut routes);
+
+        assert_eq!(routes.len(), 2);
+        assert!(routes
+            .iter()
+            .any(|r| r.input == "Cam 2" && r.output == "Monitor 1"));
+    }
+}
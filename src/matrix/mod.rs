@@ -1,3 +1,17 @@
+pub mod export;
+pub mod failover;
+pub mod impact;
+pub mod import;
+pub mod preflight;
+pub mod rename;
 pub mod router;
+pub mod salvo;
+pub mod tally;
+pub mod wildcard;
 
-pub use router::{MatrixRouter, Route};
+pub use failover::{BackupRoute, Failover, FailoverAction};
+pub use preflight::{RecallPolicy, SalvoPreflight};
+pub use router::{MatrixRouter, OutputMirror, Route};
+pub use salvo::Salvo;
+pub use tally::{TallyOutputs, TallyState};
+pub use wildcard::RebindPolicy;
@@ -0,0 +1,265 @@
+//! Checks a salvo against the sources and outputs that exist before it is applied,
+//! so a stale salvo can't blank outputs whose inputs have gone away
+
+use super::{BackupRoute, Route, Salvo};
+use crate::ndi::NdiSource;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What to do with a salvo whose preflight found problems
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecallPolicy {
+    /// Apply nothing
+    Abort,
+    /// Apply the routes that are ready and leave the other outputs as they are
+    #[default]
+    Partial,
+    /// Like partial, but route an output's available backup instead of a missing input
+    SubstituteBackups,
+    /// Apply every route, leaving missing inputs as placeholders
+    Force,
+}
+
+impl RecallPolicy {
+    pub fn all() -> [RecallPolicy; 4] {
+        [
+            RecallPolicy::Abort,
+            RecallPolicy::Partial,
+            RecallPolicy::SubstituteBackups,
+            RecallPolicy::Force,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecallPolicy::Abort => "Abort",
+            RecallPolicy::Partial => "Apply partially",
+            RecallPolicy::SubstituteBackups => "Substitute backups",
+            RecallPolicy::Force => "Apply anyway",
+        }
+    }
+}
+
+/// Why a route of a salvo can't be applied as saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteProblem {
+    /// The input is not on the network
+    MissingInput,
+    /// The input is quarantined until an admin approves it
+    AwaitingApproval,
+    /// The output is not defined
+    UnknownOutput,
+}
+
+impl fmt::Display for RouteProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteProblem::MissingInput => write!(f, "input missing"),
+            RouteProblem::AwaitingApproval => write!(f, "input awaiting approval"),
+            RouteProblem::UnknownOutput => write!(f, "unknown output"),
+        }
+    }
+}
+
+/// A route of a salvo that can't be applied as saved
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteIssue {
+    pub route: Route,
+    pub problem: RouteProblem,
+    /// Available backup input of the output (`[[matrix.backups]]`)
+    pub backup: Option<String>,
+}
+
+/// Result of checking a salvo before it is applied
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SalvoPreflight {
+    pub salvo: String,
+    /// Routes that can be applied as saved
+    pub ready: Vec<Route>,
+    pub issues: Vec<RouteIssue>,
+}
+
+impl SalvoPreflight {
+    /// Check every route of a salvo against the available sources and defined outputs
+    pub fn check(
+        salvo: &Salvo,
+        sources: &[NdiSource],
+        outputs: &[String],
+        backups: &[BackupRoute],
+    ) -> Self {
+        let available = |input: &str| {
+            sources
+                .iter()
+                .find(|s| s.matches(input))
+                .map(|s| !s.quarantined)
+        };
+
+        let mut ready = Vec::new();
+        let mut issues = Vec::new();
+        for route in &salvo.routes {
+            let problem = if !outputs.contains(&route.output) {
+                Some(RouteProblem::UnknownOutput)
+            } else {
+                match available(&route.input) {
+                    None => Some(RouteProblem::MissingInput),
+                    Some(false) => Some(RouteProblem::AwaitingApproval),
+                    Some(true) => None,
+                }
+            };
+            match problem {
+                None => ready.push(route.clone()),
+                Some(problem) => {
+                    let backup = backups
+                        .iter()
+                        .find(|b| b.output == route.output)
+                        .filter(|b| {
+                            problem != RouteProblem::UnknownOutput
+                                && available(&b.backup) == Some(true)
+                        })
+                        .map(|b| b.backup.clone());
+                    issues.push(RouteIssue {
+                        route: route.clone(),
+                        problem,
+                        backup,
+                    });
+                }
+            }
+        }
+
+        Self {
+            salvo: salvo.name.clone(),
+            ready,
+            issues,
+        }
+    }
+
+    /// Whether every route can be applied as saved
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Whether any missing input has a backup to stand in
+    pub fn has_backups(&self) -> bool {
+        self.issues.iter().any(|i| i.backup.is_some())
+    }
+
+    /// Routes to apply under a policy (None if the recall is aborted)
+    pub fn plan(&self, policy: RecallPolicy) -> Option<Vec<Route>> {
+        if self.is_clean() {
+            return Some(self.ready.clone());
+        }
+        let mut routes = self.ready.clone();
+        match policy {
+            RecallPolicy::Abort => return None,
+            RecallPolicy::Partial => {}
+            RecallPolicy::SubstituteBackups => {
+                routes.extend(self.issues.iter().filter_map(|issue| {
+                    let backup = issue.backup.clone()?;
+                    Some(Route::new(backup, issue.route.output.clone()))
+                }));
+            }
+            // Unknown outputs can't be routed at all
+            RecallPolicy::Force => routes.extend(
+                self.issues
+                    .iter()
+                    .filter(|i| i.problem != RouteProblem::UnknownOutput)
+                    .map(|i| i.route.clone()),
+            ),
+        }
+        Some(routes)
+    }
+}
+
+impl fmt::Display for SalvoPreflight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Salvo '{}': {} of {} routes ready",
+            self.salvo,
+            self.ready.len(),
+            self.ready.len() + self.issues.len()
+        )?;
+        for issue in &self.issues {
+            write!(
+                f,
+                "\n  {} <- {}: {}",
+                issue.route.output, issue.route.input, issue.problem
+            )?;
+            if let Some(backup) = &issue.backup {
+                write!(f, " (backup {} available)", backup)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str) -> NdiSource {
+        NdiSource::new(name.to_string(), format!("ndi://{}", name))
+    }
+
+    #[test]
+    fn test_preflight() {
+        let salvo = Salvo::new(
+            "Sunday".to_string(),
+            vec![
+                Route::new("CAM 1".to_string(), "Program".to_string()),
+                Route::new("CAM 2".to_string(), "Stage".to_string()),
+                Route::new("LAPTOP".to_string(), "Lobby".to_string()),
+                Route::new("CAM 1".to_string(), "Old Monitor".to_string()),
+            ],
+        );
+        let mut laptop = source("LAPTOP");
+        laptop.quarantined = true;
+        let sources = vec![source("CAM 1"), source("CAM 3"), laptop];
+        let outputs = vec![
+            "Program".to_string(),
+            "Stage".to_string(),
+            "Lobby".to_string(),
+        ];
+        let backups = vec![BackupRoute {
+            output: "Stage".to_string(),
+            backup: "CAM 3".to_string(),
+            after_secs: 3,
+            switch_back: false,
+        }];
+
+        let preflight = SalvoPreflight::check(&salvo, &sources, &outputs, &backups);
+        assert!(!preflight.is_clean());
+        assert!(preflight.has_backups());
+        assert_eq!(preflight.ready.len(), 1);
+        let problems: Vec<RouteProblem> = preflight.issues.iter().map(|i| i.problem).collect();
+        assert_eq!(
+            problems,
+            vec![
+                RouteProblem::MissingInput,
+                RouteProblem::AwaitingApproval,
+                RouteProblem::UnknownOutput,
+            ]
+        );
+
+        let inputs_of = |policy| -> Option<Vec<String>> {
+            preflight
+                .plan(policy)
+                .map(|routes| routes.into_iter().map(|r| r.input).collect())
+        };
+        assert_eq!(inputs_of(RecallPolicy::Abort), None);
+        assert_eq!(inputs_of(RecallPolicy::Partial).unwrap(), vec!["CAM 1"]);
+        assert_eq!(
+            inputs_of(RecallPolicy::SubstituteBackups).unwrap(),
+            vec!["CAM 1", "CAM 3"]
+        );
+        assert_eq!(
+            inputs_of(RecallPolicy::Force).unwrap(),
+            vec!["CAM 1", "CAM 2", "LAPTOP"]
+        );
+        assert!(preflight
+            .to_string()
+            .contains("Stage <- CAM 2: input missing (backup CAM 3 available)"));
+    }
+}
@@ -0,0 +1,384 @@
+//! Bulk rename: when a source comes back under a new name on the same machine
+//! (e.g. an OBS output renamed), everything naming the old one moves to the
+//! new name in one go
+
+use super::impact::ReferenceKind;
+use super::{Route, Salvo};
+use crate::companion::CompanionAction;
+use crate::config::Config;
+use crate::ndi::NdiSource;
+use crate::storage::Storage;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long after a source disappears a new one on its machine is taken for it
+pub const RENAME_WINDOW: Duration = Duration::from_secs(600);
+
+/// One entry a rename changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameChange {
+    pub kind: ReferenceKind,
+    /// Which entry ("Program", a salvo's name, "ndi.aliases"...)
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl fmt::Display for RenameChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} → {}",
+            self.kind, self.name, self.before, self.after
+        )
+    }
+}
+
+/// Move every entry naming a source by its NDI name `from` to `to`, returning
+/// what changed. Entries using its alias or a pattern are left as they are:
+/// the alias moves with the source and patterns are matched afresh
+pub fn rename(config: &mut Config, from: &str, to: &str) -> Vec<RenameChange> {
+    let mut changes = Vec::new();
+    let mut entry = |kind: ReferenceKind, name: &str, value: &mut String| {
+        if value == from {
+            changes.push(RenameChange {
+                kind,
+                name: name.to_string(),
+                before: from.to_string(),
+                after: to.to_string(),
+            });
+            *value = to.to_string();
+        }
+    };
+
+    let matrix = &mut config.matrix;
+    for route in &mut matrix.routes {
+        entry(ReferenceKind::SavedRoute, &route.output, &mut route.input);
+    }
+    for salvo in &mut matrix.salvos {
+        for route in &mut salvo.routes {
+            let name = format!("{} ({})", salvo.name, route.output);
+            entry(ReferenceKind::Salvo, &name, &mut route.input);
+        }
+    }
+    for backup in &mut matrix.backups {
+        entry(ReferenceKind::Backup, &backup.output, &mut backup.backup);
+    }
+    for trigger in &mut config.timecode.triggers {
+        let at = trigger.at.to_string();
+        match &mut trigger.action {
+            CompanionAction::Route { input, .. }
+            | CompanionAction::SetMaintenance { input, .. } => {
+                entry(ReferenceKind::Trigger, &at, input)
+            }
+            _ => {}
+        }
+    }
+    if let Some(substitute) = &mut matrix.maintenance_substitute {
+        entry(ReferenceKind::Setting, "maintenance_substitute", substitute);
+    }
+    if let Some(slate) = &mut matrix.standby_slate {
+        entry(ReferenceKind::Setting, "standby_slate", slate);
+    }
+    // Proxies of a matrix output follow its route
+    for proxy in config.ndi.proxies.iter_mut().filter(|p| !p.output) {
+        let name = proxy
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{} Proxy", proxy.source));
+        entry(ReferenceKind::Proxy, &name, &mut proxy.source);
+    }
+    for camera in &mut config.birddog.cameras {
+        entry(ReferenceKind::Camera, &camera.name, &mut camera.ndi_name);
+    }
+    for required in &mut config.selftest.required_sources {
+        entry(ReferenceKind::SelfTest, "required_sources", required);
+    }
+    for watched in &mut config.ndi.remote_tally {
+        entry(ReferenceKind::RemoteTally, "remote_tally", watched);
+    }
+
+    // Settings kept by NDI name
+    let ndi = &mut config.ndi;
+    let mut moved = |section: &str, done: bool| {
+        if done {
+            changes.push(RenameChange {
+                kind: ReferenceKind::Setting,
+                name: section.to_string(),
+                before: from.to_string(),
+                after: to.to_string(),
+            });
+        }
+    };
+    moved("ndi.aliases", rekey(&mut ndi.aliases, from, to));
+    moved("ndi.tags", rekey(&mut ndi.tags, from, to));
+    moved("ndi.channel_maps", rekey(&mut ndi.channel_maps, from, to));
+    moved("ndi.audio_trims", rekey(&mut ndi.audio_trims, from, to));
+    moved(
+        "ndi.source_transports",
+        rekey(&mut ndi.source_transports, from, to),
+    );
+    let approved = &mut ndi.approval.approved;
+    moved(
+        "ndi.approval",
+        !approved.contains(to) && approved.remove(from) && approved.insert(to.to_string()),
+    );
+    changes
+}
+
+/// A rename worked out over the config file and what storage keeps, not yet saved
+pub struct RenamePlan {
+    /// The config file with the rename applied
+    pub config: Config,
+    /// Every entry that changes
+    pub changes: Vec<RenameChange>,
+    /// Saved routes, when any of them change
+    routes: Option<Vec<Route>>,
+    /// Stored salvos that change
+    salvos: Vec<Salvo>,
+}
+
+impl RenamePlan {
+    /// Rename `from` to `to` in the config file's contents, the routes saved in
+    /// `saved_routes` and the salvos held in `storage`
+    pub fn new(
+        mut config: Config,
+        saved_routes: Option<&dyn Storage>,
+        storage: Option<&dyn Storage>,
+        from: &str,
+        to: &str,
+    ) -> Result<Self> {
+        let mut changes = rename(&mut config, from, to);
+        let mut stored = |kind: ReferenceKind, name: String, input: &mut String| {
+            if input != from {
+                return false;
+            }
+            changes.push(RenameChange {
+                kind,
+                name,
+                before: from.to_string(),
+                after: to.to_string(),
+            });
+            *input = to.to_string();
+            true
+        };
+
+        let mut routes = match saved_routes {
+            Some(saved_routes) => saved_routes.load_routes()?.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let mut routes_changed = false;
+        for route in &mut routes {
+            routes_changed |= stored(
+                ReferenceKind::Output,
+                route.output.clone(),
+                &mut route.input,
+            );
+        }
+        let mut salvos = match storage {
+            Some(storage) => storage.load_salvos()?,
+            None => Vec::new(),
+        };
+        salvos.retain_mut(|salvo| {
+            let mut changed = false;
+            for route in &mut salvo.routes {
+                let name = format!("{} ({})", salvo.name, route.output);
+                changed |= stored(ReferenceKind::Salvo, name, &mut route.input);
+            }
+            changed
+        });
+
+        Ok(Self {
+            config,
+            changes,
+            routes: routes_changed.then_some(routes),
+            salvos,
+        })
+    }
+
+    /// Write the config file, saved routes and salvos the rename changes
+    pub fn save(
+        &self,
+        config_path: &Path,
+        saved_routes: Option<&dyn Storage>,
+        storage: Option<&dyn Storage>,
+    ) -> Result<()> {
+        self.config.to_file(config_path)?;
+        if let (Some(saved_routes), Some(routes)) = (saved_routes, &self.routes) {
+            saved_routes.save_routes(routes)?;
+        }
+        if let Some(storage) = storage {
+            for salvo in &self.salvos {
+                storage.save_salvo(salvo)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Move a map entry to a new key, unless the new key has one already
+fn rekey<V>(map: &mut BTreeMap<String, V>, from: &str, to: &str) -> bool {
+    if map.contains_key(to) {
+        return false;
+    }
+    match map.remove(from) {
+        Some(value) => {
+            map.insert(to.to_string(), value);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Notices a source disappearing and another on the same machine appearing
+#[derive(Default)]
+pub struct RenameDetector {
+    /// Sources gone within the window, oldest first
+    removed: Vec<(Instant, NdiSource)>,
+}
+
+impl RenameDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn removed(&mut self, source: NdiSource, now: Instant) {
+        self.removed.push((now, source));
+    }
+
+    /// NDI name of a recently removed source that `added` looks like the renamed
+    /// copy of: the one on the same machine whose stream name starts the most alike
+    pub fn added(&mut self, added: &NdiSource, now: Instant) -> Option<String> {
+        self.removed
+            .retain(|(at, _)| now.saturating_duration_since(*at) <= RENAME_WINDOW);
+        // The same source coming back
+        if let Some(index) = self.removed.iter().position(|(_, s)| s.name == added.name) {
+            self.removed.remove(index);
+            return None;
+        }
+        let alike = |source: &NdiSource| common_prefix(source.stream_name(), added.stream_name());
+        let index = self
+            .removed
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, source))| {
+                source
+                    .machine_name()
+                    .eq_ignore_ascii_case(added.machine_name())
+            })
+            .max_by_key(|(_, (_, source))| alike(source))
+            .map(|(index, _)| index)?;
+        Some(self.removed.remove(index).1.name)
+    }
+}
+
+/// Number of leading characters two names share, ignoring case
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.chars()
+        .zip(b.chars())
+        .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::{Route, Salvo};
+
+    #[test]
+    fn test_rename() {
+        let mut config = Config::default();
+        let old = "STUDIO (OBS Output 3)";
+        config.matrix.routes = vec![Route::new(old.into(), "Program".into())];
+        config.matrix.salvos = vec![Salvo::new(
+            "Sunday".into(),
+            vec![
+                Route::new(old.into(), "Program".into()),
+                Route::new("Slides".into(), "Preview".into()),
+            ],
+        )];
+        config.ndi.aliases.insert(old.into(), "Slides".into());
+        config.ndi.approval.approved.insert(old.into());
+
+        let new = "STUDIO (OBS Main)";
+        let changes = rename(&mut config, old, new);
+        let names: Vec<&str> = changes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Program", "Sunday (Program)", "ndi.aliases", "ndi.approval"]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "Saved route Program: STUDIO (OBS Output 3) → STUDIO (OBS Main)"
+        );
+        assert_eq!(config.matrix.salvos[0].routes[0].input, new);
+        // The alias moved with the source, so routes using it still resolve
+        assert_eq!(config.matrix.salvos[0].routes[1].input, "Slides");
+        assert_eq!(config.ndi.aliases[new], "Slides");
+        assert!(config.ndi.approval.approved.contains(new));
+        assert!(rename(&mut config, old, new).is_empty());
+    }
+
+    #[test]
+    fn test_rename_plan_covers_storage() {
+        use crate::storage::FileStorage;
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FileStorage::open(dir.path()).unwrap();
+        let old = "STUDIO (OBS Output 3)";
+        storage
+            .save_routes(&[Route::new(old.into(), "Program".into())])
+            .unwrap();
+        storage
+            .save_salvo(&Salvo::new(
+                "Sunday".into(),
+                vec![Route::new(old.into(), "Preview".into())],
+            ))
+            .unwrap();
+        storage
+            .save_salvo(&Salvo::new(
+                "Weekday".into(),
+                vec![Route::new("Slides".into(), "Preview".into())],
+            ))
+            .unwrap();
+
+        let new = "STUDIO (OBS Main)";
+        let plan =
+            RenamePlan::new(Config::default(), Some(&storage), Some(&storage), old, new).unwrap();
+        let names: Vec<&str> = plan.changes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Program", "Sunday (Preview)"]);
+
+        let config_path = dir.path().join("rustv.toml");
+        plan.save(&config_path, Some(&storage), Some(&storage))
+            .unwrap();
+        assert_eq!(storage.load_routes().unwrap().unwrap()[0].input, new);
+        let salvos = storage.load_salvos().unwrap();
+        let sunday = salvos.iter().find(|s| s.name == "Sunday").unwrap();
+        assert_eq!(sunday.routes[0].input, new);
+        assert!(Config::from_file(&config_path).is_ok());
+    }
+
+    #[test]
+    fn test_rename_detector() {
+        let start = Instant::now();
+        let source = |name: &str| NdiSource::new(name.into(), String::new());
+        let mut detector = RenameDetector::new();
+        detector.removed(source("STUDIO (OBS Output 3)"), start);
+        detector.removed(source("STUDIO (Camera)"), start);
+
+        // Another machine, or the same source coming back, is no rename
+        assert_eq!(detector.added(&source("LAPTOP (OBS Main)"), start), None);
+        assert_eq!(detector.added(&source("STUDIO (Camera)"), start), None);
+        assert_eq!(
+            detector.added(&source("studio (OBS Main)"), start),
+            Some("STUDIO (OBS Output 3)".into())
+        );
+
+        detector.removed(source("STUDIO (Graphics)"), start);
+        let later = start + RENAME_WINDOW + Duration::from_secs(1);
+        assert_eq!(detector.added(&source("STUDIO (Graphics 2)"), later), None);
+    }
+}
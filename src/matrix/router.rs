@@ -1,27 +1,84 @@
+use super::wildcard::{self, RebindPolicy, WildcardRoute};
 use crate::ndi::NdiSource;
 use anyhow::{Context, Result};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Represents a routing from an input to an output
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Route {
     pub input: String,
     pub output: String,
+    /// Freeform note (e.g. "camera 3 is backup, use only if 1 dies")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl Route {
     pub fn new(input: String, output: String) -> Self {
-        Self { input, output }
+        Self {
+            input,
+            output,
+            note: None,
+            tags: Vec::new(),
+        }
     }
+
+    /// Whether a note or tags are attached
+    pub fn has_notes(&self) -> bool {
+        self.note.is_some() || !self.tags.is_empty()
+    }
+
+    /// Tags and note on one line (empty if there are none)
+    pub fn notes_summary(&self) -> String {
+        notes_summary(self.note.as_deref(), &self.tags)
+    }
+}
+
+/// An output that always shows whatever is routed to another (e.g. a green room
+/// TV mirroring Program)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputMirror {
+    /// Primary output
+    pub output: String,
+    /// Output kept routed like the primary
+    pub mirror: String,
+}
+
+/// Tags and a note on one line, e.g. "#backup Use only if Cam 1 dies"
+pub fn notes_summary(note: Option<&str>, tags: &[String]) -> String {
+    let tags = tags.iter().map(|t| format!("#{}", t));
+    tags.chain(note.map(str::to_string))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Matrix router for managing input/output routing
+#[derive(Clone)]
 pub struct MatrixRouter {
     routes: HashMap<String, String>,
     inputs: Vec<NdiSource>,
     outputs: Vec<String>,
+    /// Inputs under maintenance (outputs show a substitute instead)
+    maintenance: HashSet<String>,
+    /// Routes carrying notes, shown whenever the same input is routed to the output
+    notes: Vec<Route>,
+    /// Outputs routed to a pattern rather than a source, by output
+    wildcards: HashMap<String, WildcardRoute>,
+    /// Outputs that follow the route of another output
+    mirrors: Vec<OutputMirror>,
+    /// Routing table put aside while every output is blanked
+    standby: Option<Standby>,
+}
+
+/// Routes and wildcards saved by `blank_all`, restored by `resume`
+#[derive(Clone)]
+struct Standby {
+    routes: HashMap<String, String>,
+    wildcards: HashMap<String, WildcardRoute>,
 }
 
 impl MatrixRouter {
@@ -30,15 +87,22 @@ impl MatrixRouter {
             routes: HashMap::new(),
             inputs: Vec::new(),
             outputs: Vec::new(),
+            maintenance: HashSet::new(),
+            notes: Vec::new(),
+            wildcards: HashMap::new(),
+            mirrors: Vec::new(),
+            standby: None,
         }
     }
 
-    /// Add an input source
-    #[allow(dead_code)]
+    /// Add an input source, or refresh what is known about it
     pub fn add_input(&mut self, source: NdiSource) {
-        if !self.inputs.iter().any(|s| s.url == source.url) {
-            info!("Added input: {}", source.name);
-            self.inputs.push(source);
+        match self.inputs.iter_mut().find(|s| s.url == source.url) {
+            Some(existing) => *existing = source,
+            None => {
+                info!("Added input: {}", source.name);
+                self.inputs.push(source);
+            }
         }
     }
 
@@ -50,16 +114,38 @@ impl MatrixRouter {
         }
     }
 
+    /// Replace the output destinations, removing the routes of outputs that go away
+    pub fn set_outputs(&mut self, outputs: Vec<String>) {
+        for output in self.outputs.clone() {
+            if !outputs.contains(&output) {
+                self.routes.remove(&output);
+                self.wildcards.remove(&output);
+                info!("Removed output: {}", output);
+            }
+        }
+        for output in &outputs {
+            if !self.outputs.contains(output) {
+                info!("Added output: {}", output);
+            }
+        }
+        self.outputs = outputs;
+    }
+
     /// Create a route from input to output
     pub fn route(&mut self, input: &str, output: &str) -> Result<()> {
         // Validate input exists
-        if !self
-            .inputs
-            .iter()
-            .any(|s| s.url == input || s.name == input)
-        {
+        let Some(source) = self.inputs.iter().find(|s| s.matches(input)) else {
             anyhow::bail!("Input '{}' not found", input);
+        };
+        if source.quarantined {
+            anyhow::bail!("Input '{}' is awaiting approval", input);
         }
+        // Routes keep the NDI name, so they survive an alias being renamed
+        let input = if source.alias.as_deref() == Some(input) {
+            source.name.clone()
+        } else {
+            input.to_string()
+        };
 
         // Validate output exists
         if !self.outputs.contains(&output.to_string()) {
@@ -67,12 +153,15 @@ impl MatrixRouter {
         }
 
         info!("Routing {} -> {}", input, output);
-        self.routes.insert(output.to_string(), input.to_string());
+        self.routes.insert(output.to_string(), input);
+        self.wildcards.remove(output);
+        self.update_mirrors(output);
         Ok(())
     }
 
     /// Create a placeholder route to an input that may not exist yet
-    /// This allows creating routes to NDI sources before they are discovered
+    /// This allows creating routes to NDI sources before they are discovered.
+    /// An input with `*` or `?` is a pattern, bound by `resolve_wildcards`
     pub fn route_placeholder(&mut self, input: &str, output: &str) -> Result<()> {
         // Validate output exists
         if !self.outputs.contains(&output.to_string()) {
@@ -81,20 +170,117 @@ impl MatrixRouter {
 
         info!("Creating placeholder route: {} -> {}", input, output);
         self.routes.insert(output.to_string(), input.to_string());
+        if wildcard::is_pattern(input) {
+            let wildcard = WildcardRoute::new(input.to_string());
+            self.wildcards.insert(output.to_string(), wildcard);
+        } else {
+            self.wildcards.remove(output);
+        }
+        self.update_mirrors(output);
         Ok(())
     }
 
+    /// Bind wildcard routes to matching sources, or rebind them as the policy
+    /// allows. Returns the (output, source) routes that changed
+    pub fn resolve_wildcards(
+        &mut self,
+        available: &[NdiSource],
+        policy: RebindPolicy,
+    ) -> Vec<(String, String)> {
+        let mut changed = Vec::new();
+        for (output, wildcard) in &mut self.wildcards {
+            if let Some(source) = wildcard.resolve(available, policy).map(str::to_string) {
+                info!(
+                    "Wildcard {} -> {} resolved to {}",
+                    wildcard.pattern, output, source
+                );
+                self.routes.insert(output.clone(), source.clone());
+                changed.push((output.clone(), source));
+            }
+        }
+        for (output, _) in &changed {
+            self.update_mirrors(output);
+        }
+        changed.sort();
+        changed
+    }
+
+    /// Release the source a wildcard route is bound to, so it binds afresh
+    pub fn rebind_wildcard(&mut self, output: &str) {
+        if let Some(wildcard) = self.wildcards.get_mut(output) {
+            wildcard.bound = None;
+            self.routes
+                .insert(output.to_string(), wildcard.pattern.clone());
+            self.update_mirrors(output);
+        }
+    }
+
+    /// Keep outputs routed like the outputs they mirror; each mirror takes its
+    /// primary's current route right away
+    pub fn set_mirrors(&mut self, mirrors: Vec<OutputMirror>) {
+        self.mirrors = mirrors
+            .into_iter()
+            .filter(|m| m.output != m.mirror)
+            .collect();
+        let primaries: Vec<String> = self.mirrors.iter().map(|m| m.output.clone()).collect();
+        for output in primaries {
+            self.update_mirrors(&output);
+        }
+    }
+
+    /// The output an output mirrors, if any
+    pub fn mirror_of(&self, output: &str) -> Option<&str> {
+        self.mirrors
+            .iter()
+            .find(|m| m.mirror == output)
+            .map(|m| m.output.as_str())
+    }
+
+    /// Copy an output's route to the outputs mirroring it, and on to theirs
+    fn update_mirrors(&mut self, output: &str) {
+        let mut pending = vec![output.to_string()];
+        let mut done = HashSet::new();
+        while let Some(primary) = pending.pop() {
+            // A mirror loop ends where it started
+            if !done.insert(primary.clone()) {
+                continue;
+            }
+            let input = self.routes.get(&primary).cloned();
+            for mirror in self.mirrors.iter().filter(|m| m.output == primary) {
+                if done.contains(&mirror.mirror) || !self.outputs.contains(&mirror.mirror) {
+                    continue;
+                }
+                match &input {
+                    Some(input) => {
+                        info!("Mirroring {} -> {}", primary, mirror.mirror);
+                        self.routes.insert(mirror.mirror.clone(), input.clone());
+                    }
+                    None => {
+                        self.routes.remove(&mirror.mirror);
+                    }
+                }
+                self.wildcards.remove(&mirror.mirror);
+                pending.push(mirror.mirror.clone());
+            }
+        }
+    }
+
+    /// The wildcard an output is routed to, with the source it resolved to
+    pub fn wildcard(&self, output: &str) -> Option<&WildcardRoute> {
+        self.wildcards.get(output)
+    }
+
     /// Check if an input for a route exists (is not a placeholder)
     pub fn input_exists(&self, input: &str) -> bool {
-        self.inputs
-            .iter()
-            .any(|s| s.url == input || s.name == input)
+        self.inputs.iter().any(|s| s.matches(input))
     }
 
     /// Remove a route for a specific output
     pub fn unroute(&mut self, output: &str) -> Option<String> {
+        self.wildcards.remove(output);
         if let Some(input) = self.routes.remove(output) {
             info!("Removed route: {} -> {}", input, output);
+            self.update_mirrors(output);
             Some(input)
         } else {
             warn!("No route found for output: {}", output);
@@ -103,19 +289,118 @@ impl MatrixRouter {
     }
 
     /// Get current route for an output
-    #[allow(dead_code)]
     pub fn get_route(&self, output: &str) -> Option<&String> {
         self.routes.get(output)
     }
 
-    /// Get all current routes
+    /// Get all current routes, with their notes
     pub fn get_all_routes(&self) -> Vec<Route> {
         self.routes
             .iter()
-            .map(|(output, input)| Route::new(input.clone(), output.clone()))
+            .map(|(output, input)| self.with_notes(input, output))
+            .collect()
+    }
+
+    /// Routes as the operator made them: wildcard routes keep their pattern
+    /// instead of the source it resolved to (what gets saved). While blanked,
+    /// the routes that `resume` brings back
+    pub fn route_intents(&self) -> Vec<Route> {
+        let (routes, wildcards) = match &self.standby {
+            Some(standby) => (&standby.routes, &standby.wildcards),
+            None => (&self.routes, &self.wildcards),
+        };
+        routes
+            .iter()
+            .map(|(output, input)| match wildcards.get(output) {
+                Some(wildcard) => self.with_notes(&wildcard.pattern, output),
+                None => self.with_notes(input, output),
+            })
             .collect()
     }
 
+    fn with_notes(&self, input: &str, output: &str) -> Route {
+        self.notes
+            .iter()
+            .find(|r| r.output == output && r.input == input)
+            .cloned()
+            .unwrap_or_else(|| Route::new(input.to_string(), output.to_string()))
+    }
+
+    /// Remember the notes of routes (e.g. from configuration); routes without notes are ignored
+    pub fn set_notes(&mut self, routes: &[Route]) {
+        for route in routes.iter().filter(|r| r.has_notes()) {
+            self.notes
+                .retain(|r| r.output != route.output || r.input != route.input);
+            self.notes.push(route.clone());
+        }
+    }
+
+    /// Put an input into (or take it out of) maintenance; routes are kept
+    pub fn set_maintenance(&mut self, input: &str, enabled: bool) {
+        if enabled {
+            info!("Maintenance started: {}", input);
+            self.maintenance.insert(input.to_string());
+        } else if self.maintenance.remove(input) {
+            info!("Maintenance ended: {}", input);
+        }
+    }
+
+    /// Whether an input (by name or URL) is under maintenance
+    pub fn in_maintenance(&self, input: &str) -> bool {
+        if self.maintenance.contains(input) {
+            return true;
+        }
+        // Routes may name an input by URL and maintenance by name, or the other way round
+        self.inputs
+            .iter()
+            .filter(|s| s.matches(input))
+            .any(|s| self.maintenance.contains(&s.url) || self.maintenance.contains(&s.name))
+    }
+
+    /// Inputs under maintenance
+    pub fn maintenance_inputs(&self) -> Vec<String> {
+        let mut inputs: Vec<String> = self.maintenance.iter().cloned().collect();
+        inputs.sort();
+        inputs
+    }
+
+    /// Blank every output (e.g. house displays at the end of the night), putting
+    /// the routing table aside for `resume`. Outputs show `slate` if given, or
+    /// nothing. Returns false if the outputs are already blanked
+    pub fn blank_all(&mut self, slate: Option<&str>) -> bool {
+        if self.standby.is_some() {
+            return false;
+        }
+        self.standby = Some(Standby {
+            routes: std::mem::take(&mut self.routes),
+            wildcards: std::mem::take(&mut self.wildcards),
+        });
+        if let Some(slate) = slate {
+            for output in &self.outputs {
+                self.routes.insert(output.clone(), slate.to_string());
+            }
+        }
+        info!("All outputs blanked");
+        true
+    }
+
+    /// Restore the routing table from before `blank_all` exactly; routes made while
+    /// blanked are dropped. Returns false if the outputs were not blanked
+    pub fn resume(&mut self) -> bool {
+        let Some(standby) = self.standby.take() else {
+            return false;
+        };
+        self.routes = standby.routes;
+        self.wildcards = standby.wildcards;
+        info!("Outputs resumed");
+        true
+    }
+
+    /// Whether every output is blanked for standby
+    pub fn is_blanked(&self) -> bool {
+        self.standby.is_some()
+    }
+
     /// Get all inputs
     pub fn get_inputs(&self) -> &[NdiSource] {
         &self.inputs
@@ -131,6 +416,7 @@ impl MatrixRouter {
     pub fn clear_routes(&mut self) {
         info!("Clearing all routes");
         self.routes.clear();
+        self.wildcards.clear();
     }
 
     /// Load routes from a configuration
@@ -172,6 +458,173 @@ mod tests {
         assert_eq!(router.get_route("Output 1"), None);
     }
 
+    #[test]
+    fn test_route_notes() {
+        let mut router = MatrixRouter::new();
+        router.add_output("Program".to_string());
+        let mut noted = Route::new("Cam 3".to_string(), "Program".to_string());
+        noted.note = Some("Backup, use only if Cam 1 dies".to_string());
+        noted.tags = vec!["backup".to_string()];
+        router.set_notes(&[noted.clone()]);
+
+        router.route_placeholder("Cam 3", "Program").unwrap();
+        assert_eq!(router.get_all_routes(), vec![noted.clone()]);
+        assert_eq!(
+            noted.notes_summary(),
+            "#backup Backup, use only if Cam 1 dies"
+        );
+
+        // The note belongs to the route, not the output
+        router.route_placeholder("Cam 1", "Program").unwrap();
+        assert!(!router.get_all_routes()[0].has_notes());
+    }
+
+    #[test]
+    fn test_route_by_alias() {
+        let mut router = MatrixRouter::new();
+        let mut source = NdiSource::new(
+            "STUDIO-PC (OBS Output 3)".to_string(),
+            "ndi://pc".to_string(),
+        );
+        source.alias = Some("Slides".to_string());
+        router.add_input(source);
+        router.add_output("Output 1".to_string());
+
+        router.route("Slides", "Output 1").unwrap();
+        assert_eq!(
+            router.get_route("Output 1"),
+            Some(&"STUDIO-PC (OBS Output 3)".to_string())
+        );
+        assert!(router.input_exists("Slides"));
+    }
+
+    #[test]
+    fn test_maintenance() {
+        let mut router = MatrixRouter::new();
+        router.add_input(NdiSource::new(
+            "Camera 1".to_string(),
+            "ndi://cam1".to_string(),
+        ));
+        router.add_output("Output 1".to_string());
+        router.route("ndi://cam1", "Output 1").unwrap();
+
+        router.set_maintenance("Camera 1", true);
+        assert!(router.in_maintenance("ndi://cam1"));
+        assert!(router.in_maintenance("Camera 1"));
+        // The route is kept so the output reverts when maintenance ends
+        assert_eq!(
+            router.get_route("Output 1"),
+            Some(&"ndi://cam1".to_string())
+        );
+
+        router.set_maintenance("Camera 1", false);
+        assert!(!router.in_maintenance("ndi://cam1"));
+        assert!(router.maintenance_inputs().is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_route() {
+        let mut router = MatrixRouter::new();
+        router.add_output("Stage".to_string());
+        router.route_placeholder("*Slides*", "Stage").unwrap();
+        assert_eq!(router.get_route("Stage"), Some(&"*Slides*".to_string()));
+
+        let slides = NdiSource::new("PC1 (Slides)".to_string(), "ndi://pc1".to_string());
+        let changed = router.resolve_wildcards(&[slides], RebindPolicy::Sticky);
+        assert_eq!(
+            changed,
+            vec![("Stage".to_string(), "PC1 (Slides)".to_string())]
+        );
+        assert_eq!(
+            router.wildcard("Stage").unwrap().bound.as_deref(),
+            Some("PC1 (Slides)")
+        );
+        // Saved routes keep the pattern
+        assert_eq!(router.route_intents()[0].input, "*Slides*");
+        assert_eq!(router.get_all_routes()[0].input, "PC1 (Slides)");
+
+        router.rebind_wildcard("Stage");
+        assert_eq!(router.get_route("Stage"), Some(&"*Slides*".to_string()));
+
+        // An explicit route replaces the wildcard
+        router.route_placeholder("CAM 1", "Stage").unwrap();
+        assert!(router.wildcard("Stage").is_none());
+    }
+
+    #[test]
+    fn test_output_mirror() {
+        let mut router = MatrixRouter::new();
+        for output in ["Program", "Green Room", "Lobby"] {
+            router.add_output(output.to_string());
+        }
+        router.route_placeholder("CAM 1", "Program").unwrap();
+        router.set_mirrors(vec![
+            OutputMirror {
+                output: "Program".to_string(),
+                mirror: "Green Room".to_string(),
+            },
+            OutputMirror {
+                output: "Green Room".to_string(),
+                mirror: "Lobby".to_string(),
+            },
+            // Loops are harmless
+            OutputMirror {
+                output: "Lobby".to_string(),
+                mirror: "Program".to_string(),
+            },
+        ]);
+        assert_eq!(router.get_route("Lobby"), Some(&"CAM 1".to_string()));
+        assert_eq!(router.mirror_of("Green Room"), Some("Program"));
+
+        router.route_placeholder("CAM 2", "Program").unwrap();
+        assert_eq!(router.get_route("Green Room"), Some(&"CAM 2".to_string()));
+        assert_eq!(router.get_route("Lobby"), Some(&"CAM 2".to_string()));
+
+        router.unroute("Program");
+        assert_eq!(router.get_route("Green Room"), None);
+        assert_eq!(router.get_route("Lobby"), None);
+    }
+
+    #[test]
+    fn test_blank_and_resume() {
+        let mut router = MatrixRouter::new();
+        router.add_input(NdiSource::new(
+            "Camera 1".to_string(),
+            "ndi://cam1".to_string(),
+        ));
+        for output in ["Lobby", "Cry Room", "Stage"] {
+            router.add_output(output.to_string());
+        }
+        router.route("Camera 1", "Lobby").unwrap();
+        router.route_placeholder("*Slides*", "Stage").unwrap();
+        let before = router.route_intents();
+
+        assert!(router.blank_all(None));
+        assert!(router.is_blanked());
+        assert!(router.get_all_routes().is_empty());
+        assert!(router.wildcard("Stage").is_none());
+        // What gets saved is still the show's routing
+        assert_eq!(router.route_intents(), before);
+        assert!(!router.blank_all(None));
+
+        // Routing while blanked doesn't survive the resume
+        router.route("Camera 1", "Cry Room").unwrap();
+        assert!(router.resume());
+        assert!(!router.is_blanked());
+        assert_eq!(router.get_route("Lobby"), Some(&"Camera 1".to_string()));
+        assert_eq!(router.get_route("Cry Room"), None);
+        assert!(router.wildcard("Stage").is_some());
+        assert!(!router.resume());
+
+        // With a slate, every output shows it
+        router.blank_all(Some("SLATE (Goodnight)"));
+        assert_eq!(router.get_all_routes().len(), 3);
+        assert_eq!(
+            router.get_route("Cry Room"),
+            Some(&"SLATE (Goodnight)".to_string())
+        );
+    }
+
     #[test]
     fn test_invalid_routing() {
         let mut router = MatrixRouter::new();
@@ -179,4 +632,20 @@ mod tests {
         // Try to route without adding input/output
         assert!(router.route("ndi://invalid", "Output 1").is_err());
     }
+
+    #[test]
+    fn test_quarantined_input_not_routable() {
+        let mut router = MatrixRouter::new();
+        let mut laptop = NdiSource::new("LAPTOP (Chrome)".to_string(), "ndi://laptop".to_string());
+        laptop.quarantined = true;
+        router.add_input(laptop.clone());
+        router.add_output("House Left".to_string());
+
+        assert!(router.route("LAPTOP (Chrome)", "House Left").is_err());
+
+        // Approval refreshes the input
+        laptop.quarantined = false;
+        router.add_input(laptop);
+        assert!(router.route("LAPTOP (Chrome)", "House Left").is_ok());
+    }
 }
@@ -0,0 +1,45 @@
+use super::router::notes_summary;
+use super::Route;
+use crate::devices::DeviceCommand;
+use serde::{Deserialize, Serialize};
+
+/// A named set of routes that can be recalled together
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Salvo {
+    pub name: String,
+    pub routes: Vec<Route>,
+    /// Freeform note (e.g. when the salvo is used)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Device actions run in order after the routes are applied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub device_actions: Vec<DeviceCommand>,
+}
+
+impl Salvo {
+    #[allow(dead_code)]
+    pub fn new(name: String, routes: Vec<Route>) -> Self {
+        Self {
+            name,
+            routes,
+            note: None,
+            tags: Vec::new(),
+            device_actions: Vec::new(),
+        }
+    }
+
+    /// Tags and note on one line (empty if there are none)
+    pub fn notes_summary(&self) -> String {
+        notes_summary(self.note.as_deref(), &self.tags)
+    }
+
+    /// Get the input this salvo assigns to an output
+    pub fn input_for(&self, output: &str) -> Option<&str> {
+        self.routes
+            .iter()
+            .find(|r| r.output == output)
+            .map(|r| r.input.as_str())
+    }
+}
@@ -0,0 +1,96 @@
+//! Tally state of inputs, derived from the routes to program and preview outputs
+
+use super::Route;
+use crate::config::MatrixConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tally lights of one input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TallyState {
+    pub program: bool,
+    pub preview: bool,
+}
+
+impl TallyState {
+    pub fn is_off(&self) -> bool {
+        !self.program && !self.preview
+    }
+}
+
+/// Which outputs light program and preview tally
+#[derive(Debug, Clone, Default)]
+pub struct TallyOutputs {
+    program: Vec<String>,
+    preview: Vec<String>,
+}
+
+impl TallyOutputs {
+    pub fn new(program: Vec<String>, preview: Vec<String>) -> Self {
+        Self { program, preview }
+    }
+
+    pub fn from_config(config: &MatrixConfig) -> Self {
+        Self::new(
+            config.program_outputs.clone(),
+            config.preview_outputs.clone(),
+        )
+    }
+
+    /// Whether any output is designated for tally
+    pub fn is_empty(&self) -> bool {
+        self.program.is_empty() && self.preview.is_empty()
+    }
+
+    /// Tally of every input routed to a program or preview output
+    pub fn tally(&self, routes: &[Route]) -> HashMap<String, TallyState> {
+        let mut tally: HashMap<String, TallyState> = HashMap::new();
+        for route in routes {
+            let program = self.program.contains(&route.output);
+            let preview = self.preview.contains(&route.output);
+            if program || preview {
+                let state = tally.entry(route.input.clone()).or_default();
+                state.program |= program;
+                state.preview |= preview;
+            }
+        }
+        tally
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(input: &str, output: &str) -> Route {
+        Route::new(input.to_string(), output.to_string())
+    }
+
+    #[test]
+    fn test_tally() {
+        let outputs = TallyOutputs::new(vec!["Program".to_string()], vec!["Preview".to_string()]);
+        let tally = outputs.tally(&[
+            route("Cam 1", "Program"),
+            route("Cam 2", "Preview"),
+            route("Cam 3", "Monitor 1"),
+        ]);
+
+        assert_eq!(
+            tally["Cam 1"],
+            TallyState {
+                program: true,
+                preview: false
+            }
+        );
+        assert!(tally["Cam 2"].preview);
+        assert!(!tally.contains_key("Cam 3"));
+    }
+
+    #[test]
+    fn test_program_and_preview() {
+        let outputs = TallyOutputs::new(vec!["Program".to_string()], vec!["Preview".to_string()]);
+        let tally = outputs.tally(&[route("Cam 1", "Program"), route("Cam 1", "Preview")]);
+        assert!(tally["Cam 1"].program && tally["Cam 1"].preview);
+        assert!(TallyState::default().is_off());
+    }
+}
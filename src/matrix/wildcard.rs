@@ -0,0 +1,177 @@
+//! Placeholder routes to whichever source matches a pattern (e.g. "*Slides*"),
+//! for sources whose full NDI name isn't known in advance
+
+use super::MatrixRouter;
+use crate::ndi::{NdiDiscovery, NdiSource};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+
+/// What a wildcard route does once it is bound to a source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebindPolicy {
+    /// Stay on the first source matched, waiting for it if it goes away
+    #[default]
+    Sticky,
+    /// Move to another matching source while the bound one is missing
+    Follow,
+}
+
+/// The intent of a wildcard route: its pattern and the source it resolved to
+#[derive(Debug, Clone, PartialEq)]
+pub struct WildcardRoute {
+    pub pattern: String,
+    /// NDI name of the source the pattern is bound to
+    pub bound: Option<String>,
+}
+
+impl WildcardRoute {
+    pub fn new(pattern: String) -> Self {
+        Self {
+            pattern,
+            bound: None,
+        }
+    }
+
+    /// Whether a source's NDI name or alias matches the pattern
+    pub fn matches(&self, source: &NdiSource) -> bool {
+        glob_match(&self.pattern, &source.name)
+            || source
+                .alias
+                .as_deref()
+                .is_some_and(|alias| glob_match(&self.pattern, alias))
+    }
+
+    /// Pick a source to bind to, if the policy calls for one. Returns the new source
+    pub fn resolve(&mut self, available: &[NdiSource], policy: RebindPolicy) -> Option<&str> {
+        if let Some(bound) = &self.bound {
+            let present = available.iter().any(|s| &s.name == bound);
+            if present || policy == RebindPolicy::Sticky {
+                return None;
+            }
+        }
+        let source = available
+            .iter()
+            .find(|s| !s.quarantined && self.matches(s) && self.bound.as_ref() != Some(&s.name))?;
+        self.bound = Some(source.name.clone());
+        self.bound.as_deref()
+    }
+}
+
+/// Resolve the router's wildcard routes in the background whenever sources change
+pub fn follow_discovery(
+    router: Arc<Mutex<MatrixRouter>>,
+    discovery: Arc<NdiDiscovery>,
+    policy: RebindPolicy,
+) {
+    let mut changes = discovery.subscribe();
+    tokio::spawn(async move {
+        loop {
+            router
+                .lock()
+                .unwrap()
+                .resolve_wildcards(&discovery.get_sources(), policy);
+            match changes.recv().await {
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Whether an input names a pattern rather than a source
+pub fn is_pattern(input: &str) -> bool {
+    input.contains(['*', '?'])
+}
+
+/// Case-insensitive glob match: `*` matches any run of characters, `?` any one
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it has matched up to
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, t));
+            p += 1;
+        } else if let Some((after_star, matched)) = star {
+            // Let the last `*` take one more character
+            p = after_star;
+            t = matched + 1;
+            star = Some((after_star, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str) -> NdiSource {
+        NdiSource::new(name.to_string(), format!("ndi://{}", name))
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*Slides*", "STUDIO-PC (ProPresenter Slides)"));
+        assert!(glob_match("*slides*", "SLIDES"));
+        assert!(glob_match("CAM ?", "CAM 2"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbc"));
+        assert!(!glob_match("CAM ?", "CAM 12"));
+        assert!(!glob_match("*Slides", "Slides (backup)"));
+        assert!(is_pattern("*Slides*"));
+        assert!(!is_pattern("CAM 1"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut wildcard = WildcardRoute::new("*Slides*".to_string());
+        let mut quarantined = source("LAPTOP (Slides)");
+        quarantined.quarantined = true;
+        let first = source("PC1 (Slides)");
+        let second = source("PC2 (Slides)");
+
+        assert_eq!(
+            wildcard.resolve(&[source("CAM 1")], RebindPolicy::Sticky),
+            None
+        );
+        assert_eq!(
+            wildcard.resolve(
+                &[quarantined, first.clone(), second.clone()],
+                RebindPolicy::Sticky
+            ),
+            Some("PC1 (Slides)")
+        );
+
+        // Sticky waits for the bound source; follow moves on
+        let mut follow = wildcard.clone();
+        assert_eq!(
+            wildcard.resolve(std::slice::from_ref(&second), RebindPolicy::Sticky),
+            None
+        );
+        assert_eq!(
+            follow.resolve(std::slice::from_ref(&second), RebindPolicy::Follow),
+            Some("PC2 (Slides)")
+        );
+        assert_eq!(follow.resolve(&[first, second], RebindPolicy::Follow), None);
+
+        // Aliases match too
+        let mut aliased = source("STUDIO-PC (OBS Output 3)");
+        aliased.alias = Some("Slides".to_string());
+        let mut wildcard = WildcardRoute::new("slides".to_string());
+        assert_eq!(
+            wildcard.resolve(&[aliased], RebindPolicy::Sticky),
+            Some("STUDIO-PC (OBS Output 3)")
+        );
+    }
+}
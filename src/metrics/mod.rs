@@ -0,0 +1,458 @@
+//! Prometheus metrics served at `/metrics`, and a Grafana dashboard and alert
+//! rules generated for the configured outputs and cameras
+
+use crate::config::Config;
+use crate::ndi::ReceiverStats;
+use crate::supervisor::TaskStatus;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Dashboard file written by `rustv metrics export-dashboard`
+pub const DASHBOARD_FILE: &str = "rustv-dashboard.json";
+/// Alert rules file written by `rustv metrics export-dashboard`
+pub const ALERTS_FILE: &str = "rustv-alerts.yml";
+
+/// Jitter above which a receiver is alerted on (ms)
+const JITTER_ALERT_MS: f64 = 10.0;
+
+/// Reads one figure of a receiver's statistics
+type StatValue = fn(&ReceiverStats) -> f64;
+
+/// What the metrics are taken from
+pub struct Snapshot {
+    /// Every output with whether it is routed
+    pub outputs: Vec<(String, bool)>,
+    pub receivers: HashMap<String, ReceiverStats>,
+    pub tasks: Vec<TaskStatus>,
+    pub sources: usize,
+}
+
+/// Metrics in the Prometheus text format
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, f64)>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{} {}", name, labels, value);
+        }
+    };
+    let label = |key: &str, value: &str| format!("{{{}=\"{}\"}}", key, escape_label(value));
+
+    family(
+        "rustv_sources_discovered",
+        "gauge",
+        "NDI sources currently discovered",
+        vec![(String::new(), snapshot.sources as f64)],
+    );
+    family(
+        "rustv_output_routed",
+        "gauge",
+        "Whether an output has an input routed (1) or not (0)",
+        snapshot
+            .outputs
+            .iter()
+            .map(|(output, routed)| (label("output", output), *routed as u8 as f64))
+            .collect(),
+    );
+    family(
+        "rustv_task_restarts_total",
+        "counter",
+        "Times a supervised task was restarted after failing",
+        snapshot
+            .tasks
+            .iter()
+            .map(|task| (label("task", &task.name), task.restarts as f64))
+            .collect(),
+    );
+
+    let mut receivers: Vec<(&String, &ReceiverStats)> = snapshot.receivers.iter().collect();
+    receivers.sort_by_key(|(source, _)| *source);
+    let per_receiver: [(&str, &str, &str, StatValue); 6] = [
+        (
+            "rustv_receiver_frames_received_total",
+            "counter",
+            "Video frames received",
+            |s| s.frames_received as f64,
+        ),
+        (
+            "rustv_receiver_frames_dropped_total",
+            "counter",
+            "Video frames dropped because the playout buffer was full",
+            |s| s.frames_dropped as f64,
+        ),
+        (
+            "rustv_receiver_bytes_received_total",
+            "counter",
+            "Bytes received",
+            |s| s.bytes_received as f64,
+        ),
+        (
+            "rustv_receiver_frames_per_second",
+            "gauge",
+            "Video frame rate over the last 10 seconds",
+            |s| s.frames_per_second,
+        ),
+        (
+            "rustv_receiver_jitter_milliseconds",
+            "gauge",
+            "Mean deviation of the time between frames over the last 10 seconds",
+            |s| s.jitter_ms,
+        ),
+        (
+            "rustv_receiver_bytes_per_second",
+            "gauge",
+            "Current receive rate",
+            |s| s.bytes_per_second,
+        ),
+    ];
+    for (name, kind, help, value) in per_receiver {
+        family(
+            name,
+            kind,
+            help,
+            receivers
+                .iter()
+                .map(|(source, stats)| (label("source", source), value(stats)))
+                .collect(),
+        );
+    }
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A PromQL string literal matching `value` exactly
+fn promql_string(value: &str) -> String {
+    format!("\"{}\"", escape_label(value))
+}
+
+/// A PromQL regex literal matching any of `values` exactly
+fn promql_any(values: &[String]) -> String {
+    const SPECIAL: &str = r"\.+*?()|[]{}^$";
+    let escaped: Vec<String> = values
+        .iter()
+        .map(|value| {
+            value
+                .chars()
+                .flat_map(|c| {
+                    let escape = SPECIAL.contains(c).then_some('\\');
+                    escape.into_iter().chain(std::iter::once(c))
+                })
+                .collect()
+        })
+        .collect();
+    promql_string(&escaped.join("|"))
+}
+
+/// Outputs alerted on when unrouted: the program outputs, or every output if
+/// none are marked as program
+fn watched_outputs(config: &Config) -> Vec<String> {
+    if config.matrix.program_outputs.is_empty() {
+        config.matrix.outputs.clone()
+    } else {
+        config.matrix.program_outputs.clone()
+    }
+}
+
+/// A Grafana dashboard (import format) with a panel per output and camera
+pub fn dashboard(config: &Config) -> Value {
+    let mut panels = Vec::new();
+    let mut y = 0;
+    let mut id = 0;
+    let mut next_id = || {
+        id += 1;
+        id
+    };
+    let row = |id: u32, title: &str, y: u32| {
+        json!({
+            "id": id,
+            "type": "row",
+            "title": title,
+            "collapsed": false,
+            "gridPos": { "h": 1, "w": 24, "x": 0, "y": y },
+            "panels": []
+        })
+    };
+    // Queries of a panel, lettered A, B, C...
+    let targets = |queries: Vec<(String, &str)>| {
+        let targets: Vec<Value> = queries
+            .into_iter()
+            .zip('A'..='Z')
+            .map(|((expr, legend), ref_id)| {
+                json!({
+                    "datasource": { "type": "prometheus", "uid": "${DS_PROMETHEUS}" },
+                    "expr": expr,
+                    "legendFormat": legend,
+                    "refId": ref_id.to_string()
+                })
+            })
+            .collect();
+        Value::Array(targets)
+    };
+
+    panels.push(row(next_id(), "Outputs", y));
+    y += 1;
+    for (i, output) in config.matrix.outputs.iter().enumerate() {
+        let (x, row_y) = ((i % 6) as u32 * 4, y + (i / 6) as u32 * 3);
+        panels.push(json!({
+            "id": next_id(),
+            "type": "stat",
+            "title": output,
+            "gridPos": { "h": 3, "w": 4, "x": x, "y": row_y },
+            "targets": targets(vec![(format!("rustv_output_routed{{output={}}}", promql_string(output)), "")]),
+            "fieldConfig": {
+                "defaults": {
+                    "mappings": [{
+                        "type": "value",
+                        "options": {
+                            "0": { "text": "Unrouted", "color": "red" },
+                            "1": { "text": "Routed", "color": "green" }
+                        }
+                    }],
+                    "color": { "mode": "fixed", "fixedColor": "text" }
+                }
+            },
+            "options": { "colorMode": "background", "graphMode": "none" }
+        }));
+    }
+    y += config.matrix.outputs.len().div_ceil(6) as u32 * 3;
+
+    if !config.birddog.cameras.is_empty() {
+        panels.push(row(next_id(), "Cameras", y));
+        y += 1;
+        for (i, camera) in config.birddog.cameras.iter().enumerate() {
+            let source = promql_string(&camera.ndi_name);
+            let (x, row_y) = ((i % 3) as u32 * 8, y + (i / 3) as u32 * 7);
+            panels.push(json!({
+                "id": next_id(),
+                "type": "timeseries",
+                "title": camera.name,
+                "gridPos": { "h": 7, "w": 8, "x": x, "y": row_y },
+                "targets": targets(vec![
+                    (format!("rustv_receiver_frames_per_second{{source={}}}", source), "fps"),
+                    (format!("rustv_receiver_jitter_milliseconds{{source={}}}", source), "jitter (ms)"),
+                    (format!("rate(rustv_receiver_frames_dropped_total{{source={}}}[1m]) * 60", source), "dropped/min")
+                ])
+            }));
+        }
+        y += config.birddog.cameras.len().div_ceil(3) as u32 * 7;
+    }
+
+    panels.push(row(next_id(), "All receivers", y));
+    y += 1;
+    let overview = [
+        ("Bit rate", "rustv_receiver_bytes_per_second * 8", "bps"),
+        (
+            "Dropped frames per minute",
+            "rate(rustv_receiver_frames_dropped_total[1m]) * 60",
+            "short",
+        ),
+        ("Jitter", "rustv_receiver_jitter_milliseconds", "ms"),
+        (
+            "Task restarts",
+            "increase(rustv_task_restarts_total[1h])",
+            "short",
+        ),
+    ];
+    for (i, (title, expr, unit)) in overview.into_iter().enumerate() {
+        let legend = if title == "Task restarts" {
+            "{{task}}"
+        } else {
+            "{{source}}"
+        };
+        panels.push(json!({
+            "id": next_id(),
+            "type": "timeseries",
+            "title": title,
+            "gridPos": { "h": 8, "w": 12, "x": (i % 2) as u32 * 12, "y": y + (i / 2) as u32 * 8 },
+            "targets": targets(vec![(expr.to_string(), legend)]),
+            "fieldConfig": { "defaults": { "unit": unit } }
+        }));
+    }
+
+    json!({
+        "__inputs": [{
+            "name": "DS_PROMETHEUS",
+            "label": "Prometheus",
+            "type": "datasource",
+            "pluginId": "prometheus",
+            "pluginName": "Prometheus"
+        }],
+        "title": "RusTV",
+        "uid": "rustv",
+        "tags": ["rustv", "ndi"],
+        "timezone": "browser",
+        "refresh": "10s",
+        "time": { "from": "now-6h", "to": "now" },
+        "schemaVersion": 39,
+        "panels": panels
+    })
+}
+
+/// Prometheus alerting rules (YAML) for the configured outputs and cameras,
+/// scraped under `job`
+pub fn alert_rules(config: &Config, job: &str) -> String {
+    let mut rules = vec![(
+        "RusTVDown",
+        format!("up{{job={}}} == 0", promql_string(job)),
+        "1m",
+        "critical",
+        "RusTV is not answering scrapes".to_string(),
+    )];
+    let outputs = watched_outputs(config);
+    if !outputs.is_empty() {
+        rules.push((
+            "RusTVOutputUnrouted",
+            format!(
+                "rustv_output_routed{{output=~{}}} == 0",
+                promql_any(&outputs)
+            ),
+            "1m",
+            "warning",
+            "Output {{ $labels.output }} has nothing routed".to_string(),
+        ));
+    }
+    let cameras: Vec<String> = config
+        .birddog
+        .cameras
+        .iter()
+        .map(|camera| camera.ndi_name.clone())
+        .collect();
+    if !cameras.is_empty() {
+        rules.push((
+            "RusTVCameraNoVideo",
+            format!(
+                "rustv_receiver_frames_per_second{{source=~{}}} < 1",
+                promql_any(&cameras)
+            ),
+            "30s",
+            "critical",
+            "Camera {{ $labels.source }} stopped sending video".to_string(),
+        ));
+    }
+    if config.alerts.dropped_frames > 0 {
+        rules.push((
+            "RusTVDroppingFrames",
+            format!(
+                "rate(rustv_receiver_frames_dropped_total[1m]) * 60 >= {}",
+                config.alerts.dropped_frames
+            ),
+            "1m",
+            "warning",
+            "{{ $labels.source }} is dropping {{ $value | humanize }} frames a minute".to_string(),
+        ));
+    }
+    rules.push((
+        "RusTVJitter",
+        format!("rustv_receiver_jitter_milliseconds > {}", JITTER_ALERT_MS),
+        "2m",
+        "warning",
+        "{{ $labels.source }} frames arrive unevenly ({{ $value | humanize }} ms jitter)"
+            .to_string(),
+    ));
+    if config.alerts.min_bitrate_mbps > 0.0 {
+        rules.push((
+            "RusTVLowBitrate",
+            format!(
+                "rustv_receiver_bytes_per_second * 8 < {} and rustv_receiver_frames_per_second > 0",
+                config.alerts.min_bitrate_mbps * 1_000_000.0
+            ),
+            "2m",
+            "warning",
+            "{{ $labels.source }} is below its minimum bit rate".to_string(),
+        ));
+    }
+    rules.push((
+        "RusTVTaskRestarting",
+        "increase(rustv_task_restarts_total[10m]) > 0".to_string(),
+        "0m",
+        "warning",
+        "{{ $labels.task }} restarted after failing".to_string(),
+    ));
+
+    // JSON strings are valid YAML scalars, so every value is written as one
+    let quote = |s: &str| serde_json::to_string(s).unwrap();
+    let mut yaml = String::from("groups:\n  - name: rustv\n    rules:\n");
+    for (alert, expr, wait, severity, summary) in rules {
+        let _ = writeln!(yaml, "      - alert: {}", alert);
+        let _ = writeln!(yaml, "        expr: {}", quote(&expr));
+        let _ = writeln!(yaml, "        for: {}", wait);
+        let _ = writeln!(yaml, "        labels:\n          severity: {}", severity);
+        let _ = writeln!(
+            yaml,
+            "        annotations:\n          summary: {}",
+            quote(&summary)
+        );
+    }
+    yaml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CameraConfig;
+
+    #[test]
+    fn test_render() {
+        let snapshot = Snapshot {
+            outputs: vec![("Program".into(), true), ("Mon \"2\"".into(), false)],
+            receivers: HashMap::from([(
+                "CAM-1 (Wide)".to_string(),
+                ReceiverStats {
+                    frames_received: 1500,
+                    jitter_ms: 1.5,
+                    ..ReceiverStats::default()
+                },
+            )]),
+            tasks: vec![],
+            sources: 4,
+        };
+        let text = render(&snapshot);
+        assert!(text.contains("# TYPE rustv_output_routed gauge\n"));
+        assert!(text.contains("rustv_output_routed{output=\"Program\"} 1\n"));
+        assert!(text.contains("rustv_output_routed{output=\"Mon \\\"2\\\"\"} 0\n"));
+        assert!(
+            text.contains("rustv_receiver_frames_received_total{source=\"CAM-1 (Wide)\"} 1500\n")
+        );
+        assert!(text.contains("rustv_receiver_jitter_milliseconds{source=\"CAM-1 (Wide)\"} 1.5\n"));
+        assert!(text.contains("rustv_sources_discovered 4\n"));
+    }
+
+    #[test]
+    fn test_dashboard_and_alerts() {
+        let mut config = Config::default();
+        config.matrix.program_outputs = vec!["Program".into(), "Mon.1".into()];
+        config.birddog.cameras = vec![CameraConfig {
+            name: "Wide".into(),
+            ip_address: String::new(),
+            ndi_name: "CAM-1 (Wide)".into(),
+            ptz: Default::default(),
+        }];
+
+        let dashboard = dashboard(&config);
+        let panels = dashboard["panels"].as_array().unwrap();
+        let titles: Vec<&str> = panels.iter().filter_map(|p| p["title"].as_str()).collect();
+        for output in &config.matrix.outputs {
+            assert!(titles.contains(&output.as_str()));
+        }
+        assert!(titles.contains(&"Wide"));
+        let ids: std::collections::HashSet<u64> =
+            panels.iter().map(|p| p["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids.len(), panels.len());
+
+        let rules = alert_rules(&config, "rustv");
+        assert!(
+            rules.contains(r#"expr: "rustv_output_routed{output=~\"Program|Mon\\\\.1\"} == 0""#)
+        );
+        assert!(rules.contains(r#"source=~\"CAM-1 \\\\(Wide\\\\)\""#));
+        assert!(rules.contains("alert: RusTVDroppingFrames"));
+        assert!(!rules.contains("RusTVLowBitrate"));
+    }
+}
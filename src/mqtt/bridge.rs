@@ -0,0 +1,399 @@
+//! Connection to the MQTT broker: publishes state and handles commands
+
+use super::homeassistant::{slug, EntityKind, Topics, NO_INPUT, PRESS};
+use crate::birddog::BirdDogClient;
+use crate::companion::{CompanionAction, CompanionControl};
+use crate::config::{CameraConfig, Config, MqttConfig};
+use crate::matrix::{MatrixRouter, Salvo};
+use crate::ndi::NdiDiscovery;
+use log::{debug, info, warn};
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time;
+
+/// How often route state is checked for changes
+const STATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often camera status is polled (in state intervals)
+const CAMERA_POLL_TICKS: u64 = 30;
+
+/// Delay before reconnecting after a broker error
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Last polled state of a camera: (online, temperature)
+type CameraState = (bool, Option<f64>);
+
+/// Publishes RusTV state to an MQTT broker and acts on commands from it
+pub struct MqttBridge {
+    config: MqttConfig,
+    topics: Topics,
+    cameras: Vec<CameraConfig>,
+    salvos: Vec<Salvo>,
+    router: Arc<Mutex<MatrixRouter>>,
+    discovery: Arc<NdiDiscovery>,
+    control: Arc<CompanionControl>,
+    /// Retained messages already sent in this session (topic -> payload)
+    published: Mutex<HashMap<String, String>>,
+    camera_states: Mutex<HashMap<String, CameraState>>,
+}
+
+impl MqttBridge {
+    pub fn new(
+        config: &Config,
+        router: Arc<Mutex<MatrixRouter>>,
+        discovery: Arc<NdiDiscovery>,
+        control: Arc<CompanionControl>,
+    ) -> Self {
+        let mqtt = config.mqtt.clone();
+        Self {
+            topics: Topics::new(&mqtt.base_topic, &mqtt.discovery_prefix, &mqtt.client_id),
+            config: mqtt,
+            // Status comes from the BirdDog API, which NDI PTZ cameras don't have
+            cameras: config
+                .birddog
+                .cameras
+                .iter()
+                .filter(|camera| camera.is_birddog())
+                .cloned()
+                .collect(),
+            salvos: config.matrix.salvos.clone(),
+            router,
+            discovery,
+            control,
+            published: Mutex::new(HashMap::new()),
+            camera_states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connect to the broker and keep state in sync in the background
+    pub fn start(self) {
+        let mut options =
+            MqttOptions::new(&self.config.client_id, &self.config.host, self.config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            self.topics.availability(),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some(username) = &self.config.username {
+            options.set_credentials(username, self.config.password.clone().unwrap_or_default());
+        }
+
+        info!(
+            "Connecting to MQTT broker {}:{}",
+            self.config.host, self.config.port
+        );
+        let (client, mut eventloop) = AsyncClient::new(options, 100);
+        let bridge = Arc::new(self);
+
+        let events_bridge = Arc::clone(&bridge);
+        let events_client = client.clone();
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!("Connected to MQTT broker");
+                        let bridge = Arc::clone(&events_bridge);
+                        let client = events_client.clone();
+                        tokio::spawn(async move { bridge.on_connect(&client).await });
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        let bridge = Arc::clone(&events_bridge);
+                        let payload = String::from_utf8_lossy(&publish.payload).to_string();
+                        tokio::spawn(
+                            async move { bridge.on_command(&publish.topic, &payload).await },
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("MQTT connection error: {}", e);
+                        time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            let mut ticks_until_poll = 0;
+            loop {
+                if ticks_until_poll == 0 {
+                    bridge.poll_cameras().await;
+                    ticks_until_poll = CAMERA_POLL_TICKS;
+                }
+                bridge.publish_changes(&client).await;
+                ticks_until_poll -= 1;
+                time::sleep(STATE_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Announce availability and subscribe to commands after (re)connecting
+    async fn on_connect(&self, client: &AsyncClient) {
+        // Retained state is republished in full on every connection
+        self.published.lock().unwrap().clear();
+
+        if let Err(e) = client
+            .publish(self.topics.availability(), QoS::AtLeastOnce, true, "online")
+            .await
+        {
+            warn!("Failed to publish MQTT availability: {}", e);
+        }
+        if let Err(e) = client
+            .subscribe(self.topics.command_filter(), QoS::AtLeastOnce)
+            .await
+        {
+            warn!("Failed to subscribe to MQTT commands: {}", e);
+        }
+        self.publish_changes(client).await;
+    }
+
+    /// Publish retained messages whose payload changed since they were last sent
+    async fn publish_changes(&self, client: &AsyncClient) {
+        let changed: Vec<(String, String)> = {
+            let published = self.published.lock().unwrap();
+            self.messages()
+                .into_iter()
+                .filter(|(topic, payload)| published.get(topic) != Some(payload))
+                .collect()
+        };
+
+        for (topic, payload) in changed {
+            debug!("MQTT publish {}: {}", topic, payload);
+            match client
+                .publish(&topic, QoS::AtLeastOnce, true, payload.clone())
+                .await
+            {
+                Ok(()) => {
+                    self.published.lock().unwrap().insert(topic, payload);
+                }
+                Err(e) => warn!("Failed to publish {}: {}", topic, e),
+            }
+        }
+    }
+
+    /// Query the status of every configured camera
+    async fn poll_cameras(&self) {
+        for camera in &self.cameras {
+            let state = match BirdDogClient::new(&camera.ip_address).get_status().await {
+                Ok(status) => (status.online, Some(status.temperature)),
+                Err(e) => {
+                    debug!("Camera {} unreachable: {}", camera.name, e);
+                    (false, None)
+                }
+            };
+            self.camera_states
+                .lock()
+                .unwrap()
+                .insert(camera.name.clone(), state);
+        }
+    }
+
+    /// Select options: every known source plus "None"
+    fn input_options(&self, routes: &HashMap<String, String>) -> Vec<String> {
+        let mut options: Vec<String> = self
+            .discovery
+            .get_sources()
+            .into_iter()
+            .map(|s| s.name)
+            .chain(routes.values().cloned())
+            .collect();
+        options.sort();
+        options.dedup();
+        options.insert(0, NO_INPUT.to_string());
+        options
+    }
+
+    /// All retained messages describing the current state
+    fn messages(&self) -> Vec<(String, String)> {
+        let (outputs, routes) = {
+            let router = self.router.lock().unwrap();
+            let routes: HashMap<String, String> = router
+                .get_all_routes()
+                .into_iter()
+                .map(|r| {
+                    // Routes may store the source URL; HA selects use names
+                    let input = router
+                        .get_inputs()
+                        .iter()
+                        .chain(self.discovery.get_sources().iter())
+                        .find(|s| s.url == r.input)
+                        .map(|s| s.name.clone())
+                        .unwrap_or(r.input);
+                    (r.output, input)
+                })
+                .collect();
+            (router.get_outputs().to_vec(), routes)
+        };
+        let options = self.input_options(&routes);
+        let discovery = self.config.home_assistant_discovery;
+        let mut messages = Vec::new();
+
+        for output in &outputs {
+            if discovery {
+                let payload = self
+                    .topics
+                    .discovery_payload(EntityKind::Output, output, &options);
+                messages.push((
+                    self.topics.discovery(EntityKind::Output, output),
+                    payload.to_string(),
+                ));
+            }
+            let input = routes.get(output).map(String::as_str).unwrap_or(NO_INPUT);
+            messages.push((
+                self.topics.state(EntityKind::Output, output),
+                input.to_string(),
+            ));
+        }
+
+        let camera_states = self.camera_states.lock().unwrap();
+        for camera in &self.cameras {
+            if discovery {
+                for kind in [EntityKind::CameraOnline, EntityKind::CameraTemperature] {
+                    let payload = self.topics.discovery_payload(kind, &camera.name, &[]);
+                    messages.push((
+                        self.topics.discovery(kind, &camera.name),
+                        payload.to_string(),
+                    ));
+                }
+            }
+            if let Some((online, temperature)) = camera_states.get(&camera.name) {
+                let online = if *online { "ON" } else { "OFF" };
+                messages.push((
+                    self.topics.state(EntityKind::CameraOnline, &camera.name),
+                    online.to_string(),
+                ));
+                if let Some(temperature) = temperature {
+                    messages.push((
+                        self.topics
+                            .state(EntityKind::CameraTemperature, &camera.name),
+                        format!("{:.1}", temperature),
+                    ));
+                }
+            }
+        }
+
+        if discovery {
+            for salvo in &self.salvos {
+                let payload = self
+                    .topics
+                    .discovery_payload(EntityKind::Salvo, &salvo.name, &[]);
+                messages.push((
+                    self.topics.discovery(EntityKind::Salvo, &salvo.name),
+                    payload.to_string(),
+                ));
+            }
+        }
+
+        messages
+    }
+
+    /// Turn a message on a command topic into an action
+    fn command_action(&self, topic: &str, payload: &str) -> Option<CompanionAction> {
+        let (kind, name) = self.topics.parse_command(topic)?;
+        match kind {
+            EntityKind::Output => {
+                let output = self
+                    .router
+                    .lock()
+                    .unwrap()
+                    .get_outputs()
+                    .iter()
+                    .find(|o| slug(o) == name)
+                    .cloned()?;
+                let input = payload.trim();
+                Some(if input.is_empty() || input == NO_INPUT {
+                    CompanionAction::Unroute { output }
+                } else {
+                    CompanionAction::Route {
+                        input: input.to_string(),
+                        output,
+                    }
+                })
+            }
+            EntityKind::Salvo if payload.trim() == PRESS => {
+                let salvo = self.salvos.iter().find(|s| slug(&s.name) == name)?;
+                Some(CompanionAction::RecallSalvo {
+                    salvo: salvo.name.clone(),
+                    policy: None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    async fn on_command(&self, topic: &str, payload: &str) {
+        let Some(action) = self.command_action(topic, payload) else {
+            warn!("Ignoring MQTT command on {}: {}", topic, payload);
+            return;
+        };
+        if let Err(e) = self.control.handle_from("mqtt", action).await {
+            warn!("MQTT command on {} failed: {}", topic, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Route;
+
+    fn bridge() -> MqttBridge {
+        let mut config = Config::default();
+        config.matrix.salvos = vec![Salvo::new(
+            "Sunday AM".to_string(),
+            vec![Route::new("Camera 1".to_string(), "Monitor 1".to_string())],
+        )];
+
+        let mut router = MatrixRouter::new();
+        for output in &config.matrix.outputs {
+            router.add_output(output.clone());
+        }
+        router.route_placeholder("Camera 1", "Monitor 2").unwrap();
+        let router = Arc::new(Mutex::new(router));
+
+        let control = CompanionControl::from_config(Arc::clone(&router), &config);
+        MqttBridge::new(
+            &config,
+            router,
+            Arc::new(NdiDiscovery::new()),
+            Arc::new(control),
+        )
+    }
+
+    #[test]
+    fn test_messages() {
+        let messages: HashMap<String, String> = bridge().messages().into_iter().collect();
+
+        assert_eq!(messages["rustv/output/monitor_1/state"], NO_INPUT);
+        assert_eq!(messages["rustv/output/monitor_2/state"], "Camera 1");
+        assert!(
+            messages["homeassistant/select/rustv/output_monitor_2/config"].contains("Camera 1")
+        );
+        assert!(messages.contains_key("homeassistant/button/rustv/salvo_sunday_am/config"));
+    }
+
+    #[test]
+    fn test_command_action() {
+        let bridge = bridge();
+
+        assert!(matches!(
+            bridge.command_action("rustv/output/monitor_1/set", "Camera 2"),
+            Some(CompanionAction::Route { input, output })
+                if input == "Camera 2" && output == "Monitor 1"
+        ));
+        assert!(matches!(
+            bridge.command_action("rustv/output/monitor_1/set", NO_INPUT),
+            Some(CompanionAction::Unroute { .. })
+        ));
+        assert!(matches!(
+            bridge.command_action("rustv/salvo/sunday_am/set", PRESS),
+            Some(CompanionAction::RecallSalvo { salvo, .. }) if salvo == "Sunday AM"
+        ));
+        assert!(bridge
+            .command_action("rustv/output/unknown/set", "Camera 2")
+            .is_none());
+    }
+}
@@ -0,0 +1,224 @@
+//! Topic layout and Home Assistant MQTT discovery payloads
+
+use serde_json::{json, Value};
+
+/// Payload of the select option that clears a route
+pub const NO_INPUT: &str = "None";
+
+/// Payload sent by Home Assistant buttons
+pub const PRESS: &str = "PRESS";
+
+/// Topic-safe identifier for a name ("Monitor 1" -> "monitor_1")
+pub fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    slug.trim_matches('_').to_string()
+}
+
+/// Kind of entity exposed to Home Assistant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    /// Input routed to an output (select)
+    Output,
+    /// Camera reachability (binary sensor)
+    CameraOnline,
+    /// Camera temperature (sensor)
+    CameraTemperature,
+    /// Salvo recall (button)
+    Salvo,
+}
+
+impl EntityKind {
+    fn component(&self) -> &'static str {
+        match self {
+            Self::Output => "select",
+            Self::CameraOnline => "binary_sensor",
+            Self::CameraTemperature => "sensor",
+            Self::Salvo => "button",
+        }
+    }
+
+    fn object(&self) -> &'static str {
+        match self {
+            Self::Output => "output",
+            Self::CameraOnline => "camera_online",
+            Self::CameraTemperature => "camera_temperature",
+            Self::Salvo => "salvo",
+        }
+    }
+}
+
+/// Topics used by one RusTV instance
+#[derive(Debug, Clone)]
+pub struct Topics {
+    base: String,
+    discovery_prefix: String,
+    node_id: String,
+}
+
+impl Topics {
+    pub fn new(base: &str, discovery_prefix: &str, node_id: &str) -> Self {
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+            discovery_prefix: discovery_prefix.trim_end_matches('/').to_string(),
+            node_id: slug(node_id),
+        }
+    }
+
+    /// Online/offline availability (also the last will)
+    pub fn availability(&self) -> String {
+        format!("{}/status", self.base)
+    }
+
+    /// State topic of an entity
+    pub fn state(&self, kind: EntityKind, name: &str) -> String {
+        format!("{}/{}/{}/state", self.base, kind.object(), slug(name))
+    }
+
+    /// Command topic of an entity
+    pub fn command(&self, kind: EntityKind, name: &str) -> String {
+        format!("{}/{}/{}/set", self.base, kind.object(), slug(name))
+    }
+
+    /// Wildcard subscription for all command topics
+    pub fn command_filter(&self) -> String {
+        format!("{}/+/+/set", self.base)
+    }
+
+    /// Split a command topic into its entity kind and slug
+    pub fn parse_command<'a>(&self, topic: &'a str) -> Option<(EntityKind, &'a str)> {
+        let rest = topic.strip_prefix(&self.base)?.strip_prefix('/')?;
+        let mut parts = rest.split('/');
+        let kind = match parts.next()? {
+            "output" => EntityKind::Output,
+            "salvo" => EntityKind::Salvo,
+            _ => return None,
+        };
+        let slug = parts.next()?;
+        (parts.next()? == "set" && parts.next().is_none()).then_some((kind, slug))
+    }
+
+    /// Discovery config topic of an entity
+    pub fn discovery(&self, kind: EntityKind, name: &str) -> String {
+        format!(
+            "{}/{}/{}/{}_{}/config",
+            self.discovery_prefix,
+            kind.component(),
+            self.node_id,
+            kind.object(),
+            slug(name)
+        )
+    }
+
+    /// Discovery config payload of an entity
+    pub fn discovery_payload(&self, kind: EntityKind, name: &str, options: &[String]) -> Value {
+        let mut payload = json!({
+            "unique_id": format!("{}_{}_{}", self.node_id, kind.object(), slug(name)),
+            "availability_topic": self.availability(),
+            "device": {
+                "identifiers": [format!("rustv_{}", self.node_id)],
+                "name": "RusTV",
+                "manufacturer": "RusTV",
+                "model": "NDI Matrix Viewer",
+                "sw_version": env!("CARGO_PKG_VERSION"),
+            },
+        });
+
+        let fields = match kind {
+            EntityKind::Output => json!({
+                "name": name,
+                "icon": "mdi:video-switch",
+                "state_topic": self.state(kind, name),
+                "command_topic": self.command(kind, name),
+                "options": options,
+            }),
+            EntityKind::CameraOnline => json!({
+                "name": format!("{} Online", name),
+                "device_class": "connectivity",
+                "state_topic": self.state(kind, name),
+                "payload_on": "ON",
+                "payload_off": "OFF",
+            }),
+            EntityKind::CameraTemperature => json!({
+                "name": format!("{} Temperature", name),
+                "device_class": "temperature",
+                "unit_of_measurement": "°C",
+                "state_topic": self.state(kind, name),
+            }),
+            EntityKind::Salvo => json!({
+                "name": format!("Recall {}", name),
+                "icon": "mdi:playlist-play",
+                "command_topic": self.command(kind, name),
+                "payload_press": PRESS,
+            }),
+        };
+
+        if let (Value::Object(payload), Value::Object(fields)) = (&mut payload, fields) {
+            payload.extend(fields);
+        }
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topics() -> Topics {
+        Topics::new("rustv", "homeassistant", "Control Room")
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Monitor 1"), "monitor_1");
+        assert_eq!(slug("  Cam #2 (Wide) "), "cam_2_wide");
+    }
+
+    #[test]
+    fn test_topics() {
+        let topics = topics();
+        assert_eq!(
+            topics.discovery(EntityKind::Output, "Monitor 1"),
+            "homeassistant/select/control_room/output_monitor_1/config"
+        );
+        assert_eq!(
+            topics.command(EntityKind::Salvo, "Sunday AM"),
+            "rustv/salvo/sunday_am/set"
+        );
+        assert_eq!(
+            topics.parse_command("rustv/output/monitor_1/set"),
+            Some((EntityKind::Output, "monitor_1"))
+        );
+        assert_eq!(topics.parse_command("rustv/output/monitor_1/state"), None);
+        assert_eq!(topics.parse_command("other/output/monitor_1/set"), None);
+    }
+
+    #[test]
+    fn test_select_payload() {
+        let options = vec![NO_INPUT.to_string(), "Cam 1".to_string()];
+        let payload = topics().discovery_payload(EntityKind::Output, "Monitor 1", &options);
+
+        assert_eq!(payload["unique_id"], "control_room_output_monitor_1");
+        assert_eq!(payload["command_topic"], "rustv/output/monitor_1/set");
+        assert_eq!(payload["options"][1], "Cam 1");
+        assert_eq!(payload["availability_topic"], "rustv/status");
+        assert_eq!(payload["device"]["name"], "RusTV");
+    }
+
+    #[test]
+    fn test_camera_payloads() {
+        let online = topics().discovery_payload(EntityKind::CameraOnline, "Camera 1", &[]);
+        assert_eq!(online["device_class"], "connectivity");
+        assert_eq!(online["name"], "Camera 1 Online");
+
+        let temperature =
+            topics().discovery_payload(EntityKind::CameraTemperature, "Camera 1", &[]);
+        assert_eq!(temperature["unit_of_measurement"], "°C");
+    }
+}
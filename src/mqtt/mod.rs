@@ -0,0 +1,7 @@
+//! MQTT integration: state and commands over an MQTT broker, with Home Assistant
+//! MQTT discovery so cameras, routes and salvos appear automatically in HA.
+
+mod bridge;
+pub mod homeassistant;
+
+pub use bridge::MqttBridge;
@@ -0,0 +1,259 @@
+//! Stream health alerts: each receiver's dropped frames, stalls and bit rate
+//! checked against the `[alerts]` thresholds, so a degrading feed is noticed
+//! before it dies
+
+use super::ConnectionState;
+use crate::config::AlertsConfig;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Period dropped frames are counted over
+const DROP_WINDOW: Duration = Duration::from_secs(60);
+
+/// Time a receiver must stay below the bit rate threshold before it is flagged,
+/// so a momentary dip (e.g. a static slide) doesn't alert
+const LOW_BITRATE_AFTER: Duration = Duration::from_secs(10);
+
+/// What is wrong with a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AlertKind {
+    DroppedFrames,
+    Stalls,
+    LowBitrate,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertKind::DroppedFrames => write!(f, "dropping frames"),
+            AlertKind::Stalls => write!(f, "stalling"),
+            AlertKind::LowBitrate => write!(f, "low bit rate"),
+        }
+    }
+}
+
+/// What a receiver reported at one check
+#[derive(Debug, Clone, Copy)]
+pub struct StreamSample {
+    /// Frames dropped since the receiver connected
+    pub dropped_frames: u64,
+    pub state: ConnectionState,
+    pub bytes_per_second: f64,
+    /// Whether the receiver pulls full-quality video, the only kind the bit rate
+    /// threshold applies to
+    pub full_video: bool,
+}
+
+/// An alert raised or cleared
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertChange {
+    pub source: String,
+    pub kind: AlertKind,
+    pub raised: bool,
+    /// What was measured (e.g. "45 frames in the last minute")
+    pub detail: String,
+}
+
+/// Recent history of one receiver
+#[derive(Debug, Default)]
+struct StreamHistory {
+    /// Dropped frame counts over the last minute, oldest first
+    dropped: VecDeque<(Instant, u64)>,
+    /// When the stream stalled within the stall window
+    stalls: VecDeque<Instant>,
+    state: ConnectionState,
+    /// Since when the bit rate has been below the threshold
+    low_since: Option<Instant>,
+    active: BTreeSet<AlertKind>,
+}
+
+/// Tracks every receiver's health and decides when to alert
+pub struct StreamAlerts {
+    config: AlertsConfig,
+    streams: HashMap<String, StreamHistory>,
+}
+
+impl StreamAlerts {
+    pub fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Check a receiver's latest sample, returning the alerts it raised or cleared
+    pub fn check(&mut self, source: &str, sample: StreamSample, now: Instant) -> Vec<AlertChange> {
+        let config = &self.config;
+        let history = self.streams.entry(source.to_string()).or_default();
+
+        // A reconnection starts the count again
+        if history
+            .dropped
+            .back()
+            .is_some_and(|(_, count)| sample.dropped_frames < *count)
+        {
+            history.dropped.clear();
+        }
+        history.dropped.push_back((now, sample.dropped_frames));
+        while history
+            .dropped
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > DROP_WINDOW)
+        {
+            history.dropped.pop_front();
+        }
+        let dropped = sample.dropped_frames - history.dropped.front().map_or(0, |(_, c)| *c);
+
+        let stalled = matches!(
+            sample.state,
+            ConnectionState::Stale | ConnectionState::Disconnected
+        );
+        if stalled && history.state == ConnectionState::Connected {
+            history.stalls.push_back(now);
+        }
+        history.state = sample.state;
+        let stall_window = Duration::from_secs(config.stall_window_secs.max(1));
+        while history
+            .stalls
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > stall_window)
+        {
+            history.stalls.pop_front();
+        }
+
+        let mbps = sample.bytes_per_second * 8.0 / 1_000_000.0;
+        let low = config.min_bitrate_mbps > 0.0
+            && sample.full_video
+            && sample.state == ConnectionState::Connected
+            && mbps < config.min_bitrate_mbps;
+        history.low_since = if low {
+            history.low_since.or(Some(now))
+        } else {
+            None
+        };
+
+        let checks = [
+            (
+                AlertKind::DroppedFrames,
+                config.dropped_frames > 0 && dropped >= config.dropped_frames,
+                format!("{} frames dropped in the last minute", dropped),
+            ),
+            (
+                AlertKind::Stalls,
+                config.stalls > 0 && history.stalls.len() >= config.stalls,
+                format!(
+                    "{} stalls in the last {} minutes",
+                    history.stalls.len(),
+                    stall_window.as_secs() / 60
+                ),
+            ),
+            (
+                AlertKind::LowBitrate,
+                history
+                    .low_since
+                    .is_some_and(|since| now.duration_since(since) >= LOW_BITRATE_AFTER),
+                format!("{:.1} Mbps", mbps),
+            ),
+        ];
+        let mut changes = Vec::new();
+        for (kind, failing, detail) in checks {
+            let changed = if failing {
+                history.active.insert(kind)
+            } else {
+                history.active.remove(&kind)
+            };
+            if changed {
+                changes.push(AlertChange {
+                    source: source.to_string(),
+                    kind,
+                    raised: failing,
+                    detail,
+                });
+            }
+        }
+        changes
+    }
+
+    /// Stop tracking receivers that are gone, clearing their alerts quietly
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.streams.retain(|source, _| keep(source));
+    }
+
+    /// Alerts currently raised, by source
+    pub fn active(&self) -> Vec<(&str, AlertKind)> {
+        let mut active: Vec<(&str, AlertKind)> = self
+            .streams
+            .iter()
+            .flat_map(|(source, history)| {
+                history
+                    .active
+                    .iter()
+                    .map(move |kind| (source.as_str(), *kind))
+            })
+            .collect();
+        active.sort();
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(dropped_frames: u64, state: ConnectionState, mbps: f64) -> StreamSample {
+        StreamSample {
+            dropped_frames,
+            state,
+            bytes_per_second: mbps * 1_000_000.0 / 8.0,
+            full_video: true,
+        }
+    }
+
+    #[test]
+    fn test_stream_alerts() {
+        let config = AlertsConfig {
+            dropped_frames: 30,
+            stalls: 2,
+            stall_window_secs: 600,
+            min_bitrate_mbps: 20.0,
+        };
+        let mut alerts = StreamAlerts::new(config);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let connected = ConnectionState::Connected;
+
+        assert!(alerts
+            .check("CAM-1", sample(0, connected, 90.0), at(0))
+            .is_empty());
+        let changes = alerts.check("CAM-1", sample(40, connected, 90.0), at(10));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, AlertKind::DroppedFrames);
+        assert!(changes[0].raised);
+        assert_eq!(changes[0].detail, "40 frames dropped in the last minute");
+        assert_eq!(alerts.active(), [("CAM-1", AlertKind::DroppedFrames)]);
+
+        // The drops age out of the window
+        let changes = alerts.check("CAM-1", sample(40, connected, 90.0), at(75));
+        assert!(!changes[0].raised);
+        assert!(alerts.active().is_empty());
+
+        // Two stalls within the window
+        alerts.check("CAM-1", sample(40, ConnectionState::Stale, 0.0), at(80));
+        alerts.check("CAM-1", sample(40, connected, 90.0), at(85));
+        let changes = alerts.check("CAM-1", sample(40, ConnectionState::Stale, 0.0), at(90));
+        assert_eq!(changes[0].kind, AlertKind::Stalls);
+        assert!(changes[0].raised);
+
+        // Low bit rate only after it lasts
+        assert!(alerts
+            .check("CAM-2", sample(0, connected, 5.0), at(0))
+            .is_empty());
+        let changes = alerts.check("CAM-2", sample(0, connected, 5.0), at(10));
+        assert_eq!(changes[0].kind, AlertKind::LowBitrate);
+        assert_eq!(changes[0].detail, "5.0 Mbps");
+
+        alerts.retain(|source| source != "CAM-2");
+        assert!(alerts.active().iter().all(|(source, _)| *source == "CAM-1"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Remote sites connected through NDI Bridge: keeps each site's Bridge Join
+//! running and labels the sources it publishes with the site
+
+use super::NdiSource;
+use crate::config::BridgeConfig;
+use crate::supervisor::Supervisor;
+use anyhow::Context;
+use log::info;
+use std::net::IpAddr;
+use std::process::Command;
+use std::sync::Arc;
+
+/// Join command of a bridge with its placeholders filled in
+pub fn join_args(bridge: &BridgeConfig) -> Vec<String> {
+    let port = bridge.port.to_string();
+    let key = bridge.encryption_key.as_deref().unwrap_or_default();
+    bridge
+        .join_command
+        .iter()
+        .map(|arg| {
+            arg.replace("{name}", &bridge.name)
+                .replace("{host}", &bridge.host)
+                .replace("{port}", &port)
+                .replace("{key}", key)
+        })
+        .collect()
+}
+
+/// Start the Bridge Join of every bridge that has a join command, restarting
+/// any that exits
+pub fn start_joins(bridges: &[BridgeConfig], supervisor: &Arc<Supervisor>) {
+    for bridge in bridges.iter().filter(|b| !b.join_command.is_empty()) {
+        let args = join_args(bridge);
+        let name = bridge.name.clone();
+        let endpoint = format!("{}:{}", bridge.host, bridge.port);
+        supervisor.spawn_thread(&format!("NDI Bridge {}", name), move || {
+            info!("Joining NDI Bridge {} at {}", name, endpoint);
+            let status = Command::new(&args[0])
+                .args(&args[1..])
+                .status()
+                .with_context(|| format!("Failed to start {}", args[0]))?;
+            anyhow::bail!("Bridge Join for {} exited ({})", name, status)
+        });
+    }
+}
+
+/// Label sources published by a bridge's Join with the bridge's site
+pub fn tag_sites(sources: &mut [NdiSource], bridges: &[BridgeConfig]) {
+    let addresses: Vec<(IpAddr, &str)> = bridges
+        .iter()
+        .filter_map(|b| Some((b.join_address.parse().ok()?, b.name.as_str())))
+        .collect();
+    for source in sources {
+        source.site = source.ip_address.and_then(|ip| {
+            addresses
+                .iter()
+                .find(|(address, _)| *address == ip)
+                .map(|(_, site)| site.to_string())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bridge() -> BridgeConfig {
+        BridgeConfig {
+            name: "North Campus".to_string(),
+            host: "bridge.north.example.org".to_string(),
+            port: 5990,
+            encryption_key: Some("s3cret".to_string()),
+            join_address: "10.0.0.20".to_string(),
+            join_command: ["ndi-bridge-join", "--host", "{host}:{port}", "--key={key}"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_join_args() {
+        assert_eq!(
+            join_args(&bridge()),
+            vec![
+                "ndi-bridge-join",
+                "--host",
+                "bridge.north.example.org:5990",
+                "--key=s3cret"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_sites() {
+        let mut sources = vec![
+            NdiSource::new("NORTH-CAM (Pulpit)".into(), "ndi://10.0.0.20:5961".into()),
+            NdiSource::new("STUDIO-PC (Slides)".into(), "ndi://10.0.0.5:5961".into()),
+        ];
+        for source in &mut sources {
+            source.ip_address = source.address();
+        }
+        tag_sites(&mut sources, &[bridge()]);
+        assert_eq!(sources[0].site.as_deref(), Some("North Campus"));
+        assert!(sources[1].site.is_none());
+    }
+}
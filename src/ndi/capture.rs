@@ -0,0 +1,418 @@
+//! NDI receive instance backed by the NDI SDK (requires the `ndi` feature)
+
+use super::throttle::FrameRateLimit;
+use super::NdiSource;
+use super::ReceiveBandwidth;
+use crate::audio::AudioChunk;
+use crate::matrix::TallyState;
+use crate::video::VideoFrame;
+use anyhow::Result;
+use std::time::SystemTime;
+
+/// Result of one capture call
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+pub enum Captured {
+    /// Nothing arrived before the timeout
+    Nothing,
+    /// A video frame converted to RGBA, how it was sent, and the number of bytes received
+    Video(VideoFrame, SentFormat, usize),
+    /// A video frame dropped by the display-rate limit, with the number of bytes received
+    Skipped(usize),
+    /// Interleaved audio, with the number of bytes received
+    Audio(AudioChunk, usize),
+    /// A metadata frame's XML, with the number of bytes received
+    Metadata(String, usize),
+    /// The connection reported an error
+    Error,
+}
+
+/// How a video frame was sent, before conversion to RGBA
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentFormat {
+    /// Pixel format or codec FourCC (e.g. "UYVY", "H264")
+    pub fourcc: [u8; 4],
+    pub frame_rate_n: u32,
+    pub frame_rate_d: u32,
+    /// When the sender submitted the frame, by the sender's clock
+    pub sent_at: Option<SystemTime>,
+}
+
+#[cfg(feature = "ndi")]
+impl SentFormat {
+    fn of(frame: &super::sys::NDIlib_video_frame_v2_t) -> Self {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        Self {
+            fourcc: frame.FourCC.to_le_bytes(),
+            frame_rate_n: frame.frame_rate_N.max(0) as u32,
+            frame_rate_d: frame.frame_rate_D.max(0) as u32,
+            // 100 ns units since the Unix epoch
+            sent_at: (frame.timestamp > 0
+                && frame.timestamp != super::sys::NDIlib_recv_timestamp_undefined)
+                .then(|| UNIX_EPOCH + Duration::from_nanos(frame.timestamp as u64 * 100)),
+        }
+    }
+}
+
+/// A connected NDI receiver
+#[cfg(feature = "ndi")]
+pub struct RecvInstance {
+    instance: super::sys::NDIlib_recv_instance_t,
+    /// Decoder for NDI|HX video, started by the first compressed frame
+    #[cfg(feature = "hx")]
+    decoder: Option<crate::video::hx::HxDecoder>,
+}
+
+// SAFETY: NDI receive instances may be used from any thread; each instance is
+// owned by a single capture thread and destroyed once on drop.
+#[cfg(feature = "ndi")]
+unsafe impl Send for RecvInstance {}
+
+#[cfg(feature = "ndi")]
+impl RecvInstance {
+    /// Create a receiver connected to the source
+    pub fn connect(source: &NdiSource, bandwidth: ReceiveBandwidth) -> Result<Self> {
+        use super::sys;
+        use anyhow::Context;
+        use std::ffi::CString;
+
+        super::initialize_runtime()?;
+
+        let name = CString::new(source.name.as_str()).context("Invalid NDI source name")?;
+        let url = (!source.url.is_empty() && !source.url.starts_with("ndi://"))
+            .then(|| CString::new(source.url.as_str()))
+            .transpose()
+            .context("Invalid NDI source URL")?;
+        let recv_name = CString::new("RusTV").unwrap();
+
+        let settings = sys::NDIlib_recv_create_v3_t {
+            source_to_connect_to: sys::NDIlib_source_t {
+                p_ndi_name: name.as_ptr(),
+                p_url_address: url.as_ref().map_or(std::ptr::null(), |u| u.as_ptr()),
+            },
+            color_format: recv_color_format(),
+            bandwidth: match bandwidth {
+                ReceiveBandwidth::Full => sys::NDIlib_recv_bandwidth_highest,
+                ReceiveBandwidth::Low => sys::NDIlib_recv_bandwidth_lowest,
+                ReceiveBandwidth::AudioOnly => sys::NDIlib_recv_bandwidth_audio_only,
+                ReceiveBandwidth::MetadataOnly => sys::NDIlib_recv_bandwidth_metadata_only,
+            },
+            allow_video_fields: false,
+            p_ndi_recv_name: recv_name.as_ptr(),
+        };
+
+        // The runtime reads the transport from its configuration file as the receiver is created
+        let transport = super::transport::select(&source.name)?;
+        // SAFETY: settings and the strings it points to outlive the call; the SDK copies them
+        let instance = unsafe { sys::NDIlib_recv_create_v3(&settings) };
+        drop(transport);
+        if instance.is_null() {
+            anyhow::bail!("Failed to create NDI receiver for {}", source.name);
+        }
+        Ok(Self {
+            instance,
+            #[cfg(feature = "hx")]
+            decoder: None,
+        })
+    }
+
+    /// Wait up to `timeout_ms` for the next frame. Video frames over the limit
+    /// are dropped without being converted
+    pub fn capture(&mut self, timeout_ms: u32, limit: &mut FrameRateLimit) -> Captured {
+        use super::sys;
+        use log::warn;
+        use std::time::Instant;
+
+        // SAFETY: all-zero frames are valid "empty" descriptors for the SDK to fill in
+        let mut frame: sys::NDIlib_video_frame_v2_t = unsafe { std::mem::zeroed() };
+        let mut audio: sys::NDIlib_audio_frame_v2_t = unsafe { std::mem::zeroed() };
+        let mut metadata: sys::NDIlib_metadata_frame_t = unsafe { std::mem::zeroed() };
+        // SAFETY: instance is valid until drop
+        let frame_type = unsafe {
+            sys::NDIlib_recv_capture_v2(
+                self.instance,
+                &mut frame,
+                &mut audio,
+                &mut metadata,
+                timeout_ms,
+            )
+        };
+
+        match frame_type {
+            #[cfg(feature = "hx")]
+            sys::NDIlib_frame_type_video
+                if crate::video::hx::Codec::from_fourcc(frame.FourCC).is_some() =>
+            {
+                // Compressed frames carry their size in `data_size_in_bytes`
+                let bytes = frame.line_stride_in_bytes.max(0) as usize;
+                let decoded = if frame.p_data.is_null() {
+                    Ok(None)
+                } else {
+                    // SAFETY: p_data holds the compressed packet until the frame is freed
+                    let data = unsafe { std::slice::from_raw_parts(frame.p_data, bytes) };
+                    self.decode_hx(data, frame.xres.max(0) as u32, frame.yres.max(0) as u32)
+                };
+                // SAFETY: frame was filled by NDIlib_recv_capture_v2 on this instance
+                unsafe { sys::NDIlib_recv_free_video_v2(self.instance, &frame) };
+
+                // Every packet must be decoded; only the output is throttled
+                match decoded {
+                    Ok(Some(_)) if !limit.admit(Instant::now()) => Captured::Skipped(bytes),
+                    Ok(Some(video)) => Captured::Video(video, SentFormat::of(&frame), bytes),
+                    Ok(None) => Captured::Nothing,
+                    Err(e) => {
+                        warn!("Dropping NDI|HX frame: {}", e);
+                        self.decoder = None;
+                        Captured::Nothing
+                    }
+                }
+            }
+            sys::NDIlib_frame_type_video => {
+                let stride = frame.line_stride_in_bytes.max(0) as usize;
+                let height = frame.yres.max(0) as usize;
+                let bytes = stride * height;
+                if !limit.admit(Instant::now()) {
+                    // SAFETY: frame was filled by NDIlib_recv_capture_v2 on this instance
+                    unsafe { sys::NDIlib_recv_free_video_v2(self.instance, &frame) };
+                    return Captured::Skipped(bytes);
+                }
+
+                let converted = if frame.p_data.is_null() {
+                    Err(anyhow::anyhow!("Video frame without data"))
+                } else {
+                    // SAFETY: p_data holds `stride * yres` bytes until the frame is freed
+                    let data = unsafe { std::slice::from_raw_parts(frame.p_data, bytes) };
+                    convert_video(
+                        frame.FourCC,
+                        frame.xres as u32,
+                        frame.yres as u32,
+                        stride,
+                        data,
+                    )
+                };
+                // SAFETY: frame was filled by NDIlib_recv_capture_v2 on this instance
+                unsafe { sys::NDIlib_recv_free_video_v2(self.instance, &frame) };
+
+                match converted {
+                    Ok(video) => Captured::Video(video, SentFormat::of(&frame), bytes),
+                    Err(e) => {
+                        warn!("Dropping video frame: {}", e);
+                        Captured::Nothing
+                    }
+                }
+            }
+            sys::NDIlib_frame_type_audio => {
+                let channels = audio.no_channels.max(0) as usize;
+                let samples = audio.no_samples.max(0) as usize;
+                let stride = audio.channel_stride_in_bytes.max(0) as usize / 4;
+                let bytes = channels * samples * 4;
+
+                let chunk = (!audio.p_data.is_null() && channels > 0).then(|| {
+                    // SAFETY: p_data holds `channels` planes of `stride` floats until freed
+                    let planar =
+                        unsafe { std::slice::from_raw_parts(audio.p_data, channels * stride) };
+                    AudioChunk {
+                        sample_rate: audio.sample_rate.max(0) as u32,
+                        channels: channels as u16,
+                        samples: interleave(planar, channels, samples, stride),
+                    }
+                });
+                // SAFETY: audio was filled by NDIlib_recv_capture_v2 on this instance
+                unsafe { sys::NDIlib_recv_free_audio_v2(self.instance, &audio) };
+
+                match chunk {
+                    Some(chunk) => Captured::Audio(chunk, bytes),
+                    None => Captured::Nothing,
+                }
+            }
+            sys::NDIlib_frame_type_metadata => {
+                let xml = (!metadata.p_data.is_null()).then(|| {
+                    // SAFETY: p_data is a NUL-terminated string until the frame is freed
+                    unsafe { std::ffi::CStr::from_ptr(metadata.p_data) }
+                        .to_string_lossy()
+                        .into_owned()
+                });
+                // SAFETY: metadata was filled by NDIlib_recv_capture_v2 on this instance
+                unsafe { sys::NDIlib_recv_free_metadata(self.instance, &metadata) };
+
+                match xml {
+                    Some(xml) => {
+                        let bytes = metadata.length.max(0) as usize;
+                        Captured::Metadata(xml, bytes)
+                    }
+                    None => Captured::Nothing,
+                }
+            }
+            sys::NDIlib_frame_type_error => Captured::Error,
+            _ => Captured::Nothing,
+        }
+    }
+
+    /// Decode a compressed NDI|HX packet, (re)starting the decoder when the stream changes
+    #[cfg(feature = "hx")]
+    fn decode_hx(&mut self, data: &[u8], width: u32, height: u32) -> Result<Option<VideoFrame>> {
+        use crate::video::hx::{parse_packet, HxDecoder};
+
+        let packet = parse_packet(data)?;
+        let decoder = match self.decoder.take() {
+            Some(decoder) if decoder.matches(packet.codec, width, height) => decoder,
+            _ => HxDecoder::start(packet.codec, width, height)?,
+        };
+        let decoder = self.decoder.insert(decoder);
+        decoder.decode(&packet)
+    }
+
+    /// Send tally to the source (the sender lights its tally LEDs)
+    pub fn set_tally(&mut self, tally: TallyState) {
+        let tally = super::sys::NDIlib_tally_t {
+            on_program: tally.program,
+            on_preview: tally.preview,
+        };
+        // SAFETY: instance is a live receiver and tally outlives the call
+        unsafe { super::sys::NDIlib_recv_set_tally(self.instance, &tally) };
+    }
+
+    /// Whether the sender is connected (metadata can only be sent once it is)
+    pub fn is_connected(&self) -> bool {
+        // SAFETY: instance is a live receiver
+        unsafe { super::sys::NDIlib_recv_get_no_connections(self.instance) > 0 }
+    }
+
+    /// Send a metadata frame upstream to the sender (e.g. a PTZ command)
+    pub fn send_metadata(&mut self, xml: &str) -> Result<()> {
+        use anyhow::Context;
+
+        let data = std::ffi::CString::new(xml).context("Invalid metadata")?;
+        let frame = super::sys::NDIlib_metadata_frame_t {
+            length: data.as_bytes_with_nul().len() as i32,
+            timecode: i64::MAX,
+            p_data: data.as_ptr() as *mut _,
+        };
+        // SAFETY: instance is a live receiver and frame (and its data) outlive the call
+        if unsafe { super::sys::NDIlib_recv_send_metadata(self.instance, &frame) } {
+            Ok(())
+        } else {
+            anyhow::bail!("The sender did not accept the metadata")
+        }
+    }
+}
+
+#[cfg(feature = "ndi")]
+impl Drop for RecvInstance {
+    fn drop(&mut self) {
+        // SAFETY: instance was created by NDIlib_recv_create_v3 and is destroyed once
+        unsafe { super::sys::NDIlib_recv_destroy(self.instance) }
+    }
+}
+
+/// Placeholder receiver used when built without the NDI SDK
+#[cfg(not(feature = "ndi"))]
+pub struct RecvInstance;
+
+#[cfg(not(feature = "ndi"))]
+impl RecvInstance {
+    pub fn connect(_source: &NdiSource, _bandwidth: ReceiveBandwidth) -> Result<Self> {
+        anyhow::bail!("RusTV was built without NDI SDK support (rebuild with --features ndi)")
+    }
+
+    pub fn capture(&mut self, timeout_ms: u32, _limit: &mut FrameRateLimit) -> Captured {
+        std::thread::sleep(std::time::Duration::from_millis(timeout_ms as u64));
+        Captured::Nothing
+    }
+
+    pub fn set_tally(&mut self, _tally: TallyState) {}
+
+    pub fn is_connected(&self) -> bool {
+        false
+    }
+
+    pub fn send_metadata(&mut self, _xml: &str) -> Result<()> {
+        anyhow::bail!("RusTV was built without NDI SDK support (rebuild with --features ndi)")
+    }
+}
+
+/// Color format requested from the SDK. UYVY (RGBA only for sources with alpha)
+/// halves the bytes copied per frame and is converted by `convert.rs`; HX builds
+/// ask for compressed frames instead
+#[cfg(feature = "ndi")]
+fn recv_color_format() -> i32 {
+    if cfg!(feature = "hx") {
+        super::sys::NDIlib_recv_color_format_compressed_v5
+    } else {
+        super::sys::NDIlib_recv_color_format_UYVY_RGBA
+    }
+}
+
+/// Convert a received video frame to RGBA
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+fn convert_video(
+    fourcc: u32,
+    width: u32,
+    height: u32,
+    stride: usize,
+    data: &[u8],
+) -> Result<VideoFrame> {
+    use crate::video::convert::{to_rgba, PixelFormat};
+
+    match PixelFormat::from_fourcc(fourcc) {
+        Some(format) => to_rgba(format, width, height, stride, data),
+        None => anyhow::bail!("Unsupported NDI pixel format {:?}", fourcc.to_le_bytes()),
+    }
+}
+
+/// Interleave planar audio (one plane of `stride` samples per channel)
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+fn interleave(planar: &[f32], channels: usize, samples: usize, stride: usize) -> Vec<f32> {
+    let samples = samples.min(stride);
+    let mut interleaved = Vec::with_capacity(channels * samples);
+    for i in 0..samples {
+        for channel in 0..channels {
+            interleaved.push(planar.get(channel * stride + i).copied().unwrap_or(0.0));
+        }
+    }
+    interleaved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interleave() {
+        // Two channels of three samples, planes padded to a stride of four
+        let planar = [1.0, 2.0, 3.0, 0.0, -1.0, -2.0, -3.0, 0.0];
+        assert_eq!(
+            interleave(&planar, 2, 3, 4),
+            vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "ndi", not(feature = "hx")))]
+    fn test_receives_uyvy() {
+        assert_eq!(
+            recv_color_format(),
+            super::super::sys::NDIlib_recv_color_format_UYVY_RGBA
+        );
+    }
+
+    #[test]
+    fn test_convert_video() {
+        // One white and one black pixel pair, rows padded to a stride of 12
+        let uyvy = [
+            128, 235, 128, 235, 128, 16, 128, 16, 0, 0, 0, 0, //
+            128, 235, 128, 235, 128, 16, 128, 16, 0, 0, 0, 0,
+        ];
+        let frame = convert_video(u32::from_le_bytes(*b"UYVY"), 4, 2, 12, &uyvy).unwrap();
+        assert_eq!((frame.width, frame.height), (4, 2));
+        assert_eq!(frame.data[..4], [255, 255, 255, 255]);
+        assert_eq!(frame.data[12..16], [0, 0, 0, 255]);
+
+        let rgba = [1, 2, 3, 4];
+        let frame = convert_video(u32::from_le_bytes(*b"RGBA"), 1, 1, 4, &rgba).unwrap();
+        assert_eq!(frame.data, rgba);
+
+        assert!(convert_video(u32::from_le_bytes(*b"P216"), 4, 2, 16, &[0; 32]).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Details of a receiver's connection (peer, local interface, transport, codec)
+//! for checking the path its traffic takes without a packet capture
+
+use super::{
+    ConnectionState, NdiReceiver, NdiSource, NdiTransport, ReceiveBandwidth, ReceiverStats,
+};
+use crate::network::format_bit_rate;
+use std::net::IpAddr;
+
+/// What is known about one receiver's connection
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionInfo {
+    /// Sender address and port, from the source URL
+    pub peer: Option<String>,
+    /// Local address the traffic arrives on
+    pub local_interface: Option<IpAddr>,
+    /// Transport asked of the sender
+    pub transport: NdiTransport,
+    pub bandwidth: ReceiveBandwidth,
+    pub state: ConnectionState,
+    /// Codec or pixel format as sent (e.g. "UYVY", "H264")
+    pub codec: Option<String>,
+    /// Sender's product and version, if it announced them
+    pub product: Option<String>,
+    /// Version of the local NDI runtime
+    pub runtime: Option<String>,
+    pub stats: ReceiverStats,
+}
+
+impl ConnectionInfo {
+    pub fn new(
+        source: &NdiSource,
+        receiver: &NdiReceiver,
+        transport: NdiTransport,
+        local_interface: Option<IpAddr>,
+    ) -> Self {
+        let peer = source
+            .url
+            .split("://")
+            .last()
+            .and_then(|rest| rest.split('/').next())
+            .filter(|peer| !peer.is_empty())
+            .map(str::to_string);
+        let product = receiver
+            .receive_metadata()
+            .ok()
+            .and_then(|metadata| metadata.product)
+            .map(|product| {
+                let name = [product.manufacturer, product.long_name, product.version];
+                let name: Vec<String> = name.into_iter().filter(|s| !s.is_empty()).collect();
+                name.join(" ")
+            })
+            .filter(|product| !product.is_empty());
+
+        Self {
+            peer,
+            local_interface,
+            transport,
+            bandwidth: receiver.bandwidth(),
+            state: receiver.connection_state(),
+            codec: receiver.stream_info().pixel_format,
+            product,
+            runtime: super::runtime_version(),
+            stats: receiver.stats(),
+        }
+    }
+
+    /// Label and value of each detail, for display
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let unknown = || "unknown".to_string();
+        vec![
+            ("Peer", self.peer.clone().unwrap_or_else(unknown)),
+            (
+                "Interface",
+                self.local_interface
+                    .map_or_else(unknown, |ip| ip.to_string()),
+            ),
+            ("Transport", self.transport.name().to_string()),
+            ("Bandwidth", self.bandwidth.name().to_string()),
+            ("State", self.state.to_string()),
+            ("Codec", self.codec.clone().unwrap_or_else(unknown)),
+            ("Sender", self.product.clone().unwrap_or_else(unknown)),
+            (
+                "NDI runtime",
+                self.runtime.clone().unwrap_or_else(|| "not loaded".into()),
+            ),
+            ("Bit rate", format_bit_rate(self.stats.bytes_per_second)),
+            ("Jitter", format!("{:.1} ms", self.stats.jitter_ms)),
+            ("Dropped", format!("{} frames", self.stats.frames_dropped)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_info_rows() {
+        let source = NdiSource::new("CAM-1 (Wide)".into(), "ndi://10.0.0.21:5961".into());
+        let mut info = ConnectionInfo::new(&source, &NdiReceiver::new(), NdiTransport::Tcp, None);
+        assert_eq!(info.peer.as_deref(), Some("10.0.0.21:5961"));
+        info.codec = Some("H264".into());
+
+        let rows = info.rows();
+        let row = |label: &str| rows.iter().find(|(l, _)| *l == label).unwrap().1.clone();
+        assert_eq!(row("Transport"), "TCP");
+        assert_eq!(row("Interface"), "unknown");
+        assert_eq!(row("Codec"), "H264");
+        assert_eq!(row("Dropped"), "0 frames");
+    }
+}
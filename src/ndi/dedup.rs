@@ -0,0 +1,159 @@
+//! De-duplication of sources a dual-homed sender announces on several network
+//! interfaces, keeping the copy on the preferred network
+
+use super::NdiSource;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A network, written "10.10.0.0/16" (or a single address)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subnet {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Subnet {
+    /// Whether an address is on this network
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Subnet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = address
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid network '{}'", s))?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse()
+                .ok()
+                .filter(|p| *p <= bits)
+                .with_context(|| format!("Invalid prefix length in '{}'", s))?,
+            None => bits,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// What identifies a stream whichever interface it is announced on: the machine
+/// (without any domain, case-insensitive) and the stream name
+pub fn identity(source: &NdiSource) -> (String, String) {
+    let machine = source.machine_name();
+    let host = machine.split('.').next().unwrap_or(machine);
+    (host.to_lowercase(), source.stream_name().to_string())
+}
+
+/// Position of the first preferred network a source's address is on
+/// (after every preferred network if none)
+fn rank(source: &NdiSource, preference: &[Subnet]) -> usize {
+    source
+        .address()
+        .and_then(|ip| preference.iter().position(|net| net.contains(ip)))
+        .unwrap_or(preference.len())
+}
+
+/// Keep one copy of every stream: the one on the most preferred network, or
+/// the first announced. Returns the copies dropped
+pub fn deduplicate(sources: &mut Vec<NdiSource>, preference: &[Subnet]) -> Vec<NdiSource> {
+    let mut kept: Vec<NdiSource> = Vec::with_capacity(sources.len());
+    let mut dropped = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for source in sources.drain(..) {
+        match index.get(&identity(&source)) {
+            Some(&i) if rank(&source, preference) < rank(&kept[i], preference) => {
+                dropped.push(std::mem::replace(&mut kept[i], source));
+            }
+            Some(_) => dropped.push(source),
+            None => {
+                index.insert(identity(&source), kept.len());
+                kept.push(source);
+            }
+        }
+    }
+    *sources = kept;
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str, ip: &str) -> NdiSource {
+        NdiSource::new(name.to_string(), format!("ndi://{}:5961", ip))
+    }
+
+    #[test]
+    fn test_subnet() {
+        let subnet: Subnet = "10.10.0.0/16".parse().unwrap();
+        assert!(subnet.contains("10.10.4.20".parse().unwrap()));
+        assert!(!subnet.contains("10.11.0.1".parse().unwrap()));
+        assert!(!subnet.contains("fe80::1".parse().unwrap()));
+        let host: Subnet = "192.168.1.5".parse().unwrap();
+        assert_eq!(host.to_string(), "192.168.1.5/32");
+        assert!("0.0.0.0/0"
+            .parse::<Subnet>()
+            .unwrap()
+            .contains("8.8.8.8".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<Subnet>().is_err());
+        assert!("studio".parse::<Subnet>().is_err());
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let mut sources = vec![
+            source("STUDIO-PC (Slides)", "192.168.1.5"),
+            source("CAM-1 (Wide)", "10.10.0.21"),
+            source("studio-pc.local (Slides)", "10.10.0.5"),
+            source("STUDIO-PC (Lyrics)", "192.168.1.5"),
+        ];
+        let dropped = deduplicate(&mut sources, &["10.10.0.0/16".parse().unwrap()]);
+        let names: Vec<&str> = sources.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "ndi://10.10.0.5:5961",
+                "ndi://10.10.0.21:5961",
+                "ndi://192.168.1.5:5961"
+            ]
+        );
+        assert_eq!(dropped[0].url, "ndi://192.168.1.5:5961");
+
+        // Without a preference the first announced copy stays
+        let mut sources = vec![
+            source("STUDIO-PC (Slides)", "192.168.1.5"),
+            source("STUDIO-PC (Slides)", "10.10.0.5"),
+        ];
+        deduplicate(&mut sources, &[]);
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].url, "ndi://192.168.1.5:5961");
+    }
+}
@@ -1,14 +1,180 @@
-use super::NdiSource;
+use super::bridge;
+use super::dedup::{self, Subnet};
+use super::finder::{Finder, FinderOptions};
+use super::tags;
+use super::{NdiSource, StreamInfo};
+use crate::config::{ApprovalConfig, BridgeConfig, NdiConfig, ScanPolicy, StaticSource};
+use crate::supervisor::Supervisor;
 use anyhow::Result;
-use log::{debug, info, warn};
+use log::{debug, error, info, warn};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tokio::time;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Default time between source list refreshes
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Sources that changed this recently keep scans fast
+const RECENT_CHANGE: Duration = Duration::from_secs(15);
+
+/// Longest wait before checking whether the scan rate changed
+const WAIT_SLICE: Duration = Duration::from_millis(500);
+
+/// Source changes kept for subscribers that fall behind
+const EVENT_CAPACITY: usize = 256;
+
+/// A change to the list of discovered sources
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum DiscoveryEvent {
+    SourceAdded(NdiSource),
+    SourceRemoved(NdiSource),
+    /// A source kept its name but changed otherwise (e.g. its address or groups)
+    SourceChanged(NdiSource),
+}
+
+impl DiscoveryEvent {
+    /// Apply the change to a subscriber's copy of the source list
+    pub fn apply(&self, sources: &mut Vec<NdiSource>) {
+        match self {
+            DiscoveryEvent::SourceAdded(source) | DiscoveryEvent::SourceChanged(source) => {
+                match sources.iter_mut().find(|s| s.name == source.name) {
+                    Some(existing) => *existing = source.clone(),
+                    None => sources.push(source.clone()),
+                }
+            }
+            DiscoveryEvent::SourceRemoved(source) => sources.retain(|s| s.name != source.name),
+        }
+    }
+}
+
+/// The events that turn one source list into another (sources are told apart by name)
+fn changes(old: &[NdiSource], new: &[NdiSource]) -> Vec<DiscoveryEvent> {
+    let removed = old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.name == o.name))
+        .map(|o| DiscoveryEvent::SourceRemoved(o.clone()));
+    let added_or_changed = new
+        .iter()
+        .filter_map(|n| match old.iter().find(|o| o.name == n.name) {
+            None => Some(DiscoveryEvent::SourceAdded(n.clone())),
+            // Being seen again is not a change
+            Some(o) if *o != seen_at(n, o.last_seen_ms) => {
+                Some(DiscoveryEvent::SourceChanged(n.clone()))
+            }
+            Some(_) => None,
+        });
+    removed.chain(added_or_changed).collect()
+}
+
+fn seen_at(source: &NdiSource, last_seen_ms: Option<u64>) -> NdiSource {
+    NdiSource {
+        last_seen_ms,
+        ..source.clone()
+    }
+}
+
+/// Video format a receiver reported for a source
+#[derive(Debug, Clone, PartialEq)]
+struct StreamFormat {
+    resolution: (u32, u32),
+    frame_rate: Option<f64>,
+    codec: Option<String>,
+}
+
+impl StreamFormat {
+    fn apply(&self, source: &mut NdiSource) {
+        source.resolution = Some(self.resolution);
+        source.frame_rate = self.frame_rate;
+        source.codec = self.codec.clone();
+    }
+}
+
+/// Fill in what discovery knows about sources beyond their names
+fn describe(sources: &mut [NdiSource], formats: &HashMap<String, StreamFormat>) {
+    for source in sources {
+        source.ip_address = source.address();
+        source.machine = Some(source.machine_name().to_string());
+        if let Some(format) = formats.get(&source.name) {
+            format.apply(source);
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Picks the time between scans from operator activity and source changes
+#[derive(Debug, Clone)]
+struct ScanPacing {
+    policy: ScanPolicy,
+    /// Time between scans when neither busy nor idle
+    normal: Duration,
+    /// Sources are being picked (e.g. the routing panel is open)
+    browsing: bool,
+    last_input: Instant,
+    last_change: Instant,
+}
+
+impl ScanPacing {
+    fn new(policy: ScanPolicy, normal: Duration, now: Instant) -> Self {
+        Self {
+            policy,
+            normal,
+            browsing: false,
+            last_input: now,
+            last_change: now,
+        }
+    }
+
+    fn interval(&self, now: Instant) -> Duration {
+        if !self.policy.adaptive {
+            return self.normal;
+        }
+        let since_change = now.saturating_duration_since(self.last_change);
+        let since_input = now.saturating_duration_since(self.last_input);
+        if self.browsing || since_change < RECENT_CHANGE {
+            Duration::from_secs(self.policy.fast_interval.max(1))
+        } else if since_change.min(since_input) >= Duration::from_secs(self.policy.idle_after) {
+            Duration::from_secs(self.policy.idle_interval.max(1))
+        } else {
+            self.normal
+        }
+    }
+}
 
 /// NDI source discovery service
 pub struct NdiDiscovery {
     sources: Arc<Mutex<Vec<NdiSource>>>,
     is_running: Arc<Mutex<bool>>,
+    /// Finder settings (groups and extra IPs)
+    options: FinderOptions,
+    /// Sources from configuration, always listed
+    static_sources: Vec<NdiSource>,
+    /// Time between source list refreshes, adapted to activity
+    pacing: Arc<Mutex<ScanPacing>>,
+    /// Friendly names by NDI source name
+    aliases: Arc<Mutex<BTreeMap<String, String>>>,
+    /// Tags by source name or pattern
+    tags: Arc<Mutex<BTreeMap<String, Vec<String>>>>,
+    /// Remote sites whose sources are labelled with the site
+    bridges: Vec<BridgeConfig>,
+    /// Networks preferred when a source is announced on several interfaces
+    interface_preference: Vec<Subnet>,
+    /// Which discovered sources may be routed
+    approval: Arc<Mutex<ApprovalConfig>>,
+    /// Where source changes are published
+    events: broadcast::Sender<DiscoveryEvent>,
+    /// Restarts the scan loop if it fails
+    supervisor: Option<Arc<Supervisor>>,
+    /// Video formats receivers reported, by NDI source name
+    formats: Arc<Mutex<HashMap<String, StreamFormat>>>,
 }
 
 impl NdiDiscovery {
@@ -16,9 +182,79 @@ impl NdiDiscovery {
         Self {
             sources: Arc::new(Mutex::new(Vec::new())),
             is_running: Arc::new(Mutex::new(false)),
+            options: FinderOptions::default(),
+            static_sources: Vec::new(),
+            pacing: Arc::new(Mutex::new(ScanPacing::new(
+                ScanPolicy {
+                    adaptive: false,
+                    ..ScanPolicy::default()
+                },
+                DEFAULT_INTERVAL,
+                Instant::now(),
+            ))),
+            aliases: Arc::new(Mutex::new(BTreeMap::new())),
+            tags: Arc::new(Mutex::new(BTreeMap::new())),
+            bridges: Vec::new(),
+            interface_preference: Vec::new(),
+            approval: Arc::new(Mutex::new(ApprovalConfig::default())),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            supervisor: None,
+            formats: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Create a discovery service using the `[ndi]` configuration. Static sources
+    /// are listed straight away, before the first scan
+    pub fn from_config(config: &NdiConfig) -> Self {
+        let static_sources: Vec<NdiSource> =
+            config.static_sources.iter().map(static_source).collect();
+        let mut listed = static_sources.clone();
+        apply_aliases(&mut listed, &config.aliases);
+        tags::apply_tags(&mut listed, &config.tags);
+        Self {
+            sources: Arc::new(Mutex::new(listed)),
+            options: FinderOptions {
+                groups: config.groups.clone(),
+                // Bridge Joins may be on segments mDNS doesn't reach
+                extra_ips: config
+                    .extra_ips
+                    .iter()
+                    .chain(config.bridges.iter().map(|b| &b.join_address))
+                    .cloned()
+                    .collect(),
+                discovery_server: config.discovery_server.clone(),
+            },
+            static_sources,
+            pacing: Arc::new(Mutex::new(ScanPacing::new(
+                config.scan.clone(),
+                Duration::from_secs(config.discovery_interval.max(1)),
+                Instant::now(),
+            ))),
+            aliases: Arc::new(Mutex::new(config.aliases.clone())),
+            tags: Arc::new(Mutex::new(config.tags.clone())),
+            bridges: config.bridges.clone(),
+            interface_preference: config
+                .interface_preference
+                .iter()
+                .filter_map(|net| match net.parse() {
+                    Ok(subnet) => Some(subnet),
+                    Err(e) => {
+                        warn!("Ignoring interface preference: {}", e);
+                        None
+                    }
+                })
+                .collect(),
+            approval: Arc::new(Mutex::new(config.approval.clone())),
+            ..Self::new()
+        }
+    }
+
+    /// Restart the scan loop with backoff if it fails
+    pub fn with_supervisor(mut self, supervisor: Arc<Supervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
     /// Start automatic NDI source discovery
     pub async fn start(&self) -> Result<()> {
         let mut is_running = self.is_running.lock().unwrap();
@@ -31,30 +267,116 @@ impl NdiDiscovery {
 
         info!("Starting NDI source discovery...");
 
+        let options = self.options.clone();
         let sources = Arc::clone(&self.sources);
         let is_running = Arc::clone(&self.is_running);
+        let static_sources = self.static_sources.clone();
+        let pacing = Arc::clone(&self.pacing);
+        let aliases = Arc::clone(&self.aliases);
+        let tags = Arc::clone(&self.tags);
+        let bridges = self.bridges.clone();
+        let interface_preference = self.interface_preference.clone();
+        let approval = Arc::clone(&self.approval);
+        let events = self.events.clone();
+        let formats = Arc::clone(&self.formats);
+
+        // The SDK finder blocks while waiting, so it runs on its own thread. A
+        // restart creates a new finder
+        let scan = move || -> Result<()> {
+            let mut finder = match Finder::new(&options) {
+                Ok(finder) => Some(finder),
+                Err(e) => {
+                    warn!(
+                        "NDI discovery unavailable, listing static sources only: {}",
+                        e
+                    );
+                    None
+                }
+            };
 
-        tokio::spawn(async move {
             while {
                 let running = *is_running.lock().unwrap();
                 running
             } {
-                // Simulate NDI source discovery
-                // In a real implementation, this would use the NDI SDK's find functionality
                 debug!("Scanning for NDI sources...");
 
-                // For now, we'll create a mock discovery mechanism
-                // Real implementation would use ndi-sdk crate's finder
-                let discovered = Self::discover_ndi_sources().await;
+                // Wait in slices so a change of pace (or stop) takes effect quickly
+                let started = Instant::now();
+                loop {
+                    let interval = pacing.lock().unwrap().interval(Instant::now());
+                    let left = interval.saturating_sub(started.elapsed());
+                    if left.is_zero() || !*is_running.lock().unwrap() {
+                        break;
+                    }
+                    let slice = left.min(WAIT_SLICE);
+                    match finder.as_mut() {
+                        // Changed sources are picked up right away
+                        Some(finder) => {
+                            if finder.wait_for_sources(slice.as_millis() as u32) {
+                                break;
+                            }
+                        }
+                        None => thread::sleep(slice),
+                    }
+                }
 
-                {
-                    let mut sources_lock = sources.lock().unwrap();
-                    *sources_lock = discovered;
+                let mut discovered = static_sources.clone();
+                if let Some(finder) = finder.as_mut() {
+                    let seen = now_ms();
+                    // Static sources take precedence over discovered ones of the same name
+                    let mut found: Vec<NdiSource> = finder
+                        .current_sources()
+                        .into_iter()
+                        .filter(|source| !discovered.iter().any(|s| s.name == source.name))
+                        .collect();
+                    for source in &mut found {
+                        source.last_seen_ms = Some(seen);
+                    }
+                    for duplicate in dedup::deduplicate(&mut found, &interface_preference) {
+                        debug!(
+                            "Ignoring {} at {}, also announced on a preferred interface",
+                            duplicate.name, duplicate.url
+                        );
+                    }
+                    discovered.extend(found);
                 }
 
-                time::sleep(Duration::from_secs(5)).await;
+                describe(&mut discovered, &formats.lock().unwrap());
+                bridge::tag_sites(&mut discovered, &bridges);
+                apply_aliases(&mut discovered, &aliases.lock().unwrap());
+                tags::apply_tags(&mut discovered, &tags.lock().unwrap());
+                let static_count = static_sources.len();
+                apply_approval(&mut discovered[static_count..], &approval.lock().unwrap());
+
+                let mut sources_lock = sources.lock().unwrap();
+                let changed = changes(&sources_lock, &discovered);
+                if !changed.is_empty() {
+                    pacing.lock().unwrap().last_change = Instant::now();
+                }
+                *sources_lock = discovered;
+                drop(sources_lock);
+                for event in changed {
+                    if let DiscoveryEvent::SourceAdded(source) = &event {
+                        if source.quarantined {
+                            warn!("New NDI source awaiting approval: {}", source.name);
+                        }
+                    }
+                    // No subscribers is fine
+                    let _ = events.send(event);
+                }
             }
-        });
+            Ok(())
+        };
+        match &self.supervisor {
+            Some(supervisor) => supervisor.spawn_thread("NDI discovery", scan),
+            None => {
+                thread::spawn(move || {
+                    if let Err(e) = scan() {
+                        error!("NDI discovery stopped: {}", e);
+                    }
+                });
+            }
+        }
 
         Ok(())
     }
@@ -66,35 +388,142 @@ impl NdiDiscovery {
         info!("Stopped NDI source discovery");
     }
 
+    /// Scan fast while sources are being picked (e.g. the routing panel is open)
+    pub fn set_browsing(&self, browsing: bool) {
+        self.pacing.lock().unwrap().browsing = browsing;
+    }
+
+    /// The operator did something; discovery is not idle
+    pub fn operator_input(&self) {
+        self.pacing.lock().unwrap().last_input = Instant::now();
+    }
+
     /// Get currently discovered sources
     pub fn get_sources(&self) -> Vec<NdiSource> {
         self.sources.lock().unwrap().clone()
     }
 
-    /// Internal method to discover NDI sources
-    async fn discover_ndi_sources() -> Vec<NdiSource> {
-        // This is a placeholder implementation
-        // Real implementation would use the NDI SDK's finder API
-        //
-        // Example real implementation would look like:
-        // let finder = ndi::Finder::new();
-        // finder.wait_for_sources(timeout);
-        // let sources = finder.get_current_sources();
+    /// A discovered source by NDI name, including when it was last seen (which
+    /// is not published as a change)
+    pub fn get_source(&self, name: &str) -> Option<NdiSource> {
+        self.sources
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|s| s.name == name)
+            .cloned()
+    }
+
+    /// Receive every source change from now on. Subscribe before calling
+    /// `get_sources()` for the starting list, so no change is missed in between
+    pub fn subscribe(&self) -> broadcast::Receiver<DiscoveryEvent> {
+        self.events.subscribe()
+    }
+
+    /// Friendly names by NDI name
+    pub fn aliases(&self) -> BTreeMap<String, String> {
+        self.aliases.lock().unwrap().clone()
+    }
+
+    /// Tags by source name pattern
+    pub fn tags(&self) -> BTreeMap<String, Vec<String>> {
+        self.tags.lock().unwrap().clone()
+    }
 
-        debug!("Discovering NDI sources on network...");
+    /// Replace the friendly names, relabelling the listed sources
+    pub fn set_aliases(&self, aliases: BTreeMap<String, String>) {
+        let mut sources = self.sources.lock().unwrap();
+        for source in sources.iter_mut() {
+            let alias = aliases.get(&source.name).cloned();
+            if source.alias != alias {
+                source.alias = alias;
+                let _ = self
+                    .events
+                    .send(DiscoveryEvent::SourceChanged(source.clone()));
+            }
+        }
+        *self.aliases.lock().unwrap() = aliases;
+    }
 
-        // Return mock sources for demonstration
-        // In production, this would query the actual NDI network
-        vec![]
+    /// Replace the tags by pattern, retagging the listed sources
+    pub fn set_tags(&self, tags: BTreeMap<String, Vec<String>>) {
+        let mut sources = self.sources.lock().unwrap();
+        for source in sources.iter_mut() {
+            let mut tagged = source.clone();
+            tags::apply_tags(std::slice::from_mut(&mut tagged), &tags);
+            if tagged.tags != source.tags {
+                *source = tagged;
+                let _ = self
+                    .events
+                    .send(DiscoveryEvent::SourceChanged(source.clone()));
+            }
+        }
+        *self.tags.lock().unwrap() = tags;
+    }
+
+    /// Approve a quarantined source (by NDI name) so it can be routed
+    pub fn approve(&self, name: &str) {
+        self.approval
+            .lock()
+            .unwrap()
+            .approved
+            .insert(name.to_string());
+        let mut sources = self.sources.lock().unwrap();
+        for source in sources
+            .iter_mut()
+            .filter(|s| s.name == name && s.quarantined)
+        {
+            info!("Approved NDI source: {}", name);
+            source.quarantined = false;
+            let _ = self
+                .events
+                .send(DiscoveryEvent::SourceChanged(source.clone()));
+        }
+    }
+
+    /// Record the video format a receiver found a source sending, so the source
+    /// list shows it
+    pub fn record_stream(&self, name: &str, info: &StreamInfo) {
+        if !info.has_video() {
+            return;
+        }
+        let format = StreamFormat {
+            resolution: (info.width, info.height),
+            frame_rate: info.frame_rate(),
+            codec: info.pixel_format.clone(),
+        };
+        let mut formats = self.formats.lock().unwrap();
+        if formats.get(name) == Some(&format) {
+            return;
+        }
+        let mut sources = self.sources.lock().unwrap();
+        for source in sources.iter_mut().filter(|s| s.name == name) {
+            format.apply(source);
+            let _ = self
+                .events
+                .send(DiscoveryEvent::SourceChanged(source.clone()));
+        }
+        formats.insert(name.to_string(), format);
     }
 
     /// Manually add a source (useful for static sources)
     #[allow(dead_code)]
-    pub fn add_source(&self, source: NdiSource) {
+    pub fn add_source(&self, mut source: NdiSource) {
+        describe(
+            std::slice::from_mut(&mut source),
+            &self.formats.lock().unwrap(),
+        );
+        source.alias = self.aliases.lock().unwrap().get(&source.name).cloned();
+        tags::apply_tags(
+            std::slice::from_mut(&mut source),
+            &self.tags.lock().unwrap(),
+        );
+        source.quarantined = !self.approval.lock().unwrap().is_approved(&source.name);
         let mut sources = self.sources.lock().unwrap();
         if !sources.iter().any(|s| s.url == source.url) {
             info!("Added NDI source: {}", source);
-            sources.push(source);
+            sources.push(source.clone());
+            let _ = self.events.send(DiscoveryEvent::SourceAdded(source));
         }
     }
 
@@ -102,9 +531,34 @@ impl NdiDiscovery {
     #[allow(dead_code)]
     pub fn remove_source(&self, url: &str) -> bool {
         let mut sources = self.sources.lock().unwrap();
-        let len_before = sources.len();
-        sources.retain(|s| s.url != url);
-        sources.len() < len_before
+        let Some(index) = sources.iter().position(|s| s.url == url) else {
+            return false;
+        };
+        let source = sources.remove(index);
+        let _ = self.events.send(DiscoveryEvent::SourceRemoved(source));
+        true
+    }
+}
+
+/// Set the friendly name of every source that has one
+/// A source from `static_sources` in `[ndi]`
+pub fn static_source(config: &StaticSource) -> NdiSource {
+    NdiSource {
+        static_source: true,
+        ..NdiSource::new(config.name.clone(), config.url.clone())
+    }
+}
+
+fn apply_aliases(sources: &mut [NdiSource], aliases: &BTreeMap<String, String>) {
+    for source in sources {
+        source.alias = aliases.get(&source.name).cloned();
+    }
+}
+
+/// Quarantine every source that has not been approved
+fn apply_approval(sources: &mut [NdiSource], approval: &ApprovalConfig) {
+    for source in sources {
+        source.quarantined = !approval.is_approved(&source.name);
     }
 }
 
@@ -125,15 +579,199 @@ mod tests {
         discovery.stop();
     }
 
+    #[tokio::test]
+    async fn test_static_sources_listed() {
+        let config = NdiConfig {
+            auto_discovery: true,
+            discovery_interval: 1,
+            static_sources: vec![crate::config::StaticSource {
+                name: "Studio Feed".to_string(),
+                url: "ndi://10.0.0.9".to_string(),
+                bandwidth: Default::default(),
+            }],
+            groups: vec![],
+            extra_ips: vec![],
+            discovery_server: None,
+            receive: Default::default(),
+            frame_sync: Default::default(),
+            scan: Default::default(),
+            aliases: [("Studio Feed".to_string(), "Feed".to_string())].into(),
+            proxies: vec![],
+            bridges: vec![],
+            approval: Default::default(),
+            hx_decoder: Default::default(),
+            transport: Default::default(),
+            source_transports: Default::default(),
+            interface_preference: vec![],
+            remote_tally: vec![],
+            tags: [("Feed".to_string(), vec!["remote".to_string()])].into(),
+            channel_maps: BTreeMap::new(),
+            audio_trims: BTreeMap::new(),
+        };
+        let discovery = NdiDiscovery::from_config(&config);
+        // Listed before the first scan
+        let sources = discovery.get_sources();
+        assert_eq!(sources[0].display_name(), "Feed");
+        assert!(sources[0].static_source);
+        assert_eq!(sources[0].tags, ["remote"]);
+        discovery.set_tags(BTreeMap::new());
+        assert!(discovery.get_sources()[0].tags.is_empty());
+
+        discovery.start().await.unwrap();
+        assert_eq!(discovery.get_sources()[0].name, "Studio Feed");
+        discovery.stop();
+    }
+
+    #[test]
+    fn test_scan_pacing() {
+        let start = Instant::now();
+        let normal = Duration::from_secs(5);
+        let mut pacing = ScanPacing::new(ScanPolicy::default(), normal, start);
+        let fast = Duration::from_secs(1);
+        let idle = Duration::from_secs(30);
+
+        // Sources just appeared at startup
+        assert_eq!(pacing.interval(start), fast);
+        assert_eq!(pacing.interval(start + RECENT_CHANGE), normal);
+        assert_eq!(pacing.interval(start + Duration::from_secs(120)), idle);
+
+        pacing.browsing = true;
+        assert_eq!(pacing.interval(start + Duration::from_secs(120)), fast);
+        pacing.browsing = false;
+
+        // Input or a source change wakes discovery up
+        pacing.last_input = start + Duration::from_secs(100);
+        assert_eq!(pacing.interval(start + Duration::from_secs(120)), normal);
+        pacing.last_change = start + Duration::from_secs(300);
+        assert_eq!(pacing.interval(start + Duration::from_secs(301)), fast);
+
+        pacing.policy.adaptive = false;
+        assert_eq!(pacing.interval(start + Duration::from_secs(301)), normal);
+    }
+
     #[test]
     fn test_add_remove_source() {
         let discovery = NdiDiscovery::new();
+        let mut events = discovery.subscribe();
         let source = NdiSource::new("Test Source".to_string(), "ndi://test".to_string());
 
         discovery.add_source(source.clone());
-        assert_eq!(discovery.get_sources().len(), 1);
+        let sources = discovery.get_sources();
+        assert_eq!(sources.len(), 1);
+        // Discovery fills in the machine name
+        let added = sources[0].clone();
+        assert_eq!(added.machine.as_deref(), Some("Test Source"));
 
         assert!(discovery.remove_source(&source.url));
         assert_eq!(discovery.get_sources().len(), 0);
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::SourceAdded(added.clone())
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::SourceRemoved(added)
+        );
+    }
+
+    #[test]
+    fn test_quarantine_until_approved() {
+        let config = NdiConfig {
+            approval: ApprovalConfig {
+                required: true,
+                approved: ["CAM (1)".to_string()].into(),
+                admin_pin: None,
+            },
+            ..crate::config::Config::default().ndi
+        };
+        let discovery = NdiDiscovery::from_config(&config);
+        discovery.add_source(NdiSource::new(
+            "CAM (1)".to_string(),
+            "ndi://cam1".to_string(),
+        ));
+        discovery.add_source(NdiSource::new(
+            "LAPTOP (Chrome)".to_string(),
+            "ndi://laptop".to_string(),
+        ));
+        let mut events = discovery.subscribe();
+
+        let quarantined = |d: &NdiDiscovery| -> Vec<String> {
+            d.get_sources()
+                .into_iter()
+                .filter(|s| s.quarantined)
+                .map(|s| s.name)
+                .collect()
+        };
+        assert_eq!(quarantined(&discovery), vec!["LAPTOP (Chrome)"]);
+
+        discovery.approve("LAPTOP (Chrome)");
+        assert!(quarantined(&discovery).is_empty());
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::SourceChanged(s) if s.name == "LAPTOP (Chrome)"
+        ));
+    }
+
+    #[test]
+    fn test_record_stream() {
+        let discovery = NdiDiscovery::new();
+        discovery.add_source(NdiSource::new(
+            "STUDIO-PC (OBS)".to_string(),
+            "10.0.0.5:5961".to_string(),
+        ));
+        let mut events = discovery.subscribe();
+        let source = discovery.get_source("STUDIO-PC (OBS)").unwrap();
+        assert_eq!(source.ip_address, "10.0.0.5".parse().ok());
+        assert_eq!(source.machine.as_deref(), Some("STUDIO-PC"));
+
+        let info = StreamInfo {
+            width: 1920,
+            height: 1080,
+            frame_rate_n: 30000,
+            frame_rate_d: 1001,
+            pixel_format: Some("UYVY".to_string()),
+            ..Default::default()
+        };
+        discovery.record_stream("STUDIO-PC (OBS)", &info);
+        discovery.record_stream("STUDIO-PC (OBS)", &info);
+        let source = discovery.get_source("STUDIO-PC (OBS)").unwrap();
+        assert_eq!(source.resolution, Some((1920, 1080)));
+        assert_eq!(source.codec.as_deref(), Some("UYVY"));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::SourceChanged(s) if s.resolution == Some((1920, 1080))
+        ));
+        // Only a different format is a change
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_source_changes() {
+        let camera = NdiSource::new("CAM (1)".to_string(), "ndi://10.0.0.1".to_string());
+        let moved = NdiSource::new("CAM (1)".to_string(), "ndi://10.0.0.7".to_string());
+        let slides = NdiSource::new("SLIDES".to_string(), "ndi://10.0.0.2".to_string());
+        let old = vec![camera.clone(), slides.clone()];
+        let new = vec![moved.clone()];
+        let mut seen_again = moved.clone();
+        seen_again.last_seen_ms = Some(1_000);
+
+        let events = changes(&old, &new);
+        assert_eq!(
+            events,
+            vec![
+                DiscoveryEvent::SourceRemoved(slides),
+                DiscoveryEvent::SourceChanged(moved),
+            ]
+        );
+        assert!(changes(&new, &new).is_empty());
+        assert!(changes(&new, &[seen_again]).is_empty());
+
+        // A subscriber applying the events ends up with the new list
+        let mut copy = old;
+        for event in &events {
+            event.apply(&mut copy);
+        }
+        assert_eq!(copy, new);
     }
 }
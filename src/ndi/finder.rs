@@ -0,0 +1,215 @@
+//! NDI source finder backed by the NDI SDK (requires the `ndi` feature)
+
+use super::NdiSource;
+use anyhow::Result;
+
+/// NDI group used when no groups are configured
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+pub const DEFAULT_GROUP: &str = "Public";
+
+/// Finder settings
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+pub struct FinderOptions {
+    /// Groups to search (the SDK default group if empty)
+    pub groups: Vec<String>,
+    /// Extra sender IPs to query directly (for networks without mDNS)
+    pub extra_ips: Vec<String>,
+    /// NDI Discovery Server to register with instead of using mDNS
+    pub discovery_server: Option<String>,
+}
+
+impl FinderOptions {
+    /// Groups sources found with these options belong to
+    #[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+    pub fn effective_groups(&self) -> Vec<String> {
+        if self.groups.is_empty() {
+            vec![DEFAULT_GROUP.to_string()]
+        } else {
+            self.groups.clone()
+        }
+    }
+
+    /// NDI runtime configuration (`ndi-config.v1.json`) pointing at the discovery server
+    #[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+    pub fn runtime_config(&self) -> Option<serde_json::Value> {
+        let server = self.discovery_server.as_deref()?.trim();
+        if server.is_empty() {
+            return None;
+        }
+        Some(serde_json::json!({
+            "ndi": {
+                "networks": {
+                    "discovery": server,
+                    "ips": self.extra_ips.join(","),
+                }
+            }
+        }))
+    }
+}
+
+/// Point the NDI runtime at the discovery server before it initializes.
+///
+/// The SDK only reads the discovery server from its configuration file, which
+/// the transport module writes (see [`super::transport`]).
+#[cfg(feature = "ndi")]
+fn configure_runtime(options: &FinderOptions) -> Result<()> {
+    use log::info;
+
+    let Some(config) = options.runtime_config() else {
+        return Ok(());
+    };
+    let server = config["ndi"]["networks"]["discovery"].clone();
+    if super::transport::set_base(config)? {
+        info!("Using NDI discovery server {}", server);
+    }
+    Ok(())
+}
+
+/// A running NDI finder
+#[cfg(feature = "ndi")]
+pub struct Finder {
+    instance: super::sys::NDIlib_find_instance_t,
+    groups: Vec<String>,
+}
+
+// SAFETY: NDI finder instances may be used from any thread; the instance is
+// only accessed through &self/&mut self and destroyed once on drop.
+#[cfg(feature = "ndi")]
+unsafe impl Send for Finder {}
+
+#[cfg(feature = "ndi")]
+impl Finder {
+    /// Create a finder, initializing the NDI runtime if needed
+    pub fn new(options: &FinderOptions) -> Result<Self> {
+        use super::sys;
+        use anyhow::Context;
+        use std::ffi::CString;
+
+        configure_runtime(options)?;
+        super::initialize_runtime()?;
+
+        let groups = (!options.groups.is_empty())
+            .then(|| CString::new(options.groups.join(",")))
+            .transpose()
+            .context("Invalid NDI group name")?;
+        let extra_ips = (!options.extra_ips.is_empty())
+            .then(|| CString::new(options.extra_ips.join(",")))
+            .transpose()
+            .context("Invalid NDI extra IP")?;
+
+        let settings = sys::NDIlib_find_create_t {
+            show_local_sources: true,
+            p_groups: groups.as_ref().map_or(std::ptr::null(), |g| g.as_ptr()),
+            p_extra_ips: extra_ips
+                .as_ref()
+                .map_or(std::ptr::null(), |ip| ip.as_ptr()),
+        };
+
+        // SAFETY: settings and the strings it points to outlive the call; the SDK copies them
+        let instance = unsafe { sys::NDIlib_find_create_v2(&settings) };
+        if instance.is_null() {
+            anyhow::bail!("Failed to create NDI finder");
+        }
+
+        Ok(Self {
+            instance,
+            groups: options.effective_groups(),
+        })
+    }
+
+    /// Wait up to `timeout_ms` for the source list to change
+    pub fn wait_for_sources(&mut self, timeout_ms: u32) -> bool {
+        // SAFETY: instance is a valid finder until drop
+        unsafe { super::sys::NDIlib_find_wait_for_sources(self.instance, timeout_ms) }
+    }
+
+    /// Sources currently visible to the finder
+    pub fn current_sources(&mut self) -> Vec<NdiSource> {
+        use std::ffi::CStr;
+
+        let mut count = 0u32;
+        // SAFETY: instance is valid; the returned array stays valid until the next call
+        // on this finder, and we copy everything out before returning
+        unsafe {
+            let sources = super::sys::NDIlib_find_get_current_sources(self.instance, &mut count);
+            if sources.is_null() {
+                return Vec::new();
+            }
+
+            std::slice::from_raw_parts(sources, count as usize)
+                .iter()
+                .filter(|s| !s.p_ndi_name.is_null())
+                .map(|s| {
+                    let name = CStr::from_ptr(s.p_ndi_name).to_string_lossy().into_owned();
+                    let url = if s.p_url_address.is_null() {
+                        String::new()
+                    } else {
+                        CStr::from_ptr(s.p_url_address)
+                            .to_string_lossy()
+                            .into_owned()
+                    };
+                    NdiSource::new(name, url).with_groups(self.groups.clone())
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(feature = "ndi")]
+impl Drop for Finder {
+    fn drop(&mut self) {
+        // SAFETY: instance was created by NDIlib_find_create_v2 and is destroyed once
+        unsafe { super::sys::NDIlib_find_destroy(self.instance) }
+    }
+}
+
+/// Placeholder finder used when built without the NDI SDK
+#[cfg(not(feature = "ndi"))]
+pub struct Finder;
+
+#[cfg(not(feature = "ndi"))]
+impl Finder {
+    pub fn new(_options: &FinderOptions) -> Result<Self> {
+        anyhow::bail!("RusTV was built without NDI SDK support (rebuild with --features ndi)")
+    }
+
+    pub fn wait_for_sources(&mut self, _timeout_ms: u32) -> bool {
+        false
+    }
+
+    pub fn current_sources(&mut self) -> Vec<NdiSource> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_groups() {
+        assert_eq!(FinderOptions::default().effective_groups(), vec!["Public"]);
+
+        let options = FinderOptions {
+            groups: vec!["Studio A".to_string(), "Graphics".to_string()],
+            extra_ips: vec![],
+            discovery_server: None,
+        };
+        assert_eq!(options.effective_groups(), vec!["Studio A", "Graphics"]);
+    }
+
+    #[test]
+    fn test_runtime_config() {
+        assert!(FinderOptions::default().runtime_config().is_none());
+
+        let options = FinderOptions {
+            extra_ips: vec!["10.1.0.5".to_string(), "10.2.0.7".to_string()],
+            discovery_server: Some("10.0.0.2:5959".to_string()),
+            ..Default::default()
+        };
+        let config = options.runtime_config().unwrap();
+        assert_eq!(config["ndi"]["networks"]["discovery"], "10.0.0.2:5959");
+        assert_eq!(config["ndi"]["networks"]["ips"], "10.1.0.5,10.2.0.7");
+    }
+}
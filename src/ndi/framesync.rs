@@ -0,0 +1,190 @@
+//! Frame synchronisation: holds frames from every active receiver and switches
+//! them all at once on a common output cadence, so slots don't update out of step
+//! (which tears a composited or recorded multiview)
+
+use super::receiver::ReceivedFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Frames older than the sync delay plus this are dropped
+const MAX_HOLD: Duration = Duration::from_secs(1);
+
+/// Output cadence and delay of the frame-sync stage (`[ndi.frame_sync]`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameSyncConfig {
+    pub enabled: bool,
+    /// Output frames per second
+    pub rate: f64,
+    /// How long frames are held before they are shown; sources arriving up to
+    /// this much later than others still land on the same output frame
+    pub latency_ms: u64,
+}
+
+impl Default for FrameSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rate: 30.0,
+            latency_ms: 100,
+        }
+    }
+}
+
+/// Aligns frames from several receivers to one output cadence
+pub struct FrameSync {
+    interval: Duration,
+    delay: Duration,
+    /// Frames waiting to be shown, by input, with when they arrived
+    pending: HashMap<String, VecDeque<(Instant, ReceivedFrame)>>,
+    /// Frame each input shows on the current output tick
+    current: HashMap<String, ReceivedFrame>,
+    /// Time of the next output tick
+    next_tick: Option<Instant>,
+}
+
+impl FrameSync {
+    pub fn new(config: &FrameSyncConfig) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / config.rate.clamp(1.0, 240.0)),
+            delay: Duration::from_millis(config.latency_ms),
+            pending: HashMap::new(),
+            current: HashMap::new(),
+            next_tick: None,
+        }
+    }
+
+    /// Add a frame received from an input (repeats of the last frame are ignored)
+    pub fn push(&mut self, input: &str, frame: ReceivedFrame, now: Instant) {
+        let queue = self.pending.entry(input.to_string()).or_default();
+        let last = queue
+            .back()
+            .map(|(_, f)| f)
+            .or(self.current.get(input))
+            .map(|f| f.sequence);
+        if last == Some(frame.sequence) {
+            return;
+        }
+        while queue.front().is_some_and(|(arrived, _)| {
+            now.saturating_duration_since(*arrived) > self.delay + MAX_HOLD
+        }) {
+            queue.pop_front();
+        }
+        queue.push_back((now, frame));
+    }
+
+    /// Move to the next output tick if it is due, switching every input to the
+    /// newest frame that arrived at least the sync delay before the tick.
+    /// Returns whether the shown frames changed
+    pub fn advance(&mut self, now: Instant) -> bool {
+        let tick = match self.next_tick {
+            Some(tick) if now < tick => return false,
+            // Keep an even cadence unless the caller stalled for a while
+            Some(tick) if now.saturating_duration_since(tick) < self.interval => tick,
+            _ => now,
+        };
+        self.next_tick = Some(tick + self.interval);
+
+        let Some(cutoff) = tick.checked_sub(self.delay) else {
+            return false;
+        };
+        let mut changed = false;
+        for (input, queue) in &mut self.pending {
+            let mut due = None;
+            while queue.front().is_some_and(|(arrived, _)| *arrived <= cutoff) {
+                due = queue.pop_front().map(|(_, frame)| frame);
+            }
+            if let Some(frame) = due {
+                self.current.insert(input.clone(), frame);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Frame an input shows on the current output tick
+    pub fn frame(&self, input: &str) -> Option<ReceivedFrame> {
+        self.current.get(input).cloned()
+    }
+
+    /// Forget inputs that are no longer received
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.pending.retain(|input, _| keep(input));
+        self.current.retain(|input, _| keep(input));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::VideoFrame;
+    use std::sync::Arc;
+
+    fn frame(sequence: u64) -> ReceivedFrame {
+        ReceivedFrame {
+            sequence,
+            frame: Arc::new(VideoFrame::new(1, 1, vec![0; 4])),
+        }
+    }
+
+    fn sequence(sync: &FrameSync, input: &str) -> Option<u64> {
+        sync.frame(input).map(|f| f.sequence)
+    }
+
+    #[test]
+    fn test_inputs_switch_together() {
+        let config = FrameSyncConfig {
+            enabled: true,
+            rate: 25.0,
+            latency_ms: 100,
+        };
+        let mut sync = FrameSync::new(&config);
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+
+        // Two cameras arrive 30 ms apart
+        sync.push("CAM 1", frame(1), start);
+        sync.push("CAM 2", frame(1), start + ms(30));
+        sync.push("CAM 1", frame(1), start + ms(30));
+
+        // Nothing is shown until frames are older than the delay
+        assert!(!sync.advance(start + ms(50)));
+        assert_eq!(sequence(&sync, "CAM 1"), None);
+
+        // A tick 130 ms in shows both, on the same output frame
+        sync.next_tick = None;
+        assert!(sync.advance(start + ms(130)));
+        assert_eq!(sequence(&sync, "CAM 1"), Some(1));
+        assert_eq!(sequence(&sync, "CAM 2"), Some(1));
+
+        // A newer frame waits for its tick, then the newest due frame wins
+        sync.push("CAM 1", frame(2), start + ms(140));
+        sync.push("CAM 1", frame(3), start + ms(150));
+        assert!(!sync.advance(start + ms(150)));
+        assert_eq!(sequence(&sync, "CAM 1"), Some(1));
+        sync.next_tick = None;
+        assert!(sync.advance(start + ms(260)));
+        assert_eq!(sequence(&sync, "CAM 1"), Some(3));
+        assert_eq!(sequence(&sync, "CAM 2"), Some(1));
+
+        sync.retain(|input| input == "CAM 2");
+        assert_eq!(sequence(&sync, "CAM 1"), None);
+    }
+
+    #[test]
+    fn test_even_cadence() {
+        let mut sync = FrameSync::new(&FrameSyncConfig {
+            rate: 50.0,
+            ..FrameSyncConfig::default()
+        });
+        let start = Instant::now() + Duration::from_secs(1);
+        let interval = Duration::from_millis(20);
+
+        sync.advance(start);
+        // Ticks stay on the 20 ms grid even when polled late
+        assert!(!sync.advance(start + Duration::from_millis(19)));
+        sync.advance(start + Duration::from_millis(25));
+        assert_eq!(sync.next_tick, Some(start + interval * 2));
+    }
+}
@@ -0,0 +1,215 @@
+//! Receiver connection health: noticing when a source stops delivering and
+//! deciding when to reconnect
+
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Time without frames after which a connection is stale
+pub const STALE_AFTER: Duration = Duration::from_secs(2);
+
+/// Time without frames after which a connection is given up and retried
+pub const DISCONNECTED_AFTER: Duration = Duration::from_secs(6);
+
+/// Delay before the first reconnection attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest delay between reconnection attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Health of a receiver's connection to its source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionState {
+    /// Waiting for the first frame after (re)connecting
+    #[default]
+    Connecting,
+    /// Frames are arriving
+    Connected,
+    /// Frames stopped arriving a moment ago
+    Stale,
+    /// Frames stopped arriving; reconnecting with backoff
+    Disconnected,
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connecting => write!(f, "connecting"),
+            Self::Connected => write!(f, "connected"),
+            Self::Stale => write!(f, "stale"),
+            Self::Disconnected => write!(f, "disconnected"),
+        }
+    }
+}
+
+/// A receiver's connection changed state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionEvent {
+    /// Source name
+    pub source: String,
+    pub state: ConnectionState,
+}
+
+/// Tracks frame activity of one connection and schedules reconnection
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    state: ConnectionState,
+    /// Last frame, or when the current connection attempt started
+    last_activity: Instant,
+    /// Whether frames are expected (metadata-only receivers rely on the link state)
+    expects_frames: bool,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+impl HealthMonitor {
+    pub fn new(now: Instant, expects_frames: bool) -> Self {
+        Self {
+            state: ConnectionState::Connecting,
+            last_activity: now,
+            expects_frames,
+            backoff: INITIAL_BACKOFF,
+            retry_at: None,
+        }
+    }
+
+    /// Move to a state, returning it if it changed
+    fn set(&mut self, state: ConnectionState, now: Instant) -> Option<ConnectionState> {
+        if state == self.state {
+            return None;
+        }
+        if state == ConnectionState::Disconnected {
+            self.retry_at = Some(now + self.backoff);
+            self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+        }
+        self.state = state;
+        Some(state)
+    }
+
+    /// A frame arrived from the source
+    pub fn frame(&mut self, now: Instant) -> Option<ConnectionState> {
+        self.last_activity = now;
+        self.backoff = INITIAL_BACKOFF;
+        self.retry_at = None;
+        self.set(ConnectionState::Connected, now)
+    }
+
+    /// The connection reported an error
+    pub fn error(&mut self, now: Instant) -> Option<ConnectionState> {
+        self.set(ConnectionState::Disconnected, now)
+    }
+
+    /// Check for timeouts; `link_up` is whether the SDK reports the source connected
+    pub fn check(&mut self, now: Instant, link_up: bool) -> Option<ConnectionState> {
+        if self.state == ConnectionState::Disconnected {
+            return None;
+        }
+        if link_up && !self.expects_frames {
+            return self.frame(now);
+        }
+        let idle = now.saturating_duration_since(self.last_activity);
+        if idle >= DISCONNECTED_AFTER {
+            self.set(ConnectionState::Disconnected, now)
+        } else if idle >= STALE_AFTER && self.state == ConnectionState::Connected {
+            self.set(ConnectionState::Stale, now)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the backoff has elapsed and a reconnection should be attempted
+    pub fn retry_due(&self, now: Instant) -> bool {
+        self.state == ConnectionState::Disconnected && self.retry_at.is_some_and(|at| now >= at)
+    }
+
+    /// A reconnection attempt started
+    pub fn reconnecting(&mut self, now: Instant) -> Option<ConnectionState> {
+        self.last_activity = now;
+        self.retry_at = None;
+        self.set(ConnectionState::Connecting, now)
+    }
+
+    /// A reconnection attempt failed outright; try again after a longer wait
+    pub fn retry_failed(&mut self, now: Instant) {
+        self.retry_at = Some(now + self.backoff);
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_then_disconnected() {
+        let start = Instant::now();
+        let mut health = HealthMonitor::new(start, true);
+        assert_eq!(health.check(start + Duration::from_secs(1), true), None);
+
+        assert_eq!(health.frame(start), Some(ConnectionState::Connected));
+        assert_eq!(health.frame(start), None);
+        assert_eq!(
+            health.check(start + STALE_AFTER, true),
+            Some(ConnectionState::Stale)
+        );
+        assert_eq!(
+            health.frame(start + STALE_AFTER),
+            Some(ConnectionState::Connected)
+        );
+        assert_eq!(
+            health.check(start + STALE_AFTER + DISCONNECTED_AFTER, true),
+            Some(ConnectionState::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_connect_timeout() {
+        let start = Instant::now();
+        let mut health = HealthMonitor::new(start, true);
+        // Never delivering goes straight from connecting to disconnected
+        assert_eq!(health.check(start + STALE_AFTER, true), None);
+        assert_eq!(
+            health.check(start + DISCONNECTED_AFTER, false),
+            Some(ConnectionState::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let start = Instant::now();
+        let mut health = HealthMonitor::new(start, true);
+        health.error(start);
+        assert!(!health.retry_due(start));
+        assert!(health.retry_due(start + INITIAL_BACKOFF));
+
+        // Each failure doubles the wait, up to the maximum
+        let now = start + INITIAL_BACKOFF;
+        assert_eq!(health.reconnecting(now), Some(ConnectionState::Connecting));
+        health.check(now + DISCONNECTED_AFTER, false);
+        let now = now + DISCONNECTED_AFTER;
+        assert!(!health.retry_due(now + INITIAL_BACKOFF));
+        assert!(health.retry_due(now + INITIAL_BACKOFF * 2));
+
+        for _ in 0..10 {
+            health.retry_failed(now);
+        }
+        assert!(health.retry_due(now + MAX_BACKOFF));
+
+        // A frame resets the backoff
+        health.reconnecting(now);
+        health.frame(now);
+        health.error(now);
+        assert!(health.retry_due(now + INITIAL_BACKOFF));
+    }
+
+    #[test]
+    fn test_metadata_only_uses_link_state() {
+        let start = Instant::now();
+        let mut health = HealthMonitor::new(start, false);
+        assert_eq!(
+            health.check(start + DISCONNECTED_AFTER * 10, true),
+            Some(ConnectionState::Connected)
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! NDI KVM: mouse and keyboard input sent to a source that advertises
+//! `ntk_kvm`, as `<ndi_kvm>` metadata frames in the format NDI Studio Monitor uses
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+const OP_MOUSE_MOVE: u8 = 0x03;
+const OP_LEFT_DOWN: u8 = 0x04;
+const OP_MIDDLE_DOWN: u8 = 0x05;
+const OP_RIGHT_DOWN: u8 = 0x06;
+const OP_LEFT_UP: u8 = 0x07;
+const OP_MIDDLE_UP: u8 = 0x08;
+const OP_RIGHT_UP: u8 = 0x09;
+const OP_WHEEL_VERTICAL: u8 = 0x0a;
+const OP_WHEEL_HORIZONTAL: u8 = 0x0b;
+const OP_KEY_DOWN: u8 = 0x0e;
+const OP_KEY_UP: u8 = 0x0f;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+/// One mouse or keyboard event for the remote machine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KvmEvent {
+    /// Pointer position as a fraction of the video (0.0 to 1.0, top left origin)
+    MouseMove {
+        x: f32,
+        y: f32,
+    },
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    /// Wheel notches (positive scrolls up or right)
+    Wheel {
+        vertical: i32,
+        horizontal: i32,
+    },
+    /// Key pressed, as an X11 keysym
+    KeyDown(u32),
+    KeyUp(u32),
+}
+
+impl KvmEvent {
+    /// The event in the binary KVM encoding
+    pub fn encode(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(9);
+        match self {
+            KvmEvent::MouseMove { x, y } => {
+                bytes.push(OP_MOUSE_MOVE);
+                bytes.extend_from_slice(&x.clamp(0.0, 1.0).to_le_bytes());
+                bytes.extend_from_slice(&y.clamp(0.0, 1.0).to_le_bytes());
+            }
+            KvmEvent::MouseDown(button) => bytes.push(match button {
+                MouseButton::Left => OP_LEFT_DOWN,
+                MouseButton::Middle => OP_MIDDLE_DOWN,
+                MouseButton::Right => OP_RIGHT_DOWN,
+            }),
+            KvmEvent::MouseUp(button) => bytes.push(match button {
+                MouseButton::Left => OP_LEFT_UP,
+                MouseButton::Middle => OP_MIDDLE_UP,
+                MouseButton::Right => OP_RIGHT_UP,
+            }),
+            KvmEvent::Wheel {
+                vertical,
+                horizontal,
+            } => {
+                if vertical != 0 {
+                    bytes.push(OP_WHEEL_VERTICAL);
+                    bytes.extend_from_slice(&vertical.to_le_bytes());
+                }
+                if horizontal != 0 {
+                    bytes.push(OP_WHEEL_HORIZONTAL);
+                    bytes.extend_from_slice(&horizontal.to_le_bytes());
+                }
+            }
+            KvmEvent::KeyDown(keysym) => {
+                bytes.push(OP_KEY_DOWN);
+                bytes.extend_from_slice(&keysym.to_le_bytes());
+            }
+            KvmEvent::KeyUp(keysym) => {
+                bytes.push(OP_KEY_UP);
+                bytes.extend_from_slice(&keysym.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// The metadata frame XML for this event
+    pub fn to_xml(self) -> String {
+        format!(r#"<ndi_kvm u="{}"/>"#, STANDARD.encode(self.encode()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(KvmEvent::MouseDown(MouseButton::Right).encode(), vec![0x06]);
+        assert_eq!(
+            KvmEvent::KeyDown(0xff0d).encode(),
+            vec![0x0e, 0x0d, 0xff, 0x00, 0x00]
+        );
+
+        let moved = KvmEvent::MouseMove { x: 0.5, y: 2.0 }.encode();
+        assert_eq!(moved[0], 0x03);
+        assert_eq!(&moved[1..5], &0.5f32.to_le_bytes());
+        assert_eq!(&moved[5..9], &1.0f32.to_le_bytes());
+
+        assert_eq!(
+            KvmEvent::MouseUp(MouseButton::Left).to_xml(),
+            r#"<ndi_kvm u="Bw=="/>"#
+        );
+    }
+}
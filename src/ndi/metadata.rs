@@ -0,0 +1,387 @@
+//! NDI metadata frames: parsing the XML senders send to receivers into typed
+//! events, and sharing the latest metadata of every source
+
+use crate::matrix::TallyState;
+use anyhow::{Context, Result};
+use log::debug;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Events buffered for each metadata subscriber before the oldest are dropped
+const EVENT_CAPACITY: usize = 256;
+
+/// Sender product details (`<ndi_product>`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProductInfo {
+    pub long_name: String,
+    pub short_name: String,
+    pub manufacturer: String,
+    pub version: String,
+    pub serial: String,
+}
+
+/// A metadata element received from a source
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum MetadataEvent {
+    /// Tally the sender currently shows (`<ndi_tally_echo>`)
+    Tally { program: bool, preview: bool },
+    /// Features the sender supports (`<ndi_capabilities>`)
+    Capabilities {
+        ptz: bool,
+        /// Accepts mouse and keyboard input (NDI KVM)
+        kvm: bool,
+        /// Web configuration URL (`%IP%` replaced by the sender's address)
+        web_control: Option<String>,
+    },
+    /// Sender product details
+    Product(ProductInfo),
+    /// Caption or subtitle text now on screen (`<ndi_caption>`, `<caption>` or
+    /// `<subtitle>`); empty when the sender clears it
+    Caption { text: String, lang: Option<String> },
+    /// Any other element, as received
+    Other { name: String, xml: String },
+}
+
+/// Parse the XML of a metadata frame (one or more top-level elements)
+pub fn parse(xml: &str) -> Result<Vec<MetadataEvent>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut events = Vec::new();
+    loop {
+        let start = reader.buffer_position() as usize;
+        match reader.read_event().context("Invalid metadata XML")? {
+            Event::Start(element) => {
+                // Elements with children are kept whole as raw XML
+                let name = element_name(&element);
+                reader
+                    .read_to_end(element.name())
+                    .context("Invalid metadata XML")?;
+                let end = reader.buffer_position() as usize;
+                let raw = xml[start..end].trim();
+                let event = match to_event(&element, name, raw)? {
+                    // The text of a caption may be its content rather than an attribute
+                    MetadataEvent::Caption { text, lang } if text.is_empty() => {
+                        MetadataEvent::Caption {
+                            text: caption_text(raw)?,
+                            lang,
+                        }
+                    }
+                    event => event,
+                };
+                events.push(event);
+            }
+            Event::Empty(element) => {
+                let end = reader.buffer_position() as usize;
+                let name = element_name(&element);
+                events.push(to_event(&element, name, xml[start..end].trim())?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+/// Text content of a caption element, one line per text node (e.g. per `<line>`)
+fn caption_text(xml: &str) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut lines = Vec::new();
+    loop {
+        match reader.read_event().context("Invalid caption XML")? {
+            Event::Text(text) => lines.push(
+                text.unescape()
+                    .context("Invalid caption text")?
+                    .into_owned(),
+            ),
+            Event::CData(text) => lines.push(String::from_utf8_lossy(&text).into_owned()),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+fn element_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.name().as_ref()).into_owned()
+}
+
+fn to_event(element: &BytesStart, name: String, xml: &str) -> Result<MetadataEvent> {
+    let mut attributes = HashMap::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.context("Invalid metadata attribute")?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        let value = attribute
+            .unescape_value()
+            .context("Invalid metadata attribute")?;
+        attributes.insert(key, value.into_owned());
+    }
+    let text = |key: &str| attributes.get(key).cloned().unwrap_or_default();
+    let flag = |key: &str| attributes.get(key).is_some_and(|v| v == "true");
+
+    Ok(match name.as_str() {
+        "ndi_tally_echo" => MetadataEvent::Tally {
+            program: flag("on_program"),
+            preview: flag("on_preview"),
+        },
+        "ndi_capabilities" => MetadataEvent::Capabilities {
+            ptz: flag("ntk_ptz"),
+            kvm: flag("ntk_kvm"),
+            web_control: attributes.get("web_control").cloned(),
+        },
+        "ndi_product" => MetadataEvent::Product(ProductInfo {
+            long_name: text("long_name"),
+            short_name: text("short_name"),
+            manufacturer: text("manufacturer"),
+            version: text("version"),
+            serial: text("serial"),
+        }),
+        "ndi_caption" | "caption" | "subtitle" => MetadataEvent::Caption {
+            text: if flag("clear") {
+                String::new()
+            } else {
+                text("text")
+            },
+            lang: attributes.get("lang").cloned(),
+        },
+        _ => MetadataEvent::Other {
+            name,
+            xml: xml.to_string(),
+        },
+    })
+}
+
+/// Latest metadata known for a source
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SourceMetadata {
+    /// Whether the sender has echoed its tally at all
+    pub tally_reported: bool,
+    pub tally_program: bool,
+    pub tally_preview: bool,
+    pub ptz: bool,
+    pub kvm: bool,
+    pub web_control: Option<String>,
+    pub product: Option<ProductInfo>,
+    /// Caption or subtitle on screen, if any
+    pub caption: Option<String>,
+    /// Most recent element of any other kind
+    pub last_other: Option<String>,
+}
+
+impl SourceMetadata {
+    /// Tally the sender reports, from every receiver that has it on air
+    pub fn tally(&self) -> Option<TallyState> {
+        self.tally_reported.then_some(TallyState {
+            program: self.tally_program,
+            preview: self.tally_preview,
+        })
+    }
+
+    pub fn apply(&mut self, event: &MetadataEvent) {
+        match event {
+            MetadataEvent::Tally { program, preview } => {
+                self.tally_reported = true;
+                self.tally_program = *program;
+                self.tally_preview = *preview;
+            }
+            MetadataEvent::Capabilities {
+                ptz,
+                kvm,
+                web_control,
+            } => {
+                self.ptz = *ptz;
+                self.kvm = *kvm;
+                self.web_control = web_control.clone();
+            }
+            MetadataEvent::Product(product) => self.product = Some(product.clone()),
+            MetadataEvent::Caption { text, .. } => {
+                self.caption = (!text.is_empty()).then(|| text.clone())
+            }
+            MetadataEvent::Other { xml, .. } => self.last_other = Some(xml.clone()),
+        }
+    }
+}
+
+/// A metadata event with the source it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceEvent {
+    pub source: String,
+    pub event: MetadataEvent,
+}
+
+/// Metadata of every connected source, with a stream of events for subscribers
+pub struct MetadataHub {
+    sources: Mutex<HashMap<String, SourceMetadata>>,
+    events: broadcast::Sender<SourceEvent>,
+}
+
+impl MetadataHub {
+    pub fn new() -> Self {
+        Self {
+            sources: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+
+    /// Record an event from a source and pass it to subscribers
+    pub fn publish(&self, source: &str, event: MetadataEvent) {
+        debug!("Metadata from {}: {:?}", source, event);
+        self.sources
+            .lock()
+            .unwrap()
+            .entry(source.to_string())
+            .or_default()
+            .apply(&event);
+        // No subscribers is fine
+        let _ = self.events.send(SourceEvent {
+            source: source.to_string(),
+            event,
+        });
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<SourceEvent> {
+        self.events.subscribe()
+    }
+
+    /// Latest metadata of one source
+    pub fn get(&self, source: &str) -> Option<SourceMetadata> {
+        self.sources.lock().unwrap().get(source).cloned()
+    }
+
+    /// Latest metadata of every source
+    pub fn snapshot(&self) -> HashMap<String, SourceMetadata> {
+        self.sources.lock().unwrap().clone()
+    }
+}
+
+impl Default for MetadataHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_known_elements() {
+        let events = parse(
+            r#"<ndi_product long_name="BirdDog P200" short_name="P200" manufacturer="BirdDog"
+                 version="5.5" serial="123" />
+               <ndi_capabilities ntk_ptz="true" web_control="http://%IP%/" />"#,
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            MetadataEvent::Product(product) => {
+                assert_eq!(product.long_name, "BirdDog P200");
+                assert_eq!(product.manufacturer, "BirdDog");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            events[1],
+            MetadataEvent::Capabilities {
+                ptz: true,
+                kvm: false,
+                web_control: Some("http://%IP%/".to_string())
+            }
+        );
+
+        assert_eq!(
+            parse(r#"<ndi_tally_echo on_program="true" on_preview="false"/>"#).unwrap(),
+            vec![MetadataEvent::Tally {
+                program: true,
+                preview: false
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_captions() {
+        let events = parse(
+            r#"<caption lang="en"><line>Welcome &amp; thank you</line><line>for joining us</line></caption>
+               <subtitle text="Second line" />
+               <ndi_caption clear="true" />"#,
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                MetadataEvent::Caption {
+                    text: "Welcome & thank you\nfor joining us".to_string(),
+                    lang: Some("en".to_string())
+                },
+                MetadataEvent::Caption {
+                    text: "Second line".to_string(),
+                    lang: None
+                },
+                MetadataEvent::Caption {
+                    text: String::new(),
+                    lang: None
+                },
+            ]
+        );
+
+        let mut metadata = SourceMetadata::default();
+        metadata.apply(&events[0]);
+        assert_eq!(
+            metadata.caption.as_deref(),
+            Some("Welcome & thank you\nfor joining us")
+        );
+        metadata.apply(&events[2]);
+        assert_eq!(metadata.caption, None);
+    }
+
+    #[test]
+    fn test_parse_other_and_invalid() {
+        let xml = r#"<custom_data><value>42</value></custom_data>"#;
+        assert_eq!(
+            parse(&format!("\n  {}\n", xml)).unwrap(),
+            vec![MetadataEvent::Other {
+                name: "custom_data".to_string(),
+                xml: xml.to_string()
+            }]
+        );
+        assert!(parse("<broken attr=></broken>").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hub() {
+        let hub = MetadataHub::new();
+        let mut events = hub.subscribe();
+
+        hub.publish(
+            "CAM1",
+            MetadataEvent::Capabilities {
+                ptz: true,
+                kvm: false,
+                web_control: None,
+            },
+        );
+
+        assert!(hub.get("CAM1").unwrap().ptz);
+        assert_eq!(events.recv().await.unwrap().source, "CAM1");
+        assert!(hub.get("CAM2").is_none());
+
+        // Tally is only known once the sender has echoed it
+        assert_eq!(hub.get("CAM1").unwrap().tally(), None);
+        hub.publish(
+            "CAM1",
+            MetadataEvent::Tally {
+                program: true,
+                preview: false,
+            },
+        );
+        assert!(hub.get("CAM1").unwrap().tally().unwrap().program);
+    }
+}
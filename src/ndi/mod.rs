@@ -1,7 +1,72 @@
+pub mod alerts;
+pub mod bridge;
+mod capture;
+pub mod connection;
+pub mod dedup;
 pub mod discovery;
+pub mod finder;
+pub mod framesync;
+pub mod health;
+pub mod kvm;
+pub mod metadata;
+pub mod playout;
+pub mod pool;
+pub mod proxy;
+pub mod ptz;
 pub mod receiver;
+pub mod routing;
+pub mod screen;
+mod sender;
 pub mod source;
+pub mod stats;
+#[cfg(feature = "ndi")]
+mod sys;
+pub mod tags;
+pub mod tally;
+pub mod throttle;
+pub mod transport;
 
-pub use discovery::NdiDiscovery;
-pub use receiver::NdiReceiver;
+pub use connection::ConnectionInfo;
+pub use discovery::{DiscoveryEvent, NdiDiscovery};
+pub use framesync::{FrameSync, FrameSyncConfig};
+pub use health::{ConnectionEvent, ConnectionState};
+pub use metadata::MetadataHub;
+pub use playout::{LatencyMode, ReceiverTuning};
+pub use pool::ReceiverPool;
+pub use proxy::ProxyManager;
+pub use ptz::NdiPtz;
+pub use receiver::{NdiReceiver, ReceiveBandwidth, ReceivedFrame, StreamInfo};
+pub use routing::RoutingDestinations;
+pub use sender::SendInstance;
 pub use source::NdiSource;
+pub use stats::{ReceiverStats, StatsHub};
+pub use tags::SourceFilter;
+pub use tally::{RemoteTallyWatcher, TallyEmitter};
+pub use transport::{NdiTransport, TransportPolicy};
+
+/// Initialize the NDI runtime once per process
+#[cfg(feature = "ndi")]
+fn initialize_runtime() -> anyhow::Result<()> {
+    use std::sync::OnceLock;
+
+    static INITIALIZED: OnceLock<bool> = OnceLock::new();
+    // SAFETY: NDIlib_initialize takes no arguments and is only called once
+    if *INITIALIZED.get_or_init(|| unsafe { sys::NDIlib_initialize() }) {
+        Ok(())
+    } else {
+        anyhow::bail!("NDI runtime failed to initialize (unsupported CPU or missing runtime)")
+    }
+}
+
+/// Version of the NDI runtime, once it is loaded (None without NDI support)
+pub fn runtime_version() -> Option<String> {
+    #[cfg(feature = "ndi")]
+    {
+        initialize_runtime().ok()?;
+        // SAFETY: NDIlib_version returns a static NUL-terminated string
+        let version = unsafe { std::ffi::CStr::from_ptr(sys::NDIlib_version()) };
+        Some(version.to_string_lossy().into_owned())
+    }
+    #[cfg(not(feature = "ndi"))]
+    None
+}
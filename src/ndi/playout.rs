@@ -0,0 +1,299 @@
+//! Video playout: trades latency for smoothness by holding received frames in a
+//! short buffer and releasing them at the source's frame rate
+
+use super::receiver::ReceivedFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Frame interval assumed until enough frames have arrived to measure it
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// How a receiver trades latency against smooth motion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LatencyMode {
+    /// Always show the newest frame (network jitter shows as uneven motion)
+    #[default]
+    LowestLatency,
+    /// Buffer frames and play them out evenly, adding a few frames of delay
+    Smooth,
+}
+
+impl LatencyMode {
+    pub fn all() -> [LatencyMode; 2] {
+        [LatencyMode::LowestLatency, LatencyMode::Smooth]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LatencyMode::LowestLatency => "Lowest latency",
+            LatencyMode::Smooth => "Smooth",
+        }
+    }
+}
+
+/// Which frame is discarded when the buffer is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DropPolicy {
+    /// Drop the oldest buffered frame, catching up to live
+    #[default]
+    DropOldest,
+    /// Drop the arriving frame, keeping the buffered frames in sequence
+    DropNewest,
+}
+
+/// Latency settings of a receiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiverTuning {
+    pub latency: LatencyMode,
+    /// Frames buffered in smooth mode (each adds one frame of delay)
+    pub buffer_frames: usize,
+    pub drop_policy: DropPolicy,
+}
+
+impl ReceiverTuning {
+    /// The lower-latency of two settings (for an input shown in several slots)
+    pub fn tighter(self, other: ReceiverTuning) -> ReceiverTuning {
+        match (self.latency, other.latency) {
+            (LatencyMode::LowestLatency, LatencyMode::Smooth) => self,
+            (LatencyMode::Smooth, LatencyMode::LowestLatency) => other,
+            _ if other.buffer_frames < self.buffer_frames => other,
+            _ => self,
+        }
+    }
+
+    /// Frames held before the oldest is released or dropped
+    fn depth(&self) -> usize {
+        match self.latency {
+            LatencyMode::LowestLatency => 1,
+            LatencyMode::Smooth => self.buffer_frames.max(1),
+        }
+    }
+}
+
+impl Default for ReceiverTuning {
+    fn default() -> Self {
+        Self {
+            latency: LatencyMode::default(),
+            buffer_frames: 3,
+            drop_policy: DropPolicy::default(),
+        }
+    }
+}
+
+/// Frames waiting to be shown, released according to the receiver's tuning
+pub struct PlayoutBuffer {
+    tuning: ReceiverTuning,
+    queue: VecDeque<ReceivedFrame>,
+    /// Frame currently shown
+    current: Option<ReceivedFrame>,
+    /// When the current frame was due to be shown
+    released_at: Option<Instant>,
+    /// Measured time between arriving frames
+    frame_interval: Duration,
+    last_arrival: Option<Instant>,
+    dropped: u64,
+}
+
+impl PlayoutBuffer {
+    pub fn new(tuning: ReceiverTuning) -> Self {
+        Self {
+            tuning,
+            queue: VecDeque::new(),
+            current: None,
+            released_at: None,
+            frame_interval: DEFAULT_FRAME_INTERVAL,
+            last_arrival: None,
+            dropped: 0,
+        }
+    }
+
+    pub fn tuning(&self) -> ReceiverTuning {
+        self.tuning
+    }
+
+    /// Change settings without interrupting playout
+    pub fn set_tuning(&mut self, tuning: ReceiverTuning) {
+        self.tuning = tuning;
+        while self.queue.len() > tuning.depth() {
+            self.queue.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    /// Frames discarded because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Add an arriving frame
+    pub fn push(&mut self, frame: ReceivedFrame, now: Instant) {
+        if let Some(last) = self.last_arrival {
+            // Smoothed so a single late frame doesn't change the playout rate
+            let interval = now.saturating_duration_since(last);
+            self.frame_interval = (self.frame_interval * 7 + interval) / 8;
+        }
+        self.last_arrival = Some(now);
+
+        if self.queue.len() >= self.tuning.depth() {
+            self.dropped += 1;
+            match self.tuning.drop_policy {
+                DropPolicy::DropOldest => {
+                    self.queue.pop_front();
+                }
+                DropPolicy::DropNewest if self.tuning.latency == LatencyMode::Smooth => return,
+                // Lowest latency always shows the newest frame
+                DropPolicy::DropNewest => {
+                    self.queue.pop_front();
+                }
+            }
+        }
+        self.queue.push_back(frame);
+    }
+
+    /// Frame to show now (None until the first frame is released)
+    pub fn frame(&mut self, now: Instant) -> Option<ReceivedFrame> {
+        match self.tuning.latency {
+            LatencyMode::LowestLatency => {
+                if let Some(frame) = self.queue.pop_back() {
+                    self.queue.clear();
+                    self.current = Some(frame);
+                }
+            }
+            LatencyMode::Smooth => self.release_due(now),
+        }
+        self.current.clone()
+    }
+
+    fn release_due(&mut self, now: Instant) {
+        let due = match self.released_at {
+            // Fill the buffer before starting, so there is slack for late frames
+            None => self.queue.len() >= self.tuning.depth(),
+            Some(released_at) => now.saturating_duration_since(released_at) >= self.frame_interval,
+        };
+        if !due {
+            return;
+        }
+        let Some(frame) = self.queue.pop_front() else {
+            return;
+        };
+        self.current = Some(frame);
+        self.released_at = Some(match self.released_at {
+            // Keep an even cadence unless playout stalled (e.g. the buffer ran dry)
+            Some(released_at) if now - released_at < self.frame_interval * 2 => {
+                released_at + self.frame_interval
+            }
+            _ => now,
+        });
+    }
+
+    /// Newest frame received, shown or not, without advancing playout
+    pub fn latest(&self) -> Option<ReceivedFrame> {
+        self.queue.back().or(self.current.as_ref()).cloned()
+    }
+
+    /// Forget all frames (e.g. when switching sources)
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.current = None;
+        self.released_at = None;
+        self.last_arrival = None;
+        self.frame_interval = DEFAULT_FRAME_INTERVAL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::VideoFrame;
+    use std::sync::Arc;
+
+    fn frame(sequence: u64) -> ReceivedFrame {
+        ReceivedFrame {
+            sequence,
+            frame: Arc::new(VideoFrame::new(1, 1, vec![0; 4])),
+        }
+    }
+
+    fn sequence(frame: Option<ReceivedFrame>) -> Option<u64> {
+        frame.map(|f| f.sequence)
+    }
+
+    #[test]
+    fn test_latest_does_not_advance_playout() {
+        let tuning = ReceiverTuning {
+            latency: LatencyMode::Smooth,
+            ..ReceiverTuning::default()
+        };
+        let mut buffer = PlayoutBuffer::new(tuning);
+        let now = Instant::now();
+        assert_eq!(sequence(buffer.latest()), None);
+
+        buffer.push(frame(1), now);
+        buffer.push(frame(2), now);
+        assert_eq!(sequence(buffer.latest()), Some(2));
+        // Nothing has been released yet, so the buffer is still filling
+        assert_eq!(sequence(buffer.frame(now)), None);
+    }
+
+    #[test]
+    fn test_lowest_latency_shows_newest() {
+        let mut buffer = PlayoutBuffer::new(ReceiverTuning::default());
+        let now = Instant::now();
+        assert_eq!(sequence(buffer.frame(now)), None);
+
+        buffer.push(frame(1), now);
+        buffer.push(frame(2), now);
+        assert_eq!(sequence(buffer.frame(now)), Some(2));
+        assert_eq!(sequence(buffer.frame(now)), Some(2));
+        assert_eq!(buffer.dropped(), 1);
+    }
+
+    #[test]
+    fn test_smooth_buffers_and_paces_frames() {
+        let mut buffer = PlayoutBuffer::new(ReceiverTuning {
+            latency: LatencyMode::Smooth,
+            buffer_frames: 2,
+            drop_policy: DropPolicy::DropNewest,
+        });
+        let start = Instant::now();
+        let interval = DEFAULT_FRAME_INTERVAL;
+
+        buffer.push(frame(1), start);
+        assert_eq!(sequence(buffer.frame(start)), None);
+        buffer.push(frame(2), start + interval);
+        assert_eq!(sequence(buffer.frame(start + interval)), Some(1));
+        // Asking again within the same frame interval shows the same frame
+        assert_eq!(sequence(buffer.frame(start + interval)), Some(1));
+
+        // A burst overflows the buffer; the arriving frame is dropped
+        buffer.push(frame(3), start + interval);
+        buffer.push(frame(4), start + interval);
+        assert_eq!(buffer.dropped(), 1);
+
+        assert_eq!(sequence(buffer.frame(start + interval * 2)), Some(2));
+        assert_eq!(sequence(buffer.frame(start + interval * 3)), Some(3));
+        // Buffer ran dry: keep showing the last frame
+        assert_eq!(sequence(buffer.frame(start + interval * 4)), Some(3));
+    }
+
+    #[test]
+    fn test_tighter_prefers_lowest_latency() {
+        let smooth = ReceiverTuning {
+            latency: LatencyMode::Smooth,
+            ..Default::default()
+        };
+        let low = ReceiverTuning::default();
+        assert_eq!(smooth.tighter(low), low);
+        assert_eq!(low.tighter(smooth), low);
+
+        let shallow = ReceiverTuning {
+            buffer_frames: 1,
+            ..smooth
+        };
+        assert_eq!(smooth.tighter(shallow), shallow);
+    }
+}
@@ -0,0 +1,341 @@
+use super::{
+    ConnectionEvent, MetadataHub, NdiReceiver, NdiSource, ReceiveBandwidth, ReceiverTuning,
+    StatsHub,
+};
+use crate::audio::{ChannelMap, InputTrim};
+use crate::supervisor::Supervisor;
+use anyhow::Result;
+use log::error;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// One connection shared by everything showing a source
+struct PooledReceiver {
+    source: NdiSource,
+    receiver: NdiReceiver,
+    /// What each subscriber wants from the source
+    wants: HashMap<String, (ReceiveBandwidth, ReceiverTuning)>,
+    /// Display rate of subscribers that don't need every frame
+    display_rates: HashMap<String, u32>,
+}
+
+impl PooledReceiver {
+    /// The highest bandwidth and lowest latency any subscriber wants
+    fn combined(&self) -> Option<(ReceiveBandwidth, ReceiverTuning)> {
+        self.wants
+            .values()
+            .copied()
+            .reduce(|(b1, t1), (b2, t2)| (b1.max(b2), t1.tighter(t2)))
+    }
+
+    /// The highest display rate any subscriber of video wants, or None if one
+    /// wants every frame
+    fn max_fps(&self) -> Option<u32> {
+        self.wants
+            .iter()
+            .filter(|(_, (bandwidth, _))| bandwidth.includes_video())
+            .map(|(subscriber, _)| self.display_rates.get(subscriber).copied())
+            .try_fold(0, |max, rate| Some(max.max(rate?)))
+    }
+}
+
+/// Receivers shared between subscribers (view slots, windows, watchers) by source URL,
+/// so N subscribers showing one source consume one network stream
+#[derive(Default)]
+pub struct ReceiverPool {
+    /// Connections by source URL
+    receivers: HashMap<String, PooledReceiver>,
+    /// Source URL each subscriber is subscribed to
+    subscribers: HashMap<String, String>,
+    metadata_hub: Option<Arc<MetadataHub>>,
+    health_events: Option<broadcast::Sender<ConnectionEvent>>,
+    supervisor: Option<Arc<Supervisor>>,
+    /// Audio channel maps by NDI source name
+    channel_maps: BTreeMap<String, ChannelMap>,
+    stats_hub: Option<Arc<StatsHub>>,
+    /// Audio trims by NDI source name
+    trims: BTreeMap<String, InputTrim>,
+}
+
+impl ReceiverPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish connection state changes of every pooled receiver to a channel
+    pub fn with_health_events(mut self, events: broadcast::Sender<ConnectionEvent>) -> Self {
+        self.health_events = Some(events);
+        self
+    }
+
+    /// Restart the capture thread of any pooled receiver that fails
+    pub fn with_supervisor(mut self, supervisor: Arc<Supervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    /// Read the audio of these sources (by NDI name) through their channel maps
+    pub fn with_channel_maps(mut self, maps: BTreeMap<String, ChannelMap>) -> Self {
+        self.channel_maps = maps;
+        self
+    }
+
+    /// Also publish statistics of pooled receivers to a shared hub
+    pub fn with_stats_hub(mut self, hub: Arc<StatsHub>) -> Self {
+        self.stats_hub = Some(hub);
+        self
+    }
+
+    /// Trim the audio of these sources (by NDI name)
+    pub fn with_trims(mut self, trims: BTreeMap<String, InputTrim>) -> Self {
+        self.trims = trims;
+        self
+    }
+
+    /// A source's trim (none if not set)
+    pub fn trim(&self, source_name: &str) -> InputTrim {
+        self.trims.get(source_name).copied().unwrap_or_default()
+    }
+
+    /// Change a source's trim, on its receiver now and on any it gets later
+    pub fn set_trim(&mut self, source_name: &str, trim: InputTrim) {
+        for pooled in self.receivers.values() {
+            if pooled.source.name == source_name {
+                pooled.receiver.set_trim(trim);
+            }
+        }
+        self.trims.insert(source_name.to_string(), trim);
+    }
+
+    /// Also publish metadata received by pooled receivers to a shared hub
+    pub fn with_metadata_hub(mut self, hub: Arc<MetadataHub>) -> Self {
+        self.metadata_hub = Some(hub);
+        self
+    }
+
+    /// Subscribe to a source, connecting only if nobody else receives it yet.
+    ///
+    /// The shared connection runs at the highest bandwidth and lowest latency its
+    /// subscribers want; a bandwidth change reconnects, a latency change does not.
+    pub fn subscribe(
+        &mut self,
+        subscriber: &str,
+        source: &NdiSource,
+        bandwidth: ReceiveBandwidth,
+        tuning: ReceiverTuning,
+    ) -> Result<()> {
+        if self.subscribers.get(subscriber) != Some(&source.url) {
+            self.unsubscribe(subscriber);
+        }
+
+        if !self.receivers.contains_key(&source.url) {
+            let mut receiver = NdiReceiver::new().with_tuning(tuning);
+            if let Some(map) = self.channel_maps.get(&source.name) {
+                receiver = receiver.with_channel_map(map.clone());
+            }
+            if let Some(trim) = self.trims.get(&source.name) {
+                receiver = receiver.with_trim(*trim);
+            }
+            if let Some(hub) = &self.metadata_hub {
+                receiver = receiver.with_metadata_hub(Arc::clone(hub));
+            }
+            if let Some(hub) = &self.stats_hub {
+                receiver = receiver.with_stats_hub(Arc::clone(hub));
+            }
+            if let Some(events) = &self.health_events {
+                receiver = receiver.with_health_events(events.clone());
+            }
+            if let Some(supervisor) = &self.supervisor {
+                receiver = receiver.with_supervisor(Arc::clone(supervisor));
+            }
+            receiver.connect(source.clone(), bandwidth)?;
+            self.receivers.insert(
+                source.url.clone(),
+                PooledReceiver {
+                    source: source.clone(),
+                    receiver,
+                    wants: HashMap::new(),
+                    display_rates: HashMap::new(),
+                },
+            );
+        }
+
+        self.subscribers
+            .insert(subscriber.to_string(), source.url.clone());
+        let pooled = self.receivers.get_mut(&source.url).unwrap();
+        pooled
+            .wants
+            .insert(subscriber.to_string(), (bandwidth, tuning));
+        Self::apply(pooled)
+    }
+
+    /// Drop a subscription, disconnecting once a source has no subscribers left
+    pub fn unsubscribe(&mut self, subscriber: &str) {
+        let Some(url) = self.subscribers.remove(subscriber) else {
+            return;
+        };
+        let Some(pooled) = self.receivers.get_mut(&url) else {
+            return;
+        };
+        pooled.wants.remove(subscriber);
+        pooled.display_rates.remove(subscriber);
+        if pooled.wants.is_empty() {
+            if let Some(mut pooled) = self.receivers.remove(&url) {
+                pooled.receiver.disconnect();
+            }
+        } else if let Err(e) = Self::apply(pooled) {
+            error!("Failed to reconnect to {}: {}", pooled.source.name, e);
+        }
+    }
+
+    /// Show a subscriber at most `max_fps` frames a second (None for every frame).
+    /// The shared connection decimates only once every subscriber has a limit
+    pub fn set_display_rate(&mut self, subscriber: &str, max_fps: Option<u32>) {
+        let Some(url) = self.subscribers.get(subscriber) else {
+            return;
+        };
+        let Some(pooled) = self.receivers.get_mut(url) else {
+            return;
+        };
+        match max_fps.filter(|fps| *fps > 0) {
+            Some(fps) => pooled.display_rates.insert(subscriber.to_string(), fps),
+            None => pooled.display_rates.remove(subscriber),
+        };
+        pooled.receiver.set_max_fps(pooled.max_fps());
+    }
+
+    /// Keep only the subscriptions for which `keep(subscriber, source)` is true
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &NdiSource) -> bool) {
+        let dropped: Vec<String> = self
+            .subscribers
+            .iter()
+            .filter(|(subscriber, url)| {
+                self.receivers
+                    .get(*url)
+                    .is_none_or(|pooled| !keep(subscriber.as_str(), &pooled.source))
+            })
+            .map(|(subscriber, _)| subscriber.clone())
+            .collect();
+        for subscriber in dropped {
+            self.unsubscribe(&subscriber);
+        }
+    }
+
+    /// Bring a shared connection in line with what its subscribers want
+    fn apply(pooled: &mut PooledReceiver) -> Result<()> {
+        let Some((bandwidth, tuning)) = pooled.combined() else {
+            return Ok(());
+        };
+        pooled.receiver.set_max_fps(pooled.max_fps());
+        if tuning != pooled.receiver.tuning() {
+            pooled.receiver.set_tuning(tuning);
+        }
+        if bandwidth != pooled.receiver.bandwidth() {
+            pooled.receiver.connect(pooled.source.clone(), bandwidth)?;
+        }
+        Ok(())
+    }
+
+    /// Receiver of an input, given by source name, URL or alias
+    pub fn get(&self, input: &str) -> Option<&NdiReceiver> {
+        self.pooled(input).map(|pooled| &pooled.receiver)
+    }
+
+    /// Source of an input, given by source name, URL or alias
+    pub fn source(&self, input: &str) -> Option<&NdiSource> {
+        self.pooled(input).map(|pooled| &pooled.source)
+    }
+
+    fn pooled(&self, input: &str) -> Option<&PooledReceiver> {
+        self.receivers.get(input).or_else(|| {
+            self.receivers
+                .values()
+                .find(|pooled| pooled.source.matches(input))
+        })
+    }
+
+    /// Every connection with the source it receives
+    pub fn iter(&self) -> impl Iterator<Item = (&NdiSource, &NdiReceiver)> {
+        self.receivers
+            .values()
+            .map(|pooled| (&pooled.source, &pooled.receiver))
+    }
+
+    /// Number of network connections held
+    pub fn connection_count(&self) -> usize {
+        self.receivers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ndi::LatencyMode;
+
+    #[test]
+    fn test_pool_shares_connections() {
+        let mut pool = ReceiverPool::new();
+        let camera = NdiSource::new("CAM (1)".to_string(), "ndi://cam1".to_string());
+        let smooth = ReceiverTuning::default();
+        let tight = ReceiverTuning {
+            latency: LatencyMode::LowestLatency,
+            ..smooth
+        };
+
+        pool.subscribe("slot:A", &camera, ReceiveBandwidth::Low, smooth)
+            .unwrap();
+        pool.subscribe("slot:B", &camera, ReceiveBandwidth::Full, tight)
+            .unwrap();
+        assert_eq!(pool.connection_count(), 1);
+        let receiver = pool.get("CAM (1)").unwrap();
+        assert_eq!(receiver.bandwidth(), ReceiveBandwidth::Full);
+        assert_eq!(receiver.tuning(), tight);
+        assert!(pool.get("ndi://cam1").is_some());
+
+        // The remaining subscriber's wants take over
+        pool.unsubscribe("slot:B");
+        assert_eq!(
+            pool.get("ndi://cam1").unwrap().bandwidth(),
+            ReceiveBandwidth::Low
+        );
+
+        // Moving the last subscriber elsewhere drops the connection
+        let other = NdiSource::new("CAM (2)".to_string(), "ndi://cam2".to_string());
+        pool.subscribe("slot:A", &other, ReceiveBandwidth::Low, smooth)
+            .unwrap();
+        assert_eq!(pool.connection_count(), 1);
+        assert!(pool.get("ndi://cam1").is_none());
+
+        pool.retain(|subscriber, _| subscriber != "slot:A");
+        assert_eq!(pool.connection_count(), 0);
+    }
+
+    #[test]
+    fn test_pool_display_rate() {
+        let mut pool = ReceiverPool::new();
+        let camera = NdiSource::new("CAM (1)".to_string(), "ndi://cam1".to_string());
+        let tuning = ReceiverTuning::default();
+
+        pool.subscribe("slot:A", &camera, ReceiveBandwidth::Low, tuning)
+            .unwrap();
+        pool.set_display_rate("slot:A", Some(10));
+        assert_eq!(pool.get("CAM (1)").unwrap().max_fps(), Some(10));
+
+        // A subscriber wanting every frame lifts the limit
+        pool.subscribe("window:A", &camera, ReceiveBandwidth::Full, tuning)
+            .unwrap();
+        assert_eq!(pool.get("CAM (1)").unwrap().max_fps(), None);
+        pool.set_display_rate("window:A", Some(15));
+        assert_eq!(pool.get("CAM (1)").unwrap().max_fps(), Some(15));
+
+        pool.unsubscribe("window:A");
+        assert_eq!(pool.get("CAM (1)").unwrap().max_fps(), Some(10));
+        // Audio-only subscribers don't care about the video rate
+        pool.subscribe("audio:Booth", &camera, ReceiveBandwidth::AudioOnly, tuning)
+            .unwrap();
+        assert_eq!(pool.get("CAM (1)").unwrap().max_fps(), Some(10));
+        pool.set_display_rate("slot:A", None);
+        assert_eq!(pool.get("CAM (1)").unwrap().max_fps(), None);
+    }
+}
@@ -0,0 +1,544 @@
+//! NDI proxy: receives a source and re-publishes it as a new sender, optionally
+//! scaled down, for network segments that can't carry the original stream. A
+//! proxy of a matrix output follows its route instead, switching inputs on a
+//! frame boundary
+
+use super::sender::SendInstance;
+use super::{NdiDiscovery, NdiReceiver, NdiSource};
+use crate::config::ProxyConfig;
+use crate::matrix::MatrixRouter;
+use crate::supervisor::Supervisor;
+use crate::video::framerate::{FrameRate, FrameRateConverter};
+use crate::video::identify::draw_identification;
+use crate::video::transition::RouteTransition;
+use crate::video::VideoFrame;
+use anyhow::Result;
+use log::{error, info};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running proxy checks its receiver for new frames
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How often a proxy looks for a source that hasn't been discovered yet
+const SOURCE_WAIT: Duration = Duration::from_secs(1);
+
+/// State of one configured proxy, as reported by the API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProxyStatus {
+    pub source: String,
+    /// NDI name of the re-published sender
+    pub name: String,
+    pub max_height: Option<u32>,
+    /// Fixed rate the proxy sends at, if converting
+    pub frame_rate: Option<FrameRate>,
+    /// Input being re-published, for the proxy of a matrix output
+    pub input: Option<String>,
+    pub running: bool,
+    /// Receivers connected to the re-published sender
+    pub connections: usize,
+    pub frames_sent: u64,
+    /// Why the proxy stopped, if it failed
+    pub error: Option<String>,
+}
+
+/// Updated by a proxy's thread
+#[derive(Debug, Default)]
+struct ProxyStats {
+    input: Option<String>,
+    connections: usize,
+    frames_sent: u64,
+    error: Option<String>,
+    finished: bool,
+}
+
+/// A started proxy
+struct Running {
+    stop: Arc<AtomicBool>,
+    stats: Arc<Mutex<ProxyStats>>,
+}
+
+/// Starts and stops the proxies configured under `[[ndi.proxies]]`
+pub struct ProxyManager {
+    proxies: Vec<ProxyConfig>,
+    discovery: Arc<NdiDiscovery>,
+    running: Mutex<HashMap<String, Running>>,
+    supervisor: Option<Arc<Supervisor>>,
+    /// Routes followed by proxies of matrix outputs
+    router: Option<Arc<Mutex<MatrixRouter>>>,
+    /// Until when proxies of matrix outputs show their output's number and name
+    identify_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ProxyManager {
+    pub fn new(proxies: Vec<ProxyConfig>, discovery: Arc<NdiDiscovery>) -> Self {
+        Self {
+            proxies,
+            discovery,
+            running: Mutex::new(HashMap::new()),
+            supervisor: None,
+            router: None,
+            identify_until: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Let proxies of matrix outputs follow the router's routes
+    pub fn with_router(mut self, router: Arc<Mutex<MatrixRouter>>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Restart proxies that fail, with backoff, until they are stopped
+    pub fn with_supervisor(mut self, supervisor: Arc<Supervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    /// Overlay each output's number and name on its proxy for `duration`
+    pub fn identify(&self, duration: Duration) {
+        *self.identify_until.lock().unwrap() = Some(Instant::now() + duration);
+    }
+
+    /// Start every proxy enabled in configuration
+    pub fn start_enabled(&self) {
+        for proxy in self.proxies.iter().filter(|p| p.enabled) {
+            if let Err(e) = self.start(&proxy.source) {
+                error!("Failed to start NDI proxy for {}: {}", proxy.source, e);
+            }
+        }
+    }
+
+    /// Start the proxy of a source (a no-op if it is already running)
+    pub fn start(&self, source: &str) -> Result<()> {
+        let config = self.config(source)?.clone();
+        let mut running = self.running.lock().unwrap();
+        if running
+            .get(&config.source)
+            .is_some_and(|r| !r.stats.lock().unwrap().finished)
+        {
+            return Ok(());
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stats = Arc::new(Mutex::new(ProxyStats::default()));
+        running.insert(
+            config.source.clone(),
+            Running {
+                stop: Arc::clone(&stop),
+                stats: Arc::clone(&stats),
+            },
+        );
+
+        let discovery = Arc::clone(&self.discovery);
+        let router = self.router.clone();
+        let identify_until = Arc::clone(&self.identify_until);
+        match &self.supervisor {
+            // Restarted until stopped, so it is never finished while failing
+            Some(supervisor) => {
+                let task = format!("NDI proxy {}", config.sender_name());
+                supervisor.spawn_thread(&task, move || {
+                    let result = run(
+                        &config,
+                        &discovery,
+                        router.as_deref(),
+                        &identify_until,
+                        &stop,
+                        &stats,
+                    );
+                    stats.lock().unwrap().error = result.as_ref().err().map(|e| e.to_string());
+                    result
+                });
+            }
+            None => {
+                thread::spawn(move || {
+                    let result = run(
+                        &config,
+                        &discovery,
+                        router.as_deref(),
+                        &identify_until,
+                        &stop,
+                        &stats,
+                    );
+                    if let Err(e) = result {
+                        error!("NDI proxy for {} stopped: {}", config.source, e);
+                        stats.lock().unwrap().error = Some(e.to_string());
+                    }
+                    stats.lock().unwrap().finished = true;
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the proxy of a source
+    pub fn stop(&self, source: &str) -> Result<()> {
+        let config = self.config(source)?;
+        if let Some(running) = self.running.lock().unwrap().remove(&config.source) {
+            running.stop.store(true, Ordering::Relaxed);
+            info!("Stopped NDI proxy for {}", config.source);
+        }
+        Ok(())
+    }
+
+    /// State of every configured proxy
+    pub fn status(&self) -> Vec<ProxyStatus> {
+        let running = self.running.lock().unwrap();
+        self.proxies
+            .iter()
+            .map(|config| {
+                let mut status = ProxyStatus {
+                    source: config.source.clone(),
+                    name: config.sender_name(),
+                    max_height: config.max_height,
+                    frame_rate: config.frame_rate,
+                    input: None,
+                    running: false,
+                    connections: 0,
+                    frames_sent: 0,
+                    error: None,
+                };
+                if let Some(running) = running.get(&config.source) {
+                    let stats = running.stats.lock().unwrap();
+                    status.running = !stats.finished;
+                    status.input = stats.input.clone();
+                    status.connections = stats.connections;
+                    status.frames_sent = stats.frames_sent;
+                    status.error = stats.error.clone();
+                }
+                status
+            })
+            .collect()
+    }
+
+    fn config(&self, source: &str) -> Result<&ProxyConfig> {
+        self.proxies
+            .iter()
+            .find(|p| p.source == source || p.sender_name() == source)
+            .ok_or_else(|| anyhow::anyhow!("No proxy configured for '{}'", source))
+    }
+}
+
+/// Sends frames out, at the source's rate or on the proxy's own cadence
+struct Outlet<'a> {
+    sender: SendInstance,
+    converter: Option<FrameRateConverter>,
+    max_height: Option<u32>,
+    stats: &'a Mutex<ProxyStats>,
+    /// Last frame sent, to crossfade out of
+    last: Option<Arc<VideoFrame>>,
+    /// New input's frame held back while the converter sends a transition frame
+    held: Option<Arc<VideoFrame>>,
+    identify_until: &'a Mutex<Option<Instant>>,
+    /// Number and name of the output this proxy publishes, shown while identifying
+    identity: Option<(usize, String)>,
+}
+
+impl<'a> Outlet<'a> {
+    fn new(
+        config: &ProxyConfig,
+        sender: SendInstance,
+        identify_until: &'a Mutex<Option<Instant>>,
+        stats: &'a Mutex<ProxyStats>,
+    ) -> Self {
+        Self {
+            sender,
+            converter: config
+                .frame_rate
+                .map(|rate| FrameRateConverter::new(rate, config.rate_conversion)),
+            max_height: config.max_height,
+            stats,
+            last: None,
+            held: None,
+            identify_until,
+            identity: None,
+        }
+    }
+
+    fn scale(&self, frame: Arc<VideoFrame>) -> Arc<VideoFrame> {
+        match self.max_height {
+            Some(max_height) => Arc::new(frame.downscale(max_height)),
+            None => frame,
+        }
+    }
+
+    /// A new frame from the receiver, at its stream's rate
+    fn video(&mut self, frame: Arc<VideoFrame>, receiver: &NdiReceiver) {
+        let frame = self.scale(frame);
+        match &mut self.converter {
+            Some(_) if self.held.is_some() => self.held = Some(frame),
+            Some(converter) => converter.push(frame, Instant::now()),
+            None => {
+                let info = receiver.stream_info();
+                self.send(frame, info.frame_rate_n, info.frame_rate_d);
+            }
+        }
+    }
+
+    /// The first frame of a newly routed input, after the transition frame
+    fn switch(
+        &mut self,
+        transition: RouteTransition,
+        first: Arc<VideoFrame>,
+        receiver: &NdiReceiver,
+    ) {
+        let first = self.scale(first);
+        let mut frames = transition.frames(self.last.as_deref(), first).into_iter();
+        match &mut self.converter {
+            // One frame per tick, so the transition frame isn't dropped
+            Some(converter) => {
+                converter.push(frames.next().unwrap(), Instant::now());
+                self.held = frames.next();
+            }
+            None => {
+                let info = receiver.stream_info();
+                for frame in frames {
+                    self.send(frame, info.frame_rate_n, info.frame_rate_d);
+                }
+            }
+        }
+    }
+
+    /// Send the converted frame, once the next tick is due
+    fn tick(&mut self) {
+        let Some(converter) = &mut self.converter else {
+            return;
+        };
+        let now = Instant::now();
+        if let Some(frame) = converter.poll(now) {
+            let rate = converter.rate();
+            if let Some(held) = self.held.take() {
+                converter.push(held, now);
+            }
+            self.send(frame, rate.numerator, rate.denominator);
+        }
+    }
+
+    fn send(&mut self, frame: Arc<VideoFrame>, frame_rate_n: u32, frame_rate_d: u32) {
+        let identifying = self
+            .identify_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| Instant::now() < until);
+        match &self.identity {
+            Some((number, output)) if identifying => {
+                let mut identified = VideoFrame::clone(&frame);
+                draw_identification(&mut identified, *number, output);
+                self.sender
+                    .send_video(&identified, frame_rate_n, frame_rate_d);
+            }
+            _ => self.sender.send_video(&frame, frame_rate_n, frame_rate_d),
+        }
+        self.last = Some(frame);
+        self.stats.lock().unwrap().frames_sent += 1;
+    }
+}
+
+/// A receiver and the last frame taken from it
+struct Feed {
+    input: String,
+    receiver: NdiReceiver,
+    last_sequence: Option<u64>,
+}
+
+impl Feed {
+    fn connect(input: &str, source: NdiSource, config: &ProxyConfig) -> Result<Self> {
+        let mut receiver = NdiReceiver::new();
+        receiver.connect(source, config.bandwidth)?;
+        Ok(Self {
+            input: input.to_string(),
+            receiver,
+            last_sequence: None,
+        })
+    }
+
+    /// The receiver's frame, if it is one not taken yet
+    fn next_frame(&mut self) -> Option<Arc<VideoFrame>> {
+        let received = self.receiver.receive_video_frame().ok()??;
+        if self.last_sequence == Some(received.sequence) {
+            return None;
+        }
+        self.last_sequence = Some(received.sequence);
+        Some(received.frame)
+    }
+}
+
+/// Forward frames from the source (or the input routed to the output) to the
+/// new sender until stopped
+fn run(
+    config: &ProxyConfig,
+    discovery: &NdiDiscovery,
+    router: Option<&Mutex<MatrixRouter>>,
+    identify_until: &Mutex<Option<Instant>>,
+    stop: &AtomicBool,
+    stats: &Mutex<ProxyStats>,
+) -> Result<()> {
+    let name = config.sender_name();
+    let mut outlet = Outlet::new(config, SendInstance::create(&name)?, identify_until, stats);
+    let find = |input: &str| {
+        discovery
+            .get_sources()
+            .into_iter()
+            .find(|s| s.matches(input))
+    };
+
+    if config.output {
+        let router = router.ok_or_else(|| {
+            anyhow::anyhow!("No router for the proxy of output {}", config.source)
+        })?;
+        info!(
+            "Re-publishing output {} as NDI sender {}",
+            config.source, name
+        );
+        // Numbered like the GUI's slots
+        outlet.identity = router.lock().ok().and_then(|router| {
+            let index = router
+                .get_outputs()
+                .iter()
+                .position(|o| o == &config.source)?;
+            Some((index + 1, config.source.clone()))
+        });
+        let find = |input: &str| routed_source(discovery.get_sources(), input);
+        return follow_output(config, router, find, &mut outlet, stop);
+    }
+
+    // The source may not have been discovered yet (e.g. at startup)
+    let source = loop {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        if let Some(source) = find(&config.source) {
+            break source;
+        }
+        thread::sleep(SOURCE_WAIT);
+    };
+
+    info!("Re-publishing {} as NDI sender {}", source.name, name);
+    let mut feed = Feed::connect(&config.source, source, config)?;
+    while !stop.load(Ordering::Relaxed) {
+        if let Some(frame) = feed.next_frame() {
+            outlet.video(frame, &feed.receiver);
+        }
+        outlet.tick();
+        while let Ok(Some(chunk)) = feed.receiver.receive_audio_frame() {
+            outlet.sender.send_audio(&chunk);
+        }
+        stats.lock().unwrap().connections = outlet.sender.connections();
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+/// The source an output's routed input plays. Sources awaiting approval aren't
+/// re-published, as the GUI and NDI routing don't show them either
+fn routed_source(sources: Vec<NdiSource>, input: &str) -> Option<NdiSource> {
+    sources
+        .into_iter()
+        .find(|s| s.matches(input) && !s.quarantined)
+}
+
+/// Re-publish whatever is routed to the output. A newly routed input is
+/// received alongside the current one, and replaces it from its first whole frame
+fn follow_output(
+    config: &ProxyConfig,
+    router: &Mutex<MatrixRouter>,
+    find: impl Fn(&str) -> Option<NdiSource>,
+    outlet: &mut Outlet,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let mut current: Option<Feed> = None;
+    let mut pending: Option<Feed> = None;
+    // When a routed input that hasn't been discovered yet is looked for again
+    let mut retry_at = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        let routed = router
+            .lock()
+            .ok()
+            .and_then(|router| router.get_route(&config.source).cloned());
+        match &routed {
+            None => {
+                current = None;
+                pending = None;
+            }
+            Some(input)
+                if current.as_ref().is_some_and(|feed| &feed.input == input)
+                    || pending.as_ref().is_some_and(|feed| &feed.input == input) => {}
+            Some(input) if Instant::now() >= retry_at => match find(input) {
+                Some(source) => pending = Some(Feed::connect(input, source, config)?),
+                None => retry_at = Instant::now() + SOURCE_WAIT,
+            },
+            Some(_) => {}
+        }
+        // A route changed back before the new input sent anything
+        if pending.as_ref().map(|feed| &feed.input) != routed.as_ref() {
+            pending = None;
+        }
+
+        if let Some(first) = pending.as_mut().and_then(Feed::next_frame) {
+            let feed = pending.take().unwrap();
+            info!("{}: switching to {}", config.sender_name(), feed.input);
+            outlet.switch(config.transition, first, &feed.receiver);
+            outlet.stats.lock().unwrap().input = Some(feed.input.clone());
+            current = Some(feed);
+        } else if let Some(feed) = &mut current {
+            if let Some(frame) = feed.next_frame() {
+                outlet.video(frame, &feed.receiver);
+            }
+        }
+        outlet.tick();
+        if let Some(feed) = &current {
+            while let Ok(Some(chunk)) = feed.receiver.receive_audio_frame() {
+                outlet.sender.send_audio(&chunk);
+            }
+        }
+        outlet.stats.lock().unwrap().connections = outlet.sender.connections();
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routed_source_skips_quarantined() {
+        let mut unapproved = NdiSource::new("LAPTOP (Zoom)".to_string(), String::new());
+        unapproved.quarantined = true;
+        let cam = NdiSource::new("CAM (1)".to_string(), String::new());
+        let sources = vec![unapproved, cam];
+
+        assert!(routed_source(sources.clone(), "LAPTOP (Zoom)").is_none());
+        assert_eq!(routed_source(sources, "CAM (1)").unwrap().name, "CAM (1)");
+    }
+
+    #[test]
+    fn test_proxy_control() {
+        let proxies = vec![ProxyConfig {
+            source: "CAM (1)".to_string(),
+            name: None,
+            max_height: Some(540),
+            bandwidth: Default::default(),
+            frame_rate: Some("25".parse().unwrap()),
+            rate_conversion: Default::default(),
+            output: false,
+            transition: Default::default(),
+            enabled: false,
+        }];
+        let manager = ProxyManager::new(proxies, Arc::new(NdiDiscovery::new()));
+
+        let status = manager.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].name, "CAM (1) Proxy");
+        assert!(!status[0].running);
+        assert_eq!(status[0].frame_rate.unwrap().to_string(), "25");
+
+        assert!(manager.start("CAM (2)").is_err());
+        assert!(manager.start("CAM (1) Proxy").is_ok());
+        assert!(manager.stop("CAM (1)").is_ok());
+        assert!(!manager.status()[0].running);
+    }
+}
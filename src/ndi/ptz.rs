@@ -0,0 +1,255 @@
+//! Generic PTZ control over NDI metadata, for any camera that reports `ntk_ptz`
+//! (not just BirdDog cameras with their REST API)
+
+use super::{NdiReceiver, NdiSource, ReceiveBandwidth};
+use crate::birddog::ptz::{PtzCommand, PtzPosition};
+use anyhow::Result;
+use log::info;
+use std::time::Duration;
+
+/// Highest preset number in the NDI PTZ protocol
+pub const MAX_PRESET: u8 = 99;
+
+/// How often queued commands are checked while waiting for them to be sent
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A PTZ command in the standard NDI metadata protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum NdiPtzCommand {
+    /// Move to an absolute pan/tilt (-1.0 to 1.0)
+    PanTilt {
+        pan: f64,
+        tilt: f64,
+    },
+    /// Pan/tilt at a speed (-1.0 to 1.0, 0.0 stops)
+    PanTiltSpeed {
+        pan: f64,
+        tilt: f64,
+    },
+    /// Zoom to a level (0.0 zoomed in to 1.0 zoomed out, as NDI defines it)
+    Zoom(f64),
+    /// Zoom at a speed (-1.0 out to 1.0 in, 0.0 stops)
+    ZoomSpeed(f64),
+    /// Focus at a distance (0.0 near to 1.0 far)
+    Focus(f64),
+    AutoFocus,
+    StorePreset(u8),
+    /// Recall a preset, moving at a speed (0.0 to 1.0)
+    RecallPreset {
+        index: u8,
+        speed: f64,
+    },
+}
+
+impl NdiPtzCommand {
+    /// The metadata frame XML for this command
+    pub fn to_xml(&self) -> String {
+        match self {
+            NdiPtzCommand::PanTilt { pan, tilt } => format!(
+                r#"<ntk_ptz_pan_tilt pan="{:.6}" tilt="{:.6}"/>"#,
+                pan.clamp(-1.0, 1.0),
+                tilt.clamp(-1.0, 1.0)
+            ),
+            NdiPtzCommand::PanTiltSpeed { pan, tilt } => format!(
+                r#"<ntk_ptz_pan_tilt_speed pan_speed="{:.6}" tilt_speed="{:.6}"/>"#,
+                pan.clamp(-1.0, 1.0),
+                tilt.clamp(-1.0, 1.0)
+            ),
+            NdiPtzCommand::Zoom(zoom) => {
+                format!(r#"<ntk_ptz_zoom zoom="{:.6}"/>"#, zoom.clamp(0.0, 1.0))
+            }
+            NdiPtzCommand::ZoomSpeed(speed) => format!(
+                r#"<ntk_ptz_zoom_speed zoom_speed="{:.6}"/>"#,
+                speed.clamp(-1.0, 1.0)
+            ),
+            NdiPtzCommand::Focus(distance) => format!(
+                r#"<ntk_ptz_focus mode="manual" distance="{:.6}"/>"#,
+                distance.clamp(0.0, 1.0)
+            ),
+            NdiPtzCommand::AutoFocus => r#"<ntk_ptz_focus mode="auto"/>"#.to_string(),
+            NdiPtzCommand::StorePreset(index) => {
+                format!(r#"<ntk_ptz_store_preset index="{}"/>"#, index)
+            }
+            NdiPtzCommand::RecallPreset { index, speed } => format!(
+                r#"<ntk_ptz_recall_preset index="{}" speed="{:.6}"/>"#,
+                index,
+                speed.clamp(0.0, 1.0)
+            ),
+        }
+    }
+
+    /// Translate a BirdDog PTZ command (positions use the BirdDog zoom direction,
+    /// 0.0 wide to 1.0 tele)
+    pub fn from_birddog(command: &PtzCommand) -> Result<Vec<NdiPtzCommand>> {
+        Ok(match command {
+            PtzCommand::MoveAbsolute(position) => absolute(position),
+            PtzCommand::Home => absolute(&PtzPosition::home()),
+            PtzCommand::Stop => vec![
+                NdiPtzCommand::PanTiltSpeed {
+                    pan: 0.0,
+                    tilt: 0.0,
+                },
+                NdiPtzCommand::ZoomSpeed(0.0),
+            ],
+            PtzCommand::SavePreset(id) => vec![NdiPtzCommand::StorePreset(check_preset(*id)?)],
+            PtzCommand::RecallPreset(id) => vec![NdiPtzCommand::RecallPreset {
+                index: check_preset(*id)?,
+                speed: 1.0,
+            }],
+            PtzCommand::SetFocus(value) => vec![NdiPtzCommand::Focus(*value)],
+            PtzCommand::AutoFocus => vec![NdiPtzCommand::AutoFocus],
+            PtzCommand::MoveRelative { .. } => {
+                anyhow::bail!("NDI PTZ has no relative moves; use speeds or an absolute position")
+            }
+        })
+    }
+}
+
+fn absolute(position: &PtzPosition) -> Vec<NdiPtzCommand> {
+    vec![
+        NdiPtzCommand::PanTilt {
+            pan: position.pan,
+            tilt: position.tilt,
+        },
+        NdiPtzCommand::Zoom(1.0 - position.zoom),
+    ]
+}
+
+fn check_preset(id: u8) -> Result<u8> {
+    if id > MAX_PRESET {
+        anyhow::bail!("NDI PTZ presets are numbered 0 to {}", MAX_PRESET);
+    }
+    Ok(id)
+}
+
+/// Drives a camera's PTZ through a metadata-only NDI connection
+pub struct NdiPtz {
+    receiver: NdiReceiver,
+}
+
+impl NdiPtz {
+    /// Open a control connection to a source (no video or audio is pulled)
+    pub fn connect(source: NdiSource) -> Result<Self> {
+        let mut receiver = NdiReceiver::new();
+        receiver.connect(source, ReceiveBandwidth::MetadataOnly)?;
+        Ok(Self { receiver })
+    }
+
+    /// Control a camera by its NDI name
+    pub fn connect_by_name(ndi_name: &str) -> Result<Self> {
+        Self::connect(NdiSource::new(
+            ndi_name.to_string(),
+            format!("ndi://{}", ndi_name),
+        ))
+    }
+
+    /// Queue a command (sent as soon as the camera is connected)
+    pub fn send(&self, command: &NdiPtzCommand) -> Result<()> {
+        self.receiver.send_metadata(command.to_xml())
+    }
+
+    /// Queue a BirdDog-style PTZ command, translated to NDI
+    pub fn send_ptz_command(&self, command: &PtzCommand) -> Result<()> {
+        for command in NdiPtzCommand::from_birddog(command)? {
+            self.send(&command)?;
+        }
+        Ok(())
+    }
+
+    pub fn move_absolute(&self, position: PtzPosition) -> Result<()> {
+        self.send_ptz_command(&PtzCommand::MoveAbsolute(position))
+    }
+
+    /// Pan, tilt and zoom at speeds (-1.0 to 1.0; zeros stop)
+    pub fn move_speed(&self, pan: f64, tilt: f64, zoom: f64) -> Result<()> {
+        self.send(&NdiPtzCommand::PanTiltSpeed { pan, tilt })?;
+        self.send(&NdiPtzCommand::ZoomSpeed(zoom))
+    }
+
+    pub fn stop(&self) -> Result<()> {
+        self.send_ptz_command(&PtzCommand::Stop)
+    }
+
+    pub fn home(&self) -> Result<()> {
+        self.send_ptz_command(&PtzCommand::Home)
+    }
+
+    pub fn save_preset(&self, preset: u8) -> Result<()> {
+        self.send_ptz_command(&PtzCommand::SavePreset(preset))
+    }
+
+    pub fn recall_preset(&self, preset: u8) -> Result<()> {
+        self.send_ptz_command(&PtzCommand::RecallPreset(preset))
+    }
+
+    /// Wait until every queued command has been sent
+    pub async fn flush(&self, timeout: Duration) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.receiver.pending_metadata() > 0 {
+            if tokio::time::Instant::now() >= deadline {
+                let source = self.receiver.current_source().map(|s| s.name);
+                anyhow::bail!(
+                    "Timed out sending PTZ commands to {}",
+                    source.unwrap_or_default()
+                );
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        if let Some(source) = self.receiver.current_source() {
+            info!("PTZ commands sent to {}", source.name);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_xml() {
+        assert_eq!(
+            NdiPtzCommand::PanTiltSpeed {
+                pan: 0.5,
+                tilt: -2.0
+            }
+            .to_xml(),
+            r#"<ntk_ptz_pan_tilt_speed pan_speed="0.500000" tilt_speed="-1.000000"/>"#
+        );
+        assert_eq!(
+            NdiPtzCommand::RecallPreset {
+                index: 3,
+                speed: 1.0
+            }
+            .to_xml(),
+            r#"<ntk_ptz_recall_preset index="3" speed="1.000000"/>"#
+        );
+    }
+
+    #[test]
+    fn test_from_birddog() {
+        // BirdDog zoom 0.0 is wide, which NDI calls fully zoomed out
+        let commands = NdiPtzCommand::from_birddog(&PtzCommand::MoveAbsolute(PtzPosition::new(
+            0.25, -0.5, 0.0,
+        )))
+        .unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                NdiPtzCommand::PanTilt {
+                    pan: 0.25,
+                    tilt: -0.5
+                },
+                NdiPtzCommand::Zoom(1.0)
+            ]
+        );
+
+        assert!(NdiPtzCommand::from_birddog(&PtzCommand::RecallPreset(100)).is_err());
+        assert!(NdiPtzCommand::from_birddog(&PtzCommand::MoveRelative {
+            pan: 0.1,
+            tilt: 0.0,
+            zoom: 0.0
+        })
+        .is_err());
+    }
+}
@@ -1,30 +1,391 @@
+use super::capture::{Captured, RecvInstance, SentFormat};
+use super::health::{ConnectionEvent, ConnectionState, HealthMonitor};
+use super::metadata::{self, MetadataHub, SourceMetadata};
+use super::playout::{PlayoutBuffer, ReceiverTuning};
+use super::stats::{FrameStats, ReceiverStats, StatsHub};
+use super::throttle::FrameRateLimit;
 use super::NdiSource;
+use crate::audio::meter::{ChannelLevel, LevelMeter};
+use crate::audio::{AudioChunk, ChannelMap, InputTrim};
+use crate::matrix::TallyState;
+use crate::network::BandwidthMeter;
+use crate::supervisor::Supervisor;
+use crate::video::VideoFrame;
 use anyhow::Result;
-use log::{debug, info};
-use std::sync::{Arc, Mutex};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::broadcast;
+
+/// How long a capture call waits for a frame before checking for shutdown
+const CAPTURE_TIMEOUT_MS: u32 = 100;
+
+/// Audio chunks kept for a reader before the oldest are dropped
+const MAX_QUEUED_AUDIO: usize = 50;
+
+/// Audio without video for this long marks a source audio-only (e.g. an NDI audio bridge)
+const AUDIO_ONLY_AFTER: Duration = Duration::from_secs(3);
+
+/// How often statistics are published to a stats hub
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Weight of each new frame in the smoothed latency
+const LATENCY_SMOOTHING: f64 = 1.0 / 8.0;
+
+/// How much of a source a receiver pulls from the network
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReceiveBandwidth {
+    /// Full-quality video and audio
+    #[default]
+    Full,
+    /// Low-bandwidth proxy video and audio
+    Low,
+    /// Audio and metadata only
+    AudioOnly,
+    /// Metadata only (e.g. to send tally without pulling video)
+    MetadataOnly,
+}
+
+impl ReceiveBandwidth {
+    pub fn all() -> [ReceiveBandwidth; 4] {
+        [
+            ReceiveBandwidth::Full,
+            ReceiveBandwidth::Low,
+            ReceiveBandwidth::AudioOnly,
+            ReceiveBandwidth::MetadataOnly,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReceiveBandwidth::Full => "Full",
+            ReceiveBandwidth::Low => "Low (proxy)",
+            ReceiveBandwidth::AudioOnly => "Audio only",
+            ReceiveBandwidth::MetadataOnly => "Metadata only",
+        }
+    }
+
+    /// Whether video is requested at this bandwidth
+    pub fn includes_video(&self) -> bool {
+        matches!(self, ReceiveBandwidth::Full | ReceiveBandwidth::Low)
+    }
+
+    /// The larger of two bandwidths (for a source shown at several qualities)
+    pub fn max(self, other: ReceiveBandwidth) -> ReceiveBandwidth {
+        let rank = |b: ReceiveBandwidth| match b {
+            ReceiveBandwidth::MetadataOnly => 0,
+            ReceiveBandwidth::AudioOnly => 1,
+            ReceiveBandwidth::Low => 2,
+            ReceiveBandwidth::Full => 3,
+        };
+        if rank(other) > rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// What a source is actually delivering, from the frames received so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StreamInfo {
+    /// Video resolution (0x0 until the first video frame)
+    pub width: u32,
+    pub height: u32,
+    /// Frame rate as a fraction (e.g. 30000/1001), 0/0 if unknown
+    pub frame_rate_n: u32,
+    pub frame_rate_d: u32,
+    /// Pixel format or codec as sent (e.g. "UYVY", "H264")
+    pub pixel_format: Option<String>,
+    /// Audio format (0 until the first audio frame)
+    pub audio_channels: u16,
+    pub sample_rate: u32,
+    /// Audio arrives but no video although video was requested
+    pub audio_only: bool,
+    /// Smoothed time from the sender submitting a frame to it arriving here, if the
+    /// sender stamps its frames (only meaningful when both clocks are synchronized)
+    pub latency_ms: Option<f64>,
+}
+
+impl StreamInfo {
+    /// Frames per second, if the sender reported it
+    pub fn frame_rate(&self) -> Option<f64> {
+        (self.frame_rate_d > 0).then(|| self.frame_rate_n as f64 / self.frame_rate_d as f64)
+    }
+
+    pub fn has_video(&self) -> bool {
+        self.width > 0 && self.height > 0
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.audio_channels > 0
+    }
+
+    fn update_video(&mut self, frame: &VideoFrame, format: &SentFormat, received_at: SystemTime) {
+        self.width = frame.width;
+        self.height = frame.height;
+        self.frame_rate_n = format.frame_rate_n;
+        self.frame_rate_d = format.frame_rate_d;
+        self.pixel_format = Some(String::from_utf8_lossy(&format.fourcc).into_owned());
+        self.audio_only = false;
+        // A sender clock ahead of ours reads as no latency rather than none measured
+        self.latency_ms = format.sent_at.map(|sent_at| {
+            let latency = received_at
+                .duration_since(sent_at)
+                .unwrap_or_default()
+                .as_secs_f64()
+                * 1000.0;
+            match self.latency_ms {
+                Some(smoothed) => smoothed + (latency - smoothed) * LATENCY_SMOOTHING,
+                None => latency,
+            }
+        });
+    }
+
+    fn update_audio(&mut self, chunk: &AudioChunk) {
+        self.audio_channels = chunk.channels;
+        self.sample_rate = chunk.sample_rate;
+    }
+
+    /// Mark the stream audio-only once audio has arrived for a while without video
+    fn check_audio_only(&mut self, audio_for: Duration, wants_video: bool) {
+        self.audio_only =
+            wants_video && self.has_audio() && !self.has_video() && audio_for >= AUDIO_ONLY_AFTER;
+    }
+}
+
+/// e.g. "1920x1080 59.94 fps UYVY, 2 ch 48 kHz"
+impl fmt::Display for StreamInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.has_video() {
+            write!(f, "{}x{}", self.width, self.height)?;
+            if let Some(rate) = self.frame_rate() {
+                // Drop trailing zeros: 25 fps, 29.97 fps
+                let rate = format!("{:.2}", rate);
+                write!(
+                    f,
+                    " {} fps",
+                    rate.trim_end_matches('0').trim_end_matches('.')
+                )?;
+            }
+            if let Some(format) = &self.pixel_format {
+                write!(f, " {}", format)?;
+            }
+        } else if self.audio_only {
+            write!(f, "audio only")?;
+        } else {
+            write!(f, "no video")?;
+        }
+        if self.has_audio() {
+            write!(
+                f,
+                ", {} ch {} kHz",
+                self.audio_channels,
+                self.sample_rate as f64 / 1000.0
+            )?;
+        } else {
+            write!(f, ", no audio")?;
+        }
+        if let Some(latency) = self.latency_ms {
+            write!(f, ", {:.0} ms latency", latency)?;
+        }
+        Ok(())
+    }
+}
+
+/// Video frame from a receiver
+#[derive(Debug, Clone)]
+pub struct ReceivedFrame {
+    /// Incremented for every new frame
+    pub sequence: u64,
+    pub frame: Arc<VideoFrame>,
+}
 
 /// NDI receiver for viewing streams
 pub struct NdiReceiver {
     source: Option<NdiSource>,
+    receive_bandwidth: ReceiveBandwidth,
     is_active: Arc<Mutex<bool>>,
+    bandwidth: Arc<Mutex<BandwidthMeter>>,
+    /// Captured video waiting to be shown
+    video: Arc<Mutex<PlayoutBuffer>>,
+    /// Format of the received video and audio
+    stream_info: Arc<Mutex<StreamInfo>>,
+    /// Audio received but not yet read
+    audio: Arc<Mutex<VecDeque<AudioChunk>>>,
+    /// Peak and RMS level per audio channel
+    audio_meter: Arc<Mutex<LevelMeter>>,
+    /// Tally to send to the source
+    tally: Arc<Mutex<TallyState>>,
+    /// Latest metadata received from the source
+    metadata: Arc<Mutex<SourceMetadata>>,
+    /// Shared metadata of all sources (if any)
+    metadata_hub: Option<Arc<MetadataHub>>,
+    /// Metadata waiting to be sent upstream to the source
+    outgoing: Arc<Mutex<VecDeque<String>>>,
+    /// Stops the capture thread of the current connection
+    stop_capture: Option<Arc<AtomicBool>>,
+    /// Health of the current connection
+    connection: Arc<Mutex<ConnectionState>>,
+    /// Where connection state changes are published (if anywhere)
+    health_events: Option<broadcast::Sender<ConnectionEvent>>,
+    /// Restarts the capture thread if it fails
+    supervisor: Option<Arc<Supervisor>>,
+    /// Highest video frame rate wanted for display (None for every frame)
+    max_fps: Arc<Mutex<Option<u32>>>,
+    /// Which audio channels are read, and how
+    channel_map: Arc<Mutex<ChannelMap>>,
+    /// Gain and reference level applied to received audio before metering
+    trim: Arc<Mutex<InputTrim>>,
+    /// Video frames received and when
+    frame_stats: Arc<Mutex<FrameStats>>,
+    /// Where statistics are published about once a second (if anywhere)
+    stats_hub: Option<Arc<StatsHub>>,
 }
 
 impl NdiReceiver {
     pub fn new() -> Self {
         Self {
             source: None,
+            receive_bandwidth: ReceiveBandwidth::default(),
             is_active: Arc::new(Mutex::new(false)),
+            bandwidth: Arc::new(Mutex::new(BandwidthMeter::default())),
+            video: Arc::new(Mutex::new(PlayoutBuffer::new(ReceiverTuning::default()))),
+            stream_info: Arc::new(Mutex::new(StreamInfo::default())),
+            audio: Arc::new(Mutex::new(VecDeque::new())),
+            audio_meter: Arc::new(Mutex::new(LevelMeter::new())),
+            tally: Arc::new(Mutex::new(TallyState::default())),
+            metadata: Arc::new(Mutex::new(SourceMetadata::default())),
+            metadata_hub: None,
+            outgoing: Arc::new(Mutex::new(VecDeque::new())),
+            stop_capture: None,
+            connection: Arc::new(Mutex::new(ConnectionState::default())),
+            health_events: None,
+            supervisor: None,
+            max_fps: Arc::new(Mutex::new(None)),
+            channel_map: Arc::new(Mutex::new(ChannelMap::default())),
+            trim: Arc::new(Mutex::new(InputTrim::default())),
+            frame_stats: Arc::new(Mutex::new(FrameStats::new())),
+            stats_hub: None,
         }
     }
 
+    /// Publish connection state changes to a channel
+    pub fn with_health_events(mut self, events: broadcast::Sender<ConnectionEvent>) -> Self {
+        self.health_events = Some(events);
+        self
+    }
+
+    /// Restart the capture thread with backoff if it fails
+    pub fn with_supervisor(mut self, supervisor: Arc<Supervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    /// Also publish received metadata to a shared hub
+    pub fn with_metadata_hub(mut self, hub: Arc<MetadataHub>) -> Self {
+        self.metadata_hub = Some(hub);
+        self
+    }
+
+    /// Also publish statistics to a shared hub, about once a second
+    pub fn with_stats_hub(mut self, hub: Arc<StatsHub>) -> Self {
+        self.stats_hub = Some(hub);
+        self
+    }
+
+    /// Read the source's audio through a channel map (e.g. channels 3/4 only)
+    pub fn with_channel_map(self, map: ChannelMap) -> Self {
+        self.set_channel_map(map);
+        self
+    }
+
+    /// Change the channel map, for audio read from now on
+    pub fn set_channel_map(&self, map: ChannelMap) {
+        *self.channel_map.lock().unwrap() = map;
+    }
+
+    /// Trim the source's audio (e.g. a playback server lining up 20 dB hot)
+    pub fn with_trim(self, trim: InputTrim) -> Self {
+        self.set_trim(trim);
+        self
+    }
+
+    /// Change the trim, for audio received from now on
+    pub fn set_trim(&self, trim: InputTrim) {
+        *self.trim.lock().unwrap() = trim;
+    }
+
+    /// Start with latency settings other than the defaults
+    pub fn with_tuning(self, tuning: ReceiverTuning) -> Self {
+        self.set_tuning(tuning);
+        self
+    }
+
+    /// Change latency settings, taking effect immediately
+    pub fn set_tuning(&self, tuning: ReceiverTuning) {
+        self.video.lock().unwrap().set_tuning(tuning);
+    }
+
+    /// Current latency settings
+    pub fn tuning(&self) -> ReceiverTuning {
+        self.video.lock().unwrap().tuning()
+    }
+
+    /// Keep at most this many video frames a second (None for every frame),
+    /// skipping the conversion of the rest. Takes effect immediately
+    pub fn set_max_fps(&self, max_fps: Option<u32>) {
+        *self.max_fps.lock().unwrap() = max_fps;
+    }
+
+    /// Current display-rate limit
+    #[cfg(test)]
+    pub fn max_fps(&self) -> Option<u32> {
+        *self.max_fps.lock().unwrap()
+    }
+
+    /// Video frames dropped because the playout buffer was full
+    pub fn dropped_frames(&self) -> u64 {
+        self.video.lock().unwrap().dropped()
+    }
+
+    /// Current receive rate in bytes per second
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bandwidth
+            .lock()
+            .unwrap()
+            .bytes_per_second(Instant::now())
+    }
+
+    /// Frames received and dropped, jitter and bandwidth, cumulative and over
+    /// the last few seconds
+    pub fn stats(&self) -> ReceiverStats {
+        collect_stats(
+            &self.frame_stats,
+            &self.video,
+            &self.bandwidth,
+            Instant::now(),
+        )
+    }
+
     /// Connect to an NDI source
-    pub fn connect(&mut self, source: NdiSource) -> Result<()> {
-        info!("Connecting to NDI source: {}", source);
+    pub fn connect(&mut self, source: NdiSource, bandwidth: ReceiveBandwidth) -> Result<()> {
+        info!("Connecting to NDI source: {} ({:?})", source, bandwidth);
+        self.stop();
+        self.receive_bandwidth = bandwidth;
 
-        // In a real implementation, this would use the NDI SDK's receiver API
-        // Example:
-        // let recv = ndi::Receiver::new();
-        // recv.connect(&source);
+        match RecvInstance::connect(&source, bandwidth) {
+            Ok(recv) => self.spawn_capture(recv, &source),
+            // Without the SDK the receiver stays connected but never delivers frames
+            Err(e) if !cfg!(feature = "ndi") => debug!("{}", e),
+            Err(e) => return Err(e),
+        }
 
         self.source = Some(source.clone());
         let mut is_active = self.is_active.lock().unwrap();
@@ -34,11 +395,221 @@ impl NdiReceiver {
         Ok(())
     }
 
+    /// Capture frames on a background thread until the connection is stopped
+    fn spawn_capture(&mut self, recv: RecvInstance, source: &NdiSource) {
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop_capture = Some(Arc::clone(&stop));
+
+        let video = Arc::clone(&self.video);
+        let stream_info = Arc::clone(&self.stream_info);
+        let audio = Arc::clone(&self.audio);
+        let audio_meter = Arc::clone(&self.audio_meter);
+        let trim = Arc::clone(&self.trim);
+        let frame_stats = Arc::clone(&self.frame_stats);
+        let stats_hub = self.stats_hub.clone();
+        let bandwidth = Arc::clone(&self.bandwidth);
+        let tally = Arc::clone(&self.tally);
+        let source_metadata = Arc::clone(&self.metadata);
+        let hub = self.metadata_hub.clone();
+        let outgoing = Arc::clone(&self.outgoing);
+        let connection = Arc::clone(&self.connection);
+        let health_events = self.health_events.clone();
+        let max_fps = Arc::clone(&self.max_fps);
+        let source = source.clone();
+        let name = source.name.clone();
+        let receive_bandwidth = self.receive_bandwidth;
+        let initial = Mutex::new(Some(recv));
+        let task = format!("NDI receiver {}", name);
+
+        let capture = move || -> Result<()> {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            // A restart after a failure connects afresh
+            let taken = initial.lock().unwrap().take();
+            let mut recv = match taken {
+                Some(recv) => recv,
+                None => RecvInstance::connect(&source, receive_bandwidth)?,
+            };
+            let mut sequence = 0;
+            let mut audio_since = None;
+            let mut sent_tally = TallyState::default();
+            let mut limit = FrameRateLimit::default();
+            let mut health = HealthMonitor::new(
+                Instant::now(),
+                receive_bandwidth != ReceiveBandwidth::MetadataOnly,
+            );
+            let mut stats_published = Instant::now();
+            while !stop.load(Ordering::Relaxed) {
+                let wanted = *tally.lock().unwrap();
+                if wanted != sent_tally {
+                    debug!("Tally for {}: {:?}", name, wanted);
+                    recv.set_tally(wanted);
+                    sent_tally = wanted;
+                }
+                // Metadata is held until the source is connected, as the SDK drops it before then
+                if recv.is_connected() {
+                    let pending: Vec<String> = lock_live(&stop, &outgoing)
+                        .map(|mut outgoing| outgoing.drain(..).collect())
+                        .unwrap_or_default();
+                    for xml in pending {
+                        if let Err(e) = recv.send_metadata(&xml) {
+                            warn!("Failed to send metadata to {}: {}", name, e);
+                        }
+                    }
+                }
+                limit.set_max_fps(*max_fps.lock().unwrap());
+                let now = Instant::now();
+                let mut change = match recv.capture(CAPTURE_TIMEOUT_MS, &mut limit) {
+                    Captured::Video(frame, format, bytes) => {
+                        sequence += 1;
+                        record_bytes(&bandwidth, bytes);
+                        frame_stats.lock().unwrap().frame(now);
+                        if let Some(mut info) = lock_live(&stop, &stream_info) {
+                            info.update_video(&frame, &format, SystemTime::now());
+                        }
+                        if let Some(mut video) = lock_live(&stop, &video) {
+                            let frame = Arc::new(frame);
+                            video.push(ReceivedFrame { sequence, frame }, now);
+                        }
+                        health.frame(now)
+                    }
+                    Captured::Skipped(bytes) => {
+                        record_bytes(&bandwidth, bytes);
+                        frame_stats.lock().unwrap().frame(now);
+                        health.frame(now)
+                    }
+                    Captured::Audio(mut chunk, bytes) => {
+                        record_bytes(&bandwidth, bytes);
+                        trim.lock().unwrap().apply(&mut chunk);
+                        let since = *audio_since.get_or_insert(now);
+                        if let Some(mut info) = lock_live(&stop, &stream_info) {
+                            info.update_audio(&chunk);
+                            info.check_audio_only(now - since, receive_bandwidth.includes_video());
+                        }
+                        if let Some(mut meter) = lock_live(&stop, &audio_meter) {
+                            meter.push(&chunk.samples, chunk.channels as usize, now);
+                        }
+                        if let Some(mut audio) = lock_live(&stop, &audio) {
+                            if audio.len() == MAX_QUEUED_AUDIO {
+                                audio.pop_front();
+                            }
+                            audio.push_back(chunk);
+                        }
+                        health.frame(now)
+                    }
+                    Captured::Metadata(xml, bytes) => {
+                        record_bytes(&bandwidth, bytes);
+                        match metadata::parse(&xml) {
+                            Ok(events) => {
+                                for event in events {
+                                    let Some(mut current) = lock_live(&stop, &source_metadata)
+                                    else {
+                                        break;
+                                    };
+                                    current.apply(&event);
+                                    drop(current);
+                                    if let Some(hub) = &hub {
+                                        hub.publish(&name, event);
+                                    }
+                                }
+                            }
+                            Err(e) => debug!("Ignoring metadata from {}: {:#}", name, e),
+                        }
+                        health.frame(now)
+                    }
+                    Captured::Error => {
+                        warn!("Connection error on {}", name);
+                        health.error(now)
+                    }
+                    Captured::Nothing => health.check(now, recv.is_connected()),
+                };
+
+                // A dead source gets a fresh connection, in case it moved or restarted
+                if health.retry_due(now) {
+                    info!("Reconnecting to {}", name);
+                    match RecvInstance::connect(&source, receive_bandwidth) {
+                        Ok(fresh) => {
+                            recv = fresh;
+                            // Tally is sent again on the new connection
+                            sent_tally = TallyState::default();
+                            change = health.reconnecting(now);
+                        }
+                        Err(e) => {
+                            warn!("Failed to reconnect to {}: {:#}", name, e);
+                            health.retry_failed(now);
+                        }
+                    }
+                }
+
+                // A stopped connection no longer speaks for the receiver
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Some(hub) = &stats_hub {
+                    if now.duration_since(stats_published) >= STATS_INTERVAL {
+                        stats_published = now;
+                        hub.publish(&name, collect_stats(&frame_stats, &video, &bandwidth, now));
+                    }
+                }
+                if let Some(state) = change {
+                    info!("{} is {}", name, state);
+                    match lock_live(&stop, &connection) {
+                        Some(mut connection) => *connection = state,
+                        None => break,
+                    }
+                    if let Some(events) = &health_events {
+                        // No subscribers is fine
+                        let _ = events.send(ConnectionEvent {
+                            source: name.clone(),
+                            state,
+                        });
+                    }
+                }
+            }
+            // Don't leave the source's tally lit once we stop watching it
+            if !sent_tally.is_off() {
+                recv.set_tally(TallyState::default());
+            }
+            if let Some(hub) = &stats_hub {
+                hub.remove(&name);
+            }
+            debug!("Capture stopped for {}", name);
+            Ok(())
+        };
+        match &self.supervisor {
+            Some(supervisor) => supervisor.spawn_thread(&task, capture),
+            None => {
+                thread::spawn(move || {
+                    if let Err(e) = capture() {
+                        warn!("Capture failed: {:#}", e);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Stop the capture thread of the current connection
+    fn stop(&mut self) {
+        // Raised before the buffers are cleared: the thread checks it under each buffer's lock
+        if let Some(stop) = self.stop_capture.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        self.video.lock().unwrap().clear();
+        *self.stream_info.lock().unwrap() = StreamInfo::default();
+        self.audio.lock().unwrap().clear();
+        self.audio_meter.lock().unwrap().reset();
+        *self.metadata.lock().unwrap() = SourceMetadata::default();
+        self.outgoing.lock().unwrap().clear();
+        *self.connection.lock().unwrap() = ConnectionState::default();
+    }
+
     /// Disconnect from the current source
     pub fn disconnect(&mut self) {
         if let Some(source) = &self.source {
             info!("Disconnecting from: {}", source.name);
         }
+        self.stop();
 
         let mut is_active = self.is_active.lock().unwrap();
         *is_active = false;
@@ -50,52 +621,144 @@ impl NdiReceiver {
         *self.is_active.lock().unwrap()
     }
 
+    /// Bandwidth of the current connection
+    pub fn bandwidth(&self) -> ReceiveBandwidth {
+        self.receive_bandwidth
+    }
+
     /// Get current source
-    #[allow(dead_code)]
     pub fn current_source(&self) -> Option<NdiSource> {
         self.source.clone()
     }
 
-    /// Get video frame (placeholder for actual frame retrieval)
-    pub fn receive_video_frame(&self) -> Result<()> {
+    /// Get the video frame to show now (None until the first frame is ready)
+    pub fn receive_video_frame(&self) -> Result<Option<ReceivedFrame>> {
         if !self.is_active() {
             anyhow::bail!("Receiver is not active");
         }
 
-        // In real implementation:
-        // let frame = recv.capture_video(timeout);
-        // Process the frame data
+        Ok(self.video.lock().unwrap().frame(Instant::now()))
+    }
 
-        debug!("Receiving video frame...");
-        Ok(())
+    /// Health of the connection to the source
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.connection.lock().unwrap()
     }
 
-    /// Get audio frame (placeholder for actual frame retrieval)
-    #[allow(dead_code)]
-    pub fn receive_audio_frame(&self) -> Result<()> {
+    /// Latest received video frame as an image, for stills; playout is not affected
+    pub fn snapshot(&self) -> Result<image::RgbaImage> {
         if !self.is_active() {
             anyhow::bail!("Receiver is not active");
         }
 
-        // In real implementation:
-        // let frame = recv.capture_audio(timeout);
-        // Process the frame data
+        let received = self
+            .video
+            .lock()
+            .unwrap()
+            .latest()
+            .ok_or_else(|| anyhow::anyhow!("No video received from the source yet"))?;
+        received.frame.to_image()
+    }
+
+    /// Format of what the source is delivering (empty until frames arrive)
+    pub fn stream_info(&self) -> StreamInfo {
+        self.stream_info.lock().unwrap().clone()
+    }
 
-        debug!("Receiving audio frame...");
+    /// Take the oldest unread chunk of received audio (None when caught up),
+    /// with the channel map applied
+    pub fn receive_audio_frame(&self) -> Result<Option<AudioChunk>> {
+        if !self.is_active() {
+            anyhow::bail!("Receiver is not active");
+        }
+
+        let chunk = self.audio.lock().unwrap().pop_front();
+        Ok(chunk.map(|chunk| self.channel_map.lock().unwrap().apply(chunk)))
+    }
+
+    /// Peak and RMS level of each audio channel, updated about 20 times a second
+    /// (empty when no audio is arriving)
+    pub fn audio_levels(&self) -> Vec<ChannelLevel> {
+        self.audio_meter.lock().unwrap().levels(Instant::now())
+    }
+
+    /// Tally sent to the source, now and after reconnecting
+    pub fn set_tally(&self, tally: TallyState) {
+        *self.tally.lock().unwrap() = tally;
+    }
+
+    /// Tally currently requested for the source
+    pub fn tally(&self) -> TallyState {
+        *self.tally.lock().unwrap()
+    }
+
+    /// Drop audio received so far (e.g. before starting to listen)
+    pub fn clear_audio(&self) {
+        self.audio.lock().unwrap().clear();
+    }
+
+    /// Queue a metadata frame to send upstream to the source (sent once it is connected)
+    pub fn send_metadata(&self, xml: impl Into<String>) -> Result<()> {
+        if !self.is_active() {
+            anyhow::bail!("Receiver is not active");
+        }
+        if self.stop_capture.is_none() {
+            anyhow::bail!("RusTV was built without NDI SDK support (rebuild with --features ndi)");
+        }
+
+        self.outgoing.lock().unwrap().push_back(xml.into());
         Ok(())
     }
 
-    /// Get metadata (placeholder)
-    #[allow(dead_code)]
-    pub fn receive_metadata(&self) -> Result<String> {
+    /// Number of metadata frames not yet sent to the source
+    pub fn pending_metadata(&self) -> usize {
+        self.outgoing.lock().unwrap().len()
+    }
+
+    /// Latest metadata received from the source (product, capabilities, tally echo)
+    pub fn receive_metadata(&self) -> Result<SourceMetadata> {
         if !self.is_active() {
             anyhow::bail!("Receiver is not active");
         }
 
-        // In real implementation:
-        // let metadata = recv.capture_metadata(timeout);
+        Ok(self.metadata.lock().unwrap().clone())
+    }
+}
+
+impl Drop for NdiReceiver {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Lock a buffer shared with the receiver, unless the capture thread's connection
+/// was stopped. `stop` raises the flag before clearing the buffers, so a frame
+/// captured just before the stop can't land in the next connection's buffers
+fn lock_live<'a, T>(stop: &AtomicBool, buffer: &'a Mutex<T>) -> Option<MutexGuard<'a, T>> {
+    let guard = buffer.lock().unwrap();
+    (!stop.load(Ordering::Relaxed)).then_some(guard)
+}
+
+fn record_bytes(bandwidth: &Mutex<BandwidthMeter>, bytes: usize) {
+    bandwidth.lock().unwrap().record(Instant::now(), bytes);
+}
 
-        Ok(String::from("{}"))
+fn collect_stats(
+    frame_stats: &Mutex<FrameStats>,
+    video: &Mutex<PlayoutBuffer>,
+    bandwidth: &Mutex<BandwidthMeter>,
+    now: Instant,
+) -> ReceiverStats {
+    let mut frame_stats = frame_stats.lock().unwrap();
+    let (frames_per_second, jitter_ms) = frame_stats.windowed(now);
+    let mut bandwidth = bandwidth.lock().unwrap();
+    ReceiverStats {
+        frames_received: frame_stats.frames(),
+        frames_dropped: video.lock().unwrap().dropped(),
+        bytes_received: bandwidth.total_bytes(),
+        frames_per_second,
+        jitter_ms,
+        bytes_per_second: bandwidth.bytes_per_second(now),
     }
 }
 
@@ -115,10 +778,139 @@ mod tests {
         let source = NdiSource::new("Test".to_string(), "ndi://test".to_string());
 
         assert!(!receiver.is_active());
-        assert!(receiver.connect(source).is_ok());
+        assert!(receiver.connect(source, ReceiveBandwidth::Low).is_ok());
+        assert_eq!(receiver.bandwidth(), ReceiveBandwidth::Low);
         assert!(receiver.is_active());
+        assert!(receiver.receive_video_frame().unwrap().is_none());
+        assert!(receiver.receive_audio_frame().unwrap().is_none());
+        assert_eq!(
+            receiver.receive_metadata().unwrap(),
+            SourceMetadata::default()
+        );
 
         receiver.disconnect();
         assert!(!receiver.is_active());
+        assert!(receiver.receive_audio_frame().is_err());
+    }
+
+    #[test]
+    fn test_stopped_capture_fenced() {
+        let stop = AtomicBool::new(false);
+        let buffer = Mutex::new(VecDeque::from([1]));
+        lock_live(&stop, &buffer).unwrap().push_back(2);
+
+        stop.store(true, Ordering::Relaxed);
+        assert!(lock_live(&stop, &buffer).is_none());
+        assert_eq!(*buffer.lock().unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn test_stream_info() {
+        let mut info = StreamInfo::default();
+        assert!(!info.has_video());
+        assert_eq!(info.to_string(), "no video, no audio");
+
+        info.update_video(
+            &VideoFrame::new(2, 1, vec![0; 8]),
+            &SentFormat {
+                fourcc: *b"UYVY",
+                frame_rate_n: 30000,
+                frame_rate_d: 1001,
+                sent_at: None,
+            },
+            SystemTime::now(),
+        );
+        info.update_audio(&AudioChunk {
+            sample_rate: 48000,
+            channels: 2,
+            samples: Vec::new(),
+        });
+        assert_eq!(info.to_string(), "2x1 29.97 fps UYVY, 2 ch 48 kHz");
+
+        info.frame_rate_n = 25;
+        info.frame_rate_d = 1;
+        assert_eq!(info.frame_rate(), Some(25.0));
+        assert!(info.to_string().starts_with("2x1 25 fps"));
+    }
+
+    #[test]
+    fn test_audio_only_detection() {
+        let mut info = StreamInfo::default();
+        info.update_audio(&AudioChunk {
+            sample_rate: 48000,
+            channels: 2,
+            samples: Vec::new(),
+        });
+        info.check_audio_only(Duration::from_secs(1), true);
+        assert!(!info.audio_only);
+        info.check_audio_only(AUDIO_ONLY_AFTER, true);
+        assert!(info.audio_only);
+        assert_eq!(info.to_string(), "audio only, 2 ch 48 kHz");
+
+        // Not when video was never asked for
+        info.check_audio_only(AUDIO_ONLY_AFTER, false);
+        assert!(!info.audio_only);
+
+        info.check_audio_only(AUDIO_ONLY_AFTER, true);
+        info.update_video(
+            &VideoFrame::new(2, 1, vec![0; 8]),
+            &SentFormat {
+                fourcc: *b"UYVY",
+                frame_rate_n: 25,
+                frame_rate_d: 1,
+                sent_at: None,
+            },
+            SystemTime::now(),
+        );
+        assert!(!info.audio_only);
+    }
+
+    #[test]
+    fn test_latency() {
+        let mut info = StreamInfo::default();
+        let frame = VideoFrame::new(2, 1, vec![0; 8]);
+        let sent_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let format = SentFormat {
+            fourcc: *b"UYVY",
+            frame_rate_n: 25,
+            frame_rate_d: 1,
+            sent_at: Some(sent_at),
+        };
+
+        info.update_video(&frame, &format, sent_at + Duration::from_millis(80));
+        assert_eq!(info.latency_ms, Some(80.0));
+        assert!(info.to_string().ends_with(", no audio, 80 ms latency"));
+
+        // Smoothed, so a single late frame moves it only a little
+        info.update_video(&frame, &format, sent_at + Duration::from_millis(160));
+        assert_eq!(info.latency_ms, Some(90.0));
+
+        // Clocks out of sync
+        info.update_video(&frame, &format, sent_at - Duration::from_millis(5));
+        assert!(info.latency_ms.unwrap() < 90.0);
+
+        // Unstamped frames
+        let unstamped = SentFormat {
+            sent_at: None,
+            ..format
+        };
+        info.update_video(&frame, &unstamped, SystemTime::now());
+        assert_eq!(info.latency_ms, None);
+    }
+
+    #[test]
+    fn test_bandwidth_max() {
+        assert_eq!(
+            ReceiveBandwidth::Low.max(ReceiveBandwidth::Full),
+            ReceiveBandwidth::Full
+        );
+        assert_eq!(
+            ReceiveBandwidth::Low.max(ReceiveBandwidth::AudioOnly),
+            ReceiveBandwidth::Low
+        );
+        assert_eq!(
+            ReceiveBandwidth::MetadataOnly.max(ReceiveBandwidth::AudioOnly),
+            ReceiveBandwidth::AudioOnly
+        );
     }
 }
@@ -0,0 +1,290 @@
+//! NDI routing destinations: every matrix output advertised as an NDI routing
+//! source ("RusTV <output>"), so NDI tools on the network list RusTV's outputs
+//! and receivers pointed at one follow whatever the matrix routes to it
+
+use super::{NdiDiscovery, NdiSource};
+use crate::events::{EventCategory, EventLog};
+use crate::matrix::MatrixRouter;
+use anyhow::Result;
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often destinations are brought in step with the matrix
+const ROUTING_INTERVAL: Duration = Duration::from_millis(500);
+
+/// NDI name a destination is published under
+pub fn destination_name(output: &str) -> String {
+    format!("RusTV {}", output)
+}
+
+/// A routing source published on the network under its own NDI name
+#[cfg(feature = "ndi")]
+struct RoutingInstance {
+    instance: super::sys::NDIlib_routing_instance_t,
+}
+
+// SAFETY: NDI routing instances may be used from any thread; each instance is
+// owned by the destinations task and destroyed once on drop.
+#[cfg(feature = "ndi")]
+unsafe impl Send for RoutingInstance {}
+
+#[cfg(feature = "ndi")]
+impl RoutingInstance {
+    fn create(name: &str) -> Result<Self> {
+        use super::sys;
+        use anyhow::Context;
+        use std::ffi::CString;
+
+        super::initialize_runtime()?;
+
+        let ndi_name = CString::new(name).context("Invalid NDI routing name")?;
+        let settings = sys::NDIlib_routing_create_t {
+            p_ndi_name: ndi_name.as_ptr(),
+            p_groups: std::ptr::null(),
+        };
+
+        // SAFETY: settings and the name it points to outlive the call; the SDK copies them
+        let instance = unsafe { sys::NDIlib_routing_create(&settings) };
+        if instance.is_null() {
+            anyhow::bail!("Failed to create NDI routing {}", name);
+        }
+        Ok(Self { instance })
+    }
+
+    /// Send receivers of the destination to a source
+    fn change(&mut self, source: &NdiSource) -> Result<()> {
+        use super::sys;
+        use anyhow::Context;
+        use std::ffi::CString;
+
+        let name = CString::new(source.name.as_str()).context("Invalid NDI source name")?;
+        let url = CString::new(source.url.as_str()).context("Invalid NDI source URL")?;
+        let source = sys::NDIlib_source_t {
+            p_ndi_name: name.as_ptr(),
+            p_url_address: url.as_ptr(),
+        };
+        // SAFETY: instance is a live routing; the SDK copies the source strings
+        if unsafe { sys::NDIlib_routing_change(self.instance, &source) } {
+            Ok(())
+        } else {
+            anyhow::bail!("NDI routing change was refused")
+        }
+    }
+
+    /// Leave receivers of the destination without a source
+    fn clear(&mut self) {
+        // SAFETY: instance is a live routing
+        unsafe { super::sys::NDIlib_routing_clear(self.instance) };
+    }
+
+    /// NDI name of the source the destination currently points at
+    fn source_name(&self) -> Option<String> {
+        // SAFETY: instance is a live routing; the name is copied before any other call
+        let name = unsafe { super::sys::NDIlib_routing_get_source_name(self.instance) };
+        if name.is_null() {
+            return None;
+        }
+        // SAFETY: a non-null name is a NUL-terminated string owned by the SDK
+        let name = unsafe { std::ffi::CStr::from_ptr(name) };
+        Some(name.to_string_lossy().into_owned()).filter(|name| !name.is_empty())
+    }
+}
+
+#[cfg(feature = "ndi")]
+impl Drop for RoutingInstance {
+    fn drop(&mut self) {
+        // SAFETY: instance was created by NDIlib_routing_create and is destroyed once
+        unsafe { super::sys::NDIlib_routing_destroy(self.instance) }
+    }
+}
+
+/// Placeholder routing used when built without the NDI SDK
+#[cfg(not(feature = "ndi"))]
+struct RoutingInstance;
+
+#[cfg(not(feature = "ndi"))]
+impl RoutingInstance {
+    fn create(_name: &str) -> Result<Self> {
+        anyhow::bail!("RusTV was built without NDI SDK support (rebuild with --features ndi)")
+    }
+
+    fn change(&mut self, _source: &NdiSource) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear(&mut self) {}
+
+    fn source_name(&self) -> Option<String> {
+        None
+    }
+}
+
+/// What brings a destination in step with the matrix
+#[derive(Debug, PartialEq)]
+enum Step {
+    Keep,
+    /// Point the destination at the routed source, or at nothing
+    Change,
+    /// The destination was moved elsewhere: route this source to the output,
+    /// or unroute it
+    Adopt(Option<String>),
+}
+
+/// Compare the source last set on a destination, the one it points at now and
+/// the one the matrix routes to its output (all NDI names)
+fn step(set: Option<&str>, current: Option<&str>, routed: Option<&str>) -> Step {
+    if current != set {
+        Step::Adopt(current.map(str::to_string))
+    } else if routed != set {
+        Step::Change
+    } else {
+        Step::Keep
+    }
+}
+
+/// One published destination
+struct Destination {
+    instance: RoutingInstance,
+    /// Source last pointed at from here
+    set: Option<String>,
+}
+
+/// Keeps an NDI routing destination per output in step with the matrix, and
+/// the matrix in step with destinations moved from elsewhere
+pub struct RoutingDestinations {
+    router: Arc<Mutex<MatrixRouter>>,
+    discovery: Arc<NdiDiscovery>,
+    events: EventLog,
+}
+
+impl RoutingDestinations {
+    pub fn new(
+        router: Arc<Mutex<MatrixRouter>>,
+        discovery: Arc<NdiDiscovery>,
+        events: EventLog,
+    ) -> Self {
+        Self {
+            router,
+            discovery,
+            events,
+        }
+    }
+
+    /// Start publishing destinations in the background
+    pub fn start(self) {
+        info!("Advertising matrix outputs as NDI routing destinations");
+        tokio::spawn(async move {
+            // None for outputs whose destination couldn't be created, so it
+            // isn't retried every pass
+            let mut destinations: HashMap<String, Option<Destination>> = HashMap::new();
+            let mut interval = tokio::time::interval(ROUTING_INTERVAL);
+            loop {
+                interval.tick().await;
+                self.update(&mut destinations);
+            }
+        });
+    }
+
+    fn update(&self, destinations: &mut HashMap<String, Option<Destination>>) {
+        let routes: Vec<(String, Option<String>)> = {
+            let router = self.router.lock().unwrap();
+            router
+                .get_outputs()
+                .iter()
+                .map(|output| (output.clone(), router.get_route(output).cloned()))
+                .collect()
+        };
+        destinations.retain(|output, _| routes.iter().any(|(o, _)| o == output));
+        let sources = self.discovery.get_sources();
+
+        for (output, input) in routes {
+            let destination = destinations.entry(output.clone()).or_insert_with(|| {
+                let name = destination_name(&output);
+                match RoutingInstance::create(&name) {
+                    Ok(instance) => Some(Destination {
+                        instance,
+                        set: None,
+                    }),
+                    Err(e) => {
+                        error!("Failed to publish {}: {}", name, e);
+                        None
+                    }
+                }
+            });
+            let Some(destination) = destination else {
+                continue;
+            };
+
+            let routed: Option<&NdiSource> = input
+                .as_deref()
+                .and_then(|input| sources.iter().find(|s| s.matches(input)))
+                .filter(|source| !source.quarantined);
+            let current = destination.instance.source_name();
+            match step(
+                destination.set.as_deref(),
+                current.as_deref(),
+                routed.map(|s| s.name.as_str()),
+            ) {
+                Step::Keep => {}
+                Step::Change => match routed {
+                    Some(source) => match destination.instance.change(source) {
+                        Ok(()) => destination.set = Some(source.name.clone()),
+                        Err(e) => error!("Failed to route {} to {}: {}", source.name, output, e),
+                    },
+                    None => {
+                        destination.instance.clear();
+                        destination.set = None;
+                    }
+                },
+                Step::Adopt(Some(input)) => {
+                    let result = self.router.lock().unwrap().route(&input, &output);
+                    match result {
+                        Ok(()) => self.events.info(
+                            EventCategory::Routing,
+                            "ndi-routing",
+                            format!("Routed {} -> {}", input, output),
+                        ),
+                        // The matrix's route is put back next pass
+                        Err(e) => warn!("Ignoring NDI routing of {} to {}: {}", input, output, e),
+                    }
+                    destination.set = Some(input);
+                }
+                Step::Adopt(None) => {
+                    if self.router.lock().unwrap().unroute(&output).is_some() {
+                        self.events.info(
+                            EventCategory::Routing,
+                            "ndi-routing",
+                            format!("Cleared {}", output),
+                        );
+                    }
+                    destination.set = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing_step() {
+        assert_eq!(destination_name("Program"), "RusTV Program");
+
+        // Matrix changes are pushed to the destination
+        assert_eq!(step(None, None, None), Step::Keep);
+        assert_eq!(step(None, None, Some("CAM1")), Step::Change);
+        assert_eq!(step(Some("CAM1"), Some("CAM1"), Some("CAM1")), Step::Keep);
+        assert_eq!(step(Some("CAM1"), Some("CAM1"), None), Step::Change);
+
+        // A destination moved from elsewhere wins over the matrix
+        assert_eq!(
+            step(Some("CAM1"), Some("CAM2"), Some("CAM1")),
+            Step::Adopt(Some("CAM2".into()))
+        );
+        assert_eq!(step(Some("CAM1"), None, Some("CAM1")), Step::Adopt(None));
+    }
+}
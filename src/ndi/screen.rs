@@ -0,0 +1,62 @@
+//! Publishes a capture of the local screen as an NDI sender, so graphics or score
+//! feeds on this machine can be routed like any other source
+
+use super::sender::SendInstance;
+use crate::video::screen::{ScreenGrabber, ScreenTarget};
+use anyhow::Result;
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Settings of a screen sender
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenSenderConfig {
+    pub target: ScreenTarget,
+    /// NDI name of the sender
+    pub name: String,
+    /// Frames captured per second
+    pub fps: u32,
+    /// Scale down to at most this many lines
+    pub max_height: Option<u32>,
+}
+
+/// Capture and send frames until `stop` is set
+pub fn run(config: &ScreenSenderConfig, stop: &AtomicBool) -> Result<()> {
+    let mut grabber = ScreenGrabber::open(&config.target)?;
+    let mut sender = SendInstance::create(&config.name)?;
+    let fps = config.fps.clamp(1, 60);
+    let interval = Duration::from_secs(1) / fps;
+    info!(
+        "Sending {} as NDI source {} @ {} fps",
+        config.target, config.name, fps
+    );
+
+    let mut connections = 0;
+    let mut next_frame = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        let frame = grabber.grab()?;
+        let frame = match config.max_height {
+            Some(max_height) => frame.downscale(max_height),
+            None => frame,
+        };
+        sender.send_video(&frame, fps, 1);
+
+        let now_connected = sender.connections();
+        if now_connected != connections {
+            info!("{} receiver(s) watching {}", now_connected, config.name);
+            connections = now_connected;
+        }
+
+        next_frame += interval;
+        let now = Instant::now();
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        } else if now - next_frame > interval * 10 {
+            // Capture is slower than the frame rate; don't try to catch up
+            warn!("Screen capture can't keep up with {} fps", fps);
+            next_frame = now;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! NDI send instance backed by the NDI SDK (requires the `ndi` feature)
+
+use crate::audio::AudioChunk;
+use crate::video::VideoFrame;
+use anyhow::Result;
+
+/// A sender published on the network under its own NDI name
+#[cfg(feature = "ndi")]
+pub struct SendInstance {
+    instance: super::sys::NDIlib_send_instance_t,
+}
+
+// SAFETY: NDI send instances may be used from any thread; each instance is
+// owned by a single proxy thread and destroyed once on drop.
+#[cfg(feature = "ndi")]
+unsafe impl Send for SendInstance {}
+
+#[cfg(feature = "ndi")]
+impl SendInstance {
+    /// Publish a sender; frames are sent as they arrive rather than clocked
+    pub fn create(name: &str) -> Result<Self> {
+        use super::sys;
+        use anyhow::Context;
+        use std::ffi::CString;
+
+        super::initialize_runtime()?;
+
+        let ndi_name = CString::new(name).context("Invalid NDI sender name")?;
+        let settings = sys::NDIlib_send_create_t {
+            p_ndi_name: ndi_name.as_ptr(),
+            p_groups: std::ptr::null(),
+            clock_video: false,
+            clock_audio: false,
+        };
+
+        // SAFETY: settings and the name it points to outlive the call; the SDK copies them
+        let instance = unsafe { sys::NDIlib_send_create(&settings) };
+        if instance.is_null() {
+            anyhow::bail!("Failed to create NDI sender {}", name);
+        }
+        Ok(Self { instance })
+    }
+
+    /// Send an RGBA video frame
+    pub fn send_video(&mut self, frame: &VideoFrame, frame_rate_n: u32, frame_rate_d: u32) {
+        use super::sys;
+
+        let (frame_rate_n, frame_rate_d) = if frame_rate_n == 0 || frame_rate_d == 0 {
+            (30000, 1001)
+        } else {
+            (frame_rate_n, frame_rate_d)
+        };
+        let video = sys::NDIlib_video_frame_v2_t {
+            xres: frame.width as i32,
+            yres: frame.height as i32,
+            FourCC: sys::NDIlib_FourCC_video_type_RGBA,
+            frame_rate_N: frame_rate_n as i32,
+            frame_rate_D: frame_rate_d as i32,
+            picture_aspect_ratio: 0.0,
+            frame_format_type: sys::NDIlib_frame_format_type_progressive,
+            timecode: sys::NDIlib_send_timecode_synthesize,
+            p_data: frame.data.as_ptr() as *mut u8,
+            line_stride_in_bytes: (frame.width * 4) as i32,
+            p_metadata: std::ptr::null(),
+            timestamp: 0,
+        };
+        // SAFETY: instance is a live sender; the synchronous send has finished with
+        // the frame data when it returns
+        unsafe { sys::NDIlib_send_send_video_v2(self.instance, &video) };
+    }
+
+    /// Send interleaved audio (converted to the planar layout NDI uses)
+    pub fn send_audio(&mut self, chunk: &AudioChunk) {
+        use super::sys;
+
+        let channels = chunk.channels.max(1) as usize;
+        let samples = chunk.samples.len() / channels;
+        let mut planar = deinterleave(&chunk.samples, channels);
+        let audio = sys::NDIlib_audio_frame_v2_t {
+            sample_rate: chunk.sample_rate as i32,
+            no_channels: channels as i32,
+            no_samples: samples as i32,
+            timecode: sys::NDIlib_send_timecode_synthesize,
+            p_data: planar.as_mut_ptr(),
+            channel_stride_in_bytes: (samples * 4) as i32,
+            p_metadata: std::ptr::null(),
+            timestamp: 0,
+        };
+        // SAFETY: instance is a live sender and planar outlives the synchronous send
+        unsafe { sys::NDIlib_send_send_audio_v2(self.instance, &audio) };
+    }
+
+    /// Number of receivers connected to this sender
+    pub fn connections(&self) -> usize {
+        // SAFETY: instance is a live sender; a zero timeout returns immediately
+        unsafe { super::sys::NDIlib_send_get_no_connections(self.instance, 0) }.max(0) as usize
+    }
+}
+
+#[cfg(feature = "ndi")]
+impl Drop for SendInstance {
+    fn drop(&mut self) {
+        // SAFETY: instance was created by NDIlib_send_create and is destroyed once
+        unsafe { super::sys::NDIlib_send_destroy(self.instance) }
+    }
+}
+
+/// Placeholder sender used when built without the NDI SDK
+#[cfg(not(feature = "ndi"))]
+pub struct SendInstance;
+
+#[cfg(not(feature = "ndi"))]
+impl SendInstance {
+    pub fn create(_name: &str) -> Result<Self> {
+        anyhow::bail!("RusTV was built without NDI SDK support (rebuild with --features ndi)")
+    }
+
+    pub fn send_video(&mut self, _frame: &VideoFrame, _frame_rate_n: u32, _frame_rate_d: u32) {}
+
+    pub fn send_audio(&mut self, _chunk: &AudioChunk) {}
+
+    pub fn connections(&self) -> usize {
+        0
+    }
+}
+
+/// Split interleaved audio into one plane per channel
+#[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+fn deinterleave(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    let samples = interleaved.len() / channels;
+    let mut planar = Vec::with_capacity(channels * samples);
+    for channel in 0..channels {
+        planar.extend((0..samples).map(|i| interleaved[i * channels + channel]));
+    }
+    planar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deinterleave() {
+        let interleaved = [1.0, -1.0, 2.0, -2.0, 3.0, -3.0];
+        assert_eq!(
+            deinterleave(&interleaved, 2),
+            vec![1.0, 2.0, 3.0, -1.0, -2.0, -3.0]
+        );
+    }
+}
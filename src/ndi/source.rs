@@ -1,5 +1,7 @@
+use crate::matrix::TallyState;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::net::IpAddr;
 
 /// Represents an NDI source discovered on the network
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -7,6 +9,46 @@ pub struct NdiSource {
     pub name: String,
     pub url: String,
     pub groups: Vec<String>,
+    /// Sends audio but no video (e.g. an NDI audio bridge), once a receiver noticed
+    #[serde(default)]
+    pub audio_only: bool,
+    /// Friendly name from `[ndi.aliases]`, shown instead of the NDI name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Newly discovered and awaiting approval (`[ndi.approval]`); listed but not routable
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quarantined: bool,
+    /// Listed from `static_sources` in `[ndi]` rather than discovered, so scans
+    /// never remove it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub static_source: bool,
+    /// Sender's IP address, from the source's URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<IpAddr>,
+    /// Machine part of the NDI name ("MACHINE (Stream)" -> "MACHINE")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine: Option<String>,
+    /// Remote site the source arrives from through NDI Bridge (`[[ndi.bridges]]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+    /// Video format, once a receiver has seen the source: width and height
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<(u32, u32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_rate: Option<f64>,
+    /// Pixel format or codec as sent (e.g. "UYVY", "H264")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codec: Option<String>,
+    /// When discovery last found the source, in milliseconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen_ms: Option<u64>,
+    /// Tally the source reports (e.g. on air on another switcher), once a
+    /// receiver has heard it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tally: Option<TallyState>,
+    /// Tags from `[ndi.tags]` patterns the source matches (e.g. "camera", "backup")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl NdiSource {
@@ -15,18 +57,177 @@ impl NdiSource {
             name,
             url,
             groups: Vec::new(),
+            audio_only: false,
+            alias: None,
+            quarantined: false,
+            static_source: false,
+            ip_address: None,
+            machine: None,
+            site: None,
+            resolution: None,
+            frame_rate: None,
+            codec: None,
+            last_seen_ms: None,
+            tally: None,
+            tags: Vec::new(),
         }
     }
 
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "ndi"), allow(dead_code))]
     pub fn with_groups(mut self, groups: Vec<String>) -> Self {
         self.groups = groups;
         self
     }
+
+    /// Name to show operators: the alias if there is one, else the NDI name
+    pub fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether an input (NDI name, URL or alias) refers to this source
+    pub fn matches(&self, input: &str) -> bool {
+        self.name == input || self.url == input || self.alias.as_deref() == Some(input)
+    }
+
+    /// Whether the source has a tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+    }
+
+    /// Machine part of an NDI name ("MACHINE (Stream)" -> "MACHINE")
+    pub fn machine_name(&self) -> &str {
+        match self.name.find(" (") {
+            Some(idx) if self.name.ends_with(')') => &self.name[..idx],
+            _ => &self.name,
+        }
+    }
+
+    /// Stream part of an NDI name ("MACHINE (Stream)" -> "Stream")
+    pub fn stream_name(&self) -> &str {
+        match self.name.find(" (") {
+            Some(idx) if self.name.ends_with(')') => &self.name[idx + 2..self.name.len() - 1],
+            _ => &self.name,
+        }
+    }
+
+    /// IP address of the sender, if the URL contains one
+    pub fn address(&self) -> Option<IpAddr> {
+        let host = self
+            .url
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .split('/')
+            .next()
+            .unwrap_or_default();
+
+        if let Ok(ip) = host.parse() {
+            return Some(ip);
+        }
+        if let Ok(addr) = host.parse::<std::net::SocketAddr>() {
+            return Some(addr.ip());
+        }
+        host.rsplit_once(':').and_then(|(ip, _)| ip.parse().ok())
+    }
+
+    /// What is known about the source beyond its name, on one line (e.g.
+    /// "10.0.0.5 · STUDIO-PC · 1920x1080 · 59.94 fps · UYVY · seen 3 s ago")
+    pub fn details(&self, now_ms: u64) -> String {
+        let mut details = Vec::new();
+        details.extend(self.site.as_ref().map(|site| format!("🌐 {}", site)));
+        details.extend(self.ip_address.map(|ip| ip.to_string()));
+        details.extend(self.machine.clone());
+        details.extend(self.resolution.map(|(w, h)| format!("{}x{}", w, h)));
+        details.extend(self.frame_rate.map(|fps| format!("{:.2} fps", fps)));
+        details.extend(self.codec.clone());
+        details.extend(
+            self.last_seen_ms
+                .map(|seen| format!("seen {} s ago", now_ms.saturating_sub(seen) / 1000)),
+        );
+        details.join(" · ")
+    }
 }
 
 impl fmt::Display for NdiSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "NDI Source: {} ({})", self.name, self.url)
+        match &self.alias {
+            Some(alias) => write!(f, "NDI Source: {} = {} ({})", alias, self.name, self.url)?,
+            None => write!(f, "NDI Source: {} ({})", self.name, self.url)?,
+        }
+        if let Some(site) = &self.site {
+            write!(f, " [via NDI Bridge from {}]", site)?;
+        }
+        if self.quarantined {
+            write!(f, " [awaiting approval]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_parts() {
+        let source = NdiSource::new("STUDIO-PC (OBS Output 3)".to_string(), String::new());
+        assert_eq!(source.machine_name(), "STUDIO-PC");
+        assert_eq!(source.stream_name(), "OBS Output 3");
+
+        let plain = NdiSource::new("Camera".to_string(), String::new());
+        assert_eq!(plain.machine_name(), "Camera");
+        assert_eq!(plain.stream_name(), "Camera");
+    }
+
+    #[test]
+    fn test_alias() {
+        let mut source = NdiSource::new(
+            "STUDIO-PC (OBS Output 3)".to_string(),
+            "ndi://10.0.0.5".to_string(),
+        );
+        assert_eq!(source.display_name(), "STUDIO-PC (OBS Output 3)");
+        assert!(!source.matches("Slides"));
+
+        source.alias = Some("Slides".to_string());
+        assert_eq!(source.display_name(), "Slides");
+        assert!(source.matches("Slides"));
+        assert!(source.matches("STUDIO-PC (OBS Output 3)"));
+        assert!(source.matches("ndi://10.0.0.5"));
+        assert_eq!(
+            source.to_string(),
+            "NDI Source: Slides = STUDIO-PC (OBS Output 3) (ndi://10.0.0.5)"
+        );
+    }
+
+    #[test]
+    fn test_address() {
+        let source = NdiSource::new("A".to_string(), "192.168.1.20:5961".to_string());
+        assert_eq!(source.address(), "192.168.1.20".parse().ok());
+
+        let source = NdiSource::new("A".to_string(), "ndi://10.0.0.2".to_string());
+        assert_eq!(source.address(), "10.0.0.2".parse().ok());
+
+        let source = NdiSource::new("A".to_string(), "ndi://camera".to_string());
+        assert_eq!(source.address(), None);
+    }
+
+    #[test]
+    fn test_details() {
+        let mut source = NdiSource::new(
+            "STUDIO-PC (OBS Output 3)".to_string(),
+            "10.0.0.5:5961".to_string(),
+        );
+        assert_eq!(source.details(0), "");
+
+        source.ip_address = source.address();
+        source.machine = Some(source.machine_name().to_string());
+        source.resolution = Some((1920, 1080));
+        source.frame_rate = Some(60000.0 / 1001.0);
+        source.codec = Some("UYVY".to_string());
+        source.last_seen_ms = Some(10_000);
+        assert_eq!(
+            source.details(13_500),
+            "10.0.0.5 · STUDIO-PC · 1920x1080 · 59.94 fps · UYVY · seen 3 s ago"
+        );
     }
 }
@@ -0,0 +1,140 @@
+//! Receiver statistics for long-term monitoring of link quality: frames
+//! received and dropped, inter-frame jitter and bandwidth, cumulative and over a
+//! recent window
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Period the windowed figures cover
+pub const STATS_WINDOW: Duration = Duration::from_secs(10);
+
+/// Statistics of one receiver
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReceiverStats {
+    /// Video frames received since the receiver was created
+    pub frames_received: u64,
+    /// Video frames dropped because the playout buffer was full
+    pub frames_dropped: u64,
+    /// Bytes received since the receiver was created
+    pub bytes_received: u64,
+    /// Video frames per second over the window
+    pub frames_per_second: f64,
+    /// Mean deviation of the time between frames from its average, over the window
+    pub jitter_ms: f64,
+    /// Receive rate over the bandwidth meter's window
+    pub bytes_per_second: f64,
+}
+
+/// Counts video frames and when they arrived
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    frames: u64,
+    /// Arrivals within the window, oldest first
+    arrivals: VecDeque<Instant>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A video frame arrived
+    pub fn frame(&mut self, now: Instant) {
+        self.frames += 1;
+        self.arrivals.push_back(now);
+        self.expire(now);
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) > STATS_WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Frames per second and jitter (ms) over the window ending at `now`
+    pub fn windowed(&mut self, now: Instant) -> (f64, f64) {
+        self.expire(now);
+        let intervals: Vec<f64> = self
+            .arrivals
+            .iter()
+            .zip(self.arrivals.iter().skip(1))
+            .map(|(a, b)| b.duration_since(*a).as_secs_f64() * 1000.0)
+            .collect();
+        if intervals.is_empty() {
+            return (0.0, 0.0);
+        }
+        let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+        let jitter =
+            intervals.iter().map(|i| (i - mean).abs()).sum::<f64>() / intervals.len() as f64;
+        let fps = if mean > 0.0 { 1000.0 / mean } else { 0.0 };
+        (fps, jitter)
+    }
+}
+
+/// Latest statistics of every receiver that publishes them, for the control API
+#[derive(Default)]
+pub struct StatsHub {
+    sources: Mutex<HashMap<String, ReceiverStats>>,
+}
+
+impl StatsHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn publish(&self, source: &str, stats: ReceiverStats) {
+        self.sources
+            .lock()
+            .unwrap()
+            .insert(source.to_string(), stats);
+    }
+
+    /// Forget a source whose receiver stopped
+    pub fn remove(&self, source: &str) {
+        self.sources.lock().unwrap().remove(source);
+    }
+
+    pub fn get(&self, source: &str) -> Option<ReceiverStats> {
+        self.sources.lock().unwrap().get(source).cloned()
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, ReceiverStats> {
+        self.sources.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_stats() {
+        let start = Instant::now();
+        let mut stats = FrameStats::new();
+        assert_eq!(stats.windowed(start), (0.0, 0.0));
+
+        // 25 fps with every other frame 8 ms late
+        for i in 0..100u32 {
+            let late = if i % 2 == 1 { 8 } else { 0 };
+            stats.frame(start + Duration::from_millis(40) * i + Duration::from_millis(late));
+        }
+        let (fps, jitter) = stats.windowed(start + Duration::from_secs(4));
+        assert_eq!(stats.frames(), 100);
+        assert!((fps - 25.0).abs() < 0.5, "{}", fps);
+        assert!((jitter - 8.0).abs() < 0.5, "{}", jitter);
+
+        // Old arrivals leave the window, the count stays
+        assert_eq!(stats.windowed(start + Duration::from_secs(30)), (0.0, 0.0));
+        assert_eq!(stats.frames(), 100);
+    }
+}
@@ -0,0 +1,233 @@
+//! Raw bindings to the NDI SDK finder, receiver, sender and routing APIs (`Processing.NDI.Lib.h`)
+
+#![allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
+
+use std::os::raw::{c_char, c_int, c_void};
+
+pub type NDIlib_find_instance_t = *mut c_void;
+pub type NDIlib_recv_instance_t = *mut c_void;
+pub type NDIlib_send_instance_t = *mut c_void;
+pub type NDIlib_routing_instance_t = *mut c_void;
+
+/// `NDIlib_frame_type_video`
+pub const NDIlib_frame_type_video: i32 = 1;
+/// `NDIlib_frame_type_audio`
+pub const NDIlib_frame_type_audio: i32 = 2;
+/// `NDIlib_frame_type_metadata`
+pub const NDIlib_frame_type_metadata: i32 = 3;
+/// `NDIlib_frame_type_error`
+pub const NDIlib_frame_type_error: i32 = 4;
+/// `NDIlib_recv_color_format_UYVY_RGBA`: UYVY, or RGBA for sources with alpha
+#[cfg_attr(feature = "hx", allow(dead_code))]
+pub const NDIlib_recv_color_format_UYVY_RGBA: i32 = 3;
+/// `NDIlib_recv_color_format_ex_compressed_v5` (Advanced SDK): HX video stays compressed,
+/// other sources and audio are delivered decoded as usual
+#[cfg_attr(not(feature = "hx"), allow(dead_code))]
+pub const NDIlib_recv_color_format_compressed_v5: i32 = 307;
+/// `NDIlib_FourCC_video_type_RGBA`
+pub const NDIlib_FourCC_video_type_RGBA: u32 = u32::from_le_bytes(*b"RGBA");
+/// `NDIlib_frame_format_type_progressive`
+pub const NDIlib_frame_format_type_progressive: i32 = 1;
+/// `NDIlib_send_timecode_synthesize`
+pub const NDIlib_send_timecode_synthesize: i64 = i64::MAX;
+/// `NDIlib_recv_timestamp_undefined`: the sender didn't stamp the frame
+pub const NDIlib_recv_timestamp_undefined: i64 = i64::MAX;
+/// `NDIlib_recv_bandwidth_metadata_only`
+pub const NDIlib_recv_bandwidth_metadata_only: i32 = -10;
+/// `NDIlib_recv_bandwidth_audio_only`
+pub const NDIlib_recv_bandwidth_audio_only: i32 = 10;
+/// `NDIlib_recv_bandwidth_lowest`
+pub const NDIlib_recv_bandwidth_lowest: i32 = 0;
+/// `NDIlib_recv_bandwidth_highest`
+pub const NDIlib_recv_bandwidth_highest: i32 = 100;
+
+#[repr(C)]
+pub struct NDIlib_find_create_t {
+    pub show_local_sources: bool,
+    pub p_groups: *const c_char,
+    pub p_extra_ips: *const c_char,
+}
+
+#[repr(C)]
+pub struct NDIlib_source_t {
+    pub p_ndi_name: *const c_char,
+    /// Union with `p_ip_address` in the C headers
+    pub p_url_address: *const c_char,
+}
+
+#[repr(C)]
+pub struct NDIlib_recv_create_v3_t {
+    pub source_to_connect_to: NDIlib_source_t,
+    pub color_format: i32,
+    pub bandwidth: i32,
+    pub allow_video_fields: bool,
+    pub p_ndi_recv_name: *const c_char,
+}
+
+#[repr(C)]
+pub struct NDIlib_send_create_t {
+    pub p_ndi_name: *const c_char,
+    pub p_groups: *const c_char,
+    pub clock_video: bool,
+    pub clock_audio: bool,
+}
+
+#[repr(C)]
+pub struct NDIlib_routing_create_t {
+    pub p_ndi_name: *const c_char,
+    pub p_groups: *const c_char,
+}
+
+#[repr(C)]
+pub struct NDIlib_video_frame_v2_t {
+    pub xres: i32,
+    pub yres: i32,
+    pub FourCC: u32,
+    pub frame_rate_N: i32,
+    pub frame_rate_D: i32,
+    pub picture_aspect_ratio: f32,
+    pub frame_format_type: i32,
+    pub timecode: i64,
+    pub p_data: *mut u8,
+    /// Union with `data_size_in_bytes` in the C headers
+    pub line_stride_in_bytes: i32,
+    pub p_metadata: *const c_char,
+    pub timestamp: i64,
+}
+
+/// Planar 32-bit float audio
+#[repr(C)]
+pub struct NDIlib_audio_frame_v2_t {
+    pub sample_rate: i32,
+    pub no_channels: i32,
+    pub no_samples: i32,
+    pub timecode: i64,
+    pub p_data: *mut f32,
+    pub channel_stride_in_bytes: i32,
+    pub p_metadata: *const c_char,
+    pub timestamp: i64,
+}
+
+#[repr(C)]
+pub struct NDIlib_metadata_frame_t {
+    /// Length of the string in bytes, including the terminating NUL
+    pub length: i32,
+    pub timecode: i64,
+    pub p_data: *mut c_char,
+}
+
+#[repr(C)]
+pub struct NDIlib_tally_t {
+    pub on_program: bool,
+    pub on_preview: bool,
+}
+
+#[cfg_attr(
+    all(windows, not(feature = "hx")),
+    link(name = "Processing.NDI.Lib.x64")
+)]
+#[cfg_attr(
+    all(windows, feature = "hx"),
+    link(name = "Processing.NDI.Lib.Advanced.x64")
+)]
+#[cfg_attr(all(not(windows), not(feature = "hx")), link(name = "ndi"))]
+#[cfg_attr(all(not(windows), feature = "hx"), link(name = "ndi_advanced"))]
+extern "C" {
+    pub fn NDIlib_initialize() -> bool;
+
+    pub fn NDIlib_version() -> *const c_char;
+
+    pub fn NDIlib_find_create_v2(
+        p_create_settings: *const NDIlib_find_create_t,
+    ) -> NDIlib_find_instance_t;
+
+    pub fn NDIlib_find_destroy(p_instance: NDIlib_find_instance_t);
+
+    pub fn NDIlib_find_wait_for_sources(
+        p_instance: NDIlib_find_instance_t,
+        timeout_in_ms: u32,
+    ) -> bool;
+
+    pub fn NDIlib_find_get_current_sources(
+        p_instance: NDIlib_find_instance_t,
+        p_no_sources: *mut u32,
+    ) -> *const NDIlib_source_t;
+
+    pub fn NDIlib_recv_create_v3(
+        p_create_settings: *const NDIlib_recv_create_v3_t,
+    ) -> NDIlib_recv_instance_t;
+
+    pub fn NDIlib_recv_destroy(p_instance: NDIlib_recv_instance_t);
+
+    pub fn NDIlib_recv_capture_v2(
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        p_audio_data: *mut NDIlib_audio_frame_v2_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> i32;
+
+    pub fn NDIlib_recv_free_video_v2(
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *const NDIlib_video_frame_v2_t,
+    );
+
+    pub fn NDIlib_recv_free_audio_v2(
+        p_instance: NDIlib_recv_instance_t,
+        p_audio_data: *const NDIlib_audio_frame_v2_t,
+    );
+
+    pub fn NDIlib_recv_free_metadata(
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    );
+
+    pub fn NDIlib_recv_set_tally(
+        p_instance: NDIlib_recv_instance_t,
+        p_tally: *const NDIlib_tally_t,
+    ) -> bool;
+
+    pub fn NDIlib_recv_send_metadata(
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) -> bool;
+
+    pub fn NDIlib_recv_get_no_connections(p_instance: NDIlib_recv_instance_t) -> c_int;
+
+    pub fn NDIlib_send_create(
+        p_create_settings: *const NDIlib_send_create_t,
+    ) -> NDIlib_send_instance_t;
+
+    pub fn NDIlib_send_destroy(p_instance: NDIlib_send_instance_t);
+
+    pub fn NDIlib_send_send_video_v2(
+        p_instance: NDIlib_send_instance_t,
+        p_video_data: *const NDIlib_video_frame_v2_t,
+    );
+
+    pub fn NDIlib_send_send_audio_v2(
+        p_instance: NDIlib_send_instance_t,
+        p_audio_data: *const NDIlib_audio_frame_v2_t,
+    );
+
+    pub fn NDIlib_send_get_no_connections(
+        p_instance: NDIlib_send_instance_t,
+        timeout_in_ms: u32,
+    ) -> c_int;
+
+    pub fn NDIlib_routing_create(
+        p_create_settings: *const NDIlib_routing_create_t,
+    ) -> NDIlib_routing_instance_t;
+
+    pub fn NDIlib_routing_destroy(p_instance: NDIlib_routing_instance_t);
+
+    pub fn NDIlib_routing_change(
+        p_instance: NDIlib_routing_instance_t,
+        p_source: *const NDIlib_source_t,
+    ) -> bool;
+
+    pub fn NDIlib_routing_clear(p_instance: NDIlib_routing_instance_t) -> bool;
+
+    /// Name of the source currently routed, or null; valid until the next change
+    pub fn NDIlib_routing_get_source_name(p_instance: NDIlib_routing_instance_t) -> *const c_char;
+}
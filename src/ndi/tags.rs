@@ -0,0 +1,106 @@
+//! Source tags (camera, graphics, remote, backup…) given by name pattern, and
+//! saved filters picking out sources by tag
+
+use super::NdiSource;
+use crate::matrix::wildcard::WildcardRoute;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Tag every source with the tags of each pattern its NDI name or alias
+/// matches (sorted, without duplicates)
+pub fn apply_tags(sources: &mut [NdiSource], tags: &BTreeMap<String, Vec<String>>) {
+    let patterns: Vec<(WildcardRoute, &Vec<String>)> = tags
+        .iter()
+        .map(|(pattern, tags)| (WildcardRoute::new(pattern.clone()), tags))
+        .collect();
+    for source in sources {
+        let mut found: Vec<String> = patterns
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(source))
+            .flat_map(|(_, tags)| tags.iter().map(|tag| tag.trim().to_lowercase()))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        found.sort();
+        found.dedup();
+        source.tags = found;
+    }
+}
+
+/// Tags typed as a comma-separated list ("camera, backup")
+pub fn parse_list(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = text
+        .split(',')
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// A saved selection of sources (e.g. "Cameras only"): those with every one of its tags
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceFilter {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+impl SourceFilter {
+    /// A filter on one tag, named after it
+    pub fn tag(tag: &str) -> Self {
+        Self {
+            name: format!("#{}", tag),
+            tags: vec![tag.to_string()],
+        }
+    }
+
+    pub fn matches(&self, source: &NdiSource) -> bool {
+        self.tags.iter().all(|tag| source.has_tag(tag))
+    }
+
+    /// The sources the filter picks out
+    #[cfg(test)]
+    pub fn select<'a>(&self, sources: &'a [NdiSource]) -> Vec<&'a NdiSource> {
+        sources.iter().filter(|s| self.matches(s)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_and_filters() {
+        let mut sources = vec![
+            NdiSource::new("CAM-1 (Wide)".into(), "ndi://10.0.0.21:5961".into()),
+            NdiSource::new("CAM-2 (Tight)".into(), "ndi://10.0.0.22:5961".into()),
+            NdiSource::new("STUDIO-PC (Slides)".into(), "ndi://10.0.0.5:5961".into()),
+        ];
+        sources[1].alias = Some("Backup Cam".into());
+        let tags = BTreeMap::from([
+            ("CAM-*".to_string(), vec!["Camera".to_string()]),
+            ("backup*".to_string(), vec!["backup".to_string()]),
+            (
+                "STUDIO-PC (Slides)".to_string(),
+                vec!["graphics".to_string()],
+            ),
+        ]);
+        apply_tags(&mut sources, &tags);
+        assert_eq!(sources[0].tags, ["camera"]);
+        assert_eq!(sources[1].tags, ["backup", "camera"]);
+        assert_eq!(sources[2].tags, ["graphics"]);
+
+        let cameras = SourceFilter::tag("camera");
+        assert_eq!(cameras.name, "#camera");
+        assert_eq!(cameras.select(&sources).len(), 2);
+        let backup_cameras = SourceFilter {
+            name: "Backup cameras".into(),
+            tags: vec!["camera".into(), "Backup".into()],
+        };
+        let selected = backup_cameras.select(&sources);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].name, "CAM-2 (Tight)");
+
+        assert_eq!(parse_list(" Remote, camera,,remote "), ["camera", "remote"]);
+    }
+}
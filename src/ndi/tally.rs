@@ -0,0 +1,154 @@
+//! Headless tally: keeps a receiver on every input routed to a program or
+//! preview output so the source lights its tally LEDs. Also watches the tally
+//! sources report back, which includes other switchers taking them to air
+
+use super::{MetadataHub, NdiDiscovery, NdiReceiver, NdiSource, ReceiveBandwidth};
+use crate::matrix::wildcard::WildcardRoute;
+use crate::matrix::{MatrixRouter, TallyOutputs};
+use log::{error, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often routes are checked for tally changes
+const TALLY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often watched sources are checked for appearing and going away
+const REMOTE_TALLY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Sends tally to sources as routes change
+pub struct TallyEmitter {
+    router: Arc<Mutex<MatrixRouter>>,
+    discovery: Arc<NdiDiscovery>,
+    outputs: TallyOutputs,
+    metadata: Arc<MetadataHub>,
+}
+
+impl TallyEmitter {
+    pub fn new(
+        router: Arc<Mutex<MatrixRouter>>,
+        discovery: Arc<NdiDiscovery>,
+        outputs: TallyOutputs,
+        metadata: Arc<MetadataHub>,
+    ) -> Self {
+        Self {
+            router,
+            discovery,
+            outputs,
+            metadata,
+        }
+    }
+
+    /// Start following routes in the background
+    pub fn start(self) {
+        info!("Sending tally to sources on program and preview outputs");
+        tokio::spawn(async move {
+            let mut receivers: HashMap<String, NdiReceiver> = HashMap::new();
+            let mut interval = tokio::time::interval(TALLY_INTERVAL);
+            loop {
+                interval.tick().await;
+                self.update(&mut receivers);
+            }
+        });
+    }
+
+    fn update(&self, receivers: &mut HashMap<String, NdiReceiver>) {
+        let routes = self.router.lock().unwrap().get_all_routes();
+        let tally = self.outputs.tally(&routes);
+
+        // Dropping a receiver clears the tally of its source
+        receivers.retain(|input, _| tally.contains_key(input));
+
+        for (input, state) in tally {
+            if let Some(receiver) = receivers.get(&input) {
+                receiver.set_tally(state);
+                continue;
+            }
+            let Some(source) = self
+                .discovery
+                .get_sources()
+                .into_iter()
+                .find(|s| s.matches(&input))
+            else {
+                continue;
+            };
+
+            let mut receiver = NdiReceiver::new().with_metadata_hub(Arc::clone(&self.metadata));
+            receiver.set_tally(state);
+            // Tally travels as metadata, so no video or audio is pulled
+            match receiver.connect(source.clone(), ReceiveBandwidth::MetadataOnly) {
+                Ok(()) => {
+                    receivers.insert(input, receiver);
+                }
+                Err(e) => error!("Failed to connect to {} for tally: {}", source.name, e),
+            }
+        }
+    }
+}
+
+/// Keeps a metadata-only receiver on every watched source, so the tally it
+/// echoes reaches the metadata hub even while nothing here shows it
+pub struct RemoteTallyWatcher {
+    discovery: Arc<NdiDiscovery>,
+    /// Source names, aliases or patterns (e.g. "CAM-*") to watch
+    watched: Vec<WildcardRoute>,
+    metadata: Arc<MetadataHub>,
+}
+
+impl RemoteTallyWatcher {
+    pub fn new(
+        discovery: Arc<NdiDiscovery>,
+        watched: &[String],
+        metadata: Arc<MetadataHub>,
+    ) -> Self {
+        Self {
+            discovery,
+            watched: watched.iter().cloned().map(WildcardRoute::new).collect(),
+            metadata,
+        }
+    }
+
+    /// Whether a source is watched
+    pub fn watches(&self, source: &NdiSource) -> bool {
+        !source.quarantined && self.watched.iter().any(|w| w.matches(source))
+    }
+
+    /// Start watching in the background
+    pub fn start(self) {
+        info!(
+            "Watching the tally reported by {} source pattern(s)",
+            self.watched.len()
+        );
+        tokio::spawn(async move {
+            let mut receivers: HashMap<String, NdiReceiver> = HashMap::new();
+            let mut interval = tokio::time::interval(REMOTE_TALLY_INTERVAL);
+            loop {
+                interval.tick().await;
+                self.update(&mut receivers);
+            }
+        });
+    }
+
+    fn update(&self, receivers: &mut HashMap<String, NdiReceiver>) {
+        let sources: Vec<NdiSource> = self
+            .discovery
+            .get_sources()
+            .into_iter()
+            .filter(|s| self.watches(s))
+            .collect();
+        receivers.retain(|name, _| sources.iter().any(|s| &s.name == name));
+
+        for source in sources {
+            if receivers.contains_key(&source.name) {
+                continue;
+            }
+            let mut receiver = NdiReceiver::new().with_metadata_hub(Arc::clone(&self.metadata));
+            match receiver.connect(source.clone(), ReceiveBandwidth::MetadataOnly) {
+                Ok(()) => {
+                    receivers.insert(source.name.clone(), receiver);
+                }
+                Err(e) => error!("Failed to connect to {} for tally: {}", source.name, e),
+            }
+        }
+    }
+}
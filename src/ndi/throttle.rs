@@ -0,0 +1,83 @@
+//! Display-rate limit of a receiver: video frames beyond the rate a slot is
+//! shown at are dropped before they are converted
+
+// Only the SDK capture path throttles frames
+#![cfg_attr(not(feature = "ndi"), allow(dead_code))]
+
+use std::time::{Duration, Instant};
+
+/// A frame arriving this much before it is due still counts as on time, so
+/// jitter doesn't turn every other admitted frame into a skipped one
+const EARLY_TOLERANCE: Duration = Duration::from_millis(5);
+
+/// Admits video frames at up to a maximum rate, evenly spaced
+#[derive(Debug, Clone, Default)]
+pub struct FrameRateLimit {
+    max_fps: Option<u32>,
+    /// When the next frame may be admitted
+    next: Option<Instant>,
+}
+
+impl FrameRateLimit {
+    pub fn new(max_fps: Option<u32>) -> Self {
+        Self {
+            max_fps: max_fps.filter(|fps| *fps > 0),
+            next: None,
+        }
+    }
+
+    /// Change the maximum rate (`None` admits every frame)
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        let max_fps = max_fps.filter(|fps| *fps > 0);
+        if max_fps != self.max_fps {
+            *self = Self::new(max_fps);
+        }
+    }
+
+    /// Whether a frame arriving now should be kept
+    pub fn admit(&mut self, now: Instant) -> bool {
+        let Some(fps) = self.max_fps else {
+            return true;
+        };
+        let interval = Duration::from_secs(1) / fps;
+        match self.next {
+            Some(next) if now + EARLY_TOLERANCE < next => false,
+            // Keep the cadence, unless the source paused for longer than a frame
+            Some(next) if now < next + interval => {
+                self.next = Some(next + interval);
+                true
+            }
+            _ => {
+                self.next = Some(now + interval);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_rate_limit() {
+        let start = Instant::now();
+        let frame = Duration::from_secs(1) / 60;
+
+        let mut limit = FrameRateLimit::new(Some(10));
+        let admitted = (0..60).filter(|i| limit.admit(start + frame * *i)).count();
+        assert_eq!(admitted, 10);
+
+        // A 30 fps source limited to 15 fps keeps every other frame
+        let mut limit = FrameRateLimit::new(Some(15));
+        let kept: Vec<bool> = (0..6)
+            .map(|i| limit.admit(start + Duration::from_secs(1) / 30 * i))
+            .collect();
+        assert_eq!(kept, [true, false, true, false, true, false]);
+
+        let mut limit = FrameRateLimit::new(None);
+        assert!((0..10).all(|i| limit.admit(start + frame * i)));
+        limit.set_max_fps(Some(0));
+        assert!(limit.admit(start));
+    }
+}
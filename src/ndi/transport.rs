@@ -0,0 +1,265 @@
+//! NDI receive transport (multicast, TCP or RUDP), chosen globally and per source
+//!
+//! The SDK has no transport field in its receiver settings; it reads the
+//! preference from its runtime configuration file (`ndi-config.v1.json`) as each
+//! receiver is created. We write that file, together with the finder's discovery
+//! settings, into a directory of our own that `NDI_CONFIG_DIR` points the runtime
+//! at from startup. A user-provided `NDI_CONFIG_DIR` always wins.
+
+// Only the SDK receive path writes the runtime configuration
+#![cfg_attr(not(feature = "ndi"), allow(dead_code))]
+
+use crate::config::NdiConfig;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// Environment variable naming the directory of the NDI configuration file
+const CONFIG_DIR_VAR: &str = "NDI_CONFIG_DIR";
+
+/// Name of the NDI runtime configuration file
+const CONFIG_FILE: &str = "ndi-config.v1.json";
+
+/// Transport policy from configuration, set once at startup
+static POLICY: OnceLock<TransportPolicy> = OnceLock::new();
+
+/// Directory of the runtime configuration file we write, one per process
+/// (None when the user's own `NDI_CONFIG_DIR` is used)
+#[cfg(feature = "ndi")]
+static CONFIG_DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+
+/// The runtime configuration file we maintain
+static RUNTIME: Mutex<RuntimeFile> = Mutex::new(RuntimeFile {
+    base: None,
+    written: None,
+});
+
+/// How a receiver asks the sender to deliver its stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NdiTransport {
+    /// Leave the choice to the NDI runtime (UDP unicast on current versions)
+    #[default]
+    Auto,
+    /// Multicast, falling back to unicast if the sender doesn't offer it
+    Multicast,
+    /// Unicast over TCP
+    Tcp,
+    /// Unicast over reliable UDP
+    Rudp,
+}
+
+impl NdiTransport {
+    pub fn name(self) -> &'static str {
+        match self {
+            NdiTransport::Auto => "Auto (runtime default)",
+            NdiTransport::Multicast => "Multicast",
+            NdiTransport::Tcp => "TCP",
+            NdiTransport::Rudp => "Reliable UDP",
+        }
+    }
+
+    /// Receive settings of the runtime configuration selecting this transport
+    pub fn runtime_config(self) -> Option<Value> {
+        let (multicast, tcp, rudp) = match self {
+            NdiTransport::Auto => return None,
+            NdiTransport::Multicast => (true, false, false),
+            NdiTransport::Tcp => (false, true, false),
+            NdiTransport::Rudp => (false, false, true),
+        };
+        let recv = |enable: bool| serde_json::json!({ "recv": { "enable": enable } });
+        Some(serde_json::json!({
+            "ndi": {
+                "multicast": recv(multicast),
+                "tcp": recv(tcp),
+                "rudp": recv(rudp),
+            }
+        }))
+    }
+}
+
+/// Transport of every receiver, with overrides by NDI source name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransportPolicy {
+    pub default: NdiTransport,
+    pub sources: BTreeMap<String, NdiTransport>,
+}
+
+impl TransportPolicy {
+    /// Policy from the `[ndi]` configuration
+    pub fn from_config(config: &NdiConfig) -> Self {
+        Self {
+            default: config.transport,
+            sources: config.source_transports.clone(),
+        }
+    }
+
+    /// Use this policy for every receiver from now on
+    pub fn configure(self) {
+        if POLICY.set(self).is_err() {
+            warn!("NDI transport already configured");
+        }
+    }
+
+    /// Transport used to receive a source
+    pub fn for_source(&self, name: &str) -> NdiTransport {
+        match self.sources.get(name) {
+            Some(&transport) if transport != NdiTransport::Auto => transport,
+            _ => self.default,
+        }
+    }
+}
+
+/// Merge `overlay` into `base`, recursing into objects present in both
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// State of the runtime configuration file
+pub struct RuntimeFile {
+    /// Settings written for every receiver (the finder's discovery settings)
+    base: Option<Value>,
+    /// Transport the file currently selects, once written
+    written: Option<NdiTransport>,
+}
+
+impl RuntimeFile {
+    /// Write the file for a transport unless it already selects it
+    fn apply(&mut self, transport: NdiTransport) -> Result<()> {
+        let Some(dir) = runtime_dir() else {
+            return Ok(());
+        };
+        if self.written == Some(transport) {
+            return Ok(());
+        }
+        // Nothing to write until something differs from the runtime's defaults
+        if self.base.is_none() && self.written.is_none() && transport == NdiTransport::Auto {
+            return Ok(());
+        }
+
+        let mut config = self.base.clone().unwrap_or_else(|| serde_json::json!({}));
+        if let Some(settings) = transport.runtime_config() {
+            merge(&mut config, settings);
+        }
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(&config)?)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        self.written = Some(transport);
+        Ok(())
+    }
+}
+
+/// Give the NDI runtime a configuration directory of this process's own, unless
+/// the user set `NDI_CONFIG_DIR`. Call before any other thread is started: the
+/// environment may only be changed while the process is single-threaded
+#[cfg(feature = "ndi")]
+pub fn prepare_config_dir() -> Result<()> {
+    if std::env::var_os(CONFIG_DIR_VAR).is_some() {
+        return Ok(());
+    }
+    let dir = tempfile::Builder::new()
+        .prefix("rustv-ndi-")
+        .tempdir()
+        .context("Failed to create the NDI configuration directory")?;
+    std::env::set_var(CONFIG_DIR_VAR, dir.path());
+    let _ = CONFIG_DIR.set(dir);
+    Ok(())
+}
+
+/// Directory of the runtime configuration file we write, if we write one
+fn runtime_dir() -> Option<&'static Path> {
+    #[cfg(feature = "ndi")]
+    return CONFIG_DIR.get().map(|dir| dir.path());
+    #[cfg(not(feature = "ndi"))]
+    None
+}
+
+/// Add settings every receiver and finder should use (before the runtime initializes).
+/// Returns false if the user's own `NDI_CONFIG_DIR` configuration is used instead
+pub fn set_base(settings: Value) -> Result<bool> {
+    let mut runtime = RUNTIME.lock().unwrap();
+    if runtime_dir().is_none() {
+        warn!(
+            "{} is set; using its NDI configuration instead of RusTV's",
+            CONFIG_DIR_VAR
+        );
+        return Ok(false);
+    }
+    merge(
+        runtime.base.get_or_insert_with(|| serde_json::json!({})),
+        settings,
+    );
+    let transport = POLICY.get().map(|p| p.default).unwrap_or_default();
+    runtime.written = None;
+    runtime.apply(transport)?;
+    Ok(true)
+}
+
+/// Select the transport of a source before its receiver is created. Hold the
+/// returned guard until the SDK has created the receiver, so concurrent
+/// connections don't rewrite the file under it
+pub fn select(source_name: &str) -> Result<MutexGuard<'static, RuntimeFile>> {
+    let transport = POLICY
+        .get()
+        .map(|p| p.for_source(source_name))
+        .unwrap_or_default();
+    let mut runtime = RUNTIME.lock().unwrap();
+    runtime.apply(transport)?;
+    if transport != NdiTransport::Auto {
+        info!("Receiving {} over {:?}", source_name, transport);
+    }
+    Ok(runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_config() {
+        assert!(NdiTransport::Auto.runtime_config().is_none());
+
+        let mut config = serde_json::json!({
+            "ndi": { "networks": { "discovery": "10.0.0.2" } }
+        });
+        merge(&mut config, NdiTransport::Rudp.runtime_config().unwrap());
+        assert_eq!(config["ndi"]["networks"]["discovery"], "10.0.0.2");
+        assert_eq!(config["ndi"]["rudp"]["recv"]["enable"], true);
+        assert_eq!(config["ndi"]["multicast"]["recv"]["enable"], false);
+        assert_eq!(config["ndi"]["tcp"]["recv"]["enable"], false);
+    }
+
+    #[test]
+    fn test_for_source() {
+        let policy = TransportPolicy {
+            default: NdiTransport::Tcp,
+            sources: [
+                ("STUDIO-PC (Slides)".to_string(), NdiTransport::Multicast),
+                ("CAM-2 (Wide)".to_string(), NdiTransport::Auto),
+            ]
+            .into(),
+        };
+        assert_eq!(
+            policy.for_source("STUDIO-PC (Slides)"),
+            NdiTransport::Multicast
+        );
+        assert_eq!(policy.for_source("CAM-2 (Wide)"), NdiTransport::Tcp);
+        assert_eq!(policy.for_source("CAM-1"), NdiTransport::Tcp);
+    }
+}
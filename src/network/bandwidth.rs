@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Sliding-window byte rate meter
+#[derive(Debug, Clone)]
+pub struct BandwidthMeter {
+    window: Duration,
+    samples: VecDeque<(Instant, usize)>,
+    total_bytes: u64,
+}
+
+impl BandwidthMeter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+            total_bytes: 0,
+        }
+    }
+
+    /// Record bytes received at the given time
+    pub fn record(&mut self, now: Instant, bytes: usize) {
+        self.samples.push_back((now, bytes));
+        self.total_bytes += bytes as u64;
+        self.expire(now);
+    }
+
+    /// Average bytes per second over the window ending at `now`
+    pub fn bytes_per_second(&mut self, now: Instant) -> f64 {
+        self.expire(now);
+        let bytes: usize = self.samples.iter().map(|(_, b)| b).sum();
+        bytes as f64 / self.window.as_secs_f64()
+    }
+
+    /// Total bytes recorded since creation
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((time, _)) = self.samples.front() {
+            if now.duration_since(*time) > self.window {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for BandwidthMeter {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bandwidth_window() {
+        let mut meter = BandwidthMeter::new(Duration::from_secs(1));
+        let start = Instant::now();
+
+        meter.record(start, 1000);
+        meter.record(start + Duration::from_millis(500), 1000);
+        assert_eq!(
+            meter.bytes_per_second(start + Duration::from_millis(900)),
+            2000.0
+        );
+
+        // The first sample falls out of the window
+        assert_eq!(
+            meter.bytes_per_second(start + Duration::from_millis(1200)),
+            1000.0
+        );
+        assert_eq!(meter.bytes_per_second(start + Duration::from_secs(5)), 0.0);
+        assert_eq!(meter.total_bytes(), 2000);
+    }
+}
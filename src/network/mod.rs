@@ -0,0 +1,44 @@
+//! Network bandwidth measurement and attribution, and multicast helpers
+
+pub mod bandwidth;
+pub mod multicast;
+pub mod usage;
+
+pub use bandwidth::BandwidthMeter;
+pub use usage::{attribute_usage, ReceiverUsage};
+
+/// This machine's hostname, if it can be found
+pub fn local_hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+/// Format a byte rate as a human-readable bit rate
+pub fn format_bit_rate(bytes_per_second: f64) -> String {
+    let bits = bytes_per_second * 8.0;
+    if bits >= 1_000_000_000.0 {
+        format!("{:.2} Gbit/s", bits / 1_000_000_000.0)
+    } else if bits >= 1_000_000.0 {
+        format!("{:.1} Mbit/s", bits / 1_000_000.0)
+    } else if bits >= 1_000.0 {
+        format!("{:.1} kbit/s", bits / 1_000.0)
+    } else {
+        format!("{:.0} bit/s", bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bit_rate() {
+        assert_eq!(format_bit_rate(0.0), "0 bit/s");
+        assert_eq!(format_bit_rate(12_500.0), "100.0 kbit/s");
+        assert_eq!(format_bit_rate(18_750_000.0), "150.0 Mbit/s");
+        assert_eq!(format_bit_rate(250_000_000.0), "2.00 Gbit/s");
+    }
+}
@@ -0,0 +1,32 @@
+//! Multicast receive sockets shared with other listeners on the same port
+
+use anyhow::{Context, Result};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::UdpSocket;
+
+/// Open a UDP socket receiving an IPv4 multicast group.
+///
+/// The port may be shared with other sockets (several AES67 streams commonly use 5004).
+pub fn join_multicast(group: Ipv4Addr, port: u16) -> Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+        .context("Failed to create multicast socket")?;
+    socket.set_reuse_address(true)?;
+
+    // Binding to the group address filters out other groups on the same port;
+    // Windows only allows binding to the unspecified address.
+    let bind_addr = if cfg!(windows) {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        group
+    };
+    socket
+        .bind(&SocketAddr::from((bind_addr, port)).into())
+        .with_context(|| format!("Failed to bind {}:{}", bind_addr, port))?;
+    socket
+        .join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)
+        .with_context(|| format!("Failed to join multicast group {}", group))?;
+    socket.set_nonblocking(true)?;
+
+    UdpSocket::from_std(socket.into()).context("Failed to register multicast socket")
+}
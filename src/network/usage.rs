@@ -0,0 +1,118 @@
+//! Attribution of receive bandwidth to sources, machines and local interfaces
+
+use crate::ndi::NdiSource;
+use std::collections::HashMap;
+use std::net::{IpAddr, UdpSocket};
+
+/// Current receive rate of one receiver
+#[derive(Debug, Clone)]
+pub struct ReceiverUsage {
+    pub source: NdiSource,
+    pub bytes_per_second: f64,
+}
+
+/// Bandwidth broken down by source, machine and local interface
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageReport {
+    /// Per source name, highest first
+    pub per_source: Vec<(String, f64)>,
+    /// Per sending machine, highest first
+    pub per_machine: Vec<(String, f64)>,
+    /// Per local interface address, highest first
+    pub per_interface: Vec<(String, f64)>,
+    /// Total receive rate
+    pub total: f64,
+}
+
+/// Aggregate receiver usage; `interface_of` maps a source to the local interface it arrives on
+pub fn attribute_usage<F>(usages: &[ReceiverUsage], mut interface_of: F) -> UsageReport
+where
+    F: FnMut(&NdiSource) -> Option<IpAddr>,
+{
+    let mut per_source: HashMap<String, f64> = HashMap::new();
+    let mut per_machine: HashMap<String, f64> = HashMap::new();
+    let mut per_interface: HashMap<String, f64> = HashMap::new();
+    let mut total = 0.0;
+
+    for usage in usages {
+        *per_source.entry(usage.source.name.clone()).or_default() += usage.bytes_per_second;
+        *per_machine
+            .entry(usage.source.machine_name().to_string())
+            .or_default() += usage.bytes_per_second;
+
+        let interface = interface_of(&usage.source)
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        *per_interface.entry(interface).or_default() += usage.bytes_per_second;
+
+        total += usage.bytes_per_second;
+    }
+
+    UsageReport {
+        per_source: sorted_desc(per_source),
+        per_machine: sorted_desc(per_machine),
+        per_interface: sorted_desc(per_interface),
+        total,
+    }
+}
+
+/// Local interface address the OS would use to reach `peer`
+pub fn local_interface_for(peer: IpAddr) -> Option<IpAddr> {
+    let bind_addr = if peer.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(bind_addr).ok()?;
+    // Connecting a UDP socket sends nothing; it only selects a route
+    socket.connect((peer, 9)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn sorted_desc(map: HashMap<String, f64>) -> Vec<(String, f64)> {
+    let mut entries: Vec<(String, f64)> = map.into_iter().collect();
+    entries.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(name: &str, url: &str, rate: f64) -> ReceiverUsage {
+        ReceiverUsage {
+            source: NdiSource::new(name.to_string(), url.to_string()),
+            bytes_per_second: rate,
+        }
+    }
+
+    #[test]
+    fn test_attribution() {
+        let usages = vec![
+            usage("STUDIO-PC (OBS 1)", "10.0.0.5:5961", 10.0),
+            usage("STUDIO-PC (OBS 2)", "10.0.0.5:5962", 30.0),
+            usage("CAM1 (BirdDog)", "10.1.0.20:5961", 25.0),
+        ];
+
+        let report = attribute_usage(&usages, |source| match source.address() {
+            Some(IpAddr::V4(ip)) if ip.octets()[1] == 0 => "10.0.0.1".parse().ok(),
+            Some(_) => "10.1.0.1".parse().ok(),
+            None => None,
+        });
+
+        assert_eq!(report.total, 65.0);
+        assert_eq!(
+            report.per_source[0],
+            ("STUDIO-PC (OBS 2)".to_string(), 30.0)
+        );
+        assert_eq!(report.per_machine[0], ("STUDIO-PC".to_string(), 40.0));
+        assert_eq!(report.per_machine[1], ("CAM1".to_string(), 25.0));
+        assert_eq!(report.per_interface[0], ("10.0.0.1".to_string(), 40.0));
+    }
+
+    #[test]
+    fn test_local_interface_for_loopback() {
+        let local = local_interface_for("127.0.0.1".parse().unwrap());
+        assert_eq!(local, Some("127.0.0.1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,379 @@
+//! Hot-standby pairing of two daemons: the standby mirrors the primary's routes,
+//! salvos, aliases and tags through its control API and takes over publishing
+//! once the primary stops answering. Whichever side is active keeps
+//! heartbeating the other, and stands down if the other is active on a newer
+//! takeover
+
+use crate::config::PairingConfig;
+use crate::events::{Event, EventCategory, EventLog, Severity};
+use crate::matrix::{MatrixRouter, Route, Salvo};
+use crate::ndi::NdiDiscovery;
+use anyhow::Result;
+use log::{debug, info, warn};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// What a daemon tells the other side of the pair (`GET /api/pairing`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PairingState {
+    /// Whether it is publishing outputs
+    pub active: bool,
+    /// Takeovers behind it being active: when both sides are, the higher term wins
+    pub term: u64,
+    pub routes: Vec<Route>,
+    pub salvos: Vec<Salvo>,
+    /// Friendly source names (`[ndi.aliases]`)
+    pub aliases: BTreeMap<String, String>,
+    /// Source tags by pattern (`[ndi.tags]`)
+    pub tags: BTreeMap<String, Vec<String>>,
+}
+
+/// This daemon's side of the pair
+#[derive(Debug, Default)]
+pub struct PairingRole {
+    active: AtomicBool,
+    term: AtomicU64,
+}
+
+impl PairingRole {
+    /// Start publishing on the given term
+    pub fn activate(&self, term: u64) {
+        self.term.store(term, Ordering::SeqCst);
+        self.active.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub fn term(&self) -> u64 {
+        self.term.load(Ordering::SeqCst)
+    }
+}
+
+/// Whether an active daemon must give way to an active peer: the newer
+/// takeover wins, and the configured primary wins a tie
+pub fn stands_down(term: u64, standby: bool, peer: &PairingState) -> bool {
+    peer.active && (peer.term > term || (peer.term == term && standby))
+}
+
+/// Whether the primary is still alive, from when it last answered
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    takeover_after: Duration,
+    last_heard: Instant,
+}
+
+impl Heartbeat {
+    /// Start counting from `now`, so a primary that never answers is taken
+    /// over from too
+    pub fn new(takeover_after: Duration, now: Instant) -> Self {
+        Self {
+            takeover_after,
+            last_heard: now,
+        }
+    }
+
+    pub fn heard(&mut self, now: Instant) {
+        self.last_heard = now;
+    }
+
+    /// Whether the primary has been silent long enough to take over
+    pub fn lost(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_heard) >= self.takeover_after
+    }
+}
+
+/// Bring the router's routes in line with the primary's. Returns the outputs
+/// that changed
+pub fn mirror_routes(router: &mut MatrixRouter, primary: &[Route]) -> Vec<String> {
+    let mut changed = Vec::new();
+    for route in primary {
+        if router.get_route(&route.output) == Some(&route.input) {
+            continue;
+        }
+        match router.route_placeholder(&route.input, &route.output) {
+            Ok(()) => changed.push(route.output.clone()),
+            // The primary may have outputs this daemon isn't configured with
+            Err(e) => debug!("Not mirroring {} -> {}: {}", route.input, route.output, e),
+        }
+    }
+    for route in router.get_all_routes() {
+        if !primary.iter().any(|r| r.output == route.output) {
+            router.unroute(&route.output);
+            changed.push(route.output);
+        }
+    }
+    changed
+}
+
+/// Bring routes, aliases and tags in line with the active side's. Returns the
+/// outputs that changed
+pub fn mirror_state(
+    router: &mut MatrixRouter,
+    discovery: &NdiDiscovery,
+    active: &PairingState,
+) -> Vec<String> {
+    if discovery.aliases() != active.aliases {
+        discovery.set_aliases(active.aliases.clone());
+    }
+    if discovery.tags() != active.tags {
+        discovery.set_tags(active.tags.clone());
+    }
+    mirror_routes(router, &active.routes)
+}
+
+/// How a standby came to take over
+#[derive(Debug, Default)]
+pub struct Takeover {
+    /// Term to publish on, one past the last term heard
+    pub term: u64,
+    /// Last state mirrored, if the other side ever answered
+    pub mirrored: Option<PairingState>,
+}
+
+/// Heartbeats the other daemon of the pair: mirrors it while on standby, and
+/// watches that it isn't active too while publishing
+pub struct StandbyMirror {
+    peer: String,
+    /// The configured standby gives way when both sides are active on the same term
+    standby: bool,
+    client: Client,
+    router: Arc<Mutex<MatrixRouter>>,
+    discovery: Arc<NdiDiscovery>,
+    events: EventLog,
+    interval: Duration,
+    takeover_after: Duration,
+}
+
+impl StandbyMirror {
+    pub fn new(
+        config: &PairingConfig,
+        peer: &str,
+        router: Arc<Mutex<MatrixRouter>>,
+        discovery: Arc<NdiDiscovery>,
+        events: EventLog,
+    ) -> Self {
+        let takeover_after = Duration::from_secs(config.takeover_after.max(1));
+        let client = Client::builder().timeout(takeover_after).build().unwrap();
+
+        Self {
+            peer: peer.to_string(),
+            standby: config.standby,
+            client,
+            router,
+            discovery,
+            events,
+            interval: Duration::from_secs(config.heartbeat_interval.max(1)),
+            takeover_after,
+        }
+    }
+
+    /// The other side's role and state, which also serve as its heartbeat
+    pub async fn peer_state(&self) -> Result<PairingState> {
+        let url = format!("http://{}/api/pairing", self.peer);
+        let state = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(state)
+    }
+
+    /// Mirror the active side until it has been silent (or inactive) for
+    /// `takeover_after`
+    pub async fn run_until_takeover(&self) -> Takeover {
+        let mut heartbeat = Heartbeat::new(self.takeover_after, Instant::now());
+        let mut takeover = Takeover::default();
+        let mut answering = None;
+        loop {
+            match self.peer_state().await {
+                Ok(state) if state.active => {
+                    heartbeat.heard(Instant::now());
+                    if answering != Some(true) {
+                        info!("Standby of {}: mirroring its state", self.peer);
+                    }
+                    answering = Some(true);
+                    let changed =
+                        mirror_state(&mut self.router.lock().unwrap(), &self.discovery, &state);
+                    if !changed.is_empty() {
+                        debug!("Mirrored routes of {}", changed.join(", "));
+                    }
+                    takeover.term = state.term;
+                    takeover.mirrored = Some(state);
+                }
+                Ok(_) => {
+                    if answering != Some(false) {
+                        warn!("{} answers but is not publishing", self.peer);
+                    }
+                    answering = Some(false);
+                }
+                Err(e) => {
+                    if answering != Some(false) {
+                        warn!("{} is not answering: {:#}", self.peer, e);
+                    }
+                    answering = Some(false);
+                }
+            }
+
+            if heartbeat.lost(Instant::now()) {
+                self.events.record(
+                    Event::new(
+                        Severity::Warning,
+                        EventCategory::System,
+                        format!("{} stopped answering, taking over its outputs", self.peer),
+                    )
+                    .with_actor("standby"),
+                );
+                takeover.term += 1;
+                return takeover;
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    /// Keep heartbeating while active, until the other side turns out to be
+    /// active on a newer term (or the same term, when this is the configured
+    /// standby). Returns the term it is active on
+    pub async fn run_until_fenced(&self, role: &PairingRole) -> u64 {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            let peer = match self.peer_state().await {
+                Ok(peer) => peer,
+                Err(e) => {
+                    debug!("{} is not answering: {:#}", self.peer, e);
+                    continue;
+                }
+            };
+            if stands_down(role.term(), self.standby, &peer) {
+                self.events.record(
+                    Event::new(
+                        Severity::Error,
+                        EventCategory::System,
+                        format!(
+                            "{} is active on takeover {} (this daemon is on {}), standing down",
+                            self.peer,
+                            peer.term,
+                            role.term()
+                        ),
+                    )
+                    .with_actor("standby"),
+                );
+                return peer.term;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat() {
+        let start = Instant::now();
+        let mut heartbeat = Heartbeat::new(Duration::from_secs(5), start);
+        assert!(!heartbeat.lost(start + Duration::from_secs(4)));
+        heartbeat.heard(start + Duration::from_secs(4));
+        assert!(!heartbeat.lost(start + Duration::from_secs(8)));
+        assert!(heartbeat.lost(start + Duration::from_secs(9)));
+    }
+
+    #[test]
+    fn test_stands_down() {
+        let peer = |active, term| PairingState {
+            active,
+            term,
+            ..PairingState::default()
+        };
+        assert!(!stands_down(1, true, &peer(false, 5)));
+        assert!(!stands_down(1, false, &peer(true, 0)));
+        assert!(stands_down(0, false, &peer(true, 1)));
+        assert!(stands_down(1, true, &peer(true, 1)));
+        assert!(!stands_down(1, false, &peer(true, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_mirror_state_from_peer() {
+        use axum::routing::get;
+        use axum::Json;
+
+        let mut aliases = BTreeMap::new();
+        aliases.insert("CAM-1 (Wide)".to_string(), "Wide".to_string());
+        let primary = PairingState {
+            active: true,
+            term: 2,
+            routes: vec![Route::new("CAM-1 (Wide)".into(), "Program".into())],
+            salvos: vec![Salvo::new("Sunday".into(), vec![])],
+            aliases,
+            tags: BTreeMap::new(),
+        };
+        let answer = primary.clone();
+        let app = axum::Router::new().route(
+            "/api/pairing",
+            get(move || {
+                let answer = answer.clone();
+                async move { Json(answer) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut router = MatrixRouter::new();
+        router.add_output("Program".to_string());
+        let router = Arc::new(Mutex::new(router));
+        let discovery = Arc::new(NdiDiscovery::new());
+        let mirror = StandbyMirror::new(
+            &PairingConfig::default(),
+            &addr.to_string(),
+            Arc::clone(&router),
+            Arc::clone(&discovery),
+            EventLog::new(),
+        );
+
+        let state = mirror.peer_state().await.unwrap();
+        assert_eq!(state, primary);
+        mirror_state(&mut router.lock().unwrap(), &discovery, &state);
+        assert_eq!(
+            router.lock().unwrap().get_route("Program").unwrap(),
+            "CAM-1 (Wide)"
+        );
+        assert_eq!(discovery.aliases(), primary.aliases);
+
+        let role = PairingRole::default();
+        role.activate(1);
+        assert_eq!(mirror.run_until_fenced(&role).await, 2);
+    }
+
+    #[test]
+    fn test_mirror_routes() {
+        let mut router = MatrixRouter::new();
+        for output in ["Program", "Stage", "Lobby"] {
+            router.add_output(output.to_string());
+        }
+        router.route_placeholder("CAM-1 (Wide)", "Program").unwrap();
+        router
+            .route_placeholder("STUDIO-PC (Slides)", "Lobby")
+            .unwrap();
+
+        let primary = vec![
+            Route::new("CAM-1 (Wide)".into(), "Program".into()),
+            Route::new("CAM-2 (Tight)".into(), "Stage".into()),
+            Route::new("CAM-3 (Drum)".into(), "Balcony".into()),
+        ];
+        let mut changed = mirror_routes(&mut router, &primary);
+        changed.sort();
+        assert_eq!(changed, ["Lobby", "Stage"]);
+        assert_eq!(router.get_route("Stage").unwrap(), "CAM-2 (Tight)");
+        assert!(router.get_route("Lobby").is_none());
+        assert!(mirror_routes(&mut router, &primary).is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+//! HTTP client for the ProPresenter network API
+
+use super::{SlideIndexResponse, SlideStatus, SlideTextResponse};
+use anyhow::{Context, Result};
+use log::info;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Client for a ProPresenter machine
+#[derive(Clone)]
+pub struct ProPresenterClient {
+    /// HTTP client
+    client: Client,
+    /// Base URL for the ProPresenter API
+    base_url: String,
+}
+
+impl ProPresenterClient {
+    /// Create a new ProPresenter client
+    pub fn new(host: &str, port: u16) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .unwrap();
+
+        Self {
+            client,
+            base_url: format!("http://{}:{}", host, port),
+        }
+    }
+
+    /// Get the active presentation and slide
+    pub async fn slide_status(&self) -> Result<SlideStatus> {
+        let index: SlideIndexResponse = self.get("/v1/presentation/slide_index").await?;
+        let text: SlideTextResponse = self.get("/v1/status/slide").await?;
+        Ok(SlideStatus::from_responses(index, text))
+    }
+
+    /// Advance to the next slide
+    pub async fn next_slide(&self) -> Result<()> {
+        self.trigger("/v1/trigger/next").await?;
+        info!("ProPresenter: next slide");
+        Ok(())
+    }
+
+    /// Go back to the previous slide
+    pub async fn previous_slide(&self) -> Result<()> {
+        self.trigger("/v1/trigger/previous").await?;
+        info!("ProPresenter: previous slide");
+        Ok(())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to reach ProPresenter")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("ProPresenter returned error: {}", response.status());
+        }
+
+        response
+            .json::<T>()
+            .await
+            .context("Failed to parse ProPresenter response")
+    }
+
+    async fn trigger(&self, path: &str) -> Result<()> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to reach ProPresenter")?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            anyhow::bail!("ProPresenter returned error: {}", response.status())
+        }
+    }
+}
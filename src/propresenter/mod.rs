@@ -0,0 +1,121 @@
+//! ProPresenter integration module
+//!
+//! Polls the ProPresenter network API (https://renewedvision.com/propresenter) for the
+//! current presentation and slide, and triggers slide changes.
+
+mod client;
+mod monitor;
+
+pub use client::ProPresenterClient;
+pub use monitor::ProPresenterMonitor;
+
+use serde::Deserialize;
+
+/// Current presentation and slide state
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SlideStatus {
+    /// Active presentation name
+    pub presentation: Option<String>,
+    /// Zero-based index of the current slide
+    pub slide_index: Option<u32>,
+    /// Text on the current slide
+    pub current_text: String,
+    /// Text on the next slide
+    pub next_text: String,
+}
+
+impl SlideStatus {
+    /// Short label shown over the slides source
+    pub fn label(&self) -> String {
+        let mut label = match (&self.presentation, self.slide_index) {
+            (Some(name), Some(index)) => format!("{} · slide {}", name, index + 1),
+            (Some(name), None) => name.clone(),
+            (None, Some(index)) => format!("Slide {}", index + 1),
+            (None, None) => "No presentation".to_string(),
+        };
+
+        if let Some(line) = self.current_text.lines().find(|l| !l.trim().is_empty()) {
+            label.push('\n');
+            label.push_str(line.trim());
+        }
+        label
+    }
+}
+
+/// Response of `GET /v1/status/slide`
+#[derive(Debug, Clone, Deserialize, Default)]
+struct SlideTextResponse {
+    #[serde(default)]
+    current: Option<SlideText>,
+    #[serde(default)]
+    next: Option<SlideText>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+struct SlideText {
+    #[serde(default)]
+    text: String,
+}
+
+/// Response of `GET /v1/presentation/slide_index`
+#[derive(Debug, Clone, Deserialize, Default)]
+struct SlideIndexResponse {
+    #[serde(default)]
+    presentation_index: Option<PresentationIndex>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PresentationIndex {
+    index: u32,
+    presentation_id: PresentationId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PresentationId {
+    name: String,
+}
+
+impl SlideStatus {
+    fn from_responses(index: SlideIndexResponse, text: SlideTextResponse) -> Self {
+        let presentation_index = index.presentation_index;
+        Self {
+            presentation: presentation_index
+                .as_ref()
+                .map(|p| p.presentation_id.name.clone()),
+            slide_index: presentation_index.map(|p| p.index),
+            current_text: text.current.map(|s| s.text).unwrap_or_default(),
+            next_text: text.next.map(|s| s.text).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_from_responses() {
+        let index: SlideIndexResponse = serde_json::from_str(
+            r#"{"presentation_index":{"index":2,"presentation_id":{"uuid":"abc","name":"Sunday Songs","index":0}}}"#,
+        )
+        .unwrap();
+        let text: SlideTextResponse = serde_json::from_str(
+            r#"{"current":{"text":"Amazing grace\nHow sweet the sound","notes":""},"next":{"text":"That saved a wretch","notes":""}}"#,
+        )
+        .unwrap();
+
+        let status = SlideStatus::from_responses(index, text);
+        assert_eq!(status.presentation.as_deref(), Some("Sunday Songs"));
+        assert_eq!(status.slide_index, Some(2));
+        assert_eq!(status.next_text, "That saved a wretch");
+        assert_eq!(status.label(), "Sunday Songs · slide 3\nAmazing grace");
+    }
+
+    #[test]
+    fn test_status_without_presentation() {
+        let index: SlideIndexResponse =
+            serde_json::from_str(r#"{"presentation_index":null}"#).unwrap();
+        let status = SlideStatus::from_responses(index, SlideTextResponse::default());
+        assert_eq!(status.label(), "No presentation");
+    }
+}
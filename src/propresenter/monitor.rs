@@ -0,0 +1,59 @@
+//! Background polling of the ProPresenter slide state
+
+use super::{ProPresenterClient, SlideStatus};
+use log::{debug, warn};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time;
+
+/// Keeps the latest slide state from ProPresenter
+pub struct ProPresenterMonitor {
+    client: ProPresenterClient,
+    poll_interval: Duration,
+    status: Arc<Mutex<Option<SlideStatus>>>,
+}
+
+impl ProPresenterMonitor {
+    pub fn new(client: ProPresenterClient, poll_interval: Duration) -> Self {
+        Self {
+            client,
+            poll_interval,
+            status: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start polling in the background
+    pub fn start(&self) {
+        let client = self.client.clone();
+        let status = Arc::clone(&self.status);
+        let poll_interval = self.poll_interval;
+
+        tokio::spawn(async move {
+            let mut reachable = true;
+            loop {
+                match client.slide_status().await {
+                    Ok(latest) => {
+                        if !reachable {
+                            debug!("ProPresenter reachable again");
+                        }
+                        reachable = true;
+                        *status.lock().unwrap() = Some(latest);
+                    }
+                    Err(e) => {
+                        if reachable {
+                            warn!("ProPresenter unavailable: {}", e);
+                        }
+                        reachable = false;
+                        *status.lock().unwrap() = None;
+                    }
+                }
+                time::sleep(poll_interval).await;
+            }
+        });
+    }
+
+    /// Latest slide state, if ProPresenter is reachable
+    pub fn status(&self) -> Option<SlideStatus> {
+        self.status.lock().unwrap().clone()
+    }
+}
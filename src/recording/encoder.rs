@@ -0,0 +1,426 @@
+//! Frame encoders for recording outputs
+
+use super::markers::Marker;
+use crate::video::VideoFrame;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Sender, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+/// Frames queued for the encoder thread before new ones are dropped
+const QUEUED_FRAMES: usize = 30;
+
+/// Sink for a sequence of video frames
+pub trait FrameEncoder: Send {
+    /// Encode one frame
+    fn encode(&mut self, frame: &VideoFrame) -> Result<()>;
+
+    /// Flush and close the output
+    fn finish(&mut self) -> Result<()>;
+
+    /// Embed a chapter marker, for containers that support them (the sidecar
+    /// chapter file is written either way)
+    fn add_marker(&mut self, _marker: &Marker) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes each frame as a numbered PNG file in a directory
+pub struct ImageSequenceEncoder {
+    directory: PathBuf,
+    next_index: u64,
+}
+
+impl ImageSequenceEncoder {
+    /// Create the output directory and an encoder writing into it
+    pub fn create<P: AsRef<Path>>(directory: P) -> Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)
+            .with_context(|| format!("Failed to create recording directory {:?}", directory))?;
+        info!("Recording image sequence to {:?}", directory);
+
+        Ok(Self {
+            directory,
+            next_index: 0,
+        })
+    }
+
+    /// Number of frames written so far
+    #[allow(dead_code)]
+    pub fn frames_written(&self) -> u64 {
+        self.next_index
+    }
+}
+
+impl FrameEncoder for ImageSequenceEncoder {
+    fn encode(&mut self, frame: &VideoFrame) -> Result<()> {
+        let image = frame.to_image()?;
+
+        let path = self
+            .directory
+            .join(format!("frame_{:06}.png", self.next_index));
+        image
+            .save(&path)
+            .with_context(|| format!("Failed to write frame {:?}", path))?;
+
+        self.next_index += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        info!(
+            "Finished image sequence in {:?} ({} frames)",
+            self.directory, self.next_index
+        );
+        Ok(())
+    }
+}
+
+/// Pipes frames into `ffmpeg`, which encodes them to H.264 in a Matroska file
+/// (readable up to the last frame even if RusTV stops without finishing it)
+pub struct FfmpegEncoder {
+    path: PathBuf,
+    fps: u32,
+    /// Started on the first frame, which sets the video size
+    process: Option<(Child, ChildStdin)>,
+    size: (u32, u32),
+    frames: u64,
+}
+
+impl FfmpegEncoder {
+    pub fn create<P: AsRef<Path>>(path: P, fps: u32) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)
+                .with_context(|| format!("Failed to create recording directory {:?}", directory))?;
+        }
+        info!("Recording video to {:?}", path);
+
+        Ok(Self {
+            path,
+            fps: fps.max(1),
+            process: None,
+            size: (0, 0),
+            frames: 0,
+        })
+    }
+
+    fn start(&mut self, width: u32, height: u32) -> Result<()> {
+        let mut process = Command::new("ffmpeg")
+            .args(ffmpeg_args(&self.path, (width, height), self.fps))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .context("Failed to start ffmpeg for recording (is it on the PATH?)")?;
+        let stdin = process.stdin.take().context("ffmpeg stdin unavailable")?;
+        self.size = (width, height);
+        self.process = Some((process, stdin));
+        Ok(())
+    }
+}
+
+/// ffmpeg arguments reading raw RGBA frames of `size` from stdin
+pub fn ffmpeg_args(path: &Path, (width, height): (u32, u32), fps: u32) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-hide_banner",
+        "-loglevel",
+        "error",
+        "-f",
+        "rawvideo",
+        "-pix_fmt",
+        "rgba",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    args.extend([
+        "-s".to_string(),
+        format!("{}x{}", width, height),
+        "-framerate".to_string(),
+        fps.to_string(),
+    ]);
+    args.extend(
+        [
+            "-i",
+            "-",
+            // 4:2:0 needs even dimensions
+            "-vf",
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-pix_fmt",
+            "yuv420p",
+            "-y",
+        ]
+        .iter()
+        .map(|arg| arg.to_string()),
+    );
+    args.push(path.to_string_lossy().into_owned());
+    args
+}
+
+impl FrameEncoder for FfmpegEncoder {
+    fn encode(&mut self, frame: &VideoFrame) -> Result<()> {
+        if self.process.is_none() {
+            self.start(frame.width, frame.height)?;
+        }
+        // The stream keeps the first frame's size (e.g. after the window is resized)
+        let resized;
+        let frame = if (frame.width, frame.height) == self.size {
+            frame
+        } else {
+            resized = resize(frame, self.size);
+            &resized
+        };
+        let (_, stdin) = self.process.as_mut().expect("ffmpeg was just started");
+        stdin
+            .write_all(&frame.data)
+            .context("ffmpeg stopped accepting frames")?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some((mut process, stdin)) = self.process.take() else {
+            return Ok(());
+        };
+        // Closing stdin lets ffmpeg flush and close the file
+        drop(stdin);
+        let status = process.wait().context("Failed to wait for ffmpeg")?;
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to finish {:?} ({})", self.path, status);
+        }
+        info!("Finished {:?} ({} frames)", self.path, self.frames);
+        Ok(())
+    }
+}
+
+impl Drop for FfmpegEncoder {
+    fn drop(&mut self) {
+        if let Some((mut process, _)) = self.process.take() {
+            if let Err(e) = process.kill() {
+                warn!("Failed to stop ffmpeg: {}", e);
+            }
+            let _ = process.wait();
+        }
+    }
+}
+
+/// Nearest-neighbour scale to `size`
+fn resize(frame: &VideoFrame, (width, height): (u32, u32)) -> VideoFrame {
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        let row = (y as u64 * frame.height as u64 / height as u64) as u32;
+        for x in 0..width {
+            let col = (x as u64 * frame.width as u64 / width as u64) as u32;
+            let i = ((row * frame.width + col) * 4) as usize;
+            data.extend_from_slice(&frame.data[i..i + 4]);
+        }
+    }
+    VideoFrame::new(width, height, data)
+}
+
+/// Runs an encoder on its own thread, so encoding never holds up the GUI
+pub struct EncoderThread {
+    frames: Option<SyncSender<VideoFrame>>,
+    /// Markers skip the frame queue, so they are never dropped or wait on a
+    /// full one
+    markers: Option<Sender<Marker>>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl EncoderThread {
+    pub fn spawn(mut encoder: Box<dyn FrameEncoder>) -> Self {
+        let (frames, frame_queue) = mpsc::sync_channel::<VideoFrame>(QUEUED_FRAMES);
+        let (markers, marker_queue) = mpsc::channel::<Marker>();
+        let thread = thread::spawn(move || {
+            // Runs until the frame queue closes or the encoder fails. Markers
+            // carry their own position, so they are embedded before the next frame
+            for frame in frame_queue {
+                for marker in marker_queue.try_iter() {
+                    encoder.add_marker(&marker)?;
+                }
+                encoder.encode(&frame)?;
+            }
+            for marker in marker_queue.try_iter() {
+                encoder.add_marker(&marker)?;
+            }
+            encoder.finish()
+        });
+        Self {
+            frames: Some(frames),
+            markers: Some(markers),
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue a frame. Returns false if the encoder is behind and the frame was
+    /// dropped
+    pub fn encode(&mut self, frame: VideoFrame) -> Result<bool> {
+        let sent = match &self.frames {
+            Some(frames) => frames.try_send(frame),
+            None => Err(TrySendError::Disconnected(frame)),
+        };
+        match sent {
+            Ok(()) => Ok(true),
+            Err(TrySendError::Full(_)) => Ok(false),
+            Err(TrySendError::Disconnected(_)) => Err(self.failure()),
+        }
+    }
+
+    pub fn add_marker(&mut self, marker: &Marker) -> Result<()> {
+        let sent = match &self.markers {
+            Some(markers) => markers.send(marker.clone()).is_ok(),
+            None => false,
+        };
+        if sent {
+            Ok(())
+        } else {
+            Err(self.failure())
+        }
+    }
+
+    /// Encode what is queued, then finish the output
+    pub fn finish(&mut self) -> Result<()> {
+        self.markers = None;
+        self.frames = None;
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .map_err(|_| anyhow::anyhow!("Recording encoder panicked"))?,
+            None => Ok(()),
+        }
+    }
+
+    /// Why the encoder thread stopped
+    fn failure(&mut self) -> anyhow::Error {
+        match self.finish() {
+            Err(e) => e,
+            Ok(()) => anyhow::anyhow!("Recording already finished"),
+        }
+    }
+}
+
+impl Drop for EncoderThread {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            warn!("Failed to finish recording: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_sequence_encoder() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut encoder = ImageSequenceEncoder::create(dir.path().join("rec")).unwrap();
+
+        let frame = VideoFrame::solid(8, 4, [255, 0, 0, 255]);
+        encoder.encode(&frame).unwrap();
+        encoder.encode(&frame).unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(encoder.frames_written(), 2);
+        assert!(dir.path().join("rec/frame_000001.png").exists());
+    }
+
+    #[test]
+    fn test_ffmpeg_args() {
+        let args = ffmpeg_args(Path::new("rec/multiview.mkv"), (1280, 720), 10);
+        let joined = args.join(" ");
+        assert!(joined.contains("-f rawvideo -pix_fmt rgba -s 1280x720 -framerate 10 -i -"));
+        assert_eq!(args.last().unwrap(), "rec/multiview.mkv");
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut frame = VideoFrame::solid(4, 2, [0, 0, 0, 255]);
+        frame.data[4..8].copy_from_slice(&[255, 0, 0, 255]);
+        let resized = resize(&frame, (2, 1));
+        assert_eq!((resized.width, resized.height), (2, 1));
+        assert_eq!(resized.data, [0, 0, 0, 255, 0, 0, 0, 255]);
+        assert_eq!(resize(&frame, (8, 2)).data[8..12], [255, 0, 0, 255]);
+    }
+
+    struct FailingEncoder;
+
+    impl FrameEncoder for FailingEncoder {
+        fn encode(&mut self, _frame: &VideoFrame) -> Result<()> {
+            anyhow::bail!("disk full")
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_encoder_thread_reports_failure() {
+        let mut thread = EncoderThread::spawn(Box::new(FailingEncoder));
+        let frame = VideoFrame::solid(2, 2, [0, 0, 0, 255]);
+        let mut result = Ok(true);
+        for _ in 0..1000 {
+            result = thread.encode(frame.clone());
+            if result.is_err() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(result.unwrap_err().to_string(), "disk full");
+    }
+
+    /// Blocks in `encode` until released, and records the markers it is given
+    struct StalledEncoder {
+        release: mpsc::Receiver<()>,
+        markers: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl FrameEncoder for StalledEncoder {
+        fn encode(&mut self, _frame: &VideoFrame) -> Result<()> {
+            let _ = self.release.recv();
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn add_marker(&mut self, marker: &Marker) -> Result<()> {
+            self.markers.lock().unwrap().push(marker.label.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_marker_queued_while_frames_dropped() {
+        let (release, stalled) = mpsc::channel();
+        let markers = std::sync::Arc::default();
+        let mut thread = EncoderThread::spawn(Box::new(StalledEncoder {
+            release: stalled,
+            markers: std::sync::Arc::clone(&markers),
+        }));
+        let frame = VideoFrame::solid(2, 2, [0, 0, 0, 255]);
+        while thread.encode(frame.clone()).unwrap() {}
+
+        // Returns at once even though the frame queue is full
+        let marker = Marker {
+            label: "Sermon".to_string(),
+            position: std::time::Duration::from_secs(5),
+        };
+        thread.add_marker(&marker).unwrap();
+
+        drop(release);
+        thread.finish().unwrap();
+        assert_eq!(*markers.lock().unwrap(), ["Sermon"]);
+    }
+}
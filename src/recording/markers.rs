@@ -0,0 +1,154 @@
+//! Labelled markers dropped into a recording, saved as a chapter file editors can
+//! jump between
+
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Name of the sidecar chapter file written next to a recording
+pub const CHAPTERS_FILE: &str = "chapters.txt";
+
+/// A point in a recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub label: String,
+    /// Position in the recording
+    pub position: Duration,
+}
+
+/// Markers requested from outside the GUI (API, Companion), picked up by the active
+/// recording, which also reports here how long it has been running
+#[derive(Debug, Clone, Default)]
+pub struct MarkerRequests {
+    pending: Arc<Mutex<Vec<Option<String>>>>,
+    /// When the active recording started
+    started: Arc<Mutex<Option<Instant>>>,
+}
+
+impl MarkerRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask for a marker in the active recording (numbered if no label is given)
+    pub fn request(&self, label: Option<String>) -> Result<()> {
+        if !self.is_recording() {
+            anyhow::bail!("No recording in progress");
+        }
+        self.pending.lock().unwrap().push(label);
+        Ok(())
+    }
+
+    /// Markers requested since the last call
+    pub fn take(&self) -> Vec<Option<String>> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.started.lock().unwrap().is_some()
+    }
+
+    /// How long the active recording has been running
+    pub fn recording_time(&self) -> Option<Duration> {
+        self.started
+            .lock()
+            .unwrap()
+            .map(|started| started.elapsed())
+    }
+
+    /// Note whether a recording is running; requests left over from the last one are dropped
+    pub fn set_recording(&self, recording: bool) {
+        let mut started = self.started.lock().unwrap();
+        if !recording {
+            *started = None;
+            self.pending.lock().unwrap().clear();
+        } else if started.is_none() {
+            *started = Some(Instant::now());
+        }
+    }
+}
+
+/// Render markers as an FFmpeg metadata file, each chapter running to the next
+/// marker (the last to `end`). `ffmpeg -i video -i chapters.txt -map_metadata 1`
+/// embeds them in the container
+pub fn ffmetadata(markers: &[Marker], end: Duration) -> String {
+    let mut text = String::from(";FFMETADATA1\n");
+    for (i, marker) in markers.iter().enumerate() {
+        let chapter_end = markers
+            .get(i + 1)
+            .map_or(end, |next| next.position)
+            .max(marker.position);
+        let _ = write!(
+            text,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            marker.position.as_millis(),
+            chapter_end.as_millis(),
+            escape(&marker.label)
+        );
+    }
+    text
+}
+
+/// Write the chapter file for a recording
+pub fn write_chapters(path: &Path, markers: &[Marker], end: Duration) -> Result<()> {
+    fs::write(path, ffmetadata(markers, end))
+        .with_context(|| format!("Failed to write chapters {:?}", path))
+}
+
+/// Escape the characters FFmpeg metadata treats specially
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffmetadata() {
+        let markers = vec![
+            Marker {
+                label: "Sermon start".to_string(),
+                position: Duration::from_millis(1500),
+            },
+            Marker {
+                label: "Song 2; reprise".to_string(),
+                position: Duration::from_secs(90),
+            },
+        ];
+        assert_eq!(
+            ffmetadata(&markers, Duration::from_secs(120)),
+            ";FFMETADATA1\n\
+             \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=1500\nEND=90000\ntitle=Sermon start\n\
+             \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=90000\nEND=120000\ntitle=Song 2\\; reprise\n"
+        );
+    }
+
+    #[test]
+    fn test_requests_need_recording() {
+        let requests = MarkerRequests::new();
+        assert!(requests.request(None).is_err());
+        assert!(requests.recording_time().is_none());
+
+        requests.set_recording(true);
+        assert!(requests.recording_time().is_some());
+        requests.request(Some("Song 2".to_string())).unwrap();
+        requests.clone().request(None).unwrap();
+        assert_eq!(requests.take(), vec![Some("Song 2".to_string()), None]);
+        assert!(requests.take().is_empty());
+
+        requests.request(None).unwrap();
+        requests.set_recording(false);
+        assert!(requests.take().is_empty());
+    }
+}
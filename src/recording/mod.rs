@@ -0,0 +1,57 @@
+//! Recording of the multiview composite or individual outputs to disk
+
+pub mod encoder;
+pub mod markers;
+pub mod recorder;
+
+pub use encoder::{EncoderThread, FfmpegEncoder, FrameEncoder, ImageSequenceEncoder};
+pub use markers::MarkerRequests;
+pub use recorder::Recorder;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a recording captures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordingTarget {
+    /// The whole multiview composite, including overlays
+    Multiview,
+    /// A single output's view slot
+    Output(String),
+}
+
+impl RecordingTarget {
+    /// File-name-safe label for the target
+    pub fn file_stem(&self) -> String {
+        match self {
+            RecordingTarget::Multiview => "multiview".to_string(),
+            RecordingTarget::Output(name) => name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for RecordingTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingTarget::Multiview => write!(f, "Multiview"),
+            RecordingTarget::Output(name) => write!(f, "Output: {}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(RecordingTarget::Multiview.file_stem(), "multiview");
+        assert_eq!(
+            RecordingTarget::Output("Monitor 1/Left".to_string()).file_stem(),
+            "Monitor_1_Left"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Recording session driving a frame encoder at a fixed rate
+
+use super::markers::{self, Marker};
+use super::{EncoderThread, FrameEncoder, RecordingTarget};
+use crate::video::VideoFrame;
+use anyhow::Result;
+use log::{info, warn};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// An active recording of a target
+pub struct Recorder {
+    target: RecordingTarget,
+    encoder: EncoderThread,
+    frame_interval: Duration,
+    last_capture: Option<Instant>,
+    started_at: Instant,
+    frames: u64,
+    /// Frames dropped while the encoder was behind
+    dropped: u64,
+    markers: Vec<Marker>,
+    /// Sidecar chapter file the markers are written to
+    chapters_path: Option<PathBuf>,
+}
+
+impl Recorder {
+    /// Start recording `target` at `fps` frames per second; the encoder runs
+    /// on its own thread
+    pub fn new(target: RecordingTarget, encoder: Box<dyn FrameEncoder>, fps: u32) -> Self {
+        info!("Recording started: {} @ {} fps", target, fps);
+        Self {
+            target,
+            encoder: EncoderThread::spawn(encoder),
+            frame_interval: Duration::from_secs(1) / fps.max(1),
+            last_capture: None,
+            started_at: Instant::now(),
+            frames: 0,
+            dropped: 0,
+            markers: Vec::new(),
+            chapters_path: None,
+        }
+    }
+
+    /// Write markers to a sidecar chapter file
+    pub fn with_chapters(mut self, path: PathBuf) -> Self {
+        self.chapters_path = Some(path);
+        self
+    }
+
+    /// What this recording captures
+    pub fn target(&self) -> &RecordingTarget {
+        &self.target
+    }
+
+    /// Check if the next frame is due
+    pub fn is_due(&self, now: Instant) -> bool {
+        match self.last_capture {
+            Some(last) => now.duration_since(last) >= self.frame_interval,
+            None => true,
+        }
+    }
+
+    /// Mark that a frame capture has been requested
+    pub fn mark_captured(&mut self, now: Instant) {
+        self.last_capture = Some(now);
+    }
+
+    /// Hand a captured frame to the encoder
+    pub fn push_frame(&mut self, frame: VideoFrame) -> Result<()> {
+        if self.encoder.encode(frame)? {
+            self.frames += 1;
+        } else {
+            self.dropped += 1;
+        }
+        Ok(())
+    }
+
+    /// Number of frames recorded
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Number of frames dropped because the encoder couldn't keep up
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Time since the recording started
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Length of the recorded footage at the recording rate
+    pub fn duration(&self) -> Duration {
+        self.frame_interval * self.frames as u32
+    }
+
+    /// Mark the current position, numbering the marker if it has no label
+    pub fn add_marker(&mut self, label: Option<String>) -> Result<&Marker> {
+        let label = label
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(|| format!("Marker {}", self.markers.len() + 1));
+        let marker = Marker {
+            label,
+            position: self.duration(),
+        };
+        info!(
+            "Marker '{}' at {:.1}s in {}",
+            marker.label,
+            marker.position.as_secs_f64(),
+            self.target
+        );
+        self.encoder.add_marker(&marker)?;
+        self.markers.push(marker);
+        // Rewritten each time so the markers survive a crash
+        self.write_chapters()?;
+        Ok(self.markers.last().expect("marker was just added"))
+    }
+
+    /// Markers dropped so far
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    fn write_chapters(&self) -> Result<()> {
+        match &self.chapters_path {
+            Some(path) => markers::write_chapters(path, &self.markers, self.duration()),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop the recording and finalize the output
+    pub fn stop(mut self) -> Result<u64> {
+        self.encoder.finish()?;
+        if !self.markers.is_empty() {
+            self.write_chapters()?;
+        }
+        info!(
+            "Recording stopped: {} ({} frames)",
+            self.target, self.frames
+        );
+        if self.dropped > 0 {
+            warn!(
+                "{} frames of {} were dropped while the encoder was behind",
+                self.dropped, self.target
+            );
+        }
+        Ok(self.frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct CountingEncoder(Arc<Mutex<u32>>);
+
+    impl FrameEncoder for CountingEncoder {
+        fn encode(&mut self, _frame: &VideoFrame) -> Result<()> {
+            *self.0.lock().unwrap() += 1;
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_frame_pacing() {
+        let count = Arc::new(Mutex::new(0));
+        let mut recorder = Recorder::new(
+            RecordingTarget::Multiview,
+            Box::new(CountingEncoder(Arc::clone(&count))),
+            10,
+        );
+
+        let now = Instant::now();
+        assert!(recorder.is_due(now));
+        recorder.mark_captured(now);
+        assert!(!recorder.is_due(now + Duration::from_millis(50)));
+        assert!(recorder.is_due(now + Duration::from_millis(100)));
+
+        recorder
+            .push_frame(VideoFrame::solid(2, 2, [0, 0, 0, 255]))
+            .unwrap();
+        assert_eq!(recorder.stop().unwrap(), 1);
+        assert_eq!(*count.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_markers() {
+        let dir = tempfile::tempdir().unwrap();
+        let chapters = dir.path().join(markers::CHAPTERS_FILE);
+        let mut recorder = Recorder::new(
+            RecordingTarget::Multiview,
+            Box::new(CountingEncoder(Arc::new(Mutex::new(0)))),
+            10,
+        )
+        .with_chapters(chapters.clone());
+
+        let frame = VideoFrame::solid(2, 2, [0, 0, 0, 255]);
+        for _ in 0..5 {
+            recorder.push_frame(frame.clone()).unwrap();
+        }
+        let marker = recorder.add_marker(Some(" Sermon ".to_string())).unwrap();
+        assert_eq!(marker.label, "Sermon");
+        assert_eq!(marker.position, Duration::from_millis(500));
+        assert!(std::fs::read_to_string(&chapters)
+            .unwrap()
+            .contains("START=500\nEND=500\ntitle=Sermon"));
+
+        for _ in 0..10 {
+            recorder.push_frame(frame.clone()).unwrap();
+        }
+        assert_eq!(recorder.add_marker(None).unwrap().label, "Marker 2");
+        recorder.push_frame(frame.clone()).unwrap();
+        recorder.stop().unwrap();
+
+        let written = std::fs::read_to_string(&chapters).unwrap();
+        assert!(written.contains("START=500\nEND=1500\ntitle=Sermon"));
+        assert!(written.contains("START=1500\nEND=1600\ntitle=Marker 2"));
+    }
+}
@@ -0,0 +1,35 @@
+//! Free disk space lookup
+
+use anyhow::Result;
+use std::path::Path;
+
+/// Free space (bytes) available to us on the disk holding `path`.
+///
+/// The directory may not exist yet, so the nearest existing ancestor is used.
+/// Returns None on platforms where this isn't supported.
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Result<Option<u64>> {
+    use anyhow::Context;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("."));
+    let c_path = CString::new(existing.as_os_str().as_bytes()).context("Invalid path")?;
+
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is a valid C string and stat is a valid statvfs to fill in
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Failed to read free space of {:?}", existing));
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> Result<Option<u64>> {
+    Ok(None)
+}
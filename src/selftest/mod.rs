@@ -0,0 +1,250 @@
+//! Pre-show self-test: checks that cameras respond, required sources are on
+//! the network, Companion is reachable and recording disks have space
+
+mod disk;
+
+use crate::birddog::BirdDogClient;
+use crate::companion::CompanionClient;
+use crate::config::Config;
+use crate::ndi::{NdiDiscovery, NdiSource};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// How often discovered sources are checked while waiting for required ones
+const SOURCE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// The check could not run here (e.g. unsupported platform)
+    Skipped,
+}
+
+/// One line of the checklist
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self.status {
+            CheckStatus::Pass => "✔",
+            CheckStatus::Fail => "✘",
+            CheckStatus::Skipped => "–",
+        }
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.symbol(), self.name, self.detail)
+    }
+}
+
+/// Checklist produced by a self-test run
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    /// Whether no check failed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .count()
+    }
+}
+
+/// Run every configured check
+pub async fn run(config: &Config, discovery: &NdiDiscovery) -> SelfTestReport {
+    let mut checks = Vec::new();
+
+    // Cameras are queried in parallel so one offline camera doesn't hold up the rest
+    let cameras: Vec<_> = config
+        .birddog
+        .cameras
+        .iter()
+        .filter(|camera| camera.is_birddog())
+        .map(|camera| {
+            let name = format!("Camera {}", camera.name);
+            let ip = camera.ip_address.clone();
+            (
+                name,
+                tokio::spawn(async move { BirdDogClient::new(&ip).get_info().await }),
+            )
+        })
+        .collect();
+    for (name, task) in cameras {
+        checks.push(match task.await {
+            Ok(Ok(info)) => CheckResult::new(
+                name,
+                CheckStatus::Pass,
+                format!("{} (firmware {})", info.model, info.firmware_version),
+            ),
+            Ok(Err(e)) => CheckResult::new(name, CheckStatus::Fail, format!("{:#}", e)),
+            Err(e) => CheckResult::new(name, CheckStatus::Fail, e.to_string()),
+        });
+    }
+
+    let required = &config.selftest.required_sources;
+    if !required.is_empty() {
+        let timeout = Duration::from_secs(config.selftest.source_timeout);
+        let sources = wait_for_sources(discovery, required, timeout).await;
+        checks.extend(check_sources(required, &sources));
+    }
+
+    if config.companion.enabled {
+        let client = CompanionClient::new(
+            &config.companion.host,
+            config.companion.port,
+            config.companion.enabled,
+        );
+        let address = format!("{}:{}", config.companion.host, config.companion.port);
+        checks.push(if client.test_connection().await {
+            CheckResult::new(
+                "Companion",
+                CheckStatus::Pass,
+                format!("connected to {}", address),
+            )
+        } else {
+            CheckResult::new(
+                "Companion",
+                CheckStatus::Fail,
+                format!("no response from {}", address),
+            )
+        });
+    }
+
+    checks.push(check_disk(
+        &config.recording.directory,
+        config.selftest.min_free_space_gb,
+    ));
+
+    SelfTestReport { checks }
+}
+
+/// Poll discovery until every required source is present or the timeout passes
+async fn wait_for_sources(
+    discovery: &NdiDiscovery,
+    required: &[String],
+    timeout: Duration,
+) -> Vec<NdiSource> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let sources = discovery.get_sources();
+        let all_found = required
+            .iter()
+            .all(|r| sources.iter().any(|s| s.matches(r)));
+        if all_found || tokio::time::Instant::now() >= deadline {
+            return sources;
+        }
+        tokio::time::sleep(SOURCE_POLL_INTERVAL).await;
+    }
+}
+
+/// One check per required source
+fn check_sources(required: &[String], sources: &[NdiSource]) -> Vec<CheckResult> {
+    required
+        .iter()
+        .map(|r| {
+            let name = format!("Source {}", r);
+            match sources.iter().find(|s| s.matches(r)) {
+                Some(source) => CheckResult::new(name, CheckStatus::Pass, source.url.clone()),
+                None => CheckResult::new(name, CheckStatus::Fail, "not found on the network"),
+            }
+        })
+        .collect()
+}
+
+/// Check the free space of the disk recordings are written to
+fn check_disk(directory: &Path, min_free_gb: f64) -> CheckResult {
+    let name = "Recording disk";
+    match disk::free_space(directory) {
+        Ok(Some(free)) => disk_result(directory, free, min_free_gb),
+        Ok(None) => CheckResult::new(
+            name,
+            CheckStatus::Skipped,
+            "free space unknown on this platform",
+        ),
+        Err(e) => CheckResult::new(name, CheckStatus::Fail, format!("{:#}", e)),
+    }
+}
+
+fn disk_result(directory: &Path, free_bytes: u64, min_free_gb: f64) -> CheckResult {
+    let free_gb = free_bytes as f64 / 1e9;
+    let status = if free_gb >= min_free_gb {
+        CheckStatus::Pass
+    } else {
+        CheckStatus::Fail
+    };
+    CheckResult::new(
+        "Recording disk",
+        status,
+        format!(
+            "{:.1} GB free in {} (need {:.1} GB)",
+            free_gb,
+            directory.display(),
+            min_free_gb
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_sources() {
+        let sources = vec![NdiSource::new(
+            "CAM1 (Main)".to_string(),
+            "10.0.0.5:5961".to_string(),
+        )];
+        let checks = check_sources(
+            &["CAM1 (Main)".to_string(), "CAM2 (Main)".to_string()],
+            &sources,
+        );
+
+        assert_eq!(checks[0].status, CheckStatus::Pass);
+        assert_eq!(checks[1].status, CheckStatus::Fail);
+        assert!(!SelfTestReport { checks }.passed());
+    }
+
+    #[test]
+    fn test_disk_result() {
+        let dir = Path::new("/recordings");
+        assert_eq!(
+            disk_result(dir, 50_000_000_000, 10.0).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            disk_result(dir, 2_000_000_000, 10.0).status,
+            CheckStatus::Fail
+        );
+
+        let report = SelfTestReport {
+            checks: vec![CheckResult::new("Recording disk", CheckStatus::Skipped, "")],
+        };
+        assert!(report.passed());
+        assert_eq!(report.failures(), 0);
+    }
+}
@@ -0,0 +1,197 @@
+//! Flat-file storage: routes, salvos and monitoring levels as JSON, events
+//! appended to a JSONL file
+
+use super::{EventQuery, Storage};
+use crate::audio::MonitorLevel;
+use crate::events::Event;
+use crate::matrix::{Route, Salvo};
+use anyhow::{Context, Result};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const ROUTES_FILE: &str = "routes.json";
+const SALVOS_FILE: &str = "salvos.json";
+const MONITOR_LEVELS_FILE: &str = "monitor_levels.json";
+const EVENTS_FILE: &str = "events.jsonl";
+
+/// State kept as files in a directory
+pub struct FileStorage {
+    directory: PathBuf,
+    /// Serializes writers so appended events and rewritten files don't interleave
+    lock: Mutex<()>,
+}
+
+impl FileStorage {
+    /// Use a directory, creating it if needed
+    pub fn open(directory: &Path) -> Result<Self> {
+        fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create storage directory {:?}", directory))?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn read_json<T: DeserializeOwned + Default>(&self, name: &str) -> Result<T> {
+        Ok(self.read_saved_json(name)?.unwrap_or_default())
+    }
+
+    /// None if the file was never written
+    fn read_saved_json<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let path = self.directory.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    /// Replace a file by writing a temporary file and renaming it over the old one
+    fn write_json<T: Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        let path = self.directory.join(name);
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_string_pretty(value)?)
+            .with_context(|| format!("Failed to write {:?}", temp))?;
+        fs::rename(&temp, &path).with_context(|| format!("Failed to replace {:?}", path))
+    }
+}
+
+impl Storage for FileStorage {
+    fn load_routes(&self) -> Result<Option<Vec<Route>>> {
+        self.read_saved_json(ROUTES_FILE)
+    }
+
+    fn save_routes(&self, routes: &[Route]) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        self.write_json(ROUTES_FILE, &routes)
+    }
+
+    fn load_salvos(&self) -> Result<Vec<Salvo>> {
+        self.read_json(SALVOS_FILE)
+    }
+
+    fn save_salvo(&self, salvo: &Salvo) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut salvos: Vec<Salvo> = self.read_json(SALVOS_FILE)?;
+        salvos.retain(|s| s.name != salvo.name);
+        salvos.push(salvo.clone());
+        self.write_json(SALVOS_FILE, &salvos)
+    }
+
+    fn load_monitor_levels(&self) -> Result<BTreeMap<String, MonitorLevel>> {
+        self.read_json(MONITOR_LEVELS_FILE)
+    }
+
+    fn save_monitor_levels(&self, levels: &BTreeMap<String, MonitorLevel>) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        self.write_json(MONITOR_LEVELS_FILE, levels)
+    }
+
+    fn append_event(&self, event: &Event) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let path = self.directory.join(EVENTS_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {:?}", path))?;
+        writeln!(file, "{}", serde_json::to_string(event)?)
+            .with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Scans the whole file; use the SQLite backend for long histories
+    fn query_events(&self, query: &EventQuery) -> Result<Vec<Event>> {
+        let path = self.directory.join(EVENTS_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+
+        let mut events = VecDeque::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("Failed to read {:?}", path))?;
+            let event: Event = match serde_json::from_str(&line) {
+                Ok(event) => event,
+                Err(e) => {
+                    // A line cut short by a crash shouldn't hide the rest of the history
+                    debug!("Skipping unreadable event: {}", e);
+                    continue;
+                }
+            };
+            if !query.matches(&event) {
+                continue;
+            }
+            events.push_back(event);
+            if query.limit.is_some_and(|limit| events.len() > limit) {
+                events.pop_front();
+            }
+        }
+        Ok(events.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventCategory, Severity};
+
+    #[test]
+    fn test_file_storage_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FileStorage::open(&dir.path().join("state")).unwrap();
+        assert!(storage.load_routes().unwrap().is_none());
+        storage.save_routes(&[]).unwrap();
+        assert_eq!(storage.load_routes().unwrap(), Some(vec![]));
+
+        let routes = vec![Route::new("Cam 1".to_string(), "Program".to_string())];
+        storage.save_routes(&routes).unwrap();
+        assert_eq!(storage.load_routes().unwrap(), Some(routes.clone()));
+
+        storage
+            .save_salvo(&Salvo::new("Service".to_string(), routes.clone()))
+            .unwrap();
+        storage
+            .save_salvo(&Salvo::new("Service".to_string(), Vec::new()))
+            .unwrap();
+        let salvos = storage.load_salvos().unwrap();
+        assert_eq!(salvos.len(), 1);
+        assert!(salvos[0].routes.is_empty());
+
+        let levels = BTreeMap::from([("Cam 1".to_string(), MonitorLevel::new(-12.0))]);
+        storage.save_monitor_levels(&levels).unwrap();
+        assert_eq!(storage.load_monitor_levels().unwrap(), levels);
+
+        for i in 0..5 {
+            let category = if i % 2 == 0 {
+                EventCategory::Routing
+            } else {
+                EventCategory::Camera
+            };
+            storage
+                .append_event(&Event::new(
+                    Severity::Info,
+                    category,
+                    format!("event {}", i),
+                ))
+                .unwrap();
+        }
+        let events = storage
+            .query_events(&EventQuery {
+                category: Some(EventCategory::Routing),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["event 2", "event 4"]);
+    }
+}
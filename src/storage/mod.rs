@@ -0,0 +1,310 @@
+//! Persistent storage for routing state, salvos, monitoring levels and the event
+//! history, kept in flat files or an embedded SQLite database
+
+mod file;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use file::FileStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+use crate::audio::MonitorLevel;
+use crate::config::{Config, StorageBackend, StorageConfig};
+use crate::events::{Event, EventCategory, EventSink};
+use crate::matrix::{MatrixRouter, Route, Salvo};
+use anyhow::Result;
+use log::{error, info};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often the router is checked for route changes to save
+const PERSIST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Backend that keeps state across restarts
+pub trait Storage: Send + Sync {
+    /// Routes saved by the last `save_routes`, None if routes were never saved
+    fn load_routes(&self) -> Result<Option<Vec<Route>>>;
+    /// Replace the saved routes
+    fn save_routes(&self, routes: &[Route]) -> Result<()>;
+    fn load_salvos(&self) -> Result<Vec<Salvo>>;
+    /// Save a salvo, replacing any salvo with the same name
+    fn save_salvo(&self, salvo: &Salvo) -> Result<()>;
+    /// Monitoring gain and mute of each source, by NDI name
+    fn load_monitor_levels(&self) -> Result<BTreeMap<String, MonitorLevel>>;
+    /// Replace the saved monitoring levels
+    fn save_monitor_levels(&self, levels: &BTreeMap<String, MonitorLevel>) -> Result<()>;
+    fn append_event(&self, event: &Event) -> Result<()>;
+    /// Events matching a query, oldest first
+    fn query_events(&self, query: &EventQuery) -> Result<Vec<Event>>;
+}
+
+/// Filter for stored events
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    /// Only events at or after this time (milliseconds since the Unix epoch)
+    pub since_ms: Option<u64>,
+    pub category: Option<EventCategory>,
+    pub actor: Option<String>,
+    /// Only the most recent events, up to this many
+    pub limit: Option<usize>,
+}
+
+impl EventQuery {
+    pub fn matches(&self, event: &Event) -> bool {
+        self.since_ms
+            .is_none_or(|since| event.timestamp_ms >= since)
+            && self.category.is_none_or(|c| event.category == c)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|a| event.actor.as_ref() == Some(a))
+    }
+}
+
+/// Open the storage backend selected in configuration
+pub fn open(config: &StorageConfig) -> Result<Arc<dyn Storage>> {
+    info!(
+        "Opening {:?} storage in {:?}",
+        config.backend, config.directory
+    );
+    match config.backend {
+        StorageBackend::File => Ok(Arc::new(FileStorage::open(&config.directory)?)),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Ok(Arc::new(SqliteStorage::open(&config.directory)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => {
+            anyhow::bail!("RusTV was built without SQLite support (rebuild with --features sqlite)")
+        }
+    }
+}
+
+/// Restore stored routes and salvos into the configuration. Saved routes replace
+/// the configured ones, keeping the notes written in configuration for the same
+/// route; stored salvos win over configured ones of the same name, keeping their
+/// notes if they have none. Routes are left alone with `persist_routes = false`
+pub fn restore(storage: &dyn Storage, config: &mut Config) -> Result<()> {
+    if config.matrix.persist_routes {
+        restore_routes(storage, config)?;
+    }
+    for mut salvo in storage.load_salvos()? {
+        if let Some(configured) = config.matrix.salvos.iter().find(|s| s.name == salvo.name) {
+            salvo.note = salvo.note.or_else(|| configured.note.clone());
+            if salvo.tags.is_empty() {
+                salvo.tags = configured.tags.clone();
+            }
+        }
+        config.matrix.salvos.retain(|s| s.name != salvo.name);
+        config.matrix.salvos.push(salvo);
+    }
+    Ok(())
+}
+
+/// Replace the configured routes with the saved ones, if any were saved: an
+/// output unrouted since stays unrouted
+fn restore_routes(storage: &dyn Storage, config: &mut Config) -> Result<()> {
+    let Some(stored) = storage.load_routes()? else {
+        return Ok(());
+    };
+    let configured = std::mem::take(&mut config.matrix.routes);
+    config.matrix.routes = stored
+        .into_iter()
+        .map(|mut route| {
+            if let Some(configured) = configured
+                .iter()
+                .find(|r| r.output == route.output && r.input == route.input)
+                .filter(|_| !route.has_notes())
+            {
+                route.note = configured.note.clone();
+                route.tags = configured.tags.clone();
+            }
+            route
+        })
+        .collect();
+    Ok(())
+}
+
+/// Where routes are kept across restarts: the storage backend if enabled, else
+/// the routes file of file storage in the storage directory, so enabling storage
+/// later carries them on. Routes saved there replace the configured ones.
+/// None with `persist_routes = false`
+pub fn open_routes(
+    config: &mut Config,
+    storage: Option<&Arc<dyn Storage>>,
+) -> Result<Option<Arc<dyn Storage>>> {
+    if !config.matrix.persist_routes {
+        return Ok(None);
+    }
+    if let Some(storage) = storage {
+        // Restored along with the salvos
+        return Ok(Some(Arc::clone(storage)));
+    }
+    let routes = FileStorage::open(&config.storage.directory)?;
+    restore_routes(&routes, config)?;
+    Ok(Some(Arc::new(routes)))
+}
+
+/// Routes sorted by output, so unchanged routing compares equal
+fn sorted_routes(router: &MatrixRouter) -> Vec<Route> {
+    let mut routes = router.route_intents();
+    routes.sort_by(|a, b| a.output.cmp(&b.output));
+    routes
+}
+
+/// Save the router's routes in the background whenever they change
+pub fn persist_routes(router: Arc<Mutex<MatrixRouter>>, storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        let mut saved = sorted_routes(&router.lock().unwrap());
+        let mut interval = tokio::time::interval(PERSIST_INTERVAL);
+        loop {
+            interval.tick().await;
+            let routes = sorted_routes(&router.lock().unwrap());
+            if routes == saved {
+                continue;
+            }
+            match storage.save_routes(&routes) {
+                Ok(()) => saved = routes,
+                Err(e) => error!("Failed to save routes: {:#}", e),
+            }
+        }
+    });
+}
+
+/// Event sink recording every event in storage
+pub struct StorageSink {
+    storage: Arc<dyn Storage>,
+}
+
+impl StorageSink {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl EventSink for StorageSink {
+    fn emit(&self, event: &Event) {
+        if let Err(e) = self.storage.append_event(event) {
+            error!("Failed to store event: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Severity;
+
+    #[test]
+    fn test_restore_prefers_stored_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FileStorage::open(dir.path()).unwrap();
+        storage
+            .save_routes(&[
+                Route::new("Cam 2".to_string(), "Program".to_string()),
+                Route::new("Cam 3".to_string(), "Preview".to_string()),
+            ])
+            .unwrap();
+
+        let mut config = Config::default();
+        let mut noted = Route::new("Cam 3".to_string(), "Preview".to_string());
+        noted.note = Some("Wide shot".to_string());
+        config.matrix.routes = vec![
+            Route::new("Cam 1".to_string(), "Program".to_string()),
+            noted.clone(),
+        ];
+        restore(&storage, &mut config).unwrap();
+
+        assert_eq!(config.matrix.routes.len(), 2);
+        assert!(config
+            .matrix
+            .routes
+            .contains(&Route::new("Cam 2".to_string(), "Program".to_string())));
+        // The stored route keeps the configured note
+        assert!(config.matrix.routes.contains(&noted));
+    }
+
+    #[test]
+    fn test_unrouted_output_stays_unrouted() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FileStorage::open(dir.path()).unwrap();
+        let mut config = Config::default();
+        config.matrix.routes = vec![
+            Route::new("Cam 1".to_string(), "Program".to_string()),
+            Route::new("Cam 2".to_string(), "Preview".to_string()),
+        ];
+
+        // Nothing saved yet: the configured routes stand
+        restore(&storage, &mut config).unwrap();
+        assert_eq!(config.matrix.routes.len(), 2);
+
+        // Program unrouted while running
+        storage
+            .save_routes(&[Route::new("Cam 2".to_string(), "Preview".to_string())])
+            .unwrap();
+
+        let mut restarted = config.clone();
+        restore(&storage, &mut restarted).unwrap();
+        assert_eq!(
+            restarted.matrix.routes,
+            [Route::new("Cam 2".to_string(), "Preview".to_string())]
+        );
+
+        // Everything cleared is saved as no routes at all
+        storage.save_routes(&[]).unwrap();
+        let mut cleared = config.clone();
+        restore(&storage, &mut cleared).unwrap();
+        assert!(cleared.matrix.routes.is_empty());
+    }
+
+    #[test]
+    fn test_routes_kept_without_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.storage.directory = dir.path().to_path_buf();
+        let routes = open_routes(&mut config, None).unwrap().unwrap();
+        assert!(config.matrix.routes.is_empty());
+        routes
+            .save_routes(&[Route::new("Cam 2".to_string(), "Program".to_string())])
+            .unwrap();
+
+        // Restored on the next start
+        let mut restarted = Config::default();
+        restarted.storage.directory = dir.path().to_path_buf();
+        open_routes(&mut restarted, None).unwrap();
+        assert_eq!(
+            restarted.matrix.routes,
+            [Route::new("Cam 2".to_string(), "Program".to_string())]
+        );
+
+        // Not with persistence off
+        let mut off = Config::default();
+        off.storage.directory = dir.path().to_path_buf();
+        off.matrix.persist_routes = false;
+        assert!(open_routes(&mut off, None).unwrap().is_none());
+        assert!(off.matrix.routes.is_empty());
+    }
+
+    #[test]
+    fn test_event_query_matches() {
+        let event = Event::new(
+            Severity::Info,
+            EventCategory::Camera,
+            "Preset 2".to_string(),
+        )
+        .with_actor("companion");
+
+        assert!(EventQuery::default().matches(&event));
+        assert!(EventQuery {
+            category: Some(EventCategory::Camera),
+            actor: Some("companion".to_string()),
+            ..Default::default()
+        }
+        .matches(&event));
+        assert!(!EventQuery {
+            since_ms: Some(event.timestamp_ms + 1),
+            ..Default::default()
+        }
+        .matches(&event));
+    }
+}
@@ -0,0 +1,314 @@
+//! SQLite storage: indexed event history for fast queries over long audit trails
+
+use super::{EventQuery, Storage};
+use crate::audio::MonitorLevel;
+use crate::events::Event;
+use crate::matrix::{Route, Salvo};
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+const DATABASE_FILE: &str = "rustv.db";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS routes (
+        output TEXT PRIMARY KEY,
+        input TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS routes_saved (
+        id INTEGER PRIMARY KEY CHECK (id = 1)
+    );
+    CREATE TABLE IF NOT EXISTS salvos (
+        name TEXT PRIMARY KEY,
+        routes TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS monitor_levels (
+        source TEXT PRIMARY KEY,
+        gain_db REAL NOT NULL,
+        muted INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        timestamp_ms INTEGER NOT NULL,
+        severity TEXT NOT NULL,
+        category TEXT NOT NULL,
+        actor TEXT,
+        message TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS events_timestamp ON events (timestamp_ms);
+    CREATE INDEX IF NOT EXISTS events_category ON events (category, timestamp_ms);
+";
+
+/// State kept in an SQLite database in a directory
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Open (or create) the database in a directory
+    pub fn open(directory: &Path) -> Result<Self> {
+        fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create storage directory {:?}", directory))?;
+        let path = directory.join(DATABASE_FILE);
+        let connection =
+            Connection::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+        // WAL keeps event inserts from blocking readers
+        connection
+            .pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to enable WAL")?;
+        connection
+            .execute_batch(SCHEMA)
+            .context("Failed to create database schema")?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+/// A unit enum as its serialized name (e.g. "routing")
+fn to_text<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(text) => Ok(text),
+        other => anyhow::bail!("Expected a name, got {}", other),
+    }
+}
+
+fn from_text<T: DeserializeOwned>(text: String) -> Result<T> {
+    Ok(serde_json::from_value(serde_json::Value::String(text))?)
+}
+
+impl Storage for SqliteStorage {
+    fn load_routes(&self) -> Result<Option<Vec<Route>>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT input, output FROM routes")?;
+        let routes: Vec<Route> = statement
+            .query_map([], |row| Ok(Route::new(row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        // Databases from before routes_saved only hold routes once saved
+        let saved: bool =
+            connection.query_row("SELECT EXISTS (SELECT 1 FROM routes_saved)", [], |row| {
+                row.get(0)
+            })?;
+        Ok((saved || !routes.is_empty()).then_some(routes))
+    }
+
+    fn save_routes(&self, routes: &[Route]) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM routes", [])?;
+        transaction.execute("INSERT OR IGNORE INTO routes_saved (id) VALUES (1)", [])?;
+        for route in routes {
+            transaction.execute(
+                "INSERT INTO routes (output, input) VALUES (?1, ?2)",
+                params![route.output, route.input],
+            )?;
+        }
+        transaction.commit().context("Failed to save routes")
+    }
+
+    fn load_salvos(&self) -> Result<Vec<Salvo>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT name, routes FROM salvos ORDER BY name")?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter()
+            .map(|(name, routes)| {
+                let routes = serde_json::from_str(&routes)
+                    .with_context(|| format!("Invalid routes in salvo '{}'", name))?;
+                Ok(Salvo::new(name, routes))
+            })
+            .collect()
+    }
+
+    fn save_salvo(&self, salvo: &Salvo) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO salvos (name, routes) VALUES (?1, ?2)",
+                params![salvo.name, serde_json::to_string(&salvo.routes)?],
+            )
+            .context("Failed to save salvo")?;
+        Ok(())
+    }
+
+    fn load_monitor_levels(&self) -> Result<BTreeMap<String, MonitorLevel>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement =
+            connection.prepare("SELECT source, gain_db, muted FROM monitor_levels")?;
+        let levels = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    MonitorLevel {
+                        gain_db: row.get::<_, f64>(1)? as f32,
+                        muted: row.get(2)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(levels)
+    }
+
+    fn save_monitor_levels(&self, levels: &BTreeMap<String, MonitorLevel>) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM monitor_levels", [])?;
+        for (source, level) in levels {
+            transaction.execute(
+                "INSERT INTO monitor_levels (source, gain_db, muted) VALUES (?1, ?2, ?3)",
+                params![source, level.gain_db as f64, level.muted],
+            )?;
+        }
+        transaction
+            .commit()
+            .context("Failed to save monitoring levels")
+    }
+
+    fn append_event(&self, event: &Event) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute(
+                "INSERT INTO events (timestamp_ms, severity, category, actor, message)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    event.timestamp_ms as i64,
+                    to_text(&event.severity)?,
+                    to_text(&event.category)?,
+                    event.actor,
+                    event.message
+                ],
+            )
+            .context("Failed to store event")?;
+        Ok(())
+    }
+
+    fn query_events(&self, query: &EventQuery) -> Result<Vec<Event>> {
+        let mut sql = String::from(
+            "SELECT timestamp_ms, severity, category, actor, message FROM events WHERE 1 = 1",
+        );
+        let mut values = Vec::new();
+        if let Some(since) = query.since_ms {
+            sql.push_str(" AND timestamp_ms >= ?");
+            values.push(Value::Integer(since as i64));
+        }
+        if let Some(category) = &query.category {
+            sql.push_str(" AND category = ?");
+            values.push(Value::Text(to_text(category)?));
+        }
+        if let Some(actor) = &query.actor {
+            sql.push_str(" AND actor = ?");
+            values.push(Value::Text(actor.clone()));
+        }
+        // Newest first so LIMIT keeps the most recent, then back to oldest first
+        sql.push_str(" ORDER BY id DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(" LIMIT ?");
+            values.push(Value::Integer(limit as i64));
+        }
+
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(&sql)?;
+        let rows = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut events = rows
+            .into_iter()
+            .map(|(timestamp_ms, severity, category, actor, message)| {
+                Ok(Event {
+                    timestamp_ms: timestamp_ms as u64,
+                    severity: from_text(severity)?,
+                    category: from_text(category)?,
+                    actor,
+                    message,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        events.reverse();
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventCategory, Severity};
+
+    #[test]
+    fn test_sqlite_storage_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+
+        assert!(storage.load_routes().unwrap().is_none());
+        storage.save_routes(&[]).unwrap();
+        assert_eq!(storage.load_routes().unwrap(), Some(vec![]));
+
+        let routes = vec![Route::new("Cam 1".to_string(), "Program".to_string())];
+        storage.save_routes(&routes).unwrap();
+        storage.save_routes(&routes).unwrap();
+        assert_eq!(storage.load_routes().unwrap(), Some(routes.clone()));
+
+        storage
+            .save_salvo(&Salvo::new("Service".to_string(), routes.clone()))
+            .unwrap();
+        assert_eq!(storage.load_salvos().unwrap()[0].routes, routes);
+
+        let levels = BTreeMap::from([(
+            "Cam 1".to_string(),
+            MonitorLevel {
+                gain_db: -6.0,
+                muted: true,
+            },
+        )]);
+        storage.save_monitor_levels(&levels).unwrap();
+        assert_eq!(storage.load_monitor_levels().unwrap(), levels);
+
+        for i in 0..5 {
+            let event = Event::new(
+                Severity::Info,
+                EventCategory::Routing,
+                format!("event {}", i),
+            );
+            let event = if i == 3 {
+                event.with_actor("companion")
+            } else {
+                event
+            };
+            storage.append_event(&event).unwrap();
+        }
+        let events = storage
+            .query_events(&EventQuery {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["event 3", "event 4"]);
+
+        let events = storage
+            .query_events(&EventQuery {
+                actor: Some("companion".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].category, EventCategory::Routing);
+    }
+}
@@ -0,0 +1,297 @@
+//! Watchdog for long-running background tasks: restarts a task that fails or
+//! panics, with backoff, and keeps restart counts for the status bar and API
+
+use crate::events::{Event, EventCategory, EventLog, Severity};
+use anyhow::Result;
+use log::{error, info};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Wait before the first restart of a task
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest wait between restarts of a task that keeps failing
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A task that ran this long before failing starts again from the initial backoff
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// What a supervised task is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Failed, waiting to be restarted
+    Restarting,
+}
+
+/// State of one supervised task, as reported by the status bar and API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    pub restarts: u32,
+    /// Why the task last failed
+    pub last_error: Option<String>,
+}
+
+/// Time to wait before each restart, doubling while a task keeps failing
+#[derive(Debug, Clone)]
+struct Backoff {
+    next: Duration,
+}
+
+impl Backoff {
+    fn new() -> Self {
+        Self {
+            next: INITIAL_BACKOFF,
+        }
+    }
+
+    /// Wait before restarting a task that failed after running for `ran`
+    fn after_failure(&mut self, ran: Duration) -> Duration {
+        if ran >= STABLE_AFTER {
+            self.next = INITIAL_BACKOFF;
+        }
+        let wait = self.next;
+        self.next = (self.next * 2).min(MAX_BACKOFF);
+        wait
+    }
+}
+
+/// Runs background tasks and restarts them when they fail or panic.
+///
+/// A task that returns `Ok(())` has finished on purpose (e.g. it was stopped)
+/// and is not restarted.
+#[derive(Default)]
+pub struct Supervisor {
+    /// Tasks by name, with the spawn that owns the name. A name can be taken
+    /// over (e.g. by a receiver reconnecting) while the old task winds down
+    tasks: Mutex<BTreeMap<String, (u64, TaskStatus)>>,
+    next_id: AtomicU64,
+    events: Option<EventLog>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record each restart in the event log
+    pub fn with_events(mut self, events: EventLog) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Run an async task, calling `task` again for each restart
+    pub fn spawn<F, Fut>(self: &Arc<Self>, name: &str, task: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let supervisor = Arc::clone(self);
+        let name = name.to_string();
+        let id = supervisor.next_id.fetch_add(1, Ordering::Relaxed);
+        supervisor.started(&name, id);
+        tokio::spawn(async move {
+            let mut backoff = Backoff::new();
+            loop {
+                let started = Instant::now();
+                // Run on its own task so a panic is caught here
+                let failure = match tokio::spawn(task()).await {
+                    Ok(Ok(())) => break,
+                    Ok(Err(e)) => format!("{:#}", e),
+                    Err(e) => panic_message(e.into_panic()),
+                };
+                let wait = supervisor.failed(&name, id, failure, &mut backoff, started.elapsed());
+                tokio::time::sleep(wait).await;
+                supervisor.started(&name, id);
+            }
+            supervisor.finished(&name, id);
+        });
+    }
+
+    /// Run a blocking task on its own thread, calling `task` again for each restart
+    pub fn spawn_thread<F>(self: &Arc<Self>, name: &str, task: F)
+    where
+        F: Fn() -> Result<()> + Send + 'static,
+    {
+        let supervisor = Arc::clone(self);
+        let name = name.to_string();
+        let id = supervisor.next_id.fetch_add(1, Ordering::Relaxed);
+        supervisor.started(&name, id);
+        thread::spawn(move || {
+            let mut backoff = Backoff::new();
+            loop {
+                let started = Instant::now();
+                let failure = match panic::catch_unwind(AssertUnwindSafe(&task)) {
+                    Ok(Ok(())) => break,
+                    Ok(Err(e)) => format!("{:#}", e),
+                    Err(payload) => panic_message(payload),
+                };
+                let wait = supervisor.failed(&name, id, failure, &mut backoff, started.elapsed());
+                thread::sleep(wait);
+                supervisor.started(&name, id);
+            }
+            supervisor.finished(&name, id);
+        });
+    }
+
+    /// State of every running task, by name
+    pub fn status(&self) -> Vec<TaskStatus> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(_, status)| status.clone())
+            .collect()
+    }
+
+    /// Restarts of all running tasks
+    pub fn total_restarts(&self) -> u32 {
+        self.tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(_, status)| status.restarts)
+            .sum()
+    }
+
+    fn started(&self, name: &str, id: u64) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let fresh = || {
+            let status = TaskStatus {
+                name: name.to_string(),
+                state: TaskState::Running,
+                restarts: 0,
+                last_error: None,
+            };
+            (id, status)
+        };
+        let (owner, status) = tasks.entry(name.to_string()).or_insert_with(fresh);
+        if *owner != id {
+            (*owner, *status) = fresh();
+        } else if status.state == TaskState::Restarting {
+            status.state = TaskState::Running;
+            status.restarts += 1;
+            info!("Restarted {} (restart {})", name, status.restarts);
+        }
+    }
+
+    /// Record a failure and return how long to wait before restarting
+    fn failed(
+        &self,
+        name: &str,
+        id: u64,
+        failure: String,
+        backoff: &mut Backoff,
+        ran: Duration,
+    ) -> Duration {
+        let wait = backoff.after_failure(ran);
+        error!(
+            "{} failed: {} (restarting in {} s)",
+            name,
+            failure,
+            wait.as_secs()
+        );
+        if let Some(events) = &self.events {
+            events.record(
+                Event::new(
+                    Severity::Warning,
+                    EventCategory::System,
+                    format!("{} failed and will be restarted: {}", name, failure),
+                )
+                .with_actor("supervisor"),
+            );
+        }
+        if let Some((_, status)) = self
+            .tasks
+            .lock()
+            .unwrap()
+            .get_mut(name)
+            .filter(|(owner, _)| *owner == id)
+        {
+            status.state = TaskState::Restarting;
+            status.last_error = Some(failure);
+        }
+        wait
+    }
+
+    fn finished(&self, name: &str, id: u64) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if tasks.get(name).is_some_and(|(owner, _)| *owner == id) {
+            tasks.remove(name);
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown cause".to_string());
+    format!("panicked: {}", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new();
+        let quick = Duration::from_millis(10);
+        assert_eq!(backoff.after_failure(quick), Duration::from_secs(1));
+        assert_eq!(backoff.after_failure(quick), Duration::from_secs(2));
+        assert_eq!(backoff.after_failure(quick), Duration::from_secs(4));
+        for _ in 0..10 {
+            backoff.after_failure(quick);
+        }
+        assert_eq!(backoff.after_failure(quick), MAX_BACKOFF);
+
+        // A task that stayed up a while starts over
+        assert_eq!(backoff.after_failure(STABLE_AFTER), INITIAL_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_restarts_failed_task() {
+        let supervisor = Arc::new(Supervisor::new());
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = Arc::clone(&runs);
+        supervisor.spawn_thread("flaky", move || {
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => panic!("lost the finder"),
+                _ => {
+                    thread::sleep(Duration::from_secs(5));
+                    Ok(())
+                }
+            }
+        });
+
+        tokio::time::sleep(INITIAL_BACKOFF + Duration::from_millis(500)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        let status = supervisor.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].state, TaskState::Running);
+        assert_eq!(status[0].restarts, 1);
+        assert_eq!(
+            status[0].last_error.as_deref(),
+            Some("panicked: lost the finder")
+        );
+        assert_eq!(supervisor.total_restarts(), 1);
+
+        // Finishing on purpose is not a failure
+        supervisor.spawn("once", || async { Ok(()) });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(supervisor.status().iter().all(|t| t.name != "once"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Reads show timecode from the configured source and fires timecode triggers
+
+use super::ltc::LtcDecoder;
+use super::triggers::{self, TriggerSchedule};
+use super::Timecode;
+use crate::companion::CompanionControl;
+use crate::config::{TimecodeConfig, TimecodeSource, TimecodeTrigger};
+use anyhow::Result;
+use log::{error, info, warn};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Listens for show timecode and runs the configured triggers
+pub struct TimecodeListener {
+    config: TimecodeConfig,
+    /// Latest timecode received
+    current: Arc<Mutex<Option<Timecode>>>,
+}
+
+impl TimecodeListener {
+    pub fn new(config: &TimecodeConfig) -> Self {
+        Self {
+            config: config.clone(),
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Latest timecode received (None until timecode arrives)
+    pub fn current(&self) -> Option<Timecode> {
+        *self.current.lock().unwrap()
+    }
+
+    /// The next trigger timecode will reach, with the time until it does
+    pub fn next_trigger(&self) -> Option<(TimecodeTrigger, Duration)> {
+        let now = self.current()?;
+        triggers::next_trigger(&self.config.triggers, self.config.fps, now)
+            .map(|(trigger, countdown)| (trigger.clone(), countdown))
+    }
+
+    /// Start reading timecode; triggers fire through `control` when one is given
+    pub fn start(&self, control: Option<Arc<CompanionControl>>) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let source = self.config.source;
+        let input = self.config.input.clone();
+        let fps = self.config.fps;
+
+        info!("Listening for {:?} timecode", source);
+        // Audio and MIDI devices are driven from a dedicated thread
+        thread::spawn(move || {
+            let result = match source {
+                TimecodeSource::Ltc => read_ltc(input.as_deref(), &tx),
+                TimecodeSource::Mtc => read_mtc(input.as_deref(), fps, &tx),
+            };
+            if let Err(e) = result {
+                error!("Timecode input stopped: {}", e);
+            }
+        });
+
+        let current = Arc::clone(&self.current);
+        let mut schedule = TriggerSchedule::new(&self.config.triggers, self.config.fps);
+        tokio::spawn(async move {
+            while let Some(tc) = rx.recv().await {
+                *current.lock().unwrap() = Some(tc);
+
+                let Some(control) = &control else {
+                    continue;
+                };
+                for action in schedule.advance(tc) {
+                    info!("Timecode {} reached: {:?}", tc, action);
+                    let control = Arc::clone(control);
+                    tokio::spawn(async move {
+                        if let Err(e) = control.handle_from("timecode", action).await {
+                            warn!("Timecode trigger failed: {}", e);
+                        }
+                    });
+                }
+            }
+        });
+    }
+}
+
+/// Decode LTC from the first channel of an audio input
+fn read_ltc(device: Option<&str>, tx: &UnboundedSender<Timecode>) -> Result<()> {
+    use crate::audio::MicCapture;
+
+    let (_capture, chunks) = MicCapture::start(device)?;
+    let mut decoder: Option<LtcDecoder> = None;
+
+    for chunk in chunks {
+        let decoder = decoder.get_or_insert_with(|| LtcDecoder::new(chunk.sample_rate));
+        let first_channel: Vec<f32> = chunk
+            .samples
+            .iter()
+            .step_by(chunk.channels.max(1) as usize)
+            .copied()
+            .collect();
+        for tc in decoder.push(&first_channel) {
+            if tx.send(tc).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse MTC from a MIDI input port
+#[cfg(feature = "midi")]
+fn read_mtc(port_name: Option<&str>, fps: u32, tx: &UnboundedSender<Timecode>) -> Result<()> {
+    use super::mtc::MtcParser;
+    use anyhow::Context;
+    use midir::{Ignore, MidiInput};
+    use std::sync::mpsc as std_mpsc;
+
+    let mut midi = MidiInput::new("RusTV").context("Failed to open MIDI input")?;
+    // Timecode arrives as time and SysEx messages, which are ignored by default
+    midi.ignore(Ignore::ActiveSense);
+
+    let ports = midi.ports();
+    let port = match port_name {
+        Some(name) => ports
+            .iter()
+            .find(|p| midi.port_name(p).map(|n| n == name).unwrap_or(false))
+            .with_context(|| format!("MIDI port '{}' not found", name))?,
+        None => ports.first().context("No MIDI input ports available")?,
+    };
+    info!(
+        "Reading MTC from {}",
+        midi.port_name(port).unwrap_or_default()
+    );
+
+    let (done_tx, done_rx) = std_mpsc::channel::<()>();
+    let tx = tx.clone();
+    let mut parser = MtcParser::new();
+    let mut rate_checked = false;
+    let _connection = midi
+        .connect(
+            port,
+            "rustv-mtc",
+            move |_, message, _| {
+                if let Some(tc) = parser.push(message) {
+                    match parser.fps() {
+                        Some(rate) if rate != fps && !rate_checked => {
+                            warn!("MTC runs at {} fps but triggers use {} fps", rate, fps)
+                        }
+                        _ => {}
+                    }
+                    rate_checked = true;
+                    if tx.send(tc).is_err() {
+                        let _ = done_tx.send(());
+                    }
+                }
+            },
+            (),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to connect to MIDI port: {}", e))?;
+
+    // Keep the connection open until the listener goes away
+    let _ = done_rx.recv();
+    Ok(())
+}
+
+/// Placeholder used when RusTV is built without MIDI support
+#[cfg(not(feature = "midi"))]
+fn read_mtc(_port_name: Option<&str>, _fps: u32, _tx: &UnboundedSender<Timecode>) -> Result<()> {
+    anyhow::bail!("RusTV was built without MIDI support (enable the `midi` feature)")
+}
@@ -0,0 +1,163 @@
+//! Linear timecode (SMPTE LTC) decoding from audio samples
+
+use super::Timecode;
+
+/// Sync word ending every LTC frame (bits 64-79, first transmitted bit lowest)
+const SYNC_WORD: u128 = 0xBFFC;
+
+/// Bits in one LTC frame
+const FRAME_BITS: usize = 80;
+
+/// Level a signal must cross to count as a transition (rejects noise around zero)
+const HYSTERESIS: f32 = 0.02;
+
+/// Decodes biphase-mark LTC from a mono audio signal
+pub struct LtcDecoder {
+    /// Estimated samples per bit
+    bit_period: f32,
+    /// Signal level above zero (with hysteresis)
+    high: bool,
+    /// Samples since the last transition
+    since_transition: u32,
+    /// A short interval waiting for its pair (a "1" bit)
+    half_bit: bool,
+    /// Last 80 bits received, newest at bit 79
+    bits: u128,
+}
+
+impl LtcDecoder {
+    pub fn new(sample_rate: u32) -> Self {
+        // Between the bit periods of 24 and 30 fps, so either locks on
+        Self {
+            bit_period: sample_rate as f32 / 2200.0,
+            high: false,
+            since_transition: 0,
+            half_bit: false,
+            bits: 0,
+        }
+    }
+
+    /// Feed samples, returning every complete frame decoded from them
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Timecode> {
+        let mut decoded = Vec::new();
+
+        for &sample in samples {
+            self.since_transition += 1;
+            let transition =
+                (self.high && sample < -HYSTERESIS) || (!self.high && sample > HYSTERESIS);
+            if !transition {
+                continue;
+            }
+            self.high = !self.high;
+            let interval = self.since_transition as f32;
+            self.since_transition = 0;
+
+            if interval > self.bit_period * 0.75 {
+                // Long interval: a whole "0" bit
+                self.bit_period += (interval - self.bit_period) * 0.1;
+                self.half_bit = false;
+                if let Some(tc) = self.push_bit(false) {
+                    decoded.push(tc);
+                }
+            } else if self.half_bit {
+                // Second short interval: a "1" bit
+                self.bit_period += (interval * 2.0 - self.bit_period) * 0.1;
+                self.half_bit = false;
+                if let Some(tc) = self.push_bit(true) {
+                    decoded.push(tc);
+                }
+            } else {
+                self.half_bit = true;
+            }
+        }
+
+        decoded
+    }
+
+    fn push_bit(&mut self, bit: bool) -> Option<Timecode> {
+        self.bits = (self.bits >> 1) | ((bit as u128) << (FRAME_BITS - 1));
+        if self.bits >> 64 != SYNC_WORD {
+            return None;
+        }
+        decode_frame(self.bits)
+    }
+}
+
+/// Decode the BCD time fields of an LTC frame
+fn decode_frame(bits: u128) -> Option<Timecode> {
+    let field = |start: u32, len: u32| ((bits >> start) & ((1 << len) - 1)) as u8;
+
+    let frames = field(8, 2) * 10 + field(0, 4);
+    let seconds = field(24, 3) * 10 + field(16, 4);
+    let minutes = field(40, 3) * 10 + field(32, 4);
+    let hours = field(56, 2) * 10 + field(48, 4);
+
+    (frames < 60 && seconds < 60 && minutes < 60 && hours < 24)
+        .then(|| Timecode::new(hours, minutes, seconds, frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a frame's 80 bits (first transmitted first)
+    fn frame_bits(tc: Timecode) -> Vec<bool> {
+        let mut value: u128 = SYNC_WORD << 64;
+        let mut set = |start: u32, v: u8| value |= (v as u128) << start;
+        set(0, tc.frames % 10);
+        set(8, tc.frames / 10);
+        set(16, tc.seconds % 10);
+        set(24, tc.seconds / 10);
+        set(32, tc.minutes % 10);
+        set(40, tc.minutes / 10);
+        set(48, tc.hours % 10);
+        set(56, tc.hours / 10);
+        (0..FRAME_BITS).map(|i| value >> i & 1 == 1).collect()
+    }
+
+    /// Biphase-mark modulate frames into audio samples
+    fn encode(frames: &[Timecode], samples_per_bit: usize) -> Vec<f32> {
+        let mut level = 0.5f32;
+        let mut samples = Vec::new();
+        for bit in frames.iter().flat_map(|tc| frame_bits(*tc)) {
+            level = -level;
+            for i in 0..samples_per_bit {
+                if bit && i == samples_per_bit / 2 {
+                    level = -level;
+                }
+                samples.push(level);
+            }
+        }
+        // Closing edge so the final bit is complete
+        samples.extend([-level; 4]);
+        samples
+    }
+
+    #[test]
+    fn test_decode_25fps() {
+        let frames: Vec<Timecode> = (0..6)
+            .map(|i| Timecode::from_frames(Timecode::new(10, 59, 59, 20).to_frames(25) + i, 25))
+            .collect();
+        // 48 kHz at 25 fps: 24 samples per bit
+        let audio = encode(&frames, 24);
+
+        let mut decoder = LtcDecoder::new(48000);
+        let decoded: Vec<Timecode> = audio
+            .chunks(480)
+            .flat_map(|chunk| decoder.push(chunk))
+            .collect();
+
+        assert!(decoded.len() >= 5);
+        assert!(decoded.contains(&Timecode::new(10, 59, 59, 24)));
+        assert_eq!(decoded.last(), Some(&Timecode::new(11, 0, 0, 0)));
+    }
+
+    #[test]
+    fn test_decode_30fps() {
+        let frames: Vec<Timecode> = (0..3).map(|i| Timecode::new(1, 2, 3, 27 + i)).collect();
+        // 48 kHz at 30 fps: 20 samples per bit
+        let mut decoder = LtcDecoder::new(48000);
+        let decoded = decoder.push(&encode(&frames, 20));
+        assert_eq!(decoded.last(), Some(&Timecode::new(1, 2, 3, 29)));
+    }
+}
@@ -0,0 +1,127 @@
+//! Show timecode (LTC from an audio input, MTC from a MIDI port) and actions
+//! triggered at specific timecode values
+
+pub mod listener;
+pub mod ltc;
+pub mod mtc;
+pub mod triggers;
+
+pub use listener::TimecodeListener;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A SMPTE timecode value (HH:MM:SS:FF)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+}
+
+impl Timecode {
+    pub fn new(hours: u8, minutes: u8, seconds: u8, frames: u8) -> Self {
+        Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+        }
+    }
+
+    /// Frames since 00:00:00:00 at the given frame rate
+    pub fn to_frames(self, fps: u32) -> u64 {
+        let seconds = self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64;
+        seconds * fps as u64 + self.frames as u64
+    }
+
+    /// Timecode for a frame count at the given frame rate (wrapping at 24 hours)
+    pub fn from_frames(frames: u64, fps: u32) -> Self {
+        let fps = fps.max(1) as u64;
+        let seconds = (frames / fps) % (24 * 3600);
+        Self::new(
+            (seconds / 3600) as u8,
+            (seconds / 60 % 60) as u8,
+            (seconds % 60) as u8,
+            (frames % fps) as u8,
+        )
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+impl FromStr for Timecode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Drop-frame timecode is written with ';' before the frames
+        let parts: Vec<&str> = s.trim().split([':', ';']).collect();
+        if parts.len() != 4 {
+            anyhow::bail!("Invalid timecode '{}' (expected HH:MM:SS:FF)", s);
+        }
+        let field = |i: usize, max: u8| -> Result<u8> {
+            let value: u8 = parts[i]
+                .parse()
+                .with_context(|| format!("Invalid timecode '{}'", s))?;
+            if value > max {
+                anyhow::bail!("Invalid timecode '{}'", s);
+            }
+            Ok(value)
+        };
+        Ok(Self::new(
+            field(0, 23)?,
+            field(1, 59)?,
+            field(2, 59)?,
+            field(3, 59)?,
+        ))
+    }
+}
+
+impl TryFrom<String> for Timecode {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Timecode> for String {
+    fn from(tc: Timecode) -> Self {
+        tc.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let tc: Timecode = "01:02:03:04".parse().unwrap();
+        assert_eq!(tc, Timecode::new(1, 2, 3, 4));
+        assert_eq!(tc.to_string(), "01:02:03:04");
+        assert_eq!("10:00:00;12".parse::<Timecode>().unwrap().frames, 12);
+
+        assert!("01:02:03".parse::<Timecode>().is_err());
+        assert!("25:00:00:00".parse::<Timecode>().is_err());
+    }
+
+    #[test]
+    fn test_frames_round_trip() {
+        let tc = Timecode::new(1, 0, 0, 10);
+        assert_eq!(tc.to_frames(25), 90_010);
+        assert_eq!(Timecode::from_frames(90_010, 25), tc);
+    }
+}
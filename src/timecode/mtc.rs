@@ -0,0 +1,123 @@
+//! MIDI timecode (MTC) parsing from quarter-frame and full-frame messages
+
+// Only the MIDI input path parses MTC
+#![cfg_attr(not(feature = "midi"), allow(dead_code))]
+
+use super::Timecode;
+
+/// Frame rate for an MTC rate code (29.97 drop-frame counts like 30)
+fn rate_fps(code: u8) -> u32 {
+    match code & 0x03 {
+        0 => 24,
+        1 => 25,
+        _ => 30,
+    }
+}
+
+/// Assembles timecode from a stream of MIDI messages
+#[derive(Debug, Default)]
+pub struct MtcParser {
+    /// Quarter-frame nibbles received for the current frame
+    pieces: [u8; 8],
+    /// Bit set of pieces received since piece 0
+    received: u8,
+    /// Frame rate from the last complete timecode
+    fps: Option<u32>,
+}
+
+impl MtcParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Frame rate reported by the sender
+    pub fn fps(&self) -> Option<u32> {
+        self.fps
+    }
+
+    /// Feed one MIDI message, returning the timecode once a full value is known
+    pub fn push(&mut self, message: &[u8]) -> Option<Timecode> {
+        match message {
+            [0xF1, data] => self.quarter_frame(*data),
+            // Full frame: F0 7F <device> 01 01 hr mn sc fr F7
+            [0xF0, 0x7F, _, 0x01, 0x01, hr, mn, sc, fr, 0xF7] => {
+                self.fps = Some(rate_fps(hr >> 5));
+                self.received = 0;
+                Some(Timecode::new(hr & 0x1F, *mn, *sc, *fr))
+            }
+            _ => None,
+        }
+    }
+
+    fn quarter_frame(&mut self, data: u8) -> Option<Timecode> {
+        let piece = (data >> 4) as usize & 0x07;
+        if piece == 0 {
+            self.received = 0;
+        }
+        self.pieces[piece] = data & 0x0F;
+        self.received |= 1 << piece;
+
+        if piece != 7 || self.received != 0xFF {
+            return None;
+        }
+        self.received = 0;
+
+        let p = &self.pieces;
+        let fps = rate_fps(p[7] >> 1);
+        self.fps = Some(fps);
+        let tc = Timecode::new(
+            (p[7] & 0x01) << 4 | p[6],
+            (p[5] & 0x03) << 4 | p[4],
+            (p[3] & 0x03) << 4 | p[2],
+            (p[1] & 0x01) << 4 | p[0],
+        );
+
+        // The eight quarter frames span two frames; they describe the first one
+        Some(Timecode::from_frames(tc.to_frames(fps) + 2, fps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quarter_frames(tc: Timecode, rate: u8) -> Vec<[u8; 2]> {
+        let values = [
+            tc.frames & 0x0F,
+            tc.frames >> 4,
+            tc.seconds & 0x0F,
+            tc.seconds >> 4,
+            tc.minutes & 0x0F,
+            tc.minutes >> 4,
+            tc.hours & 0x0F,
+            tc.hours >> 4 | rate << 1,
+        ];
+        values
+            .iter()
+            .enumerate()
+            .map(|(piece, value)| [0xF1, (piece as u8) << 4 | value])
+            .collect()
+    }
+
+    #[test]
+    fn test_quarter_frames() {
+        let mut parser = MtcParser::new();
+        let messages = quarter_frames(Timecode::new(1, 59, 59, 23), 1);
+
+        // Joining mid-frame yields nothing until a full sequence arrives
+        assert_eq!(parser.push(&messages[5]), None);
+        let decoded: Vec<Timecode> = messages.iter().filter_map(|m| parser.push(m)).collect();
+
+        assert_eq!(decoded, vec![Timecode::new(2, 0, 0, 0)]);
+        assert_eq!(parser.fps(), Some(25));
+    }
+
+    #[test]
+    fn test_full_frame() {
+        let mut parser = MtcParser::new();
+        let message = [0xF0, 0x7F, 0x7F, 0x01, 0x01, 0x60 | 10, 20, 30, 15, 0xF7];
+        assert_eq!(parser.push(&message), Some(Timecode::new(10, 20, 30, 15)));
+        assert_eq!(parser.fps(), Some(30));
+        assert_eq!(parser.push(&[0x90, 60, 100]), None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Actions fired when show timecode passes configured values
+
+use super::Timecode;
+use crate::companion::CompanionAction;
+use crate::config::TimecodeTrigger;
+use std::time::Duration;
+
+/// Largest forward step (in seconds) treated as playback rather than a locate
+const MAX_PLAYBACK_STEP_SECS: u64 = 2;
+
+/// Decides which triggers fire as timecode advances
+pub struct TriggerSchedule {
+    triggers: Vec<(u64, TimecodeTrigger)>,
+    fps: u32,
+    /// Frame count of the previous timecode
+    last: Option<u64>,
+}
+
+impl TriggerSchedule {
+    pub fn new(triggers: &[TimecodeTrigger], fps: u32) -> Self {
+        let fps = fps.max(1);
+        let mut triggers: Vec<(u64, TimecodeTrigger)> = triggers
+            .iter()
+            .map(|t| (t.at.to_frames(fps), t.clone()))
+            .collect();
+        triggers.sort_by_key(|(frames, _)| *frames);
+
+        Self {
+            triggers,
+            fps,
+            last: None,
+        }
+    }
+
+    /// Record a new timecode and return the actions of the triggers it reached.
+    ///
+    /// While timecode plays forward every trigger passed since the previous value
+    /// fires once. After a jump (locate, rewind, or the first value received) only
+    /// a trigger exactly at the new timecode fires. A repeated timecode fires nothing.
+    pub fn advance(&mut self, tc: Timecode) -> Vec<CompanionAction> {
+        let now = tc.to_frames(self.fps);
+        if self.last == Some(now) {
+            return Vec::new();
+        }
+        let playing = self.last.is_some_and(|last| {
+            now > last && now - last <= MAX_PLAYBACK_STEP_SECS * self.fps as u64
+        });
+        let from = match self.last {
+            Some(last) if playing => last + 1,
+            _ => now,
+        };
+        self.last = Some(now);
+
+        self.triggers
+            .iter()
+            .filter(|(at, _)| (from..=now).contains(at))
+            .map(|(_, trigger)| trigger.action.clone())
+            .collect()
+    }
+}
+
+/// The first trigger after `now`, with the time until timecode reaches it
+pub fn next_trigger(
+    triggers: &[TimecodeTrigger],
+    fps: u32,
+    now: Timecode,
+) -> Option<(&TimecodeTrigger, Duration)> {
+    let fps = fps.max(1);
+    let now = now.to_frames(fps);
+    triggers
+        .iter()
+        .map(|t| (t.at.to_frames(fps), t))
+        .filter(|(at, _)| *at > now)
+        .min_by_key(|(at, _)| *at)
+        .map(|(at, trigger)| {
+            let frames = at - now;
+            (trigger, Duration::from_secs_f64(frames as f64 / fps as f64))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(at: &str, salvo: &str) -> TimecodeTrigger {
+        TimecodeTrigger {
+            at: at.parse().unwrap(),
+            action: CompanionAction::RecallSalvo {
+                salvo: salvo.to_string(),
+                policy: None,
+            },
+        }
+    }
+
+    fn fired(schedule: &mut TriggerSchedule, tc: &str) -> Vec<String> {
+        schedule
+            .advance(tc.parse().unwrap())
+            .into_iter()
+            .map(|action| match action {
+                CompanionAction::RecallSalvo { salvo, .. } => salvo,
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fires_when_passed() {
+        let mut schedule = TriggerSchedule::new(
+            &[
+                trigger("00:01:00:00", "Walk-in"),
+                trigger("00:01:00:01", "Band"),
+            ],
+            25,
+        );
+
+        assert!(fired(&mut schedule, "00:00:59:23").is_empty());
+        assert_eq!(fired(&mut schedule, "00:00:59:24"), Vec::<String>::new());
+        // A dropped frame still fires both triggers it skipped over
+        assert_eq!(fired(&mut schedule, "00:01:00:02"), vec!["Walk-in", "Band"]);
+        assert!(fired(&mut schedule, "00:01:00:03").is_empty());
+    }
+
+    #[test]
+    fn test_jumps_do_not_fire() {
+        let mut schedule = TriggerSchedule::new(&[trigger("00:01:00:00", "Walk-in")], 25);
+
+        assert!(fired(&mut schedule, "00:00:10:00").is_empty());
+        // Locating past the trigger skips it
+        assert!(fired(&mut schedule, "00:05:00:00").is_empty());
+        // Rewinding and playing through fires it again
+        assert!(fired(&mut schedule, "00:00:59:24").is_empty());
+        assert_eq!(fired(&mut schedule, "00:01:00:00"), vec!["Walk-in"]);
+        // Paused timecode repeating the same frame fires nothing more
+        assert!(fired(&mut schedule, "00:01:00:00").is_empty());
+        // Landing exactly on a trigger fires it
+        assert!(fired(&mut schedule, "00:00:00:00").is_empty());
+        assert_eq!(fired(&mut schedule, "00:01:00:00"), vec!["Walk-in"]);
+    }
+
+    #[test]
+    fn test_next_trigger() {
+        let triggers = [trigger("17:55:00:00", "PM"), trigger("09:00:00:00", "AM")];
+        let (next, countdown) =
+            next_trigger(&triggers, 25, "17:54:30:00".parse().unwrap()).unwrap();
+        assert_eq!(next.at.to_string(), "17:55:00:00");
+        assert_eq!(countdown, Duration::from_secs(30));
+
+        let (next, _) = next_trigger(&triggers, 25, "08:00:00:00".parse().unwrap()).unwrap();
+        assert_eq!(next.at.to_string(), "09:00:00:00");
+        assert!(next_trigger(&triggers, 25, "17:55:00:00".parse().unwrap()).is_none());
+    }
+}
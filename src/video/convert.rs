@@ -0,0 +1,308 @@
+//! Conversion of received pixel formats to packed RGBA
+
+// Only the NDI receive path converts frames
+#![cfg_attr(not(feature = "ndi"), allow(dead_code))]
+
+use super::VideoFrame;
+use anyhow::{bail, Result};
+
+/// Pixel layouts delivered by NDI receivers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba,
+    Rgbx,
+    Bgra,
+    Bgrx,
+    /// 4:2:2 YCbCr (BT.709, limited range)
+    Uyvy,
+}
+
+impl PixelFormat {
+    /// Map an NDI FourCC code to a pixel format
+    pub fn from_fourcc(fourcc: u32) -> Option<Self> {
+        match &fourcc.to_le_bytes() {
+            b"RGBA" => Some(Self::Rgba),
+            b"RGBX" => Some(Self::Rgbx),
+            b"BGRA" => Some(Self::Bgra),
+            b"BGRX" => Some(Self::Bgrx),
+            b"UYVY" => Some(Self::Uyvy),
+            _ => None,
+        }
+    }
+
+    /// Bytes used by one row of `width` pixels (without padding)
+    fn row_bytes(&self, width: usize) -> usize {
+        match self {
+            Self::Uyvy => width * 2,
+            _ => width * 4,
+        }
+    }
+}
+
+/// Frames with at least this many pixels are converted on several threads
+const PARALLEL_MIN_PIXELS: usize = 1280 * 720;
+
+/// Threads one frame is split across. Each receiver converts on its own capture
+/// thread already, so a 16-view multiview gains little from more
+const MAX_CONVERT_THREADS: usize = 4;
+
+/// Convert a frame with the given row stride into packed RGBA
+pub fn to_rgba(
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    stride: usize,
+    data: &[u8],
+) -> Result<VideoFrame> {
+    let (w, h) = (width as usize, height as usize);
+    let row_bytes = format.row_bytes(w);
+    if stride < row_bytes || (h > 0 && data.len() < stride * (h - 1) + row_bytes) {
+        bail!(
+            "Frame data too small for {}x{} {:?} (stride {}, {} bytes)",
+            width,
+            height,
+            format,
+            stride,
+            data.len()
+        );
+    }
+    if format == PixelFormat::Uyvy && w % 2 != 0 {
+        bail!("UYVY frames must have an even width");
+    }
+
+    let mut rgba = vec![0; w * h * 4];
+    let threads = convert_threads(w, h);
+    if threads > 1 {
+        // Bands of whole scanlines, one per thread
+        let band_rows = h.div_ceil(threads);
+        std::thread::scope(|scope| {
+            for (band, out) in rgba.chunks_mut(band_rows * w * 4).enumerate() {
+                let src = &data[band * band_rows * stride..];
+                scope.spawn(move || convert_rows(format, w, stride, src, out));
+            }
+        });
+    } else if h > 0 {
+        convert_rows(format, w, stride, data, &mut rgba);
+    }
+
+    Ok(VideoFrame::new(width, height, rgba))
+}
+
+/// Threads to convert a `w` x `h` frame on
+fn convert_threads(w: usize, h: usize) -> usize {
+    if w * h < PARALLEL_MIN_PIXELS {
+        return 1;
+    }
+    std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_CONVERT_THREADS)
+}
+
+/// Convert consecutive scanlines until `out` is full
+fn convert_rows(format: PixelFormat, w: usize, stride: usize, src: &[u8], out: &mut [u8]) {
+    let row_bytes = format.row_bytes(w);
+    for (y, out_row) in out.chunks_exact_mut(w * 4).enumerate() {
+        let row = &src[y * stride..y * stride + row_bytes];
+        match format {
+            PixelFormat::Rgba => out_row.copy_from_slice(row),
+            PixelFormat::Rgbx => {
+                for (px, out) in row.chunks_exact(4).zip(out_row.chunks_exact_mut(4)) {
+                    out.copy_from_slice(&[px[0], px[1], px[2], 255]);
+                }
+            }
+            PixelFormat::Bgra => {
+                for (px, out) in row.chunks_exact(4).zip(out_row.chunks_exact_mut(4)) {
+                    out.copy_from_slice(&[px[2], px[1], px[0], px[3]]);
+                }
+            }
+            PixelFormat::Bgrx => {
+                for (px, out) in row.chunks_exact(4).zip(out_row.chunks_exact_mut(4)) {
+                    out.copy_from_slice(&[px[2], px[1], px[0], 255]);
+                }
+            }
+            PixelFormat::Uyvy => uyvy_row(row, out_row),
+        }
+    }
+}
+
+// BT.709 limited-range YCbCr to RGB in 16-bit fixed point, as `(a << shift) * k >> 16`
+// with 5 fractional bits left. The scalar and SIMD paths compute identical results.
+const K_Y: i16 = 19071; // 1.164 << 14
+const K_RV: i16 = 29376; // 1.793 << 14
+const K_GU: i16 = 3490; // 0.213 << 14
+const K_GV: i16 = 8733; // 0.533 << 14
+const K_BU: i16 = 17302; // 2.112 << 13
+
+/// Convert one UYVY scanline, 8 pixels at a time with SIMD where available
+fn uyvy_row(row: &[u8], out: &mut [u8]) {
+    #[cfg(target_arch = "x86_64")]
+    let done = {
+        let blocks = row.len() / 16;
+        // SAFETY: SSE2 is part of the x86_64 baseline; uyvy_blocks stays within
+        // `blocks` 16-byte input and 32-byte output blocks
+        unsafe { sse2::uyvy_blocks(row.as_ptr(), out.as_mut_ptr(), blocks) };
+        blocks * 16
+    };
+    #[cfg(not(target_arch = "x86_64"))]
+    let done = 0;
+
+    for (px, out) in row[done..]
+        .chunks_exact(4)
+        .zip(out[done * 2..].chunks_exact_mut(8))
+    {
+        let (u, y0, v, y1) = (px[0], px[1], px[2], px[3]);
+        out[..4].copy_from_slice(&yuv_to_rgba(y0, u, v));
+        out[4..].copy_from_slice(&yuv_to_rgba(y1, u, v));
+    }
+}
+
+/// Fixed-point multiply keeping the high half, like `_mm_mulhi_epi16`
+fn mulhi(a: i16, b: i16) -> i16 {
+    ((a as i32 * b as i32) >> 16) as i16
+}
+
+/// BT.709 limited-range YCbCr to RGBA
+fn yuv_to_rgba(y: u8, u: u8, v: u8) -> [u8; 4] {
+    let y = mulhi((y as i16 - 16) << 7, K_Y);
+    let cb = u as i16 - 128;
+    let cr = v as i16 - 128;
+
+    let r = y + mulhi(cr << 7, K_RV);
+    let g = y - mulhi(cb << 7, K_GU) - mulhi(cr << 7, K_GV);
+    let b = y + mulhi(cb << 8, K_BU);
+
+    [clamp_u8(r), clamp_u8(g), clamp_u8(b), 255]
+}
+
+/// Round off the fractional bits and saturate
+fn clamp_u8(value: i16) -> u8 {
+    ((value + 16) >> 5).clamp(0, 255) as u8
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use super::{K_BU, K_GU, K_GV, K_RV, K_Y};
+    use std::arch::x86_64::*;
+
+    /// Convert `blocks` runs of 8 UYVY pixels (16 bytes) to RGBA (32 bytes)
+    ///
+    /// # Safety
+    /// `src` must be readable for `blocks * 16` bytes and `dst` writable for `blocks * 32`.
+    pub unsafe fn uyvy_blocks(src: *const u8, dst: *mut u8, blocks: usize) {
+        let low_bytes = _mm_set1_epi16(0x00ff);
+        let low_words = _mm_set1_epi32(0x0000_ffff);
+        let alpha = _mm_set1_epi8(-1);
+
+        for i in 0..blocks {
+            let uyvy = _mm_loadu_si128(src.add(i * 16) as *const __m128i);
+
+            // Y of each pixel, and U V pairs shared by two pixels
+            let y = _mm_sub_epi16(_mm_srli_epi16(uyvy, 8), _mm_set1_epi16(16));
+            let uv = _mm_and_si128(uyvy, low_bytes);
+            let u = _mm_and_si128(uv, low_words);
+            let u = _mm_or_si128(u, _mm_slli_epi32(u, 16));
+            let v = _mm_srli_epi32(uv, 16);
+            let v = _mm_or_si128(v, _mm_slli_epi32(v, 16));
+            let cb = _mm_sub_epi16(u, _mm_set1_epi16(128));
+            let cr = _mm_sub_epi16(v, _mm_set1_epi16(128));
+
+            let y = _mm_mulhi_epi16(_mm_slli_epi16(y, 7), _mm_set1_epi16(K_Y));
+            let cb7 = _mm_slli_epi16(cb, 7);
+            let cr7 = _mm_slli_epi16(cr, 7);
+            let r = _mm_add_epi16(y, _mm_mulhi_epi16(cr7, _mm_set1_epi16(K_RV)));
+            let g = _mm_sub_epi16(
+                _mm_sub_epi16(y, _mm_mulhi_epi16(cb7, _mm_set1_epi16(K_GU))),
+                _mm_mulhi_epi16(cr7, _mm_set1_epi16(K_GV)),
+            );
+            let b = _mm_add_epi16(
+                y,
+                _mm_mulhi_epi16(_mm_slli_epi16(cb, 8), _mm_set1_epi16(K_BU)),
+            );
+
+            // Round, saturate to bytes and interleave into RGBA
+            let round = |c| _mm_srai_epi16(_mm_add_epi16(c, _mm_set1_epi16(16)), 5);
+            let r = _mm_packus_epi16(round(r), round(r));
+            let g = _mm_packus_epi16(round(g), round(g));
+            let b = _mm_packus_epi16(round(b), round(b));
+            let rg = _mm_unpacklo_epi8(r, g);
+            let ba = _mm_unpacklo_epi8(b, alpha);
+            let out = dst.add(i * 32) as *mut __m128i;
+            _mm_storeu_si128(out, _mm_unpacklo_epi16(rg, ba));
+            _mm_storeu_si128(out.add(1), _mm_unpackhi_epi16(rg, ba));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fourcc() {
+        assert_eq!(
+            PixelFormat::from_fourcc(u32::from_le_bytes(*b"UYVY")),
+            Some(PixelFormat::Uyvy)
+        );
+        assert_eq!(PixelFormat::from_fourcc(u32::from_le_bytes(*b"P216")), None);
+    }
+
+    #[test]
+    fn test_bgrx_with_stride() {
+        // 1x2 frame, 8 bytes of padding per row
+        let data = [
+            1, 2, 3, 0, 0, 0, 0, 0, //
+            4, 5, 6, 0,
+        ];
+        let frame = to_rgba(PixelFormat::Bgrx, 1, 2, 8, &data).unwrap();
+        assert_eq!(frame.data, vec![3, 2, 1, 255, 6, 5, 4, 255]);
+    }
+
+    #[test]
+    fn test_uyvy_black_and_white() {
+        let data = [128, 16, 128, 235];
+        let frame = to_rgba(PixelFormat::Uyvy, 2, 1, 4, &data).unwrap();
+        assert_eq!(frame.data, vec![0, 0, 0, 255, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_uyvy_simd_matches_scalar() {
+        // Every byte value in every position, plus a tail that isn't a whole block
+        let row: Vec<u8> = (0..=255u8).chain(0..=255).chain(0..12).collect();
+        let mut out = vec![0; row.len() * 2];
+        uyvy_row(&row, &mut out);
+
+        let mut expected = Vec::new();
+        for px in row.chunks_exact(4) {
+            expected.extend_from_slice(&yuv_to_rgba(px[1], px[0], px[2]));
+            expected.extend_from_slice(&yuv_to_rgba(px[3], px[0], px[2]));
+        }
+        assert_eq!(out, expected);
+
+        // Within a step of the floating point conversion
+        for (px, rgba) in row.chunks_exact(4).zip(out.chunks_exact(8)) {
+            let y = 1.164 * (px[1] as f32 - 16.0);
+            let (cb, cr) = (px[0] as f32 - 128.0, px[2] as f32 - 128.0);
+            let reference = [y + 1.793 * cr, y - 0.213 * cb - 0.533 * cr, y + 2.112 * cb];
+            for (c, expected) in rgba.iter().zip(reference) {
+                assert!((*c as f32 - expected.round().clamp(0.0, 255.0)).abs() <= 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_conversion() {
+        // Large enough to be split across threads, with row padding
+        let (w, h, stride) = (1280, 720, 1280 * 2 + 64);
+        let data: Vec<u8> = (0..stride * h).map(|i| (i * 7 % 251) as u8).collect();
+        let frame = to_rgba(PixelFormat::Uyvy, w as u32, h as u32, stride, &data).unwrap();
+
+        let mut expected = vec![0; w * h * 4];
+        convert_rows(PixelFormat::Uyvy, w, stride, &data, &mut expected);
+        assert_eq!(frame.data, expected);
+    }
+
+    #[test]
+    fn test_short_data_rejected() {
+        assert!(to_rgba(PixelFormat::Rgba, 2, 2, 8, &[0; 12]).is_err());
+    }
+}
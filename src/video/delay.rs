@@ -0,0 +1,89 @@
+//! Fixed video delay, for lining up a source that arrives ahead of the others
+//! (e.g. a hardware encoder next to a software one) on the multiview
+
+use super::VideoFrame;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest delay offered, in frames. Every frame within the delay is held in
+/// memory, about 8 MB each at 1080p
+pub const MAX_DELAY_FRAMES: u32 = 30;
+
+/// Holds frames back by a fixed time
+pub struct FrameDelay {
+    delay: Duration,
+    /// Frames by when they arrived, oldest first
+    queue: VecDeque<(Instant, u64, Arc<VideoFrame>)>,
+}
+
+impl FrameDelay {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Delay of `frames` frames at a frame rate
+    pub fn frames(frames: u32, fps: f64) -> Duration {
+        Duration::from_secs_f64(frames as f64 / fps.max(1.0))
+    }
+
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// Take the newest frame of the source, returning the frame (and its
+    /// sequence) to show now. Until the delay has filled, the oldest frame held
+    /// is shown
+    pub fn push(
+        &mut self,
+        sequence: u64,
+        frame: &Arc<VideoFrame>,
+        now: Instant,
+    ) -> (u64, Arc<VideoFrame>) {
+        if self.queue.back().is_none_or(|(_, s, _)| *s != sequence) {
+            self.queue.push_back((now, sequence, Arc::clone(frame)));
+        }
+        // Drop frames once the next one is also due
+        while self
+            .queue
+            .get(1)
+            .is_some_and(|(at, _, _)| now.saturating_duration_since(*at) >= self.delay)
+        {
+            self.queue.pop_front();
+        }
+        let (_, sequence, frame) = &self.queue[0];
+        (*sequence, Arc::clone(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_delay() {
+        let start = Instant::now();
+        let frame = |shade: u8| Arc::new(VideoFrame::solid(2, 2, [shade, shade, shade, 255]));
+        let frames: Vec<Arc<VideoFrame>> = (0..10).map(frame).collect();
+        assert_eq!(FrameDelay::frames(3, 50.0), Duration::from_millis(60));
+
+        let mut delay = FrameDelay::new(FrameDelay::frames(3, 50.0));
+        let mut shown = Vec::new();
+        for (i, frame) in frames.iter().enumerate() {
+            let now = start + Duration::from_millis(20) * i as u32;
+            // Drawn twice per frame
+            delay.push(i as u64, frame, now);
+            let (sequence, _) = delay.push(i as u64, frame, now + Duration::from_millis(10));
+            shown.push(sequence);
+        }
+        assert_eq!(shown, [0, 0, 0, 0, 1, 2, 3, 4, 5, 6]);
+
+        // A shorter delay catches up at once
+        delay.set_delay(Duration::ZERO);
+        let now = start + Duration::from_millis(200);
+        assert_eq!(delay.push(9, &frames[9], now).0, 9);
+    }
+}
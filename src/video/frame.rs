@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+
+/// A video frame in packed 8-bit RGBA
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFrame {
+    pub width: u32,
+    pub height: u32,
+    /// Pixel data, `width * height * 4` bytes
+    pub data: Vec<u8>,
+}
+
+impl VideoFrame {
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Self {
+        debug_assert_eq!(data.len(), (width * height * 4) as usize);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Convert to an image, e.g. for saving as PNG
+    pub fn to_image(&self) -> Result<image::RgbaImage> {
+        image::RgbaImage::from_raw(self.width, self.height, self.data.clone())
+            .context("Frame data does not match its dimensions")
+    }
+
+    /// Create a frame filled with a single color
+    pub fn solid(width: u32, height: u32, rgba: [u8; 4]) -> Self {
+        let data = rgba
+            .iter()
+            .copied()
+            .cycle()
+            .take((width * height * 4) as usize)
+            .collect();
+        Self::new(width, height, data)
+    }
+
+    /// Copy a rectangular region out of the frame, clamped to the frame bounds
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> VideoFrame {
+        let x = x.min(self.width);
+        let y = y.min(self.height);
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for row in y..y + height {
+            let start = ((row * self.width + x) * 4) as usize;
+            let end = start + (width * 4) as usize;
+            data.extend_from_slice(&self.data[start..end]);
+        }
+
+        VideoFrame::new(width, height, data)
+    }
+
+    /// Scale down (never up) to at most `max_height` lines, keeping the aspect ratio.
+    /// Each output pixel averages the block of source pixels it covers.
+    pub fn downscale(&self, max_height: u32) -> VideoFrame {
+        if max_height == 0 || self.height <= max_height {
+            return self.clone();
+        }
+        let height = max_height;
+        let width = ((self.width as u64 * height as u64 / self.height as u64) as u32).max(1);
+
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            let y0 = y * self.height / height;
+            let y1 = ((y + 1) * self.height / height).max(y0 + 1);
+            for x in 0..width {
+                let x0 = x * self.width / width;
+                let x1 = ((x + 1) * self.width / width).max(x0 + 1);
+                let mut sum = [0u32; 4];
+                for row in y0..y1 {
+                    for col in x0..x1 {
+                        let i = ((row * self.width + col) * 4) as usize;
+                        for (total, value) in sum.iter_mut().zip(&self.data[i..i + 4]) {
+                            *total += *value as u32;
+                        }
+                    }
+                }
+                let count = (y1 - y0) * (x1 - x0);
+                data.extend(sum.iter().map(|total| (total / count) as u8));
+            }
+        }
+
+        VideoFrame::new(width, height, data)
+    }
+
+    /// Mix with another frame of the same size: `weight` 0 is this frame, 1 the
+    /// other. A frame of another size is taken as it is
+    pub fn blend(&self, other: &VideoFrame, weight: f32) -> VideoFrame {
+        if (self.width, self.height) != (other.width, other.height) {
+            return other.clone();
+        }
+        let weight = weight.clamp(0.0, 1.0);
+        let data = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| (*a as f32 + (*b as f32 - *a as f32) * weight).round() as u8)
+            .collect();
+        VideoFrame::new(self.width, self.height, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crop() {
+        // 4x2 frame where each pixel's red channel is its index
+        let data: Vec<u8> = (0..8u8).flat_map(|i| [i, 0, 0, 255]).collect();
+        let frame = VideoFrame::new(4, 2, data);
+
+        let cropped = frame.crop(1, 0, 2, 2);
+        assert_eq!(cropped.width, 2);
+        assert_eq!(cropped.height, 2);
+        let reds: Vec<u8> = cropped.data.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![1, 2, 5, 6]);
+    }
+
+    #[test]
+    fn test_downscale() {
+        // Left half black, right half white
+        let data: Vec<u8> = (0..16u32)
+            .flat_map(|i| if i % 4 < 2 { [0, 0, 0, 255] } else { [255; 4] })
+            .collect();
+        let frame = VideoFrame::new(4, 4, data);
+
+        let scaled = frame.downscale(2);
+        assert_eq!((scaled.width, scaled.height), (2, 2));
+        assert_eq!(&scaled.data[..8], &[0, 0, 0, 255, 255, 255, 255, 255]);
+
+        // Never scaled up
+        assert_eq!(frame.downscale(8), frame);
+    }
+
+    #[test]
+    fn test_blend() {
+        let black = VideoFrame::solid(2, 2, [0, 0, 0, 255]);
+        let white = VideoFrame::solid(2, 2, [255; 4]);
+        assert_eq!(black.blend(&white, 0.0), black);
+        assert_eq!(&black.blend(&white, 0.5).data[..4], &[128, 128, 128, 255]);
+        let small = VideoFrame::solid(1, 1, [255; 4]);
+        assert_eq!(black.blend(&small, 0.5), small);
+    }
+
+    #[test]
+    fn test_crop_clamped() {
+        let frame = VideoFrame::solid(4, 4, [0, 0, 0, 255]);
+        let cropped = frame.crop(3, 3, 10, 10);
+        assert_eq!((cropped.width, cropped.height), (1, 1));
+    }
+}
@@ -0,0 +1,212 @@
+//! Frame rate conversion for senders with a fixed output rate: frames are sent
+//! on the output's own cadence, whatever rate the source arrives at
+
+use super::VideoFrame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A frame rate as a fraction (30000/1001 for 29.97)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FrameRate {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl FrameRate {
+    /// Time between frames
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.denominator as u64) / self.numerator
+    }
+}
+
+impl fmt::Display for FrameRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.numerator, self.denominator) {
+            (n, 1) => write!(f, "{}", n),
+            (n, 1001) => write!(f, "{:.2}", n as f64 / 1001.0),
+            (n, d) => write!(f, "{}/{}", n, d),
+        }
+    }
+}
+
+impl FromStr for FrameRate {
+    type Err = String;
+
+    /// Parse "25", "29.97", "59.94" (NTSC rates) or a fraction such as "30000/1001"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid frame rate '{}' (e.g. \"25\" or \"29.97\")", s);
+        let s = s.trim();
+        let (numerator, denominator) = match s.split_once('/') {
+            Some((n, d)) => (
+                n.trim().parse().map_err(|_| invalid())?,
+                d.trim().parse().map_err(|_| invalid())?,
+            ),
+            None => match s.parse::<u32>() {
+                Ok(n) => (n, 1),
+                Err(_) => {
+                    let fps: f64 = s.parse().map_err(|_| invalid())?;
+                    // NTSC rates are a whole rate slowed by 1000/1001
+                    let whole = (fps * 1001.0 / 1000.0).round();
+                    if (fps - whole * 1000.0 / 1001.0).abs() > 0.01 {
+                        return Err(invalid());
+                    }
+                    (whole as u32 * 1000, 1001)
+                }
+            },
+        };
+        if numerator == 0 || denominator == 0 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            numerator,
+            denominator,
+        })
+    }
+}
+
+impl TryFrom<String> for FrameRate {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<FrameRate> for String {
+    fn from(rate: FrameRate) -> Self {
+        rate.to_string()
+    }
+}
+
+/// How frames are made for an output rate the source doesn't arrive at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateConversion {
+    /// Send the newest frame on each tick, dropping or repeating frames
+    #[default]
+    Drop,
+    /// Mix the two newest frames by where the tick falls between them, one
+    /// source frame late, for smoother motion
+    Blend,
+}
+
+/// Turns frames arriving at any rate into frames at a fixed rate
+pub struct FrameRateConverter {
+    rate: FrameRate,
+    conversion: RateConversion,
+    /// When the next frame is due
+    next: Option<Instant>,
+    previous: Option<(Instant, Arc<VideoFrame>)>,
+    latest: Option<(Instant, Arc<VideoFrame>)>,
+}
+
+impl FrameRateConverter {
+    pub fn new(rate: FrameRate, conversion: RateConversion) -> Self {
+        Self {
+            rate,
+            conversion,
+            next: None,
+            previous: None,
+            latest: None,
+        }
+    }
+
+    pub fn rate(&self) -> FrameRate {
+        self.rate
+    }
+
+    /// A frame arrived from the source
+    pub fn push(&mut self, frame: Arc<VideoFrame>, arrived: Instant) {
+        self.previous = self.latest.replace((arrived, frame));
+    }
+
+    /// The frame to send, once the next tick is due
+    pub fn poll(&mut self, now: Instant) -> Option<Arc<VideoFrame>> {
+        let (latest_at, latest) = self.latest.as_ref()?;
+        let interval = self.rate.interval();
+        let tick = match self.next {
+            Some(next) if now < next => return None,
+            // Keep the cadence, unless sending stalled for longer than a frame
+            Some(next) if now < next + interval => next,
+            _ => now,
+        };
+        self.next = Some(tick + interval);
+
+        match (&self.previous, self.conversion) {
+            (Some((previous_at, previous)), RateConversion::Blend) => {
+                let gap = latest_at.saturating_duration_since(*previous_at);
+                if gap.is_zero() {
+                    return Some(Arc::clone(latest));
+                }
+                let weight =
+                    tick.saturating_duration_since(*latest_at).as_secs_f32() / gap.as_secs_f32();
+                Some(Arc::new(previous.blend(latest, weight)))
+            }
+            _ => Some(Arc::clone(latest)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_rate() {
+        let ntsc: FrameRate = "29.97".parse().unwrap();
+        assert_eq!((ntsc.numerator, ntsc.denominator), (30000, 1001));
+        assert_eq!(ntsc.to_string(), "29.97");
+        assert_eq!("59.94".parse::<FrameRate>().unwrap().numerator, 60000);
+        assert_eq!("25".parse::<FrameRate>().unwrap().to_string(), "25");
+        assert_eq!(
+            "30000/1001".parse::<FrameRate>().unwrap().interval(),
+            ntsc.interval()
+        );
+        assert!("27.3".parse::<FrameRate>().is_err());
+        assert!("0".parse::<FrameRate>().is_err());
+    }
+
+    #[test]
+    fn test_frame_rate_conversion() {
+        let start = Instant::now();
+        let frame = |shade: u8| Arc::new(VideoFrame::solid(2, 2, [shade, shade, shade, 255]));
+
+        // 50 -> 25 sends every other frame
+        let mut converter = FrameRateConverter::new("25".parse().unwrap(), RateConversion::Drop);
+        assert!(converter.poll(start).is_none());
+        let mut sent = Vec::new();
+        for i in 0..10u8 {
+            let now = start + Duration::from_millis(20) * i as u32;
+            converter.push(frame(i), now);
+            if let Some(out) = converter.poll(now) {
+                sent.push(out.data[0]);
+            }
+        }
+        assert_eq!(sent, [0, 2, 4, 6, 8]);
+
+        // 25 -> 50 repeats each frame
+        let mut converter = FrameRateConverter::new("50".parse().unwrap(), RateConversion::Drop);
+        let mut sent = Vec::new();
+        for i in 0..8u32 {
+            let now = start + Duration::from_millis(20) * i;
+            if i % 2 == 0 {
+                converter.push(frame(i as u8), now);
+            }
+            if let Some(out) = converter.poll(now) {
+                sent.push(out.data[0]);
+            }
+        }
+        assert_eq!(sent, [0, 0, 2, 2, 4, 4, 6, 6]);
+
+        // Blending halfway between two frames
+        let mut converter = FrameRateConverter::new("50".parse().unwrap(), RateConversion::Blend);
+        converter.push(frame(0), start);
+        converter.push(frame(200), start + Duration::from_millis(40));
+        let out = converter.poll(start + Duration::from_millis(60)).unwrap();
+        assert_eq!(out.data[0], 100);
+    }
+}
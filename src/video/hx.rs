@@ -0,0 +1,380 @@
+//! NDI|HX (H.264/HEVC) decoding through an `ffmpeg` process (requires the `hx` feature)
+//!
+//! Compressed frames arrive as an NDI compressed packet: a small header followed
+//! by the Annex B bitstream and, on keyframes, the codec's extra data (SPS/PPS).
+//! ffmpeg decodes on the GPU where one is available (see [`HwDecoder`]).
+
+// Only the HX receive path decodes frames
+#![cfg_attr(not(feature = "hx"), allow(dead_code))]
+
+use super::VideoFrame;
+use anyhow::{bail, Context, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::mem::{offset_of, size_of};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::OnceLock;
+use std::thread;
+
+/// Header of a compressed frame: `NDIlib_compressed_packet_t` from the NDI Advanced SDK.
+/// The SDK packs it to 4 bytes, so `version_0` (the header size) is 44
+#[repr(C, packed(4))]
+#[allow(dead_code)]
+struct PacketHeader {
+    version: u32,
+    fourcc: u32,
+    pts: i64,
+    dts: i64,
+    reserved: u64,
+    flags: u32,
+    data_size: u32,
+    extra_data_size: u32,
+}
+
+/// `NDIlib_compressed_packet_t::version_0`
+const PACKET_HEADER_SIZE: usize = size_of::<PacketHeader>();
+
+/// `NDIlib_compressed_packet_flags_keyframe`
+const FLAG_KEYFRAME: u32 = 1;
+
+/// Decoded frames kept waiting for the receiver before the oldest are dropped
+const MAX_DECODED_FRAMES: usize = 2;
+
+/// Present when an NVIDIA driver is loaded (Linux)
+const NVIDIA_DEVICE: &str = "/dev/nvidia0";
+
+/// First DRM render node, used for VAAPI (Linux)
+const VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+/// Decoder chosen in configuration, set once at startup
+static DECODER: OnceLock<HwDecoder> = OnceLock::new();
+
+/// How NDI|HX video is decoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HwDecoder {
+    /// Pick the platform's hardware decoder if one is present, else software
+    #[default]
+    Auto,
+    /// Decode on the CPU
+    Software,
+    /// NVIDIA GPUs
+    Nvdec,
+    /// Intel and AMD GPUs on Linux
+    Vaapi,
+    /// macOS
+    VideoToolbox,
+}
+
+impl HwDecoder {
+    /// Use this decoder for every NDI|HX stream from now on
+    pub fn configure(self) {
+        if DECODER.set(self).is_err() {
+            warn!("NDI|HX decoder already configured");
+        }
+    }
+
+    /// The configured decoder, with `Auto` resolved for this machine
+    fn current() -> Self {
+        static RESOLVED: OnceLock<HwDecoder> = OnceLock::new();
+        *RESOLVED.get_or_init(|| {
+            let decoder = DECODER
+                .get()
+                .copied()
+                .unwrap_or_default()
+                .resolve(std::env::consts::OS, |path| Path::new(path).exists());
+            info!("Decoding NDI|HX with {:?}", decoder);
+            decoder
+        })
+    }
+
+    /// Resolve `Auto` from the OS and the devices present
+    fn resolve(self, os: &str, exists: impl Fn(&str) -> bool) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        match os {
+            "macos" => Self::VideoToolbox,
+            "linux" if exists(NVIDIA_DEVICE) => Self::Nvdec,
+            "linux" if exists(VAAPI_DEVICE) => Self::Vaapi,
+            _ => Self::Software,
+        }
+    }
+
+    /// ffmpeg input options selecting the decoder. Decoded frames are copied back
+    /// to memory for conversion, and ffmpeg decodes in software if the device fails
+    fn ffmpeg_args(&self) -> Vec<&'static str> {
+        match self {
+            Self::Auto | Self::Software => vec![],
+            Self::Nvdec => vec!["-hwaccel", "cuda"],
+            Self::Vaapi => vec!["-hwaccel", "vaapi", "-hwaccel_device", VAAPI_DEVICE],
+            Self::VideoToolbox => vec!["-hwaccel", "videotoolbox"],
+        }
+    }
+}
+
+/// Compressed video codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    H264,
+    Hevc,
+}
+
+impl Codec {
+    /// Map an NDI FourCC code to a codec
+    pub fn from_fourcc(fourcc: u32) -> Option<Self> {
+        match &fourcc.to_le_bytes() {
+            b"H264" | b"h264" => Some(Self::H264),
+            b"HEVC" | b"hevc" => Some(Self::Hevc),
+            _ => None,
+        }
+    }
+
+    /// ffmpeg input format name
+    fn ffmpeg_format(&self) -> &'static str {
+        match self {
+            Self::H264 => "h264",
+            Self::Hevc => "hevc",
+        }
+    }
+}
+
+/// One compressed frame
+#[derive(Debug, PartialEq)]
+pub struct CompressedPacket<'a> {
+    pub codec: Codec,
+    pub keyframe: bool,
+    pub data: &'a [u8],
+    /// Codec configuration (SPS/PPS), sent with keyframes
+    pub extra_data: &'a [u8],
+}
+
+/// Parse an `NDIlib_compressed_packet_t` and its payload
+pub fn parse_packet(bytes: &[u8]) -> Result<CompressedPacket<'_>> {
+    if bytes.len() < PACKET_HEADER_SIZE {
+        bail!("Compressed packet too short ({} bytes)", bytes.len());
+    }
+    let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+    let header_size = u32_at(offset_of!(PacketHeader, version)) as usize;
+    let fourcc = offset_of!(PacketHeader, fourcc);
+    let codec = Codec::from_fourcc(u32_at(fourcc)).with_context(|| {
+        format!(
+            "Unsupported compressed codec {:?}",
+            &bytes[fourcc..fourcc + 4]
+        )
+    })?;
+    let flags = u32_at(offset_of!(PacketHeader, flags));
+    let data_size = u32_at(offset_of!(PacketHeader, data_size)) as usize;
+    let extra_size = u32_at(offset_of!(PacketHeader, extra_data_size)) as usize;
+
+    let data_start = header_size.max(PACKET_HEADER_SIZE);
+    let extra_start = data_start + data_size;
+    if bytes.len() < extra_start + extra_size {
+        bail!(
+            "Compressed packet truncated ({} bytes, expected {})",
+            bytes.len(),
+            extra_start + extra_size
+        );
+    }
+
+    Ok(CompressedPacket {
+        codec,
+        keyframe: flags & FLAG_KEYFRAME != 0,
+        data: &bytes[data_start..extra_start],
+        extra_data: &bytes[extra_start..extra_start + extra_size],
+    })
+}
+
+/// Decodes a compressed stream to RGBA frames with an `ffmpeg` child process
+pub struct HxDecoder {
+    codec: Codec,
+    width: u32,
+    height: u32,
+    process: Child,
+    stdin: ChildStdin,
+    frames: Receiver<VideoFrame>,
+    /// Frames are dropped until the first keyframe (the decoder can't start mid-GOP)
+    started: bool,
+}
+
+impl HxDecoder {
+    /// Start a decoder for a stream of the given codec and size
+    pub fn start(codec: Codec, width: u32, height: u32) -> Result<Self> {
+        let decoder = HwDecoder::current();
+        debug!(
+            "Starting {:?} decoder ({:?}) for {}x{}",
+            codec, decoder, width, height
+        );
+        let mut process = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error"])
+            .args(decoder.ffmpeg_args())
+            .args(["-f", codec.ffmpeg_format()])
+            .args(["-i", "pipe:0", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height), "pipe:1"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .context("Failed to start ffmpeg for NDI|HX decoding (is it on the PATH?)")?;
+
+        let stdin = process.stdin.take().context("ffmpeg stdin unavailable")?;
+        let mut stdout = process.stdout.take().context("ffmpeg stdout unavailable")?;
+        let (tx, frames) = mpsc::sync_channel(MAX_DECODED_FRAMES);
+
+        thread::spawn(move || {
+            let mut buffer = vec![0u8; (width * height * 4) as usize];
+            while stdout.read_exact(&mut buffer).is_ok() {
+                let frame = VideoFrame::new(width, height, buffer.clone());
+                // A full queue means the receiver is behind; drop the frame
+                if let Err(mpsc::TrySendError::Disconnected(_)) = tx.try_send(frame) {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            codec,
+            width,
+            height,
+            process,
+            stdin,
+            frames,
+            started: false,
+        })
+    }
+
+    /// Whether this decoder can take a stream of the given codec and size
+    pub fn matches(&self, codec: Codec, width: u32, height: u32) -> bool {
+        self.codec == codec && self.width == width && self.height == height
+    }
+
+    /// Feed a compressed packet, returning the newest frame decoded so far
+    pub fn decode(&mut self, packet: &CompressedPacket) -> Result<Option<VideoFrame>> {
+        if !self.started && !packet.keyframe {
+            return Ok(None);
+        }
+        self.started = true;
+
+        if packet.keyframe {
+            self.stdin.write_all(packet.extra_data)?;
+        }
+        self.stdin
+            .write_all(packet.data)
+            .context("ffmpeg stopped accepting data")?;
+
+        Ok(self.frames.try_iter().last())
+    }
+}
+
+impl Drop for HxDecoder {
+    fn drop(&mut self) {
+        if let Err(e) = self.process.kill() {
+            warn!("Failed to stop ffmpeg: {}", e);
+        }
+        let _ = self.process.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A packet as the SDK lays it out in memory
+    fn packet(fourcc: &[u8; 4], flags: u32, data: &[u8], extra: &[u8]) -> Vec<u8> {
+        let header = PacketHeader {
+            version: PACKET_HEADER_SIZE as u32,
+            fourcc: u32::from_le_bytes(*fourcc),
+            pts: 0,
+            dts: 0,
+            reserved: 0,
+            flags,
+            data_size: data.len() as u32,
+            extra_data_size: extra.len() as u32,
+        };
+        // SAFETY: the header is plain integers, read as bytes for its size only
+        let mut bytes = unsafe {
+            std::slice::from_raw_parts(
+                (&header as *const PacketHeader).cast::<u8>(),
+                PACKET_HEADER_SIZE,
+            )
+        }
+        .to_vec();
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(extra);
+        bytes
+    }
+
+    #[test]
+    fn test_packet_layout() {
+        // Offsets of NDIlib_compressed_packet_t in the NDI Advanced SDK
+        assert_eq!(PACKET_HEADER_SIZE, 44);
+        assert_eq!(offset_of!(PacketHeader, reserved), 24);
+        assert_eq!(offset_of!(PacketHeader, flags), 32);
+        assert_eq!(offset_of!(PacketHeader, data_size), 36);
+        assert_eq!(offset_of!(PacketHeader, extra_data_size), 40);
+    }
+
+    #[test]
+    fn test_parse_packet() {
+        let bytes = packet(
+            b"H264",
+            FLAG_KEYFRAME,
+            &[0, 0, 0, 1, 0x65],
+            &[0, 0, 0, 1, 0x67],
+        );
+        let parsed = parse_packet(&bytes).unwrap();
+        assert_eq!(parsed.codec, Codec::H264);
+        assert!(parsed.keyframe);
+        assert_eq!(parsed.data, &[0, 0, 0, 1, 0x65]);
+        assert_eq!(parsed.extra_data, &[0, 0, 0, 1, 0x67]);
+
+        let bytes = packet(b"HEVC", 0, &[1, 2, 3], &[]);
+        let parsed = parse_packet(&bytes).unwrap();
+        assert_eq!(parsed.codec, Codec::Hevc);
+        assert!(!parsed.keyframe);
+    }
+
+    #[test]
+    fn test_decoder_selection() {
+        let none = |_: &str| false;
+        let nvidia = |path: &str| path == NVIDIA_DEVICE;
+        let intel = |path: &str| path == VAAPI_DEVICE;
+
+        assert_eq!(
+            HwDecoder::Auto.resolve("macos", none),
+            HwDecoder::VideoToolbox
+        );
+        assert_eq!(HwDecoder::Auto.resolve("linux", nvidia), HwDecoder::Nvdec);
+        assert_eq!(HwDecoder::Auto.resolve("linux", intel), HwDecoder::Vaapi);
+        assert_eq!(HwDecoder::Auto.resolve("linux", none), HwDecoder::Software);
+        assert_eq!(
+            HwDecoder::Auto.resolve("windows", none),
+            HwDecoder::Software
+        );
+
+        // An explicit choice is kept
+        assert_eq!(
+            HwDecoder::Software.resolve("macos", none),
+            HwDecoder::Software
+        );
+        assert_eq!(HwDecoder::Vaapi.resolve("linux", nvidia), HwDecoder::Vaapi);
+        assert_eq!(
+            HwDecoder::Vaapi.ffmpeg_args(),
+            vec!["-hwaccel", "vaapi", "-hwaccel_device", VAAPI_DEVICE]
+        );
+    }
+
+    #[test]
+    fn test_invalid_packets() {
+        assert!(parse_packet(&[0; 10]).is_err());
+        assert!(parse_packet(&packet(b"SHQ2", 0, &[1], &[])).is_err());
+
+        let mut truncated = packet(b"H264", 0, &[1, 2, 3, 4], &[]);
+        truncated.truncate(truncated.len() - 2);
+        assert!(parse_packet(&truncated).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Output identification burned into sent frames: the output's number and name
+//! in block letters big enough to read on the screen it feeds
+
+use super::VideoFrame;
+
+const NUMBER_COLOR: [u8; 4] = [255, 255, 0, 255];
+const NAME_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// Glyph cell of the built-in font, in font pixels
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Darken the frame and draw the output number and name over it, laid out like
+/// the identification shown on the GUI's slots
+pub fn draw_identification(frame: &mut VideoFrame, number: usize, output: &str) {
+    for pixel in frame.data.chunks_exact_mut(4) {
+        for value in &mut pixel[..3] {
+            *value = (*value as u32 * 55 / 255) as u8;
+        }
+    }
+    let height = frame.height as f32;
+    let center_x = frame.width / 2;
+    draw_text(
+        frame,
+        &number.to_string(),
+        (center_x, (height * 0.38) as u32),
+        (height * 0.5) as u32,
+        NUMBER_COLOR,
+    );
+    draw_text(
+        frame,
+        output,
+        (center_x, (height * 0.78) as u32),
+        ((height * 0.12) as u32).max(GLYPH_HEIGHT),
+        NAME_COLOR,
+    );
+}
+
+/// Draw one line of text centered on `center`, `height` pixels tall, shrunk to
+/// fit the frame's width
+fn draw_text(frame: &mut VideoFrame, text: &str, center: (u32, u32), height: u32, color: [u8; 4]) {
+    let glyphs: Vec<[u8; 7]> = text.chars().map(glyph).collect();
+    if glyphs.is_empty() {
+        return;
+    }
+    // Glyphs are one font pixel apart
+    let advance = GLYPH_WIDTH + 1;
+    let line_width = glyphs.len() as u32 * advance - 1;
+    let scale = (height / GLYPH_HEIGHT)
+        .min(frame.width * 9 / 10 / line_width)
+        .max(1);
+
+    let left = center.0 as i64 - (line_width * scale / 2) as i64;
+    let top = center.1 as i64 - (GLYPH_HEIGHT * scale / 2) as i64;
+    for (index, rows) in glyphs.iter().enumerate() {
+        let glyph_left = left + (index as u32 * advance * scale) as i64;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (0x10 >> col) != 0 {
+                    fill(
+                        frame,
+                        glyph_left + (col * scale) as i64,
+                        top + (row as u32 * scale) as i64,
+                        scale,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Fill a `size` square, clipped to the frame
+fn fill(frame: &mut VideoFrame, x: i64, y: i64, size: u32, color: [u8; 4]) {
+    let x0 = x.clamp(0, frame.width as i64) as u32;
+    let y0 = y.clamp(0, frame.height as i64) as u32;
+    let x1 = (x + size as i64).clamp(0, frame.width as i64) as u32;
+    let y1 = (y + size as i64).clamp(0, frame.height as i64) as u32;
+    for row in y0..y1 {
+        let start = ((row * frame.width + x0) * 4) as usize;
+        let end = ((row * frame.width + x1) * 4) as usize;
+        for pixel in frame.data[start..end].chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+}
+
+/// Rows of a 5x7 glyph, most significant of the low five bits on the left.
+/// Letters are drawn in capitals; characters without a glyph as `?`
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00; 7],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '&' => [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_identification() {
+        let mut frame = VideoFrame::solid(320, 180, [255, 255, 255, 255]);
+        draw_identification(&mut frame, 1, "Lobby");
+
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * frame.width + x) * 4) as usize;
+            [frame.data[i], frame.data[i + 1], frame.data[i + 2]]
+        };
+        // Darkened outside the text
+        assert_eq!(pixel(2, 2), [55, 55, 55]);
+        // The stem of the "1" runs through the middle of the number line
+        assert_eq!(pixel(160, 68), [255, 255, 0]);
+        assert!(frame
+            .data
+            .chunks_exact(4)
+            .skip((140 * 320) as usize)
+            .take(320)
+            .any(|pixel| pixel[..3] == [255, 255, 255]));
+    }
+
+    #[test]
+    fn test_long_names_fit() {
+        let mut frame = VideoFrame::solid(64, 64, [0, 0, 0, 255]);
+        draw_identification(&mut frame, 12, &"Overflow Room ".repeat(8));
+        // Clipped to the frame, away from the corners
+        assert_eq!(frame.data.len(), 64 * 64 * 4);
+        assert_eq!(frame.data[..4], [0, 0, 0, 255]);
+    }
+}
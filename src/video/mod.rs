@@ -0,0 +1,12 @@
+//! Video frame types shared by receivers, recording and output paths
+
+pub mod convert;
+pub mod delay;
+pub mod frame;
+pub mod framerate;
+pub mod hx;
+pub mod identify;
+pub mod screen;
+pub mod transition;
+
+pub use frame::VideoFrame;
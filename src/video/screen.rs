@@ -0,0 +1,228 @@
+//! Local screen capture: grabs the whole desktop, one display or one window so it
+//! can be published as an NDI source
+
+use super::VideoFrame;
+use anyhow::Result;
+use std::fmt;
+
+/// Part of the local screen to capture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreenTarget {
+    /// Every display, as laid out on the desktop
+    Desktop,
+    /// One display, by connector, model or EDID id (as listed by `rustv displays`)
+    Display(String),
+    /// The first window whose title contains this text (case-insensitive)
+    Window(String),
+}
+
+impl fmt::Display for ScreenTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenTarget::Desktop => write!(f, "desktop"),
+            ScreenTarget::Display(name) => write!(f, "display {}", name),
+            ScreenTarget::Window(title) => write!(f, "window \"{}\"", title),
+        }
+    }
+}
+
+/// Whether a window title matches the text a window target looks for
+pub fn title_matches(title: &str, wanted: &str) -> bool {
+    title.to_lowercase().contains(&wanted.to_lowercase())
+}
+
+/// Convert 32-bit BGRX pixels (X11 ZPixmap on little-endian servers), with rows
+/// `stride` bytes apart, to an opaque RGBA frame
+pub fn bgrx_to_frame(data: &[u8], width: u32, height: u32, stride: usize) -> Result<VideoFrame> {
+    let row_len = width as usize * 4;
+    if stride < row_len || data.len() < stride * (height as usize).saturating_sub(1) + row_len {
+        anyhow::bail!(
+            "Captured image is too small for {}x{} ({} bytes)",
+            width,
+            height,
+            data.len()
+        );
+    }
+    let mut rgba = Vec::with_capacity(row_len * height as usize);
+    for row in data.chunks(stride).take(height as usize) {
+        for pixel in row[..row_len].chunks_exact(4) {
+            rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+        }
+    }
+    Ok(VideoFrame::new(width, height, rgba))
+}
+
+/// Grabs frames of a screen target from the X server
+#[cfg(target_os = "linux")]
+pub struct ScreenGrabber {
+    conn: x11rb::rust_connection::RustConnection,
+    /// Drawable the image is read from (the root window, or the captured window)
+    drawable: u32,
+    /// Area of the drawable to capture; `None` follows the drawable's current size
+    area: Option<(i16, i16, u16, u16)>,
+}
+
+#[cfg(target_os = "linux")]
+impl ScreenGrabber {
+    /// Connect to the X server and find the target
+    pub fn open(target: &ScreenTarget) -> Result<Self> {
+        use anyhow::Context;
+        use x11rb::connection::Connection;
+
+        let (conn, screen) = x11rb::connect(None).context("Failed to connect to the X server")?;
+        let root = conn.setup().roots[screen].root;
+
+        let (drawable, area) = match target {
+            ScreenTarget::Desktop => (root, None),
+            ScreenTarget::Display(name) => {
+                let displays = crate::gui::displays::probe()?;
+                let display = displays
+                    .iter()
+                    .find(|d| d.matches(name))
+                    .with_context(|| format!("Display '{}' is not connected", name))?;
+                let area = (
+                    display.x as i16,
+                    display.y as i16,
+                    display.width as u16,
+                    display.height as u16,
+                );
+                (root, Some(area))
+            }
+            ScreenTarget::Window(title) => (find_window(&conn, root, title)?, None),
+        };
+        Ok(Self {
+            conn,
+            drawable,
+            area,
+        })
+    }
+
+    /// Capture the current picture
+    pub fn grab(&mut self) -> Result<VideoFrame> {
+        use anyhow::Context;
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::{ConnectionExt as _, ImageFormat};
+
+        let (x, y, width, height) = match self.area {
+            Some(area) => area,
+            None => {
+                // Windows can be resized between frames
+                let geometry = self
+                    .conn
+                    .get_geometry(self.drawable)?
+                    .reply()
+                    .context("Captured window has closed")?;
+                (0, 0, geometry.width, geometry.height)
+            }
+        };
+        let image = self
+            .conn
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                self.drawable,
+                x,
+                y,
+                width,
+                height,
+                !0,
+            )?
+            .reply()
+            .context("Failed to capture the screen")?;
+
+        let bits_per_pixel = self
+            .conn
+            .setup()
+            .pixmap_formats
+            .iter()
+            .find(|f| f.depth == image.depth)
+            .map(|f| f.bits_per_pixel);
+        if bits_per_pixel != Some(32) {
+            anyhow::bail!("Unsupported screen depth {}", image.depth);
+        }
+        let stride = image.data.len() / height.max(1) as usize;
+        bgrx_to_frame(&image.data, width.into(), height.into(), stride)
+    }
+}
+
+/// Find a top-level window by title, using the window manager's client list
+#[cfg(target_os = "linux")]
+fn find_window(
+    conn: &x11rb::rust_connection::RustConnection,
+    root: u32,
+    title: &str,
+) -> Result<u32> {
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
+
+    let client_list = conn.intern_atom(false, b"_NET_CLIENT_LIST")?.reply()?.atom;
+    let net_wm_name = conn.intern_atom(false, b"_NET_WM_NAME")?.reply()?.atom;
+    let windows: Vec<u32> = conn
+        .get_property(false, root, client_list, AtomEnum::WINDOW, 0, u32::MAX)?
+        .reply()?
+        .value32()
+        .map(|v| v.collect())
+        .unwrap_or_default();
+
+    for window in windows {
+        for name_atom in [net_wm_name, AtomEnum::WM_NAME.into()] {
+            let name = conn
+                .get_property(false, window, name_atom, AtomEnum::ANY, 0, 1024)?
+                .reply()?;
+            if !name.value.is_empty() {
+                if title_matches(&String::from_utf8_lossy(&name.value), title) {
+                    return Ok(window);
+                }
+                break;
+            }
+        }
+    }
+    anyhow::bail!("No window titled '{}'", title)
+}
+
+/// Placeholder grabber for platforms without X11
+#[cfg(not(target_os = "linux"))]
+pub struct ScreenGrabber;
+
+#[cfg(not(target_os = "linux"))]
+impl ScreenGrabber {
+    pub fn open(_target: &ScreenTarget) -> Result<Self> {
+        anyhow::bail!("Screen capture is only supported on Linux (X11)")
+    }
+
+    pub fn grab(&mut self) -> Result<VideoFrame> {
+        anyhow::bail!("Screen capture is only supported on Linux (X11)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bgrx_to_frame() {
+        // 2x2 with 4 bytes of row padding
+        let data = [
+            0, 0, 255, 0, 0, 255, 0, 0, 9, 9, 9, 9, //
+            255, 0, 0, 0, 10, 20, 30, 0, 9, 9, 9, 9,
+        ];
+        let frame = bgrx_to_frame(&data, 2, 2, 12).unwrap();
+        assert_eq!(
+            frame.data,
+            vec![
+                255, 0, 0, 255, 0, 255, 0, 255, //
+                0, 0, 255, 255, 30, 20, 10, 255,
+            ]
+        );
+        assert!(bgrx_to_frame(&data, 4, 2, 12).is_err());
+        assert!(bgrx_to_frame(&data[..19], 2, 2, 12).is_err());
+    }
+
+    #[test]
+    fn test_title_matches() {
+        assert!(title_matches("Scoreboard - Chromium", "scoreboard"));
+        assert!(!title_matches("Lyrics", "scoreboard"));
+        assert_eq!(
+            ScreenTarget::Window("Scores".to_string()).to_string(),
+            "window \"Scores\""
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Route changes on senders: the new input goes out from its first whole frame,
+//! optionally after one black or crossfaded frame, so a switch never tears
+
+use super::VideoFrame;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// What goes out on the frame a route change switches on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteTransition {
+    /// Straight to the new input's first frame
+    #[default]
+    Cut,
+    /// One black frame between the two inputs
+    Black,
+    /// One frame mixing the old input's last frame with the new input's first
+    Crossfade,
+}
+
+impl RouteTransition {
+    /// Frames to send, in order, when switching from `last` (the last frame
+    /// sent, if any) to `first`, the new input's first frame
+    pub fn frames(self, last: Option<&VideoFrame>, first: Arc<VideoFrame>) -> Vec<Arc<VideoFrame>> {
+        let between = match (self, last) {
+            (RouteTransition::Cut, _) | (_, None) => None,
+            (RouteTransition::Black, Some(_)) => {
+                Some(VideoFrame::solid(first.width, first.height, [0, 0, 0, 255]))
+            }
+            // Inputs of different sizes cut
+            (RouteTransition::Crossfade, Some(last)) => ((last.width, last.height)
+                == (first.width, first.height))
+                .then(|| last.blend(&first, 0.5)),
+        };
+        between.map(Arc::new).into_iter().chain([first]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_transition() {
+        let old = VideoFrame::solid(2, 2, [200, 200, 200, 255]);
+        let new = Arc::new(VideoFrame::solid(2, 2, [100, 100, 100, 255]));
+        let shades = |frames: Vec<Arc<VideoFrame>>| -> Vec<u8> {
+            frames.iter().map(|frame| frame.data[0]).collect()
+        };
+
+        let cut = RouteTransition::Cut.frames(Some(&old), Arc::clone(&new));
+        assert_eq!(shades(cut), [100]);
+        let black = RouteTransition::Black.frames(Some(&old), Arc::clone(&new));
+        assert_eq!(shades(black), [0, 100]);
+        let crossfade = RouteTransition::Crossfade.frames(Some(&old), Arc::clone(&new));
+        assert_eq!(shades(crossfade), [150, 100]);
+
+        // Nothing to fade from, or from a frame of another size
+        assert_eq!(
+            shades(RouteTransition::Black.frames(None, Arc::clone(&new))),
+            [100]
+        );
+        let small = VideoFrame::solid(1, 1, [200, 200, 200, 255]);
+        assert_eq!(
+            shades(RouteTransition::Crossfade.frames(Some(&small), new)),
+            [100]
+        );
+    }
+}